@@ -1,16 +1,79 @@
 extern crate gcc;
 
-use std::fs;
-use std::path;
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::process;
 
-fn main() {
-    if !path::Path::new("odpi/include/dpi.h").exists() {
+/// Find the value following `name` on a line of the given source, e.g. the `2` in
+/// `#define DPI_MAJOR_VERSION 2`.
+fn find_version(source: &str, name: &str) -> Option<u32> {
+    source
+        .lines()
+        .filter(|line| line.contains(name))
+        .filter_map(|line| {
+            let digits = line.split(name)
+                .nth(1)?
+                .chars()
+                .skip_while(|c| !c.is_digit(10))
+                .take_while(|c| c.is_digit(10))
+                .collect::<String>();
+            digits.parse().ok()
+        })
+        .next()
+}
+
+/// The structures in `src/odpi` are laid out for a specific ODPI-C release, 2.0 or, with the
+/// `odpi-3` feature, 3.0. ODPI-C fills in whole structures when initializing them, so a header of
+/// any other release, even a newer minor one, fails the build rather than corrupting memory at
+/// runtime.
+///
+/// * `header` - the path of the `dpi.h` the library is built from.
+fn check_version(header: &Path) {
+    let mut source = String::new();
+    if let Err(e) = File::open(header).and_then(|mut f| f.read_to_string(&mut source)) {
+        println!("Unable to read '{}': {}", header.display(), e);
+        process::exit(1);
+    }
+
+    let version = |name: &str| match find_version(&source, name) {
+        Some(version) => version,
+        None => {
+            println!("Unable to find {} in '{}'", name, header.display());
+            process::exit(1);
+        }
+    };
+    let header_version = (version("DPI_MAJOR_VERSION"), version("DPI_MINOR_VERSION"));
+    let bindings_version = if env::var("CARGO_FEATURE_ODPI_3").is_ok() {
+        (3, 0)
+    } else {
+        (2, 0)
+    };
+
+    if header_version != bindings_version {
+        println!("'{}' is ODPI-C {}.{}, but the bindings are built for ODPI-C {}.{}.",
+                 header.display(),
+                 header_version.0,
+                 header_version.1,
+                 bindings_version.0,
+                 bindings_version.1);
+        println!("Use a matching ODPI-C release, or toggle the odpi-3 feature.");
+        process::exit(1);
+    }
+}
+
+/// Compile the ODPI-C source in the odpi submodule and link it statically.
+fn build_embedded() {
+    if !Path::new("odpi/include/dpi.h").exists() {
         println!("The odpi submodule isn't initialized. Run the following commands.");
         println!("  git submodule init");
         println!("  git submodule update");
-        std::process::exit(1);
+        process::exit(1);
     }
 
+    check_version(Path::new("odpi/include/dpi.h"));
+
     let mut cfg = gcc::Config::new();
     for entry in fs::read_dir("odpi/src").unwrap() {
         let fname = entry.unwrap().file_name().into_string().unwrap();
@@ -22,3 +85,29 @@ fn main() {
         .include("odpi/src")
         .compile("libodpic.a");
 }
+
+/// Link against an ODPI-C library installed on the system. `ODPIC_LIB_DIR` adds a directory to
+/// the library search path. If `ODPIC_INCLUDE_DIR` is set, the version of the `dpi.h` in it is
+/// checked against the bindings.
+fn link_system() {
+    println!("cargo:rerun-if-env-changed=ODPIC_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=ODPIC_INCLUDE_DIR");
+
+    if let Ok(include_dir) = env::var("ODPIC_INCLUDE_DIR") {
+        check_version(&Path::new(&include_dir).join("dpi.h"));
+    }
+
+    if let Ok(lib_dir) = env::var("ODPIC_LIB_DIR") {
+        println!("cargo:rustc-link-search=native={}", lib_dir);
+    }
+
+    println!("cargo:rustc-link-lib=odpic");
+}
+
+fn main() {
+    if env::var("CARGO_FEATURE_EMBEDDED_ODPI").is_ok() {
+        build_embedded();
+    } else {
+        link_system();
+    }
+}
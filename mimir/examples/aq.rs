@@ -0,0 +1,73 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Advanced Queuing producer/consumer. The queue must already exist, e.g.
+//!
+//! ```sql
+//! exec dbms_aqadm.create_queue_table('MIMIR_QUEUE_TAB', 'MIMIR_MSG_TYPE');
+//! exec dbms_aqadm.create_queue('MIMIR_QUEUE', 'MIMIR_QUEUE_TAB');
+//! exec dbms_aqadm.start_queue('MIMIR_QUEUE');
+//! ```
+//!
+//! ```text
+//! cargo run --example aq
+//! ```
+extern crate mimir;
+
+mod common;
+
+use mimir::{Context, DeqWait};
+use mimir::enums::ODPIDeqMode::Remove;
+use mimir::enums::ODPIDeqNavigation::FirstMsg;
+use mimir::enums::ODPIVisibility::OnCommit;
+use mimir::error::Result;
+use mimir::flags;
+use std::time::Duration;
+
+/// The queue used by the example.
+const QUEUE: &str = "MIMIR_QUEUE";
+
+fn aq(ctxt: &Context) -> Result<()> {
+    let conn = common::connect(ctxt, true)?;
+
+    // Producer
+    let enq_opts = conn.new_enq_options()?;
+    enq_opts.set_visibility(OnCommit)?;
+    let enq_props = conn.new_msg_props()?;
+    enq_props.set_correlation("mimir-example")?;
+    enq_props.set_priority(1)?;
+    let (enq_id, _) = conn.enqueue_object(QUEUE, &enq_opts, &enq_props)?;
+    conn.commit()?;
+    println!("enqueued message {}", enq_id);
+
+    // Consumer
+    let deq_opts = conn.new_deq_options()?;
+    deq_opts.set_mode(Remove)?;
+    deq_opts.set_navigation(FirstMsg)?;
+    deq_opts.set_correlation(Some("mimir-example"))?;
+    deq_opts.set_wait(DeqWait::Seconds(Duration::from_secs(5)))?;
+    let deq_props = conn.new_msg_props()?;
+    let (deq_id, _) = conn.deque_object(QUEUE, &deq_opts, &deq_props)?;
+    conn.commit()?;
+    println!("dequeued message {} (attempts: {}, priority: {})",
+             deq_id,
+             deq_props.get_num_attempts()?,
+             deq_props.get_priority()?);
+
+    deq_props.release()?;
+    deq_opts.release()?;
+    enq_props.release()?;
+    enq_opts.release()?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+fn main() {
+    common::run("aq", &aq)
+}
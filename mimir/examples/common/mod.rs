@@ -0,0 +1,79 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Shared setup for the examples.
+//!
+//! The examples connect using the `MIMIR_USERNAME`, `MIMIR_PASSWORD`, `MIMIR_CONNECT_STRING` and
+//! optional `MIMIR_ENCODING` environment variables. When built with the `examples-no-db` feature
+//! they exit before touching the database, so CI can compile and run them without an Oracle
+//! instance.
+#![allow(dead_code)]
+use mimir::{ConnectParams, Connection, Context, Pool};
+use mimir::error::Result;
+use std::io::{self, Write};
+use std::process;
+
+/// Returns true if the examples were built to skip database access.
+pub fn skip() -> bool {
+    cfg!(feature = "examples-no-db")
+}
+
+/// Runs the given example body, reporting any error (with the ODPI-C error information, if
+/// available) on stderr and exiting with a non-zero status.
+pub fn run(name: &str, f: &dyn Fn(&Context) -> Result<()>) {
+    if skip() {
+        writeln!(io::stdout(), "{}: built with examples-no-db, skipping", name)
+            .expect("badness");
+        return;
+    }
+
+    let ctxt = match Context::create() {
+        Ok(ctxt) => ctxt,
+        Err(e) => {
+            writeln!(io::stderr(), "{}: {}", name, e).expect("badness");
+            process::exit(1);
+        }
+    };
+
+    if let Err(e) = f(&ctxt) {
+        writeln!(io::stderr(), "{}: {}\n{}", name, e, ctxt.get_error()).expect("badness");
+        process::exit(1);
+    }
+}
+
+/// Reads the connection parameters from the environment, defaulting to UTF-8.
+pub fn config() -> Result<ConnectParams> {
+    let mut config = ConnectParams::from_env()?;
+
+    if config.get_encoding().is_none() {
+        config.set_encoding(Some("UTF-8"))?;
+    }
+    Ok(config)
+}
+
+/// Creates a standalone connection.
+pub fn connect(ctxt: &Context, events: bool) -> Result<Connection> {
+    let config = config()?;
+    let mut ccp = config.init_common_create_params(ctxt)?;
+
+    if events {
+        ccp.set_create_mode(::mimir::flags::DPI_MODE_CREATE_EVENTS);
+    }
+
+    config.connect_with(ctxt, ccp)
+}
+
+/// Creates a session pool with the given minimum and maximum number of sessions.
+pub fn pool(ctxt: &Context, min_sessions: u32, max_sessions: u32) -> Result<Pool> {
+    let config = config()?;
+    let mut pcp = ctxt.init_pool_create_params()?;
+    pcp.set_min_sessions(min_sessions);
+    pcp.set_max_sessions(max_sessions);
+
+    config.create_pool(ctxt, Some(pcp))
+}
@@ -0,0 +1,78 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Continuous Query Notification listener. Registers a query on the `username` table and prints
+//! the change events received while the example listens. Change the table from another session
+//! to see them arrive.
+//!
+//! ```text
+//! cargo run --example cqn_listener
+//! ```
+extern crate mimir;
+
+mod common;
+
+use mimir::{ChangeEvent, Context, CqnListener};
+use mimir::error::Result;
+use mimir::flags;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
+
+/// How long to listen for notifications.
+const LISTEN_SECS: u64 = 60;
+
+fn print_event(event: &ChangeEvent) {
+    let event = event.notification();
+    println!("{:?} on {}", event.event_type, event.db_name);
+    for query in &event.queries {
+        println!("  query {} ({:?})", query.id, query.operation);
+        for table in &query.tables {
+            println!("    {} ({:?}): {} row(s)", table.name, table.operation, table.rows.len());
+            for row in &table.rows {
+                println!("      {} ({:?})", row.rowid, row.operation);
+            }
+        }
+    }
+    if let Some(ref error) = event.error {
+        println!("  error: {}", error);
+    }
+}
+
+fn cqn_listener(ctxt: &Context) -> Result<()> {
+    let conn = common::connect(ctxt, true)?;
+
+    let mut scp = ctxt.init_subscr_create_params()?;
+    scp.set_qos(flags::DPI_SUBSCR_QOS_QUERY | flags::DPI_SUBSCR_QOS_ROWIDS);
+    scp.set_timeout(LISTEN_SECS as u32);
+
+    let mut listener = CqnListener::new(&conn, scp)?;
+    println!("registered subscription {}", listener.id());
+    listener.register_query("select * from username")?;
+
+    let deadline = Instant::now() + Duration::from_secs(LISTEN_SECS);
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        match listener.receiver().recv_timeout(deadline - now) {
+            Ok(event) => print_event(&event),
+            Err(RecvTimeoutError::Timeout) |
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    listener.close()?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+fn main() {
+    common::run("cqn_listener", &cqn_listener)
+}
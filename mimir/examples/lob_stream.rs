@@ -0,0 +1,72 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Streams data into a temporary BLOB chunk by chunk, binds it to a query without creating a
+//! variable, and reads it back both through the locator and inline.
+//!
+//! ```text
+//! cargo run --example lob_stream
+//! ```
+extern crate mimir;
+
+mod common;
+
+use mimir::Context;
+use mimir::enums::ODPIOracleTypeNum::Blob;
+use mimir::error::Result;
+use mimir::flags;
+
+/// The number of chunks written to the LOB.
+const CHUNKS: u64 = 16;
+
+fn lob_stream(ctxt: &Context) -> Result<()> {
+    let conn = common::connect(ctxt, false)?;
+
+    let lob = conn.new_temp_lob(Blob)?;
+    let chunk_size = u64::from(lob.get_chunk_size()?);
+    lob.open_resource()?;
+    for chunk in 0..CHUNKS {
+        let buffer: Vec<i8> = ::std::iter::repeat((chunk % 128) as i8)
+            .take(chunk_size as usize)
+            .collect();
+        lob.write_bytes(&buffer, chunk * chunk_size + 1)?;
+    }
+    lob.close_resource()?;
+    println!("wrote {} bytes", lob.get_size()?);
+
+    let mut offset = 1;
+    let size = lob.get_size()?;
+    while offset <= size {
+        let chunk = lob.read_bytes(offset, chunk_size)?;
+        println!("read chunk at {}: {} bytes", offset, chunk.len());
+        offset += chunk_size;
+    }
+
+    let stmt = conn.prepare_stmt(Some("select to_blob(dbms_lob.substr(:1, 16)) from dual"),
+                                 None,
+                                 false)?;
+    stmt.bind_lob_by_pos(1, &lob)?;
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    let var = stmt.define_inline(&conn, 1, 1)?;
+    let (found, idx) = stmt.fetch()?;
+    if found {
+        let data = var.get_value(idx)?;
+        println!("inline prefix: {:?}", data.get_string().as_bytes());
+    }
+
+    stmt.close(None)?;
+    var.release()?;
+    lob.release()?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+fn main() {
+    common::run("lob_stream", &lob_stream)
+}
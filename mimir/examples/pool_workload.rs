@@ -0,0 +1,60 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Simulates a request-per-connection workload (as a web server would have) on top of a session
+//! pool. Each "request" acquires a connection, runs a query and releases the connection back to
+//! the pool.
+//!
+//! ```text
+//! cargo run --example pool_workload
+//! ```
+extern crate mimir;
+
+mod common;
+
+use mimir::{Context, Pool};
+use mimir::error::Result;
+use mimir::flags;
+
+/// The number of simulated requests.
+const REQUESTS: u32 = 50;
+
+fn handle_request(pool: &Pool, id: u32) -> Result<i64> {
+    let conn = pool.acquire_connection(None, None, None)?;
+    let stmt = conn.prepare_stmt(Some("select :id * 2 from dual"), Some("pool_workload"), false)?;
+    let res = stmt.binder()
+        .set("id", i64::from(id))
+        .and_then(|binder| binder.bind())
+        .and_then(|_| stmt.single_value(&conn));
+
+    // Closing with the tag returns the statement to the session's statement cache.
+    stmt.close(Some("pool_workload"))?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    res
+}
+
+fn pool_workload(ctxt: &Context) -> Result<()> {
+    let pool = common::pool(ctxt, 2, 4)?;
+
+    for id in 0..REQUESTS {
+        let doubled = handle_request(&pool, id)?;
+        println!("request {}: {} (busy: {}, open: {})",
+                 id,
+                 doubled,
+                 pool.get_busy_count()?,
+                 pool.get_open_count()?);
+    }
+
+    pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT)?;
+
+    Ok(())
+}
+
+fn main() {
+    common::run("pool_workload", &pool_workload)
+}
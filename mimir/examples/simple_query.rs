@@ -0,0 +1,52 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Runs a simple query, consuming the rows in chunks, and prints the database time.
+//!
+//! ```text
+//! cargo run --example simple_query
+//! ```
+extern crate mimir;
+
+mod common;
+
+use mimir::{Context, ControlFlow};
+use mimir::error::Result;
+use mimir::flags;
+
+fn simple_query(ctxt: &Context) -> Result<()> {
+    let conn = common::connect(ctxt, false)?;
+
+    println!("server time: {}", conn.server_time()?);
+
+    let stmt = conn.prepare_stmt(Some("select table_name, num_rows from user_tables"),
+                                 None,
+                                 false)?;
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+
+    let mut total = 0;
+    stmt.for_each_chunk(&conn, 100, |batch| {
+            for row in 0..batch.num_rows() {
+                if let Some(name) = batch.get(row, 1) {
+                    println!("{}", name.get_string());
+                }
+            }
+            total += batch.num_rows();
+            ControlFlow::Continue
+        })?;
+    println!("{} tables", total);
+
+    stmt.close(None)?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+fn main() {
+    common::run("simple_query", &simple_query)
+}
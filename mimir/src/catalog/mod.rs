@@ -0,0 +1,364 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Schema introspection over the `ALL_TABLES`, `ALL_TAB_COLUMNS` and `ALL_CONSTRAINTS` data
+//! dictionary views. Only the objects the connected user has privileges on are visible. Names are
+//! given as they would be written unquoted in SQL, and are upper cased the way Oracle stores
+//! them; names that are not valid without quotes are matched as given.
+//!
+//! ```ignore
+//! let catalog = Catalog::new(&conn);
+//! for table in catalog.tables()? {
+//!     let key = catalog.primary_key(table.name())?;
+//!     println!("{}: {:?}", table.name(), key.map(|key| key.columns().to_vec()));
+//! }
+//! ```
+use connection::Connection;
+use error::Result;
+use oracletype::{FLOAT_SCALE, OracleType};
+use sql::check_identifier;
+use statement::ControlFlow;
+use statement::binder::BindValue;
+use statement::buffers::FetchBuffers;
+
+/// The number of rows fetched from the data dictionary at one time.
+const FETCH_ROWS: u32 = 100;
+/// The schema queried when no owner is given.
+const CURRENT_SCHEMA: &str = "nvl(:owner, sys_context('USERENV', 'CURRENT_SCHEMA'))";
+
+/// Typed queries over the data dictionary of one schema.
+pub struct Catalog<'a> {
+    /// The connection the queries are run on.
+    conn: &'a Connection,
+    /// The schema queried, or None for the current schema of the session.
+    owner: Option<String>,
+}
+
+impl<'a> Catalog<'a> {
+    /// Create a new `Catalog` over the current schema of the session.
+    ///
+    /// * `conn` - the connection the queries are run on.
+    pub fn new(conn: &'a Connection) -> Catalog<'a> {
+        Catalog {
+            conn: conn,
+            owner: None,
+        }
+    }
+
+    /// Create a new `Catalog` over the given schema.
+    ///
+    /// * `conn` - the connection the queries are run on.
+    /// * `owner` - the schema queried.
+    pub fn for_owner(conn: &'a Connection, owner: &str) -> Catalog<'a> {
+        Catalog {
+            conn: conn,
+            owner: Some(dictionary_name(owner)),
+        }
+    }
+
+    /// Returns the columns of the given table in column order. The result is empty if the table
+    /// does not exist or is not visible.
+    ///
+    /// * `table` - the name of the table.
+    pub fn columns(&self, table: &str) -> Result<Vec<Column>> {
+        let sql = format!("select column_id, column_name, data_type, char_length, data_length, \
+                           data_precision, data_scale, nullable \
+                           from all_tab_columns \
+                           where owner = {} and table_name = :table_name \
+                           order by column_id",
+                          CURRENT_SCHEMA);
+        let mut columns = Vec::new();
+
+        self.query(&sql,
+                   Some(table),
+                   |buffers: &mut (Vec<i64>,
+                                   Vec<String>,
+                                   Vec<Option<String>>,
+                                   Vec<Option<i64>>,
+                                   Vec<i64>,
+                                   Vec<Option<i64>>,
+                                   Vec<Option<i64>>,
+                                   Vec<String>)| {
+            for row in 0..buffers.0.len() {
+                let data_type = buffers.2[row].clone().unwrap_or_default();
+                let oracle_type = column_type(&data_type,
+                                              buffers.3[row].unwrap_or(0) as u32,
+                                              buffers.4[row] as u32,
+                                              buffers.5[row].map(|precision| precision as i16),
+                                              buffers.6[row].map(|scale| scale as i8));
+                columns.push(Column::new(buffers.0[row] as u32,
+                                         &buffers.1[row],
+                                         &data_type,
+                                         oracle_type,
+                                         buffers.7[row] == "Y"));
+            }
+        })?;
+
+        Ok(columns)
+    }
+
+    /// Returns the primary key of the given table, or None if it has none or does not exist.
+    ///
+    /// * `table` - the name of the table.
+    pub fn primary_key(&self, table: &str) -> Result<Option<PrimaryKey>> {
+        let sql = format!("select c.constraint_name, cc.column_name \
+                           from all_constraints c \
+                           join all_cons_columns cc \
+                           on cc.owner = c.owner and cc.constraint_name = c.constraint_name \
+                           where c.constraint_type = 'P' and c.owner = {} \
+                           and c.table_name = :table_name \
+                           order by cc.position",
+                          CURRENT_SCHEMA);
+        let mut key: Option<PrimaryKey> = None;
+
+        self.query(&sql,
+                   Some(table),
+                   |buffers: &mut (Vec<String>, Vec<String>)| {
+            for (name, column) in buffers.0.drain(..).zip(buffers.1.drain(..)) {
+                let key = key.get_or_insert_with(|| {
+                                                     PrimaryKey {
+                                                         name: name,
+                                                         columns: Vec::new(),
+                                                     }
+                                                 });
+                key.columns.push(column);
+            }
+        })?;
+
+        Ok(key)
+    }
+
+    /// Returns the tables of the schema, ordered by name.
+    pub fn tables(&self) -> Result<Vec<Table>> {
+        let sql = format!("select owner, table_name, tablespace_name, num_rows \
+                           from all_tables \
+                           where owner = {} \
+                           order by table_name",
+                          CURRENT_SCHEMA);
+        let mut tables = Vec::new();
+
+        self.query(&sql,
+                   None,
+                   |buffers: &mut (Vec<String>,
+                                   Vec<String>,
+                                   Vec<Option<String>>,
+                                   Vec<Option<i64>>)| {
+            let rows = buffers.0.drain(..).zip(buffers.1.drain(..));
+            let stats = buffers.2.drain(..).zip(buffers.3.drain(..));
+            for ((owner, name), (tablespace, num_rows)) in rows.zip(stats) {
+                tables.push(Table {
+                                owner: owner,
+                                name: name,
+                                tablespace: tablespace,
+                                num_rows: num_rows.map(|num_rows| num_rows as u64),
+                            });
+            }
+        })?;
+
+        Ok(tables)
+    }
+
+    /// Run a data dictionary query with the owner, and optionally the table name, bound, handing
+    /// each chunk of rows to the closure.
+    fn query<B, F>(&self, sql: &str, table: Option<&str>, mut f: F) -> Result<()>
+        where B: FetchBuffers + Default,
+              F: FnMut(&mut B)
+    {
+        let stmt = self.conn.prepare_stmt(Some(sql), None, false)?;
+        let res = stmt.binder()
+            .set("owner", BindValue::from(self.owner.clone()))
+            .and_then(|binder| match table {
+                          Some(table) => binder.set("table_name", dictionary_name(table)),
+                          None => Ok(binder),
+                      })
+            .and_then(|binder| binder.execute())
+            .and_then(|_| {
+                          stmt.fetch_into(self.conn, FETCH_ROWS, &mut B::default(), |buffers| {
+                f(buffers);
+                ControlFlow::Continue
+            })
+                      });
+        stmt.release()?;
+        res
+    }
+}
+
+/// A table, from `ALL_TABLES`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Table {
+    /// The schema owning the table.
+    owner: String,
+    /// The name of the table.
+    name: String,
+    /// The tablespace holding the table, or None for partitioned and temporary tables.
+    tablespace: Option<String>,
+    /// The number of rows, or None if statistics have not been gathered.
+    num_rows: Option<u64>,
+}
+
+impl Table {
+    /// Get the `owner` value.
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// Get the `name` value.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the `tablespace` value. None for partitioned and temporary tables.
+    pub fn tablespace(&self) -> Option<&str> {
+        self.tablespace.as_ref().map(|tablespace| &tablespace[..])
+    }
+
+    /// Get the `num_rows` value. This is the row count when statistics were last gathered, or
+    /// None if they never were.
+    pub fn num_rows(&self) -> Option<u64> {
+        self.num_rows
+    }
+}
+
+/// A column of a table, from `ALL_TAB_COLUMNS`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Column {
+    /// The position of the column in the table. The first position is 1.
+    position: u32,
+    /// The name of the column.
+    name: String,
+    /// The data type, as named by the data dictionary, e.g. `TIMESTAMP(6)` or the name of an
+    /// object type.
+    data_type: String,
+    /// The Oracle type, or None if the data type is not one `OracleType` describes.
+    oracle_type: Option<OracleType>,
+    /// Whether the column accepts NULL values.
+    nullable: bool,
+}
+
+impl Column {
+    /// Create a new `Column`, e.g. to describe a table to `codegen` without querying it.
+    ///
+    /// * `position` - the position of the column in the table. The first position is 1.
+    /// * `name` - the name of the column.
+    /// * `data_type` - the data type, as named by the data dictionary.
+    /// * `oracle_type` - the Oracle type, if `OracleType` describes the data type.
+    /// * `nullable` - whether the column accepts NULL values.
+    pub fn new(position: u32,
+               name: &str,
+               data_type: &str,
+               oracle_type: Option<OracleType>,
+               nullable: bool)
+               -> Column {
+        Column {
+            position: position,
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            oracle_type: oracle_type,
+            nullable: nullable,
+        }
+    }
+
+    /// Get the `position` value. The first position is 1.
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    /// Get the `name` value.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the `data_type` value, as named by the data dictionary.
+    pub fn data_type(&self) -> &str {
+        &self.data_type
+    }
+
+    /// Get the `oracle_type` value. None for object types and other data types `OracleType` does
+    /// not describe.
+    pub fn oracle_type(&self) -> Option<OracleType> {
+        self.oracle_type
+    }
+
+    /// Get the `nullable` value.
+    pub fn nullable(&self) -> bool {
+        self.nullable
+    }
+}
+
+/// The primary key constraint of a table, from `ALL_CONSTRAINTS`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrimaryKey {
+    /// The name of the constraint.
+    name: String,
+    /// The key columns, in key order.
+    columns: Vec<String>,
+}
+
+impl PrimaryKey {
+    /// Get the `name` value.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the `columns` value, in key order.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+}
+
+/// Returns the `OracleType` of a column described by `ALL_TAB_COLUMNS`, or None if the data type
+/// is not one `OracleType` describes, e.g. an object type.
+///
+/// * `data_type` - the `DATA_TYPE` column, e.g. `VARCHAR2` or `INTERVAL DAY(2) TO SECOND(6)`.
+/// * `char_length` - the `CHAR_LENGTH` column, the size of string types in characters.
+/// * `data_length` - the `DATA_LENGTH` column, the size of RAW in bytes.
+/// * `precision` - the `DATA_PRECISION` column.
+/// * `scale` - the `DATA_SCALE` column.
+pub fn column_type(data_type: &str,
+                   char_length: u32,
+                   data_length: u32,
+                   precision: Option<i16>,
+                   scale: Option<i8>)
+                   -> Option<OracleType> {
+    match data_type {
+        "VARCHAR2" | "NVARCHAR2" | "CHAR" | "NCHAR" => {
+            format!("{}({})", data_type, char_length).parse().ok()
+        }
+        "RAW" => format!("RAW({})", data_length).parse().ok(),
+        "NUMBER" => {
+            match (precision, scale) {
+                (Some(precision), Some(scale)) => Some(OracleType::Number(precision, scale)),
+                // INTEGER and SMALLINT are reported with a scale only.
+                (None, Some(0)) => Some(OracleType::Number(38, 0)),
+                _ => Some(OracleType::Number(0, 0)),
+            }
+        }
+        "FLOAT" => Some(OracleType::Number(precision.unwrap_or(126), FLOAT_SCALE)),
+        _ if data_type.starts_with("INTERVAL") => {
+            // The leading and fractional precisions are not part of `OracleType`.
+            let mut name = String::new();
+            let mut depth = 0;
+            for c in data_type.chars() {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ if depth == 0 => name.push(c),
+                    _ => {}
+                }
+            }
+            name.parse().ok()
+        }
+        _ => data_type.parse().ok(),
+    }
+}
+
+/// Returns a name as stored in the data dictionary: upper cased if it is valid without quotes,
+/// and as given otherwise.
+fn dictionary_name(name: &str) -> String {
+    check_identifier(name).unwrap_or_else(|_| name.to_string())
+}
@@ -0,0 +1,417 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Change data capture of a single table, for keeping caches keyed by the table's rows up to date.
+//! `watch_table()` takes a snapshot of the keys of the table's rows, and subscribes to
+//! Continuous Query Notification for the table with a `cqn::Listener`. The notifications are then
+//! turned into a stream of `RowEvent` values, each an insert, update or delete of a row with its
+//! key.
+//!
+//! The subscription is registered before the snapshot is taken, so no change committed after the
+//! snapshot is missed; changes committed in between are both in the snapshot and notified. A
+//! notification only carries the rowids of the changed rows, so each is read again by rowid and
+//! reconciled with the keys last seen for it: a row read again but not seen before is an insert, a
+//! row seen before but gone is a delete, and a row whose key changed is a delete of the old key
+//! followed by an insert of the new one. Notified inserts of rows already in the snapshot, with
+//! the same key, are skipped; other changes of such rows are reported as updates, so a change
+//! may be reported twice, but never lost. When the database reports that too many rows changed
+//! to list them, or the table was altered, or the subscription lapsed and had to be renewed, the
+//! snapshot is taken again and compared with the previous one, reporting every row as changed.
+//!
+//! The key values are read as text with `TO_CHAR()`, so dates are formatted with the session's
+//! `NLS_DATE_FORMAT`, and a NULL value is read as an empty string. Index-organized tables, which
+//! have logical rowids, cannot be watched. The connection must be created with
+//! `DPI_MODE_CREATE_EVENTS` set in its `CommonCreate` parameters, and is used to read the changed
+//! rows, so it only sees changes committed by other sessions once they are notified.
+//!
+//! ```ignore
+//! let mut watch = cdc::watch_table(&conn, "orders", &["id"])?;
+//! let mut cache = load(watch.snapshot());
+//!
+//! loop {
+//!     for event in watch.recv()? {
+//!         cache.invalidate(event.key());
+//!     }
+//! }
+//! ```
+use connection::Connection;
+use context::params::SubscrCreate;
+use cqn::{ChangeEvent, Listener};
+use error::{ErrorKind, Result};
+use odpi::flags;
+use odpi::structs::ODPISubscrCreateParams;
+use sql::{check_identifier, check_qualified_name};
+use statement::Statement;
+use std::collections::BTreeMap;
+use std::mem;
+use std::sync::mpsc::{RecvTimeoutError, TryRecvError};
+use std::time::Duration;
+
+/// The name of the bind variable holding the rowid of a changed row.
+const ROWID_BIND: &str = "CDC_ROWID";
+
+/// The kind of change made to a row.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Operation {
+    /// The row was inserted.
+    Insert,
+    /// The row was updated, keeping its key.
+    Update,
+    /// The row was deleted.
+    Delete,
+}
+
+/// A change made to a row of a watched table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RowEvent {
+    /// The kind of change.
+    operation: Operation,
+    /// The rowid of the row.
+    rowid: String,
+    /// The values of the key columns, as text. For a delete, the key the row had when last seen.
+    key: Vec<String>,
+}
+
+impl RowEvent {
+    /// Create a new `RowEvent`.
+    ///
+    /// * `operation` - the kind of change.
+    /// * `rowid` - the rowid of the row.
+    /// * `key` - the values of the key columns, as text.
+    pub fn new(operation: Operation, rowid: &str, key: Vec<String>) -> RowEvent {
+        RowEvent {
+            operation: operation,
+            rowid: rowid.to_string(),
+            key: key,
+        }
+    }
+
+    /// Get the `key` value.
+    pub fn key(&self) -> &[String] {
+        &self.key
+    }
+
+    /// Get the `operation` value.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Get the `rowid` value.
+    pub fn rowid(&self) -> &str {
+        &self.rowid
+    }
+}
+
+/// The keys of the rows of a watched table as last seen, by rowid. Changes are reconciled with it
+/// to decide which `RowEvent` values they produce.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Snapshot {
+    /// The values of the key columns, by rowid.
+    rows: BTreeMap<String, Vec<String>>,
+}
+
+impl Snapshot {
+    /// Create a new `Snapshot` of the given rows.
+    ///
+    /// * `rows` - the rowid and the values of the key columns of each row.
+    pub fn new(rows: Vec<(String, Vec<String>)>) -> Snapshot {
+        Snapshot { rows: rows.into_iter().collect() }
+    }
+
+    /// Reconciles a notified change of a row with the row as read again, updating the snapshot
+    /// and returning the events the change produces.
+    ///
+    /// * `rowid` - the rowid of the changed row.
+    /// * `operation` - the operations notified for the row.
+    /// * `current` - the values of the key columns of the row as read again, or None if the row no
+    /// longer exists.
+    pub fn apply(&mut self,
+                 rowid: &str,
+                 operation: flags::ODPIOpCode,
+                 current: Option<Vec<String>>)
+                 -> Vec<RowEvent> {
+        let known = self.rows.remove(rowid);
+
+        match (known, current) {
+            (None, Some(key)) => {
+                self.rows.insert(rowid.to_string(), key.clone());
+                vec![RowEvent::new(Operation::Insert, rowid, key)]
+            }
+            (Some(old), Some(key)) => {
+                self.rows.insert(rowid.to_string(), key.clone());
+                if old != key {
+                    vec![RowEvent::new(Operation::Delete, rowid, old),
+                         RowEvent::new(Operation::Insert, rowid, key)]
+                } else if operation == flags::DPI_OPCODE_INSERT {
+                    // The insert was committed before the snapshot was taken.
+                    Vec::new()
+                } else {
+                    vec![RowEvent::new(Operation::Update, rowid, key)]
+                }
+            }
+            (Some(old), None) => vec![RowEvent::new(Operation::Delete, rowid, old)],
+            (None, None) => Vec::new(),
+        }
+    }
+
+    /// Returns the values of the key columns of the row with the given rowid, if it is in the
+    /// snapshot.
+    ///
+    /// * `rowid` - the rowid of the row.
+    pub fn get(&self, rowid: &str) -> Option<&[String]> {
+        self.rows.get(rowid).map(|key| key.as_slice())
+    }
+
+    /// Returns true if the snapshot holds no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns the number of rows in the snapshot.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Replaces the snapshot with the given rows, returning the events turning the old snapshot
+    /// into the new one: a delete for each row that is gone, then for each row an insert if it is
+    /// new or its key changed, after a delete of the old key, or else an update, as the changes
+    /// made to it are not known.
+    ///
+    /// * `rows` - the rowid and the values of the key columns of each row.
+    pub fn refresh(&mut self, rows: Vec<(String, Vec<String>)>) -> Vec<RowEvent> {
+        let mut old = mem::replace(&mut self.rows, BTreeMap::new());
+        let mut changed = Vec::new();
+
+        for (rowid, key) in rows {
+            match old.remove(&rowid) {
+                Some(old_key) => {
+                    if old_key != key {
+                        changed.push(RowEvent::new(Operation::Delete, &rowid, old_key));
+                        changed.push(RowEvent::new(Operation::Insert, &rowid, key.clone()));
+                    } else {
+                        changed.push(RowEvent::new(Operation::Update, &rowid, key.clone()));
+                    }
+                }
+                None => changed.push(RowEvent::new(Operation::Insert, &rowid, key.clone())),
+            }
+            self.rows.insert(rowid, key);
+        }
+
+        let mut events: Vec<RowEvent> = old.into_iter()
+            .map(|(rowid, key)| RowEvent::new(Operation::Delete, &rowid, key))
+            .collect();
+        events.extend(changed);
+        events
+    }
+
+    /// Returns the rowid and the values of the key columns of each row, ordered by rowid.
+    pub fn rows(&self) -> Vec<(&str, &[String])> {
+        self.rows
+            .iter()
+            .map(|(rowid, key)| (rowid.as_str(), key.as_slice()))
+            .collect()
+    }
+}
+
+/// A watched table, turning its change notifications into `RowEvent` values. Dropping it closes
+/// the subscription.
+pub struct TableWatch<'a> {
+    /// The connection the changed rows are read on.
+    conn: &'a Connection,
+    /// The listener receiving the change notifications of the table.
+    listener: Listener,
+    /// The watched table, optionally qualified by its schema, in upper case.
+    table: String,
+    /// The key columns, in upper case.
+    keys: Vec<String>,
+    /// The keys of the rows as last seen.
+    snapshot: Snapshot,
+}
+
+/// Starts watching the given table for changes. The subscription is registered first, and the
+/// snapshot of the table's rows is taken after it, available from `TableWatch::snapshot()`.
+///
+/// * `conn` - the connection the subscription is created on, and the rows are read on. It must be
+/// created with `DPI_MODE_CREATE_EVENTS` set.
+/// * `table` - the table to watch, optionally qualified by its schema.
+/// * `keys` - the columns identifying a row, e.g. the primary key columns.
+pub fn watch_table<'a>(conn: &'a Connection, table: &str, keys: &[&str]) -> Result<TableWatch<'a>> {
+    let table = check_qualified_name(table)?;
+    if keys.is_empty() {
+        let msg = format!("watch of {} needs a key column", table);
+        return Err(ErrorKind::Subscription(msg).into());
+    }
+    let keys = keys.iter()
+        .map(|key| check_identifier(key))
+        .collect::<Result<Vec<String>>>()?;
+
+    let mut scp = SubscrCreate::new(ODPISubscrCreateParams::default());
+    scp.set_qos(flags::DPI_SUBSCR_QOS_ROWIDS);
+    let listener = Listener::new(conn, scp)?;
+    listener.register_query(&format!("SELECT {} FROM {}", keys.join(", "), table))?;
+
+    let mut watch = TableWatch {
+        conn: conn,
+        listener: listener,
+        table: table,
+        keys: keys,
+        snapshot: Snapshot::default(),
+    };
+    watch.snapshot = Snapshot::new(watch.read_rows(None)?);
+    Ok(watch)
+}
+
+impl<'a> TableWatch<'a> {
+    /// Closes the subscription, so no more changes are notified.
+    pub fn close(&mut self) -> Result<()> {
+        self.listener.close()
+    }
+
+    /// Get the `id` value of the subscription.
+    pub fn id(&self) -> u32 {
+        self.listener.id()
+    }
+
+    /// Waits for the next notification of changes to the table, returning the events it produces.
+    /// The events may be empty, e.g. if the changes were already in the snapshot. Returns an error
+    /// if the subscription is closed, or the notification reports an error.
+    pub fn recv(&mut self) -> Result<Vec<RowEvent>> {
+        let event = self.listener
+            .receiver()
+            .recv()
+            .map_err(|_| ErrorKind::Subscription("table watch is closed".to_string()))?;
+        self.process(event)
+    }
+
+    /// Waits up to the given time for the next notification of changes to the table, returning
+    /// the events it produces, or None if there is none by then. See `recv()`.
+    ///
+    /// * `timeout` - the longest time to wait.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Vec<RowEvent>>> {
+        match self.listener.receiver().recv_timeout(timeout) {
+            Ok(event) => self.process(event).map(Some),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(ErrorKind::Subscription("table watch is closed".to_string()).into())
+            }
+        }
+    }
+
+    /// Takes the snapshot of the table again, returning the events turning the previous snapshot
+    /// into the new one. See `Snapshot::refresh()`.
+    pub fn resync(&mut self) -> Result<Vec<RowEvent>> {
+        let rows = self.read_rows(None)?;
+        Ok(self.snapshot.refresh(rows))
+    }
+
+    /// Get the `snapshot` value, holding the keys of the rows as last seen.
+    pub fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    /// Returns the events produced by the next notification already received, without waiting,
+    /// or None if there is none. See `recv()`.
+    pub fn try_recv(&mut self) -> Result<Option<Vec<RowEvent>>> {
+        match self.listener.receiver().try_recv() {
+            Ok(event) => self.process(event).map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => {
+                Err(ErrorKind::Subscription("table watch is closed".to_string()).into())
+            }
+        }
+    }
+
+    /// Turns a notification into the events it produces.
+    fn process(&mut self, event: ChangeEvent) -> Result<Vec<RowEvent>> {
+        if let Some(ref info) = event.notification().error {
+            return Err(ErrorKind::Subscription(info.message().to_string()).into());
+        }
+
+        if event.is_deregistered() {
+            self.listener.renew(self.conn)?;
+            return self.resync();
+        }
+
+        let all_rows = flags::DPI_OPCODE_ALL_ROWS | flags::DPI_OPCODE_ALTER |
+                       flags::DPI_OPCODE_DROP;
+        let tables: Vec<_> = event
+            .notification()
+            .tables
+            .iter()
+            .filter(|table| self.is_watched(&table.name))
+            .collect();
+
+        if tables
+               .iter()
+               .any(|table| table.operation.intersects(all_rows)) {
+            return self.resync();
+        }
+
+        let mut events = Vec::new();
+        for row in tables.iter().flat_map(|table| table.rows.iter()) {
+            let current = self.read_rows(Some(&row.rowid))?
+                .into_iter()
+                .next()
+                .map(|(_, key)| key);
+            events.extend(self.snapshot.apply(&row.rowid, row.operation, current));
+        }
+        Ok(events)
+    }
+
+    /// Returns true if the notified table name, qualified by its schema, is the watched table.
+    fn is_watched(&self, name: &str) -> bool {
+        let name = name.to_uppercase();
+        if self.table.contains('.') {
+            name == self.table
+        } else {
+            name.rsplit('.').next() == Some(&self.table)
+        }
+    }
+
+    /// Reads the rowid and the values of the key columns of the table's rows, or of the row with
+    /// the given rowid.
+    fn read_rows(&self, rowid: Option<&str>) -> Result<Vec<(String, Vec<String>)>> {
+        let columns: Vec<String> = self.keys
+            .iter()
+            .map(|key| format!("TO_CHAR({})", key))
+            .collect();
+        let mut sql = format!("SELECT ROWIDTOCHAR(ROWID), {} FROM {}",
+                              columns.join(", "),
+                              self.table);
+        if rowid.is_some() {
+            sql.push_str(&format!(" WHERE ROWID = CHARTOROWID(:{})", ROWID_BIND));
+        }
+
+        let stmt = self.conn.prepare_stmt(Some(&sql), None, false)?;
+        let res = match rowid {
+                Some(rowid) => stmt.binder().set(ROWID_BIND, rowid),
+                None => Ok(stmt.binder()),
+            }
+            .and_then(|binder| binder.execute())
+            .and_then(|_| fetch_keys(&stmt, self.keys.len() as u32));
+        stmt.release()?;
+        res
+    }
+}
+
+/// Fetches the rowid and the key values of each row of an executed query.
+fn fetch_keys(stmt: &Statement, num_keys: u32) -> Result<Vec<(String, Vec<String>)>> {
+    let mut rows = Vec::new();
+
+    while stmt.fetch()?.0 {
+        let row = stmt.row()?;
+        let key = (2..num_keys + 2)
+            .map(|pos| {
+                     row.get_at::<Option<String>>(pos)
+                         .map(|value| value.unwrap_or_default())
+                 })
+            .collect::<Result<Vec<String>>>()?;
+        rows.push((row.get_at(1)?, key));
+    }
+    Ok(rows)
+}
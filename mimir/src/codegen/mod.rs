@@ -0,0 +1,264 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Generates Rust structs for tables from the data dictionary, so models can be kept in sync with
+//! the database from the `build.rs` of a downstream crate. Enabled by the `codegen` feature.
+//!
+//! Each table becomes a struct named after it in camel case, with a field per column in column
+//! order. Nullable columns become `Option` fields, and a column whose field name is already taken
+//! gets a numeric suffix. The struct derives `Clone`, `Debug` and `PartialEq` where every field
+//! allows it. When every column has a type that `Statement::fetch_into()` can fetch, the struct
+//! implements `FetchRow`, so a `select` of all its columns in column order can be read with
+//! `Statement::single_row()` or `Connection::query_row()`. The column types are taken from
+//! `types::rust_type_for()`, with LOBs fetched inline as `String` or `Vec<u8>`.
+//!
+//! ```ignore
+//! // build.rs
+//! let mut code = String::new();
+//! codegen::generate_structs(&conn, "HR", &["employees", "departments"], &mut code)?;
+//! fs::write(Path::new(&env::var("OUT_DIR")?).join("models.rs"), code)?;
+//! ```
+use catalog::{Catalog, Column};
+use connection::Connection;
+use error::{ErrorKind, Result};
+use oracletype::OracleType;
+use std::fmt::Write;
+use types::RustType;
+
+/// The most columns a `FetchRow` tuple holds.
+const MAX_ROW_COLUMNS: usize = 8;
+/// The traits derived by a generated struct, when every field implements them.
+const DERIVES: &[&str] = &["Clone", "Debug", "PartialEq"];
+/// The keywords and reserved words of Rust 2015, which cannot be used as field names.
+const KEYWORDS: &[&str] = &["abstract", "alignof", "as", "become", "box", "break", "const",
+                            "continue", "crate", "do", "else", "enum", "extern", "false", "final",
+                            "fn", "for", "if", "impl", "in", "let", "loop", "macro", "match",
+                            "mod", "move", "mut", "offsetof", "override", "priv", "proc", "pub",
+                            "pure", "ref", "return", "self", "sizeof", "static", "struct",
+                            "super", "trait", "true", "type", "typeof", "unsafe", "unsized",
+                            "use", "virtual", "where", "while", "yield"];
+
+/// Appends a struct definition, with a `FetchRow` impl where possible, for each of the given
+/// tables to `out`. An error is returned if a table does not exist or is not visible.
+///
+/// * `conn` - the connection the data dictionary is queried on.
+/// * `schema` - the schema owning the tables.
+/// * `tables` - the names of the tables.
+/// * `out` - the string the generated code is appended to.
+pub fn generate_structs(conn: &Connection,
+                        schema: &str,
+                        tables: &[&str],
+                        out: &mut String)
+                        -> Result<()> {
+    let catalog = Catalog::for_owner(conn, schema);
+
+    for (idx, table) in tables.iter().enumerate() {
+        let columns = catalog.columns(table)?;
+
+        if columns.is_empty() {
+            let msg = format!("table {}.{} does not exist or is not visible", schema, table);
+            return Err(ErrorKind::Codegen(msg).into());
+        }
+
+        if idx > 0 {
+            out.push('\n');
+        }
+        write_struct(table, &columns, out);
+    }
+
+    Ok(())
+}
+
+/// Appends the struct definition for a table with the given columns to `out`, along with a
+/// `FetchRow` impl if every column can be fetched. The struct derives those of `Clone`, `Debug` and
+/// `PartialEq` that every field implements. Columns whose type `OracleType` does not describe,
+/// such as object types, are left out of the struct with a comment.
+///
+/// * `table` - the name of the table.
+/// * `columns` - the columns of the table, in column order.
+/// * `out` - the string the generated code is appended to.
+pub fn write_struct(table: &str, columns: &[Column], out: &mut String) {
+    let name = struct_name(table);
+    let mut fields: Vec<(String, String)> = Vec::new();
+    let mut fetchable = true;
+    let mut derives = DERIVES.to_vec();
+    let mut body = String::new();
+
+    // Writing to a String cannot fail.
+    for column in columns {
+        let field = unique_field_name(column.name(), &fields);
+        let sql_type = column
+            .oracle_type()
+            .map_or_else(|| column.data_type().to_string(), |ty| ty.to_string());
+        let not_null = if column.nullable() { "" } else { " NOT NULL" };
+
+        let field_type = match column.oracle_type() {
+            Some(oracle_type) => {
+                match fetch_type(oracle_type) {
+                    Some(fetch_type) => fetch_type.to_string(),
+                    None => {
+                        fetchable = false;
+                        let rust_type = oracle_type.rust_type();
+                        let allowed = derives_for(rust_type);
+                        derives.retain(|derive| allowed.contains(derive));
+                        rust_type.to_string()
+                    }
+                }
+            }
+            None => {
+                fetchable = false;
+                let _ = writeln!(body,
+                                 "    // {} {}{} has no Rust type.",
+                                 column.name(),
+                                 sql_type,
+                                 not_null);
+                continue;
+            }
+        };
+        let field_type = if column.nullable() {
+            format!("Option<{}>", field_type)
+        } else {
+            field_type
+        };
+
+        let _ = writeln!(body, "    /// `{} {}{}`", column.name(), sql_type, not_null);
+        let _ = writeln!(body, "    pub {}: {},", field, field_type);
+        fields.push((field, field_type));
+    }
+
+    let _ = writeln!(out, "/// A row of the `{}` table.", table.to_uppercase());
+    if !derives.is_empty() {
+        let _ = writeln!(out, "#[derive({})]", derives.join(", "));
+    }
+    let _ = writeln!(out, "pub struct {} {{", name);
+    out.push_str(&body);
+    let _ = writeln!(out, "}}");
+
+    if !fetchable || fields.is_empty() || fields.len() > MAX_ROW_COLUMNS {
+        return;
+    }
+
+    let tuple = if fields.len() == 1 {
+        format!("({},)", fields[0].1)
+    } else {
+        let types: Vec<&str> = fields.iter().map(|&(_, ref ty)| &ty[..]).collect();
+        format!("({})", types.join(", "))
+    };
+    let names: Vec<&str> = fields.iter().map(|&(ref field, _)| &field[..]).collect();
+    let pattern = if names.len() == 1 {
+        format!("({},)", names[0])
+    } else {
+        format!("({})", names.join(", "))
+    };
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "impl ::mimir::FetchRow for {} {{", name);
+    let _ = writeln!(out,
+                     "    type Buffers = <{} as ::mimir::FetchRow>::Buffers;",
+                     tuple);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    fn take(buffers: Self::Buffers) -> Option<{}> {{", name);
+    let _ = writeln!(out,
+                     "        let row = <{} as ::mimir::FetchRow>::take(buffers);",
+                     tuple);
+    let _ = writeln!(out, "        row.map(|{}| {{", pattern);
+    let _ = writeln!(out, "            {} {{", name);
+    for field in &names {
+        let _ = writeln!(out, "                {}: {},", field, field);
+    }
+    let _ = writeln!(out, "            }}");
+    let _ = writeln!(out, "        }})");
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out, "}}");
+}
+
+/// Returns the traits of `DERIVES` a field of the given type allows, for the types of columns
+/// that cannot be fetched. Handle types such as `Lob` implement none of them.
+fn derives_for(rust_type: RustType) -> &'static [&'static str] {
+    match rust_type {
+        RustType::Lob | RustType::Rowid | RustType::Statement => &[],
+        RustType::Object => &["Clone", "Debug"],
+        _ => DERIVES,
+    }
+}
+
+/// Returns the type a column of the given Oracle type is fetched into by
+/// `Statement::fetch_into()`, or None if it has no `FetchValue` type.
+fn fetch_type(oracle_type: OracleType) -> Option<&'static str> {
+    match oracle_type {
+        OracleType::Clob | OracleType::NClob => Some("String"),
+        OracleType::Blob => Some("Vec<u8>"),
+        _ => {
+            match oracle_type.rust_type() {
+                RustType::String => Some("String"),
+                RustType::Bytes => Some("Vec<u8>"),
+                RustType::I64 => Some("i64"),
+                RustType::F64 => Some("f64"),
+                RustType::DateTime => Some("::chrono::DateTime<::chrono::FixedOffset>"),
+                RustType::DateTimeUtc => Some("::chrono::DateTime<::chrono::Utc>"),
+                RustType::Duration => Some("::chrono::Duration"),
+                RustType::YearsMonths => Some("::mimir::YearsMonths"),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Returns the camel case struct name for a table, e.g. `OrderLines` for `ORDER_LINES`.
+fn struct_name(table: &str) -> String {
+    let mut name = String::new();
+
+    for word in table.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            name.extend(chars.map(|c| c.to_ascii_lowercase()));
+        }
+    }
+
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("Table{}", name)
+    } else {
+        name
+    }
+}
+
+/// Returns the field name for a column that is not already taken by one of the given fields. A
+/// name shared with an earlier column, e.g. `ORDER ID` and `ORDER_ID`, gets a numeric suffix.
+fn unique_field_name(column: &str, fields: &[(String, String)]) -> String {
+    let name = field_name(column);
+    let taken = |candidate: &str| fields.iter().any(|&(ref field, _)| field == candidate);
+
+    if !taken(&name) {
+        return name;
+    }
+    (2..)
+        .map(|suffix| format!("{}_{}", name, suffix))
+        .find(|candidate| !taken(candidate))
+        .expect("an unused suffix")
+}
+
+/// Returns the snake case field name for a column, e.g. `order_id` for `ORDER_ID`.
+fn field_name(column: &str) -> String {
+    let name: String = column
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() {
+                 c.to_ascii_lowercase()
+             } else {
+                 '_'
+             })
+        .collect();
+
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else if KEYWORDS.contains(&&name[..]) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
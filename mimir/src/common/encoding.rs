@@ -7,6 +7,7 @@
 // modified, or distributed except according to those terms.
 
 //! This structure is used for transferring encoding information from ODPI-C.
+use odpi::enums::ODPIOracleTypeNum;
 use odpi::structs::ODPIEncodingInfo;
 use std::ffi::CStr;
 
@@ -49,6 +50,44 @@ impl Info {
     pub fn max_bytes_per_nchar(&self) -> i32 {
         self.max_bytes_per_nchar
     }
+
+    /// Returns the maximum number of bytes required for each character of the given type: the
+    /// NCHAR value for NCHAR, NVARCHAR2 and NCLOB, the CHAR value for the other character types,
+    /// and 1 for types whose sizes are always in bytes.
+    ///
+    /// * `oracle_type` - the Oracle type of the data.
+    pub fn max_bytes_per_char_of(&self, oracle_type: ODPIOracleTypeNum) -> u32 {
+        let max_bytes = match oracle_type {
+            ODPIOracleTypeNum::NChar | ODPIOracleTypeNum::NVarchar | ODPIOracleTypeNum::NClob => {
+                self.max_bytes_per_nchar
+            }
+            ODPIOracleTypeNum::Char |
+            ODPIOracleTypeNum::Varchar |
+            ODPIOracleTypeNum::LongVarchar |
+            ODPIOracleTypeNum::Clob => self.max_bytes_per_char,
+            _ => 1,
+        };
+        if max_bytes > 0 { max_bytes as u32 } else { 1 }
+    }
+
+    /// Returns the number of bytes needed to hold the given number of characters of the given
+    /// type, as ODPI-C computes it for a variable sized in characters. The result saturates at
+    /// `u32::MAX`.
+    ///
+    /// * `oracle_type` - the Oracle type of the data.
+    /// * `size_in_chars` - the number of characters.
+    pub fn bytes_for_chars(&self, oracle_type: ODPIOracleTypeNum, size_in_chars: u32) -> u32 {
+        size_in_chars.saturating_mul(self.max_bytes_per_char_of(oracle_type))
+    }
+
+    /// Returns the number of characters of the given type that are certain to fit in the given
+    /// number of bytes, whatever the characters are.
+    ///
+    /// * `oracle_type` - the Oracle type of the data.
+    /// * `size_in_bytes` - the number of bytes.
+    pub fn chars_for_bytes(&self, oracle_type: ODPIOracleTypeNum, size_in_bytes: u32) -> u32 {
+        size_in_bytes / self.max_bytes_per_char_of(oracle_type)
+    }
 }
 
 impl From<ODPIEncodingInfo> for Info {
@@ -9,10 +9,12 @@
 //! This structure is used for transferring error information from ODPI-C.
 use odpi::structs::ODPIErrorInfo;
 use std::ffi::CStr;
+use std::os::raw::c_char;
 use std::{fmt, slice};
 
-/// This structure is used for transferring error information from ODPI-C. All of the strings
-/// referenced here may become invalid as soon as the next ODPI-C call is made.
+/// This structure is used for transferring error information from ODPI-C. The strings ODPI-C
+/// refers to are only valid until the next ODPI-C call is made, so they are copied when the
+/// `ODPIErrorInfo` is converted.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Info {
     /// The OCI error code if an OCI error has taken place. If no OCI error has taken place the
@@ -85,44 +87,86 @@ impl Info {
         &self.sql_state
     }
 
-    /// Get the `recoverable` value.
+    /// Get the `recoverable` value. True if the error is recoverable, e.g. by retrying the
+    /// transaction after a failover. Always false unless both client and server are at release
+    /// 12.1 or higher.
     pub fn recoverable(&self) -> bool {
         self.recoverable
     }
+
+    /// Returns the line of the given SQL containing the error offset, followed by a line with a
+    /// caret under the offending character. Lines after the first are prefixed with their line
+    /// number. The offset is only meaningful for errors raised while parsing or executing the SQL.
+    ///
+    /// * `sql` - the SQL the error was raised for.
+    pub fn render_snippet(&self, sql: &str) -> String {
+        let mut offset = ::std::cmp::min(self.offset as usize, sql.len());
+        while !sql.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line_start = sql[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+        let line_end = sql[offset..].find('\n').map_or(sql.len(), |idx| offset + idx);
+        let line_num = sql[..line_start].matches('\n').count() + 1;
+        let line = sql[line_start..line_end].trim_right_matches('\r');
+        let padding: String = sql[line_start..offset]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        if line_num == 1 {
+            format!("{}\n{}^", line, padding)
+        } else {
+            let prefix = format!("{}: ", line_num);
+            format!("{}{}\n{}{}^", prefix, line, " ".repeat(prefix.len()), padding)
+        }
+    }
 }
 
 impl fmt::Display for Info {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f,
-                 "{}: {}\nfn: {}\naction: {}\nsql_state: {}\nrecoverable: {}",
+                 "{}: {}\nfn: {}\naction: {}\nsql_state: {}\noffset: {}\nrecoverable: {}",
                  self.code,
                  self.message,
                  self.fn_name,
                  self.action,
                  self.sql_state,
+                 self.offset,
                  self.recoverable)
     }
 }
 
+/// Copies the error information, which ODPI-C only keeps until the next call. Strings that are not
+/// set are converted to empty strings.
 impl From<ODPIErrorInfo> for Info {
     fn from(err: ODPIErrorInfo) -> Info {
-        let slice =
-            unsafe { slice::from_raw_parts(err.message as *mut u8, err.message_length as usize) };
-        let fn_name = unsafe { CStr::from_ptr(err.fn_name) }
-            .to_string_lossy()
-            .into_owned();
-        let action = unsafe { CStr::from_ptr(err.action) }
-            .to_string_lossy()
-            .into_owned();
-        let sql_state = unsafe { CStr::from_ptr(err.sql_state) }
-            .to_string_lossy()
-            .into_owned();
+        let message = if err.message.is_null() {
+            String::new()
+        } else {
+            let bytes = unsafe {
+                slice::from_raw_parts(err.message as *const u8, err.message_length as usize)
+            };
+            String::from_utf8_lossy(bytes).into_owned()
+        };
+
         Info::new(err.code,
                   err.offset,
-                  String::from_utf8_lossy(slice).into_owned(),
-                  fn_name,
-                  action,
-                  sql_state,
-                  err.is_recoverable.is_positive())
+                  message,
+                  c_string(err.fn_name),
+                  c_string(err.action),
+                  c_string(err.sql_state),
+                  err.is_recoverable != 0)
+    }
+}
+
+/// Copies a null-terminated string, or returns an empty string if the pointer is NULL.
+fn c_string(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(ptr) }
+            .to_string_lossy()
+            .into_owned()
     }
 }
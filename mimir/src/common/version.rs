@@ -12,9 +12,20 @@ use odpi::structs::ODPIVersionInfo;
 use std::fmt;
 
 /// Wrapper for the `ODPIVersionInfo` structure.
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Info {
     /// The version.
     version: String,
+    /// The major version.
+    major: i32,
+    /// The release version.
+    release_num: i32,
+    /// The update version.
+    update_num: i32,
+    /// The port specific release version.
+    port_release_num: i32,
+    /// The port specific update version.
+    port_update_num: i32,
     /// The version number.
     version_num: u32,
     /// The release string.
@@ -27,6 +38,31 @@ impl Info {
         &self.version
     }
 
+    /// Get the `major` value.
+    pub fn major(&self) -> i32 {
+        self.major
+    }
+
+    /// Get the `release_num` value.
+    pub fn release_num(&self) -> i32 {
+        self.release_num
+    }
+
+    /// Get the `update_num` value.
+    pub fn update_num(&self) -> i32 {
+        self.update_num
+    }
+
+    /// Get the `port_release_num` value.
+    pub fn port_release_num(&self) -> i32 {
+        self.port_release_num
+    }
+
+    /// Get the `port_update_num` value.
+    pub fn port_update_num(&self) -> i32 {
+        self.port_update_num
+    }
+
     /// Get the `version_num` value.
     pub fn version_num(&self) -> u32 {
         self.version_num
@@ -58,6 +94,11 @@ impl From<ODPIVersionInfo> for Info {
                               ovi.port_update_num);
         Info {
             version: version,
+            major: ovi.version_num,
+            release_num: ovi.release_num,
+            update_num: ovi.update_num,
+            port_release_num: ovi.port_release_num,
+            port_update_num: ovi.port_update_num,
             version_num: ovi.full_version_num,
             release: None,
         }
@@ -0,0 +1,416 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Connection parameters (username, password, connect string and encoding) read from the
+//! environment or from a file, so the examples and tests do not hardcode the database they run
+//! against.
+//!
+//! From the environment, the parameters are read from `<PREFIX>_USERNAME`, `<PREFIX>_PASSWORD`,
+//! `<PREFIX>_CONNECT_STRING` and the optional `<PREFIX>_ENCODING` and
+//! `<PREFIX>_RESULT_CACHE_MODE`. A file holds one `key = value` pair per line using the keys
+//! `username`, `password`, `connect_string`, `encoding` and `result_cache_mode`. Blank lines and
+//! lines starting with `#` are ignored.
+//!
+//! The `Debug` output of `ConnectParams` never includes the password. Use
+//! `redact_connect_string()` before logging a connect string, as connect strings of the form
+//! `user/password@database` carry credentials.
+use connection::Connection;
+use context::{self, Context};
+use context::params::{CommonCreate, PoolCreate};
+use error::{ErrorKind, Result};
+use pool::Pool;
+use resultcache::ResultCacheMode;
+use std::collections::HashMap;
+use std::env;
+use std::ffi::CString;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The default environment variable prefix used by `ConnectParams::from_env()`.
+pub const DEFAULT_ENV_PREFIX: &str = "MIMIR";
+
+/// The keys of the parameters read from a file.
+pub const KEYS: &[&str] = &["username",
+                            "password",
+                            "connect_string",
+                            "encoding",
+                            "result_cache_mode"];
+
+/// The text used in place of redacted passwords.
+pub const REDACTED: &str = "<redacted>";
+
+/// The parameters needed to connect to a database.
+#[derive(Clone, Eq, PartialEq)]
+pub struct ConnectParams {
+    /// The name of the user used for authenticating.
+    username: String,
+    /// The password used for authenticating.
+    password: String,
+    /// The connect string identifying the database.
+    connect_string: String,
+    /// The encoding used for both CHAR and NCHAR data. None uses NLS_LANG and NLS_NCHAR.
+    encoding: Option<CString>,
+    /// The result cache mode set on standalone connections. None leaves the database default.
+    result_cache_mode: Option<ResultCacheMode>,
+}
+
+impl ConnectParams {
+    /// Create a new `ConnectParams` struct.
+    ///
+    /// * `username` - the name of the user used for authenticating.
+    /// * `password` - the password used for authenticating.
+    /// * `connect_string` - the connect string identifying the database.
+    pub fn new(username: &str, password: &str, connect_string: &str) -> ConnectParams {
+        ConnectParams {
+            username: username.to_string(),
+            password: password.to_string(),
+            connect_string: connect_string.to_string(),
+            encoding: None,
+            result_cache_mode: None,
+        }
+    }
+
+    /// Creates a standalone connection using these parameters.
+    ///
+    /// * `context` - the context handle created earlier using the function `Context::create()`, or
+    /// None to use the global context; see `Context::global()`.
+    pub fn connect<'a, C>(&self, context: C) -> Result<Connection>
+        where C: Into<Option<&'a Context>>
+    {
+        let context = context::resolve(context.into())?;
+        self.connect_with(context, self.init_common_create_params(context)?)
+    }
+
+    /// Creates a standalone connection using these parameters and the given `CommonCreate`
+    /// structure. Use `init_common_create_params()` to get a structure with the encoding set. The
+    /// result cache mode, if any, is set on the new connection.
+    ///
+    /// * `context` - the context handle created earlier using the function `Context::create()`, or
+    /// None to use the global context; see `Context::global()`.
+    /// * `common_create_params` - the context parameters used for connection creation.
+    pub fn connect_with<'a, C>(&self,
+                               context: C,
+                               common_create_params: CommonCreate)
+                               -> Result<Connection>
+        where C: Into<Option<&'a Context>>
+    {
+        Connection::create(context, self, Some(common_create_params), None)
+    }
+
+    /// Creates a standalone connection using these parameters and the global context. See
+    /// `Context::global()`.
+    #[cfg(feature = "global-context")]
+    pub fn connect_global(&self) -> Result<Connection> {
+        self.connect(Context::global()?)
+    }
+
+    /// Creates a session pool using these parameters. The result cache mode is not applied to
+    /// pooled sessions; call `Connection::set_result_cache_mode()` after acquiring a session.
+    ///
+    /// * `context` - the context handle created earlier using the function `Context::create()`, or
+    /// None to use the global context; see `Context::global()`.
+    /// * `pool_create_params` - an optional `PoolCreate` structure which is used to specify
+    /// parameters for pool creation.
+    pub fn create_pool<'a, C>(&self,
+                              context: C,
+                              pool_create_params: Option<PoolCreate>)
+                              -> Result<Pool>
+        where C: Into<Option<&'a Context>>
+    {
+        let context = context::resolve(context.into())?;
+        Pool::create(context,
+                     self,
+                     Some(self.init_common_create_params(context)?),
+                     pool_create_params)
+    }
+
+    /// Creates a session pool using these parameters and the global context. See
+    /// `Context::global()`.
+    ///
+    /// * `pool_create_params` - an optional `PoolCreate` structure which is used to specify
+    /// parameters for pool creation.
+    #[cfg(feature = "global-context")]
+    pub fn create_global_pool(&self, pool_create_params: Option<PoolCreate>) -> Result<Pool> {
+        self.create_pool(Context::global()?, pool_create_params)
+    }
+
+    /// Read the parameters from the environment, using the `MIMIR` prefix.
+    pub fn from_env() -> Result<ConnectParams> {
+        ConnectParams::from_env_prefix(DEFAULT_ENV_PREFIX)
+    }
+
+    /// Read the parameters from the environment, using the given prefix.
+    ///
+    /// * `prefix` - the environment variable prefix, e.g. `ODPIC` to read `ODPIC_USERNAME`.
+    pub fn from_env_prefix(prefix: &str) -> Result<ConnectParams> {
+        let var = |name: &str| env::var(format!("{}_{}", prefix, name));
+        let mut params = ConnectParams::new(&var("USERNAME")?,
+                                            &var("PASSWORD")?,
+                                            &var("CONNECT_STRING")?);
+
+        if let Ok(encoding) = var("ENCODING") {
+            params.set_encoding(Some(&encoding))?;
+        }
+
+        if let Ok(mode) = var("RESULT_CACHE_MODE") {
+            params.set_result_cache_mode(Some(mode.parse()?));
+        }
+
+        Ok(params)
+    }
+
+    /// Read the parameters from the file at the given path.
+    ///
+    /// * `path` - the path to the file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<ConnectParams> {
+        let mut contents = String::new();
+        File::open(path)?.read_to_string(&mut contents)?;
+        ConnectParams::parse(&contents)
+    }
+
+    /// Get the `connect_string` value.
+    pub fn get_connect_string(&self) -> &str {
+        &self.connect_string
+    }
+
+    /// Get the `encoding` value.
+    pub fn get_encoding(&self) -> Option<&str> {
+        self.encoding.as_ref().and_then(|encoding| encoding.to_str().ok())
+    }
+
+    /// Get the `password` value.
+    pub fn get_password(&self) -> &str {
+        &self.password
+    }
+
+    /// Get the `result_cache_mode` value.
+    pub fn get_result_cache_mode(&self) -> Option<ResultCacheMode> {
+        self.result_cache_mode
+    }
+
+    /// Get the `username` value.
+    pub fn get_username(&self) -> &str {
+        &self.username
+    }
+
+    /// Initializes a `CommonCreate` structure to default values, with the CHAR and NCHAR encodings
+    /// set to the encoding of these parameters. The structure refers to the encoding stored here,
+    /// so these parameters must outlive its use.
+    ///
+    /// * `context` - the context handle created earlier using the function `Context::create()`, or
+    /// None to use the global context; see `Context::global()`.
+    pub fn init_common_create_params<'a, C>(&self, context: C) -> Result<CommonCreate>
+        where C: Into<Option<&'a Context>>
+    {
+        let mut ccp = context::resolve(context.into())?.init_common_create_params()?;
+
+        if let Some(ref encoding) = self.encoding {
+            ccp.set_encoding(encoding.as_ptr());
+            ccp.set_nchar_encoding(encoding.as_ptr());
+        }
+
+        Ok(ccp)
+    }
+
+    /// Parse the parameters from the contents of a file.
+    ///
+    /// * `contents` - the `key = value` lines to parse.
+    pub fn parse(contents: &str) -> Result<ConnectParams> {
+        let mut values = HashMap::new();
+
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = match parts.next() {
+                Some(value) => value.trim(),
+                None => {
+                    let err = format!("line {}: expected 'key = value'", idx + 1);
+                    return Err(ErrorKind::Config(err).into());
+                }
+            };
+
+            if !KEYS.contains(&key) {
+                let err = format!("line {}: unknown key '{}'", idx + 1, key);
+                return Err(ErrorKind::Config(err).into());
+            }
+            values.insert(key, value.to_string());
+        }
+
+        from_values(&values)
+    }
+
+    /// Set the `encoding` value, used for both CHAR and NCHAR data.
+    ///
+    /// * `encoding` - an IANA or Oracle specific character set name, e.g. "UTF-8". None uses the
+    /// NLS_LANG and NLS_NCHAR environment variables.
+    pub fn set_encoding(&mut self, encoding: Option<&str>) -> Result<&mut ConnectParams> {
+        self.encoding = match encoding {
+            Some(encoding) => Some(CString::new(encoding)?),
+            None => None,
+        };
+        Ok(self)
+    }
+
+    /// Set the `result_cache_mode` value, applied to standalone connections created with these
+    /// parameters.
+    ///
+    /// * `mode` - the result cache mode. None leaves the database default.
+    pub fn set_result_cache_mode(&mut self, mode: Option<ResultCacheMode>) -> &mut ConnectParams {
+        self.result_cache_mode = mode;
+        self
+    }
+
+    /// Checks that the credentials are consistent with the way the session is authenticated, so
+    /// that mistakes are reported before a round trip to the database.
+    ///
+    /// * `external_auth` - true if external authentication is used, in which case no password
+    /// may be given.
+    /// * `required` - true if a username and password must be given when external authentication
+    /// is not used.
+    #[doc(hidden)]
+    pub fn validate(&self, external_auth: bool, required: bool) -> Result<()> {
+        check_credentials(&self.username, &self.password, external_auth, required)
+    }
+}
+
+impl<'a> From<&'a ConnectParams> for ConnectParams {
+    fn from(params: &'a ConnectParams) -> ConnectParams {
+        params.clone()
+    }
+}
+
+impl<'a, 'b, 'c> From<(&'a str, &'b str, &'c str)> for ConnectParams {
+    fn from((username, password, connect_string): (&'a str, &'b str, &'c str)) -> ConnectParams {
+        ConnectParams::new(username, password, connect_string)
+    }
+}
+
+impl fmt::Debug for ConnectParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConnectParams")
+            .field("username", &self.username)
+            .field("password", &REDACTED)
+            .field("connect_string", &redact_connect_string(&self.connect_string))
+            .field("encoding", &self.get_encoding())
+            .field("result_cache_mode", &self.result_cache_mode)
+            .finish()
+    }
+}
+
+/// Create the parameters from the values of `KEYS`, checking that the required ones are present.
+///
+/// * `values` - the values, by key.
+pub fn from_values(values: &HashMap<&str, String>) -> Result<ConnectParams> {
+    let required = |key: &str| {
+        values
+            .get(key)
+            .cloned()
+            .ok_or_else(|| ErrorKind::Config(format!("missing '{}'", key)))
+    };
+    let mut params = ConnectParams::new(&required("username")?,
+                                        &required("password")?,
+                                        &required("connect_string")?);
+
+    if let Some(encoding) = values.get("encoding") {
+        params.set_encoding(Some(encoding))?;
+    }
+
+    if let Some(mode) = values.get("result_cache_mode") {
+        params.set_result_cache_mode(Some(mode.parse()?));
+    }
+
+    Ok(params)
+}
+
+/// Checks that a username and password are consistent with the way a session is authenticated.
+/// Empty values are treated as not given.
+///
+/// * `username` - the name of the user used for authenticating.
+/// * `password` - the password used for authenticating.
+/// * `external_auth` - true if external authentication is used, in which case no password may be
+/// given.
+/// * `required` - true if a username and password must be given when external authentication is
+/// not used.
+#[doc(hidden)]
+pub fn check_credentials(username: &str,
+                         password: &str,
+                         external_auth: bool,
+                         required: bool)
+                         -> Result<()> {
+    let err = if external_auth {
+        if password.is_empty() {
+            return Ok(());
+        }
+        "a password cannot be given when external authentication is used"
+    } else if username.is_empty() && !password.is_empty() {
+        "a password was given without a username"
+    } else if required && (username.is_empty() || password.is_empty()) {
+        "a username and password are required unless external authentication is used"
+    } else {
+        return Ok(());
+    };
+
+    Err(ErrorKind::Config(err.to_string()).into())
+}
+
+/// Returns a copy of the given connect string that is safe to log. The password in a
+/// `user/password@database` prefix and the value of any `password=` parameter are replaced with
+/// `<redacted>`. The prefix ends at the last `@`, so a password containing `@` is redacted whole.
+///
+/// * `connect_string` - the connect string to redact.
+pub fn redact_connect_string(connect_string: &str) -> String {
+    let mut redacted = match connect_string.rfind('@') {
+        Some(at) => {
+            let (credentials, database) = connect_string.split_at(at);
+            match credentials.find('/') {
+                Some(slash) if slash > 0 && !credentials.contains('(') => {
+                    format!("{}/{}{}", &credentials[..slash], REDACTED, database)
+                }
+                _ => connect_string.to_string(),
+            }
+        }
+        None => connect_string.to_string(),
+    };
+
+    let mut start = 0;
+    while let Some(pos) = redacted[start..].to_ascii_lowercase().find("password") {
+        let key_end = start + pos + "password".len();
+        let value_start = match redacted[key_end..].find(|c: char| !c.is_whitespace()) {
+            Some(offset) if redacted[key_end + offset..].starts_with('=') => {
+                let after_eq = key_end + offset + 1;
+                after_eq +
+                redacted[after_eq..]
+                    .find(|c: char| !c.is_whitespace())
+                    .unwrap_or(redacted.len() - after_eq)
+            }
+            _ => {
+                start = key_end;
+                continue;
+            }
+        };
+        let value_end = redacted[value_start..]
+            .find(|c: char| c == ')' || c == ';' || c == '&' || c.is_whitespace())
+            .map_or(redacted.len(), |offset| value_start + offset);
+
+        redacted = format!("{}{}{}",
+                           &redacted[..value_start],
+                           REDACTED,
+                           &redacted[value_end..]);
+        start = value_start + REDACTED.len();
+    }
+
+    redacted
+}
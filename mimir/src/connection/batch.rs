@@ -0,0 +1,330 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A batch of statements executed in order, created with `Connection::batch()`. Each operation is
+//! a DML statement or PL/SQL block with its named binds, and its outcome is returned in the order
+//! the operations were added.
+//!
+//! Consecutive operations with the same statement text, the same bind names and values of the same
+//! types form a run, which is sent to the server as one array DML execution
+//! (`Statement::execute_many()`), so a run of any length takes a single round trip. The rows of a
+//! run are executed as a unit, with batch errors enabled, so each row reports its own outcome even
+//! if an earlier row of the run failed. An operation that starts a new statement is executed on its
+//! own. Array DML row counts and batch errors need Oracle Client 12.1; with an older client every
+//! operation is executed on its own.
+//!
+//! ```ignore
+//! let results = conn.batch()
+//!     .add("insert into orders (id, item) values (:id, :item)", &first)
+//!     .add("insert into orders (id, item) values (:id, :item)", &second)
+//!     .add("update stock set count = count - 2 where item = :item", &stock)
+//!     .execute();
+//! ```
+use connection::Connection;
+use error::{Error, ErrorKind, Result};
+use odpi::enums::{ODPINativeTypeNum, ODPIOracleTypeNum};
+use odpi::flags;
+use statement::Statement;
+use statement::binder::{self, BindValue};
+use std::{cmp, iter};
+use variable::{self, Var};
+
+/// The maximum number of operations in a run. ODPI-C reports the row of a batch error as a 16-bit
+/// offset, so longer runs are split.
+const MAX_RUN_LEN: usize = 65_536;
+
+/// An operation queued on a `Batch`.
+struct Operation {
+    /// The text of the statement.
+    sql: String,
+    /// The values of the named bind variables of the statement.
+    binds: Vec<(String, BindValue)>,
+}
+
+/// A queue of statements, executed in order by `execute()`.
+pub struct Batch<'a> {
+    /// The connection the operations are executed on.
+    conn: &'a Connection,
+    /// The queued operations.
+    operations: Vec<Operation>,
+    /// Whether the operations after a failed one are executed.
+    continue_on_error: bool,
+}
+
+impl<'a> Batch<'a> {
+    /// Create a new, empty `Batch` executing its operations on the given connection.
+    #[doc(hidden)]
+    pub fn new(conn: &'a Connection) -> Batch<'a> {
+        Batch {
+            conn: conn,
+            operations: Vec::new(),
+            continue_on_error: false,
+        }
+    }
+
+    /// Queues a DML statement or PL/SQL block. Nothing is sent to the server until `execute()` is
+    /// called.
+    ///
+    /// * `sql` - the statement to execute.
+    /// * `binds` - the values of the named bind variables of the statement.
+    pub fn add(mut self, sql: &str, binds: &[(&str, BindValue)]) -> Batch<'a> {
+        self.operations
+            .push(Operation {
+                      sql: sql.to_string(),
+                      binds: binds
+                          .iter()
+                          .map(|&(name, ref value)| (name.to_string(), value.clone()))
+                          .collect(),
+                  });
+        self
+    }
+
+    /// Sets whether the operations after a failed one are still executed. This is off by default,
+    /// so the batch stops after the run holding the first failure. The other rows of that run have
+    /// been executed with it and report their own outcomes.
+    ///
+    /// * `continue_on_error` - true to execute every operation regardless of failures.
+    pub fn continue_on_error(mut self, continue_on_error: bool) -> Batch<'a> {
+        self.continue_on_error = continue_on_error;
+        self
+    }
+
+    /// Returns true if no operations are queued.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Returns the number of operations queued.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Executes the queued operations in order, returning for each the number of rows affected by
+    /// a DML statement (0 for other statements), or the error it failed with. Unless
+    /// `continue_on_error()` is set, the operations after the run holding the first failure are
+    /// not executed and fail with `ErrorKind::Connection`. Nothing is committed.
+    pub fn execute(self) -> Vec<Result<u64>> {
+        let mut results = Vec::with_capacity(self.operations.len());
+        let mut use_arrays = true;
+        let mut failed = false;
+
+        while results.len() < self.operations.len() {
+            let start = results.len();
+
+            if failed && !self.continue_on_error {
+                let err = "batch: not executed after an earlier operation failed";
+                results.push(Err(ErrorKind::Connection(err.to_string()).into()));
+                continue;
+            }
+
+            let run = &self.operations[start..start + run_len(&self.operations[start..])];
+            let run_results = if use_arrays && run.len() > 1 {
+                match self.execute_run(run) {
+                    Ok(run_results) => run_results,
+                    Err(ref err) if is_unsupported(err) => {
+                        use_arrays = false;
+                        continue;
+                    }
+                    Err(err) => {
+                        let mut failed_run: Vec<Result<u64>> =
+                            (1..run.len()).map(|_| Err(copy_error(&err))).collect();
+                        failed_run.insert(0, Err(err));
+                        failed_run
+                    }
+                }
+            } else {
+                vec![self.execute_operation(&run[0])]
+            };
+
+            failed |= run_results.iter().any(Result::is_err);
+            results.extend(run_results);
+        }
+
+        results
+    }
+
+    /// Prepares, binds and executes one operation, returning the number of rows affected.
+    fn execute_operation(&self, operation: &Operation) -> Result<u64> {
+        let stmt = self.conn.prepare_stmt(Some(&operation.sql), None, false)?;
+        let res = operation
+            .binds
+            .iter()
+            .fold(Ok(stmt.binder()),
+                  |binder, &(ref name, ref value)| binder?.set(name, value.clone()))
+            .and_then(|binder| binder.bind())
+            .and_then(|_| stmt.execute(flags::DPI_MODE_EXEC_DEFAULT))
+            .and_then(|_| stmt.get_row_count());
+        let released = stmt.release();
+        res.and_then(|rows| released.map(|_| rows))
+    }
+
+    /// Executes a run of operations sharing a statement in one round trip, binding an array for
+    /// each bind variable. Returns the outcome of each operation, or an error if the run as a
+    /// whole failed.
+    fn execute_run(&self, run: &[Operation]) -> Result<Vec<Result<u64>>> {
+        let stmt = self.conn.prepare_stmt(Some(&run[0].sql), None, false)?;
+        let mut vars = Vec::new();
+        let mode = flags::DPI_MODE_EXEC_BATCH_ERRORS | flags::DPI_MODE_EXEC_ARRAY_DML_ROWCOUNTS;
+        let res = self.bind_arrays(&stmt, run, &mut vars)
+            .and_then(|_| stmt.execute_many(mode, run.len() as u32))
+            .and_then(|_| outcomes(&stmt, run.len()));
+        let released = vars.iter()
+            .map(Var::release)
+            .chain(iter::once(stmt.release()))
+            .collect::<Result<Vec<()>>>();
+        res.and_then(|outcomes| released.map(|_| outcomes))
+    }
+
+    /// Binds an array holding the values of each bind variable across the run. The variables
+    /// created are pushed onto `vars`, for the caller to release.
+    fn bind_arrays(&self, stmt: &Statement, run: &[Operation], vars: &mut Vec<Var>) -> Result<()> {
+        // Binding the values of the first operation checks the names against the statement, as
+        // for a single operation. The arrays bound below replace them.
+        run[0]
+            .binds
+            .iter()
+            .fold(Ok(stmt.binder()),
+                  |binder, &(ref name, ref value)| binder?.set(name, value.clone()))
+            .and_then(|binder| binder.bind())?;
+
+        for (col, &(ref name, _)) in run[0].binds.iter().enumerate() {
+            let values: Vec<&BindValue> = run.iter().map(|op| &op.binds[col].1).collect();
+            let native_type = values
+                .iter()
+                .find(|value| ***value != BindValue::Null)
+                .map_or(ODPINativeTypeNum::Int64, |value| value.native_type());
+            let size = values
+                .iter()
+                .map(|value| match **value {
+                         BindValue::String(ref val) => val.len() as u32,
+                         _ => 0,
+                     })
+                .fold(1, cmp::max);
+
+            let var = self.conn
+                .new_var(oracle_type(native_type),
+                         native_type,
+                         values.len() as u32,
+                         size,
+                         true,
+                         false,
+                         None)?;
+            vars.push(var);
+            let var = &vars[vars.len() - 1];
+
+            for (slot, value) in unsafe { variable::data(var)? }.iter_mut().zip(&values) {
+                if let BindValue::String(_) = **value {
+                    continue;
+                }
+                *slot = unsafe { *value.to_data().inner() };
+            }
+            for (pos, value) in values.iter().enumerate() {
+                if let BindValue::String(ref val) = **value {
+                    var.set_from_bytes(pos as u32, val)?;
+                }
+            }
+
+            stmt.bind_by_name(&binder::normalize(name), var)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the number of operations at the start of `operations` that form a run: the same
+/// statement, the same bind names in the same order, and values of one type in each bind variable,
+/// NULL matching any type.
+fn run_len(operations: &[Operation]) -> usize {
+    let first = &operations[0];
+    let mut types: Vec<Option<ODPINativeTypeNum>> = first
+        .binds
+        .iter()
+        .map(|&(_, ref value)| native_type(value))
+        .collect();
+
+    1 +
+    operations[1..]
+        .iter()
+        .take(MAX_RUN_LEN - 1)
+        .take_while(|op| {
+            if op.sql != first.sql || op.binds.len() != first.binds.len() {
+                return false;
+            }
+
+            let fits = op.binds
+                .iter()
+                .zip(&first.binds)
+                .zip(&types)
+                .all(|((&(ref name, ref value), &(ref first_name, _)), col_type)| {
+                    binder::normalize(name) == binder::normalize(first_name) &&
+                    (col_type.is_none() || native_type(value).is_none() ||
+                     native_type(value) == *col_type)
+                });
+            if fits {
+                for (col_type, &(_, ref value)) in types.iter_mut().zip(&op.binds) {
+                    if col_type.is_none() {
+                        *col_type = native_type(value);
+                    }
+                }
+            }
+            fits
+        })
+        .count()
+}
+
+/// The native type of a bound value, or None for NULL, which can be bound as any type.
+fn native_type(value: &BindValue) -> Option<ODPINativeTypeNum> {
+    match *value {
+        BindValue::Null => None,
+        _ => Some(value.native_type()),
+    }
+}
+
+/// The Oracle type of an array bound with the given native type, the type ODPI-C uses when a
+/// single value of that type is bound.
+fn oracle_type(native_type: ODPINativeTypeNum) -> ODPIOracleTypeNum {
+    match native_type {
+        ODPINativeTypeNum::Double => ODPIOracleTypeNum::NativeDouble,
+        ODPINativeTypeNum::Bytes => ODPIOracleTypeNum::Varchar,
+        ODPINativeTypeNum::Timestamp => ODPIOracleTypeNum::Timestamp,
+        ODPINativeTypeNum::IntervalDS => ODPIOracleTypeNum::IntervalDS,
+        ODPINativeTypeNum::IntervalYM => ODPIOracleTypeNum::IntervalYM,
+        _ => ODPIOracleTypeNum::Number,
+    }
+}
+
+/// Reads the row count or batch error of each of the `num_rows` rows of an array execution.
+fn outcomes(stmt: &Statement, num_rows: usize) -> Result<Vec<Result<u64>>> {
+    let row_counts = stmt.get_row_counts()?;
+    let errors = match stmt.get_batch_error_count()? {
+        0 => Vec::new(),
+        count => stmt.get_batch_errors(count)?,
+    };
+
+    Ok((0..num_rows)
+           .map(|row| match errors.iter().find(|info| info.offset() as usize == row) {
+                    Some(info) => Err(ErrorKind::OciError(info.clone()).into()),
+                    None => Ok(row_counts.get(row).cloned().unwrap_or(0)),
+                })
+           .collect())
+}
+
+/// Returns true if the error reports a feature the Oracle Client does not support.
+fn is_unsupported(err: &Error) -> bool {
+    match *err.kind() {
+        ErrorKind::Unsupported(..) => true,
+        _ => false,
+    }
+}
+
+/// Returns an error to report for each operation of a run that failed as a whole.
+fn copy_error(err: &Error) -> Error {
+    match *err.kind() {
+        ErrorKind::OciError(ref info) => ErrorKind::OciError(info.clone()).into(),
+        _ => ErrorKind::Connection(format!("batch: the run failed: {}", err)).into(),
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The compilation diagnostics returned by `Connection::execute_immediate()` and
+//! `Connection::get_object_errors()`. Creating or compiling a stored object, such as a procedure
+//! or a view created with `FORCE`, succeeds even if the object fails to compile; its errors and
+//! warnings are only recorded in `ALL_ERRORS`.
+use std::fmt;
+
+/// The object types whose compilation errors are recorded in `ALL_ERRORS`.
+const COMPILED_OBJECTS: &[&str] = &["FUNCTION", "JAVA", "LIBRARY", "PACKAGE", "PROCEDURE",
+                                    "TRIGGER", "TYPE", "VIEW"];
+
+/// The keywords that may come between `CREATE` and the object type.
+const CREATE_MODIFIERS: &[&str] = &["OR", "REPLACE", "EDITIONABLE", "NONEDITIONABLE", "FORCE",
+                                    "NO", "NOFORCE"];
+
+/// A stored object compiled by a DDL statement, as named in `ALL_ERRORS`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompiledObject {
+    /// The schema named in the statement, or None for the current schema.
+    pub owner: Option<String>,
+    /// The object type, e.g. `PROCEDURE` or `PACKAGE BODY`.
+    pub object_type: String,
+    /// The name of the object, upper cased unless it was quoted.
+    pub name: String,
+}
+
+/// A compilation error or warning of a stored object, from `ALL_ERRORS`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// The line of the object source the diagnostic refers to, starting from 1.
+    pub line: u32,
+    /// The position in the line, starting from 1.
+    pub position: u32,
+    /// The text of the diagnostic, e.g. `PLS-00201: identifier 'X' must be declared`.
+    pub text: String,
+    /// Is this a warning, rather than an error that leaves the object invalid?
+    pub is_warning: bool,
+    /// The number of the message, e.g. 201 for `PLS-00201`.
+    pub message_number: u32,
+}
+
+/// Formats the diagnostic as `line:position: text`.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.position, self.text)
+    }
+}
+
+/// Returns the stored object created or compiled by the given DDL statement, or None if the
+/// statement does not compile one. `CREATE` statements for the types in `ALL_ERRORS` and
+/// `ALTER ... COMPILE` statements are recognized.
+///
+/// * `sql` - the DDL statement.
+pub fn compiled_object(sql: &str) -> Option<CompiledObject> {
+    let mut rest = sql.trim_left();
+    let verb = next_word(&mut rest)?;
+
+    if verb == "CREATE" {
+        let mut word = next_word(&mut rest)?;
+        while CREATE_MODIFIERS.contains(&&word[..]) {
+            word = next_word(&mut rest)?;
+        }
+        let object_type = object_type(word, &mut rest)?;
+        let (owner, name) = object_name(&mut rest)?;
+
+        Some(CompiledObject {
+                 owner: owner,
+                 object_type: object_type,
+                 name: name,
+             })
+    } else if verb == "ALTER" {
+        let word = next_word(&mut rest)?;
+        let mut object_type = object_type(word, &mut rest)?;
+        let (owner, name) = object_name(&mut rest)?;
+
+        if next_word(&mut rest)? != "COMPILE" {
+            return None;
+        }
+        if next_word(&mut rest).map_or(false, |word| word == "BODY") {
+            object_type.push_str(" BODY");
+        }
+
+        Some(CompiledObject {
+                 owner: owner,
+                 object_type: object_type,
+                 name: name,
+             })
+    } else {
+        None
+    }
+}
+
+/// Returns the object type starting with the given word, taking `BODY` or `SOURCE` off the rest
+/// of the statement where it follows, or None if it is not a compiled object type.
+fn object_type(word: String, rest: &mut &str) -> Option<String> {
+    if !COMPILED_OBJECTS.contains(&&word[..]) {
+        return None;
+    }
+
+    let mut lookahead = *rest;
+    match next_word(&mut lookahead) {
+        Some(ref next) if (next == "BODY" && (word == "PACKAGE" || word == "TYPE")) ||
+                          (next == "SOURCE" && word == "JAVA") => {
+            *rest = lookahead;
+            Some(format!("{} {}", word, next))
+        }
+        _ if word == "JAVA" => None,
+        _ => Some(word),
+    }
+}
+
+/// Takes a possibly schema-qualified object name off the start of the statement, returning the
+/// owner and the name.
+pub(crate) fn object_name(rest: &mut &str) -> Option<(Option<String>, String)> {
+    let first = name_part(rest)?;
+
+    if rest.starts_with('.') {
+        *rest = &rest[1..];
+        let second = name_part(rest)?;
+        Some((Some(first), second))
+    } else {
+        Some((None, first))
+    }
+}
+
+/// Takes a quoted or unquoted name off the start of the statement. Unquoted names are upper cased
+/// the way Oracle stores them.
+fn name_part(rest: &mut &str) -> Option<String> {
+    *rest = rest.trim_left();
+
+    if rest.starts_with('"') {
+        let end = rest[1..].find('"')? + 1;
+        let name = rest[1..end].to_string();
+        *rest = &rest[end + 1..];
+        Some(name)
+    } else {
+        let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$' || c == '#'))
+            .unwrap_or_else(|| rest.len());
+        if end == 0 {
+            return None;
+        }
+        let name = rest[..end].to_uppercase();
+        *rest = &rest[end..];
+        Some(name)
+    }
+}
+
+/// Takes the next word off the start of the statement, upper cased.
+fn next_word(rest: &mut &str) -> Option<String> {
+    *rest = rest.trim_left();
+    let end = rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or_else(|| rest.len());
+
+    if end == 0 {
+        None
+    } else {
+        let word = rest[..end].to_uppercase();
+        *rest = &rest[end..];
+        Some(word)
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! An `ExpiredPassword` is returned inside `ErrorKind::PasswordExpired` when `Connection::create()`
+//! fails because the password of the account has expired (ORA-28001). It remembers how the
+//! connection was requested, so the login can be completed by changing the password with
+//! `complete()`.
+//!
+//! A password in its grace period (ORA-28002) does not fail the login: the connection is created,
+//! and the warning is returned by `Connection::get_warning()`.
+use common::error;
+use config;
+use connection::Connection;
+use context::Context;
+use context::params::CommonCreate;
+use error::Result;
+use std::fmt;
+
+/// ORA-28001: the password has expired.
+pub const ORA_PASSWORD_EXPIRED: i32 = 28_001;
+/// ORA-28002: the password will expire within the grace period.
+pub const ORA_PASSWORD_GRACE: i32 = 28_002;
+
+/// A handle to a connection attempt that failed because the password has expired.
+#[derive(Clone, Eq, PartialEq)]
+pub struct ExpiredPassword {
+    /// The name of the user whose password has expired.
+    username: String,
+    /// The connect string used for the failed connection attempt.
+    connect_string: Option<String>,
+    /// The error information returned by the failed connection attempt.
+    info: error::Info,
+}
+
+impl ExpiredPassword {
+    /// Create a new `ExpiredPassword` struct.
+    #[doc(hidden)]
+    pub fn new(username: &str, connect_string: Option<&str>, info: error::Info) -> ExpiredPassword {
+        ExpiredPassword {
+            username: username.to_string(),
+            connect_string: connect_string.map(|s| s.to_string()),
+            info: info,
+        }
+    }
+
+    /// Completes the login by changing the password as part of creating the connection, using the
+    /// new-password field of `ConnCreate`. Returns the new connection.
+    ///
+    /// * `context` - the context handle used for the failed connection attempt, or None to use the
+    /// global context; see `Context::global()`.
+    /// * `old_password` - the expired password.
+    /// * `new_password` - the password to change to.
+    /// * `common_create_params` - An optional `CommonCreate` structure which is used to specify
+    /// context parameters for connection creation.
+    pub fn complete<'a, C>(&self,
+                           context: C,
+                           old_password: &str,
+                           new_password: &str,
+                           common_create_params: Option<CommonCreate>)
+                           -> Result<Connection>
+        where C: Into<Option<&'a Context>>
+    {
+        Connection::create_with_new_password(context,
+                                             &self.username,
+                                             old_password,
+                                             new_password,
+                                             self.connect_string.as_ref().map(|s| s.as_str()),
+                                             common_create_params,
+                                             None)
+    }
+
+    /// Get the `connect_string` value.
+    pub fn connect_string(&self) -> Option<&str> {
+        self.connect_string.as_ref().map(|s| s.as_str())
+    }
+
+    /// Get the `info` value.
+    pub fn info(&self) -> &error::Info {
+        &self.info
+    }
+
+    /// Returns true if the given error code indicates an expired password.
+    pub fn is_expired_code(code: i32) -> bool {
+        code == ORA_PASSWORD_EXPIRED
+    }
+
+    /// Returns true if the given error code is the warning that the password will expire within
+    /// its grace period.
+    pub fn is_grace_code(code: i32) -> bool {
+        code == ORA_PASSWORD_GRACE
+    }
+
+    /// Get the `username` value.
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+}
+
+impl fmt::Debug for ExpiredPassword {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let connect_string = self.connect_string
+            .as_ref()
+            .map(|connect_string| config::redact_connect_string(connect_string));
+
+        f.debug_struct("ExpiredPassword")
+            .field("username", &self.username)
+            .field("connect_string", &connect_string)
+            .field("info", &self.info)
+            .finish()
+    }
+}
+
+impl fmt::Display for ExpiredPassword {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "password for '{}' has expired: {}", self.username, self.info)
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A `LazyConnection` is configured up front but does not connect to the database until it is
+//! first used. This allows services to be constructed before the database is reachable. The
+//! connection can optionally be pre-warmed on a background thread by calling `prewarm()`.
+//!
+//! Only the most common `Connection` methods are mirrored on `LazyConnection`. The rest of the
+//! `Connection` API is reached through `get()`, which connects first if need be.
+use chrono::{DateTime, FixedOffset};
+use common::version;
+use config::{self, ConnectParams};
+use connection::{Connection, Created, Pending};
+use connection::nls::DateFormat;
+use connection::script::ScriptResult;
+use connection::trace::TraceAttrs;
+use context::{self, Context};
+use error::{ErrorKind, Result};
+use odpi::flags;
+use statement::Statement;
+use std::cell::{Ref, RefCell};
+use std::fmt;
+use std::thread::{self, JoinHandle};
+
+/// The connection parameters captured when the `LazyConnection` is created.
+#[derive(Clone)]
+struct Params {
+    /// The username used to authenticate.
+    username: Option<String>,
+    /// The password used to authenticate.
+    password: Option<String>,
+    /// The connect string identifying the database.
+    connect_string: Option<String>,
+}
+
+impl Params {
+    /// Convert to `ConnectParams`, using empty values for any missing parameters.
+    fn to_connect_params(&self) -> ConnectParams {
+        let value = |s: &Option<String>| s.as_ref().map_or("", |s| s.as_str()).to_string();
+        ConnectParams::new(&value(&self.username),
+                           &value(&self.password),
+                           &value(&self.connect_string))
+    }
+}
+
+/// A connection that is established on first use.
+pub struct LazyConnection<'a> {
+    /// The context used to create the connection, or None to use the global context.
+    context: Option<&'a Context>,
+    /// The connection parameters.
+    params: Params,
+    /// The connection, once established.
+    conn: RefCell<Option<Connection>>,
+    /// The pre-warm thread, if one has been started and not yet joined.
+    prewarm: RefCell<Option<JoinHandle<Result<Created>>>>,
+}
+
+impl<'a> LazyConnection<'a> {
+    /// Create a new `LazyConnection`. No connection to the database is made until the connection
+    /// is first used, or `prewarm()` is called.
+    ///
+    /// * `context` - the context handle created earlier using the function `Context::create()`, or
+    /// None to use the global context; see `Context::global()`.
+    /// * `username` - the name of the user used for authenticating the user.
+    /// * `password` - the password to use for authenticating the user.
+    /// * `connect_string` - the connect string identifying the database to which a connection is to
+    /// be established.
+    pub fn new<C>(context: C,
+                  username: Option<&str>,
+                  password: Option<&str>,
+                  connect_string: Option<&str>)
+                  -> LazyConnection<'a>
+        where C: Into<Option<&'a Context>>
+    {
+        LazyConnection {
+            context: context.into(),
+            params: Params {
+                username: username.map(|s| s.to_string()),
+                password: password.map(|s| s.to_string()),
+                connect_string: connect_string.map(|s| s.to_string()),
+            },
+            conn: RefCell::new(None),
+            prewarm: RefCell::new(None),
+        }
+    }
+
+    /// Returns the underlying `Connection`, connecting to the database first if that has not
+    /// already taken place. If a pre-warm is in progress, this waits for it to complete. If the
+    /// pre-warm failed, its error is returned, and the next call connects on the calling thread.
+    pub fn get(&self) -> Result<Ref<Connection>> {
+        if self.conn.borrow().is_none() {
+            let conn = self.connect()?;
+            *self.conn.borrow_mut() = Some(conn);
+        }
+
+        Ok(Ref::map(self.conn.borrow(), |conn| {
+            conn.as_ref().expect("connection established above")
+        }))
+    }
+
+    /// Returns true if the connection to the database has been established.
+    pub fn is_connected(&self) -> bool {
+        self.conn.borrow().is_some()
+    }
+
+    /// Starts connecting to the database on a background thread. Subsequent calls to `get()` will
+    /// wait for the background connection rather than starting a new one. This is a no-op if the
+    /// connection has already been established or a pre-warm is already in progress.
+    pub fn prewarm(&self) -> Result<()> {
+        if self.is_connected() || self.prewarm.borrow().is_some() {
+            return Ok(());
+        }
+
+        let context = self.context()?;
+        let params = self.params.to_connect_params();
+        let mut comm_cp = params.init_common_create_params(context)?;
+        let create_mode = comm_cp.get_create_mode() | flags::DPI_MODE_CREATE_THREADED;
+        comm_cp.set_create_mode(create_mode);
+        let pending = Pending::new(context, params, Some(comm_cp), None)?;
+        let handle = thread::Builder::new()
+            .name("mimir-prewarm".to_string())
+            .spawn(move || pending.create())?;
+        *self.prewarm.borrow_mut() = Some(handle);
+        Ok(())
+    }
+
+    /// Establish the connection, using the pre-warmed connection if one was started.
+    fn connect(&self) -> Result<Connection> {
+        if let Some(handle) = self.prewarm.borrow_mut().take() {
+            let created = handle
+                .join()
+                .map_err(|_| ErrorKind::Connection("pre-warm thread panicked".to_string()))??;
+            return self.adopt(created);
+        }
+
+        Connection::create(self.context, self.params.to_connect_params(), None, None)
+    }
+
+    /// Returns the context the connection is created in.
+    fn context(&self) -> Result<&'a Context> {
+        context::resolve(self.context)
+    }
+
+    /// Wraps a connection created by the pre-warm thread.
+    fn adopt(&self, created: Created) -> Result<Connection> {
+        Connection::from_created(self.context()?, created)
+    }
+
+    /// See `Connection::break_execution()`.
+    pub fn break_execution(&self) -> Result<()> {
+        self.get()?.break_execution()
+    }
+
+    /// See `Connection::change_password()`.
+    pub fn change_password(&self,
+                           username: &str,
+                           old_password: &str,
+                           new_password: &str)
+                           -> Result<()> {
+        self.get()?.change_password(username, old_password, new_password)
+    }
+
+    /// See `Connection::close()`.
+    pub fn close<M>(&self, mode: M, tag: Option<&str>) -> Result<()>
+        where M: Into<flags::ODPIConnCloseMode>
+    {
+        self.get()?.close(mode, tag)
+    }
+
+    /// See `Connection::commit()`.
+    pub fn commit(&self) -> Result<()> {
+        self.get()?.commit()
+    }
+
+    /// See `Connection::get_container()`.
+    pub fn get_container(&self) -> Result<String> {
+        self.get()?.get_container()
+    }
+
+    /// See `Connection::get_current_schema()`.
+    pub fn get_current_schema(&self) -> Result<String> {
+        self.get()?.get_current_schema()
+    }
+
+    /// See `Connection::get_date_format()`.
+    pub fn get_date_format(&self, kind: DateFormat) -> Result<String> {
+        self.get()?.get_date_format(kind)
+    }
+
+    /// See `Connection::get_server_version()`.
+    pub fn get_server_version(&self) -> Result<version::Info> {
+        self.get()?.get_server_version()
+    }
+
+    /// See `Connection::ping()`.
+    pub fn ping(&self) -> Result<()> {
+        self.get()?.ping()
+    }
+
+    /// See `Connection::prepare_stmt()`.
+    pub fn prepare_stmt(&self,
+                        sql: Option<&str>,
+                        tag: Option<&str>,
+                        scrollable: bool)
+                        -> Result<Statement> {
+        self.get()?.prepare_stmt(sql, tag, scrollable)
+    }
+
+    /// See `Connection::rollback()`.
+    pub fn rollback(&self) -> Result<()> {
+        self.get()?.rollback()
+    }
+
+    /// See `Connection::run_script()`.
+    pub fn run_script(&self, script: &str) -> Result<Vec<ScriptResult>> {
+        self.get()?.run_script(script)
+    }
+
+    /// See `Connection::server_time()`.
+    pub fn server_time(&self) -> Result<DateTime<FixedOffset>> {
+        self.get()?.server_time()
+    }
+
+    /// See `Connection::set_action()`.
+    pub fn set_action(&self, action: &str) -> Result<()> {
+        self.get()?.set_action(action)
+    }
+
+    /// See `Connection::set_client_identifier()`.
+    pub fn set_client_identifier(&self, id: &str) -> Result<()> {
+        self.get()?.set_client_identifier(id)
+    }
+
+    /// See `Connection::set_client_info()`.
+    pub fn set_client_info(&self, info: &str) -> Result<()> {
+        self.get()?.set_client_info(info)
+    }
+
+    /// See `Connection::set_container()`.
+    pub fn set_container(&self, pdb_name: &str) -> Result<()> {
+        self.get()?.set_container(pdb_name)
+    }
+
+    /// See `Connection::set_current_schema()`.
+    pub fn set_current_schema(&self, schema: &str) -> Result<()> {
+        self.get()?.set_current_schema(schema)
+    }
+
+    /// See `Connection::set_date_format()`.
+    pub fn set_date_format(&self, kind: DateFormat, format: &str) -> Result<()> {
+        self.get()?.set_date_format(kind, format)
+    }
+
+    /// See `Connection::set_module()`.
+    pub fn set_module(&self, module: &str) -> Result<()> {
+        self.get()?.set_module(module)
+    }
+
+    /// See `Connection::set_trace_attrs()`.
+    pub fn set_trace_attrs(&self, attrs: &TraceAttrs) -> Result<()> {
+        self.get()?.set_trace_attrs(attrs)
+    }
+}
+
+/// The `Debug` output never includes the password.
+impl<'a> fmt::Debug for LazyConnection<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let connect_string = self.params
+            .connect_string
+            .as_ref()
+            .map(|connect_string| config::redact_connect_string(connect_string));
+
+        f.debug_struct("LazyConnection")
+            .field("username", &self.params.username)
+            .field("connect_string", &connect_string)
+            .field("connected", &self.is_connected())
+            .finish()
+    }
+}
+
+impl<'a> Drop for LazyConnection<'a> {
+    fn drop(&mut self) {
+        // The pre-warm thread borrows the context, so it must finish before the context can be
+        // destroyed. A connection created by it that was never used is released here.
+        if let Some(handle) = self.prewarm.borrow_mut().take() {
+            if let Ok(Ok(created)) = handle.join() {
+                if let Ok(conn) = self.adopt(created) {
+                    let _ = conn.release();
+                }
+            }
+        }
+    }
+}
@@ -11,45 +11,205 @@
 //! calling the function `Pool::acquireConnection()`. They can be closed by calling the function
 //! `close()` or releasing the last reference to the connection by calling the function `release()`.
 //! Connection handles are used to create all handles other than session pools and context handles.
-use common::{encoding, version};
-use context::Context;
+use chrono::{DateTime, FixedOffset};
+use common::{encoding, error, version};
+use config::{self, ConnectParams};
+use context::{self, Context, ContextRef};
 use context::params::{CommonCreate, ConnCreate, SubscrCreate};
+use data::Data;
 use dequeue;
 use enqueue;
 use error::{ErrorKind, Result};
+use flashback::{AsOf, Flashback, Scn};
 use lob::Lob;
+use logger::Logger;
 use message::Properties;
 use object::Object;
 use objecttype::ObjectType;
+use oracletype::OracleType;
+use odpi::constants::DPI_SUCCESS;
 use odpi::{enums, externs, flags};
-use odpi::opaque::ODPIConn;
-use odpi::structs::{ODPIEncodingInfo, ODPIVersionInfo};
-use slog::Logger;
-use statement::Statement;
+use odpi::opaque::{ODPIConn, ODPIContext};
+use odpi::structs::{ODPIEncodingInfo, ODPIErrorInfo, ODPIVersionInfo};
+use resultcache::{self, PrepareOptions, ResultCacheMode};
+use sql;
+use statement::{ControlFlow, Statement};
+use statement::binder::BindValue;
+use statement::buffers::{FetchRow, RowValue};
+use statement::tracker::{OpenStatement, Tracker};
+use stmtcache::{self, CacheStats, Tracker as CacheTracker};
+use self::trace::TraceAttrs;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
 use std::ffi::{CStr, CString};
+use std::fmt;
 use std::ptr;
-use subscription::Subscription;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use subscription::{self, Channel, Renewal, Subscription};
+use timing::{Timing, Timings};
+use transaction::{Transaction, TxnOptions};
 use util::ODPIStr;
 use variable::Var;
 
+pub mod batch;
+pub mod ddl;
+pub mod expired;
+pub mod lazy;
+pub mod nls;
+pub mod script;
+pub mod session;
+pub mod trace;
+
+use self::batch::Batch;
+use self::ddl::{CompiledObject, Diagnostic};
+use self::expired::ExpiredPassword;
+use self::nls::DateFormat;
+use self::script::{ScriptResult, split_script};
+use self::session::SessionId;
+
+/// The SQL used to retrieve the database timestamp in `Connection::server_time()`.
+const SERVER_TIME_SQL: &str = "select systimestamp from dual";
+/// The statement cache tag used for the `Connection::server_time()` statement.
+const SERVER_TIME_TAG: &str = "mimir_server_time";
+/// The SQL used to retrieve the current SCN in `Connection::current_scn()`. The SCN is converted
+/// to a string, as large SCNs lose precision when fetched as a double.
+const CURRENT_SCN_SQL: &str = "select to_char(dbms_flashback.get_system_change_number) from dual";
+/// The SQL used to retrieve the SID and serial number in `Connection::current_session_id()`.
+/// Unlike `V$SESSION`, `DBMS_DEBUG_JDWP` can be used without any privilege.
+const CURRENT_SESSION_SQL: &str = "select to_char(dbms_debug_jdwp.current_session_id), \
+                                   to_char(dbms_debug_jdwp.current_session_serial) from dual";
+/// The SQL used to retrieve the `COMPATIBLE` setting in `Connection::max_identifier_length()`. It
+/// requires the SELECT privilege on `V$PARAMETER`, e.g. through `SELECT_CATALOG_ROLE`.
+const COMPATIBLE_SQL: &str = "select value from v$parameter where name = 'compatible'";
+/// The SQL used to retrieve the current container in `Connection::get_container()`.
+const CONTAINER_NAME_SQL: &str = "select sys_context('USERENV', 'CON_NAME') from dual";
+/// The SQL used to retrieve the id of the current container in `Connection::is_container_db()`.
+/// It is 0 in a database that is not a container database.
+const CONTAINER_ID_SQL: &str = "select to_number(sys_context('USERENV', 'CON_ID')) from dual";
+/// The SQL used to read the value of an NLS session parameter in `Connection::get_date_format()`.
+const NLS_PARAMETER_SQL: &str = "select value from nls_session_parameters where parameter = :name";
+/// The SQL used to enable `DBMS_OUTPUT` in `Connection::enable_dbms_output()`.
+const DBMS_OUTPUT_ENABLE_SQL: &str = "begin dbms_output.enable(buffer_size => :buffer_size); end;";
+/// The SQL used to read lines of `DBMS_OUTPUT` in `Connection::fetch_dbms_output()`. The lines are
+/// read into a PL/SQL array bind, so a batch of lines takes a single round trip.
+const DBMS_OUTPUT_LINES_SQL: &str = "declare \
+                                       l_line varchar2(32767); \
+                                       l_status integer; \
+                                     begin \
+                                       for i in 1 .. :max_lines loop \
+                                         dbms_output.get_line(l_line, l_status); \
+                                         exit when l_status = 1; \
+                                         :lines(i) := l_line; \
+                                       end loop; \
+                                     end;";
+/// The statement cache tag used for the `Connection::fetch_dbms_output()` statement.
+const DBMS_OUTPUT_LINES_TAG: &str = "mimir_dbms_output_lines";
+/// The number of `DBMS_OUTPUT` lines read in one round trip by `Connection::fetch_dbms_output()`.
+const DBMS_OUTPUT_BATCH: u32 = 100;
+/// The maximum length of a `DBMS_OUTPUT` line, in bytes.
+const DBMS_OUTPUT_LINE_LEN: u32 = 32_767;
+/// The SQL used to retrieve the compilation diagnostics in `Connection::execute_immediate()`.
+const COMPILE_ERRORS_SQL: &str = "select line, position, text, attribute, message_number \
+                                  from all_errors \
+                                  where owner = nvl(:owner, sys_context('USERENV', \
+                                  'CURRENT_SCHEMA')) and type = :type and name = :name \
+                                  order by sequence";
+
 /// Connection handles are used to represent connections to the database.
 #[allow(dead_code)]
 pub struct Connection {
     /// The ODPI-C connection.
     inner: *mut ODPIConn,
+    /// The context the connection was created in, used to retrieve error information.
+    context: ContextRef,
     /// Optional stdout logger.
     stdout: Option<Logger>,
     /// Optoinal stderr logger.
     stderr: Option<Logger>,
+    /// The server version, cached after the first call to `get_server_version()`.
+    server_version: RefCell<Option<version::Info>>,
+    /// The maximum identifier length, cached after the first call to `max_identifier_length()`.
+    max_identifier_len: Cell<Option<usize>>,
+    /// The encoding information, cached after the first call to `get_encoding_info()`.
+    encoding_info: RefCell<Option<Rc<encoding::Info>>>,
+    /// Is the database a container database? Cached after the first call to `is_container_db()`.
+    container_db: Cell<Option<bool>>,
+    /// The options of the transaction started by `begin_transaction_with()`, if it is active.
+    txn_options: Cell<Option<TxnOptions>>,
+    /// The LOB prefetch size given to statements prepared on the connection.
+    lob_prefetch_size: Cell<u32>,
+    /// The define size margin given to statements prepared on the connection.
+    define_size_margin: Cell<Option<f64>>,
+    /// The connect string used to create a standalone connection, with any password redacted.
+    connect_string: Option<String>,
+    /// The open statement tracker, if tracking has been enabled with `set_track_statements()`.
+    stmt_tracker: RefCell<Option<Rc<Tracker>>>,
+    /// The statement cache model, if statistics have been enabled with
+    /// `set_statement_cache_stats()`.
+    stmt_cache: RefCell<Option<CacheTracker>>,
+    /// The time taken to create or acquire the connection.
+    connect_time: Option<Duration>,
+    /// The call timings, if timing has been enabled with `set_timing()`.
+    timings: RefCell<Option<Rc<Timings>>>,
+    /// The idle time after which `tick()` pings the connection, if keep-alive is enabled.
+    keep_alive: Cell<Option<Duration>>,
+    /// The time of the last call that may have made a round trip to the server, shared with the
+    /// statements prepared on the connection.
+    last_activity: Rc<Cell<Instant>>,
+    /// Did the last ping succeed?
+    healthy: Cell<bool>,
+    /// The tag the session is given when the connection is closed without one.
+    release_tag: RefCell<Option<String>>,
+    /// The tag the session had when it was acquired from a session pool, if it was tagged.
+    acquired_tag: Option<String>,
+    /// Did the session acquired from a session pool have the requested tag? None if no tag was
+    /// requested, or the connection is standalone.
+    tag_found: Option<bool>,
+    /// The warning returned when the connection was created, such as ORA-28002.
+    warning: Option<error::Info>,
 }
 
 impl Connection {
+    /// Create a new `Connection` from an `ODPIConn` pointer and the context it was created in.
+    #[doc(hidden)]
+    pub fn with_context(inner: *mut ODPIConn, context: ContextRef) -> Connection {
+        Connection {
+            inner: inner,
+            context: context,
+            stdout: None,
+            stderr: None,
+            server_version: RefCell::new(None),
+            max_identifier_len: Cell::new(None),
+            container_db: Cell::new(None),
+            encoding_info: RefCell::new(None),
+            txn_options: Cell::new(None),
+            lob_prefetch_size: Cell::new(0),
+            define_size_margin: Cell::new(None),
+            connect_string: None,
+            stmt_tracker: RefCell::new(None),
+            stmt_cache: RefCell::new(None),
+            connect_time: None,
+            timings: RefCell::new(None),
+            keep_alive: Cell::new(None),
+            last_activity: Rc::new(Cell::new(Instant::now())),
+            healthy: Cell::new(true),
+            release_tag: RefCell::new(None),
+            acquired_tag: None,
+            tag_found: None,
+            warning: None,
+        }
+    }
+
     /// Adds a reference to the connection. This is intended for situations where a reference to the
     /// connection needs to be maintained independently of the reference returned when the
     /// connection was created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiConn_addRef(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(added, "Connection", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Connection("dpiConn_addRef".to_string()))
     }
 
@@ -82,6 +242,30 @@ impl Connection {
         }
     }
 
+    /// Returns a guard for the transaction on the connection. Oracle begins a transaction
+    /// implicitly with the first DML statement, so no round trip is made here. The transaction is
+    /// rolled back if the guard is dropped without calling `Transaction::commit()`.
+    pub fn begin_transaction(&self) -> Result<Transaction> {
+        Ok(Transaction::new(self, Default::default()))
+    }
+
+    /// Begins a transaction with the given options by issuing the appropriate SET TRANSACTION
+    /// statement. This must be called before any other statement in the transaction. The options
+    /// are tracked until the transaction is committed or rolled back, and can be retrieved by
+    /// calling `get_transaction_options()`.
+    ///
+    /// * `options` - the isolation level and read-only mode of the transaction.
+    pub fn begin_transaction_with(&self, options: TxnOptions) -> Result<Transaction> {
+        if self.txn_options.get().is_some() {
+            let err = "begin_transaction_with: a transaction is already active!".to_string();
+            return Err(ErrorKind::Transaction(err).into());
+        }
+
+        let txn = Transaction::begin(self, options)?;
+        self.txn_options.set(Some(options));
+        Ok(txn)
+    }
+
     /// Performs an immediate (asynchronous) termination of any currently executing function on the
     /// server associated with the connection.
     pub fn break_execution(&self) -> Result<()> {
@@ -118,6 +302,36 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_changePassword".to_string()))
     }
 
+    /// Changes the password of the specified user and verifies the new credentials by creating a
+    /// new connection with them. The existing session remains valid after a password change, so a
+    /// successful `change_password()` alone does not prove that the new password can be used to log
+    /// in. Returns the new connection; the existing connection is left open.
+    ///
+    /// * `context` - the context handle used to create the new connection, or None to use the
+    /// global context; see `Context::global()`.
+    /// * `username` - the name of the user whose password is to be changed.
+    /// * `old_password` - the old password of the user.
+    /// * `new_password` - the new password of the user.
+    /// * `connect_string` - the connect string identifying the database to reconnect to.
+    /// * `common_create_params` - An optional `CommonCreate` structure used when creating the new
+    /// connection.
+    pub fn change_password_and_reconnect<'a, C>(&self,
+                                                context: C,
+                                                username: &str,
+                                                old_password: &str,
+                                                new_password: &str,
+                                                connect_string: Option<&str>,
+                                                common_create_params: Option<CommonCreate>)
+                                                -> Result<Connection>
+        where C: Into<Option<&'a Context>>
+    {
+        self.change_password(username, old_password, new_password)?;
+        Connection::create(context,
+                           (username, new_password, connect_string.unwrap_or("")),
+                           common_create_params,
+                           None)
+    }
+
     /// Closes the connection and makes it unusable for further activity.
     ///
     /// * `mode` - one or more of the values from the enumeration `ODPIConnCloseMode`, OR'ed
@@ -125,81 +339,183 @@ impl Connection {
     /// * `tag` - a byte string in the encoding used for CHAR data, indicating what tag should be
     /// set on the connection when it is released back to the pool. None is also acceptable when
     /// indicating that the tag should be cleared. This value is ignored unless the close mode
-    /// includes the value DPI_MODE_CONN_CLOSE_RETAG.
-    pub fn close(&self, mode: flags::ODPIConnCloseMode, tag: Option<&str>) -> Result<()> {
+    /// includes the value DPI_MODE_CONN_CLOSE_RETAG. If the session was initialized by a pool
+    /// with `Pool::with_session_init_sql()` and the close mode includes neither
+    /// DPI_MODE_CONN_CLOSE_RETAG nor DPI_MODE_CONN_CLOSE_DROP, the session keeps the tag marking
+    /// it as initialized.
+    pub fn close<M>(&self, mode: M, tag: Option<&str>) -> Result<()>
+        where M: Into<flags::ODPIConnCloseMode>
+    {
+        let mode = mode.into();
+        let release_tag = self.release_tag.borrow();
+        let keep = flags::DPI_MODE_CONN_CLOSE_RETAG | flags::DPI_MODE_CONN_CLOSE_DROP;
+        let (mode, tag) = match *release_tag {
+            Some(ref release_tag) if !mode.intersects(keep) => {
+                (mode | flags::DPI_MODE_CONN_CLOSE_RETAG, Some(&release_tag[..]))
+            }
+            _ => (mode, tag),
+        };
         let tag_s = ODPIStr::from(tag);
 
+        for open in self.get_open_statements() {
+            try_warn!(self.stderr, "{}", open);
+        }
+
         try_dpi!(externs::dpiConn_close(self.inner, mode, tag_s.ptr(), tag_s.len()),
                  Ok(()),
                  ErrorKind::Connection("dpiConn_close".to_string()))
     }
 
+    /// Closes the connection and, if it was acquired from a session pool, drops the session from
+    /// the pool instead of returning it for reuse. This should be used for sessions that are known
+    /// to be broken, for example after a database failover, so they are not handed out again.
+    pub fn close_drop(&self) -> Result<()> {
+        self.close(flags::DPI_MODE_CONN_CLOSE_DROP, None)
+    }
+
     /// Commits the current active transaction.
     pub fn commit(&self) -> Result<()> {
-        try_dpi!(externs::dpiConn_commit(self.inner),
-                 Ok(()),
-                 ErrorKind::Connection("dpiConn_commit".to_string()))
+        self.timed("dpiConn_commit", || {
+            try_dpi!(externs::dpiConn_commit(self.inner),
+                     {
+                         self.txn_options.set(None);
+                         Ok(())
+                     },
+                     ErrorKind::Connection("dpiConn_commit".to_string()))
+        })
+    }
+
+    /// Returns the number of rows the given query returns, by running `select count(*)` over it,
+    /// so only the count is sent back rather than the rows themselves.
+    ///
+    /// * `sql` - the query whose rows are counted, without a trailing `;`.
+    /// * `binds` - the values of the named bind variables of the query.
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_sign_loss))]
+    pub fn count(&self, sql: &str, binds: &[(&str, BindValue)]) -> Result<u64> {
+        let count: i64 = self.query_scalar(&format!("select count(*) from ({})", sql), binds)?;
+        Ok(count as u64)
     }
 
     /// Creates a standalone connection to a database or acquires a connection from a session pool
     /// and returns a reference to the connection.
     ///
-    /// * `context` - the context handle created earlier using the function `Context::create()`. If
-    /// the handle is NULL or invalid an error is returned.
-    /// * `username` - the name of the user used for authenticating the user, as a byte string in
-    /// the encoding used for CHAR data. None is also acceptable if external authentication is being
-    /// requested or if credentials were specified when the pool was created.
-    /// * `password` - the password to use for authenticating the user, as a byte string in the
-    /// encoding used for CHAR data. None is also acceptable if external authentication is being
-    /// requested or if credentials were specified when the pool was created.
-    /// * `connect_string` - the connect string identifying the database to which a connection is to
-    /// be established, as a byte string in the encoding used for CHAR data. None is also acceptable
-    /// for local connections (identified by the environment variable $ORACLE_SID) or when a
-    /// connection is being acquired from a session pool. This value is ignored when a connection is
-    /// being acquired from a session pool.
+    /// * `context` - the context handle created earlier using the function `Context::create()`, or
+    /// None to use the global context; see `Context::global()`. If the handle is NULL or invalid
+    /// an error is returned.
+    /// * `params` - the username, password and connect string, e.g. a `ConnectParams` or a
+    /// `(username, password, connect_string)` tuple. The username and password may be empty if
+    /// external authentication is being requested or if a connection is being acquired from a
+    /// session pool created with credentials. The connect string may be empty for local connections
+    /// (identified by the environment variable $ORACLE_SID) and is ignored when a connection is
+    /// being acquired from a session pool. The credentials are checked before connecting, and an
+    /// `ErrorKind::Config` error is returned if they do not fit the authentication mode. The
+    /// result cache mode of the parameters, if any, is set on the new connection.
     /// * `common_create_params` - An optional `CommonCreate` structure which is used to specify
-    /// context parameters for connection creation. None is also acceptable in which case all
-    /// default parameters will be used when creating the connection. This value is ignored when a
-    /// cquiring a connection from a session pool.
+    /// context parameters for connection creation. None is also acceptable in which case the
+    /// default parameters with the encoding of `params` will be used when creating the connection.
+    /// This value is ignored when acquiring a connection from a session pool.
     /// * `conn_create_params` - An optional `ConnCreate` structure which is used to specify
     /// parameters for connection creation. None is also acceptable in which case all default
     /// parameters will be used when creating the connection.
-    pub fn create(context: &Context,
-                  username: Option<&str>,
-                  password: Option<&str>,
-                  connect_string: Option<&str>,
-                  common_create_params: Option<CommonCreate>,
-                  conn_create_params: Option<ConnCreate>)
-                  -> Result<Connection> {
-        let username_s = ODPIStr::from(username);
-        let password_s = ODPIStr::from(password);
-        let connect_string_s = ODPIStr::from(connect_string);
-        let mut inner: *mut ODPIConn = ptr::null_mut();
-
-        let comm_cp = if let Some(common_create_params) = common_create_params {
-            common_create_params
-        } else {
-            context.init_common_create_params()?
-        };
+    pub fn create<'a, C, P>(context: C,
+                            params: P,
+                            common_create_params: Option<CommonCreate>,
+                            conn_create_params: Option<ConnCreate>)
+                            -> Result<Connection>
+        where C: Into<Option<&'a Context>>,
+              P: Into<ConnectParams>
+    {
+        let context = context::resolve(context.into())?;
+        let pending =
+            Pending::new(context, params.into(), common_create_params, conn_create_params)?;
+        Connection::from_created(context, pending.create()?)
+    }
 
-        let conn_cp = if let Some(conn_create_params) = conn_create_params {
+    /// Creates a standalone connection to a database, changing the password of the user as part of
+    /// the login. This allows a session to be established for an account whose password has
+    /// expired, which would otherwise fail with ORA-28001.
+    ///
+    /// * `context` - the context handle created earlier using the function `Context::create()`, or
+    /// None to use the global context; see `Context::global()`.
+    /// * `username` - the name of the user used for authenticating the user.
+    /// * `old_password` - the current (possibly expired) password of the user.
+    /// * `new_password` - the password to change to.
+    /// * `connect_string` - the connect string identifying the database to which a connection is to
+    /// be established.
+    /// * `common_create_params` - An optional `CommonCreate` structure which is used to specify
+    /// context parameters for connection creation.
+    /// * `conn_create_params` - An optional `ConnCreate` structure which is used to specify
+    /// parameters for connection creation. Any new password already set on it is replaced.
+    pub fn create_with_new_password<'a, C>(context: C,
+                                           username: &str,
+                                           old_password: &str,
+                                           new_password: &str,
+                                           connect_string: Option<&str>,
+                                           common_create_params: Option<CommonCreate>,
+                                           conn_create_params: Option<ConnCreate>)
+                                           -> Result<Connection>
+        where C: Into<Option<&'a Context>>
+    {
+        let context = context::resolve(context.into())?;
+        let mut conn_cp = if let Some(conn_create_params) = conn_create_params {
             conn_create_params
         } else {
             context.init_conn_create_params()?
         };
+        conn_cp.set_new_password(new_password);
+
+        Connection::create(context,
+                           (username, old_password, connect_string.unwrap_or("")),
+                           common_create_params,
+                           Some(conn_cp))
+    }
 
-        try_dpi!(externs::dpiConn_create(context.inner(),
-                                         username_s.ptr(),
-                                         username_s.len(),
-                                         password_s.ptr(),
-                                         password_s.len(),
-                                         connect_string_s.ptr(),
-                                         connect_string_s.len(),
-                                         &comm_cp.inner(),
-                                         &mut conn_cp.inner(),
-                                         &mut inner),
-                 Ok(inner.into()),
-                 ErrorKind::Connection("dpiConn_create".to_string()))
+    /// Returns the current system change number (SCN) of the database. This requires execute
+    /// privilege on `DBMS_FLASHBACK`. See the `flashback` module.
+    pub fn current_scn(&self) -> Result<Scn> {
+        let stmt = self.prepare_stmt(Some(CURRENT_SCN_SQL), None, false)?;
+        let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)
+            .and_then(|_| stmt.fetch())
+            .and_then(|(found, _)| if found {
+                          let (_, data) = stmt.get_query_value(1)?;
+                          let scn = data.get_string();
+                          scn.parse().map(Scn).map_err(|_| {
+                              let err = format!("current_scn: invalid SCN '{}'!", scn);
+                              ErrorKind::Connection(err).into()
+                          })
+                      } else {
+                          let err = "current_scn: no rows returned!".to_string();
+                          Err(ErrorKind::Connection(err).into())
+                      });
+        stmt.release()?;
+        res
+    }
+
+    /// Returns the SID and serial number of the session of the connection, e.g. to report it to
+    /// an administrator or to kill it from another connection with `kill_session()`.
+    pub fn current_session_id(&self) -> Result<SessionId> {
+        let stmt = self.prepare_stmt(Some(CURRENT_SESSION_SQL), None, false)?;
+        let parse = |pos: u32| -> Result<u32> {
+            let (_, data) = stmt.get_query_value(pos)?;
+            let value = data.get_string();
+            value.parse().map_err(|_| {
+                let err = format!("current_session_id: invalid value '{}'!", value);
+                ErrorKind::Connection(err).into()
+            })
+        };
+        let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)
+            .and_then(|_| stmt.fetch())
+            .and_then(|(found, _)| if found {
+                          Ok(SessionId {
+                                 sid: parse(1)?,
+                                 serial: parse(2)?,
+                             })
+                      } else {
+                          let err = "current_session_id: no rows returned!".to_string();
+                          Err(ErrorKind::Connection(err).into())
+                      });
+        stmt.release()?;
+        res
     }
 
     /// Dequeues a message from a queue.
@@ -232,6 +548,20 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_deqObject".to_string()))
     }
 
+    /// Enables `DBMS_OUTPUT` for the session, so the lines PL/SQL blocks write with
+    /// `DBMS_OUTPUT.PUT_LINE` are buffered on the server until read with `fetch_dbms_output()`.
+    ///
+    /// * `buffer_size` - the size of the buffer on the server, in bytes, from 2,000 to 1,000,000,
+    /// or None for an unlimited buffer. Writes that overflow the buffer fail with ORA-20000.
+    pub fn enable_dbms_output(&self, buffer_size: Option<u32>) -> Result<()> {
+        let stmt = self.prepare_stmt(Some(DBMS_OUTPUT_ENABLE_SQL), None, false)?;
+        let res = stmt.binder()
+            .set("buffer_size", buffer_size.map(i64::from))
+            .and_then(|binder| binder.execute());
+        stmt.release()?;
+        res.map(|_| ())
+    }
+
     /// Enqueues a message to a queue.
     ///
     /// * `queue_name` - the name of the queue to which the message is to be enqueued, as a byte
@@ -262,6 +592,83 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_enqObject".to_string()))
     }
 
+    /// Executes a DDL statement, and returns the compilation errors and warnings of the stored
+    /// object it creates or compiles, such as a procedure, package body, trigger or forced view.
+    /// Such statements succeed even if the object does not compile, so without checking the
+    /// diagnostics an invalid object goes unnoticed until it is used. The result is empty for
+    /// statements that do not compile an object, and for objects that compiled cleanly. Warnings
+    /// are only reported if `PLSQL_WARNINGS` enables them for the session. See the `ddl` module.
+    ///
+    /// * `sql` - the DDL statement to execute.
+    pub fn execute_immediate(&self, sql: &str) -> Result<Vec<Diagnostic>> {
+        self.context.check()?;
+        self.run_script_statement(sql)?;
+
+        match ddl::compiled_object(sql) {
+            Some(object) => self.compile_diagnostics(&object),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Returns whether the given query returns any row. The query is limited with
+    /// `where rownum = 1`, so the server stops at the first row found and nothing but a count is
+    /// sent back.
+    ///
+    /// * `sql` - the query to probe, without a trailing `;`.
+    /// * `binds` - the values of the named bind variables of the query.
+    pub fn exists(&self, sql: &str, binds: &[(&str, BindValue)]) -> Result<bool> {
+        let sql = format!("select count(*) from ({}) where rownum = 1", sql);
+        let count: i64 = self.query_scalar(&sql, binds)?;
+        Ok(count > 0)
+    }
+
+    /// Reads, and removes from the server buffer, all of the lines written with `DBMS_OUTPUT` since
+    /// the last call. The lines are read in batches, each taking one round trip. Nothing is
+    /// buffered unless `enable_dbms_output()` was called first.
+    pub fn fetch_dbms_output(&self) -> Result<Vec<String>> {
+        let stmt = self.prepare_stmt(Some(DBMS_OUTPUT_LINES_SQL),
+                                     Some(DBMS_OUTPUT_LINES_TAG),
+                                     false)?;
+        let res = self.new_var(enums::ODPIOracleTypeNum::Varchar,
+                               enums::ODPINativeTypeNum::Bytes,
+                               DBMS_OUTPUT_BATCH,
+                               DBMS_OUTPUT_LINE_LEN,
+                               true,
+                               true,
+                               None)
+            .and_then(|lines| {
+                          let res = self.read_dbms_output(&stmt, &lines);
+                          lines.release()?;
+                          res
+                      });
+        stmt.close(Some(DBMS_OUTPUT_LINES_TAG))?;
+        res
+    }
+
+    /// Runs the queries of the session as of the given SCN or time, until the returned guard is
+    /// dropped. This requires execute privilege on `DBMS_FLASHBACK`, and DML is not allowed while
+    /// the guard is alive. To query a single table in the past instead, add an `AsOf` clause after
+    /// the table name. See the `flashback` module.
+    ///
+    /// * `as_of` - the SCN or time the queries read data as of.
+    pub fn flashback(&self, as_of: AsOf) -> Result<Flashback> {
+        Flashback::begin(self, as_of)
+    }
+
+    /// Returns the tag the session had when it was acquired from a session pool, or None if the
+    /// session was not tagged or the connection is standalone. With `match_any_tag` set in the
+    /// `ConnCreate` parameters, this may differ from the tag requested.
+    pub fn get_acquired_tag(&self) -> Option<&str> {
+        self.acquired_tag.as_ref().map(|tag| &tag[..])
+    }
+
+    /// Returns the time taken to create the connection, or to acquire it from a session pool
+    /// including any time spent waiting for a free session. None if the connection was not created
+    /// by this crate.
+    pub fn get_connect_time(&self) -> Option<Duration> {
+        self.connect_time
+    }
+
     /// Get the current schema.
     pub fn get_current_schema(&self) -> Result<String> {
         let mut pdst = ptr::null();
@@ -272,6 +679,33 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_getCurrentSchema".to_string()))
     }
 
+    /// Returns the format the session converts values of the given datetime type to and from
+    /// strings with. See `set_date_format()`.
+    ///
+    /// * `kind` - the datetime type.
+    pub fn get_date_format(&self, kind: DateFormat) -> Result<String> {
+        self.query_scalar(NLS_PARAMETER_SQL, &[("name", BindValue::from(kind.parameter()))])
+    }
+
+    /// Returns the name of the container the session is connected to: the pluggable database, or
+    /// `CDB$ROOT` in the root of a container database. In a database that is not a container
+    /// database, this is the name of the database. Oracle 12.1 or later is required.
+    pub fn get_container(&self) -> Result<String> {
+        self.query_scalar(CONTAINER_NAME_SQL, &[])
+    }
+
+    /// Returns the default define size margin given to statements prepared on the connection. See
+    /// `Statement::set_define_size_margin()`.
+    pub fn get_default_define_size_margin(&self) -> Option<f64> {
+        self.define_size_margin.get()
+    }
+
+    /// Returns the default LOB prefetch size, in bytes, given to statements prepared on the
+    /// connection. See `Statement::set_lob_prefetch_size()`.
+    pub fn get_default_lob_prefetch_size(&self) -> u32 {
+        self.lob_prefetch_size.get()
+    }
+
     /// Returns the edition that is being used by the connection.
     pub fn get_edition(&self) -> Result<String> {
         let mut pdst = ptr::null();
@@ -284,12 +718,25 @@ impl Connection {
 
     /// Returns the encoding information used by the connection. This will be equivalent to the
     /// values passed when the standalone connection or session pool was created, or the values
-    /// retrieved from the environment variables NLS_LANG and NLS_NCHAR.
+    /// retrieved from the environment variables NLS_LANG and NLS_NCHAR. The information is cached
+    /// on the connection, and shared with the statements and variables created on it.
     pub fn get_encoding_info(&self) -> Result<encoding::Info> {
-        let mut encoding_info: ODPIEncodingInfo = Default::default();
+        self.encoding_info().map(|encoding_info| (*encoding_info).clone())
+    }
 
+    /// Returns the encoding information, retrieving it from ODPI-C on the first call.
+    fn encoding_info(&self) -> Result<Rc<encoding::Info>> {
+        if let Some(ref encoding_info) = *self.encoding_info.borrow() {
+            return Ok(encoding_info.clone());
+        }
+
+        let mut encoding_info: ODPIEncodingInfo = Default::default();
         try_dpi!(externs::dpiConn_getEncodingInfo(self.inner, &mut encoding_info),
-                 Ok(encoding_info.into()),
+                 {
+                     let encoding_info = Rc::new(encoding::Info::from(encoding_info));
+                     *self.encoding_info.borrow_mut() = Some(encoding_info.clone());
+                     Ok(encoding_info)
+                 },
                  ErrorKind::Connection("dpiConn_getEncodingInfo".to_string()))
     }
 
@@ -360,6 +807,12 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_getInternalName".to_string()))
     }
 
+    /// Returns the idle time after which `tick()` pings the connection, or None if keep-alive is
+    /// disabled.
+    pub fn get_keep_alive(&self) -> Option<Duration> {
+        self.keep_alive.get()
+    }
+
     /// Returns the logical transaction id for the connection. This value is used in Transaction
     /// Guard to determine if the last failed call was completed and if the transaction was
     /// committed using the procedure call dbms_app_cont.get_ltxid_outcome().
@@ -372,6 +825,36 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_getLTXID".to_string()))
     }
 
+    /// Returns the compilation errors and warnings of a stored object, from `ALL_ERRORS`, in the
+    /// order the compiler reported them. The result is empty if the object compiled cleanly, or if
+    /// it does not exist or is not visible.
+    ///
+    /// * `object_type` - the type of the object as named in `ALL_ERRORS`, e.g. `PROCEDURE` or
+    /// `PACKAGE BODY`. It is matched case insensitively.
+    /// * `name` - the name of the object, optionally qualified by its schema. Unquoted names are
+    /// upper cased the way Oracle stores them.
+    pub fn get_object_errors(&self, object_type: &str, name: &str) -> Result<Vec<Diagnostic>> {
+        let mut rest = name;
+        let (owner, name) = match ddl::object_name(&mut rest) {
+            Some(parts) if rest.trim().is_empty() => parts,
+            _ => {
+                let err = format!("get_object_errors: invalid object name '{}'!", name);
+                return Err(ErrorKind::Connection(err).into());
+            }
+        };
+        let object_type = object_type
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_uppercase();
+
+        self.compile_diagnostics(&CompiledObject {
+                                     owner: owner,
+                                     object_type: object_type,
+                                     name: name,
+                                 })
+    }
+
     /// Looks up an object type by name in the database and returns a reference to it. The reference
     /// should be released as soon as it is no longer needed.
     ///
@@ -382,13 +865,21 @@ impl Connection {
         let name_s = ODPIStr::from(name);
 
         try_dpi!(externs::dpiConn_getObjectType(self.inner, name_s.ptr(), name_s.len(), &mut pobj),
-                 Ok(pobj.into()),
+                 {
+                     track_ref!(created_in, self.context.inner(), "ObjectType", pobj);
+                     Ok(pobj.into())
+                 },
                  ErrorKind::Connection("dpiConn_getObjectType".to_string()))
     }
 
     /// Returns the version information of the Oracle Database to which the connection has been
-    /// made.
+    /// made. The version is retrieved from the server on the first call and cached on the
+    /// connection, so subsequent calls do not require a round trip.
     pub fn get_server_version(&self) -> Result<version::Info> {
+        if let Some(ref ver_info) = *self.server_version.borrow() {
+            return Ok(ver_info.clone());
+        }
+
         let mut pdst = ptr::null();
         let mut dstlen = 0;
         let mut version_info: ODPIVersionInfo = Default::default();
@@ -401,11 +892,39 @@ impl Connection {
                      let mut ver_info: version::Info = version_info.into();
                      let release_s = ODPIStr::new(pdst, dstlen);
                      ver_info.set_release(Some(release_s.into()));
+                     *self.server_version.borrow_mut() = Some(ver_info.clone());
                      Ok(ver_info)
                  },
                  ErrorKind::Connection("dpiConn_getServerVersion".to_string()))
     }
 
+    /// Removes every statement from the statement cache, so the statements prepared afterwards are
+    /// parsed again. Call this after DDL has changed or recompiled an object that cached
+    /// statements refer to, which otherwise keep failing with errors such as ORA-04068 or
+    /// ORA-04061 until the connection is closed. The cache is flushed by shrinking it to zero and
+    /// restoring its size. Statements that are still open are cached again when they are closed
+    /// or released, so they should be closed first. ODPI-C 2.0 has no call to remove a single
+    /// statement from the cache.
+    pub fn flush_statement_cache(&self) -> Result<()> {
+        let size = self.get_statement_cache_size()?;
+        self.set_statement_cache_size(0)?;
+        let res = self.set_statement_cache_size(size);
+
+        if let Some(ref stmt_cache) = *self.stmt_cache.borrow() {
+            stmt_cache.flushed();
+        }
+        res
+    }
+
+    /// Returns the statements prepared on the connection that have not been released or closed.
+    /// This is always empty unless tracking has been enabled with `set_track_statements()`.
+    pub fn get_open_statements(&self) -> Vec<OpenStatement> {
+        self.stmt_tracker
+            .borrow()
+            .as_ref()
+            .map_or_else(Vec::new, |tracker| tracker.open_statements())
+    }
+
     /// Returns the size of the statement cache, in number of statements.
     pub fn get_statement_cache_size(&self) -> Result<u32> {
         let mut size = 0;
@@ -415,57 +934,232 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_getStmtCacheSize".to_string()))
     }
 
+    /// Returns the statement cache hits and misses of the statements prepared since statistics
+    /// were enabled with `set_statement_cache_stats()`. ODPI-C does not report cache hits, so they
+    /// are estimated as described by `CacheStats`. Zero if statistics are disabled.
+    pub fn get_statement_cache_stats(&self) -> CacheStats {
+        self.stmt_cache
+            .borrow()
+            .as_ref()
+            .map_or_else(CacheStats::default, |stmt_cache| stmt_cache.stats())
+    }
+
+    /// Returns whether the session acquired from a session pool had the tag requested in the
+    /// `ConnCreate` parameters, or None if no tag was requested or the connection is standalone.
+    /// ODPI-C 2.0 does not report whether a session is brand new, so tags stand in for it: a
+    /// session that was set up is closed with a tag describing its state, and `Some(false)` tells
+    /// that the session acquired still needs that setup.
+    pub fn get_tag_found(&self) -> Option<bool> {
+        self.tag_found
+    }
+
+    /// Returns the wall-clock timings of the calls made on the connection and on the statements
+    /// prepared on it since timing was enabled with `set_timing()`, keyed by the name of the ODPI-C
+    /// function called. Empty if timing is disabled.
+    pub fn get_timings(&self) -> BTreeMap<&'static str, Timing> {
+        self.timings
+            .borrow()
+            .as_ref()
+            .map_or_else(BTreeMap::new, |timings| timings.timings())
+    }
+
+    /// Returns the options of the transaction started by `begin_transaction_with()`, or None if no
+    /// such transaction is active.
+    pub fn get_transaction_options(&self) -> Option<TxnOptions> {
+        self.txn_options.get()
+    }
+
+    /// Returns the warning the server returned when the connection was created, or None. This is
+    /// ORA-28002 when the password will expire within its grace period: the connection succeeded,
+    /// but the password should be changed, e.g. with `change_password()`.
+    pub fn get_warning(&self) -> Option<&error::Info> {
+        self.warning.as_ref()
+    }
+
+    /// Inserts a row whose LOB column is set to `EMPTY_BLOB()` and writes the given data to the
+    /// locator the statement returns, so data larger than the limit of an inline bind is inserted
+    /// in one call. The statement must end with a `RETURNING` clause into its only bind variable:
+    ///
+    /// ```sql
+    /// insert into docs (id, body) values (1, empty_blob()) returning body into :body
+    /// ```
+    ///
+    /// The data is written to the LOB of every row inserted, and the number of rows inserted is
+    /// returned. Nothing is committed.
+    ///
+    /// * `sql` - the INSERT statement.
+    /// * `lob_data` - the data written to the BLOB column.
+    pub fn insert_with_lob(&self, sql: &str, lob_data: &[i8]) -> Result<u64> {
+        let stmt = self.prepare_stmt(Some(sql), None, false)?;
+        let res = self.new_var(enums::ODPIOracleTypeNum::Blob,
+                               enums::ODPINativeTypeNum::Lob,
+                               1,
+                               0,
+                               false,
+                               false,
+                               None)
+            .and_then(|var| {
+                          let res = self.insert_returning_lob(&stmt, &var, lob_data);
+                          var.release()?;
+                          res
+                      });
+        stmt.release()?;
+        res
+    }
+
+    /// Returns false if the `Context` the connection was created in has been dropped. Calls that
+    /// need the context, such as `prepare_stmt()`, then return `ErrorKind::ContextDropped`.
+    pub fn is_context_alive(&self) -> bool {
+        self.context.is_alive()
+    }
+
+    /// Returns false if the last ping of the connection, made by `ping()` or `tick()`, failed.
+    /// An unhealthy connection should be closed and replaced.
+    pub fn is_healthy(&self) -> bool {
+        self.healthy.get()
+    }
+
+    /// Returns true if the database is a multitenant container database (CDB), whose pluggable
+    /// databases can be switched between with `set_container()`. Always false before Oracle 12.1.
+    /// The answer is retrieved from the server on the first call and cached on the connection.
+    pub fn is_container_db(&self) -> Result<bool> {
+        if let Some(container_db) = self.container_db.get() {
+            return Ok(container_db);
+        }
+
+        let features = sql::ServerFeatures::from(&self.get_server_version()?);
+        let container_db = if features.multitenant() {
+            self.query_scalar::<i64>(CONTAINER_ID_SQL, &[])? != 0
+        } else {
+            false
+        };
+        self.container_db.set(Some(container_db));
+        Ok(container_db)
+    }
+
+    /// Returns the maximum length of an identifier, in bytes: 128 from Oracle 12.2 on, when the
+    /// database's `COMPATIBLE` setting is also 12.2 or later, and 30 otherwise. On a 12.2 or later
+    /// server, the setting is read from `V$PARAMETER`, which requires the SELECT privilege on it,
+    /// e.g. through `SELECT_CATALOG_ROLE`. The length is retrieved from the server on the first
+    /// call and cached on the connection.
+    pub fn max_identifier_length(&self) -> Result<usize> {
+        if let Some(max_len) = self.max_identifier_len.get() {
+            return Ok(max_len);
+        }
+
+        let version = self.get_server_version()?;
+        let max_len = if sql::max_identifier_len(&version) == sql::LEGACY_MAX_IDENTIFIER_LEN {
+            sql::LEGACY_MAX_IDENTIFIER_LEN
+        } else {
+            let compatible: String = self.query_scalar(COMPATIBLE_SQL, &[])?;
+            sql::compatible_max_identifier_len(&compatible)
+        };
+        self.max_identifier_len.set(Some(max_len));
+        Ok(max_len)
+    }
+
     /// Returns a reference to a new set of dequeue options, used in dequeuing objects from a queue.
     /// The reference should be released as soon as it is no longer needed.
     pub fn new_deq_options(&self) -> Result<dequeue::Options> {
         let mut deq_ptr = ptr::null_mut();
 
         try_dpi!(externs::dpiConn_newDeqOptions(self.inner, &mut deq_ptr),
-                 Ok(deq_ptr.into()),
+                 {
+                     track_ref!(created_in, self.context.inner(), "DeqOptions", deq_ptr);
+                     Ok(deq_ptr.into())
+                 },
                  ErrorKind::Connection("dpiConn_newDeqOptions".to_string()))
     }
 
+    /// Returns a reference to a new set of dequeue options with the options collected by the given
+    /// builder applied. If any option cannot be applied, the new options are released and the
+    /// error is returned. The reference should be released as soon as it is no longer needed.
+    ///
+    /// * `builder` - the options to apply.
+    pub fn new_deq_options_with(&self, builder: &dequeue::Builder) -> Result<dequeue::Options> {
+        let options = self.new_deq_options()?;
+
+        match builder.apply(&options) {
+            Ok(()) => Ok(options),
+            Err(e) => {
+                options.release()?;
+                Err(e)
+            }
+        }
+    }
+
     /// Returns a reference to a new set of enqueue options, used in enqueuing objects into a queue.
     /// The reference should be released as soon as it is no longer needed.
     pub fn new_enq_options(&self) -> Result<enqueue::Options> {
         let mut enq_ptr = ptr::null_mut();
 
         try_dpi!(externs::dpiConn_newEnqOptions(self.inner, &mut enq_ptr),
-                 Ok(enq_ptr.into()),
+                 {
+                     track_ref!(created_in, self.context.inner(), "EnqOptions", enq_ptr);
+                     Ok(enq_ptr.into())
+                 },
                  ErrorKind::Connection("dpiConn_newEnqOptions".to_string()))
     }
 
+    /// Returns a reference to a new set of enqueue options with the options collected by the given
+    /// builder applied. If any option cannot be applied, the new options are released and the
+    /// error is returned. The reference should be released as soon as it is no longer needed.
+    ///
+    /// * `builder` - the options to apply.
+    pub fn new_enq_options_with(&self, builder: &enqueue::Builder) -> Result<enqueue::Options> {
+        let options = self.new_enq_options()?;
+
+        match builder.apply(&options) {
+            Ok(()) => Ok(options),
+            Err(e) => {
+                options.release()?;
+                Err(e)
+            }
+        }
+    }
+
     /// Returns a reference to a new set of message properties, used in enqueuing and dequeuing
     /// objects in a queue. The reference should be released as soon as it is no longer needed.
     pub fn new_msg_props(&self) -> Result<Properties> {
         let mut msg_props_ptr = ptr::null_mut();
         try_dpi!(externs::dpiConn_newMsgProps(self.inner, &mut msg_props_ptr),
-                 Ok(msg_props_ptr.into()),
+                 {
+                     track_ref!(created_in, self.context.inner(), "MsgProps", msg_props_ptr);
+                     Ok(msg_props_ptr.into())
+                 },
                  ErrorKind::Connection("dpiConn_newMsgProps".to_string()))
     }
 
     /// Returns a reference to a subscription which is used for requesting notifications of changes
     /// on tables or queries that are made in the database. The reference should be released as soon
-    /// as it is no longer needed.
+    /// as it is no longer needed. If no callback is set and the `Callback` protocol is used, the
+    /// notifications can be read from the receiver returned by `Subscription::into_receiver()`.
     pub fn new_subscription(&self,
-                            subscr_create_params: SubscrCreate)
+                            mut subscr_create_params: SubscrCreate)
                             -> Result<(u32, Subscription)> {
-        let mut subscr_ptr = ptr::null_mut();
-        let mut subscr_id = 0;
-
-        try_dpi!(externs::dpiConn_newSubscription(self.inner,
-                                                  &mut subscr_create_params.inner(),
-                                                  &mut subscr_ptr,
-                                                  &mut subscr_id),
-                 {
-                     if subscr_ptr.is_null() {
-                         Err(ErrorKind::Connection("dpiConn_newSubscription".to_string()).into())
-                     } else {
-                         let sub: Subscription = subscr_ptr.into();
-                         Ok((subscr_id, sub))
-                     }
-                 },
-                 ErrorKind::Connection("dpiConn_newSubscription".to_string()))
+        let renewal = Renewal::new(&subscr_create_params);
+        let channel = Channel::install(&mut subscr_create_params);
+        let mut params = subscr_create_params.inner();
+
+        let res = subscription::subscribe(self.inner, &mut params).and_then(|(id, subscr_ptr)| {
+            if subscr_ptr.is_null() {
+                let err = "new_subscription: no subscription was created".to_string();
+                return Err(ErrorKind::Connection(err).into());
+            }
+            track_ref!(created_in, self.context.inner(), "Subscription", subscr_ptr);
+            let sub = Subscription::with_channel(subscr_ptr, channel.clone())
+                .with_renewal(renewal);
+            #[cfg(feature = "odpi-3")]
+            let sub = sub.with_conn(self.inner)?;
+            Ok((id, sub))
+        });
+
+        if res.is_err() {
+            if let Some(ref channel) = channel {
+                Channel::unregister(channel);
+            }
+        }
+        res
     }
 
     /// Returns a reference to a new temporary LOB which may subsequently be written and bound to a
@@ -484,7 +1178,10 @@ impl Connection {
         }
 
         try_dpi!(externs::dpiConn_newTempLob(self.inner, lob_type, &mut lob_ptr),
-                 Ok(lob_ptr.into()),
+                 {
+                     track_ref!(created_in, self.context.inner(), "Lob", lob_ptr);
+                     Ok(lob_ptr.into())
+                 },
                  ErrorKind::Connection("dpiConn_newTempLob".to_string()))
     }
 
@@ -501,47 +1198,116 @@ impl Connection {
     /// * `size` - the maximum size of the buffer used for transferring data to/from Oracle. This
     /// value is only used for variables transferred as byte strings. Size is either in characters
     /// or bytes depending on the value of the `size_is_bytes` parameter. If the value is in
-    /// characters, internally the value will be multipled by the maximum number of bytes for each
-    /// character and that value used instead when determining the necessary buffer size.
+    /// characters, it is converted to bytes with `encoding::Info::bytes_for_chars()`, using the
+    /// maximum number of bytes per character of the connection's CHAR or NCHAR encoding.
     /// * `size_is_bytes` - boolean value indicating if the size parameter refers to characters or
     /// bytes. This flag is only used if the variable refers to character data.
     /// * `is_array` - boolean value indicating if the variable refers to a PL/SQL array or simply
     /// to buffers used for binding or fetching data.
+    /// * `object_type` - the object type of the data. This value is required when the Oracle type
+    /// is `Object`; for all other types it is ignored and None should be passed.
     pub fn new_var(&self,
                    oracle_type_num: enums::ODPIOracleTypeNum,
                    native_type_num: enums::ODPINativeTypeNum,
                    max_array_size: u32,
                    size: u32,
                    size_is_bytes: bool,
-                   is_array: bool)
+                   is_array: bool,
+                   object_type: Option<&ObjectType>)
                    -> Result<Var> {
         let mut var_ptr = ptr::null_mut();
         let mut data_ptr = ptr::null_mut();
-        let object_type = ptr::null_mut();
+        let object_type = if let Some(object_type) = object_type {
+            object_type.inner()
+        } else {
+            ptr::null_mut()
+        };
 
-        let sib = if size_is_bytes { 1 } else { 0 };
+        let encoding_info = self.encoding_info()?;
+        let size = if size_is_bytes {
+            size
+        } else {
+            encoding_info.bytes_for_chars(oracle_type_num, size)
+        };
         let ia = if is_array { 1 } else { 0 };
 
-        /// TODO: Fix object_type when Object is implemented fully.
         try_dpi!(externs::dpiConn_newVar(self.inner,
                                          oracle_type_num,
                                          native_type_num,
                                          max_array_size,
                                          size,
-                                         sib,
+                                         1,
                                          ia,
                                          object_type,
                                          &mut var_ptr,
                                          &mut data_ptr),
-                 Ok(var_ptr.into()),
+                 {
+                     track_ref!(created_in, self.context.inner(), "Var", var_ptr);
+                     let mut var = Var::new(var_ptr, data_ptr, max_array_size);
+                     var.set_encoding(encoding_info, oracle_type_num);
+                     Ok(var)
+                 },
                  ErrorKind::Connection("dpiConn_newVar".to_string()))
     }
 
+    /// Returns a reference to a new variable of the given type, using the native type and size of
+    /// the type. See `new_var()`.
+    ///
+    /// * `oracle_type` - the type of the variable. String types are sized in characters.
+    /// * `max_array_size` - the maximum number of elements of the variable.
+    /// * `object_type` - the type of the object, which must be given for `OracleType::Object`.
+    pub fn new_var_of(&self,
+                      oracle_type: OracleType,
+                      max_array_size: u32,
+                      object_type: Option<&ObjectType>)
+                      -> Result<Var> {
+        self.new_var(oracle_type.oracle_type_num(),
+                     oracle_type.native_type_num(),
+                     max_array_size,
+                     oracle_type.size(),
+                     false,
+                     false,
+                     object_type)
+    }
+
+    /// Kills the given session with `ALTER SYSTEM KILL SESSION`, which requires the `ALTER SYSTEM`
+    /// privilege. The killed session rolls back its transaction, and its next call fails with
+    /// ORA-00028.
+    ///
+    /// * `sid` - the session identifier.
+    /// * `serial` - the serial number of the session.
+    /// * `immediate` - should the session be ended without waiting for its transaction to be
+    /// rolled back?
+    pub fn kill_session(&self, sid: u32, serial: u32, immediate: bool) -> Result<()> {
+        let session = SessionId {
+            sid: sid,
+            serial: serial,
+        };
+        let sql = format!("alter system kill session {}{}",
+                          sql::quote_literal(&session.to_string()),
+                          if immediate { " immediate" } else { "" });
+        let stmt = self.prepare_stmt(Some(&sql), None, false)?;
+        let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT);
+        stmt.release()?;
+        res.map(|_| ())
+    }
+
+    /// Returns an empty `Batch` for queueing statements that are executed in order on this
+    /// connection, with runs of the same statement sent as one array execution. See the `batch`
+    /// module.
+    pub fn batch(&self) -> Batch {
+        Batch::new(self)
+    }
+
     /// Pings the database to verify that the connection is still alive.
     pub fn ping(&self) -> Result<()> {
-        try_dpi!(externs::dpiConn_ping(self.inner),
-                 Ok(()),
-                 ErrorKind::Connection("dpiConn_ping".to_string()))
+        let res = self.timed("dpiConn_ping", || {
+            try_dpi!(externs::dpiConn_ping(self.inner),
+                     Ok(()),
+                     ErrorKind::Connection("dpiConn_ping".to_string()))
+        });
+        self.healthy.set(res.is_ok());
+        res
     }
 
     /// Prepares a distributed transaction for commit. This function should only be called after
@@ -574,16 +1340,108 @@ impl Connection {
         let tag_s = ODPIStr::from(tag);
         let scroll_i = if scrollable { 0 } else { 1 };
         let mut stmt_ptr = ptr::null_mut();
+        self.context.check()?;
+        let encoding_info = self.encoding_info()?;
+
+        let res = self.timed("dpiConn_prepareStmt", || {
+            try_dpi!(externs::dpiConn_prepareStmt(self.inner,
+                                                  scroll_i,
+                                                  sql_s.ptr(),
+                                                  sql_s.len(),
+                                                  tag_s.ptr(),
+                                                  tag_s.len(),
+                                                  &mut stmt_ptr),
+                     {
+                         track_ref!(created_in, self.context.inner(), "Statement", stmt_ptr);
+                         let mut stmt = Statement::with_context(stmt_ptr, self.context.clone());
+                         stmt.set_lob_prefetch_size(self.lob_prefetch_size.get());
+                         stmt.set_define_size_margin(self.define_size_margin.get());
+                         if let Some(sql) = sql {
+                             stmt.set_sql(sql);
+                         }
+                         if let Some(ref tracker) = *self.stmt_tracker.borrow() {
+                             stmt.set_tracker(tracker.clone(), sql);
+                         }
+                         if let Some(ref timings) = *self.timings.borrow() {
+                             stmt.set_timings(timings.clone());
+                         }
+                         stmt.set_activity(self.last_activity.clone());
+                         stmt.set_stderr(self.stderr.clone());
+                         stmt.set_encoding(encoding_info);
+                         Ok(stmt)
+                     },
+                     ErrorKind::Connection("dpiConn_prepareStmt".to_string()))
+        });
+
+        if let Some(ref stmt_cache) = *self.stmt_cache.borrow() {
+            match (tag.or(sql), res.is_ok()) {
+                (Some(key), true) => {
+                    let capacity = self.get_statement_cache_size().unwrap_or(0);
+                    stmt_cache.prepared(key, sql.is_none(), capacity as usize);
+                }
+                (Some(key), false) if sql.is_none() => stmt_cache.not_found(key),
+                _ => {}
+            }
+        }
+        res
+    }
 
-        try_dpi!(externs::dpiConn_prepareStmt(self.inner,
-                                              scroll_i,
-                                              sql_s.ptr(),
-                                              sql_s.len(),
-                                              tag_s.ptr(),
-                                              tag_s.len(),
-                                              &mut stmt_ptr),
-                 Ok(Statement::new(stmt_ptr)),
-                 ErrorKind::Connection("dpiConn_prepareStmt".to_string()))
+    /// Prepares a statement, looking it up in the statement cache by a tag derived from its SQL
+    /// text and the given key with `cache_tag()`. Close the statement with
+    /// `Statement::close_cached()` to return it to the cache under that tag. Unlike a tag chosen
+    /// by hand, the derived tag cannot be shared by statements with different SQL, which would get
+    /// each other's cursor from the cache. The key keeps separate cursors for the same SQL, e.g.
+    /// one per set of bind types, so a cursor is not re-parsed when it is bound differently.
+    ///
+    /// * `sql` - the SQL that is to be prepared.
+    /// * `key` - an additional key distinguishing cursors with the same SQL text, or None.
+    pub fn prepare_cached(&self, sql: &str, key: Option<&str>) -> Result<Statement> {
+        let tag = stmtcache::cache_tag(sql, key);
+        let mut stmt = self.prepare_stmt(Some(sql), Some(&tag), false)?;
+        stmt.set_cache_tag(tag);
+        Ok(stmt)
+    }
+
+    /// Prepares a statement using the given options, adding the `RESULT_CACHE` hint to the query
+    /// if `result_cache` is set. See `prepare_stmt()`.
+    ///
+    /// * `sql` - the SQL that is to be prepared.
+    /// * `options` - the options used to prepare the statement.
+    pub fn prepare_stmt_with(&self, sql: &str, options: PrepareOptions) -> Result<Statement> {
+        if options.result_cache {
+            let sql = resultcache::add_result_cache_hint(sql);
+            self.prepare_stmt(Some(&sql), options.tag, options.scrollable)
+        } else {
+            self.prepare_stmt(Some(sql), options.tag, options.scrollable)
+        }
+    }
+
+    /// Prepares and runs a query that returns exactly one row, e.g. a lookup by primary key, and
+    /// returns that row with one value per column. Returns `ErrorKind::NoRows` if the query returns
+    /// no rows, and `ErrorKind::TooManyRows` if it returns more than one. See
+    /// `Statement::single_row()`.
+    ///
+    /// * `sql` - the query to run.
+    /// * `binds` - the values of the named bind variables of the query.
+    pub fn query_row<R: FetchRow>(&self, sql: &str, binds: &[(&str, BindValue)]) -> Result<R> {
+        let stmt = self.prepare_stmt(Some(sql), None, false)?;
+        let res = binds
+            .iter()
+            .fold(Ok(stmt.binder()),
+                  |binder, &(name, ref value)| binder?.set(name, value.clone()))
+            .and_then(|binder| binder.bind())
+            .and_then(|_| stmt.single_row(self));
+        stmt.release()?;
+        res
+    }
+
+    /// Prepares and runs a query that returns exactly one row of a single column, e.g.
+    /// `select count(*) from ...`, and returns its value. See `query_row()`.
+    ///
+    /// * `sql` - the query to run.
+    /// * `binds` - the values of the named bind variables of the query.
+    pub fn query_scalar<T: RowValue>(&self, sql: &str, binds: &[(&str, BindValue)]) -> Result<T> {
+        self.query_row(sql, binds).map(|(value,)| value)
     }
 
     /// Releases a reference to the connection. A count of the references to the connection is
@@ -592,15 +1450,174 @@ impl Connection {
     /// already taken place using the function `close()`.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiConn_release(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(released, "Connection", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Connection("dpiConn_release".to_string()))
     }
 
     /// Rolls back the current active transaction.
     pub fn rollback(&self) -> Result<()> {
-        try_dpi!(externs::dpiConn_rollback(self.inner),
-                 Ok(()),
-                 ErrorKind::Connection("dpiConn_rollback".to_string()))
+        self.timed("dpiConn_rollback", || {
+            try_dpi!(externs::dpiConn_rollback(self.inner),
+                     {
+                         self.txn_options.set(None);
+                         Ok(())
+                     },
+                     ErrorKind::Connection("dpiConn_rollback".to_string()))
+        })
+    }
+
+    /// Runs a SQL*Plus-like script, split into statements as described in the `script` module.
+    /// Each statement is prepared and executed in turn. As in SQL*Plus, a failing statement does
+    /// not stop the script, so the results hold the outcome of every statement. Nothing is
+    /// committed unless the script commits itself or `commit()` is called afterwards.
+    ///
+    /// * `script` - the text of the script.
+    pub fn run_script(&self, script: &str) -> Result<Vec<ScriptResult>> {
+        self.context.check()?;
+        Ok(split_script(script)
+               .into_iter()
+               .map(|statement| {
+                        let result = self.run_script_statement(&statement.sql);
+                        ScriptResult {
+                            statement: statement,
+                            result: result,
+                        }
+                    })
+               .collect())
+    }
+
+    /// Executes an INSERT returning a LOB locator into the given variable and writes the data to
+    /// each locator returned.
+    fn insert_returning_lob(&self, stmt: &Statement, var: &Var, lob_data: &[i8]) -> Result<u64> {
+        let info = stmt.get_info()?;
+        if info.statement_type() != enums::ODPIStatementType::Insert {
+            let err = "insert_with_lob: the statement must be an INSERT".to_string();
+            return Err(ErrorKind::Connection(err).into());
+        } else if !info.is_returning() || stmt.get_bind_count()? != 1 {
+            let err = "insert_with_lob: the statement must return the LOB into its only bind \
+                       variable"
+                    .to_string();
+            return Err(ErrorKind::Connection(err).into());
+        }
+
+        stmt.bind_by_pos(1, var)?;
+        stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+        // The returned rows can move and resize the data array of the variable.
+        for data in var.refresh_data()? {
+            let lob = Lob::from(unsafe { data.value.as_lob });
+            lob.set_from_bytes(lob_data)?;
+        }
+        stmt.get_row_count()
+    }
+
+    /// Reads batches of `DBMS_OUTPUT` lines into the given array variable until a batch is not
+    /// full.
+    fn read_dbms_output(&self, stmt: &Statement, lines: &Var) -> Result<Vec<String>> {
+        let max_lines = Data::default();
+        max_lines.set_int64(i64::from(DBMS_OUTPUT_BATCH));
+        stmt.bind_value_by_name("max_lines", enums::ODPINativeTypeNum::Int64, &max_lines)?;
+        stmt.bind_by_name("lines", lines)?;
+
+        let mut output = Vec::new();
+        loop {
+            lines.set_num_elements_in_array(0)?;
+            stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+
+            let num_lines = lines.get_num_elements_in_array()?;
+            for pos in 0..num_lines {
+                let line = lines.get_value(pos)?;
+                // An empty line is NULL.
+                output.push(if line.is_null() {
+                                String::new()
+                            } else {
+                                line.get_string()
+                            });
+            }
+
+            if num_lines < DBMS_OUTPUT_BATCH {
+                return Ok(output);
+            }
+        }
+    }
+
+    /// Executes the `server_time()` statement and reads the timestamp it returns.
+    fn read_server_time(&self, stmt: &Statement) -> Result<DateTime<FixedOffset>> {
+        stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+        let (found, _) = stmt.fetch()?;
+
+        if found {
+            let (native_type, data) = stmt.get_query_value(1)?;
+
+            if native_type == enums::ODPINativeTypeNum::Timestamp {
+                Ok(data.get_datetime())
+            } else {
+                let err = format!("server_time: unexpected native type '{}'!", native_type);
+                Err(ErrorKind::Connection(err).into())
+            }
+        } else {
+            let err = "server_time: no rows returned!".to_string();
+            Err(ErrorKind::Connection(err).into())
+        }
+    }
+
+    /// Reads the compilation errors and warnings of a stored object from `ALL_ERRORS`.
+    fn compile_diagnostics(&self, object: &CompiledObject) -> Result<Vec<Diagnostic>> {
+        let stmt = self.prepare_stmt(Some(COMPILE_ERRORS_SQL), None, false)?;
+        let mut diagnostics = Vec::new();
+        let res = stmt.binder()
+            .set("owner", object.owner.clone())
+            .and_then(|binder| binder.set("type", &object.object_type[..]))
+            .and_then(|binder| binder.set("name", &object.name[..]))
+            .and_then(|binder| binder.execute())
+            .and_then(|_| {
+                let mut buffers: (Vec<i64>, Vec<i64>, Vec<String>, Vec<String>, Vec<i64>) =
+                    Default::default();
+                stmt.fetch_into(self, 16, &mut buffers, |rows| {
+                    for row in 0..rows.0.len() {
+                        diagnostics.push(Diagnostic {
+                                             line: rows.0[row] as u32,
+                                             position: rows.1[row] as u32,
+                                             text: rows.2[row].trim_right().to_string(),
+                                             is_warning: rows.3[row] == "WARNING",
+                                             message_number: rows.4[row] as u32,
+                                         });
+                    }
+                    ControlFlow::Continue
+                })
+            });
+        stmt.release()?;
+        res.map(|_| diagnostics)
+    }
+
+    /// Prepares and executes one statement of a script, returning the number of rows affected.
+    fn run_script_statement(&self, sql: &str) -> Result<u64> {
+        let stmt = self.prepare_stmt(Some(sql), None, false)?;
+        let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT).and_then(|_| stmt.get_row_count());
+        stmt.release()?;
+        res
+    }
+
+    /// Returns the current database timestamp (SYSTIMESTAMP), including the time zone offset of
+    /// the database server. This requires a round trip to the server, so it can also be used as a
+    /// liveness check that returns useful data. The underlying statement is tagged and returned to
+    /// the statement cache after each call, so repeated calls do not re-parse the query.
+    pub fn server_time(&self) -> Result<DateTime<FixedOffset>> {
+        let stmt = self.prepare_stmt(Some(SERVER_TIME_SQL), Some(SERVER_TIME_TAG), false)?;
+        let res = self.read_server_time(&stmt);
+        stmt.close(Some(SERVER_TIME_TAG))?;
+        res
+    }
+
+    /// Returns what the server supports, as consulted by the SQL builders such as `Merge` to
+    /// generate SQL the server accepts. See `get_server_version()` and `max_identifier_length()`.
+    pub fn server_features(&self) -> Result<sql::ServerFeatures> {
+        let features = sql::ServerFeatures::from(&self.get_server_version()?);
+        Ok(features
+               .with_max_identifier_len(self.max_identifier_length()?)
+               .with_container_db(self.is_container_db()?))
     }
 
     /// Sets the action attribute on the connection. This is one of the end-to-end tracing
@@ -645,6 +1662,44 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_setClientInfo".to_string()))
     }
 
+    /// Enables or disables keep-alive pings. While enabled, each call to `tick()` pings the
+    /// connection once it has been idle for at least the given time, so that firewalls do not drop
+    /// the session of a long idle connection. Idle time is measured from the last call made on the
+    /// connection, or on a statement prepared on it, that may have required a round trip.
+    ///
+    /// * `idle` - the idle time after which the connection is pinged. None disables keep-alive.
+    pub fn set_keep_alive(&self, idle: Option<Duration>) {
+        self.keep_alive.set(idle);
+    }
+
+    /// Set the time taken to create or acquire the connection.
+    #[doc(hidden)]
+    pub fn set_connect_time(&mut self, connect_time: Duration) {
+        self.connect_time = Some(connect_time);
+    }
+
+    /// Switches the session to another container of a container database, as the SQL statement
+    /// ALTER SESSION SET CONTAINER does. The user must have the `SET CONTAINER` privilege in the
+    /// target container. The name is validated with `sql::check_identifier()` before the
+    /// statement is built, and an error is returned without a round trip if the database is not a
+    /// container database. The current schema reverts to the user's own schema in the new
+    /// container.
+    ///
+    /// * `pdb_name` - the name of the pluggable database, or `CDB$ROOT` for the root.
+    pub fn set_container(&self, pdb_name: &str) -> Result<()> {
+        let name = sql::check_identifier(pdb_name)?;
+
+        if !self.is_container_db()? {
+            let err = format!("set_container: cannot switch to '{}', the database is not a \
+                               container database",
+                              name);
+            return Err(ErrorKind::Connection(err).into());
+        }
+
+        self.run_script_statement(&format!("alter session set container = {}", name))
+            .map(|_| ())
+    }
+
     /// Sets the current schema to be used on the connection. This has the same effect as the SQL
     /// statement ALTER SESSION SET CURRENT_SCHEMA. The value be changed when the next call
     /// requiring a round trip to the server is performed. If the new schema name does not exist,
@@ -663,6 +1718,19 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_setCurrentSchema".to_string()))
     }
 
+    /// Sets the format the session converts values of the given datetime type to and from strings
+    /// with, as the SQL statement ALTER SESSION SET NLS_DATE_FORMAT and its timestamp variants do.
+    /// This governs the text of datetime columns fetched as `Bytes`, and the parsing of strings
+    /// bound to datetime columns, regardless of the defaults of the client and server. The format
+    /// lasts until the session ends, so sessions returned to a pool keep it.
+    ///
+    /// * `kind` - the datetime type.
+    /// * `format` - the datetime format model, e.g. `YYYY-MM-DD HH24:MI:SS`. An invalid model is
+    /// reported by the server.
+    pub fn set_date_format(&self, kind: DateFormat, format: &str) -> Result<()> {
+        self.run_script_statement(&kind.alter_session_sql(format)).map(|_| ())
+    }
+
     /// Sets the database operation attribute on the connection. This is one of the end-to-end
     /// tracing attributes that can be tracked in database views, shown in audit trails and seen in
     /// tools such as Enterprise Manager.
@@ -677,6 +1745,38 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_setDbOp".to_string()))
     }
 
+    /// Sets the default define size margin given to statements subsequently prepared on the
+    /// connection, so queries whose columns are undersized by the client are fetched without
+    /// ORA-01406. See `Statement::set_define_size_margin()`.
+    ///
+    /// * `margin` - the factor the size of string and raw columns is multiplied by. None uses the
+    /// size reported by the client.
+    pub fn set_default_define_size_margin(&self, margin: Option<f64>) {
+        self.define_size_margin.set(margin);
+    }
+
+    /// Sets the default LOB prefetch size, in bytes, given to statements subsequently prepared on
+    /// the connection. This is useful for report-style queries that fetch many small LOBs inline.
+    /// See `Statement::set_lob_prefetch_size()`.
+    ///
+    /// * `size` - the default LOB prefetch size, in bytes.
+    pub fn set_default_lob_prefetch_size(&self, size: u32) {
+        self.lob_prefetch_size.set(size);
+    }
+
+    /// Sets the execution context id attribute on the connection. This is one of the end-to-end
+    /// tracing attributes that can be tracked in database views, shown in audit trails and seen in
+    /// tools such as Enterprise Manager.
+    ///
+    /// * `id` - a string in the encoding used for CHAR data which will be used to set the execution
+    /// context id attribute.
+    pub fn set_econtext_id(&self, id: &str) -> Result<()> {
+        let id_s = ODPIStr::from(id);
+
+        try_dpi!(externs::dpiConn_setEcontextId(self.inner, id_s.ptr(), id_s.len()),
+                 Ok(()),
+                 ErrorKind::Connection("dpiConn_setEcontextId".to_string()))
+    }
 
     /// Sets the external name that is being used by the connection. This value is used when logging
     /// distributed transactions.
@@ -737,6 +1837,31 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_setModule".to_string()))
     }
 
+    /// Set the tag the session is given when the connection is closed without one.
+    #[doc(hidden)]
+    pub fn set_release_tag(&self, tag: Option<&str>) {
+        *self.release_tag.borrow_mut() = tag.map(|tag| tag.to_string());
+    }
+
+    /// Set the tag the session had when it was acquired from a session pool, and whether the
+    /// requested tag, if any, was found.
+    #[doc(hidden)]
+    pub fn set_acquired_tag(&mut self, tag: Option<String>, found: Option<bool>) {
+        self.acquired_tag = tag;
+        self.tag_found = found;
+    }
+
+    /// Sets the result cache mode of the session. In `Force` mode the results of all queries are
+    /// cached, as if each had the `RESULT_CACHE` hint.
+    ///
+    /// * `mode` - the result cache mode.
+    pub fn set_result_cache_mode(&self, mode: ResultCacheMode) -> Result<()> {
+        let stmt = self.prepare_stmt(Some(mode.alter_session_sql()), None, false)?;
+        let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT);
+        stmt.release()?;
+        res.map(|_| ())
+    }
+
     /// Sets the size of the statement cache.
     ///
     /// * `size` - the new size of the statement cache, in number of statements.
@@ -746,6 +1871,95 @@ impl Connection {
                  ErrorKind::Connection("dpiConn_setStmtCacheSize".to_string()))
     }
 
+    /// Sets the logger warnings are written to, such as the failed keep-alive pings of the
+    /// connection and the coercion warnings of the statements subsequently prepared on it.
+    ///
+    /// * `stderr` - the logger, or None to discard the warnings.
+    pub fn set_stderr(&mut self, stderr: Option<Logger>) {
+        self.stderr = stderr;
+    }
+
+    /// Enables or disables statement cache statistics for the statements prepared on the
+    /// connection. The statistics are returned by `get_statement_cache_stats()`. Disabling them
+    /// forgets any statistics recorded so far.
+    ///
+    /// * `enabled` - should statement cache hits and misses be counted?
+    pub fn set_statement_cache_stats(&self, enabled: bool) {
+        let mut stmt_cache = self.stmt_cache.borrow_mut();
+
+        if !enabled {
+            *stmt_cache = None;
+        } else if stmt_cache.is_none() {
+            *stmt_cache = Some(CacheTracker::default());
+        }
+    }
+
+    /// Set the statement cache model of a connection acquired from a pool that records statement
+    /// cache statistics.
+    #[doc(hidden)]
+    pub fn set_statement_cache_tracker(&self, tracker: CacheTracker) {
+        *self.stmt_cache.borrow_mut() = Some(tracker);
+    }
+
+    /// Enables or disables wall-clock timing of the calls made on the connection and on the
+    /// statements prepared after timing is enabled. Only calls that may require a round trip to
+    /// the server are timed. The timings are returned by `get_timings()`. Disabling timing forgets
+    /// any timings recorded so far.
+    ///
+    /// * `enabled` - should calls be timed?
+    pub fn set_timing(&self, enabled: bool) {
+        let mut timings = self.timings.borrow_mut();
+
+        if !enabled {
+            *timings = None;
+        } else if timings.is_none() {
+            *timings = Some(Rc::new(Timings::default()));
+        }
+    }
+
+    /// Sets the end-to-end tracing attributes given in `attrs` that are not None. The attributes
+    /// are sent to the database with the next round trip.
+    ///
+    /// * `attrs` - the tracing attributes to set.
+    pub fn set_trace_attrs(&self, attrs: &TraceAttrs) -> Result<()> {
+        if let Some(ref module) = attrs.module {
+            self.set_module(module)?;
+        }
+        if let Some(ref action) = attrs.action {
+            self.set_action(action)?;
+        }
+        if let Some(ref client_id) = attrs.client_id {
+            self.set_client_identifier(client_id)?;
+        }
+        if let Some(ref client_info) = attrs.client_info {
+            self.set_client_info(client_info)?;
+        }
+        if let Some(ref db_op) = attrs.db_op {
+            self.set_db_op(db_op)?;
+        }
+        if let Some(ref ecid) = attrs.ecid {
+            self.set_econtext_id(ecid)?;
+        }
+        Ok(())
+    }
+
+    /// Enables or disables tracking of the statements prepared on the connection. While enabled,
+    /// each statement is recorded until it is released or closed, along with the backtrace of the
+    /// call that prepared it in debug builds. Statements that are still open are returned by
+    /// `get_open_statements()` and logged as warnings when the connection is closed. Disabling
+    /// tracking forgets any statements recorded so far.
+    ///
+    /// * `enabled` - should statements be tracked?
+    pub fn set_track_statements(&self, enabled: bool) {
+        let mut stmt_tracker = self.stmt_tracker.borrow_mut();
+
+        if !enabled {
+            *stmt_tracker = None;
+        } else if stmt_tracker.is_none() {
+            *stmt_tracker = Some(Rc::new(Tracker::default()));
+        }
+    }
+
     /// Shuts down the database. This function must be called twice for the database to be shut down
     /// successfully. After calling this function the first time, the SQL statements "alter database
     /// close normal" and "alter database dismount" must be executed. Once that is complete this
@@ -767,14 +1981,211 @@ impl Connection {
                  Ok(()),
                  ErrorKind::Connection("dpiConn_startupDatabase".to_string()))
     }
+
+    /// Pings the connection if keep-alive is enabled with `set_keep_alive()` and the connection
+    /// has been idle for at least the keep-alive time. This should be called periodically, for
+    /// example from the event loop of a daemon, at an interval shorter than the keep-alive time. A
+    /// failed ping is logged and marks the connection as unhealthy rather than returning an error.
+    ///
+    /// Returns the value of `is_healthy()` after any ping.
+    pub fn tick(&self) -> bool {
+        if let Some(idle) = self.keep_alive.get() {
+            if self.last_activity.get().elapsed() >= idle {
+                if let Err(e) = self.ping() {
+                    try_warn!(self.stderr, "keep-alive ping failed: {}", e);
+                }
+            }
+        }
+        self.healthy.get()
+    }
+
+    /// Run the given closure, timing it as a call to the given ODPI-C function if timing is
+    /// enabled, and record it as activity on the connection.
+    fn timed<T, F>(&self, fn_name: &'static str, f: F) -> T
+        where F: FnOnce() -> T
+    {
+        let timings = self.timings.borrow().clone();
+
+        let res = match timings {
+            Some(timings) => timings.time(fn_name, f),
+            None => f(),
+        };
+        self.last_activity.set(Instant::now());
+        res
+    }
+
+    /// Wraps a connection created by `Pending::create()`. The result cache mode of its parameters,
+    /// if any, is set on a standalone connection.
+    fn from_created(context: &Context, created: Created) -> Result<Connection> {
+        track_ref!(created_in, context.inner(), "Connection", created.inner);
+        let mut conn = Connection::with_context(created.inner, context.handle());
+        let params = created.pending.params;
+        if !params.get_connect_string().is_empty() {
+            conn.connect_string = Some(config::redact_connect_string(params.get_connect_string()));
+        }
+        conn.connect_time = Some(created.connect_time);
+        conn.warning = created.warning;
+
+        if let Some(mode) = params.get_result_cache_mode() {
+            if !created.pooled {
+                conn.set_result_cache_mode(mode)?;
+            }
+        }
+        Ok(conn)
+    }
+}
+
+/// Get the ODPI-C pointer of the connection, e.g. for a watchdog breaking its calls.
+pub fn inner(conn: &Connection) -> *mut ODPIConn {
+    conn.inner
 }
 
 impl From<*mut ODPIConn> for Connection {
     fn from(inner: *mut ODPIConn) -> Connection {
-        Connection {
-            inner: inner,
-            stdout: None,
-            stderr: None,
+        track_ref!(wrapped, "Connection", inner);
+        Connection::with_context(inner, ContextRef::none())
+    }
+}
+
+/// The `Debug` output identifies the database and schema the connection is for, but never
+/// includes credentials. The server version is only shown once it has been retrieved by
+/// `get_server_version()`, so formatting a connection never requires a round trip.
+impl fmt::Debug for Connection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let server_version = self.server_version
+            .borrow()
+            .as_ref()
+            .map(|ver_info| ver_info.version().to_string());
+
+        f.debug_struct("Connection")
+            .field("connect_string", &self.connect_string)
+            .field("current_schema", &self.get_current_schema().ok())
+            .field("server_version", &server_version)
+            .finish()
+    }
+}
+
+/// A connection whose parameters have been resolved against its context, ready to be created.
+/// The creation can take place on another thread, as `LazyConnection::prewarm()` does, when the
+/// create mode includes `DPI_MODE_CREATE_THREADED`.
+struct Pending {
+    /// The ODPI-C context the connection is created in.
+    context: *mut ODPIContext,
+    /// The username, password and connect string.
+    params: ConnectParams,
+    /// The common create parameters, which may point into the encoding of `params`.
+    comm_cp: CommonCreate,
+    /// The connection create parameters.
+    conn_cp: ConnCreate,
+}
+
+unsafe impl Send for Pending {}
+
+impl Pending {
+    /// Resolve the create parameters of a connection, and check the credentials against the
+    /// authentication mode. See `Connection::create()`.
+    fn new(context: &Context,
+           params: ConnectParams,
+           common_create_params: Option<CommonCreate>,
+           conn_create_params: Option<ConnCreate>)
+           -> Result<Pending> {
+        let comm_cp = if let Some(common_create_params) = common_create_params {
+            common_create_params
+        } else {
+            params.init_common_create_params(context)?
+        };
+
+        let conn_cp = if let Some(conn_create_params) = conn_create_params {
+            conn_create_params
+        } else {
+            context.init_conn_create_params()?
+        };
+
+        let pooled = !conn_cp.inner().pool.is_null();
+        params.validate(conn_cp.get_external_auth() != 0, !pooled)?;
+
+        Ok(Pending {
+               context: context.inner(),
+               params: params,
+               comm_cp: comm_cp,
+               conn_cp: conn_cp,
+           })
+    }
+
+    /// Create the connection with `dpiConn_create`. The error information is read on the calling
+    /// thread, so a failure reports why the connection was refused.
+    fn create(self) -> Result<Created> {
+        let mut inner: *mut ODPIConn = ptr::null_mut();
+        let start = Instant::now();
+        let res = {
+            let username_s = ODPIStr::from(self.params.get_username());
+            let password_s = ODPIStr::from(self.params.get_password());
+            let connect_string_s = ODPIStr::from(self.params.get_connect_string());
+
+            unsafe {
+                externs::dpiConn_create(self.context,
+                                        username_s.ptr(),
+                                        username_s.len(),
+                                        password_s.ptr(),
+                                        password_s.len(),
+                                        connect_string_s.ptr(),
+                                        connect_string_s.len(),
+                                        &self.comm_cp.inner(),
+                                        &mut self.conn_cp.inner(),
+                                        &mut inner)
+            }
+        };
+        let connect_time = start.elapsed();
+        let mut error_info: ODPIErrorInfo = Default::default();
+        unsafe {
+            externs::dpiContext_getError(self.context, &mut error_info);
+        }
+        let info: error::Info = error_info.into();
+
+        if res != DPI_SUCCESS {
+            let kind = if ExpiredPassword::is_expired_code(info.code()) {
+                let connect_string = if self.params.get_connect_string().is_empty() {
+                    None
+                } else {
+                    Some(self.params.get_connect_string())
+                };
+                let username = self.params.get_username();
+                let expired = ExpiredPassword::new(username, connect_string, info);
+                ErrorKind::PasswordExpired(expired)
+            } else {
+                ErrorKind::Connection("dpiConn_create".to_string())
+            };
+            return Err(kind.into());
         }
+
+        // A password in its grace period does not fail the login, but leaves ORA-28002 behind.
+        let warning = if ExpiredPassword::is_grace_code(info.code()) {
+            Some(info)
+        } else {
+            None
+        };
+        Ok(Created {
+               inner: inner,
+               pooled: !self.conn_cp.inner().pool.is_null(),
+               connect_time: connect_time,
+               warning: warning,
+               pending: self,
+           })
     }
 }
+
+/// A connection created by `Pending::create()`, not yet wrapped in a `Connection`.
+struct Created {
+    /// The ODPI-C connection.
+    inner: *mut ODPIConn,
+    /// Was the connection acquired from a session pool?
+    pooled: bool,
+    /// The time taken by `dpiConn_create`.
+    connect_time: Duration,
+    /// The grace period warning left by the login, if any.
+    warning: Option<error::Info>,
+    /// The parameters the connection was created with.
+    pending: Pending,
+}
+
+unsafe impl Send for Created {}
@@ -0,0 +1,53 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The session formats used when datetime values are converted to and from strings, e.g. when a
+//! DATE column is fetched as `Bytes`. They default to the NLS settings of the client and server,
+//! so tools that read datetimes as text set them with `Connection::set_date_format()` to produce
+//! the same output against any database.
+use sql;
+use std::fmt;
+
+/// A datetime type whose string format is set per session.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DateFormat {
+    /// DATE values, formatted with `NLS_DATE_FORMAT`.
+    Date,
+    /// TIMESTAMP and TIMESTAMP WITH LOCAL TIME ZONE values, formatted with
+    /// `NLS_TIMESTAMP_FORMAT`.
+    Timestamp,
+    /// TIMESTAMP WITH TIME ZONE values, formatted with `NLS_TIMESTAMP_TZ_FORMAT`.
+    TimestampTz,
+}
+
+impl DateFormat {
+    /// Returns the name of the NLS session parameter holding the format.
+    pub fn parameter(&self) -> &'static str {
+        match *self {
+            DateFormat::Date => "NLS_DATE_FORMAT",
+            DateFormat::Timestamp => "NLS_TIMESTAMP_FORMAT",
+            DateFormat::TimestampTz => "NLS_TIMESTAMP_TZ_FORMAT",
+        }
+    }
+
+    /// Returns the ALTER SESSION statement setting the format. The format is quoted with
+    /// `sql::quote_literal()`, and its validity is checked by the server.
+    ///
+    /// * `format` - the datetime format model, e.g. `YYYY-MM-DD HH24:MI:SS`.
+    pub fn alter_session_sql(&self, format: &str) -> String {
+        format!("alter session set {} = {}",
+                self.parameter(),
+                sql::quote_literal(format))
+    }
+}
+
+impl fmt::Display for DateFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.parameter())
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Splitting of SQL*Plus-like scripts into statements, used by `Connection::run_script()`.
+//!
+//! SQL statements end with a `;` or with a line holding only a `/`. PL/SQL blocks (anonymous
+//! blocks and `CREATE` statements for functions, procedures, packages, triggers, types and
+//! libraries) contain `;` themselves, so they only end with a `/` line. Terminators inside string
+//! literals (including `q'[...]'` literals), quoted identifiers and comments are ignored. Comments
+//! before a statement are dropped. SQL*Plus commands such as `SET` or `PROMPT` are not recognized
+//! and are sent to the database like any other statement.
+use error::Result;
+
+/// A statement of a script, as split by `split_script()`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScriptStatement {
+    /// The line of the script the statement starts on, starting from 1.
+    pub line: usize,
+    /// The text of the statement, without the terminating `;` or `/`. PL/SQL blocks keep the `;`
+    /// after their final `END`.
+    pub sql: String,
+    /// Is the statement a PL/SQL block?
+    pub plsql: bool,
+}
+
+/// The outcome of running one statement of a script with `Connection::run_script()`.
+#[derive(Debug)]
+pub struct ScriptResult {
+    /// The statement that was run.
+    pub statement: ScriptStatement,
+    /// The number of rows affected by a DML statement (0 for other statements), or the error
+    /// returned when the statement was prepared or executed.
+    pub result: Result<u64>,
+}
+
+/// The object types whose `CREATE` statements are PL/SQL blocks.
+const PLSQL_OBJECTS: &[&str] = &["FUNCTION", "LIBRARY", "PACKAGE", "PROCEDURE", "TRIGGER", "TYPE"];
+
+/// The keywords that may come between `CREATE` and the object type.
+const CREATE_MODIFIERS: &[&str] = &["OR", "REPLACE", "EDITIONABLE", "NONEDITIONABLE"];
+
+/// Splits a script into statements. Statements that are empty or hold only comments are skipped.
+///
+/// * `script` - the text of the script.
+pub fn split_script(script: &str) -> Vec<ScriptStatement> {
+    let bytes = script.as_bytes();
+    let mut statements = Vec::new();
+    let mut start: Option<(usize, usize, bool)> = None;
+    let mut line = 1;
+    let mut idx = 0;
+
+    {
+        let mut flush = |start: &mut Option<(usize, usize, bool)>, end: usize| {
+            if let Some((from, start_line, plsql)) = start.take() {
+                statements.push(ScriptStatement {
+                                    line: start_line,
+                                    sql: script[from..end].trim_right().to_string(),
+                                    plsql: plsql,
+                                });
+            }
+        };
+
+        while idx < bytes.len() {
+            let b = bytes[idx];
+            let next = bytes.get(idx + 1).cloned();
+
+            if b == b'/' && is_slash_line(bytes, idx) {
+                flush(&mut start, line_start(bytes, idx));
+                idx = skip_to(bytes, idx, b"\n");
+                continue;
+            }
+
+            match b {
+                b'\n' => {
+                    line += 1;
+                    idx += 1;
+                }
+                b'-' if next == Some(b'-') => idx = skip_to(bytes, idx, b"\n"),
+                b'/' if next == Some(b'*') => {
+                    let end = skip_past(bytes, idx + 2, b"*/");
+                    line += count_lines(&bytes[idx..end]);
+                    idx = end;
+                }
+                b';' if start.map_or(true, |(_, _, plsql)| !plsql) => {
+                    flush(&mut start, idx);
+                    idx += 1;
+                }
+                _ if is_space(b) => idx += 1,
+                _ => {
+                    if start.is_none() {
+                        start = Some((idx, line, is_plsql(&script[idx..])));
+                    }
+
+                    let end = match b {
+                        b'\'' => skip_literal(bytes, idx),
+                        b'"' => skip_past(bytes, idx + 1, b"\""),
+                        _ => idx + 1,
+                    };
+                    line += count_lines(&bytes[idx..end]);
+                    idx = end;
+                }
+            }
+        }
+
+        flush(&mut start, bytes.len());
+    }
+
+    statements
+}
+
+/// Counts the newlines in the given bytes.
+fn count_lines(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|b| **b == b'\n').count()
+}
+
+/// Does the statement starting with the given text begin a PL/SQL block?
+fn is_plsql(text: &str) -> bool {
+    let mut words = text.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_uppercase());
+
+    match words.next() {
+        Some(ref first) if first == "BEGIN" || first == "DECLARE" => true,
+        Some(ref first) if first == "CREATE" => {
+            words
+                .find(|word| !CREATE_MODIFIERS.contains(&&word[..]))
+                .map_or(false, |word| PLSQL_OBJECTS.contains(&&word[..]))
+        }
+        _ => false,
+    }
+}
+
+/// Is the byte ASCII whitespace?
+fn is_space(b: u8) -> bool {
+    b < 0x80 && (b as char).is_whitespace()
+}
+
+/// Is the `/` at `idx` the only non-whitespace character on its line?
+fn is_slash_line(bytes: &[u8], idx: usize) -> bool {
+    let end = skip_to(bytes, idx, b"\n");
+    let mut rest = bytes[line_start(bytes, idx)..end].iter().filter(|b| !is_space(**b));
+    rest.next() == Some(&b'/') && rest.next().is_none()
+}
+
+/// Returns the index of the start of the line holding `idx`.
+fn line_start(bytes: &[u8], idx: usize) -> usize {
+    bytes[..idx].iter().rposition(|b| *b == b'\n').map_or(0, |pos| pos + 1)
+}
+
+/// Returns the index just past the string literal starting with the quote at `idx`. Handles the
+/// alternative quoting mechanism, `q'<delimiter>...<delimiter>'`, as well as doubled quotes.
+fn skip_literal(bytes: &[u8], idx: usize) -> usize {
+    let quoted = idx > 0 && (bytes[idx - 1] == b'q' || bytes[idx - 1] == b'Q') &&
+                 (idx < 2 || !(bytes[idx - 2] as char).is_alphanumeric() ||
+                  bytes[idx - 2] == b'n' || bytes[idx - 2] == b'N');
+
+    match bytes.get(idx + 1) {
+        Some(&open) if quoted => {
+            let close = match open {
+                b'[' => b']',
+                b'{' => b'}',
+                b'(' => b')',
+                b'<' => b'>',
+                other => other,
+            };
+            skip_past(bytes, idx + 2, &[close, b'\''])
+        }
+        _ => skip_past(bytes, idx + 1, b"'"),
+    }
+}
+
+/// Returns the index just past the first occurrence of the terminator at or after `from`, or the
+/// end of the text if there is none.
+fn skip_past(bytes: &[u8], from: usize, terminator: &[u8]) -> usize {
+    let mut idx = from;
+
+    while idx + terminator.len() <= bytes.len() {
+        if &bytes[idx..idx + terminator.len()] == terminator {
+            return idx + terminator.len();
+        }
+        idx += 1;
+    }
+    bytes.len()
+}
+
+/// Returns the index of the first occurrence of the terminator at or after `from`, or the end of
+/// the text if there is none.
+fn skip_to(bytes: &[u8], from: usize, terminator: &[u8]) -> usize {
+    let end = skip_past(bytes, from, terminator);
+
+    if bytes[from..end].ends_with(terminator) {
+        end - terminator.len()
+    } else {
+        end
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The identity of a database session, as returned by `Connection::current_session_id()` and
+//! taken by `Connection::kill_session()`. The SID alone is reused once a session ends, so a session
+//! is only identified by its SID and serial number together.
+use std::fmt;
+
+/// The SID and serial number of a session, as found in the `SID` and `SERIAL#` columns of
+/// `V$SESSION`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SessionId {
+    /// The session identifier.
+    pub sid: u32,
+    /// The serial number, which tells apart sessions that reused the same SID.
+    pub serial: u32,
+}
+
+/// Formats the session as `sid,serial`, the form used by `ALTER SYSTEM KILL SESSION`.
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.sid, self.serial)
+    }
+}
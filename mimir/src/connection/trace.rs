@@ -0,0 +1,29 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The end-to-end tracing attributes of a connection, applied together by calling
+//! `Connection::set_trace_attrs()`. The attributes are not sent to the database when they are set;
+//! they are piggybacked on the next round trip, so setting all of them at once costs no more than
+//! setting one.
+
+/// End-to-end tracing attributes. Attributes that are None are left unchanged when applied.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TraceAttrs {
+    /// The action attribute.
+    pub action: Option<String>,
+    /// The client identifier attribute.
+    pub client_id: Option<String>,
+    /// The client info attribute.
+    pub client_info: Option<String>,
+    /// The database operation attribute.
+    pub db_op: Option<String>,
+    /// The execution context id attribute.
+    pub ecid: Option<String>,
+    /// The module attribute.
+    pub module: Option<String>,
+}
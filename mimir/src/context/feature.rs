@@ -0,0 +1,64 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The features that depend on the release of the Oracle Client libraries ODPI-C loads at
+//! runtime. `Context::client_supports()` probes for them, and the calls that use them return
+//! `ErrorKind::Unsupported` on older clients instead of the error OCI raises.
+use common::version::Info;
+use std::fmt;
+
+/// A feature that needs a minimum release of the Oracle Client libraries.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Feature {
+    /// The row counts of each iteration of `Statement::execute_many()`, with
+    /// `DPI_MODE_EXEC_ARRAY_DML_ROWCOUNTS`, read with `Statement::get_row_counts()`.
+    ArrayDmlRowCounts,
+    /// The errors of the failed iterations of `Statement::execute_many()`, with
+    /// `DPI_MODE_EXEC_BATCH_ERRORS`, read with `Statement::get_batch_errors()`.
+    BatchErrors,
+    /// A timeout of each round trip enforced by the client. The timeouts implemented by breaking
+    /// the call, such as `Statement::execute_with_timeout()`, work with any client.
+    CallTimeout,
+    /// The result sets returned by PL/SQL with `DBMS_SQL.RETURN_RESULT`, read with
+    /// `Statement::get_implicit_result()`.
+    ImplicitResults,
+}
+
+impl Feature {
+    /// Returns the major and release version of the first client release supporting the feature.
+    pub fn min_client_version(&self) -> (i32, i32) {
+        match *self {
+            Feature::ArrayDmlRowCounts | Feature::BatchErrors | Feature::ImplicitResults => (12, 1),
+            Feature::CallTimeout => (18, 1),
+        }
+    }
+
+    /// Returns the first client release supporting the feature, e.g. "12.1".
+    pub fn needs(&self) -> String {
+        let (major, release_num) = self.min_client_version();
+        format!("{}.{}", major, release_num)
+    }
+
+    /// Returns true if the given client release supports the feature.
+    ///
+    /// * `client_version` - the version of the Oracle Client, from `Context::get_client_version()`.
+    pub fn is_supported_by(&self, client_version: &Info) -> bool {
+        (client_version.major(), client_version.release_num()) >= self.min_client_version()
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Feature::ArrayDmlRowCounts => write!(f, "array DML row counts"),
+            Feature::BatchErrors => write!(f, "batch errors"),
+            Feature::CallTimeout => write!(f, "call timeout"),
+            Feature::ImplicitResults => write!(f, "implicit results"),
+        }
+    }
+}
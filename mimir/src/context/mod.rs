@@ -12,19 +12,28 @@
 //! the version used by the application.
 use common::{error, version};
 use error::{ErrorKind, Result};
+use logger::Logger;
 use odpi::constants::{DPI_FAILURE, DPI_MAJOR_VERSION, DPI_MINOR_VERSION};
-use odpi::externs;
+use odpi::{externs, flags};
 use odpi::opaque::ODPIContext;
 use odpi::structs::{ODPICommonCreateParams, ODPIConnCreateParams, ODPIErrorInfo,
                     ODPIPoolCreateParams, ODPISubscrCreateParams, ODPIVersionInfo};
-use slog::Logger;
+use std::fmt;
 use std::ptr;
+#[cfg(feature = "global-context")]
+use std::sync::Mutex;
+use std::sync::{Arc, Weak};
 use util::ODPIStr;
 
+pub mod feature;
 pub mod params;
 
+use self::feature::Feature;
 use self::params::{CommonCreate, ConnCreate, PoolCreate, SubscrCreate};
 
+/// The driver name reported to the database when connections and pools are created.
+const DRIVER_NAME: &str = "Rust Oracle: 0.1.0";
+
 /// This structure represents the context in which all activity in the library takes place.
 pub struct Context {
     /// A pointer the the ODPI-C dpiContext struct.
@@ -33,6 +42,9 @@ pub struct Context {
     stdout: Option<Logger>,
     /// Optoinal stderr logger.
     stderr: Option<Logger>,
+    /// Shared with the handles derived from the context, so they can tell when it is dropped. None
+    /// for the process-wide context returned by `global()`, which is never dropped.
+    alive: Option<Arc<()>>,
 }
 
 impl Context {
@@ -45,11 +57,15 @@ impl Context {
                                             DPI_MINOR_VERSION,
                                             &mut ctxt,
                                             &mut err),
-                 Ok(Context {
-                        context: ctxt,
-                        stdout: None,
-                        stderr: None,
-                    }),
+                 {
+                     track_ref!(context_created, ctxt);
+                     Ok(Context {
+                            context: ctxt,
+                            stdout: None,
+                            stderr: None,
+                            alive: Some(Arc::new(())),
+                        })
+                 },
                  ErrorKind::Context("dpiContext_create".to_string()))
     }
 
@@ -59,6 +75,56 @@ impl Context {
         self.context
     }
 
+    /// Returns the process-wide context, creating it the first time it is asked for. ODPI-C
+    /// recommends a single context per process, which this shares between all threads. It is
+    /// never destroyed, so handles derived from it do not track its lifetime, and connections and
+    /// pools created with it use `DPI_MODE_CREATE_THREADED` by default. The functions creating
+    /// connections and pools use it when no context is given. Returns `ErrorKind::Context` if the
+    /// context could not be created, in which case the next call tries to create it again.
+    #[cfg(feature = "global-context")]
+    pub fn global() -> Result<&'static Context> {
+        let mut global = GLOBAL_CONTEXT.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(global) = *global {
+            return Ok(&global.0);
+        }
+
+        let mut context = Context::create()?;
+        context.alive = None;
+        let created: &'static GlobalContext = Box::leak(Box::new(GlobalContext(context)));
+        *global = Some(created);
+        Ok(&created.0)
+    }
+
+    /// Get a `ContextRef` for a handle derived from this context.
+    #[doc(hidden)]
+    pub fn handle(&self) -> ContextRef {
+        ContextRef {
+            context: self.context,
+            alive: self.alive.as_ref().map(Arc::downgrade),
+        }
+    }
+
+    /// Returns true if this is the process-wide context returned by `global()`.
+    pub fn is_global(&self) -> bool {
+        self.alive.is_none()
+    }
+
+    /// Returns a human readable description of the driver and the Oracle Client that is being
+    /// used, including the port specific release and update (patch) levels of the client, e.g.
+    /// "Rust Oracle: 0.1.0 (Oracle Client 12.2.0.1.0)".
+    pub fn client_info_string(&self) -> Result<String> {
+        let version_info = self.get_client_version()?;
+        Ok(format!("{} (Oracle Client {})", DRIVER_NAME, version_info.version()))
+    }
+
+    /// Returns true if the Oracle Client that is being used supports the given feature. The calls
+    /// using a feature the client does not support return `ErrorKind::Unsupported`.
+    ///
+    /// * `feature` - the feature to probe for.
+    pub fn client_supports(&self, feature: Feature) -> Result<bool> {
+        Ok(feature.is_supported_by(&self.get_client_version()?))
+    }
+
     /// Return information about the version of the Oracle Client that is being used.
     pub fn get_client_version(&self) -> Result<version::Info> {
         let mut version_info: ODPIVersionInfo = Default::default();
@@ -79,16 +145,19 @@ impl Context {
         }
     }
 
-    /// Initializes the `CommonCreate` structure to default values.
+    /// Initializes the `CommonCreate` structure to default values. For the global context, the
+    /// create mode includes `DPI_MODE_CREATE_THREADED`.
     pub fn init_common_create_params(&self) -> Result<CommonCreate> {
         let mut ccp: ODPICommonCreateParams = Default::default();
 
         try_dpi!(externs::dpiContext_initCommonCreateParams(self.context, &mut ccp),
                  {
-                     let driver_name = "Rust Oracle: 0.1.0";
-                     let driver_name_s = ODPIStr::from(driver_name);
+                     let driver_name_s = ODPIStr::from(DRIVER_NAME);
                      ccp.driver_name = driver_name_s.ptr();
                      ccp.driver_name_length = driver_name_s.len();
+                     if self.is_global() {
+                         ccp.create_mode |= flags::DPI_MODE_CREATE_THREADED;
+                     }
                      Ok(CommonCreate::new(ccp))
                  },
                  ErrorKind::Context("dpiContext_initCommonCreateParams".to_string()))
@@ -120,12 +189,135 @@ impl Context {
     }
 }
 
+/// The process-wide context. A `Context` is not `Sync` because of its loggers and the lifetime
+/// tracking of its handles; the global context has no loggers and is never dropped, so handles
+/// derived from it share only the ODPI-C pointer, which is safe to use from any thread.
+#[cfg(feature = "global-context")]
+struct GlobalContext(Context);
+
+#[cfg(feature = "global-context")]
+unsafe impl Send for GlobalContext {}
+#[cfg(feature = "global-context")]
+unsafe impl Sync for GlobalContext {}
+
+#[cfg(feature = "global-context")]
+lazy_static! {
+    /// The process-wide context, once it has been created.
+    static ref GLOBAL_CONTEXT: Mutex<Option<&'static GlobalContext>> = Mutex::new(None);
+}
+
+/// Returns the given context or, if none is given, the global context. Without the
+/// `global-context` feature, a context must be given.
+///
+/// * `context` - the context given by the caller, if any.
+pub fn resolve(context: Option<&Context>) -> Result<&Context> {
+    match context {
+        Some(context) => Ok(context),
+        #[cfg(feature = "global-context")]
+        None => Context::global(),
+        #[cfg(not(feature = "global-context"))]
+        None => {
+            let err = "no context given; the global context needs the global-context feature";
+            Err(ErrorKind::Context(err.to_string()).into())
+        }
+    }
+}
+
+/// A reference to the `Context` a handle was derived from. The context pointer is only handed out
+/// while the `Context` is alive, so a handle that outlives its context gets an error rather than a
+/// dangling pointer. The lifetime is tracked atomically, so a reference moved to another thread
+/// with a threaded handle can still tell, but the `Context` must outlive any call made through it.
+#[doc(hidden)]
+#[derive(Clone, Debug)]
+pub struct ContextRef {
+    /// A pointer to the ODPI-C dpiContext struct.
+    context: *mut ODPIContext,
+    /// Tracks the lifetime of the `Context`. None if the handle was not derived from a `Context`,
+    /// in which case the pointer is NULL, or if it was derived from the global context, which is
+    /// never dropped.
+    alive: Option<Weak<()>>,
+}
+
+impl ContextRef {
+    /// A `ContextRef` for a handle that was not derived from a `Context`.
+    pub fn none() -> ContextRef {
+        ContextRef {
+            context: ptr::null_mut(),
+            alive: None,
+        }
+    }
+
+    /// Returns an error if the context has been dropped.
+    pub fn check(&self) -> Result<()> {
+        if self.is_alive() {
+            Ok(())
+        } else {
+            Err(ErrorKind::ContextDropped.into())
+        }
+    }
+
+    /// Get the pointer to the inner ODPI struct, or NULL if there is no context or it has been
+    /// dropped.
+    pub fn inner(&self) -> *mut ODPIContext {
+        match self.alive {
+            Some(ref alive) if alive.upgrade().is_none() => ptr::null_mut(),
+            _ => self.context,
+        }
+    }
+
+    /// Returns false if the handle was derived from a `Context` that has since been dropped.
+    pub fn is_alive(&self) -> bool {
+        self.alive.as_ref().map_or(true, |alive| alive.upgrade().is_some())
+    }
+
+    /// Returns `ErrorKind::Unsupported` if the Oracle Client does not support the given feature.
+    /// Nothing is checked if there is no context, or the client version cannot be read, leaving
+    /// ODPI-C to report the error.
+    pub fn require(&self, feature: Feature) -> Result<()> {
+        let context = self.inner();
+        if context.is_null() {
+            return Ok(());
+        }
+
+        let mut version_info: ODPIVersionInfo = Default::default();
+        let res = unsafe { externs::dpiContext_getClientVersion(context, &mut version_info) };
+        if res == DPI_FAILURE || feature.is_supported_by(&version_info.into()) {
+            Ok(())
+        } else {
+            Err(ErrorKind::Unsupported(feature.to_string(), feature.needs()).into())
+        }
+    }
+}
+
+/// The `Debug` output includes the version of the Oracle Client library in use.
+impl fmt::Debug for Context {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let client_version = self.get_client_version()
+            .ok()
+            .map(|ver_info| ver_info.version().to_string());
+
+        f.debug_struct("Context")
+            .field("client_version", &client_version)
+            .finish()
+    }
+}
+
 impl Drop for Context {
     fn drop(&mut self) {
+        let outliving = self.alive.as_ref().map_or(0, Arc::weak_count);
+        if outliving > 0 {
+            try_warn!(self.stderr,
+                      "{} handle(s) outlive the context and can no longer use it",
+                      outliving);
+        }
+
         if unsafe { externs::dpiContext_destroy(self.context) } == DPI_FAILURE {
-            try_error!(self.stderr, "Failed to destroy context");
+            let info = self.get_error();
+            try_error!(self.stderr, "Failed to destroy context: {}", info);
         } else {
             try_info!(self.stdout, "Successfully destroyed context");
         }
+
+        track_ref!(context_dropped, self.context);
     }
 }
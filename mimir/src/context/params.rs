@@ -13,6 +13,7 @@ use odpi::structs::{ODPIAppContext, ODPICommonCreateParams, ODPIConnCreateParams
                     ODPIPoolCreateParams, ODPISubscrCreateParams};
 use pool::Pool;
 use std::ffi::CStr;
+use std::time::Duration;
 use util::ODPIStr;
 
 /// This structure is used for passing application context to the database during the process of
@@ -108,8 +109,10 @@ impl CommonCreate {
     }
 
     /// Set the `create_mode` value.
-    pub fn set_create_mode(&mut self, create_mode: flags::ODPICreateMode) -> &mut CommonCreate {
-        self.ccp.create_mode = create_mode;
+    pub fn set_create_mode<M>(&mut self, create_mode: M) -> &mut CommonCreate
+        where M: Into<flags::ODPICreateMode>
+    {
+        self.ccp.create_mode = create_mode.into();
         self
     }
 
@@ -193,13 +196,18 @@ impl CommonCreate {
 pub struct ConnCreate {
     /// The ODPI-C dpiConnCreateParams struct.
     conn: ODPIConnCreateParams,
+    /// The maximum time `Pool::acquire_connection()` waits for a free session.
+    wait_timeout: Option<Duration>,
 }
 
 impl ConnCreate {
     /// Create a new `ConnCreate` struct.
     #[doc(hidden)]
     pub fn new(conn: ODPIConnCreateParams) -> ConnCreate {
-        ConnCreate { conn: conn }
+        ConnCreate {
+            conn: conn,
+            wait_timeout: None,
+        }
     }
 
     /// Get the inner FFI struct.
@@ -218,8 +226,8 @@ impl ConnCreate {
     }
 
     /// Set the `auth_mode` value.
-    pub fn set_auth_mode(&mut self, auth_mode: flags::ODPIAuthMode) -> &mut ConnCreate {
-        self.conn.auth_mode = auth_mode;
+    pub fn set_auth_mode<M: Into<flags::ODPIAuthMode>>(&mut self, auth_mode: M) -> &mut ConnCreate {
+        self.conn.auth_mode = auth_mode.into();
         self
     }
 
@@ -404,6 +412,22 @@ impl ConnCreate {
         self
     }
 
+    /// Get the `wait_timeout` value.
+    ///
+    /// Specifies the maximum time `Pool::acquire_connection()` waits for a free session when the
+    /// pool is exhausted and its get mode is `Wait`, in place of the wait timeout of the pool. This
+    /// value is not part of the ODPI-C structure and only applies to that call. The default value
+    /// is None, which uses the wait timeout of the pool.
+    pub fn get_wait_timeout(&self) -> Option<Duration> {
+        self.wait_timeout
+    }
+
+    /// Set the `wait_timeout` value.
+    pub fn set_wait_timeout(&mut self, wait_timeout: Option<Duration>) -> &mut ConnCreate {
+        self.wait_timeout = wait_timeout;
+        self
+    }
+
     /// Get the `out_tag` value.
     ///
     /// Specifies the tag of the connection that was acquired from a session pool, or NULL if the
@@ -646,8 +670,8 @@ impl SubscrCreate {
     }
 
     /// Set the `qos` value.
-    pub fn set_qos(&mut self, qos: flags::ODPISubscrQOS) -> &mut SubscrCreate {
-        self.subscr.qos = qos;
+    pub fn set_qos<Q: Into<flags::ODPISubscrQOS>>(&mut self, qos: Q) -> &mut SubscrCreate {
+        self.subscr.qos = qos.into();
         self
     }
 
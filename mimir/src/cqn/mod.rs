@@ -0,0 +1,127 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Continuous Query Notification through a channel. A `Listener` owns a subscription whose
+//! notifications are decoded into `ChangeEvent` values and sent on a `std::sync::mpsc` channel,
+//! using `Subscription::into_receiver()`, so the changes are read with an ordinary `Receiver`
+//! rather than in an `extern "C"` callback.
+//!
+//! The connection must be created with `DPI_MODE_CREATE_EVENTS` set in its `CommonCreate`
+//! parameters.
+//!
+//! ```ignore
+//! let mut scp = ctxt.init_subscr_create_params()?;
+//! scp.set_qos(flags::DPI_SUBSCR_QOS_QUERY | flags::DPI_SUBSCR_QOS_ROWIDS);
+//! let listener = Listener::new(&conn, scp)?;
+//! listener.register_query("select * from orders")?;
+//!
+//! for event in listener.receiver().iter() {
+//!     println!("{:?}", event);
+//! }
+//! ```
+//!
+//! A subscription created with a timeout, or with `DPI_SUBSCR_QOS_DEREG_NFY`, lapses. The listener
+//! then receives a `ChangeEvent::Deregistered`, and can call `Listener::renew()` to subscribe
+//! again.
+use connection::Connection;
+use context::params::SubscrCreate;
+use error::{ErrorKind, Result};
+use odpi::{enums, flags};
+use std::cell::RefCell;
+use std::sync::mpsc;
+use subscription::Receiver;
+
+pub use subscription::message::{Message as ChangeEvent, Notification, QueryChange, RowChange,
+                                TableChange};
+
+/// A subscription delivering its notifications as `ChangeEvent` values on a channel. Dropping the
+/// listener closes the subscription.
+pub struct Listener {
+    /// The id of the subscription.
+    id: u32,
+    /// The receiver of the notifications, which owns the subscription.
+    receiver: Receiver,
+    /// The queries registered on the subscription, to register them again in `renew()`.
+    queries: RefCell<Vec<String>>,
+}
+
+impl Listener {
+    /// Create a new `Listener`, subscribing to database change notifications on the given
+    /// connection. The namespace, protocol and callback of the parameters are overwritten; the
+    /// others, such as the quality of service and timeout, are used as given.
+    ///
+    /// * `conn` - the connection the subscription is created on.
+    /// * `subscr_create_params` - the parameters of the subscription, from
+    /// `Context::init_subscr_create_params()`.
+    pub fn new(conn: &Connection, mut subscr_create_params: SubscrCreate) -> Result<Listener> {
+        subscr_create_params.set_subscr_namespace(enums::ODPISubscrNamespace::DbChange);
+        subscr_create_params.set_protocol(enums::ODPISubscrProtocol::Callback);
+        subscr_create_params.set_callback(None);
+        let (id, subscription) = conn.new_subscription(subscr_create_params)?;
+
+        Ok(Listener {
+               id: id,
+               receiver: subscription.into_receiver()?,
+               queries: RefCell::new(Vec::new()),
+           })
+    }
+
+    /// Closes the subscription, so no more notifications are sent. Events already received can
+    /// still be read from the receiver until the listener is dropped.
+    pub fn close(&mut self) -> Result<()> {
+        self.receiver.close()
+    }
+
+    /// Get the `id` value.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns the receiver the change events are sent on.
+    pub fn receiver(&self) -> &mpsc::Receiver<ChangeEvent> {
+        &self.receiver
+    }
+
+    /// Registers a query on the subscription. Notifications are sent when its results change, or
+    /// when the tables it reads are changed, depending on the quality of service requested.
+    ///
+    /// * `sql` - the query to register.
+    pub fn register_query(&self, sql: &str) -> Result<()> {
+        self.register(sql)?;
+        self.queries.borrow_mut().push(sql.to_string());
+        Ok(())
+    }
+
+    /// Replaces the subscription with a new one, created on the given connection with the same
+    /// parameters, and registers the same queries on it. Call this on receiving a
+    /// `ChangeEvent::Deregistered`, so the listener keeps receiving changes after its subscription
+    /// has lapsed. ODPI-C 2.0 cannot extend a registration, so the new subscription has a new id.
+    /// Its events arrive on the same receiver, after any not yet read. See
+    /// `Subscription::renew()`.
+    ///
+    /// * `conn` - the connection the new subscription is created on.
+    pub fn renew(&mut self, conn: &Connection) -> Result<()> {
+        self.id = self.receiver.renew(conn)?;
+
+        for sql in self.queries.borrow().iter() {
+            self.register(sql)?;
+        }
+        Ok(())
+    }
+
+    /// Registers a query on the current subscription.
+    fn register(&self, sql: &str) -> Result<()> {
+        let subscription = self.receiver
+            .subscription()
+            .ok_or_else(|| ErrorKind::Subscription("listener is closed".to_string()))?;
+        let stmt = subscription.prepare_statement(sql)?;
+        let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT);
+        stmt.release()?;
+        res.map(|_| ())
+    }
+}
@@ -11,13 +11,15 @@
 //! (such as Go) do not have the ability to manipulate structures containing unions or the ability
 //! to process macros. For this reason, none of these functions perform any error checking. They are
 //! assumed to be replacements for direct manipulation of the various members of the structure.
-use chrono::{Datelike, DateTime, Duration, Timelike, TimeZone, Utc};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
 use odpi::opaque;
-use odpi::structs::{ODPIData, ODPIDataValueUnion};
+use odpi::structs::{ODPIData, ODPIDataValueUnion, ODPIIntervalYM};
+use std::fmt;
+use std::marker::PhantomData;
 use util::ODPIStr;
 
 /// This structure is used for holding Oracle year to month interval data information.
-#[derive(Default, Getters, Setters)]
+#[derive(Clone, Copy, Debug, Default, Eq, Getters, PartialEq, Setters)]
 pub struct YearsMonths {
     /// The years in an Oracle YEARS TO MONTHS interval.
     #[get = "pub"]
@@ -29,24 +31,72 @@ pub struct YearsMonths {
     months: i32,
 }
 
+impl YearsMonths {
+    /// Create a new `YearsMonths` interval. Both values should have the same sign.
+    ///
+    /// * `years` - the years in the interval.
+    /// * `months` - the months in the interval, between -11 and 11.
+    pub fn new(years: i32, months: i32) -> YearsMonths {
+        YearsMonths {
+            years: years,
+            months: months,
+        }
+    }
+
+    /// Create a new `YearsMonths` interval from a number of months, split into years and months
+    /// with the sign of the number.
+    ///
+    /// * `months` - the length of the interval, in months.
+    pub fn from_months(months: i32) -> YearsMonths {
+        YearsMonths::new(months / 12, months % 12)
+    }
+
+    /// Get the length of the interval, in months.
+    pub fn total_months(&self) -> i32 {
+        self.years * 12 + self.months
+    }
+}
+
+impl From<ODPIIntervalYM> for YearsMonths {
+    fn from(interval: ODPIIntervalYM) -> YearsMonths {
+        YearsMonths::new(interval.years, interval.months)
+    }
+}
+
+impl From<YearsMonths> for ODPIIntervalYM {
+    fn from(interval: YearsMonths) -> ODPIIntervalYM {
+        ODPIIntervalYM {
+            years: interval.years,
+            months: interval.months,
+        }
+    }
+}
+
 /// This structure is used for passing data to and from the database for variables and for
-/// manipulating object attributes and collection values.
-#[derive(Debug)]
-pub struct Data {
+/// manipulating object attributes and collection values. A `Data` either owns its value, or
+/// refers to a value held by a statement or variable, and cannot outlive it.
+pub struct Data<'a> {
     /// The ODPI-C data pointer.
     inner: *mut ODPIData,
+    /// The storage `inner` points to, when the data was created by `new()` rather than referring
+    /// to data owned by ODPI-C.
+    owned: Option<Box<ODPIData>>,
+    /// Ties a borrowed value to the statement or variable that holds it.
+    marker: PhantomData<&'a ODPIData>,
 }
 
-impl Data {
-    /// Create a new `Data` struct;
+impl Data<'static> {
+    /// Create a new `Data` struct that owns its value.
     #[doc(hidden)]
-    pub fn new(is_null: bool, val: ODPIDataValueUnion) -> Data {
-        let mut odpi_data = ODPIData {
-            is_null: if is_null { 1 } else { 0 },
-            value: val,
-        };
-        Data { inner: &mut odpi_data }
+    pub fn new(is_null: bool, val: ODPIDataValueUnion) -> Data<'static> {
+        Data::from(ODPIData {
+                       is_null: if is_null { 1 } else { 0 },
+                       value: val,
+                   })
     }
+}
+
+impl<'a> Data<'a> {
 
     /// Get the `inner` value.
     #[doc(hidden)]
@@ -54,6 +104,11 @@ impl Data {
         self.inner
     }
 
+    /// Returns true if the value is null.
+    pub fn is_null(&self) -> bool {
+        unsafe { (*self.inner).is_null == 1 }
+    }
+
     /// Get the value as a boolean when the native type is DPI_NATIVE_TYPE_BOOLEAN.
     pub fn get_boolean(&self) -> bool {
         unsafe { (*self.inner).value.as_boolean == 1 }
@@ -77,27 +132,12 @@ impl Data {
     /// Get the value as a `Duration` when the native type is DPI_NATIVE_TYPE_INTERVAL_DS.
     pub fn get_duration(&self) -> Duration {
         let odpi_int_ds = unsafe { (*self.inner).value.as_interval_ds };
-        let mut dur = Duration::days(odpi_int_ds.days as i64);
-        dur = dur + Duration::hours(odpi_int_ds.hours as i64);
-        dur = dur + Duration::minutes(odpi_int_ds.minutes as i64);
-        dur = dur + Duration::seconds(odpi_int_ds.seconds as i64);
-        dur = dur + Duration::nanoseconds(odpi_int_ds.fseconds as i64);
-        dur
+        odpi_int_ds.into()
     }
 
     /// Sets the value of the data when the native type is DPI_NATIVE_TYPE_INTERVAL_DS.
-    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
     pub fn set_duration(&self, val: Duration) {
-        let mut odpi_int_ds = unsafe { (*self.inner).value.as_interval_ds };
-        odpi_int_ds.days = val.num_days() as i32;
-        odpi_int_ds.hours = val.num_hours() as i32;
-        odpi_int_ds.minutes = val.num_minutes() as i32;
-        odpi_int_ds.seconds = val.num_seconds() as i32;
-        odpi_int_ds.fseconds = if let Some(ns) = val.num_nanoseconds() {
-            ns as i32
-        } else {
-            0
-        };
+        unsafe { (*self.inner).value.as_interval_ds = val.into() }
     }
 
     /// Get the value as a `f32` when the native type is DPI_NATIVE_TYPE_FLOAT.
@@ -160,11 +200,16 @@ impl Data {
     }
 
     /// Sets the value of the data when the native type is DPI_NATIVE_TYPE_BYTES.
+    ///
+    /// The data refers to the given string rather than copying it, so the string must outlive
+    /// any use of the data.
     pub fn set_string(&self, val: &str) {
         let val_s = ODPIStr::from(val);
-        let mut bytes = unsafe { (*self.inner).value.as_bytes };
-        bytes.ptr = val_s.ptr() as *mut i8;
-        bytes.length = val_s.len();
+        unsafe {
+            let bytes = &mut (*self.inner).value.as_bytes;
+            bytes.ptr = val_s.ptr() as *mut i8;
+            bytes.length = val_s.len();
+        }
     }
 
     /// Get the value as a `u64` when the native type is DPI_NATIVE_TYPE_UINT64.
@@ -177,49 +222,89 @@ impl Data {
         unsafe { (*self.inner).value.as_uint_64 = val }
     }
 
-    /// Get the value as a `Utc` when the native type is DPI_NATIVE_TYPE_TIMESTAMP.
+    /// Get the value as a `DateTime<FixedOffset>` when the native type is
+    /// DPI_NATIVE_TYPE_TIMESTAMP. The time zone offset is preserved, which makes this the right
+    /// accessor for TIMESTAMP WITH TIME ZONE data.
+    pub fn get_datetime(&self) -> DateTime<FixedOffset> {
+        let odpi_ts = unsafe { (*self.inner).value.as_timestamp };
+        odpi_ts.into()
+    }
+
+    /// Get the value as a `Utc` when the native type is DPI_NATIVE_TYPE_TIMESTAMP. The time zone
+    /// offset of TIMESTAMP WITH TIME ZONE data is applied, and the fractional seconds are kept to
+    /// the nanosecond.
     pub fn get_utc(&self) -> DateTime<Utc> {
         let odpi_ts = unsafe { (*self.inner).value.as_timestamp };
-        let y = odpi_ts.year as i32;
-        let m = odpi_ts.month as u32;
-        let d = odpi_ts.day as u32;
-        let h = odpi_ts.hour as u32;
-        let mi = odpi_ts.minute as u32;
-        let s = odpi_ts.second as u32;
-        Utc.ymd(y, m, d).and_hms_nano(h, mi, s, odpi_ts.fsecond)
+        odpi_ts.into()
     }
 
-    /// Sets the value of the data when the native type is DPI_NATIVE_TYPE_TIMESTAMP.
-    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    /// Sets the value of the data when the native type is DPI_NATIVE_TYPE_TIMESTAMP. The
+    /// fractional seconds are kept to the nanosecond, except that a leap second is stored as the
+    /// last nanosecond of its minute.
     pub fn set_utc(&self, val: DateTime<Utc>) {
-        let mut odpi_ts = unsafe { (*self.inner).value.as_timestamp };
-        odpi_ts.year = val.year() as i16;
-        odpi_ts.month = val.month() as u8;
-        odpi_ts.day = val.day() as u8;
-        odpi_ts.hour = val.hour() as u8;
-        odpi_ts.minute = val.minute() as u8;
-        odpi_ts.second = val.second() as u8;
+        unsafe { (*self.inner).value.as_timestamp = val.into() }
     }
 
     /// Get the value as a `YearsMonths` when the native type is DPI_NATIVE_TYPE_INTERVAL_YM.
     pub fn get_years_months(&self) -> YearsMonths {
         let odpi_int_ym = unsafe { (*self.inner).value.as_interval_ym };
-        let mut ym: YearsMonths = Default::default();
-        ym.set_years(odpi_int_ym.years);
-        ym.set_months(odpi_int_ym.months);
-        ym
+        odpi_int_ym.into()
     }
 
     /// Sets the value of the data when the native type is DPI_NATIVE_TYPE_INTERVAL_YM.
     pub fn set_years_months(&self, val: YearsMonths) {
-        let mut odpi_int_ym = unsafe { (*self.inner).value.as_interval_ym };
-        odpi_int_ym.years = *val.years();
-        odpi_int_ym.months = *val.months();
+        unsafe { (*self.inner).value.as_interval_ym = val.into() }
+    }
+}
+
+/// An owned, non-null value of zero, e.g. to set a value into before binding it.
+impl Default for Data<'static> {
+    fn default() -> Data<'static> {
+        Data::new(false, ODPIDataValueUnion { as_int_64: 0 })
+    }
+}
+
+impl<'a> fmt::Debug for Data<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Data")
+            .field("inner", &self.inner)
+            .field("owned", &self.owned.is_some())
+            .finish()
+    }
+}
+
+/// Takes ownership of a value returned by ODPI-C in a structure rather than by pointer.
+impl From<ODPIData> for Data<'static> {
+    fn from(data: ODPIData) -> Data<'static> {
+        let mut owned = Box::new(data);
+        Data {
+            inner: &mut *owned,
+            owned: Some(owned),
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Create a `Data` holding a copy of a value owned by ODPI-C, so it can be read without allowing
+/// writes to the original. Any buffer the value points to, such as the bytes of a string, is still
+/// borrowed for the lifetime `'a`.
+pub fn copied<'a>(data: &'a ODPIData) -> Data<'a> {
+    let mut owned = Box::new(*data);
+    Data {
+        inner: &mut *owned,
+        owned: Some(owned),
+        marker: PhantomData,
     }
 }
 
-impl From<*mut ODPIData> for Data {
-    fn from(inner: *mut ODPIData) -> Data {
-        Data { inner: inner }
+/// Create a `Data` that refers to a value owned by ODPI-C. See `raw::data_from_ptr()`.
+///
+/// The caller must ensure that `inner` points to a valid `ODPIData` structure for the lifetime
+/// `'a`.
+pub unsafe fn from_ptr<'a>(inner: *mut ODPIData) -> Data<'a> {
+    Data {
+        inner: inner,
+        owned: None,
+        marker: PhantomData,
     }
 }
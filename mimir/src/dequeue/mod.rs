@@ -11,17 +11,57 @@
 //! are destroyed by releasing the last reference by calling the function
 //! `dequeue::Options::release()`.
 use error::{ErrorKind, Result};
+use odpi::constants::{DPI_DEQ_WAIT_FOREVER, DPI_DEQ_WAIT_NO_WAIT};
 use odpi::{enums, externs};
 use odpi::opaque::ODPIDeqOptions;
-use std::ptr;
+use std::cell::Cell;
+use std::{cmp, ptr};
+use std::time::Duration;
 use util::ODPIStr;
 
+/// How long a dequeue waits for a message matching the search criteria.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Wait {
+    /// Return immediately if no message is available.
+    NoWait,
+    /// Wait until a message is available.
+    Forever,
+    /// Wait for at most the given time, rounded down to whole seconds. A wait of less than a
+    /// second is the same as `NoWait`, and is read back as such.
+    Seconds(Duration),
+}
+
+impl Wait {
+    /// The number of seconds passed to ODPI-C, or one of the `DPI_DEQ_WAIT_*` constants.
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    fn as_secs(&self) -> u32 {
+        match *self {
+            Wait::NoWait => DPI_DEQ_WAIT_NO_WAIT,
+            Wait::Forever => DPI_DEQ_WAIT_FOREVER,
+            Wait::Seconds(wait) => {
+                cmp::min(wait.as_secs(), u64::from(DPI_DEQ_WAIT_FOREVER - 1)) as u32
+            }
+        }
+    }
+}
+
+impl From<u32> for Wait {
+    fn from(secs: u32) -> Wait {
+        match secs {
+            DPI_DEQ_WAIT_NO_WAIT => Wait::NoWait,
+            DPI_DEQ_WAIT_FOREVER => Wait::Forever,
+            secs => Wait::Seconds(Duration::from_secs(u64::from(secs))),
+        }
+    }
+}
+
 /// Dequeue option handles are used to represent the options specified when dequeuing messages using
 /// advanced queueing.
-#[derive(Clone)]
 pub struct Options {
     /// The ODPI-C DeqOptions pointer.
     inner: *mut ODPIDeqOptions,
+    /// The delivery mode last set with `set_delivery_mode()`. ODPI-C cannot read it back.
+    delivery_mode: Cell<enums::ODPIMessageDeliveryMode>,
 }
 
 impl Options {
@@ -36,7 +76,10 @@ impl Options {
     /// the handle was created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiDeqOptions_addRef(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(added, "DeqOptions", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::DeqOptions("dpiDeqOptions_addRef".to_string()))
     }
 
@@ -97,6 +140,12 @@ impl Options {
                  ErrorKind::DeqOptions("dpiDeqOptions_getCorrelation".to_string()))
     }
 
+    /// Returns the message delivery mode that is used when dequeuing messages, as last set with
+    /// `dequeue::Options::set_delivery_mode()` on this handle. The default is `Persistent`.
+    pub fn get_delivery_mode(&self) -> enums::ODPIMessageDeliveryMode {
+        self.delivery_mode.get()
+    }
+
     /// Returns the mode that is to be used when dequeuing messages.
     pub fn get_mode(&self) -> Result<enums::ODPIDeqMode> {
         let mut deq_mod_ptr = enums::ODPIDeqMode::Remove;
@@ -163,13 +212,13 @@ impl Options {
                  ErrorKind::DeqOptions("dpiDeqOptions_getVisibility".to_string()))
     }
 
-    /// Returns the time to wait, in seconds, for a message matching the search criteria. See
-    /// function `dequeue::Options::set_wait()` for more information.
-    pub fn get_wait(&self) -> Result<u32> {
+    /// Returns the time to wait for a message matching the search criteria. See function
+    /// `dequeue::Options::set_wait()` for more information.
+    pub fn get_wait(&self) -> Result<Wait> {
         let mut wait = 0;
 
         try_dpi!(externs::dpiDeqOptions_getWait(self.inner, &mut wait),
-                 Ok(wait),
+                 Ok(wait.into()),
                  ErrorKind::DeqOptions("dpiDeqOptions_getWait".to_string()))
     }
 
@@ -178,7 +227,10 @@ impl Options {
     /// options is freed.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiDeqOptions_release(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(released, "DeqOptions", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::DeqOptions("dpiDeqOptions_release".to_string()))
     }
 
@@ -225,6 +277,18 @@ impl Options {
                  ErrorKind::DeqOptions("dpiDeqOptions_setCorrelation".to_string()))
     }
 
+    /// Sets the message delivery mode that is to be used when dequeuing messages.
+    ///
+    /// * `mode` - one of the values from the enumeration `ODPIMessageDeliveryMode`.
+    pub fn set_delivery_mode(&self, mode: enums::ODPIMessageDeliveryMode) -> Result<()> {
+        try_dpi!(externs::dpiDeqOptions_setDeliveryMode(self.inner, mode),
+                 {
+                     self.delivery_mode.set(mode);
+                     Ok(())
+                 },
+                 ErrorKind::DeqOptions("dpiDeqOptions_setDeliveryMode".to_string()))
+    }
+
     /// Sets the mode that is to be used when dequeuing messages.
     ///
     /// * `mode` - he mode that should be used. It should be one of the values from the enumeration
@@ -284,20 +348,168 @@ impl Options {
                  ErrorKind::DeqOptions("dpiDeqOptions_setVisibility".to_string()))
     }
 
-    /// Set the time to wait, in seconds, for a message matching the search criteria.
+    /// Set the time to wait for a message matching the search criteria.
     ///
-    /// * `wait` - the number of seconds to wait for a message matching the search criteria. Any
-    /// integer is valid but the predefined constants DPI_DEQ_WAIT_NO_WAIT and DPI_DEQ_WAIT_FOREVER
-    /// are provided as a convenience.
-    pub fn set_wait(&self, wait: u32) -> Result<()> {
-        try_dpi!(externs::dpiDeqOptions_setWait(self.inner, wait),
+    /// * `wait` - how long to wait for a message matching the search criteria.
+    pub fn set_wait(&self, wait: Wait) -> Result<()> {
+        try_dpi!(externs::dpiDeqOptions_setWait(self.inner, wait.as_secs()),
                  Ok(()),
                  ErrorKind::DeqOptions("dpiDeqOptions_setWait".to_string()))
     }
+
+    /// Adds a reference to the dequeue options and returns a new handle to it, which must be
+    /// released separately. Unlike `clone()`, which panics if the reference cannot be added, this
+    /// returns the error.
+    pub fn try_clone(&self) -> Result<Options> {
+        self.add_ref()?;
+        Ok(Options {
+               inner: self.inner,
+               delivery_mode: Cell::new(self.delivery_mode.get()),
+           })
+    }
+}
+
+/// Cloning adds a reference to the dequeue options, so the clone must be released separately.
+/// Panics if the reference cannot be added; use `try_clone()` to handle the error.
+impl Clone for Options {
+    fn clone(&self) -> Options {
+        self.try_clone().expect("Options::clone: dpiDeqOptions_addRef failed")
+    }
 }
 
 impl From<*mut ODPIDeqOptions> for Options {
     fn from(inner: *mut ODPIDeqOptions) -> Options {
-        Options { inner: inner }
+        track_ref!(wrapped, "DeqOptions", inner);
+        Options {
+            inner: inner,
+            delivery_mode: Cell::new(enums::ODPIMessageDeliveryMode::Persistent),
+        }
+    }
+}
+
+/// Collects dequeue options declaratively, to be applied to a new options handle at once with
+/// `Connection::new_deq_options_with()`. Options that are not set keep the ODPI-C defaults.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Builder {
+    /// The condition which must be true for messages to be dequeued.
+    condition: Option<String>,
+    /// The name of the consumer which is dequeuing messages.
+    consumer_name: Option<String>,
+    /// The correlation of the messages to be dequeued.
+    correlation: Option<String>,
+    /// The delivery mode of the messages to be dequeued.
+    delivery_mode: Option<enums::ODPIMessageDeliveryMode>,
+    /// The dequeue mode.
+    mode: Option<enums::ODPIDeqMode>,
+    /// The position of the message that is to be dequeued.
+    navigation: Option<enums::ODPIDeqNavigation>,
+    /// The transformation of the messages to be dequeued.
+    transformation: Option<String>,
+    /// Whether dequeuing is part of the current transaction.
+    visibility: Option<enums::ODPIVisibility>,
+    /// The time to wait for a matching message.
+    wait: Option<Wait>,
+}
+
+impl Builder {
+    /// Create a new `Builder` with no options set.
+    pub fn new() -> Builder {
+        Default::default()
+    }
+
+    /// Set the condition which must be true for messages to be dequeued. See
+    /// `dequeue::Options::set_condition()`.
+    pub fn condition(mut self, condition: &str) -> Builder {
+        self.condition = Some(condition.to_string());
+        self
+    }
+
+    /// Set the name of the consumer which is dequeuing messages. See
+    /// `dequeue::Options::set_consumer_name()`.
+    pub fn consumer(mut self, consumer_name: &str) -> Builder {
+        self.consumer_name = Some(consumer_name.to_string());
+        self
+    }
+
+    /// Set the correlation of the messages to be dequeued. See
+    /// `dequeue::Options::set_correlation()`.
+    pub fn correlation(mut self, correlation: &str) -> Builder {
+        self.correlation = Some(correlation.to_string());
+        self
+    }
+
+    /// Set the delivery mode of the messages to be dequeued. See
+    /// `dequeue::Options::set_delivery_mode()`.
+    pub fn delivery_mode(mut self, mode: enums::ODPIMessageDeliveryMode) -> Builder {
+        self.delivery_mode = Some(mode);
+        self
+    }
+
+    /// Set the dequeue mode. See `dequeue::Options::set_mode()`.
+    pub fn mode(mut self, mode: enums::ODPIDeqMode) -> Builder {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Set the position of the message that is to be dequeued. See
+    /// `dequeue::Options::set_navigation()`.
+    pub fn navigation(mut self, navigation: enums::ODPIDeqNavigation) -> Builder {
+        self.navigation = Some(navigation);
+        self
+    }
+
+    /// Set the transformation of the messages to be dequeued. See
+    /// `dequeue::Options::set_transformation()`.
+    pub fn transformation(mut self, transformation: &str) -> Builder {
+        self.transformation = Some(transformation.to_string());
+        self
+    }
+
+    /// Set whether dequeuing is part of the current transaction. See
+    /// `dequeue::Options::set_visibility()`.
+    pub fn visibility(mut self, visibility: enums::ODPIVisibility) -> Builder {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    /// Set the time to wait for a message matching the search criteria. See
+    /// `dequeue::Options::set_wait()`.
+    pub fn wait(mut self, wait: Wait) -> Builder {
+        self.wait = Some(wait);
+        self
+    }
+
+    /// Apply the options that have been set to the given dequeue options handle.
+    ///
+    /// * `options` - the dequeue options to apply the options to.
+    pub fn apply(&self, options: &Options) -> Result<()> {
+        if let Some(ref condition) = self.condition {
+            options.set_condition(Some(condition))?;
+        }
+        if let Some(ref consumer_name) = self.consumer_name {
+            options.set_consumer_name(Some(consumer_name))?;
+        }
+        if let Some(ref correlation) = self.correlation {
+            options.set_correlation(Some(correlation))?;
+        }
+        if let Some(delivery_mode) = self.delivery_mode {
+            options.set_delivery_mode(delivery_mode)?;
+        }
+        if let Some(mode) = self.mode {
+            options.set_mode(mode)?;
+        }
+        if let Some(navigation) = self.navigation {
+            options.set_navigation(navigation)?;
+        }
+        if let Some(ref transformation) = self.transformation {
+            options.set_transformation(Some(transformation))?;
+        }
+        if let Some(visibility) = self.visibility {
+            options.set_visibility(visibility)?;
+        }
+        if let Some(wait) = self.wait {
+            options.set_wait(wait)?;
+        }
+        Ok(())
     }
 }
@@ -13,14 +13,16 @@
 use error::{ErrorKind, Result};
 use odpi::{enums, externs};
 use odpi::opaque::ODPIEnqOptions;
+use std::cell::Cell;
 use std::ptr;
 use util::ODPIStr;
 
 /// Enqueue option handles are used to represent the options specified when enqueuing messages.
-#[derive(Clone)]
 pub struct Options {
     /// The ODPI-C EnqOptions pointer.
     inner: *mut ODPIEnqOptions,
+    /// The delivery mode last set with `set_delivery_mode()`. ODPI-C cannot read it back.
+    delivery_mode: Cell<enums::ODPIMessageDeliveryMode>,
 }
 
 impl Options {
@@ -34,10 +36,19 @@ impl Options {
     /// the handle was created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiEnqOptions_addRef(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(added, "EnqOptions", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::EnqOptions("dpiEnqOptions_addRef".to_string()))
     }
 
+    /// Returns the message delivery mode that is used when enqueuing messages, as last set with
+    /// `enqueue::Options::set_delivery_mode()` on this handle. The default is `Persistent`.
+    pub fn get_delivery_mode(&self) -> enums::ODPIMessageDeliveryMode {
+        self.delivery_mode.get()
+    }
+
     /// Returns the transformation of the message to be enqueued. See function
     /// `enqueue::Options::set_transformation()` for more information.
     pub fn get_transformation(&self) -> Result<String> {
@@ -72,14 +83,20 @@ impl Options {
     /// options is freed.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiEnqOptions_release(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(released, "EnqOptions", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::EnqOptions("dpiEnqOptions_release".to_string()))
     }
 
     /// Sets the message delivery mode that is to be used when enqueuing messages.
     pub fn set_delivery_mode(&self, mode: enums::ODPIMessageDeliveryMode) -> Result<()> {
         try_dpi!(externs::dpiEnqOptions_setDeliveryMode(self.inner, mode),
-                 Ok(()),
+                 {
+                     self.delivery_mode.set(mode);
+                     Ok(())
+                 },
                  ErrorKind::EnqOptions("dpiEnqOptions_setDeliveryMode".to_string()))
     }
 
@@ -101,10 +118,89 @@ impl Options {
                  Ok(()),
                  ErrorKind::EnqOptions("dpiEnqOptions_setVisibility".to_string()))
     }
+
+    /// Adds a reference to the enqueue options and returns a new handle to it, which must be
+    /// released separately. Unlike `clone()`, which panics if the reference cannot be added, this
+    /// returns the error.
+    pub fn try_clone(&self) -> Result<Options> {
+        self.add_ref()?;
+        Ok(Options {
+               inner: self.inner,
+               delivery_mode: Cell::new(self.delivery_mode.get()),
+           })
+    }
+}
+
+/// Cloning adds a reference to the enqueue options, so the clone must be released separately.
+/// Panics if the reference cannot be added; use `try_clone()` to handle the error.
+impl Clone for Options {
+    fn clone(&self) -> Options {
+        self.try_clone().expect("Options::clone: dpiEnqOptions_addRef failed")
+    }
 }
 
 impl From<*mut ODPIEnqOptions> for Options {
     fn from(inner: *mut ODPIEnqOptions) -> Options {
-        Options { inner: inner }
+        track_ref!(wrapped, "EnqOptions", inner);
+        Options {
+            inner: inner,
+            delivery_mode: Cell::new(enums::ODPIMessageDeliveryMode::Persistent),
+        }
+    }
+}
+
+/// Collects enqueue options declaratively, to be applied to a new options handle at once with
+/// `Connection::new_enq_options_with()`. Options that are not set keep the ODPI-C defaults.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Builder {
+    /// The delivery mode of the messages to be enqueued.
+    delivery_mode: Option<enums::ODPIMessageDeliveryMode>,
+    /// The transformation of the messages to be enqueued.
+    transformation: Option<String>,
+    /// Whether enqueuing is part of the current transaction.
+    visibility: Option<enums::ODPIVisibility>,
+}
+
+impl Builder {
+    /// Create a new `Builder` with no options set.
+    pub fn new() -> Builder {
+        Default::default()
+    }
+
+    /// Set the delivery mode of the messages to be enqueued. See
+    /// `enqueue::Options::set_delivery_mode()`.
+    pub fn delivery_mode(mut self, mode: enums::ODPIMessageDeliveryMode) -> Builder {
+        self.delivery_mode = Some(mode);
+        self
+    }
+
+    /// Set the transformation of the messages to be enqueued. See
+    /// `enqueue::Options::set_transformation()`.
+    pub fn transformation(mut self, transformation: &str) -> Builder {
+        self.transformation = Some(transformation.to_string());
+        self
+    }
+
+    /// Set whether enqueuing is part of the current transaction. See
+    /// `enqueue::Options::set_visibility()`.
+    pub fn visibility(mut self, visibility: enums::ODPIVisibility) -> Builder {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    /// Apply the options that have been set to the given enqueue options handle.
+    ///
+    /// * `options` - the enqueue options to apply the options to.
+    pub fn apply(&self, options: &Options) -> Result<()> {
+        if let Some(delivery_mode) = self.delivery_mode {
+            options.set_delivery_mode(delivery_mode)?;
+        }
+        if let Some(ref transformation) = self.transformation {
+            options.set_transformation(Some(transformation))?;
+        }
+        if let Some(visibility) = self.visibility {
+            options.set_visibility(visibility)?;
+        }
+        Ok(())
     }
 }
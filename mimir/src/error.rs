@@ -8,6 +8,7 @@
 
 //! `mimir` errors
 use common::error::Info;
+use connection::expired::ExpiredPassword;
 
 error_chain! {
     foreign_links {
@@ -17,10 +18,22 @@ error_chain! {
     }
 
     errors {
+        Bind(msg: String) {
+            description("Bind: invalid bind values!")
+            display("Bind: {}", msg)
+        }
         BranchId {
             description("The given batch id is longer than 64 bytes!")
             display("The given batch id is longer than 64 bytes!")
         }
+        Codegen(msg: String) {
+            description("Codegen: failed to generate code!")
+            display("Codegen: {}", msg)
+        }
+        Config(msg: String) {
+            description("Config: invalid connection parameters!")
+            display("Config: {}", msg)
+        }
         Connection(fn_name: String) {
             description("Connection: call to ODPI-C function failed!")
             display("Connection: call to '{}' function failed!", fn_name)
@@ -33,6 +46,10 @@ error_chain! {
             description("Failed to create the ODPI-C context!")
             display("Failed to create the ODPI-C context!")
         }
+        ContextDropped {
+            description("The context the handle was created in has been dropped!")
+            display("The context the handle was created in has been dropped!")
+        }
         DeqOptions(fn_name: String) {
             description("DeqOptions: call to ODPI-C function failed!")
             display("DeqOptions: call to '{}' function failed!", fn_name)
@@ -45,14 +62,36 @@ error_chain! {
             description("EnqOptions: call to ODPI-C function failed!")
             display("EnqOptions: call to '{}' function failed!", fn_name)
         }
+        Identifier(msg: String) {
+            description("Identifier: invalid SQL name!")
+            display("Identifier: {}", msg)
+        }
         Lob(fn_name: String) {
             description("LOB: call to ODPI-C function failed!")
             display("LOB: call to '{}' function failed!", fn_name)
         }
+        Migration(msg: String) {
+            description("Migration: failed to migrate the schema!")
+            display("Migration: {}", msg)
+        }
         MsgProps(fn_name: String) {
             description("MsgProps: call to ODPI-C function failed!")
             display("MsgProps: call to '{}' function failed!", fn_name)
         }
+        Naming(msg: String) {
+            description("Naming: failed to resolve the connect string!")
+            display("Naming: {}", msg)
+        }
+        NoRows {
+            description("The query returned no rows!")
+            display("The query returned no rows!")
+        }
+        NotAQuery(fn_name: String, statement_type: String) {
+            description("The statement is not a query!")
+            display("NotAQuery: '{}' needs a query, but the statement is of type {}",
+                    fn_name,
+                    statement_type)
+        }
         Object(fn_name: String) {
             description("Object: call to ODPI-C function failed!")
             display("Object: call to '{}' function failed!", fn_name)
@@ -65,14 +104,37 @@ error_chain! {
             description("OCI Error!")
             display("OCI Error! {}", err)
         }
+        OracleType(msg: String) {
+            description("OracleType: invalid type!")
+            display("OracleType: {}", msg)
+        }
+        PasswordExpired(expired: ExpiredPassword) {
+            description("The password has expired!")
+            display("Connection: {}", expired)
+        }
         Pool(fn_name: String) {
             description("Pool: call to ODPI-C function failed!")
             display("Pool: call to '{}' function failed!", fn_name)
         }
+        PoolBusy(busy: u32) {
+            description("Pool: sessions are still busy!")
+            display("Pool: cannot close the pool while {} sessions are busy", busy)
+        }
+        PoolTimeout(millis: u64) {
+            description("Pool: timed out waiting for a free session!")
+            display("Pool: no session became available within {}ms", millis)
+        }
         Rowid(fn_name: String) {
             description("Rowid: call to ODPI-C function failed!")
             display("Rowid: call to '{}' function failed!", fn_name)
         }
+        StaleRow(table: String, version: i64) {
+            description("StaleRow: the row was changed or deleted by another session!")
+            display("StaleRow: the row of '{}' at version {} was changed or deleted by another \
+                     session",
+                    table,
+                    version)
+        }
         Statement(fn_name: String) {
             description("Statement: call to ODPI-C function failed!")
             display("Statement: call to '{}' function failed!", fn_name)
@@ -81,13 +143,47 @@ error_chain! {
             description("Subscription: call to ODPI-C function failed!")
             display("Subscription: call to '{}' function failed!", fn_name)
         }
+        Timeout(millis: u64) {
+            description("Timeout: the call did not complete in time!")
+            display("Timeout: the call did not complete within {}ms", millis)
+        }
+        TooManyRows {
+            description("The query returned more than one row!")
+            display("The query returned more than one row!")
+        }
+        Transaction(msg: String) {
+            description("Transaction: invalid operation!")
+            display("Transaction: {}", msg)
+        }
         TxnId {
             description("The given transaction id is longer than 64 bytes!")
             display("The given transaction id is longer than 64 bytes!")
         }
+        Unsupported(feature: String, needs: String) {
+            description("Unsupported: the Oracle Client does not support the feature!")
+            display("Unsupported: {} needs Oracle Client {} or later", feature, needs)
+        }
         Var(fn_name: String) {
             description("Var: call to ODPI-C function failed!")
             display("Var: call to '{}' function failed!", fn_name)
         }
+        VarRange(msg: String) {
+            description("Var: array position out of range!")
+            display("Var: {}", msg)
+        }
+    }
+}
+
+impl Error {
+    /// Returns the SQL line containing the position at which an OCI error was raised, with a caret
+    /// under it. See `Info::render_snippet()`. Returns None for errors that do not carry OCI error
+    /// information.
+    ///
+    /// * `sql` - the SQL the error was raised for.
+    pub fn render_snippet(&self, sql: &str) -> Option<String> {
+        match *self.kind() {
+            ErrorKind::OciError(ref info) => Some(info.render_snippet(sql)),
+            _ => None,
+        }
     }
 }
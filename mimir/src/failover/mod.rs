@@ -0,0 +1,174 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Notification of database instances starting up and shutting down, so pooled sessions to an
+//! instance that went down can be retired before they are handed out, rather than found broken on
+//! their next call.
+//!
+//! A `Watcher` owns a subscription in the `DbChange` namespace. Besides the changes of registered
+//! queries, the database sends such subscriptions an event when an instance is started up, shut
+//! down or dropped, which the watcher decodes into an `InstanceEvent`. The connection must be
+//! created with `DPI_MODE_CREATE_EVENTS` set in its `CommonCreate` parameters. Pools created in
+//! events mode also have the Oracle client drop their sessions to an instance that went down on
+//! receiving a FAN event, where the database publishes them.
+//!
+//! ODPI-C 2.0 does not expose the OCI failover callback, so a session migrating to another
+//! instance through TAF is not reported; `ErrorInfo::recoverable()` tells which errors a retry
+//! may succeed after.
+//!
+//! ```ignore
+//! let watcher = Watcher::new(&conn, ctxt.init_subscr_create_params()?)?;
+//!
+//! loop {
+//!     let retired = watcher.retire_idle(&pool)?;
+//!     ...
+//! }
+//! ```
+use connection::Connection;
+use context::params::SubscrCreate;
+use error::Result;
+use odpi::enums::{self, ODPIEventType};
+use pool::Pool;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
+use subscription::Receiver;
+use subscription::message::Message;
+
+/// A database instance starting up, shutting down or being dropped.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstanceEvent {
+    /// The type of the event: `Startup`, `Shutdown`, `ShutdownAny` or `DropDB`.
+    pub event_type: ODPIEventType,
+    /// The name of the database that generated the event.
+    pub db_name: String,
+}
+
+impl InstanceEvent {
+    /// Returns the instance event a notification reports, or None if it reports a change to
+    /// registered queries or objects, or a deregistration.
+    ///
+    /// * `message` - the decoded notification.
+    pub fn from_message(message: &Message) -> Option<InstanceEvent> {
+        let notification = message.notification();
+        match notification.event_type {
+            ODPIEventType::Startup |
+            ODPIEventType::Shutdown |
+            ODPIEventType::ShutdownAny |
+            ODPIEventType::DropDB => {
+                Some(InstanceEvent {
+                         event_type: notification.event_type,
+                         db_name: notification.db_name.clone(),
+                     })
+            }
+            _ => None,
+        }
+    }
+
+    /// Is the instance going away? True for shutdowns and for the database being dropped, after
+    /// which the sessions connected to it are broken.
+    pub fn is_down(&self) -> bool {
+        match self.event_type {
+            ODPIEventType::Shutdown | ODPIEventType::ShutdownAny | ODPIEventType::DropDB => true,
+            _ => false,
+        }
+    }
+}
+
+/// A subscription delivering the instance events of a database. Dropping the watcher closes the
+/// subscription.
+pub struct Watcher {
+    /// The id of the subscription.
+    id: u32,
+    /// The receiver of the notifications, which owns the subscription.
+    receiver: Receiver,
+}
+
+impl Watcher {
+    /// Create a new `Watcher`, subscribing to the events of the database the given connection is
+    /// connected to. The namespace, protocol and callback of the parameters are overwritten; the
+    /// others, such as the timeout, are used as given.
+    ///
+    /// * `conn` - the connection the subscription is created on.
+    /// * `subscr_create_params` - the parameters of the subscription, from
+    /// `Context::init_subscr_create_params()`.
+    pub fn new(conn: &Connection, mut subscr_create_params: SubscrCreate) -> Result<Watcher> {
+        subscr_create_params.set_subscr_namespace(enums::ODPISubscrNamespace::DbChange);
+        subscr_create_params.set_protocol(enums::ODPISubscrProtocol::Callback);
+        subscr_create_params.set_callback(None);
+        let (id, subscription) = conn.new_subscription(subscr_create_params)?;
+
+        Ok(Watcher {
+               id: id,
+               receiver: subscription.into_receiver()?,
+           })
+    }
+
+    /// Closes the subscription, so no more events are sent. Events already received can still be
+    /// read until the watcher is dropped.
+    pub fn close(&mut self) -> Result<()> {
+        self.receiver.close()
+    }
+
+    /// Get the `id` value.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns the next instance event already received, without waiting, or None if there is
+    /// none. Other notifications are skipped.
+    pub fn try_next(&self) -> Option<InstanceEvent> {
+        self.receiver
+            .try_iter()
+            .filter_map(|message| InstanceEvent::from_message(&message))
+            .next()
+    }
+
+    /// Returns the next instance event, waiting up to the given time for one to be received, or
+    /// None if there is none by then or the subscription is closed. Other notifications are
+    /// skipped.
+    ///
+    /// * `timeout` - the longest time to wait.
+    pub fn wait(&self, timeout: Duration) -> Option<InstanceEvent> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let now = Instant::now();
+            let remaining = if deadline > now {
+                deadline - now
+            } else {
+                Duration::from_secs(0)
+            };
+
+            match self.receiver.recv_timeout(remaining) {
+                Ok(message) => {
+                    if let Some(event) = InstanceEvent::from_message(&message) {
+                        return Some(event);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) |
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+    }
+
+    /// Reads the instance events already received and, if any reports an instance going down,
+    /// drops the idle sessions of the pool with `Pool::purge()`, so sessions to the lost instance
+    /// are not handed out again. Busy sessions are left to their users, who can drop them with
+    /// `Connection::close_drop()`. The get mode of the pool is left as it was, even on error.
+    /// Returns the number of sessions dropped.
+    ///
+    /// * `pool` - the pool whose sessions are connected to the watched database.
+    pub fn retire_idle(&self, pool: &Pool) -> Result<u32> {
+        let mut down = false;
+        while let Some(event) = self.try_next() {
+            down |= event.is_down();
+        }
+
+        if down { pool.purge() } else { Ok(0) }
+    }
+}
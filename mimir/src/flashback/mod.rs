@@ -0,0 +1,144 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Flashback queries read data as it was at a past system change number (SCN) or time. A single
+//! table is queried in the past by adding an `AsOf` clause after it in the SQL. Every query on a
+//! session is run in the past by calling `Connection::flashback()`, which returns a guard that
+//! returns the session to the present when dropped.
+//!
+//! The `ORA_ROWSCN` pseudo-column holds the SCN of the last change to a row (or to its block,
+//! unless the table was created with `ROWDEPENDENCIES`). Fetching it into a `Vec<Scn>` buffer with
+//! `Statement::fetch_into()`, or reading the current SCN with `Connection::current_scn()`, allows
+//! optimistic locking: update a row only if its `ORA_ROWSCN` has not changed since it was read.
+use chrono::{DateTime, Utc};
+use connection::Connection;
+use error::Result;
+use odpi::enums::ODPINativeTypeNum;
+use odpi::flags;
+use odpi::structs::ODPIData;
+use statement::buffers::FetchValue;
+use std::cell::Cell;
+use std::fmt;
+use std::ops::Deref;
+
+/// The PL/SQL block that returns the session to the present.
+const DISABLE_SQL: &str = "begin dbms_flashback.disable; end;";
+
+/// A system change number, identifying a committed version of the database.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Scn(pub u64);
+
+impl fmt::Display for Scn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u64> for Scn {
+    fn from(scn: u64) -> Scn {
+        Scn(scn)
+    }
+}
+
+impl FetchValue for Scn {
+    fn native_type() -> ODPINativeTypeNum {
+        ODPINativeTypeNum::Uint64
+    }
+
+    unsafe fn from_data(data: &ODPIData) -> Scn {
+        Scn(data.value.as_uint_64)
+    }
+}
+
+/// The point in the past a flashback query reads data as of.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AsOf {
+    /// The data as of the given SCN.
+    Scn(Scn),
+    /// The data as of the given time. Oracle maps the time to an SCN with a granularity of about
+    /// three seconds.
+    Timestamp(DateTime<Utc>),
+}
+
+/// Formats the flashback clause, e.g. `AS OF SCN 1234`, for use after a table name.
+impl fmt::Display for AsOf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AsOf::Scn(scn) => write!(f, "AS OF SCN {}", scn),
+            AsOf::Timestamp(time) => {
+                write!(f,
+                       "AS OF TIMESTAMP TIMESTAMP '{}'",
+                       time.format("%Y-%m-%d %H:%M:%S%.6f +00:00"))
+            }
+        }
+    }
+}
+
+/// A guard for a session that is running queries in the past, returned by
+/// `Connection::flashback()`. DML is not allowed while the guard is alive. The session returns to
+/// the present when the guard is dropped, or when `end()` is called to see any error.
+pub struct Flashback<'a> {
+    /// The connection running queries in the past.
+    conn: &'a Connection,
+    /// Has the session returned to the present?
+    ended: Cell<bool>,
+}
+
+impl<'a> Flashback<'a> {
+    /// Start running the queries of the session in the past.
+    #[doc(hidden)]
+    pub fn begin(conn: &'a Connection, as_of: AsOf) -> Result<Flashback<'a>> {
+        let sql = match as_of {
+            AsOf::Scn(_) => "begin dbms_flashback.enable_at_system_change_number(:as_of); end;",
+            AsOf::Timestamp(_) => "begin dbms_flashback.enable_at_time(:as_of); end;",
+        };
+        let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+        let binder = match as_of {
+            AsOf::Scn(scn) => stmt.binder().set("as_of", scn.0),
+            AsOf::Timestamp(time) => stmt.binder().set("as_of", time),
+        };
+        let res = binder.and_then(|binder| binder.execute());
+        stmt.release()?;
+        res?;
+
+        Ok(Flashback {
+               conn: conn,
+               ended: Cell::new(false),
+           })
+    }
+
+    /// Returns the session to the present.
+    pub fn end(self) -> Result<()> {
+        self.ended.set(true);
+        execute(self.conn, DISABLE_SQL)
+    }
+}
+
+impl<'a> Deref for Flashback<'a> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn
+    }
+}
+
+impl<'a> Drop for Flashback<'a> {
+    fn drop(&mut self) {
+        if !self.ended.get() {
+            let _ = execute(self.conn, DISABLE_SQL);
+        }
+    }
+}
+
+/// Prepare, execute, and release the given SQL statement.
+fn execute(conn: &Connection, sql: &str) -> Result<()> {
+    let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+    let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT);
+    stmt.release()?;
+    res.map(|_| ())
+}
@@ -7,9 +7,18 @@
 // modified, or distributed except according to those terms.
 
 //! Rust bindings over the Oracle Database Programming Interface for Drivers and Applications.
+//!
+//! # Handle ownership
+//!
+//! The wrappers around ODPI-C handles, such as `Connection`, `Statement` and `Object`, own one
+//! reference to the handle. The reference is not released when the wrapper is dropped; call
+//! `release()` once the handle is no longer needed. Each call to `add_ref()` adds a reference that
+//! must be released by its own call to `release()`. Wrappers that implement `Clone` add a reference
+//! when cloned, so the original and every clone are released separately. If the handle is invalid,
+//! adding the reference fails and the clone is equally unusable.
 #![deny(missing_docs)]
 #![feature(untagged_unions)]
-#![recursion_limit="128"]
+#![recursion_limit="256"]
 #![cfg_attr(feature = "cargo-clippy", allow(unseparated_literal_suffix))]
 
 #[macro_use]
@@ -19,53 +28,123 @@ extern crate error_chain;
 #[macro_use]
 extern crate getset;
 #[macro_use]
+extern crate lazy_static;
+#[cfg(all(feature = "log", not(feature = "slog")))]
+#[macro_use]
+extern crate log;
+#[cfg(feature = "slog")]
+#[macro_use]
 extern crate slog;
 #[macro_use]
 mod macros;
 
 extern crate chrono;
+extern crate toml;
 
+pub mod catalog;
+pub mod cdc;
+#[cfg(feature = "codegen")]
+pub mod codegen;
 mod common;
+mod config;
 mod connection;
 mod context;
+pub mod cqn;
 mod data;
 mod dequeue;
 mod enqueue;
-mod lob;
+pub mod failover;
+pub mod flashback;
+pub mod lob;
+mod logger;
+pub mod merge;
 mod message;
+pub mod migrate;
+pub mod modes;
+mod naming;
 mod odpi;
 mod object;
 mod objectattr;
 mod objecttype;
+pub mod optimistic;
+mod oracletype;
 mod pool;
 mod query;
+pub mod raw;
+#[cfg(feature = "ref-tracking")]
+pub mod reftrack;
+pub mod registry;
+mod resultcache;
 mod rowid;
+pub mod sql;
 mod statement;
+mod stmtcache;
 mod subscription;
+mod timing;
+mod transaction;
+pub mod types;
 mod util;
 mod variable;
+pub mod xml;
 
 // Public API
 #[allow(missing_docs, unused_doc_comment)]
 pub mod error;
+#[cfg(feature = "test-util")]
+pub mod testutil;
 
+pub use common::error::Info as ErrorInfo;
+pub use config::{ConnectParams, redact_connect_string};
 pub use connection::Connection;
+pub use connection::batch::Batch;
+pub use connection::ddl::{CompiledObject, Diagnostic as CompileDiagnostic, compiled_object};
+pub use connection::expired::ExpiredPassword;
+pub use connection::lazy::LazyConnection;
+pub use connection::nls::DateFormat;
+pub use connection::script::{ScriptResult, ScriptStatement, split_script};
+pub use connection::session::SessionId;
+pub use connection::trace::TraceAttrs;
 pub use context::Context;
+pub use context::feature::Feature;
 pub use context::params::AppContext;
-pub use data::Data;
-pub use dequeue::Options as DeqOptions;
-pub use enqueue::Options as EnqOptions;
+pub use cdc::{RowEvent, TableWatch};
+pub use cqn::{ChangeEvent, Listener as CqnListener};
+pub use data::{Data, YearsMonths};
+pub use dequeue::{Builder as DeqOptionsBuilder, Options as DeqOptions, Wait as DeqWait};
+pub use enqueue::{Builder as EnqOptionsBuilder, Options as EnqOptions};
+pub use failover::{InstanceEvent, Watcher as FailoverWatcher};
+pub use flashback::{AsOf, Scn};
 pub use lob::Lob;
+pub use merge::Merge;
 pub use message::Properties as MsgProps;
+pub use migrate::Migrator;
+pub use modes::{AuthMode, ConnCloseMode, CreateMode, ExecMode, PoolCloseMode, SubscrQos};
+pub use naming::{NamingMethod, TnsNames};
+pub use naming::connectstring::{Compression, ConnectString};
 pub use object::Object;
 pub use objectattr::ObjectAttr;
+pub use objectattr::info::Info as ObjectAttrInfo;
 pub use objecttype::ObjectType;
+pub use objecttype::info::{ElementInfo, Info as ObjectTypeInfo};
 pub use odpi::{constants, enums, flags};
-pub use odpi::structs::{ODPIBytes, ODPIData, ODPIDataValueUnion, ODPIObjectAttrInfo,
-                        ODPIObjectTypeInfo, ODPISubscrMessage};
-pub use pool::Pool;
-pub use query::Info as QueryInfo;
+pub use optimistic::VersionedUpdate;
+pub use oracletype::{ConversionLoss, OracleType};
+pub use pool::{Pool, PoolHandle};
+pub use query::{CoercionWarning, ColumnInfo, Info as QueryInfo};
+pub use registry::{DatabasePool, Databases};
+pub use resultcache::{PrepareOptions, ResultCacheMode, add_result_cache_hint};
 pub use rowid::Rowid;
-pub use statement::Statement;
-pub use util::ODPIStr;
+pub use sql::ServerFeatures;
+pub use statement::{ControlFlow, RowBatch, Statement};
+pub use statement::binder::{BindValue, Binder};
+pub use statement::bindinfo::Info as BindInfo;
+pub use statement::buffers::{ColumnBuffer, FetchBuffers, FetchRow, FetchValue, RowValue};
+pub use statement::row::{FromValue, Row};
+pub use statement::tracker::OpenStatement;
+pub use stmtcache::{CacheStats, cache_tag};
+pub use subscription::{Receiver as SubscrReceiver, Subscription};
+pub use subscription::message::{Message as SubscrMessage, Notification as SubscrNotification};
+pub use timing::Timing;
+pub use transaction::{Isolation, Savepoint, Transaction, TxnOptions};
+pub use types::RustType;
 pub use variable::Var;
@@ -16,9 +16,62 @@
 use error::{ErrorKind, Result};
 use odpi::externs;
 use odpi::opaque::ODPILob;
-use std::ptr;
+use std::{cmp, ptr};
 use util::ODPIStr;
 
+/// Copies the content of one LOB into another, which may belong to a different connection,
+/// replacing the content of the destination. The data is streamed through a client side buffer
+/// in pieces of `chunk_size`, so both LOBs should be of the same kind. For character LOBs the
+/// chunk size and the returned amount are in characters; for binary LOBs they are in bytes.
+///
+/// Returns the amount of data copied.
+///
+/// * `src` - the LOB to read from.
+/// * `dst` - the LOB to write to.
+/// * `chunk_size` - the amount of data to copy at a time. A value of 0 uses the chunk size of the
+/// source LOB.
+pub fn copy_between(src: &Lob, dst: &Lob, chunk_size: u64) -> Result<u64> {
+    let chunk_size = if chunk_size == 0 {
+        u64::from(src.get_chunk_size()?)
+    } else {
+        chunk_size
+    };
+    let size = src.get_size()?;
+    let mut buffer = Vec::new();
+
+    let was_open = dst.get_is_resource_open()?;
+    if !was_open {
+        dst.open_resource()?;
+    }
+
+    let copied = copy_chunks(src, dst, size, chunk_size, &mut buffer);
+
+    if !was_open {
+        dst.close_resource()?;
+    }
+    copied
+}
+
+/// Copies `size` characters or bytes from `src` to `dst`, `chunk_size` at a time.
+fn copy_chunks(src: &Lob,
+               dst: &Lob,
+               size: u64,
+               chunk_size: u64,
+               buffer: &mut Vec<i8>)
+               -> Result<u64> {
+    dst.trim(0)?;
+
+    let mut offset = 1;
+    while offset <= size {
+        let amount = cmp::min(chunk_size, size - offset + 1);
+        src.read_into(offset, amount, buffer)?;
+        dst.write_bytes(buffer, offset)?;
+        offset += amount;
+    }
+
+    Ok(size)
+}
+
 /// LOB handles are used to represent large objects (CLOB, BLOB, NCLOB, BFILE).
 pub struct Lob {
     /// The ODPI-C LOB pointer.
@@ -35,7 +88,10 @@ impl Lob {
     /// needs to be maintained independently of the reference returned when the LOB was created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiLob_addRef(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(added, "Lob", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Lob("dpiLob_addRef".to_string()))
     }
 
@@ -154,19 +210,32 @@ impl Lob {
     }
 
     /// Reads data from the LOB at the specified offset into the provided buffer.
-    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    ///
+    /// * `offset` - the offset into the LOB data from which to start reading. The first position
+    /// is 1. For character LOBs this is in characters; for binary LOBs it is in bytes.
+    /// * `length` - the amount of data to read. For character LOBs this is in characters; for
+    /// binary LOBs it is in bytes.
     pub fn read_bytes(&self, offset: u64, length: u64) -> Result<Vec<i8>> {
-        let mut buffer: Vec<i8> = Vec::with_capacity(length as usize);
+        let mut buffer = Vec::new();
+        self.read_into(offset, length, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Reads data from the LOB into the given buffer, replacing its content. The buffer is grown
+    /// to hold `length` characters in the client encoding when the LOB is a character LOB.
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    fn read_into(&self, offset: u64, length: u64, buffer: &mut Vec<i8>) -> Result<()> {
+        let mut buf_len = self.get_buffer_size(length)?;
+        buffer.clear();
+        buffer.reserve(buf_len as usize);
         let buf_ptr = buffer.as_mut_ptr();
-        let mut buf_len = length;
 
         try_dpi!(externs::dpiLob_readBytes(self.inner, offset, length, buf_ptr, &mut buf_len),
                  {
                      unsafe { buffer.set_len(buf_len as usize) };
-                     Ok(buffer)
+                     Ok(())
                  },
                  ErrorKind::Lob("dpiLob_readBytes".to_string()))
-
     }
 
     /// Releases a reference to the LOB. A count of the references to the LOB is maintained and when
@@ -174,7 +243,10 @@ impl Lob {
     /// unless that has already taken place using the function `Lob:::close()`.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiLob_release(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(released, "Lob", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Lob("dpiLob_release".to_string()))
     }
 
@@ -237,6 +309,7 @@ impl Lob {
 
 impl From<*mut ODPILob> for Lob {
     fn from(inner: *mut ODPILob) -> Lob {
+        track_ref!(wrapped, "Lob", inner);
         Lob { inner: inner }
     }
 }
@@ -0,0 +1,29 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Logging facade. The logging backend is selected at compile time:
+//!
+//! * `slog` (default) - records are written to the optional slog `Logger`s held by the handles.
+//! * `log` - records are emitted through the `log` crate, using the held value as the target.
+//! This backend is only used when the `slog` feature is disabled.
+//!
+//! With neither feature enabled, logging compiles away entirely.
+
+/// The logger held by handles that support logging.
+#[cfg(feature = "slog")]
+pub type Logger = ::slog::Logger;
+
+/// The logger held by handles that support logging. Records are emitted through the `log` crate
+/// with this value as the target.
+#[cfg(all(feature = "log", not(feature = "slog")))]
+pub type Logger = &'static str;
+
+/// The logger held by handles that support logging. No logging backend is enabled, so records are
+/// discarded.
+#[cfg(not(any(feature = "log", feature = "slog")))]
+pub type Logger = ();
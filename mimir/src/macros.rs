@@ -17,6 +17,26 @@ macro_rules! try_dpi {
     }};
 }
 
+#[doc(hidden)]
+#[allow(unused_macros)]
+macro_rules! track_ref {
+    (created_in, $context:expr, $kind:expr, $handle:expr) => {{
+        #[cfg(feature = "ref-tracking")]
+        ::reftrack::created_in($context as *const ::std::os::raw::c_void,
+                               $kind,
+                               $handle as *const ::std::os::raw::c_void);
+    }};
+    ($op:ident, $context:expr) => {{
+        #[cfg(feature = "ref-tracking")]
+        ::reftrack::$op($context as *const ::std::os::raw::c_void);
+    }};
+    ($op:ident, $kind:expr, $handle:expr) => {{
+        #[cfg(feature = "ref-tracking")]
+        ::reftrack::$op($kind, $handle as *const ::std::os::raw::c_void);
+    }};
+}
+
+#[cfg(feature = "slog")]
 #[doc(hidden)]
 #[allow(unused_macros)]
 macro_rules! try_trace(
@@ -32,6 +52,32 @@ macro_rules! try_trace(
     }
 );
 
+#[cfg(all(feature = "log", not(feature = "slog")))]
+#[doc(hidden)]
+#[allow(unused_macros)]
+macro_rules! try_trace(
+    ($l:expr, $($k:expr => $v:expr),+; $($args:tt)+) => {
+        if let Some(target) = $l {
+            trace!(target: target, $($args)+);
+        }
+    };
+    ($l:expr, $($args:tt)+) => {
+        if let Some(target) = $l {
+            trace!(target: target, $($args)+);
+        }
+    }
+);
+
+#[cfg(not(any(feature = "log", feature = "slog")))]
+#[doc(hidden)]
+#[allow(unused_macros)]
+macro_rules! try_trace(
+    ($l:expr, $($args:tt)+) => {
+        let _ = &$l;
+    }
+);
+
+#[cfg(feature = "slog")]
 #[doc(hidden)]
 #[allow(unused_macros)]
 macro_rules! try_debug(
@@ -47,7 +93,34 @@ macro_rules! try_debug(
     }
 );
 
+#[cfg(all(feature = "log", not(feature = "slog")))]
 #[doc(hidden)]
+#[allow(unused_macros)]
+macro_rules! try_debug(
+    ($l:expr, $($k:expr => $v:expr),+; $($args:tt)+) => {
+        if let Some(target) = $l {
+            debug!(target: target, $($args)+);
+        }
+    };
+    ($l:expr, $($args:tt)+) => {
+        if let Some(target) = $l {
+            debug!(target: target, $($args)+);
+        }
+    }
+);
+
+#[cfg(not(any(feature = "log", feature = "slog")))]
+#[doc(hidden)]
+#[allow(unused_macros)]
+macro_rules! try_debug(
+    ($l:expr, $($args:tt)+) => {
+        let _ = &$l;
+    }
+);
+
+#[cfg(feature = "slog")]
+#[doc(hidden)]
+#[allow(unused_macros)]
 macro_rules! try_info(
     ($l:expr, $($k:expr => $v:expr),+; $($args:tt)+) => {
         if let Some(ref log) = $l {
@@ -61,6 +134,32 @@ macro_rules! try_info(
     }
 );
 
+#[cfg(all(feature = "log", not(feature = "slog")))]
+#[doc(hidden)]
+#[allow(unused_macros)]
+macro_rules! try_info(
+    ($l:expr, $($k:expr => $v:expr),+; $($args:tt)+) => {
+        if let Some(target) = $l {
+            info!(target: target, $($args)+);
+        }
+    };
+    ($l:expr, $($args:tt)+) => {
+        if let Some(target) = $l {
+            info!(target: target, $($args)+);
+        }
+    }
+);
+
+#[cfg(not(any(feature = "log", feature = "slog")))]
+#[doc(hidden)]
+#[allow(unused_macros)]
+macro_rules! try_info(
+    ($l:expr, $($args:tt)+) => {
+        let _ = &$l;
+    }
+);
+
+#[cfg(feature = "slog")]
 #[doc(hidden)]
 #[allow(unused_macros)]
 macro_rules! try_warn(
@@ -76,7 +175,34 @@ macro_rules! try_warn(
     }
 );
 
+#[cfg(all(feature = "log", not(feature = "slog")))]
 #[doc(hidden)]
+#[allow(unused_macros)]
+macro_rules! try_warn(
+    ($l:expr, $($k:expr => $v:expr),+; $($args:tt)+) => {
+        if let Some(target) = $l {
+            warn!(target: target, $($args)+);
+        }
+    };
+    ($l:expr, $($args:tt)+) => {
+        if let Some(target) = $l {
+            warn!(target: target, $($args)+);
+        }
+    }
+);
+
+#[cfg(not(any(feature = "log", feature = "slog")))]
+#[doc(hidden)]
+#[allow(unused_macros)]
+macro_rules! try_warn(
+    ($l:expr, $($args:tt)+) => {
+        let _ = &$l;
+    }
+);
+
+#[cfg(feature = "slog")]
+#[doc(hidden)]
+#[allow(unused_macros)]
 macro_rules! try_error(
     ($l:expr, $($k:expr => $v:expr),+; $($args:tt)+) => {
         if let Some(ref log) = $l {
@@ -90,6 +216,32 @@ macro_rules! try_error(
     }
 );
 
+#[cfg(all(feature = "log", not(feature = "slog")))]
+#[doc(hidden)]
+#[allow(unused_macros)]
+macro_rules! try_error(
+    ($l:expr, $($k:expr => $v:expr),+; $($args:tt)+) => {
+        if let Some(target) = $l {
+            error!(target: target, $($args)+);
+        }
+    };
+    ($l:expr, $($args:tt)+) => {
+        if let Some(target) = $l {
+            error!(target: target, $($args)+);
+        }
+    }
+);
+
+#[cfg(not(any(feature = "log", feature = "slog")))]
+#[doc(hidden)]
+#[allow(unused_macros)]
+macro_rules! try_error(
+    ($l:expr, $($args:tt)+) => {
+        let _ = &$l;
+    }
+);
+
+#[cfg(feature = "slog")]
 #[doc(hidden)]
 #[allow(unused_macros)]
 macro_rules! try_crit(
@@ -105,62 +257,27 @@ macro_rules! try_crit(
     }
 );
 
+#[cfg(all(feature = "log", not(feature = "slog")))]
+#[doc(hidden)]
 #[allow(unused_macros)]
-macro_rules! with_conn {
-    ($tst:pat => $b:expr) => {{
-        match Context::new() {
-            Ok(ref mut ctxt) => {
-                ctxt.set_encoding("UTF-8");
-                ctxt.set_nchar_encoding("UTF-8");
-                match Connection::connect(ctxt,
-                                          Some(&CREDS[0]),
-                                          Some(&CREDS[1]),
-                                          "//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL") {
-                    $tst => { $b }
-                    Err(_e) => {
-                        use std::io::{self, Write};
-                        writeln!(io::stderr(), "{}", error::from_dpi_context(ctxt))
-                            .expect("badness");
-                        assert!(false)
-                    }
-                }
-            }
-            Err(_e) => assert!(false),
+macro_rules! try_crit(
+    ($l:expr, $($k:expr => $v:expr),+; $($args:tt)+) => {
+        if let Some(target) = $l {
+            error!(target: target, $($args)+);
         }
-    }}
-}
+    };
+    ($l:expr, $($args:tt)+) => {
+        if let Some(target) = $l {
+            error!(target: target, $($args)+);
+        }
+    }
+);
 
+#[cfg(not(any(feature = "log", feature = "slog")))]
+#[doc(hidden)]
 #[allow(unused_macros)]
-macro_rules! with_stmt {
-    ($tst:pat => $b:expr; $stmt:expr) => {{
-        match Context::new() {
-            Ok(ref mut ctxt) => {
-                ctxt.set_encoding("UTF-8");
-                ctxt.set_nchar_encoding("UTF-8");
-                match Connection::connect(ctxt,
-                                          Some(&CREDS[0]),
-                                          Some(&CREDS[1]),
-                                          "//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL") {
-                    Ok(conn) => {
-                        match conn.prepare_stmt($stmt, None, false) {
-                            $tst => { $b }
-                            Err(_e) => {
-                                use std::io::{self, Write};
-                                writeln!(io::stderr(), "{}", error::from_dpi_context(ctxt))
-                                    .expect("badness");
-                                assert!(false)
-                            }
-                        }
-                    }
-                    Err(_e) => {
-                        use std::io::{self, Write};
-                        writeln!(io::stderr(), "{}", error::from_dpi_context(ctxt))
-                            .expect("badness");
-                        assert!(false)
-                    }
-                }
-            }
-            Err(_e) => assert!(false),
-        }
-    }}
-}
+macro_rules! try_crit(
+    ($l:expr, $($args:tt)+) => {
+        let _ = &$l;
+    }
+);
@@ -0,0 +1,170 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A builder for MERGE statements that insert a row, or update it if a row with the same key
+//! exists. The values are bound by name rather than formatted into the SQL, so the statement text
+//! is the same for every row and is reused from the statement cache.
+//!
+//! ```ignore
+//! let merged = Merge::into("accounts")
+//!     .on(&["id"])
+//!     .set(&[("id", 7.into()), ("name", "savings".into())])
+//!     .execute(&conn)?;
+//! ```
+//!
+//! renders, on a single line,
+//!
+//! ```sql
+//! MERGE INTO ACCOUNTS TGT
+//! USING (SELECT :ID AS ID, :NAME AS NAME FROM DUAL) SRC
+//! ON (TGT.ID = SRC.ID)
+//! WHEN MATCHED THEN UPDATE SET TGT.NAME = SRC.NAME
+//! WHEN NOT MATCHED THEN INSERT (ID, NAME) VALUES (SRC.ID, SRC.NAME)
+//! ```
+//!
+//! `execute()` renders the statement for the features of the server it runs on, so names are
+//! checked against the server's identifier length limit.
+use connection::Connection;
+use error::{ErrorKind, Result};
+use sql::{ServerFeatures, check_identifier_with_limit, check_qualified_name_with_limit};
+use statement::binder::BindValue;
+
+/// A MERGE statement inserting or updating a single row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Merge {
+    /// The target table, optionally qualified by its schema.
+    table: String,
+    /// The columns matching an existing row.
+    keys: Vec<String>,
+    /// The values of the row, by column name, in the order they were first set.
+    values: Vec<(String, BindValue)>,
+}
+
+impl Merge {
+    /// Create a new `Merge` into the given table. Names are validated when the statement is
+    /// rendered.
+    ///
+    /// * `table` - the target table, optionally qualified by its schema.
+    pub fn into(table: &str) -> Merge {
+        Merge {
+            table: table.to_uppercase(),
+            keys: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Executes the statement on the given connection, or `Transaction` guard, without
+    /// committing. Returns the number of rows merged.
+    ///
+    /// * `conn` - the connection the statement is run on.
+    pub fn execute(&self, conn: &Connection) -> Result<u64> {
+        let sql = self.sql_for(&conn.server_features()?)?;
+        let stmt = conn.prepare_stmt(Some(&sql), None, false)?;
+        let res = self.values
+            .iter()
+            .fold(Ok(stmt.binder()),
+                  |binder, &(ref name, ref value)| binder?.set(name, value.clone()))
+            .and_then(|binder| binder.execute())
+            .and_then(|_| stmt.get_row_count());
+        stmt.release()?;
+        res
+    }
+
+    /// Add the columns matching an existing row. Their values are given with `set()`.
+    ///
+    /// * `keys` - the key column names.
+    pub fn on(mut self, keys: &[&str]) -> Merge {
+        for key in keys {
+            let key = key.to_uppercase();
+            if !self.keys.contains(&key) {
+                self.keys.push(key);
+            }
+        }
+        self
+    }
+
+    /// Set the values of the row, replacing values set earlier for the same columns. The values
+    /// of the key columns are used to match an existing row; the others are inserted or updated.
+    ///
+    /// * `values` - the values, by column name.
+    pub fn set(mut self, values: &[(&str, BindValue)]) -> Merge {
+        for &(column, ref value) in values {
+            let column = column.to_uppercase();
+            match self.values.iter().position(|&(ref set, _)| *set == column) {
+                Some(idx) => self.values[idx].1 = value.clone(),
+                None => self.values.push((column, value.clone())),
+            }
+        }
+        self
+    }
+
+    /// Returns the MERGE statement for the latest releases. Returns an error if a name is not a
+    /// simple identifier or is a reserved word, if no key column was given, or if a key column has
+    /// no value. When every column is a key column, matching rows are left unchanged.
+    pub fn sql(&self) -> Result<String> {
+        self.sql_for(&ServerFeatures::default())
+    }
+
+    /// Returns the MERGE statement for a server with the given features. As well as the errors of
+    /// `sql()`, returns an error if a name is longer than the server allows, or if every column is
+    /// a key column and the server requires a WHEN MATCHED clause.
+    ///
+    /// * `features` - the features of the server, e.g. from `Connection::server_features()`.
+    pub fn sql_for(&self, features: &ServerFeatures) -> Result<String> {
+        let max_len = features.max_identifier_len();
+        check_qualified_name_with_limit(&self.table, max_len)?;
+        for &(ref column, _) in &self.values {
+            check_identifier_with_limit(column, max_len)?;
+        }
+
+        if self.keys.is_empty() {
+            let msg = format!("merge into {} needs a key column", self.table);
+            return Err(ErrorKind::Bind(msg).into());
+        }
+        for key in &self.keys {
+            if self.values.iter().all(|&(ref column, _)| column != key) {
+                let msg = format!("merge into {} has no value for key column {}", self.table, key);
+                return Err(ErrorKind::Bind(msg).into());
+            }
+        }
+
+        let columns: Vec<&str> = self.values.iter().map(|&(ref column, _)| &column[..]).collect();
+        let selects: Vec<String> = columns
+            .iter()
+            .map(|column| format!(":{} AS {}", column, column))
+            .collect();
+        let on: Vec<String> = self.keys
+            .iter()
+            .map(|key| format!("TGT.{} = SRC.{}", key, key))
+            .collect();
+        let updates: Vec<String> = columns
+            .iter()
+            .filter(|column| !self.keys.iter().any(|key| key == *column))
+            .map(|column| format!("TGT.{} = SRC.{}", column, column))
+            .collect();
+
+        if updates.is_empty() && !features.optional_merge_clauses() {
+            let msg = format!("merge into {} needs a non-key column on this server", self.table);
+            return Err(ErrorKind::Bind(msg).into());
+        }
+
+        let inserts: Vec<String> = columns.iter().map(|column| format!("SRC.{}", column)).collect();
+
+        let mut sql = format!("MERGE INTO {} TGT USING (SELECT {} FROM DUAL) SRC ON ({})",
+                              self.table,
+                              selects.join(", "),
+                              on.join(" AND "));
+        if !updates.is_empty() {
+            sql.push_str(&format!(" WHEN MATCHED THEN UPDATE SET {}", updates.join(", ")));
+        }
+        sql.push_str(&format!(" WHEN NOT MATCHED THEN INSERT ({}) VALUES ({})",
+                              columns.join(", "),
+                              inserts.join(", ")));
+        Ok(sql)
+    }
+}
@@ -10,6 +10,12 @@
 //! and dequeued using advanced queuing. They are created by calling the function
 //! `Connection::new_msg_props()` and are destroyed by releasing the last reference by calling the
 //! function `Properties::release()`.
+//!
+//! Messages are enqueued and dequeued with an object payload, using `Connection::enqueue_object()`
+//! and `Connection::deque_object()`. The JSON payloads of Oracle 21c queues need
+//! `dpiMsgProps_setPayloadJson()` and `dpiMsgProps_getPayloadJson()`, which were added in ODPI-C
+//! 4.0, so they are not supported by the ODPI-C 2.0 this crate binds to. Until then, a JSON
+//! document can be carried as a `VARCHAR2` or `CLOB` attribute of an object payload.
 use chrono::{DateTime, Utc};
 use error::{ErrorKind, Result};
 use odpi::{enums, externs};
@@ -19,7 +25,6 @@ use std::ptr;
 use util::ODPIStr;
 
 /// ODPI-C Message Props wrapper.
-#[derive(Clone)]
 pub struct Properties {
     /// The ODPI-C MsgProps pointer.
     inner: *mut ODPIMsgProps,
@@ -36,7 +41,10 @@ impl Properties {
     /// returned when the handle was created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiMsgProps_addRef(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(added, "MsgProps", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::MsgProps("dpiMsgProps_addRef".to_string()))
     }
 
@@ -160,7 +168,10 @@ impl Properties {
     /// properties is freed.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiMsgProps_release(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(released, "MsgProps", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::MsgProps("dpiMsgProps_release".to_string()))
     }
 
@@ -227,10 +238,27 @@ impl Properties {
                  Ok(()),
                  ErrorKind::MsgProps("dpiMsgProps_setPriority".to_string()))
     }
+
+    /// Adds a reference to the message properties and returns a new handle to it, which must be
+    /// released separately. Unlike `clone()`, which panics if the reference cannot be added, this
+    /// returns the error.
+    pub fn try_clone(&self) -> Result<Properties> {
+        self.add_ref()?;
+        Ok(Properties { inner: self.inner })
+    }
+}
+
+/// Cloning adds a reference to the message properties, so the clone must be released separately.
+/// Panics if the reference cannot be added; use `try_clone()` to handle the error.
+impl Clone for Properties {
+    fn clone(&self) -> Properties {
+        self.try_clone().expect("Properties::clone: dpiMsgProps_addRef failed")
+    }
 }
 
 impl From<*mut ODPIMsgProps> for Properties {
     fn from(inner: *mut ODPIMsgProps) -> Properties {
+        track_ref!(wrapped, "MsgProps", inner);
         Properties { inner: inner }
     }
 }
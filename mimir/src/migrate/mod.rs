@@ -0,0 +1,377 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Schema migrations read from a directory of SQL scripts. Each script is named
+//! `<version>_<name>.sql`, e.g. `0001_create_users.sql`, and scripts are applied in version order.
+//! The applied versions are recorded, with a checksum of the script, in a registry table that is
+//! created on first use.
+//!
+//! Each script is run with `split_script()`, stopping at the first failing statement. The
+//! statements of a script and its registry row are committed together, but Oracle commits DDL
+//! implicitly, so a script that fails after running DDL is only partially rolled back. Keeping DDL
+//! and DML in separate scripts keeps the DML transactional.
+use connection::Connection;
+use connection::script::split_script;
+use data::Data;
+use error::{ErrorKind, Result};
+use odpi::flags;
+use sql::check_identifier;
+use statement::binder::BindValue;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// The default name of the registry table.
+pub const DEFAULT_TABLE: &str = "MIMIR_MIGRATIONS";
+
+/// A migration script found in a migrations directory.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Migration {
+    /// The version, parsed from the leading digits of the file name.
+    version: i64,
+    /// The name, the rest of the file name without the `.sql` extension.
+    name: String,
+    /// The path to the script.
+    path: PathBuf,
+    /// The contents of the script.
+    script: String,
+}
+
+impl Migration {
+    /// Reads the migration script at the given path. The file name must have the form
+    /// `<version>_<name>.sql`.
+    ///
+    /// * `path` - the path to the script.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Migration> {
+        let path = path.as_ref();
+        let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+        let (version, name) = parse_file_name(file_name)?;
+        let mut script = String::new();
+        File::open(path)?.read_to_string(&mut script)?;
+
+        Ok(Migration {
+               version: version,
+               name: name.to_string(),
+               path: path.to_path_buf(),
+               script: script,
+           })
+    }
+
+    /// Returns a checksum of the script, as 16 hex digits, used to detect scripts that were
+    /// changed after they were applied.
+    pub fn checksum(&self) -> String {
+        checksum(&self.script)
+    }
+
+    /// Get the `name` value.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the `path` value.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the `script` value.
+    pub fn script(&self) -> &str {
+        &self.script
+    }
+
+    /// Get the `version` value.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+}
+
+/// A migration recorded in the registry table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Applied {
+    /// The version of the migration.
+    pub version: i64,
+    /// The name of the migration.
+    pub name: String,
+    /// The checksum of the script when it was applied.
+    pub checksum: String,
+}
+
+/// The state of a migration, as reported by `Migrator::status()`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum State {
+    /// The migration has been applied and its script is unchanged.
+    Applied,
+    /// The migration has been applied but its script has changed since.
+    Changed,
+    /// The migration has been applied but its script is no longer in the directory.
+    Missing,
+    /// The migration has not been applied.
+    Pending,
+}
+
+/// The status of one migration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Status {
+    /// The version of the migration.
+    pub version: i64,
+    /// The name of the migration.
+    pub name: String,
+    /// The state of the migration.
+    pub state: State,
+}
+
+/// Applies the migrations in a directory to the schema of a connection.
+pub struct Migrator<'a> {
+    /// The connection the migrations are applied on.
+    conn: &'a Connection,
+    /// The name of the registry table.
+    table: String,
+}
+
+impl<'a> Migrator<'a> {
+    /// Create a new `Migrator` using the default registry table, `MIMIR_MIGRATIONS`.
+    ///
+    /// * `conn` - the connection the migrations are applied on.
+    pub fn new(conn: &'a Connection) -> Migrator<'a> {
+        Migrator {
+            conn: conn,
+            table: DEFAULT_TABLE.to_string(),
+        }
+    }
+
+    /// Create a new `Migrator` using the given registry table.
+    ///
+    /// * `conn` - the connection the migrations are applied on.
+    /// * `table` - the name of the registry table, a simple identifier as it is interpolated into
+    /// SQL.
+    pub fn with_table(conn: &'a Connection, table: &str) -> Result<Migrator<'a>> {
+        let table = check_identifier(table)
+            .map_err(|e| ErrorKind::Migration(format!("invalid registry table name: {}", e)))?;
+        Ok(Migrator {
+               conn: conn,
+               table: table,
+           })
+    }
+
+    /// Applies the pending migrations in the given directory, in version order, and returns the
+    /// migrations that were applied. Nothing is applied if a migration that was already applied
+    /// has changed or is missing, or if a pending migration has a lower version than the latest
+    /// applied one. A failing migration is rolled back as far as possible, and the error names the
+    /// script and the line of the failing statement.
+    ///
+    /// * `dir` - the directory holding the migration scripts.
+    pub fn apply<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<Migration>> {
+        let migrations = migrations(dir)?;
+        let applied = self.applied()?;
+        let latest = applied.last().map_or(i64::min_value(), |applied| applied.version);
+
+        for status in merge(&migrations, &applied) {
+            let err = match status.state {
+                State::Changed => "has changed since it was applied",
+                State::Missing => "was applied but its script is missing",
+                State::Pending if status.version < latest => "is older than the latest applied",
+                _ => continue,
+            };
+            let msg = format!("migration {} ({}) {}", status.version, status.name, err);
+            return Err(ErrorKind::Migration(msg).into());
+        }
+
+        let mut done = Vec::new();
+        for migration in migrations {
+            if applied.iter().all(|applied| applied.version != migration.version) {
+                self.apply_one(&migration)?;
+                done.push(migration);
+            }
+        }
+        Ok(done)
+    }
+
+    /// Returns the migrations recorded in the registry table, in version order. The table is
+    /// created if it does not exist.
+    pub fn applied(&self) -> Result<Vec<Applied>> {
+        self.init()?;
+
+        let sql = format!("select version, name, checksum from {} order by version",
+                          self.table);
+        let stmt = self.conn.prepare_stmt(Some(&sql), None, false)?;
+        let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT).and_then(|_| {
+            let mut applied = Vec::new();
+            while stmt.fetch()?.0 {
+                let value = |pos| -> Result<Data> { Ok(stmt.get_query_value(pos)?.1) };
+                applied.push(Applied {
+                                 version: value(1)?.get_int64(),
+                                 name: value(2)?.get_string(),
+                                 checksum: value(3)?.get_string(),
+                             });
+            }
+            Ok(applied)
+        });
+        stmt.release()?;
+        res
+    }
+
+    /// Returns the state of every migration, either found in the given directory or recorded in
+    /// the registry table, in version order.
+    ///
+    /// * `dir` - the directory holding the migration scripts.
+    pub fn status<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<Status>> {
+        let migrations = migrations(dir)?;
+        let applied = self.applied()?;
+        Ok(merge(&migrations, &applied))
+    }
+
+    /// Runs the statements of a migration and records it in the registry table, committing both
+    /// together. On failure the transaction is rolled back.
+    fn apply_one(&self, migration: &Migration) -> Result<()> {
+        let res = self.run(migration).and_then(|_| {
+            let sql = format!("insert into {} (version, name, checksum) \
+                               values (:version, :name, :checksum)",
+                              self.table);
+            let stmt = self.conn.prepare_stmt(Some(&sql), None, false)?;
+            let res = stmt.binder()
+                .set("version", migration.version)?
+                .set("name", migration.name())?
+                .set("checksum", migration.checksum())?
+                .execute();
+            stmt.release()?;
+            res
+        });
+
+        match res {
+            Ok(_) => self.conn.commit(),
+            Err(e) => {
+                self.conn.rollback()?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Creates the registry table if it does not exist.
+    fn init(&self) -> Result<()> {
+        let sql = "select count(*) from user_tables where table_name = :name";
+        let count: i64 = self.conn
+            .query_scalar(sql, &[("name", BindValue::from(&self.table[..]))])?;
+
+        if count > 0 {
+            Ok(())
+        } else {
+            let sql = format!("create table {} (\
+                               version number(18) primary key, \
+                               name varchar2(256) not null, \
+                               checksum varchar2(16) not null, \
+                               applied_on timestamp default systimestamp not null)",
+                              self.table);
+            let stmt = self.conn.prepare_stmt(Some(&sql), None, false)?;
+            let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT);
+            stmt.release()?;
+            res.map(|_| ())
+        }
+    }
+
+    /// Runs the statements of a migration, stopping at the first failure.
+    fn run(&self, migration: &Migration) -> Result<()> {
+        for statement in split_script(migration.script()) {
+            let stmt = self.conn.prepare_stmt(Some(&statement.sql), None, false)?;
+            let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT);
+            stmt.release()?;
+
+            if let Err(e) = res {
+                let msg = format!("{}: line {}: {}",
+                                  migration.path.display(),
+                                  statement.line,
+                                  e);
+                return Err(ErrorKind::Migration(msg).into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the migrations in the given directory, in version order. Files without the `.sql`
+/// extension are ignored.
+///
+/// * `dir` - the directory holding the migration scripts.
+pub fn migrations<P: AsRef<Path>>(dir: P) -> Result<Vec<Migration>> {
+    let mut migrations = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().map_or(false, |ext| ext == "sql") {
+            migrations.push(Migration::from_file(path)?);
+        }
+    }
+
+    migrations.sort_by_key(|migration| migration.version);
+    for pair in migrations.windows(2) {
+        if pair[0].version == pair[1].version {
+            let msg = format!("duplicate version {} in '{}' and '{}'",
+                              pair[0].version,
+                              pair[0].path.display(),
+                              pair[1].path.display());
+            return Err(ErrorKind::Migration(msg).into());
+        }
+    }
+
+    Ok(migrations)
+}
+
+/// Returns the FNV-1a hash of the given text, as 16 hex digits.
+fn checksum(text: &str) -> String {
+    let hash = text.bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64,
+              |hash, b| (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3));
+    format!("{:016x}", hash)
+}
+
+/// Merges the migrations found in the directory with those recorded in the registry table.
+fn merge(migrations: &[Migration], applied: &[Applied]) -> Vec<Status> {
+    let mut statuses: Vec<Status> = migrations
+        .iter()
+        .map(|migration| {
+            let state = match applied.iter().find(|a| a.version == migration.version) {
+                Some(a) if a.checksum == migration.checksum() => State::Applied,
+                Some(_) => State::Changed,
+                None => State::Pending,
+            };
+            Status {
+                version: migration.version,
+                name: migration.name.clone(),
+                state: state,
+            }
+        })
+        .collect();
+
+    for a in applied {
+        if migrations.iter().all(|migration| migration.version != a.version) {
+            statuses.push(Status {
+                              version: a.version,
+                              name: a.name.clone(),
+                              state: State::Missing,
+                          });
+        }
+    }
+
+    statuses.sort_by_key(|status| status.version);
+    statuses
+}
+
+/// Splits a file name of the form `<version>_<name>.sql` into its version and name.
+fn parse_file_name(file_name: &str) -> Result<(i64, &str)> {
+    let stem = file_name.trim_right_matches(".sql");
+    let digits = stem.chars().take_while(|c| c.is_ascii_digit()).count();
+    let name = stem[digits..].trim_left_matches('_');
+
+    match stem[..digits].parse() {
+        Ok(version) if digits < stem.len() && stem[digits..].starts_with('_') => {
+            Ok((version, name))
+        }
+        _ => {
+            let msg = format!("'{}' is not named '<version>_<name>.sql'", file_name);
+            Err(ErrorKind::Migration(msg).into())
+        }
+    }
+}
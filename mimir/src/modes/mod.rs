@@ -0,0 +1,187 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Typed builders over the ODPI-C mode flags. Each converts into its flags type, so it can be
+//! given wherever the raw flags are accepted, e.g.
+//! `stmt.execute(ExecMode::default().commit_on_success())` or
+//! `ccp.set_auth_mode(AuthMode::sysdba())`.
+//!
+//! Flags that exclude each other, such as the administrative privileges of `AuthMode` or the close
+//! modes of `ConnCloseMode`, are chosen with constructors. Flags that can be combined are added
+//! with methods.
+use odpi::flags::{self, ODPIAuthMode, ODPIConnCloseMode, ODPICreateMode, ODPIExecMode,
+                  ODPIPoolCloseMode, ODPISubscrQOS};
+
+/// Generates a mode type wrapping a flags type, with its `Default` and its conversions.
+macro_rules! mode {
+    ($(#[$attr:meta])* $name:ident, $flags:ident, $default:ident) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub struct $name($flags);
+
+        impl $name {
+            /// Get the flags of the mode.
+            pub fn bits(&self) -> $flags {
+                self.0
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> $name {
+                $name(flags::$default)
+            }
+        }
+
+        impl From<$name> for $flags {
+            fn from(mode: $name) -> $flags {
+                mode.0
+            }
+        }
+    }
+}
+
+mode!(
+    /// The mode used to authorize a connection, passed to `ConnCreate::set_auth_mode()`.
+    AuthMode, ODPIAuthMode, DPI_MODE_AUTH_DEFAULT
+);
+
+impl AuthMode {
+    /// Connect with the SYSDBA privilege.
+    pub fn sysdba() -> AuthMode {
+        AuthMode(flags::DPI_MODE_AUTH_SYSDBA)
+    }
+
+    /// Connect with the SYSOPER privilege.
+    pub fn sysoper() -> AuthMode {
+        AuthMode(flags::DPI_MODE_AUTH_SYSOPER)
+    }
+
+    /// Connect with the SYSASM privilege.
+    pub fn sysasm() -> AuthMode {
+        AuthMode(flags::DPI_MODE_AUTH_SYSASM)
+    }
+
+    /// Make a preliminary connection, as needed to start up a database that is down, e.g.
+    /// `AuthMode::sysdba().prelim()`.
+    pub fn prelim(self) -> AuthMode {
+        AuthMode(self.0 | flags::DPI_MODE_AUTH_PRELIM)
+    }
+}
+
+mode!(
+    /// The mode used to close a connection, passed to `Connection::close()`.
+    ConnCloseMode, ODPIConnCloseMode, DPI_MODE_CONN_CLOSE_DEFAULT
+);
+
+impl ConnCloseMode {
+    /// Drop the session from its pool rather than returning it for reuse.
+    pub fn drop_session() -> ConnCloseMode {
+        ConnCloseMode(flags::DPI_MODE_CONN_CLOSE_DROP)
+    }
+
+    /// Return the session to its pool with the tag given to `Connection::close()`.
+    pub fn retag() -> ConnCloseMode {
+        ConnCloseMode(flags::DPI_MODE_CONN_CLOSE_RETAG)
+    }
+}
+
+mode!(
+    /// The mode used to create a connection or pool, passed to `CommonCreate::set_create_mode()`.
+    CreateMode, ODPICreateMode, DPI_MODE_CREATE_DEFAULT
+);
+
+impl CreateMode {
+    /// Protect the internal OCI structures with a mutex, so handles can be used from more than one
+    /// thread.
+    pub fn threaded(self) -> CreateMode {
+        CreateMode(self.0 | flags::DPI_MODE_CREATE_THREADED)
+    }
+
+    /// Enable events, as needed for subscriptions and continuous query notification.
+    pub fn events(self) -> CreateMode {
+        CreateMode(self.0 | flags::DPI_MODE_CREATE_EVENTS)
+    }
+}
+
+mode!(
+    /// The mode used to execute a statement, passed to `Statement::execute()`.
+    ExecMode, ODPIExecMode, DPI_MODE_EXEC_DEFAULT
+);
+
+impl ExecMode {
+    /// Only describe the query, so its columns are known without executing it.
+    pub fn describe_only() -> ExecMode {
+        ExecMode(flags::DPI_MODE_EXEC_DESCRIBE_ONLY)
+    }
+
+    /// Only parse the statement, so errors in it are found without executing it.
+    pub fn parse_only() -> ExecMode {
+        ExecMode(flags::DPI_MODE_EXEC_PARSE_ONLY)
+    }
+
+    /// Commit the transaction if the execution succeeds.
+    pub fn commit_on_success(self) -> ExecMode {
+        ExecMode(self.0 | flags::DPI_MODE_EXEC_COMMIT_ON_SUCCESS)
+    }
+
+    /// Let the iterations of `Statement::execute_many()` succeed even if some of them fail.
+    pub fn batch_errors(self) -> ExecMode {
+        ExecMode(self.0 | flags::DPI_MODE_EXEC_BATCH_ERRORS)
+    }
+
+    /// Record the number of rows affected by each iteration of `Statement::execute_many()`.
+    pub fn array_dml_row_counts(self) -> ExecMode {
+        ExecMode(self.0 | flags::DPI_MODE_EXEC_ARRAY_DML_ROWCOUNTS)
+    }
+}
+
+mode!(
+    /// The mode used to close a pool, passed to `Pool::close()`.
+    PoolCloseMode, ODPIPoolCloseMode, DPI_MODE_POOL_CLOSE_DEFAULT
+);
+
+impl PoolCloseMode {
+    /// Close the pool even if sessions are still busy.
+    pub fn force() -> PoolCloseMode {
+        PoolCloseMode(flags::DPI_MODE_POOL_CLOSE_FORCE)
+    }
+}
+
+mode!(
+    /// The quality of service of a subscription, passed to `SubscrCreate::set_qos()`.
+    SubscrQos, ODPISubscrQOS, DPI_SUBSCR_QOS_NONE
+);
+
+impl SubscrQos {
+    /// Send the notifications reliably, so they are not lost if the database fails.
+    pub fn reliable(self) -> SubscrQos {
+        SubscrQos(self.0 | flags::DPI_SUBSCR_QOS_RELIABLE)
+    }
+
+    /// Unregister the subscription after the first notification.
+    pub fn deregister_on_notify(self) -> SubscrQos {
+        SubscrQos(self.0 | flags::DPI_SUBSCR_QOS_DEREG_NFY)
+    }
+
+    /// Include the rowids of the changed rows in the notifications.
+    pub fn rowids(self) -> SubscrQos {
+        SubscrQos(self.0 | flags::DPI_SUBSCR_QOS_ROWIDS)
+    }
+
+    /// Notify when the results of the registered queries change, rather than on any change to the
+    /// tables they read.
+    pub fn query(self) -> SubscrQos {
+        SubscrQos(self.0 | flags::DPI_SUBSCR_QOS_QUERY)
+    }
+
+    /// Perform query notification in best effort mode, which may notify when the results have
+    /// not in fact changed. This is needed for queries too complex for guaranteed mode.
+    pub fn best_effort(self) -> SubscrQos {
+        SubscrQos(self.0 | flags::DPI_SUBSCR_QOS_BEST_EFFORT)
+    }
+}
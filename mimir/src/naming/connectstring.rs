@@ -0,0 +1,245 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A builder for connect descriptors. ODPI-C 2.0 has no create parameters for the Oracle Net
+//! options, so dead connection detection (`EXPIRE_TIME`), out-of-band breaks (`DISABLE_OOB`) and
+//! network compression (`COMPRESSION`) are set in the descriptor itself. The client libraries
+//! must support the options used: 18c for `EXPIRE_TIME` and `DISABLE_OOB` in a descriptor, 12c for
+//! compression.
+//!
+//! For RAC and Data Guard, further listeners can be added with `ConnectString::address()`. The
+//! addresses are then put in an `ADDRESS_LIST`, which the client tries in order, moving on to the
+//! next address if one cannot be reached, or in random order with `load_balance()`.
+//! `retry_count()` and `retry_delay()` have the whole list tried again when no address can be
+//! reached, e.g. while a service is being relocated; `RETRY_DELAY` needs 18c client libraries.
+//!
+//! ```ignore
+//! let descriptor = ConnectString::new("rac1.example.com", 1521, "sales")
+//!     .address("rac2.example.com", 1521)
+//!     .load_balance()
+//!     .retry_count(3)
+//!     .retry_delay(Duration::from_secs(5))
+//!     .build()?;
+//! ```
+use error::{ErrorKind, Result};
+use naming::validate;
+use std::fmt;
+use std::time::Duration;
+
+/// The network compression level.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Compression {
+    /// Compression with a lower ratio and lower CPU usage.
+    Low,
+    /// Compression with a higher ratio and higher CPU usage.
+    High,
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Compression::Low => write!(f, "LOW"),
+            Compression::High => write!(f, "HIGH"),
+        }
+    }
+}
+
+/// Builds a connect descriptor for a service, reached through one or more listener addresses.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConnectString {
+    /// The host names or IP addresses and ports of the listeners, in the order they are tried.
+    addresses: Vec<(String, u16)>,
+    /// Try the addresses in random order, spreading connections across the listeners.
+    load_balance: bool,
+    /// Move on to the next address if one cannot be reached. None leaves the client default, which
+    /// is on for an address list.
+    failover: Option<bool>,
+    /// The number of times the address list is tried again when no address can be reached.
+    retry_count: Option<u32>,
+    /// The delay, in seconds, before the address list is tried again.
+    retry_delay: Option<u64>,
+    /// The service name of the database.
+    service_name: String,
+    /// Use TCPS rather than TCP.
+    tcps: bool,
+    /// The interval, in minutes, between probes sent to detect dead connections.
+    expire_time: Option<u64>,
+    /// Disable out-of-band breaks.
+    disable_oob: bool,
+    /// The network compression level.
+    compression: Option<Compression>,
+}
+
+impl ConnectString {
+    /// Create a new `ConnectString` for the given listener and service, with no options set.
+    ///
+    /// * `host` - the host name or IP address of the listener.
+    /// * `port` - the port of the listener, usually 1521.
+    /// * `service_name` - the service name of the database.
+    pub fn new(host: &str, port: u16, service_name: &str) -> ConnectString {
+        ConnectString {
+            addresses: vec![(host.to_string(), port)],
+            load_balance: false,
+            failover: None,
+            retry_count: None,
+            retry_delay: None,
+            service_name: service_name.to_string(),
+            tcps: false,
+            expire_time: None,
+            disable_oob: false,
+            compression: None,
+        }
+    }
+
+    /// Add the address of another listener of the service, tried after the ones already added
+    /// unless `load_balance()` is set.
+    ///
+    /// * `host` - the host name or IP address of the listener.
+    /// * `port` - the port of the listener, usually 1521.
+    pub fn address(mut self, host: &str, port: u16) -> ConnectString {
+        self.addresses.push((host.to_string(), port));
+        self
+    }
+
+    /// Returns the connect descriptor, on a single line. Returns an error if a host or the service
+    /// name is empty or contains characters that are not allowed in a descriptor value.
+    pub fn build(&self) -> Result<String> {
+        for &(ref host, _) in &self.addresses {
+            check_value("host", host)?;
+        }
+        check_value("service name", &self.service_name)?;
+
+        let mut descriptor = String::from("(DESCRIPTION=");
+
+        if let Some(count) = self.retry_count {
+            descriptor.push_str(&format!("(RETRY_COUNT={})", count));
+        }
+
+        if let Some(secs) = self.retry_delay {
+            descriptor.push_str(&format!("(RETRY_DELAY={})", secs));
+        }
+
+        if let Some(minutes) = self.expire_time {
+            descriptor.push_str(&format!("(EXPIRE_TIME={})", minutes));
+        }
+
+        if self.disable_oob {
+            descriptor.push_str("(DISABLE_OOB=ON)");
+        }
+
+        if let Some(level) = self.compression {
+            descriptor.push_str(&format!("(COMPRESSION=ON)(COMPRESSION_LEVELS=(LEVEL={}))",
+                                         level));
+        }
+
+        let list = self.addresses.len() > 1 || self.load_balance || self.failover.is_some();
+        if list {
+            descriptor.push_str("(ADDRESS_LIST=");
+            if self.load_balance {
+                descriptor.push_str("(LOAD_BALANCE=ON)");
+            }
+            if let Some(failover) = self.failover {
+                descriptor.push_str(if failover {
+                                        "(FAILOVER=ON)"
+                                    } else {
+                                        "(FAILOVER=OFF)"
+                                    });
+            }
+        }
+
+        for &(ref host, port) in &self.addresses {
+            descriptor.push_str(&format!("(ADDRESS=(PROTOCOL={})(HOST={})(PORT={}))",
+                                         if self.tcps { "TCPS" } else { "TCP" },
+                                         host,
+                                         port));
+        }
+
+        if list {
+            descriptor.push(')');
+        }
+        descriptor.push_str(&format!("(CONNECT_DATA=(SERVICE_NAME={})))", self.service_name));
+        validate(&descriptor)
+    }
+
+    /// Enable network compression at the given level. Compression takes effect only if the server
+    /// also enables it (`SQLNET.COMPRESSION=on` in its sqlnet.ora).
+    ///
+    /// * `level` - the compression level.
+    pub fn compression(mut self, level: Compression) -> ConnectString {
+        self.compression = Some(level);
+        self
+    }
+
+    /// Disable out-of-band breaks, so that breaks are sent in-band. Needed when a firewall or load
+    /// balancer between the client and the database drops urgent data.
+    pub fn disable_oob(mut self) -> ConnectString {
+        self.disable_oob = true;
+        self
+    }
+
+    /// Set whether the client moves on to the next address when one cannot be reached. It does by
+    /// default when there are several addresses.
+    ///
+    /// * `enabled` - move on to the next address?
+    pub fn failover(mut self, enabled: bool) -> ConnectString {
+        self.failover = Some(enabled);
+        self
+    }
+
+    /// Try the addresses in random order rather than in the order they were added, spreading
+    /// connections across the listeners.
+    pub fn load_balance(mut self) -> ConnectString {
+        self.load_balance = true;
+        self
+    }
+
+    /// Set the number of times the address list is tried again when no address can be reached.
+    ///
+    /// * `count` - the number of retries.
+    pub fn retry_count(mut self, count: u32) -> ConnectString {
+        self.retry_count = Some(count);
+        self
+    }
+
+    /// Set the delay before the address list is tried again, when `retry_count()` is set. The
+    /// delay is rounded up to whole seconds.
+    ///
+    /// * `delay` - the time between attempts.
+    pub fn retry_delay(mut self, delay: Duration) -> ConnectString {
+        self.retry_delay = Some(delay.as_secs() + if delay.subsec_nanos() > 0 { 1 } else { 0 });
+        self
+    }
+
+    /// Set the interval between probes used to detect dead connections. The probes also keep idle
+    /// connections from being dropped by load balancers with an idle timeout. The interval is
+    /// rounded up to whole minutes.
+    ///
+    /// * `interval` - the time between probes.
+    pub fn expire_time(mut self, interval: Duration) -> ConnectString {
+        let secs = interval.as_secs() + if interval.subsec_nanos() > 0 { 1 } else { 0 };
+        self.expire_time = Some((secs + 59) / 60);
+        self
+    }
+
+    /// Use TCPS rather than TCP to connect to the listener.
+    pub fn tcps(mut self) -> ConnectString {
+        self.tcps = true;
+        self
+    }
+}
+
+/// Checks that a descriptor value is not empty and holds no parentheses, '=' or whitespace.
+fn check_value(name: &str, value: &str) -> Result<()> {
+    let invalid = |c: char| c == '(' || c == ')' || c == '=' || c.is_whitespace();
+
+    if value.is_empty() || value.contains(invalid) {
+        Err(ErrorKind::Naming(format!("invalid {} '{}'", name, value)).into())
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Naming methods are used to turn what an application knows about a database (a TNS alias, an
+//! easy connect string or a full connect descriptor) into a validated connect string that can be
+//! passed to `Connection::create()` or `Pool::create()`. `ConnectString` builds a descriptor with
+//! Oracle Net options such as dead connection detection and network compression, and with the
+//! address lists used to fail over and balance load across RAC listeners.
+//!
+//! Directory naming, where aliases are looked up in an LDAP server configured in ldap.ora, is not
+//! supported. Such aliases can be resolved outside of `mimir` and passed as `NamingMethod::Direct`.
+use error::{ErrorKind, Result};
+use std::collections::HashMap;
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub mod connectstring;
+
+/// The name of the file holding the TNS aliases.
+const TNSNAMES_ORA: &str = "tnsnames.ora";
+
+/// The method used to resolve a connect string.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NamingMethod {
+    /// The connect string is used as given, e.g. an easy connect string such as
+    /// "//host:1521/service" or a full connect descriptor such as "(DESCRIPTION=...)".
+    Direct(String),
+    /// The alias is looked up in the given tnsnames.ora file.
+    TnsNames {
+        /// The path to the tnsnames.ora file.
+        path: PathBuf,
+        /// The alias to resolve.
+        alias: String,
+    },
+    /// The alias is looked up in the tnsnames.ora file found in the directory named by the
+    /// TNS_ADMIN environment variable, falling back to $ORACLE_HOME/network/admin. See
+    /// `TnsNames::from_dir()` to read the file of a given directory instead.
+    TnsAdmin(String),
+}
+
+impl NamingMethod {
+    /// Resolves the naming method to a connect string. Connect descriptors are validated (the
+    /// parentheses must be balanced) and returned on a single line.
+    pub fn resolve(&self) -> Result<String> {
+        match *self {
+            NamingMethod::Direct(ref connect_string) => validate(connect_string),
+            NamingMethod::TnsNames { ref path, ref alias } => {
+                TnsNames::from_file(path)?.resolve(alias)
+            }
+            NamingMethod::TnsAdmin(ref alias) => {
+                TnsNames::from_dir(tns_admin_dir()?)?.resolve(alias)
+            }
+        }
+    }
+}
+
+/// The aliases parsed from a tnsnames.ora file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TnsNames {
+    /// The connect descriptors keyed by upper case alias.
+    entries: HashMap<String, String>,
+}
+
+impl TnsNames {
+    /// Reads and parses the given tnsnames.ora file.
+    ///
+    /// * `path` - the path to the tnsnames.ora file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<TnsNames> {
+        let mut contents = String::new();
+        File::open(path)?.read_to_string(&mut contents)?;
+        TnsNames::parse(&contents)
+    }
+
+    /// Reads and parses the tnsnames.ora file of the given directory, e.g. a TNS_ADMIN directory.
+    ///
+    /// * `dir` - the directory holding the tnsnames.ora file.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<TnsNames> {
+        TnsNames::from_file(dir.as_ref().join(TNSNAMES_ORA))
+    }
+
+    /// Parses the contents of a tnsnames.ora file. Comments start with '#' and run to the end of
+    /// the line. Each entry has the form `alias[, alias...] = (DESCRIPTION = ...)` and may span
+    /// several lines.
+    ///
+    /// * `contents` - the contents of the tnsnames.ora file.
+    pub fn parse(contents: &str) -> Result<TnsNames> {
+        let stripped = contents
+            .lines()
+            .map(|line| match line.find('#') {
+                     Some(idx) => &line[..idx],
+                     None => line,
+                 })
+            .collect::<Vec<&str>>()
+            .join("\n");
+        let mut entries = HashMap::new();
+        let mut rest = stripped.trim_left();
+
+        while !rest.is_empty() {
+            let eq_idx = match rest.find('=') {
+                Some(idx) => idx,
+                None => {
+                    let err = format!("tnsnames: expected '=' after '{}'", rest.trim());
+                    return Err(ErrorKind::Naming(err).into());
+                }
+            };
+            let aliases = rest[..eq_idx]
+                .split(',')
+                .map(|alias| alias.trim().to_uppercase())
+                .collect::<Vec<String>>();
+
+            if aliases.iter().any(|alias| alias.is_empty() || alias.contains('(')) {
+                let err = format!("tnsnames: invalid alias '{}'", rest[..eq_idx].trim());
+                return Err(ErrorKind::Naming(err).into());
+            }
+
+            rest = rest[eq_idx + 1..].trim_left();
+            let value_len = if rest.starts_with('(') {
+                balanced_len(rest)?
+            } else {
+                rest.find('\n').unwrap_or_else(|| rest.len())
+            };
+            let descriptor = validate(&rest[..value_len])?;
+
+            for alias in aliases {
+                entries.insert(alias, descriptor.clone());
+            }
+
+            rest = rest[value_len..].trim_left();
+        }
+
+        Ok(TnsNames { entries: entries })
+    }
+
+    /// Returns the connect descriptor for the given alias, if present. Aliases are matched case
+    /// insensitively.
+    ///
+    /// * `alias` - the alias to look up.
+    pub fn get(&self, alias: &str) -> Option<&str> {
+        self.entries.get(&alias.trim().to_uppercase()).map(|d| &d[..])
+    }
+
+    /// Returns the connect descriptor for the given alias, or an error if the alias is unknown.
+    ///
+    /// * `alias` - the alias to look up.
+    pub fn resolve(&self, alias: &str) -> Result<String> {
+        match self.get(alias) {
+            Some(descriptor) => Ok(descriptor.to_string()),
+            None => Err(ErrorKind::Naming(format!("tnsnames: unknown alias '{}'", alias)).into()),
+        }
+    }
+
+    /// Returns the aliases, in upper case.
+    pub fn aliases(&self) -> Vec<&str> {
+        self.entries.keys().map(|k| &k[..]).collect()
+    }
+}
+
+/// Returns the directory of the tnsnames.ora file named by the TNS_ADMIN or ORACLE_HOME
+/// environment variables.
+fn tns_admin_dir() -> Result<PathBuf> {
+    if let Ok(tns_admin) = env::var("TNS_ADMIN") {
+        Ok(PathBuf::from(tns_admin))
+    } else {
+        let oracle_home = env::var("ORACLE_HOME")?;
+        Ok(Path::new(&oracle_home).join("network").join("admin"))
+    }
+}
+
+/// Returns the length, in bytes, of the balanced parenthesized expression at the start of `s`.
+fn balanced_len(s: &str) -> Result<usize> {
+    let mut depth = 0;
+
+    for (idx, ch) in s.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(idx + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Err(ErrorKind::Naming(format!("unbalanced parentheses in '{}'", s.trim())).into())
+}
+
+/// Validates a connect string and returns it on a single line. Descriptors must consist of a
+/// single balanced parenthesized expression; the whitespace around parentheses and '=' is removed.
+fn validate(connect_string: &str) -> Result<String> {
+    let trimmed = connect_string.trim();
+
+    if trimmed.is_empty() {
+        Err(ErrorKind::Naming("empty connect string".to_string()).into())
+    } else if trimmed.starts_with('(') {
+        if balanced_len(trimmed)? != trimmed.len() {
+            let err = format!("trailing characters after descriptor '{}'", trimmed);
+            return Err(ErrorKind::Naming(err).into());
+        }
+
+        let mut descriptor = String::with_capacity(trimmed.len());
+        for token in trimmed.split_whitespace() {
+            let joins = descriptor.ends_with(|c| c == '(' || c == ')' || c == '=') ||
+                        token.starts_with(|c| c == '(' || c == ')' || c == '=');
+            if !descriptor.is_empty() && !joins {
+                descriptor.push(' ');
+            }
+            descriptor.push_str(token);
+        }
+        Ok(descriptor)
+    } else if trimmed.contains(char::is_whitespace) || trimmed.contains(|c| c == '(' || c == ')') {
+        Err(ErrorKind::Naming(format!("invalid connect string '{}'", trimmed)).into())
+    } else {
+        Ok(trimmed.to_string())
+    }
+}
@@ -22,7 +22,7 @@ use std::ptr;
 
 /// This structure represents instances of the types created by the SQL command CREATE OR REPLACE
 /// TYPE
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct Object {
     /// The ODPI-C Object pointer.
     pub inner: *mut ODPIObject,
@@ -40,16 +40,16 @@ impl Object {
     /// created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiObject_addRef(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(added, "Object", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Object("dpiObject_addRef".to_string()))
     }
 
-    /// Sets the value of the element found at the specified index.
-    pub fn append_element(&self,
-                          native_type: enums::ODPINativeTypeNum,
-                          data: &mut structs::ODPIData)
-                          -> Result<()> {
-        try_dpi!(externs::dpiObject_appendElement(self.inner, native_type, data),
+    /// Appends an element with the specified value to the collection.
+    pub fn append_element(&self, native_type: enums::ODPINativeTypeNum, data: &Data) -> Result<()> {
+        try_dpi!(externs::dpiObject_appendElement(self.inner, native_type, data.inner()),
                  Ok(()),
                  ErrorKind::Object("dpiObject_appendElement".to_string()))
     }
@@ -60,7 +60,10 @@ impl Object {
         let mut copied = ptr::null_mut();
 
         try_dpi!(externs::dpiObject_copy(self.inner, &mut copied),
-                 Ok(copied.into()),
+                 {
+                     track_ref!(created, "Object", copied);
+                     Ok(copied.into())
+                 },
                  ErrorKind::Object("dpiObject_copy".to_string()))
     }
 
@@ -72,18 +75,19 @@ impl Object {
                  ErrorKind::Object("dpiObject_deleteElementByIndex".to_string()))
     }
 
-    /// Returns the value of one of the object's attributes.
+    /// Returns the value of one of the object's attributes, as the given native type, e.g. the
+    /// attribute's `default_native_type_num()`.
     pub fn get_attribute_value(&self,
                                attr: &ObjectAttr,
-                               info: &structs::ODPIObjectAttrInfo)
-                               -> Result<structs::ODPIData> {
-        let mut data_blah: structs::ODPIData = Default::default();
+                               native_type: enums::ODPINativeTypeNum)
+                               -> Result<Data> {
+        let mut value: structs::ODPIData = Default::default();
 
         try_dpi!(externs::dpiObject_getAttributeValue(self.inner,
                                                       attr.inner(),
-                                                      info.default_native_type_num,
-                                                      &mut data_blah),
-                 Ok(data_blah),
+                                                      native_type,
+                                                      &mut value),
+                 Ok(value.into()),
                  ErrorKind::Object("dpiObject_getAttributeValue".to_string()))
     }
 
@@ -100,14 +104,14 @@ impl Object {
     pub fn get_element_value_by_index(&self,
                                       index: i32,
                                       native_type: enums::ODPINativeTypeNum)
-                                      -> Result<structs::ODPIData> {
+                                      -> Result<Data> {
         let mut value: structs::ODPIData = Default::default();
 
         try_dpi!(externs::dpiObject_getElementValueByIndex(self.inner,
                                                            index,
                                                            native_type,
                                                            &mut value),
-                 Ok(value),
+                 Ok(value.into()),
                  ErrorKind::Object("dpiObject_getElementExistsByIndex".to_string()))
     }
 
@@ -164,13 +168,16 @@ impl Object {
     /// and when this count reaches zero, the memory associated with the object is freed.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiObject_release(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(released, "Object", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Object("dpiObject_release".to_string()))
     }
 
     /// Sets the value of one of the object’s attributes.
     pub fn set_attribute_value(&self,
-                               attribute: ObjectAttr,
+                               attribute: &ObjectAttr,
                                native_type: enums::ODPINativeTypeNum,
                                value: Data)
                                -> Result<()> {
@@ -202,10 +209,27 @@ impl Object {
                  Ok(()),
                  ErrorKind::Object("dpiObject_trim".to_string()))
     }
+
+    /// Adds a reference to the object and returns a new handle to it, which must be released
+    /// separately. Unlike `clone()`, which panics if the reference cannot be added, this returns
+    /// the error.
+    pub fn try_clone(&self) -> Result<Object> {
+        self.add_ref()?;
+        Ok(Object { inner: self.inner })
+    }
+}
+
+/// Cloning adds a reference to the object, so the clone must be released separately. Panics if the
+/// reference cannot be added; use `try_clone()` to handle the error.
+impl Clone for Object {
+    fn clone(&self) -> Object {
+        self.try_clone().expect("Object::clone: dpiObject_addRef failed")
+    }
 }
 
 impl From<*mut ODPIObject> for Object {
     fn from(inner: *mut ODPIObject) -> Object {
+        track_ref!(wrapped, "Object", inner);
         Object { inner: inner }
     }
 }
@@ -0,0 +1,74 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Object attribute metadata, returned by `ObjectAttr::get_attr_info()`.
+use objecttype::ObjectType;
+use odpi::enums;
+use odpi::structs::ODPIObjectAttrInfo;
+use util::ODPIStr;
+
+/// Information about an attribute of an object type.
+pub struct Info {
+    /// The name of the attribute.
+    name: String,
+    /// The Oracle type of the attribute.
+    oracle_type_num: enums::ODPIOracleTypeNum,
+    /// The default native type of the attribute.
+    default_native_type_num: enums::ODPINativeTypeNum,
+    /// The object type of the attribute, if it refers to a named type.
+    object_type: Option<ObjectType>,
+}
+
+impl Info {
+    /// Get the `name` value.
+    ///
+    /// Specifies the name of the attribute.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the `oracle_type_num` value.
+    ///
+    /// Specifies the Oracle type of the attribute. It will be one of the values from the
+    /// enumeration `ODPIOracleTypeNum`.
+    pub fn oracle_type_num(&self) -> enums::ODPIOracleTypeNum {
+        self.oracle_type_num
+    }
+
+    /// Get the `default_native_type_num` value.
+    ///
+    /// Specifies the default native type of the attribute. It will be one of the values from the
+    /// enumeration `ODPINativeTypeNum`.
+    pub fn default_native_type_num(&self) -> enums::ODPINativeTypeNum {
+        self.default_native_type_num
+    }
+
+    /// Get the `object_type` value.
+    ///
+    /// Specifies the object type of the attribute, if the attribute refers to a named type. For
+    /// all other attributes the value is None.
+    pub fn object_type(&self) -> Option<&ObjectType> {
+        self.object_type.as_ref()
+    }
+}
+
+impl From<ODPIObjectAttrInfo> for Info {
+    fn from(inner: ODPIObjectAttrInfo) -> Info {
+        let type_info = inner.type_info();
+        Info {
+            name: ODPIStr::new(inner.name, inner.name_length).into(),
+            oracle_type_num: type_info.oracle_type_num,
+            default_native_type_num: type_info.default_native_type_num,
+            object_type: if type_info.object_type.is_null() {
+                None
+            } else {
+                Some(type_info.object_type.into())
+            },
+        }
+    }
+}
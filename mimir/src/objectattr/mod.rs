@@ -15,9 +15,13 @@ use odpi::externs;
 use odpi::opaque::ODPIObjectAttr;
 use odpi::structs::ODPIObjectAttrInfo;
 
-/// Object type handles are used to represent types such as those created by the SQL command CREATE
-/// OR REPLACE TYPE.
-#[derive(Clone, Debug)]
+pub mod info;
+
+use self::info::Info;
+
+/// Object attribute handles are used to represent the attributes of types such as those created by
+/// the SQL command CREATE OR REPLACE TYPE.
+#[derive(Debug)]
 pub struct ObjectAttr {
     /// A pointer to the opaque `ODPIObjectAttr`.
     inner: *mut ODPIObjectAttr,
@@ -39,13 +43,10 @@ impl ObjectAttr {
                  ErrorKind::ObjectType("dpiObjectAttr_addRef".to_string()))
     }
 
-    /// Returns information about the attribute.
-    pub fn get_info(&self) -> Result<ODPIObjectAttrInfo> {
-        let mut object_attr_info: ODPIObjectAttrInfo = Default::default();
-
-        try_dpi!(externs::dpiObjectAttr_getInfo(self.inner, &mut object_attr_info),
-                 Ok(object_attr_info),
-                 ErrorKind::ObjectType("dpiObjectAttr_getInfo".to_string()))
+    /// Returns information about the attribute as an `Info` struct, decoded from the ODPI-C
+    /// structure returned by `raw::object_attr_info()`.
+    pub fn get_attr_info(&self) -> Result<Info> {
+        info(self).map(Info::from)
     }
 
     /// Releases a reference to the attribute. A count of the references to the attribute is
@@ -56,6 +57,22 @@ impl ObjectAttr {
                  Ok(()),
                  ErrorKind::ObjectType("dpiObjectAttr_release".to_string()))
     }
+
+    /// Adds a reference to the attribute and returns a new handle to it, which must be released
+    /// separately. Unlike `clone()`, which panics if the reference cannot be added, this returns
+    /// the error.
+    pub fn try_clone(&self) -> Result<ObjectAttr> {
+        self.add_ref()?;
+        Ok(ObjectAttr { inner: self.inner })
+    }
+}
+
+/// Cloning adds a reference to the attribute, so the clone must be released separately. Panics if
+/// the reference cannot be added; use `try_clone()` to handle the error.
+impl Clone for ObjectAttr {
+    fn clone(&self) -> ObjectAttr {
+        self.try_clone().expect("ObjectAttr::clone: dpiObjectAttr_addRef failed")
+    }
 }
 
 impl From<*mut ODPIObjectAttr> for ObjectAttr {
@@ -63,3 +80,13 @@ impl From<*mut ODPIObjectAttr> for ObjectAttr {
         ObjectAttr { inner: oot }
     }
 }
+
+/// Returns information about the attribute as the ODPI-C structure. See
+/// `raw::object_attr_info()`.
+pub fn info(attr: &ObjectAttr) -> Result<ODPIObjectAttrInfo> {
+    let mut object_attr_info: ODPIObjectAttrInfo = Default::default();
+
+    try_dpi!(externs::dpiObjectAttr_getInfo(attr.inner, &mut object_attr_info),
+             Ok(object_attr_info),
+             ErrorKind::ObjectType("dpiObjectAttr_getInfo".to_string()))
+}
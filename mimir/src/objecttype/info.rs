@@ -0,0 +1,114 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Object type metadata, returned by `ObjectType::get_type_info()`. For collections the type of
+//! the elements is available from `Info::element()`.
+use objecttype::ObjectType;
+use odpi::enums;
+use odpi::structs::ODPIObjectTypeInfo;
+use util::ODPIStr;
+
+/// This structure is used for passing information about an object type from ODPI-C.
+pub struct Info {
+    /// The ODPI-C object type info struct.
+    inner: ODPIObjectTypeInfo,
+}
+
+impl Info {
+    /// Create a new `Info` struct.
+    pub fn new(inner: ODPIObjectTypeInfo) -> Info {
+        Info { inner: inner }
+    }
+
+    /// Get the `schema` value.
+    ///
+    /// Specifies the schema which owns the object type.
+    pub fn schema(&self) -> String {
+        let schema_s = ODPIStr::new(self.inner.schema, self.inner.schema_length);
+        schema_s.into()
+    }
+
+    /// Get the `name` value.
+    ///
+    /// Specifies the name of the object type.
+    pub fn name(&self) -> String {
+        let name_s = ODPIStr::new(self.inner.name, self.inner.name_length);
+        name_s.into()
+    }
+
+    /// Get the `is_collection` value.
+    ///
+    /// Specifies if the object type is a collection or not.
+    pub fn is_collection(&self) -> bool {
+        self.inner.is_collection == 1
+    }
+
+    /// Get the `num_attributes` value.
+    ///
+    /// Specifies the number of attributes that the object type has.
+    pub fn num_attributes(&self) -> u16 {
+        self.inner.num_attributes
+    }
+
+    /// Get the element type information.
+    ///
+    /// Specifies the type of the elements in the collection if the object type refers to a
+    /// collection. For all other object types the value is None.
+    pub fn element(&self) -> Option<ElementInfo> {
+        if self.is_collection() {
+            let type_info = self.inner.element_type_info();
+            Some(ElementInfo {
+                     oracle_type_num: type_info.oracle_type_num,
+                     default_native_type_num: type_info.default_native_type_num,
+                     object_type: if type_info.object_type.is_null() {
+                         None
+                     } else {
+                         Some(type_info.object_type.into())
+                     },
+                 })
+        } else {
+            None
+        }
+    }
+}
+
+/// The type of the elements of a collection.
+pub struct ElementInfo {
+    /// The Oracle type of the elements.
+    oracle_type_num: enums::ODPIOracleTypeNum,
+    /// The default native type of the elements.
+    default_native_type_num: enums::ODPINativeTypeNum,
+    /// The object type of the elements, if they are objects.
+    object_type: Option<ObjectType>,
+}
+
+impl ElementInfo {
+    /// Get the `oracle_type_num` value.
+    ///
+    /// Specifies the Oracle type of the elements in the collection. It will be one of the values
+    /// from the enumeration `ODPIOracleTypeNum`.
+    pub fn oracle_type_num(&self) -> enums::ODPIOracleTypeNum {
+        self.oracle_type_num
+    }
+
+    /// Get the `default_native_type_num` value.
+    ///
+    /// Specifies the default native type of the elements in the collection. It will be one of the
+    /// values from the enumeration `ODPINativeTypeNum`.
+    pub fn default_native_type_num(&self) -> enums::ODPINativeTypeNum {
+        self.default_native_type_num
+    }
+
+    /// Get the `object_type` value.
+    ///
+    /// Specifies the object type of the elements in the collection. This value is only populated
+    /// when the elements are objects. For all other collections the value is None.
+    pub fn object_type(&self) -> Option<&ObjectType> {
+        self.object_type.as_ref()
+    }
+}
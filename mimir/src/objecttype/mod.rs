@@ -10,16 +10,21 @@
 //! OR REPLACE TYPE. They are created using the function `Connection::get_object_type()` or
 //! implicitly when fetching from a column containing objects by calling the function
 //! `Statement::get_query_info()`. Object types are also retrieved when used as attributes in
-//! another object by calling the function `ObjectAttribute::get_info()` or as the element type of a
-//! collection by calling the function `ObjectType::get_info()`. They are destroyed when the last
-//! reference is released by calling the function `ObjectType::release()`.
+//! another object by calling the function `ObjectAttr::get_attr_info()` or as the element type of
+//! a collection by calling the function `ObjectType::get_type_info()`. They are destroyed when the
+//! last reference is released by calling the function `ObjectType::release()`.
 use error::{ErrorKind, Result};
 use object::Object;
+use objectattr::ObjectAttr;
 use odpi::externs;
 use odpi::opaque::{ODPIObject, ODPIObjectAttr, ODPIObjectType};
 use odpi::structs::ODPIObjectTypeInfo;
 use std::ptr;
 
+pub mod info;
+
+use self::info::Info;
+
 /// Object type handles are used to represent types such as those created by the SQL command CREATE
 /// OR REPLACE TYPE.
 #[derive(Debug)]
@@ -40,7 +45,10 @@ impl ObjectType {
     /// object type was created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiObjectType_addRef(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(added, "ObjectType", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::ObjectType("dpiObjectType_addRef".to_string()))
     }
 
@@ -50,30 +58,31 @@ impl ObjectType {
         let mut object: *mut ODPIObject = ptr::null_mut();
 
         try_dpi!(externs::dpiObjectType_createObject(self.inner, &mut object),
-                 Ok(object.into()),
+                 {
+                     track_ref!(created, "Object", object);
+                     Ok(object.into())
+                 },
                  ErrorKind::ObjectType("dpiObjectType_createObject".to_string()))
     }
 
-    /// Returns the list of attributes that belong to the object type.
-    pub fn get_attributes(&self, length: u16) -> Result<Vec<*mut ODPIObjectAttr>> {
+    /// Returns the list of attributes that belong to the object type. Each attribute should be
+    /// released as soon as it is no longer needed.
+    pub fn get_attributes(&self, length: u16) -> Result<Vec<ObjectAttr>> {
         let mut buffer: Vec<*mut ODPIObjectAttr> = Vec::with_capacity(length as usize);
         let buf_ptr = buffer.as_mut_ptr();
 
         try_dpi!(externs::dpiObjectType_getAttributes(self.inner, length, buf_ptr),
                  {
                      unsafe { buffer.set_len(length as usize) };
-                     Ok(buffer)
+                     Ok(buffer.into_iter().map(ObjectAttr::from).collect())
                  },
                  ErrorKind::ObjectType("dpiObjectType_getAttributes".to_string()))
     }
 
-    /// Returns information about the object type.
-    pub fn get_info(&self) -> Result<ODPIObjectTypeInfo> {
-        let mut object_type_info: ODPIObjectTypeInfo = Default::default();
-
-        try_dpi!(externs::dpiObjectType_getInfo(self.inner, &mut object_type_info),
-                 Ok(object_type_info),
-                 ErrorKind::ObjectType("dpiObjectType_getInfo".to_string()))
+    /// Returns information about the object type as an `Info` struct, which gives typed access to
+    /// the element type of a collection.
+    pub fn get_type_info(&self) -> Result<Info> {
+        info(self).map(Info::new)
     }
 
     /// Releases a reference to the object type. A count of the references to the object type is
@@ -81,13 +90,27 @@ impl ObjectType {
     /// freed.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiObjectType_release(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(released, "ObjectType", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::ObjectType("dpiObjectType_release".to_string()))
     }
 }
 
 impl From<*mut ODPIObjectType> for ObjectType {
     fn from(oot: *mut ODPIObjectType) -> ObjectType {
+        track_ref!(wrapped, "ObjectType", oot);
         ObjectType { inner: oot }
     }
 }
+
+/// Returns information about the object type as the ODPI-C structure. See
+/// `raw::object_type_info()`.
+pub fn info(object_type: &ObjectType) -> Result<ODPIObjectTypeInfo> {
+    let mut object_type_info: ODPIObjectTypeInfo = Default::default();
+
+    try_dpi!(externs::dpiObjectType_getInfo(object_type.inner, &mut object_type_info),
+             Ok(object_type_info),
+             ErrorKind::ObjectType("dpiObjectType_getInfo".to_string()))
+}
@@ -10,10 +10,22 @@
 use std::os::raw::{c_int, c_uint};
 
 /// Major version of ODPI-C
+#[cfg(not(feature = "odpi-3"))]
 pub const DPI_MAJOR_VERSION: c_uint = 2;
 /// Minor version of ODPI-C
+#[cfg(not(feature = "odpi-3"))]
+pub const DPI_MINOR_VERSION: c_uint = 0;
+/// Major version of ODPI-C
+#[cfg(feature = "odpi-3")]
+pub const DPI_MAJOR_VERSION: c_uint = 3;
+/// Minor version of ODPI-C
+#[cfg(feature = "odpi-3")]
 pub const DPI_MINOR_VERSION: c_uint = 0;
 /// Value returned on success.
 pub const DPI_SUCCESS: c_int = 0;
 /// Value returned on failure.
 pub const DPI_FAILURE: c_int = -1;
+/// Dequeue wait value that returns immediately if no message is available.
+pub const DPI_DEQ_WAIT_NO_WAIT: c_uint = 0;
+/// Dequeue wait value that waits until a message is available.
+pub const DPI_DEQ_WAIT_FOREVER: c_uint = 4_294_967_295;
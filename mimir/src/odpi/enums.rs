@@ -63,6 +63,9 @@ pub enum ODPIEventType {
     ObjectChange = 6,
     /// Indicates that a query change has taken place.
     QueryChange = 7,
+    /// Indicates that messages are available to dequeue from a queue.
+    #[cfg(feature = "odpi-3")]
+    Aq = 100,
 }
 
 #[repr(u32)]
@@ -122,6 +125,7 @@ pub enum ODPIMessageState {
 #[repr(u32)]
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 /// This enumeration identifies the type of data that is being transferred to and from the database.
+/// The `types` module lists the Rust type each Oracle type is read as, and its native type.
 pub enum ODPINativeTypeNum {
     /// An invalid native type num.
     Invalid = 0,
@@ -398,7 +402,15 @@ pub enum ODPIStatementType {
 pub enum ODPISubscrNamespace {
     /// Identifies the namespace used for receiving notifications for database object changes
     /// and query changes.
+    #[cfg(not(feature = "odpi-3"))]
     DbChange = 0,
+    /// Identifies the namespace used for receiving notifications for Advanced Queuing.
+    #[cfg(feature = "odpi-3")]
+    Aq = 1,
+    /// Identifies the namespace used for receiving notifications for database object changes
+    /// and query changes.
+    #[cfg(feature = "odpi-3")]
+    DbChange = 2,
 }
 
 #[repr(u32)]
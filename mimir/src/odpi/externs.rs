@@ -115,6 +115,7 @@ extern "C" {
     pub fn dpiConn_newMsgProps(conn: *mut opaque::ODPIConn,
                                props: *mut *mut opaque::ODPIMsgProps)
                                -> ::std::os::raw::c_int;
+    #[cfg(not(feature = "odpi-3"))]
     pub fn dpiConn_newSubscription(conn: *mut opaque::ODPIConn,
                                    params: *mut structs::ODPISubscrCreateParams,
                                    subscr: *mut *mut opaque::ODPISubscr,
@@ -169,6 +170,10 @@ extern "C" {
                            value: *const ::std::os::raw::c_char,
                            valueLength: u32)
                            -> ::std::os::raw::c_int;
+    pub fn dpiConn_setEcontextId(conn: *mut opaque::ODPIConn,
+                                 value: *const ::std::os::raw::c_char,
+                                 valueLength: u32)
+                                 -> ::std::os::raw::c_int;
     pub fn dpiConn_setExternalName(conn: *mut opaque::ODPIConn,
                                    value: *const ::std::os::raw::c_char,
                                    valueLength: u32)
@@ -190,6 +195,15 @@ extern "C" {
     pub fn dpiConn_startupDatabase(conn: *mut opaque::ODPIConn,
                                    mode: enums::ODPIStartupMode)
                                    -> ::std::os::raw::c_int;
+    #[cfg(feature = "odpi-3")]
+    pub fn dpiConn_subscribe(conn: *mut opaque::ODPIConn,
+                             params: *mut structs::ODPISubscrCreateParams,
+                             subscr: *mut *mut opaque::ODPISubscr)
+                             -> ::std::os::raw::c_int;
+    #[cfg(feature = "odpi-3")]
+    pub fn dpiConn_unsubscribe(conn: *mut opaque::ODPIConn,
+                               subscr: *mut opaque::ODPISubscr)
+                               -> ::std::os::raw::c_int;
 }
 
 extern "C" {
@@ -265,6 +279,9 @@ extern "C" {
                                         value: *const ::std::os::raw::c_char,
                                         valueLength: u32)
                                         -> ::std::os::raw::c_int;
+    pub fn dpiDeqOptions_setDeliveryMode(options: *mut opaque::ODPIDeqOptions,
+                                         value: enums::ODPIMessageDeliveryMode)
+                                         -> ::std::os::raw::c_int;
     pub fn dpiDeqOptions_setMode(options: *mut opaque::ODPIDeqOptions,
                                  value: enums::ODPIDeqMode)
                                  -> ::std::os::raw::c_int;
@@ -595,6 +612,10 @@ extern "C" {
                          tag: *const ::std::os::raw::c_char,
                          tagLength: u32)
                          -> ::std::os::raw::c_int;
+    pub fn dpiStmt_define(stmt: *mut opaque::ODPIStmt,
+                          pos: u32,
+                          var: *mut opaque::ODPIVar)
+                          -> ::std::os::raw::c_int;
     pub fn dpiStmt_execute(stmt: *mut opaque::ODPIStmt,
                            mode: flags::ODPIExecMode,
                            numQueryColumns: *mut u32)
@@ -649,16 +670,24 @@ extern "C" {
     pub fn dpiStmt_getRowCount(stmt: *mut opaque::ODPIStmt,
                                count: *mut u64)
                                -> ::std::os::raw::c_int;
+    pub fn dpiStmt_getRowCounts(stmt: *mut opaque::ODPIStmt,
+                                numRowCounts: *mut u32,
+                                rowCounts: *mut *mut u64)
+                                -> ::std::os::raw::c_int;
     pub fn dpiStmt_release(stmt: *mut opaque::ODPIStmt) -> ::std::os::raw::c_int;
     pub fn dpiStmt_scroll(stmt: *mut opaque::ODPIStmt,
                           mode: enums::ODPIFetchMode,
                           offset: i32,
                           rowCountOffset: i32)
                           -> ::std::os::raw::c_int;
+    pub fn dpiStmt_setFetchArraySize(stmt: *mut opaque::ODPIStmt,
+                                     arraySize: u32)
+                                     -> ::std::os::raw::c_int;
 }
 
 extern "C" {
     pub fn dpiSubscr_addRef(subscr: *mut opaque::ODPISubscr) -> ::std::os::raw::c_int;
+    #[cfg(not(feature = "odpi-3"))]
     pub fn dpiSubscr_close(subscr: *mut opaque::ODPISubscr) -> ::std::os::raw::c_int;
     pub fn dpiSubscr_prepareStmt(subscr: *mut opaque::ODPISubscr,
                                  sql: *const ::std::os::raw::c_char,
@@ -9,6 +9,8 @@
 //! `oic` ODPI-C wrappers.
 pub mod constants;
 pub mod enums;
+// The functions are documented by ODPI-C, under the same names.
+#[allow(missing_docs)]
 pub mod externs;
 pub mod flags;
 pub mod opaque;
@@ -7,8 +7,9 @@
 // modified, or distributed except according to those terms.
 
 //! ODPI-C public structs.
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{Datelike, DateTime, Duration, FixedOffset, Timelike, TimeZone, Utc};
 use odpi::{enums, externs, flags, opaque};
+use std::cmp;
 use std::os::raw::{c_char, c_int, c_void};
 use std::ptr;
 use util::ODPIStr;
@@ -184,6 +185,22 @@ pub struct ODPIConnCreateParams {
     /// filled in if the connection was acquired from a session pool and a tag was initially
     /// specified.
     pub out_tag_found: c_int,
+    /// Specifies an array of `ODPIShardingKeyColumn` structures which are used to identify which
+    /// shard should be used for the connection. The default value is NULL.
+    #[cfg(feature = "odpi-3")]
+    pub sharding_key_columns: *mut ODPIShardingKeyColumn,
+    /// Specifies the number of elements in the `sharding_key_columns` member. The default value is
+    /// 0.
+    #[cfg(feature = "odpi-3")]
+    pub num_sharding_key_columns: u8,
+    /// Specifies an array of `ODPIShardingKeyColumn` structures which are used to identify which
+    /// shard group should be used for the connection. The default value is NULL.
+    #[cfg(feature = "odpi-3")]
+    pub super_sharding_key_columns: *mut ODPIShardingKeyColumn,
+    /// Specifies the number of elements in the `super_sharding_key_columns` member. The default
+    /// value is 0.
+    #[cfg(feature = "odpi-3")]
+    pub num_super_sharding_key_columns: u8,
 }
 
 impl Default for ODPIConnCreateParams {
@@ -206,6 +223,14 @@ impl Default for ODPIConnCreateParams {
             out_tag: ptr::null(),
             out_tag_length: 0,
             out_tag_found: 0,
+            #[cfg(feature = "odpi-3")]
+            sharding_key_columns: ptr::null_mut(),
+            #[cfg(feature = "odpi-3")]
+            num_sharding_key_columns: 0,
+            #[cfg(feature = "odpi-3")]
+            super_sharding_key_columns: ptr::null_mut(),
+            #[cfg(feature = "odpi-3")]
+            num_super_sharding_key_columns: 0,
         }
     }
 }
@@ -280,6 +305,62 @@ pub union ODPIDataValueUnion {
     pub as_rowid: *mut opaque::ODPIRowid,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+/// This structure is used for passing information about the type of a column, object attribute or
+/// collection element from ODPI-C. ODPI-C 3 returns it as a member of `ODPIQueryInfo`,
+/// `ODPIObjectAttrInfo` and `ODPIObjectTypeInfo`; for ODPI-C 2 it is filled in from the members of
+/// those structures by their `type_info()` methods.
+pub struct ODPIDataTypeInfo {
+    /// Specifies the Oracle type of the data. It will be one of the values from the enumeration
+    /// `ODPIOracleTypeNum`.
+    pub oracle_type_num: enums::ODPIOracleTypeNum,
+    /// Specifies the default native type of the data. It will be one of the values from the
+    /// enumeration `ODPINativeTypeNum`.
+    pub default_native_type_num: enums::ODPINativeTypeNum,
+    /// Specifies the OCI type code of the data, which can be useful if the type is not supported by
+    /// ODPI-C. It is 0 for ODPI-C 2.
+    pub oci_type_code: u16,
+    /// Specifies the size in bytes (from the database's perspective) of the data. This value is
+    /// only populated for strings and binary data. For all other data the value is zero.
+    pub db_size_in_bytes: u32,
+    /// Specifies the size in bytes (from the client's perspective) of the data. This value is only
+    /// populated for strings and binary data. For all other data the value is zero.
+    pub client_size_in_bytes: u32,
+    /// Specifies the size in characters of the data. This value is only populated for string data.
+    /// For all other data the value is zero.
+    pub size_in_chars: u32,
+    /// Specifies the precision of the data. This value is only populated for numeric and interval
+    /// data. For all other data the value is zero.
+    pub precision: i16,
+    /// Specifies the scale of the data. This value is only populated for numeric data. For all
+    /// other data the value is zero.
+    pub scale: i8,
+    /// Specifies the fractional seconds precision of the data. This value is only populated for
+    /// timestamp and interval day to second data. For all other data the value is zero.
+    pub fs_precision: u8,
+    /// Specifies a reference to the type of the object. This value is only populated for named
+    /// type data. For all other data the value is NULL.
+    pub object_type: *mut opaque::ODPIObjectType,
+}
+
+impl Default for ODPIDataTypeInfo {
+    fn default() -> ODPIDataTypeInfo {
+        ODPIDataTypeInfo {
+            oracle_type_num: enums::ODPIOracleTypeNum::TypeNone,
+            default_native_type_num: enums::ODPINativeTypeNum::Invalid,
+            oci_type_code: 0,
+            db_size_in_bytes: 0,
+            client_size_in_bytes: 0,
+            size_in_chars: 0,
+            precision: 0,
+            scale: 0,
+            fs_precision: 0,
+            object_type: ptr::null_mut(),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
 /// This structure is used for transferring encoding information from ODPI-C.
@@ -360,7 +441,7 @@ impl Default for ODPIErrorInfo {
 }
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 /// This structure is used for passing interval (days to seconds) data to and from the database in
 /// the structure dpiData.
 pub struct ODPIIntervalDS {
@@ -376,8 +457,35 @@ pub struct ODPIIntervalDS {
     pub fseconds: i32,
 }
 
+impl From<ODPIIntervalDS> for Duration {
+    fn from(interval: ODPIIntervalDS) -> Duration {
+        Duration::days(i64::from(interval.days)) + Duration::hours(i64::from(interval.hours)) +
+        Duration::minutes(i64::from(interval.minutes)) +
+        Duration::seconds(i64::from(interval.seconds)) +
+        Duration::nanoseconds(i64::from(interval.fseconds))
+    }
+}
+
+/// Each field takes its share of the duration only, with the sign of the duration, so that the
+/// fields add up to the duration again.
+impl From<Duration> for ODPIIntervalDS {
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    fn from(duration: Duration) -> ODPIIntervalDS {
+        let seconds = duration.num_seconds();
+        let fseconds = (duration - Duration::seconds(seconds)).num_nanoseconds().unwrap_or(0);
+
+        ODPIIntervalDS {
+            days: (seconds / 86_400) as i32,
+            hours: (seconds % 86_400 / 3_600) as i32,
+            minutes: (seconds % 3_600 / 60) as i32,
+            seconds: (seconds % 60) as i32,
+            fseconds: fseconds as i32,
+        }
+    }
+}
+
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 /// This structure is used for passing interval (years to months) data to and from the database in
 // the structure dpiData.
 pub struct ODPIIntervalYM {
@@ -387,10 +495,11 @@ pub struct ODPIIntervalYM {
     pub months: i32,
 }
 
+#[cfg(not(feature = "odpi-3"))]
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
 /// This structure is used for passing information about an object type from ODPI-C. It is used by
-/// the function `ObjectAttr::get_info()`.
+/// the function `raw::object_attr_info()`.
 pub struct ODPIObjectAttrInfo {
     /// Specifies the name of the attribute, as a byte string in the encoding used for CHAR data.
     pub name: *const ::std::os::raw::c_char,
@@ -407,6 +516,7 @@ pub struct ODPIObjectAttrInfo {
     pub object_type: *mut opaque::ODPIObjectType,
 }
 
+#[cfg(not(feature = "odpi-3"))]
 impl Default for ODPIObjectAttrInfo {
     fn default() -> ODPIObjectAttrInfo {
         ODPIObjectAttrInfo {
@@ -419,6 +529,56 @@ impl Default for ODPIObjectAttrInfo {
     }
 }
 
+#[cfg(not(feature = "odpi-3"))]
+impl ODPIObjectAttrInfo {
+    /// Returns the type of the attribute.
+    pub fn type_info(&self) -> ODPIDataTypeInfo {
+        ODPIDataTypeInfo {
+            oracle_type_num: self.oracle_type_num,
+            default_native_type_num: self.default_native_type_num,
+            object_type: self.object_type,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "odpi-3")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+/// This structure is used for passing information about an object type from ODPI-C. It is used by
+/// the function `raw::object_attr_info()`.
+pub struct ODPIObjectAttrInfo {
+    /// Specifies the name of the attribute, as a byte string in the encoding used for CHAR data.
+    pub name: *const ::std::os::raw::c_char,
+    /// Specifies the length of the `name` member, in bytes.
+    pub name_length: u32,
+    /// Specifies the type of the attribute.
+    pub type_info: ODPIDataTypeInfo,
+}
+
+#[cfg(feature = "odpi-3")]
+impl Default for ODPIObjectAttrInfo {
+    fn default() -> ODPIObjectAttrInfo {
+        ODPIObjectAttrInfo {
+            name: ptr::null(),
+            name_length: 0,
+            type_info: ODPIDataTypeInfo {
+                oracle_type_num: enums::ODPIOracleTypeNum::Max,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(feature = "odpi-3")]
+impl ODPIObjectAttrInfo {
+    /// Returns the type of the attribute.
+    pub fn type_info(&self) -> ODPIDataTypeInfo {
+        self.type_info
+    }
+}
+
+#[cfg(not(feature = "odpi-3"))]
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
 /// This structure is used for passing information about an object type from ODPI-C. It is used by
@@ -449,6 +609,7 @@ pub struct ODPIObjectTypeInfo {
     pub num_attributes: u16,
 }
 
+#[cfg(not(feature = "odpi-3"))]
 impl Default for ODPIObjectTypeInfo {
     fn default() -> ODPIObjectTypeInfo {
         ODPIObjectTypeInfo {
@@ -465,6 +626,69 @@ impl Default for ODPIObjectTypeInfo {
     }
 }
 
+#[cfg(not(feature = "odpi-3"))]
+impl ODPIObjectTypeInfo {
+    /// Returns the type of the elements in the collection, if the object type is a collection.
+    pub fn element_type_info(&self) -> ODPIDataTypeInfo {
+        ODPIDataTypeInfo {
+            oracle_type_num: self.element_oracle_type_num,
+            default_native_type_num: self.element_default_native_type_num,
+            object_type: self.element_object_type,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(feature = "odpi-3")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+/// This structure is used for passing information about an object type from ODPI-C. It is used by
+/// the function `ObjectType::getInfo()`.
+pub struct ODPIObjectTypeInfo {
+    /// Specifies the schema which owns the object type, as a byte string in the encoding used for
+    /// CHAR data.
+    pub schema: *const ::std::os::raw::c_char,
+    /// Specifies the length of the `schema` member, in bytes.
+    pub schema_length: u32,
+    /// Specifies the name of the object type, as a byte string in the encoding used for CHAR data.
+    pub name: *const ::std::os::raw::c_char,
+    /// Specifies the length of the `name` member, in bytes.
+    pub name_length: u32,
+    /// Specifies if the object type is a collection (1) or not (0).
+    pub is_collection: ::std::os::raw::c_int,
+    /// Specifies the type of the elements in the collection if the object type refers to a
+    /// collection.
+    pub element_type_info: ODPIDataTypeInfo,
+    /// Specifies the number of attributes that the object type has.
+    pub num_attributes: u16,
+}
+
+#[cfg(feature = "odpi-3")]
+impl Default for ODPIObjectTypeInfo {
+    fn default() -> ODPIObjectTypeInfo {
+        ODPIObjectTypeInfo {
+            schema: ptr::null(),
+            schema_length: 0,
+            name: ptr::null(),
+            name_length: 0,
+            is_collection: 0,
+            element_type_info: ODPIDataTypeInfo {
+                oracle_type_num: enums::ODPIOracleTypeNum::Max,
+                ..Default::default()
+            },
+            num_attributes: 0,
+        }
+    }
+}
+
+#[cfg(feature = "odpi-3")]
+impl ODPIObjectTypeInfo {
+    /// Returns the type of the elements in the collection, if the object type is a collection.
+    pub fn element_type_info(&self) -> ODPIDataTypeInfo {
+        self.element_type_info
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
 /// This structure is used for creating session pools, which can in turn be used to create
@@ -512,6 +736,19 @@ pub struct ODPIPoolCreateParams {
     /// `Pool::create()`. It is the length of the `out_pool_name` member, in bytes. Any value
     /// specified prior to creating the session pool is ignored.
     pub out_pool_name_length: u32,
+    /// Specifies the length of time in seconds after which idle sessions in the pool are
+    /// terminated. The default value is 0, which means idle sessions are never terminated.
+    #[cfg(feature = "odpi-3")]
+    pub timeout: u32,
+    /// Specifies the length of time in milliseconds that the caller should wait for a session to
+    /// become available in the pool before returning with an error, when the `get_mode` member is
+    /// DPI_MODE_POOL_GET_TIMEDWAIT. The default value is 0.
+    #[cfg(feature = "odpi-3")]
+    pub wait_timeout: u32,
+    /// Specifies the maximum length of time in seconds a pooled session may exist. The default
+    /// value is 0, which means there is no limit.
+    #[cfg(feature = "odpi-3")]
+    pub max_lifetime_session: u32,
 }
 
 impl Default for ODPIPoolCreateParams {
@@ -527,10 +764,17 @@ impl Default for ODPIPoolCreateParams {
             get_mode: enums::ODPIPoolGetMode::NoWait,
             out_pool_name: ptr::null(),
             out_pool_name_length: 0,
+            #[cfg(feature = "odpi-3")]
+            timeout: 0,
+            #[cfg(feature = "odpi-3")]
+            wait_timeout: 0,
+            #[cfg(feature = "odpi-3")]
+            max_lifetime_session: 0,
         }
     }
 }
 
+#[cfg(not(feature = "odpi-3"))]
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
 /// This structure is used for passing query metadata from ODPI-C. It is populated by the function
@@ -573,6 +817,7 @@ pub struct ODPIQueryInfo {
     pub object_type: *mut opaque::ODPIObjectType,
 }
 
+#[cfg(not(feature = "odpi-3"))]
 impl Default for ODPIQueryInfo {
     fn default() -> ODPIQueryInfo {
         ODPIQueryInfo {
@@ -591,6 +836,80 @@ impl Default for ODPIQueryInfo {
     }
 }
 
+#[cfg(not(feature = "odpi-3"))]
+impl ODPIQueryInfo {
+    /// Returns the type of the column.
+    pub fn type_info(&self) -> ODPIDataTypeInfo {
+        ODPIDataTypeInfo {
+            oracle_type_num: self.oracle_type_num,
+            default_native_type_num: self.default_native_type_num,
+            oci_type_code: 0,
+            db_size_in_bytes: self.db_size_in_bytes,
+            client_size_in_bytes: self.client_size_in_bytes,
+            size_in_chars: self.size_in_chars,
+            precision: self.precision,
+            scale: self.scale,
+            fs_precision: 0,
+            object_type: self.object_type,
+        }
+    }
+}
+
+#[cfg(feature = "odpi-3")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+/// This structure is used for passing query metadata from ODPI-C. It is populated by the function
+/// `Statement::getQueryInfo()`. All values remain valid as long as a reference is held to the
+/// statement and the statement is not re-executed or closed.
+pub struct ODPIQueryInfo {
+    /// Specifies the name of the column which is being queried, as a byte string in the encoding
+    /// used for CHAR data.
+    pub name: *const ::std::os::raw::c_char,
+    /// Specifies the length of the `name` member, in bytes.
+    pub name_length: u32,
+    /// Specifies the type of the column that is being queried.
+    pub type_info: ODPIDataTypeInfo,
+    /// Specifies if the column that is being queried may return null values (1) or not (0).
+    pub null_ok: ::std::os::raw::c_int,
+}
+
+#[cfg(feature = "odpi-3")]
+impl Default for ODPIQueryInfo {
+    fn default() -> ODPIQueryInfo {
+        ODPIQueryInfo {
+            name: ptr::null(),
+            name_length: 0,
+            type_info: Default::default(),
+            null_ok: 0,
+        }
+    }
+}
+
+#[cfg(feature = "odpi-3")]
+impl ODPIQueryInfo {
+    /// Returns the type of the column.
+    pub fn type_info(&self) -> ODPIDataTypeInfo {
+        self.type_info
+    }
+}
+
+#[cfg(feature = "odpi-3")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+/// This structure is used for passing sharding key column values to the database. It is part of
+/// the `ODPIConnCreateParams` structure.
+pub struct ODPIShardingKeyColumn {
+    /// Specifies the Oracle type of the column which makes up the sharding key. It is expected to
+    /// be one of the values from the enumeration `ODPIOracleTypeNum`.
+    pub oracle_type_num: enums::ODPIOracleTypeNum,
+    /// Specifies the native type of the column which makes up the sharding key. It is expected to
+    /// be one of the values from the enumeration `ODPINativeTypeNum`.
+    pub native_type_num: enums::ODPINativeTypeNum,
+    /// Specifies the value of the column which makes up the sharding key. It is expected to match
+    /// the `native_type_num` member.
+    pub value: ODPIDataValueUnion,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
 /// This structure is used for passing information about a statement from ODPI-C. It is used by the
@@ -684,6 +1003,30 @@ pub struct ODPISubscrCreateParams {
     pub recipient_name: *const c_char,
     /// Specifies the length of the `recipient_name` member, in bytes. The default value is 0.
     pub recipient_name_length: u32,
+    /// Specifies the IP address on which the subscription listens to receive notifications, as a
+    /// byte string in the encoding used for CHAR data. The default value is NULL, which means an
+    /// IP address is selected by the Oracle client.
+    #[cfg(feature = "odpi-3")]
+    pub ip_address: *const c_char,
+    /// Specifies the length of the `ip_address` member, in bytes. The default value is 0.
+    #[cfg(feature = "odpi-3")]
+    pub ip_address_length: u32,
+    /// Specifies the grouping class of the notifications. The default value is 0, which means
+    /// notifications are not grouped.
+    #[cfg(feature = "odpi-3")]
+    pub grouping_class: u8,
+    /// Specifies the number of seconds over which notifications are grouped, when the
+    /// `grouping_class` member is set. The default value is 0.
+    #[cfg(feature = "odpi-3")]
+    pub grouping_value: u32,
+    /// Specifies how grouped notifications are sent. The default value is 0, which sends a summary
+    /// of the grouped notifications.
+    #[cfg(feature = "odpi-3")]
+    pub grouping_type: u8,
+    /// This member is populated upon successful creation of a subscription using the function
+    /// `Connection::new_subscription()`. It is the id of the registration.
+    #[cfg(feature = "odpi-3")]
+    pub out_reg_id: u64,
 }
 
 impl Default for ODPISubscrCreateParams {
@@ -701,6 +1044,18 @@ impl Default for ODPISubscrCreateParams {
             callback_context: ptr::null_mut(),
             recipient_name: ptr::null(),
             recipient_name_length: 0,
+            #[cfg(feature = "odpi-3")]
+            ip_address: ptr::null(),
+            #[cfg(feature = "odpi-3")]
+            ip_address_length: 0,
+            #[cfg(feature = "odpi-3")]
+            grouping_class: 0,
+            #[cfg(feature = "odpi-3")]
+            grouping_value: 0,
+            #[cfg(feature = "odpi-3")]
+            grouping_type: 0,
+            #[cfg(feature = "odpi-3")]
+            out_reg_id: 0,
         }
     }
 }
@@ -734,6 +1089,60 @@ pub struct ODPISubscrMessage {
     /// taken place. If this value is not NULL the other members in this structure may not contain
     /// valid values.
     pub error_info: *mut ODPIErrorInfo,
+    /// Specifies the id of the transaction which generated the notification, as a series of bytes.
+    #[cfg(feature = "odpi-3")]
+    pub tx_id: *const c_void,
+    /// Specifies the length of the `tx_id` member, in bytes.
+    #[cfg(feature = "odpi-3")]
+    pub tx_id_length: u32,
+    /// Specifies if the subscription is still registered with the database (1) or not (0).
+    #[cfg(feature = "odpi-3")]
+    pub registered: c_int,
+    /// Specifies the name of the queue which has messages available to dequeue, as a byte string
+    /// in the encoding used for CHAR data. This value is NULL unless the value of the `event_type`
+    /// member is DPI_EVENT_AQ.
+    #[cfg(feature = "odpi-3")]
+    pub queue_name: *const c_char,
+    /// Specifies the length of the `queue_name` member, in bytes.
+    #[cfg(feature = "odpi-3")]
+    pub queue_name_length: u32,
+    /// Specifies the consumer name for which messages are available to dequeue, as a byte string
+    /// in the encoding used for CHAR data. This value is NULL unless the value of the `event_type`
+    /// member is DPI_EVENT_AQ and the queue is a multi-consumer queue.
+    #[cfg(feature = "odpi-3")]
+    pub consumer_name: *const c_char,
+    /// Specifies the length of the `consumer_name` member, in bytes.
+    #[cfg(feature = "odpi-3")]
+    pub consumer_name_length: u32,
+}
+
+impl Default for ODPISubscrMessage {
+    fn default() -> ODPISubscrMessage {
+        ODPISubscrMessage {
+            event_type: enums::ODPIEventType::No,
+            db_name: ptr::null(),
+            db_name_length: 0,
+            tables: ptr::null_mut(),
+            num_tables: 0,
+            queries: ptr::null_mut(),
+            num_queries: 0,
+            error_info: ptr::null_mut(),
+            #[cfg(feature = "odpi-3")]
+            tx_id: ptr::null(),
+            #[cfg(feature = "odpi-3")]
+            tx_id_length: 0,
+            #[cfg(feature = "odpi-3")]
+            registered: 0,
+            #[cfg(feature = "odpi-3")]
+            queue_name: ptr::null(),
+            #[cfg(feature = "odpi-3")]
+            queue_name_length: 0,
+            #[cfg(feature = "odpi-3")]
+            consumer_name: ptr::null(),
+            #[cfg(feature = "odpi-3")]
+            consumer_name_length: 0,
+        }
+    }
 }
 
 #[repr(C)]
@@ -816,19 +1225,47 @@ pub struct ODPITimestamp {
 
 impl From<ODPITimestamp> for DateTime<Utc> {
     fn from(timestamp: ODPITimestamp) -> DateTime<Utc> {
+        if timestamp.year == -10100 && timestamp.month == 0 && timestamp.day == 0 {
+            Utc::now()
+        } else {
+            DateTime::<FixedOffset>::from(timestamp).with_timezone(&Utc)
+        }
+    }
+}
+
+/// Oracle has no leap seconds, so the extra second chrono represents with a fraction of a second
+/// of one billion nanoseconds or more is folded into the last nanosecond of the minute.
+impl From<DateTime<Utc>> for ODPITimestamp {
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    fn from(datetime: DateTime<Utc>) -> ODPITimestamp {
+        ODPITimestamp {
+            year: datetime.year() as i16,
+            month: datetime.month() as u8,
+            day: datetime.day() as u8,
+            hour: datetime.hour() as u8,
+            minute: datetime.minute() as u8,
+            second: datetime.second() as u8,
+            fsecond: cmp::min(datetime.nanosecond(), 999_999_999),
+            tz_hour_offset: 0,
+            tz_minute_offset: 0,
+        }
+    }
+}
+
+impl From<ODPITimestamp> for DateTime<FixedOffset> {
+    fn from(timestamp: ODPITimestamp) -> DateTime<FixedOffset> {
         let y = timestamp.year as i32;
         let m = timestamp.month as u32;
         let d = timestamp.day as u32;
         let h = timestamp.hour as u32;
         let mm = timestamp.minute as u32;
         let s = timestamp.second as u32;
-        let fs = timestamp.fsecond * 1000;
+        let offset_secs = (timestamp.tz_hour_offset as i32 * 3600) +
+                          (timestamp.tz_minute_offset as i32 * 60);
 
-        if y == -10100 && m == 0 && d == 0 {
-            Utc::now()
-        } else {
-            Utc.ymd(y, m, d).and_hms_micro(h, mm, s, fs)
-        }
+        FixedOffset::east(offset_secs)
+            .ymd(y, m, d)
+            .and_hms_nano(h, mm, s, timestamp.fsecond)
     }
 }
 
@@ -0,0 +1,139 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Optimistic locking with a version column. A row is read along with its version, and written
+//! back only if the version is unchanged, incrementing it in the same statement. If another
+//! session changed or deleted the row in between, no row is updated and `ErrorKind::StaleRow` is
+//! returned, so the caller can re-read the row and retry.
+//!
+//! The update is run on the given connection without committing, so it joins the transaction in
+//! progress. Pass a `Transaction` guard to run it within the transaction, which is rolled back if
+//! the guard is dropped after a stale row error.
+//!
+//! ```ignore
+//! let update = VersionedUpdate::new("accounts", "version")?.key("id")?.column("balance")?;
+//! let txn = conn.begin_transaction()?;
+//! let version = update.execute(&txn, &[("id", 7.into()), ("balance", 100.0.into())], 3)?;
+//! txn.commit()?;
+//! ```
+use connection::Connection;
+use error::{ErrorKind, Result};
+use sql::{check_identifier, check_qualified_name};
+use statement::binder::BindValue;
+
+/// The name of the bind variable holding the version the row was read at.
+const OLD_VERSION: &str = "OLD_VERSION";
+
+/// An UPDATE statement that only updates a row if its version column is unchanged.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VersionedUpdate {
+    /// The table to update, optionally qualified by its schema.
+    table: String,
+    /// The version column, incremented by each update.
+    version_column: String,
+    /// The columns identifying the row.
+    key_columns: Vec<String>,
+    /// The columns set by the update.
+    columns: Vec<String>,
+}
+
+impl VersionedUpdate {
+    /// Create a new `VersionedUpdate` for the given table and version column. The version column
+    /// must hold an integer.
+    ///
+    /// * `table` - the table to update, optionally qualified by its schema.
+    /// * `version_column` - the version column.
+    pub fn new(table: &str, version_column: &str) -> Result<VersionedUpdate> {
+        Ok(VersionedUpdate {
+               table: check_qualified_name(table)?,
+               version_column: check_identifier(version_column)?,
+               key_columns: Vec::new(),
+               columns: Vec::new(),
+           })
+    }
+
+    /// Add a column set by the update. Its value is bound to the bind variable of the same name.
+    ///
+    /// * `column` - the column name.
+    pub fn column(mut self, column: &str) -> Result<VersionedUpdate> {
+        let column = self.check_column(column)?;
+        self.columns.push(column);
+        Ok(self)
+    }
+
+    /// Executes the update, returning the new version of the row. Returns `ErrorKind::StaleRow` if
+    /// no row has the given key and version.
+    ///
+    /// * `conn` - the connection, or a `Transaction` guard, the update is run on. The update is not
+    /// committed.
+    /// * `values` - the values of the key columns and of the columns set, by column name.
+    /// * `old_version` - the version of the row when it was read.
+    pub fn execute(&self,
+                   conn: &Connection,
+                   values: &[(&str, BindValue)],
+                   old_version: i64)
+                   -> Result<i64> {
+        let sql = self.sql()?;
+        let stmt = conn.prepare_stmt(Some(&sql), None, false)?;
+        let res = values
+            .iter()
+            .fold(stmt.binder().set(OLD_VERSION, old_version),
+                  |binder, &(name, ref value)| binder?.set(name, value.clone()))
+            .and_then(|binder| binder.execute())
+            .and_then(|_| stmt.get_row_count());
+        stmt.release()?;
+
+        match res? {
+            0 => Err(ErrorKind::StaleRow(self.table.clone(), old_version).into()),
+            _ => Ok(old_version + 1),
+        }
+    }
+
+    /// Add a column identifying the row. Its value is bound to the bind variable of the same name.
+    ///
+    /// * `column` - the column name.
+    pub fn key(mut self, column: &str) -> Result<VersionedUpdate> {
+        let column = self.check_column(column)?;
+        self.key_columns.push(column);
+        Ok(self)
+    }
+
+    /// Returns the UPDATE statement. Returns an error if no key column or no column to set was
+    /// added.
+    pub fn sql(&self) -> Result<String> {
+        if self.key_columns.is_empty() || self.columns.is_empty() {
+            let msg = format!("update of {} needs a key column and a column to set", self.table);
+            return Err(ErrorKind::Bind(msg).into());
+        }
+
+        let assign = |column: &String| format!("{} = :{}", column, column);
+        let mut sets: Vec<String> = self.columns.iter().map(&assign).collect();
+        sets.push(format!("{} = {} + 1", self.version_column, self.version_column));
+        let mut conditions: Vec<String> = self.key_columns.iter().map(&assign).collect();
+        conditions.push(format!("{} = :{}", self.version_column, OLD_VERSION));
+
+        Ok(format!("UPDATE {} SET {} WHERE {}",
+                   self.table,
+                   sets.join(", "),
+                   conditions.join(" AND ")))
+    }
+
+    /// Validates a column name, returning it in upper case. The version column, the bind variable
+    /// of the old version and columns already added are rejected.
+    fn check_column(&self, column: &str) -> Result<String> {
+        let column = check_identifier(column)?;
+
+        if column == self.version_column || column == OLD_VERSION ||
+           self.key_columns.contains(&column) || self.columns.contains(&column) {
+            let msg = format!("column {} cannot be added to the update of {}", column, self.table);
+            Err(ErrorKind::Bind(msg).into())
+        } else {
+            Ok(column)
+        }
+    }
+}
@@ -0,0 +1,403 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Oracle column and variable types, together with their size, precision and scale. An
+//! `OracleType` is displayed and parsed using the SQL type names, e.g. `VARCHAR2(256)`,
+//! `NUMBER(38,0)` or `TIMESTAMP(6) WITH TIME ZONE`, so that code and logs dealing with types do not
+//! need the numeric ODPI-C enumerations. The type of a queried column is returned by
+//! `query::Info::oracle_type()`, and variables of a type are created with
+//! `Connection::new_var_of()`.
+use error::{Error, ErrorKind, Result};
+use odpi::enums::ODPINativeTypeNum;
+use odpi::enums::ODPIOracleTypeNum;
+use std::fmt;
+use std::str::FromStr;
+use types::{self, RustType};
+
+/// The fractional seconds precision of TIMESTAMP types declared without one.
+pub const DEFAULT_FSPRECISION: u8 = 6;
+/// The scale Oracle reports for floating point NUMBER and FLOAT columns.
+pub const FLOAT_SCALE: i8 = -127;
+
+/// The most significant decimal digits an `f64` holds exactly.
+const DOUBLE_DIGITS: i16 = 15;
+/// The binary precision of an `f64`.
+const DOUBLE_BITS: i16 = 53;
+/// The most significant decimal digits an `f32` holds exactly.
+const FLOAT_DIGITS: i16 = 6;
+/// The binary precision of an `f32`.
+const FLOAT_BITS: i16 = 24;
+/// The most decimal digits of an integer that always fits in an `i64`.
+const INT64_DIGITS: i16 = 18;
+
+/// The data that may be lost when values of an Oracle type are fetched as a native type.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ConversionLoss {
+    /// The fractional part of the values is truncated.
+    Fraction,
+    /// The values may be out of the range of the native type, which fails the fetch.
+    Overflow,
+    /// The digits beyond the precision of the native type are rounded.
+    Precision,
+}
+
+impl fmt::Display for ConversionLoss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConversionLoss::Fraction => write!(f, "the fractional part is truncated"),
+            ConversionLoss::Overflow => write!(f, "values may be out of range"),
+            ConversionLoss::Precision => write!(f, "digits beyond its precision are rounded"),
+        }
+    }
+}
+
+/// An Oracle type, along with its size, precision or scale where the type has one.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OracleType {
+    /// VARCHAR2 with the maximum size in characters.
+    Varchar2(u32),
+    /// NVARCHAR2 with the maximum size in characters.
+    NVarchar2(u32),
+    /// CHAR with the size in characters.
+    Char(u32),
+    /// NCHAR with the size in characters.
+    NChar(u32),
+    /// The ROWID pseudocolumn.
+    Rowid,
+    /// RAW with the maximum size in bytes.
+    Raw(u32),
+    /// BINARY_FLOAT.
+    BinaryFloat,
+    /// BINARY_DOUBLE.
+    BinaryDouble,
+    /// PL/SQL PLS_INTEGER.
+    PlsInteger,
+    /// NUMBER with the precision and scale. A precision of 0 is an unconstrained NUMBER, and a
+    /// scale of `FLOAT_SCALE` is a FLOAT with the given binary precision.
+    Number(i16, i8),
+    /// DATE.
+    Date,
+    /// TIMESTAMP with the fractional seconds precision.
+    Timestamp(u8),
+    /// TIMESTAMP WITH TIME ZONE with the fractional seconds precision.
+    TimestampTz(u8),
+    /// TIMESTAMP WITH LOCAL TIME ZONE with the fractional seconds precision.
+    TimestampLtz(u8),
+    /// INTERVAL DAY TO SECOND.
+    IntervalDS,
+    /// INTERVAL YEAR TO MONTH.
+    IntervalYM,
+    /// CLOB.
+    Clob,
+    /// NCLOB.
+    NClob,
+    /// BLOB.
+    Blob,
+    /// BFILE.
+    BFile,
+    /// A REF CURSOR in PL/SQL or a CURSOR expression in SQL.
+    Cursor,
+    /// PL/SQL BOOLEAN.
+    Boolean,
+    /// A named type created with CREATE TYPE.
+    Object,
+    /// LONG.
+    Long,
+    /// LONG RAW.
+    LongRaw,
+}
+
+impl OracleType {
+    /// Create an `OracleType` from the ODPI-C type and the metadata describing it. Returns None
+    /// for `TypeNone`, `Max` and `NativeUint`, which do not describe a database type.
+    ///
+    /// * `oracle_type_num` - the ODPI-C Oracle type.
+    /// * `size` - the size in characters of string types, or in bytes of RAW.
+    /// * `precision` - the precision of NUMBER, or the fractional seconds precision of TIMESTAMP.
+    /// * `scale` - the scale of NUMBER.
+    pub fn from_odpi(oracle_type_num: ODPIOracleTypeNum,
+                     size: u32,
+                     precision: i16,
+                     scale: i8)
+                     -> Option<OracleType> {
+        let fsprecision = precision as u8;
+
+        let oracle_type = match oracle_type_num {
+            ODPIOracleTypeNum::Varchar => OracleType::Varchar2(size),
+            ODPIOracleTypeNum::NVarchar => OracleType::NVarchar2(size),
+            ODPIOracleTypeNum::Char => OracleType::Char(size),
+            ODPIOracleTypeNum::NChar => OracleType::NChar(size),
+            ODPIOracleTypeNum::RowID => OracleType::Rowid,
+            ODPIOracleTypeNum::Raw => OracleType::Raw(size),
+            ODPIOracleTypeNum::NativeFloat => OracleType::BinaryFloat,
+            ODPIOracleTypeNum::NativeDouble => OracleType::BinaryDouble,
+            ODPIOracleTypeNum::NativeInt => OracleType::PlsInteger,
+            ODPIOracleTypeNum::Number => OracleType::Number(precision, scale),
+            ODPIOracleTypeNum::Date => OracleType::Date,
+            ODPIOracleTypeNum::Timestamp => OracleType::Timestamp(fsprecision),
+            ODPIOracleTypeNum::TimestampTz => OracleType::TimestampTz(fsprecision),
+            ODPIOracleTypeNum::TimestampLtz => OracleType::TimestampLtz(fsprecision),
+            ODPIOracleTypeNum::IntervalDS => OracleType::IntervalDS,
+            ODPIOracleTypeNum::IntervalYM => OracleType::IntervalYM,
+            ODPIOracleTypeNum::Clob => OracleType::Clob,
+            ODPIOracleTypeNum::NClob => OracleType::NClob,
+            ODPIOracleTypeNum::Blob => OracleType::Blob,
+            ODPIOracleTypeNum::BFile => OracleType::BFile,
+            ODPIOracleTypeNum::Stmt => OracleType::Cursor,
+            ODPIOracleTypeNum::Boolean => OracleType::Boolean,
+            ODPIOracleTypeNum::Object => OracleType::Object,
+            ODPIOracleTypeNum::LongVarchar => OracleType::Long,
+            ODPIOracleTypeNum::LongRaw => OracleType::LongRaw,
+            ODPIOracleTypeNum::TypeNone |
+            ODPIOracleTypeNum::NativeUint |
+            ODPIOracleTypeNum::Max => return None,
+        };
+
+        Some(oracle_type)
+    }
+
+    /// Returns the data that may be lost when values of this type are fetched as the given native
+    /// type, or None if every value keeps its significant digits. Only conversions to the numeric
+    /// native types are checked. An unconstrained NUMBER is reported with a precision of 0 and a
+    /// scale of `FLOAT_SCALE`, so it may lose data as any of them.
+    ///
+    /// * `native_type` - the native type the values are fetched as.
+    pub fn conversion_loss(&self, native_type: ODPINativeTypeNum) -> Option<ConversionLoss> {
+        match (native_type, *self) {
+            (ODPINativeTypeNum::Int64, OracleType::BinaryFloat) |
+            (ODPINativeTypeNum::Int64, OracleType::BinaryDouble) => Some(ConversionLoss::Fraction),
+            (ODPINativeTypeNum::Int64, OracleType::Number(precision, scale)) => {
+                if scale > 0 || scale == FLOAT_SCALE {
+                    Some(ConversionLoss::Fraction)
+                } else if precision == 0 || precision - i16::from(scale) > INT64_DIGITS {
+                    Some(ConversionLoss::Overflow)
+                } else {
+                    None
+                }
+            }
+            (ODPINativeTypeNum::Double, OracleType::Number(precision, scale)) => {
+                float_loss(precision, scale, DOUBLE_DIGITS, DOUBLE_BITS)
+            }
+            (ODPINativeTypeNum::Float, OracleType::BinaryDouble) => Some(ConversionLoss::Precision),
+            (ODPINativeTypeNum::Float, OracleType::Number(precision, scale)) => {
+                float_loss(precision, scale, FLOAT_DIGITS, FLOAT_BITS)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the native type used for variables of this type. Integral NUMBER types that fit in
+    /// 64 bits use `Int64`; other NUMBER types use `Double`.
+    pub fn native_type_num(&self) -> ODPINativeTypeNum {
+        self.rust_type().native_type_num()
+    }
+
+    /// Returns the ODPI-C Oracle type.
+    pub fn oracle_type_num(&self) -> ODPIOracleTypeNum {
+        match *self {
+            OracleType::Varchar2(_) => ODPIOracleTypeNum::Varchar,
+            OracleType::NVarchar2(_) => ODPIOracleTypeNum::NVarchar,
+            OracleType::Char(_) => ODPIOracleTypeNum::Char,
+            OracleType::NChar(_) => ODPIOracleTypeNum::NChar,
+            OracleType::Rowid => ODPIOracleTypeNum::RowID,
+            OracleType::Raw(_) => ODPIOracleTypeNum::Raw,
+            OracleType::BinaryFloat => ODPIOracleTypeNum::NativeFloat,
+            OracleType::BinaryDouble => ODPIOracleTypeNum::NativeDouble,
+            OracleType::PlsInteger => ODPIOracleTypeNum::NativeInt,
+            OracleType::Number(..) => ODPIOracleTypeNum::Number,
+            OracleType::Date => ODPIOracleTypeNum::Date,
+            OracleType::Timestamp(_) => ODPIOracleTypeNum::Timestamp,
+            OracleType::TimestampTz(_) => ODPIOracleTypeNum::TimestampTz,
+            OracleType::TimestampLtz(_) => ODPIOracleTypeNum::TimestampLtz,
+            OracleType::IntervalDS => ODPIOracleTypeNum::IntervalDS,
+            OracleType::IntervalYM => ODPIOracleTypeNum::IntervalYM,
+            OracleType::Clob => ODPIOracleTypeNum::Clob,
+            OracleType::NClob => ODPIOracleTypeNum::NClob,
+            OracleType::Blob => ODPIOracleTypeNum::Blob,
+            OracleType::BFile => ODPIOracleTypeNum::BFile,
+            OracleType::Cursor => ODPIOracleTypeNum::Stmt,
+            OracleType::Boolean => ODPIOracleTypeNum::Boolean,
+            OracleType::Object => ODPIOracleTypeNum::Object,
+            OracleType::Long => ODPIOracleTypeNum::LongVarchar,
+            OracleType::LongRaw => ODPIOracleTypeNum::LongRaw,
+        }
+    }
+
+    /// Returns the Rust type values of this type are read as. See the `types` module.
+    pub fn rust_type(&self) -> RustType {
+        let (precision, scale) = match *self {
+            OracleType::Number(precision, scale) => (precision, scale),
+            _ => (0, 0),
+        };
+
+        types::rust_type_for(self.oracle_type_num(), precision, scale)
+            .expect("every OracleType is a database type")
+    }
+
+    /// Returns the size of the type: in characters for string types, in bytes for RAW, and 0 for
+    /// all other types.
+    pub fn size(&self) -> u32 {
+        match *self {
+            OracleType::Varchar2(size) |
+            OracleType::NVarchar2(size) |
+            OracleType::Char(size) |
+            OracleType::NChar(size) |
+            OracleType::Raw(size) => size,
+            _ => 0,
+        }
+    }
+}
+
+/// Returns the loss of a NUMBER fetched as a floating point type with the given precision, or
+/// None if every value keeps its significant digits.
+fn float_loss(precision: i16, scale: i8, digits: i16, bits: i16) -> Option<ConversionLoss> {
+    let exact = if scale == FLOAT_SCALE {
+        precision > 0 && precision <= bits
+    } else {
+        precision > 0 && precision <= digits
+    };
+
+    if exact {
+        None
+    } else {
+        Some(ConversionLoss::Precision)
+    }
+}
+
+impl fmt::Display for OracleType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OracleType::Varchar2(size) => write!(f, "VARCHAR2({})", size),
+            OracleType::NVarchar2(size) => write!(f, "NVARCHAR2({})", size),
+            OracleType::Char(size) => write!(f, "CHAR({})", size),
+            OracleType::NChar(size) => write!(f, "NCHAR({})", size),
+            OracleType::Rowid => write!(f, "ROWID"),
+            OracleType::Raw(size) => write!(f, "RAW({})", size),
+            OracleType::BinaryFloat => write!(f, "BINARY_FLOAT"),
+            OracleType::BinaryDouble => write!(f, "BINARY_DOUBLE"),
+            OracleType::PlsInteger => write!(f, "PLS_INTEGER"),
+            OracleType::Number(0, _) => write!(f, "NUMBER"),
+            OracleType::Number(precision, FLOAT_SCALE) => write!(f, "FLOAT({})", precision),
+            OracleType::Number(precision, scale) => write!(f, "NUMBER({},{})", precision, scale),
+            OracleType::Date => write!(f, "DATE"),
+            OracleType::Timestamp(fsp) => write!(f, "TIMESTAMP({})", fsp),
+            OracleType::TimestampTz(fsp) => write!(f, "TIMESTAMP({}) WITH TIME ZONE", fsp),
+            OracleType::TimestampLtz(fsp) => write!(f, "TIMESTAMP({}) WITH LOCAL TIME ZONE", fsp),
+            OracleType::IntervalDS => write!(f, "INTERVAL DAY TO SECOND"),
+            OracleType::IntervalYM => write!(f, "INTERVAL YEAR TO MONTH"),
+            OracleType::Clob => write!(f, "CLOB"),
+            OracleType::NClob => write!(f, "NCLOB"),
+            OracleType::Blob => write!(f, "BLOB"),
+            OracleType::BFile => write!(f, "BFILE"),
+            OracleType::Cursor => write!(f, "REF CURSOR"),
+            OracleType::Boolean => write!(f, "BOOLEAN"),
+            OracleType::Object => write!(f, "OBJECT"),
+            OracleType::Long => write!(f, "LONG"),
+            OracleType::LongRaw => write!(f, "LONG RAW"),
+        }
+    }
+}
+
+/// Parses the SQL name of a type, ignoring case and extra whitespace. String and RAW types must
+/// be given a size. TIMESTAMP types without a fractional seconds precision use
+/// `DEFAULT_FSPRECISION`, and `NUMBER(p)` has a scale of 0.
+impl FromStr for OracleType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<OracleType> {
+        let invalid = || -> Error { ErrorKind::OracleType(format!("invalid type '{}'", s)).into() };
+        let normalized = s.split_whitespace().collect::<Vec<_>>().join(" ").to_uppercase();
+
+        // Split "NAME(ARGS) SUFFIX" into its parts.
+        let (name, args, suffix) = match normalized.find('(') {
+            Some(open) => {
+                let close = normalized[open..].find(')').ok_or_else(&invalid)? + open;
+                (normalized[..open].trim(),
+                 Some(&normalized[open + 1..close]),
+                 normalized[close + 1..].trim())
+            }
+            None => (&normalized[..], None, ""),
+        };
+        let nums = match args {
+            Some(args) => {
+                args.split(',')
+                    .map(|arg| arg.trim().parse::<i64>().map_err(|_| invalid()))
+                    .collect::<Result<Vec<_>>>()?
+            }
+            None => Vec::new(),
+        };
+        let full_name = if suffix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{} {}", name, suffix)
+        };
+
+        let arg = |idx: usize, min: i64, max: i64| match nums.get(idx) {
+            Some(&num) if num >= min && num <= max => Ok(num),
+            _ => Err(invalid()),
+        };
+        let size = || if nums.len() == 1 {
+            arg(0, 1, i64::from(u32::max_value())).map(|size| size as u32)
+        } else {
+            Err(invalid())
+        };
+        let fsprecision = || match nums.len() {
+            0 => Ok(DEFAULT_FSPRECISION),
+            1 => arg(0, 0, 9).map(|fsp| fsp as u8),
+            _ => Err(invalid()),
+        };
+        let no_args = |oracle_type: OracleType| if nums.is_empty() {
+            Ok(oracle_type)
+        } else {
+            Err(invalid())
+        };
+
+        match &full_name[..] {
+            "VARCHAR2" => Ok(OracleType::Varchar2(size()?)),
+            "NVARCHAR2" => Ok(OracleType::NVarchar2(size()?)),
+            "CHAR" => Ok(OracleType::Char(size()?)),
+            "NCHAR" => Ok(OracleType::NChar(size()?)),
+            "RAW" => Ok(OracleType::Raw(size()?)),
+            "NUMBER" => {
+                match nums.len() {
+                    0 => Ok(OracleType::Number(0, 0)),
+                    1 => Ok(OracleType::Number(arg(0, 1, 38)? as i16, 0)),
+                    2 => Ok(OracleType::Number(arg(0, 1, 38)? as i16, arg(1, -84, 127)? as i8)),
+                    _ => Err(invalid()),
+                }
+            }
+            "FLOAT" => {
+                match nums.len() {
+                    0 => Ok(OracleType::Number(126, FLOAT_SCALE)),
+                    1 => Ok(OracleType::Number(arg(0, 1, 126)? as i16, FLOAT_SCALE)),
+                    _ => Err(invalid()),
+                }
+            }
+            "TIMESTAMP" => Ok(OracleType::Timestamp(fsprecision()?)),
+            "TIMESTAMP WITH TIME ZONE" => Ok(OracleType::TimestampTz(fsprecision()?)),
+            "TIMESTAMP WITH LOCAL TIME ZONE" => Ok(OracleType::TimestampLtz(fsprecision()?)),
+            "ROWID" => no_args(OracleType::Rowid),
+            "BINARY_FLOAT" => no_args(OracleType::BinaryFloat),
+            "BINARY_DOUBLE" => no_args(OracleType::BinaryDouble),
+            "PLS_INTEGER" => no_args(OracleType::PlsInteger),
+            "DATE" => no_args(OracleType::Date),
+            "INTERVAL DAY TO SECOND" => no_args(OracleType::IntervalDS),
+            "INTERVAL YEAR TO MONTH" => no_args(OracleType::IntervalYM),
+            "CLOB" => no_args(OracleType::Clob),
+            "NCLOB" => no_args(OracleType::NClob),
+            "BLOB" => no_args(OracleType::Blob),
+            "BFILE" => no_args(OracleType::BFile),
+            "REF CURSOR" => no_args(OracleType::Cursor),
+            "BOOLEAN" => no_args(OracleType::Boolean),
+            "OBJECT" => no_args(OracleType::Object),
+            "LONG" => no_args(OracleType::Long),
+            "LONG RAW" => no_args(OracleType::LongRaw),
+            _ => Err(invalid()),
+        }
+    }
+}
@@ -11,21 +11,108 @@
 //! by calling the function `release()`. Pools can be used to create connections by calling the
 //! function `acquireConnection()`.
 use common::encoding;
+use config::{self, ConnectParams};
 use connection::Connection;
-use context::Context;
+use context::{self, Context, ContextRef};
 use context::params::{CommonCreate, ConnCreate, PoolCreate};
-use error::{ErrorKind, Result};
+use error::{Error, ErrorKind, Result};
+use odpi::constants::DPI_SUCCESS;
 use odpi::{enums, externs, flags};
 use odpi::opaque::{ODPIConn, ODPIPool};
-use odpi::structs::ODPIEncodingInfo;
+use odpi::structs::{ODPIEncodingInfo, ODPIErrorInfo};
+use stmtcache::{CacheStats, Tracker as CacheTracker};
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
 use std::ptr;
-use util::ODPIStr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use util::{ODPIStr, duration_millis};
+
+/// ORA-24422: the pool could not be closed because sessions are still busy.
+pub const ORA_POOL_BUSY: i32 = 24_422;
+/// ORA-24418: a session could not be acquired because the pool is at its maximum size and the get
+/// mode is `NoWait`.
+pub const ORA_POOL_EXHAUSTED: i32 = 24_418;
+/// ORA-24457: no free session was found within the wait timeout.
+pub const ORA_POOL_WAIT_TIMEOUT: i32 = 24_457;
+/// The longest interval between attempts to acquire a session while waiting for one to become
+/// free.
+const MAX_POLL_INTERVAL_MS: u64 = 100;
+/// The prefix of the tag marking sessions initialized with the statements given to
+/// `Pool::with_session_init_sql()`.
+const SESSION_INIT_TAG_PREFIX: &str = "mimir_session_init=";
 
 /// This structure represents session pools and is available by handle to a calling application or '
 /// driver.
 pub struct Pool {
     /// An ODPI-C dpiPool opaque struct pointer.
     inner: *mut ODPIPool,
+    /// The context the pool was created in, used to classify acquire errors.
+    context: ContextRef,
+    /// The maximum time `acquire_connection()` waits for a free session in `Wait` mode.
+    wait_timeout: Cell<Option<Duration>>,
+    /// The connect string used to create the pool, with any password redacted.
+    connect_string: Option<String>,
+    /// True if all sessions in the pool use the credentials the pool was created with.
+    homogeneous: bool,
+    /// The minimum number of sessions the pool was created with, opened by `warm_up()`.
+    min_sessions: u32,
+    /// The maximum number of sessions the pool was created with, or 0 if not known.
+    max_sessions: u32,
+    /// The statement cache statistics of the connections acquired from the pool, if enabled with
+    /// `set_stmt_cache_stats()`.
+    stmt_cache_stats: RefCell<Option<Arc<Mutex<CacheStats>>>>,
+    /// The statements run on new sessions, if set with `with_session_init_sql()`.
+    session_init: Option<Arc<SessionInit>>,
+    /// True if the pool was created with `DPI_MODE_CREATE_THREADED`, so it can be used from
+    /// several threads.
+    threaded: bool,
+}
+
+/// A reference to a session pool that can be moved to another thread, created with
+/// `Pool::thread_handle()`. The thread turns it back into a `Pool` with `into_pool()`, and
+/// releases that `Pool` when done. A handle dropped without being turned into a `Pool` releases
+/// its reference. The `Pool` keeps the context, session initialization statements and statement
+/// cache statistics of the pool the handle was created from, so acquire errors are classified and
+/// new sessions initialized the same way on every thread.
+pub struct PoolHandle {
+    /// An ODPI-C dpiPool opaque struct pointer, null once the handle is turned into a `Pool`.
+    inner: *mut ODPIPool,
+    /// The context the pool was created in, used to classify acquire errors.
+    context: ContextRef,
+    /// The maximum time `acquire_connection()` waits for a free session in `Wait` mode.
+    wait_timeout: Option<Duration>,
+    /// The connect string used to create the pool, with any password redacted.
+    connect_string: Option<String>,
+    /// True if all sessions in the pool use the credentials the pool was created with.
+    homogeneous: bool,
+    /// The minimum number of sessions the pool was created with.
+    min_sessions: u32,
+    /// The maximum number of sessions the pool was created with, or 0 if not known.
+    max_sessions: u32,
+    /// The statement cache statistics shared with the pool, if enabled when the handle was
+    /// created.
+    stmt_cache_stats: Option<Arc<Mutex<CacheStats>>>,
+    /// The statements run on new sessions, shared with the pool.
+    session_init: Option<Arc<SessionInit>>,
+}
+
+/// The pool was created with `DPI_MODE_CREATE_THREADED`, so ODPI-C serializes the calls made on it
+/// from several threads.
+unsafe impl Send for PoolHandle {}
+
+/// The statements run on each new session of a pool, and the tag marking sessions they have been
+/// run on.
+struct SessionInit {
+    /// The statements, run in order.
+    statements: Vec<String>,
+    /// The tag of the initialized sessions.
+    tag: String,
 }
 
 impl Pool {
@@ -36,7 +123,12 @@ impl Pool {
     }
 
     /// Acquires a connection from the pool and returns a reference to it. This reference should be
-    /// released as soon as it is no longer needed.
+    /// released as soon as it is no longer needed. If the pool is exhausted and the get mode is
+    /// `Wait`, this blocks until a session is released, or until the wait timeout expires, in which
+    /// case `ErrorKind::PoolTimeout` is returned. The wait timeout given in the `ConnCreate`
+    /// parameters takes precedence over the one set with `set_wait_timeout()`. Sessions
+    /// in a homogeneous pool always use the credentials the pool was created with, so
+    /// `ErrorKind::Config` is returned if credentials are given here for such a pool.
     ///
     /// * `username` - the name of the user used for authenticating the user, as a string in the
     /// encoding used for CHAR data. None is also acceptable if external authentication is being
@@ -52,80 +144,128 @@ impl Pool {
                               password: Option<&str>,
                               conn_create_params: Option<ConnCreate>)
                               -> Result<Connection> {
-        let username_s = ODPIStr::from(username);
-        let password_s = ODPIStr::from(password);
         let conn_cp = if let Some(conn_create_params) = conn_create_params {
             conn_create_params
         } else {
             Default::default()
         };
-        let mut conn: *mut ODPIConn = ptr::null_mut();
+        self.validate_acquire(username, password, &conn_cp)?;
 
-        try_dpi!(externs::dpiPool_acquireConnection(self.inner,
-                                                    username_s.ptr(),
-                                                    username_s.len(),
-                                                    password_s.ptr(),
-                                                    password_s.len(),
-                                                    &mut conn_cp.inner(),
-                                                    &mut conn),
-                 Ok(conn.into()),
-                 ErrorKind::Pool("dpiPool_acquireConnection".to_string()))
+        let start = Instant::now();
+        let wait_timeout = conn_cp.get_wait_timeout().or_else(|| self.wait_timeout.get());
+
+        match wait_timeout {
+            Some(timeout) if self.get_get_mode()? == enums::ODPIPoolGetMode::Wait => {
+                self.wait_for_session(timeout)?;
+                self.acquire(username, password, &conn_cp)
+                    .map(|mut conn| {
+                             conn.set_connect_time(start.elapsed());
+                             conn
+                         })
+                    .map_err(|(_, err)| err)
+            }
+            _ => {
+                self.acquire(username, password, &conn_cp)
+                    .map_err(|(code, err)| if code == ORA_POOL_WAIT_TIMEOUT {
+                        let millis = wait_timeout.map_or(0, duration_millis);
+                        ErrorKind::PoolTimeout(millis).into()
+                    } else {
+                        err
+                    })
+            }
+        }
     }
 
     /// Adds a reference to the pool. This is intended for situations where a reference to the pool
     /// needs to be maintained independently of the reference returned when the pool was created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiPool_addRef(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(added, "Pool", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Pool("dpiPool_addRef".to_string()))
     }
 
-    /// Closes the pool and makes it unusable for further activity.
+    /// Closes the pool and makes it unusable for further activity. Unless the pool is closed in
+    /// force mode, `ErrorKind::PoolBusy` is returned, with the number of busy sessions, if sessions
+    /// are still busy. See `close_gracefully()`.
     ///
     /// * `close_mode` - one or more of the values from the enumeration `ODPIPoolCloseMode`, OR'ed
     /// together.
-    pub fn close(&self, close_mode: flags::ODPIPoolCloseMode) -> Result<()> {
-        try_dpi!(externs::dpiPool_close(self.inner, close_mode),
-                 Ok(()),
-                 ErrorKind::Pool("dpiPool_close".to_string()))
+    pub fn close<M: Into<flags::ODPIPoolCloseMode>>(&self, close_mode: M) -> Result<()> {
+        if unsafe { externs::dpiPool_close(self.inner, close_mode.into()) } == DPI_SUCCESS {
+            Ok(())
+        } else if self.last_error_code() == ORA_POOL_BUSY {
+            Err(ErrorKind::PoolBusy(self.get_busy_count().unwrap_or(0)).into())
+        } else {
+            Err(ErrorKind::Pool("dpiPool_close".to_string()).into())
+        }
+    }
+
+    /// Closes the pool once its busy sessions have been released, waiting for them up to the given
+    /// timeout. If sessions are still busy when the timeout expires, the pool is closed in force
+    /// mode, closing those sessions too. Returns the number of sessions that were busy when the
+    /// pool was closed, which is 0 unless it was forced.
+    ///
+    /// * `timeout` - the longest time to wait for the busy sessions.
+    pub fn close_gracefully(&self, timeout: Duration) -> Result<u32> {
+        let deadline = Instant::now() + timeout;
+        let mut interval = 1;
+
+        loop {
+            let busy = self.get_busy_count()?;
+            if busy == 0 {
+                match self.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT) {
+                    Err(Error(ErrorKind::PoolBusy(_), _)) => {}
+                    res => return res.map(|_| 0),
+                }
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return self.close(flags::DPI_MODE_POOL_CLOSE_FORCE).map(|_| busy);
+            }
+
+            thread::sleep(cmp::min(Duration::from_millis(interval), deadline - now));
+            interval = cmp::min(interval * 2, MAX_POLL_INTERVAL_MS);
+        }
     }
 
     /// Creates a session pool which creates and maintains a group of stateless sessions to the
     /// database. The main benefit of session pooling is performance since making a connection to
     /// the database is a time-consuming activity, especially when the database is remote.
     ///
-    /// * `context` - the context handle created earlier using the function `Context::create()`.
-    /// * `username` - the name of the user used for authenticating sessions, as a string in the
-    /// encoding used for CHAR data. None is also acceptable if external authentication is being
-    /// requested or if a heterogeneous pool is being created.
-    /// * `password` - the password to use for authenticating sessions, as a string in the encoding
-    /// used for CHAR data. None is also acceptable if external authentication is being requested or
-    ///  if a heterogeneous pool is being created.
-    /// * `connect_string` - the connect string identifying the database to which connections are to
-    /// be established by the session pool, as a string in the encoding used for CHAR data. None is
-    /// also acceptable for local connections (identified by the environment variable ORACLE_SID).
+    /// * `context` - the context handle created earlier using the function `Context::create()`, or
+    /// None to use the global context; see `Context::global()`.
+    /// * `params` - the username, password and connect string, e.g. a `ConnectParams` or a
+    /// `(username, password, connect_string)` tuple. The username and password may be empty if
+    /// external authentication is being requested or if a heterogeneous pool is being created, and
+    /// are required otherwise. The connect string may be empty for local connections (identified by
+    /// the environment variable ORACLE_SID). The credentials are checked before the pool is
+    /// created, and an `ErrorKind::Config` error is returned if they do not fit the pool.
     /// * `common_create_params` - a `CommonCreate` structure which is used to specify context
-    /// parameters for pool creation. None is also acceptable in which case all default parameters
-    /// will be used when creating the pool.
-    /// * `conn_create_params` - a `PoolCreate` structure which is used to specify parameters for
+    /// parameters for pool creation. None is also acceptable in which case the default parameters
+    /// with the encoding of `params` will be used when creating the pool.
+    /// * `pool_create_params` - a `PoolCreate` structure which is used to specify parameters for
     /// pool creation. None is also acceptable in which case all default parameters will be used for
     /// pool creation.
-    pub fn create(context: &Context,
-                  username: Option<&str>,
-                  password: Option<&str>,
-                  connect_string: Option<&str>,
-                  common_create_params: Option<CommonCreate>,
-                  pool_create_params: Option<PoolCreate>)
-                  -> Result<Pool> {
-        let username_s = ODPIStr::from(username);
-        let password_s = ODPIStr::from(password);
-        let connect_string_s = ODPIStr::from(connect_string);
+    pub fn create<'a, C, P>(context: C,
+                            params: P,
+                            common_create_params: Option<CommonCreate>,
+                            pool_create_params: Option<PoolCreate>)
+                            -> Result<Pool>
+        where C: Into<Option<&'a Context>>,
+              P: Into<ConnectParams>
+    {
+        let context = context::resolve(context.into())?;
+        let params = params.into();
         let mut inner: *mut ODPIPool = ptr::null_mut();
 
         let comm_cp = if let Some(common_create_params) = common_create_params {
             common_create_params
         } else {
-            context.init_common_create_params()?
+            params.init_common_create_params(context)?
         };
 
         let pool_cp = if let Some(pool_create_params) = pool_create_params {
@@ -134,6 +274,16 @@ impl Pool {
             context.init_pool_create_params()?
         };
 
+        let threaded = comm_cp.get_create_mode().contains(flags::DPI_MODE_CREATE_THREADED);
+        let homogeneous = pool_cp.get_homogeneous();
+        let min_sessions = pool_cp.get_min_sessions();
+        let max_sessions = pool_cp.get_max_sessions();
+        params.validate(pool_cp.get_external_auth(), homogeneous)?;
+
+        let username_s = ODPIStr::from(params.get_username());
+        let password_s = ODPIStr::from(params.get_password());
+        let connect_string_s = ODPIStr::from(params.get_connect_string());
+
         try_dpi!(externs::dpiPool_create(context.inner(),
                                          username_s.ptr(),
                                          username_s.len(),
@@ -144,7 +294,20 @@ impl Pool {
                                          &comm_cp.inner(),
                                          &mut pool_cp.inner(),
                                          &mut inner),
-                 Ok(inner.into()),
+                 {
+                     track_ref!(created_in, context.inner(), "Pool", inner);
+                     let mut pool: Pool = inner.into();
+                     pool.context = context.handle();
+                     pool.homogeneous = homogeneous;
+                     pool.min_sessions = min_sessions;
+                     pool.max_sessions = max_sessions;
+                     pool.threaded = threaded;
+                     if !params.get_connect_string().is_empty() {
+                         let connect_string = params.get_connect_string();
+                         pool.connect_string = Some(config::redact_connect_string(connect_string));
+                     }
+                     Ok(pool)
+                 },
                  ErrorKind::Pool("dpiPool_create".to_string()))
     }
 
@@ -188,6 +351,12 @@ impl Pool {
                  ErrorKind::Pool("dpiPool_getMaxLifetimeSession".to_string()))
     }
 
+    /// Get the `min_sessions` value, the minimum number of sessions the pool was created with. It
+    /// is 0 for a pool wrapped from an ODPI-C handle.
+    pub fn get_min_sessions(&self) -> u32 {
+        self.min_sessions
+    }
+
     /// Returns the number of sessions in the pool that are open.
     pub fn get_open_count(&self) -> Result<u32> {
         let mut open_count = 0;
@@ -207,6 +376,17 @@ impl Pool {
                  ErrorKind::Pool("dpiPool_getStmtCacheSize".to_string()))
     }
 
+    /// Returns the statement cache hits and misses of the connections acquired from the pool since
+    /// statistics were enabled with `set_stmt_cache_stats()`, as estimated by
+    /// `Connection::get_statement_cache_stats()`. Zero if statistics are disabled.
+    pub fn get_stmt_cache_stats(&self) -> CacheStats {
+        self.stmt_cache_stats
+            .borrow()
+            .as_ref()
+            .map_or_else(CacheStats::default,
+                         |stats| *stats.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
     /// Returns the amount of time, in seconds, after which idle sessions in the pool are
     /// terminated, but only when another session is released back to the pool.
     pub fn get_timeout(&self) -> Result<u32> {
@@ -217,12 +397,40 @@ impl Pool {
                  ErrorKind::Pool("dpiPool_getTimeout".to_string()))
     }
 
+    /// Returns the maximum time `acquire_connection()` waits for a free session when the pool is
+    /// exhausted and the get mode is `Wait`. None waits indefinitely.
+    pub fn get_wait_timeout(&self) -> Option<Duration> {
+        self.wait_timeout.get()
+    }
+
+    /// Returns false if the `Context` the pool was created in has been dropped. Calls that need the
+    /// context, such as `acquire_connection()`, then return `ErrorKind::ContextDropped`.
+    pub fn is_context_alive(&self) -> bool {
+        self.context.is_alive()
+    }
+
+    /// Closes all of the idle sessions in the pool, dropping them rather than returning them to
+    /// the pool. This allows the pool to be drained of dead sessions after a database failover
+    /// instead of waiting for them to time out. Sessions that are busy are not affected; they can
+    /// be dropped by calling `Connection::close_drop()` when they are found to be broken. The pool
+    /// must be homogeneous, as the idle sessions are acquired using the credentials supplied when
+    /// the pool was created. The get mode of the pool is left alone, so other threads acquiring
+    /// sessions are not affected; instead, a session is only acquired while one is idle. Returns
+    /// the number of sessions dropped.
+    pub fn purge(&self) -> Result<u32> {
+        let idle_count = self.get_open_count()?.saturating_sub(self.get_busy_count()?);
+        self.drop_idle(idle_count)
+    }
+
     /// Releases a reference to the pool. A count of the references to the pool is maintained and
     /// when this count reaches zero, the memory associated with the pool is freed and the session
     /// pool is closed if that has not already taken place using the function `Pool::close()`.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiPool_release(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(released, "Pool", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Pool("dpiPool_release".to_string()))
     }
 
@@ -255,6 +463,21 @@ impl Pool {
                  ErrorKind::Pool("dpiPool_setStmtCacheSize".to_string()))
     }
 
+    /// Enables or disables statement cache statistics for the connections acquired from the pool
+    /// afterwards. Each connection also records its own statistics. Disabling them forgets any
+    /// statistics recorded so far, and stops the connections already acquired adding to them.
+    ///
+    /// * `enabled` - should statement cache hits and misses be counted?
+    pub fn set_stmt_cache_stats(&self, enabled: bool) {
+        let mut stmt_cache_stats = self.stmt_cache_stats.borrow_mut();
+
+        if !enabled {
+            *stmt_cache_stats = None;
+        } else if stmt_cache_stats.is_none() {
+            *stmt_cache_stats = Some(Arc::new(Mutex::new(CacheStats::default())));
+        }
+    }
+
     /// Sets the amount of time, in seconds, after which idle sessions in the pool are terminated,
     /// but only when another session is released back to the pool.
     pub fn set_timeout(&self, timeout: u32) -> Result<()> {
@@ -262,10 +485,416 @@ impl Pool {
                  Ok(()),
                  ErrorKind::Pool("dpiPool_setTimeout".to_string()))
     }
+
+    /// Sets the maximum time `acquire_connection()` waits for a free session when the pool is
+    /// exhausted and the get mode is `Wait`, unless a wait timeout is given in the `ConnCreate`
+    /// parameters of the call. ODPI-C 2.0 has no timed wait, so the session counts of the pool are
+    /// polled until a session is free or the timeout expires, and the session is then acquired in
+    /// `Wait` mode. If another caller takes the free session first, that acquire blocks until a
+    /// session is released.
+    ///
+    /// * `wait_timeout` - the maximum time to wait. None waits indefinitely.
+    pub fn set_wait_timeout(&self, wait_timeout: Option<Duration>) {
+        self.wait_timeout.set(wait_timeout);
+    }
+
+    /// Adds a reference to the pool and returns it as a `PoolHandle`, which can be moved to another
+    /// thread. The pool must have been created with `DPI_MODE_CREATE_THREADED` in the create mode
+    /// of the `CommonCreate` parameters, or `ErrorKind::Pool` is returned.
+    pub fn thread_handle(&self) -> Result<PoolHandle> {
+        if !self.threaded {
+            let err = "thread_handle: the pool was not created with DPI_MODE_CREATE_THREADED";
+            return Err(ErrorKind::Pool(err.to_string()).into());
+        }
+
+        self.add_ref()?;
+        Ok(PoolHandle {
+               inner: self.inner,
+               context: self.context.clone(),
+               wait_timeout: self.wait_timeout.get(),
+               connect_string: self.connect_string.clone(),
+               homogeneous: self.homogeneous,
+               min_sessions: self.min_sessions,
+               max_sessions: self.max_sessions,
+               stmt_cache_stats: self.stmt_cache_stats.borrow().clone(),
+               session_init: self.session_init.clone(),
+           })
+    }
+
+    /// Acquires a connection from the pool if a session is available without waiting. Returns
+    /// `Ok(None)` if the pool is exhausted and the get mode is `NoWait`, so callers can apply their
+    /// own backpressure rather than blocking. In the other get modes this behaves like
+    /// `acquire_connection()`.
+    ///
+    /// * `username` - the name of the user used for authenticating the user. None is also
+    /// acceptable if external authentication is being requested or credentials were supplied when
+    /// the pool was created.
+    /// * `password` - the password to use for authenticating the user. None is also acceptable if
+    /// external authentication is being requested or credentials were supplied when the pool was
+    /// created.
+    /// * `conn_create_params` - An optional `ConnCreate` structure which is used to specify
+    /// parameters for connection creation.
+    pub fn try_acquire(&self,
+                       username: Option<&str>,
+                       password: Option<&str>,
+                       conn_create_params: Option<ConnCreate>)
+                       -> Result<Option<Connection>> {
+        if self.get_get_mode()? != enums::ODPIPoolGetMode::NoWait {
+            return self.acquire_connection(username, password, conn_create_params).map(Some);
+        }
+
+        let conn_cp = if let Some(conn_create_params) = conn_create_params {
+            conn_create_params
+        } else {
+            Default::default()
+        };
+        self.validate_acquire(username, password, &conn_cp)?;
+
+        match self.acquire(username, password, &conn_cp) {
+            Ok(conn) => Ok(Some(conn)),
+            Err((ORA_POOL_EXHAUSTED, _)) => Ok(None),
+            Err((_, err)) => Err(err),
+        }
+    }
+
+    /// Establishes the minimum number of sessions of the pool up front, so a service pays the
+    /// cost of connecting at startup rather than on its first requests. Sessions are acquired
+    /// without waiting and held until `get_min_sessions()` of them are held, or the pool is
+    /// exhausted, so each is a distinct session, opened if it was not open already. The session
+    /// initialization statements given to `with_session_init_sql()` are run on the new sessions.
+    /// The sessions are then closed back to the pool. As with `purge()`, the get mode of the pool
+    /// is left alone, and the pool must be homogeneous. Returns the number of sessions newly
+    /// opened, which are idle in the pool.
+    pub fn warm_up(&self) -> Result<u32> {
+        let conn_cp: ConnCreate = Default::default();
+        self.validate_acquire(None, None, &conn_cp)?;
+        let open_count = self.get_open_count()?;
+
+        self.hold_min_sessions(&conn_cp)?;
+        Ok(self.get_open_count()?.saturating_sub(open_count))
+    }
+
+    /// Runs the given statements, such as `ALTER SESSION SET ...`, on every new session before it
+    /// is handed out, so per-session NLS settings or roles are always in place. Initialized
+    /// sessions are tagged, and the tag is applied again when the connection is closed, so the
+    /// statements run once per session rather than on every acquire. The tag is only used when no
+    /// tag is given in the `ConnCreate` parameters; sessions acquired with a tag of their own are
+    /// left to the caller. If a statement fails, the session is dropped and the error returned.
+    ///
+    /// * `statements` - the statements to run, in order.
+    pub fn with_session_init_sql(mut self, statements: &[&str]) -> Pool {
+        let statements: Vec<String> = statements.iter().map(|sql| sql.to_string()).collect();
+        let mut hasher = DefaultHasher::new();
+        statements.hash(&mut hasher);
+
+        self.session_init = if statements.is_empty() {
+            None
+        } else {
+            Some(Arc::new(SessionInit {
+                              statements: statements,
+                              tag: format!("{}{:016x}", SESSION_INIT_TAG_PREFIX, hasher.finish()),
+                          }))
+        };
+        self
+    }
+
+    /// Acquires a connection, returning the error code along with the error on failure. The code
+    /// is read immediately, as the error information is cleared by the next ODPI-C call.
+    fn acquire(&self,
+               username: Option<&str>,
+               password: Option<&str>,
+               conn_create_params: &ConnCreate)
+               -> ::std::result::Result<Connection, (i32, Error)> {
+        let username_s = ODPIStr::from(username);
+        let password_s = ODPIStr::from(password);
+        let mut conn_cp = conn_create_params.inner();
+        let mut conn: *mut ODPIConn = ptr::null_mut();
+        let start = Instant::now();
+
+        let session_init = match self.session_init {
+            Some(ref init) if conn_cp.tag_length == 0 => Some(init),
+            _ => None,
+        };
+        let tag_s = ODPIStr::from(session_init.map(|init| &init.tag[..]));
+        if session_init.is_some() {
+            conn_cp.tag = tag_s.ptr();
+            conn_cp.tag_length = tag_s.len();
+        }
+
+        let res = unsafe {
+            externs::dpiPool_acquireConnection(self.inner,
+                                               username_s.ptr(),
+                                               username_s.len(),
+                                               password_s.ptr(),
+                                               password_s.len(),
+                                               &mut conn_cp,
+                                               &mut conn)
+        };
+
+        if res == DPI_SUCCESS {
+            track_ref!(created_in, self.context.inner(), "Connection", conn);
+            let mut conn = Connection::with_context(conn, self.context.clone());
+            let out_tag = if conn_cp.out_tag.is_null() || conn_cp.out_tag_length == 0 {
+                None
+            } else {
+                Some(ODPIStr::new(conn_cp.out_tag, conn_cp.out_tag_length).into())
+            };
+            let tag_found = if conn_cp.tag_length > 0 {
+                Some(conn_cp.out_tag_found != 0)
+            } else {
+                None
+            };
+            conn.set_acquired_tag(out_tag, tag_found);
+            if let Some(init) = session_init {
+                if conn_cp.out_tag_found == 0 {
+                    init_session(&conn, init).map_err(|err| (0, err))?;
+                }
+                conn.set_release_tag(Some(&init.tag));
+            }
+            conn.set_connect_time(start.elapsed());
+            if let Some(ref stats) = *self.stmt_cache_stats.borrow() {
+                conn.set_statement_cache_tracker(CacheTracker::with_pool_stats(stats.clone()));
+            }
+            Ok(conn)
+        } else {
+            let err = ErrorKind::Pool("dpiPool_acquireConnection".to_string());
+            Err((self.last_error_code(), err.into()))
+        }
+    }
+
+    /// Polls the session counts of the pool until a session is idle, or another can be opened,
+    /// or the timeout expires. The get mode of the pool is left alone, so other callers are not
+    /// affected by the wait.
+    fn wait_for_session(&self, timeout: Duration) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        let mut interval = 1;
+
+        loop {
+            if self.has_free_session()? {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(ErrorKind::PoolTimeout(duration_millis(timeout)).into());
+            }
+
+            thread::sleep(cmp::min(Duration::from_millis(interval), deadline - now));
+            interval = cmp::min(interval * 2, MAX_POLL_INTERVAL_MS);
+        }
+    }
+
+    /// Acquires up to `idle_count` idle sessions and drops them from the pool, stopping early when
+    /// no session is idle, so the acquire does not wait whatever the get mode of the pool. Every
+    /// session acquired is released, even if acquiring or dropping another fails, and the first
+    /// error is returned.
+    fn drop_idle(&self, idle_count: u32) -> Result<u32> {
+        let conn_cp: ConnCreate = Default::default();
+        let mut acquired = Vec::with_capacity(idle_count as usize);
+        let mut res = Ok(());
+        for _ in 0..idle_count {
+            match self.has_idle_session() {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => {
+                    res = Err(e);
+                    break;
+                }
+            }
+
+            match self.acquire(None, None, &conn_cp) {
+                Ok(conn) => acquired.push(conn),
+                Err((ORA_POOL_EXHAUSTED, _)) => break,
+                Err((_, err)) => {
+                    res = Err(err);
+                    break;
+                }
+            }
+        }
+
+        let mut dropped = 0;
+        for conn in acquired {
+            let closed = conn.close_drop();
+            let released = conn.release();
+            match closed.and(released) {
+                Ok(()) => dropped += 1,
+                Err(e) => {
+                    if res.is_ok() {
+                        res = Err(e);
+                    }
+                }
+            }
+        }
+
+        res.map(|_| dropped)
+    }
+
+    /// Acquires sessions until `min_sessions` are held or the pool is exhausted, then closes them
+    /// back to the pool. A session is only acquired while one is idle or another can be opened,
+    /// so the acquire does not wait whatever the get mode of the pool. Every session acquired is
+    /// closed and released, even if acquiring or closing another fails, and the first error is
+    /// returned.
+    fn hold_min_sessions(&self, conn_create_params: &ConnCreate) -> Result<()> {
+        let mut acquired = Vec::with_capacity(self.min_sessions as usize);
+        let mut res = Ok(());
+        while (acquired.len() as u32) < self.min_sessions {
+            match self.has_free_session() {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => {
+                    res = Err(e);
+                    break;
+                }
+            }
+
+            match self.acquire(None, None, conn_create_params) {
+                Ok(conn) => acquired.push(conn),
+                Err((ORA_POOL_EXHAUSTED, _)) => break,
+                Err((_, err)) => {
+                    res = Err(err);
+                    break;
+                }
+            }
+        }
+
+        for conn in acquired {
+            let closed = conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None);
+            let released = conn.release();
+            if let Err(e) = closed.and(released) {
+                if res.is_ok() {
+                    res = Err(e);
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Returns true if a session of the pool is idle.
+    fn has_idle_session(&self) -> Result<bool> {
+        let open = self.get_open_count()?;
+        Ok(self.get_busy_count()? < open)
+    }
+
+    /// Returns true if a session of the pool is idle, or another can be opened, so acquiring a
+    /// session does not wait.
+    fn has_free_session(&self) -> Result<bool> {
+        let open = self.get_open_count()?;
+        Ok(self.get_busy_count()? < open || self.max_sessions == 0 || open < self.max_sessions)
+    }
+
+    /// Returns the error code of the last ODPI-C call, or 0 if the pool has no context.
+    fn last_error_code(&self) -> i32 {
+        let context = self.context.inner();
+        if context.is_null() {
+            return 0;
+        }
+
+        let mut error_info: ODPIErrorInfo = Default::default();
+        unsafe {
+            externs::dpiContext_getError(context, &mut error_info);
+        }
+        error_info.code
+    }
+
+    /// Checks that the pool's context is alive and the credentials given when acquiring a session
+    /// are valid. Sessions in a homogeneous pool always use the credentials the pool was created
+    /// with.
+    fn validate_acquire(&self,
+                        username: Option<&str>,
+                        password: Option<&str>,
+                        conn_create_params: &ConnCreate)
+                        -> Result<()> {
+        self.context.check()?;
+        let username = username.unwrap_or("");
+        let password = password.unwrap_or("");
+
+        if self.homogeneous && !(username.is_empty() && password.is_empty()) {
+            let err = "credentials cannot be given when acquiring from a homogeneous pool";
+            return Err(ErrorKind::Config(err.to_string()).into());
+        }
+
+        config::check_credentials(username,
+                                  password,
+                                  conn_create_params.get_external_auth() != 0,
+                                  false)
+    }
+}
+
+/// Runs the session initialization statements on a new session. If one fails, the session is
+/// dropped from the pool.
+fn init_session(conn: &Connection, init: &SessionInit) -> Result<()> {
+    for sql in &init.statements {
+        let res = conn.prepare_stmt(Some(sql), None, false)
+            .and_then(|stmt| {
+                          let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT);
+                          stmt.release()?;
+                          res
+                      });
+
+        if let Err(err) = res {
+            conn.close_drop()?;
+            conn.release()?;
+            return Err(err);
+        }
+    }
+    Ok(())
 }
 
 impl From<*mut ODPIPool> for Pool {
     fn from(inner: *mut ODPIPool) -> Pool {
-        Pool { inner: inner }
+        track_ref!(wrapped, "Pool", inner);
+        Pool {
+            inner: inner,
+            context: ContextRef::none(),
+            wait_timeout: Cell::new(None),
+            connect_string: None,
+            homogeneous: false,
+            min_sessions: 0,
+            max_sessions: 0,
+            stmt_cache_stats: RefCell::new(None),
+            session_init: None,
+            threaded: false,
+        }
+    }
+}
+
+impl PoolHandle {
+    /// Turns the handle into a `Pool` owning its reference, for use on the current thread. The
+    /// `Pool` should be released when it is no longer needed.
+    pub fn into_pool(mut self) -> Pool {
+        let inner = mem::replace(&mut self.inner, ptr::null_mut());
+        let mut pool: Pool = inner.into();
+        pool.context = self.context.clone();
+        pool.wait_timeout.set(self.wait_timeout);
+        pool.connect_string = self.connect_string.take();
+        pool.homogeneous = self.homogeneous;
+        pool.min_sessions = self.min_sessions;
+        pool.max_sessions = self.max_sessions;
+        pool.stmt_cache_stats = RefCell::new(self.stmt_cache_stats.take());
+        pool.session_init = self.session_init.take();
+        pool.threaded = true;
+        pool
+    }
+}
+
+impl Drop for PoolHandle {
+    fn drop(&mut self) {
+        if !self.inner.is_null() {
+            let _ = Pool::from(self.inner).release();
+        }
+    }
+}
+
+/// The `Debug` output identifies the database the pool connects to and its session counts, but
+/// never includes credentials.
+impl fmt::Debug for Pool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("connect_string", &self.connect_string)
+            .field("open_count", &self.get_open_count().ok())
+            .field("busy_count", &self.get_busy_count().ok())
+            .field("get_mode", &self.get_get_mode().ok())
+            .finish()
     }
 }
@@ -10,9 +10,16 @@
 //! This structure is used for passing query metadata from ODPI-C.
 use objecttype::ObjectType;
 use odpi::enums;
+use oracletype::{ConversionLoss, OracleType};
+use std::fmt;
 use odpi::structs::ODPIQueryInfo;
 use util::ODPIStr;
 
+/// The schema of the XMLTYPE object type.
+const XML_TYPE_SCHEMA: &str = "SYS";
+/// The name of the XMLTYPE object type.
+const XML_TYPE_NAME: &str = "XMLTYPE";
+
 /// This structure is used for passing query metadata from ODPI-C. It is populated by the function
 /// `Statement::get_query_info()`. All values remain valid as long as a reference is held to the
 /// statement and the statement is not re-executed or closed.
@@ -41,7 +48,21 @@ impl Info {
     /// Specifies the type of the column that is being queried. It will be one of the values from
     /// the enumeration `ODPIOracleTypeNum`.
     pub fn oracle_type_num(&self) -> enums::ODPIOracleTypeNum {
-        self.inner.oracle_type_num
+        self.inner.type_info().oracle_type_num
+    }
+
+    /// Returns the type of the column, with its size, precision and scale, or None if ODPI-C did
+    /// not report a database type.
+    pub fn oracle_type(&self) -> Option<OracleType> {
+        let type_info = self.inner.type_info();
+        let size = match type_info.oracle_type_num {
+            enums::ODPIOracleTypeNum::Raw => type_info.db_size_in_bytes,
+            _ => type_info.size_in_chars,
+        };
+        OracleType::from_odpi(type_info.oracle_type_num,
+                              size,
+                              type_info.precision,
+                              type_info.scale)
     }
 
     /// Get the `default_native_type_num` value.
@@ -49,7 +70,7 @@ impl Info {
     /// Specifies the default native type for the column that is being queried. It will be one of
     /// the values from the enumeration `ODPINativeTypeNum`.
     pub fn default_native_type_num(&self) -> enums::ODPINativeTypeNum {
-        self.inner.default_native_type_num
+        self.inner.type_info().default_native_type_num
     }
 
     /// Get the `db_size_in_bytes` value.
@@ -58,7 +79,7 @@ impl Info {
     /// queried. This value is only populated for strings and binary columns. For all other columns
     /// the value is zero.
     pub fn db_size_in_bytes(&self) -> u32 {
-        self.inner.db_size_in_bytes
+        self.inner.type_info().db_size_in_bytes
     }
 
     /// Get the `client_size_in_bytes` value.
@@ -67,7 +88,7 @@ impl Info {
     /// queried. This value is only populated for strings and binary columns. For all other columns
     /// the value is zero.
     pub fn client_size_in_bytes(&self) -> u32 {
-        self.inner.client_size_in_bytes
+        self.inner.type_info().client_size_in_bytes
     }
 
     /// Get the `size_in_chars` value.
@@ -75,7 +96,7 @@ impl Info {
     /// Specifies the size in characters of the column that is being queried. This value is only
     /// populated for string columns. For all other columns the value is zero.
     pub fn size_in_chars(&self) -> u32 {
-        self.inner.size_in_chars
+        self.inner.type_info().size_in_chars
     }
 
     /// Get the `precision` value.
@@ -83,7 +104,7 @@ impl Info {
     /// Specifies the precision of the column that is being queried. This value is only populated
     /// for numeric and timestamp columns. For all other columns the value is zero.
     pub fn precision(&self) -> i16 {
-        self.inner.precision
+        self.inner.type_info().precision
     }
 
     /// Get the `scale` value.
@@ -91,7 +112,7 @@ impl Info {
     /// Specifies the scale of the column that is being queried. This value is only populated for
     /// numeric columns. For all other columns the value is zero.
     pub fn scale(&self) -> i8 {
-        self.inner.scale
+        self.inner.type_info().scale
     }
 
     /// Get the `null_ok' value.
@@ -106,10 +127,198 @@ impl Info {
     /// Specifies a reference to the type of the object that is being queried. This value is only
     /// populated for named type columns. For all other columns the value is None.
     pub fn object_type(&self) -> Option<ObjectType> {
-        if self.inner.object_type.is_null() {
+        let object_type = self.inner.type_info().object_type;
+        if object_type.is_null() {
             None
         } else {
-            Some(self.inner.object_type.into())
+            Some(object_type.into())
+        }
+    }
+
+    /// Is the column an XMLTYPE? Such columns are reported as named type columns of the opaque
+    /// type `SYS.XMLTYPE`, which ODPI-C cannot fetch; see the `xml` module for selecting them as
+    /// CLOB instead.
+    pub fn is_xml(&self) -> bool {
+        self.object_type()
+            .and_then(|object_type| object_type.get_type_info().ok())
+            .map_or(false, |info| info.schema() == XML_TYPE_SCHEMA && info.name() == XML_TYPE_NAME)
+    }
+}
+
+/// A snapshot of the metadata of a query column, taken from `Info`. Unlike `Info` it owns its
+/// values, so it remains valid after the statement is re-executed, closed or released. The object
+/// type of named type columns is not kept; use `Statement::get_query_info()` for it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ColumnInfo {
+    /// The position of the column, starting from 1.
+    position: u32,
+    /// The name of the column.
+    name: String,
+    /// The type of the column.
+    oracle_type_num: enums::ODPIOracleTypeNum,
+    /// The type of the column, with its size, precision and scale.
+    oracle_type: Option<OracleType>,
+    /// The default native type of the column.
+    default_native_type_num: enums::ODPINativeTypeNum,
+    /// The size in bytes of the column, from the database's perspective.
+    db_size_in_bytes: u32,
+    /// The size in bytes of the column, from the client's perspective.
+    client_size_in_bytes: u32,
+    /// The size in characters of the column.
+    size_in_chars: u32,
+    /// The precision of the column.
+    precision: i16,
+    /// The scale of the column.
+    scale: i8,
+    /// Whether the column may return null values.
+    null_ok: bool,
+    /// Whether the column is an XMLTYPE.
+    is_xml: bool,
+}
+
+impl ColumnInfo {
+    /// Create a new `ColumnInfo` struct from the query metadata of the column at the given
+    /// position.
+    ///
+    /// * `position` - the position of the column, starting from 1.
+    /// * `info` - the query metadata of the column.
+    pub fn new(position: u32, info: &Info) -> ColumnInfo {
+        ColumnInfo {
+            position: position,
+            name: info.name(),
+            oracle_type_num: info.oracle_type_num(),
+            oracle_type: info.oracle_type(),
+            default_native_type_num: info.default_native_type_num(),
+            db_size_in_bytes: info.db_size_in_bytes(),
+            client_size_in_bytes: info.client_size_in_bytes(),
+            size_in_chars: info.size_in_chars(),
+            precision: info.precision(),
+            scale: info.scale(),
+            null_ok: info.null_ok(),
+            is_xml: info.is_xml(),
+        }
+    }
+
+    /// Get the `position` value, starting from 1.
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+
+    /// Get the `name` value. See `Info::name()`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the `oracle_type_num` value. See `Info::oracle_type_num()`.
+    pub fn oracle_type_num(&self) -> enums::ODPIOracleTypeNum {
+        self.oracle_type_num
+    }
+
+    /// Get the `oracle_type` value. See `Info::oracle_type()`.
+    pub fn oracle_type(&self) -> Option<OracleType> {
+        self.oracle_type
+    }
+
+    /// Get the `default_native_type_num` value. See `Info::default_native_type_num()`.
+    pub fn default_native_type_num(&self) -> enums::ODPINativeTypeNum {
+        self.default_native_type_num
+    }
+
+    /// Get the `db_size_in_bytes` value. See `Info::db_size_in_bytes()`.
+    pub fn db_size_in_bytes(&self) -> u32 {
+        self.db_size_in_bytes
+    }
+
+    /// Get the `client_size_in_bytes` value. See `Info::client_size_in_bytes()`.
+    pub fn client_size_in_bytes(&self) -> u32 {
+        self.client_size_in_bytes
+    }
+
+    /// Get the `size_in_chars` value. See `Info::size_in_chars()`.
+    pub fn size_in_chars(&self) -> u32 {
+        self.size_in_chars
+    }
+
+    /// Get the `precision` value. See `Info::precision()`.
+    pub fn precision(&self) -> i16 {
+        self.precision
+    }
+
+    /// Get the `scale` value. See `Info::scale()`.
+    pub fn scale(&self) -> i8 {
+        self.scale
+    }
+
+    /// Get the `null_ok` value. See `Info::null_ok()`.
+    pub fn null_ok(&self) -> bool {
+        self.null_ok
+    }
+
+    /// Get the `is_xml` value. See `Info::is_xml()`.
+    pub fn is_xml(&self) -> bool {
+        self.is_xml
+    }
+}
+
+/// A column fetched as a native type that may lose data, e.g. a NUMBER with a scale fetched into
+/// an `i64` buffer, whose fractional part is truncated. Returned by
+/// `Statement::get_coercion_warnings()`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoercionWarning {
+    /// The column that is fetched.
+    column: ColumnInfo,
+    /// The native type the column is fetched as.
+    native_type: enums::ODPINativeTypeNum,
+    /// The data that may be lost.
+    loss: ConversionLoss,
+}
+
+impl CoercionWarning {
+    /// Create a new `CoercionWarning` if fetching the column as the given native type may lose
+    /// data, or return None.
+    ///
+    /// * `column` - the column that is fetched.
+    /// * `native_type` - the native type the column is fetched as.
+    pub fn new(column: ColumnInfo,
+               native_type: enums::ODPINativeTypeNum)
+               -> Option<CoercionWarning> {
+        let loss = column
+            .oracle_type()
+            .and_then(|oracle_type| oracle_type.conversion_loss(native_type));
+
+        loss.map(|loss| {
+                     CoercionWarning {
+                         column: column,
+                         native_type: native_type,
+                         loss: loss,
+                     }
+                 })
+    }
+
+    /// Get the `column` value.
+    pub fn column(&self) -> &ColumnInfo {
+        &self.column
+    }
+
+    /// Get the `loss` value.
+    pub fn loss(&self) -> ConversionLoss {
+        self.loss
+    }
+
+    /// Get the `native_type` value.
+    pub fn native_type(&self) -> enums::ODPINativeTypeNum {
+        self.native_type
+    }
+}
+
+/// Names the column, its type and the native type, e.g. `column 2 (PRICE) NUMBER(10,2) fetched
+/// as Int64: the fractional part is truncated`.
+impl fmt::Display for CoercionWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "column {} ({}) ", self.column.position(), self.column.name())?;
+        if let Some(oracle_type) = self.column.oracle_type() {
+            write!(f, "{} ", oracle_type)?;
         }
+        write!(f, "fetched as {:?}: {}", self.native_type, self.loss)
     }
 }
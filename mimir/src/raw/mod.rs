@@ -0,0 +1,28 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The low-level FFI surface of ODPI-C: the C structures, the opaque handle types, the `extern`
+//! functions and `ODPIStr`, the string view used to pass text across the boundary.
+//!
+//! Nothing here is needed to use the safe API, which is pointer-free: values are read and written
+//! through `Data`, query values are returned as `Data`, and notifications, error information and
+//! object metadata are decoded into owned types such as `SubscrMessage`, `ErrorInfo` and
+//! `ObjectTypeInfo`. This module is for code that calls ODPI-C directly, or that builds the C
+//! structures itself, e.g. to test decoding. The structures hold raw pointers, whose validity is up
+//! to the caller, and every function in `externs` is `unsafe`.
+//!
+//! The accessors below hand out the ODPI-C structures behind the safe types: the data array of a
+//! variable, a `Data` referring to an arbitrary `ODPIData` pointer, and the metadata of object
+//! types and attributes, whose strings point into memory owned by ODPI-C.
+pub use data::from_ptr as data_from_ptr;
+pub use objectattr::info as object_attr_info;
+pub use objecttype::info as object_type_info;
+pub use odpi::{externs, opaque};
+pub use odpi::structs::*;
+pub use util::ODPIStr;
+pub use variable::data as var_data;
@@ -0,0 +1,216 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Reference count tracking for tests, enabled with the `ref-tracking` feature. Every handle
+//! created through the wrappers (connections, pools, statements, variables, LOBs, objects, object
+//! types, rowids, subscriptions and the AQ option and message property handles) is recorded with
+//! one reference, and each successful `add_ref()` and `release()` adjusts its count. When a
+//! `Context` is dropped, the handles created in it are checked, and the drop panics if any still
+//! holds references or was released more often than referenced, so leaks in both the crate and the
+//! calling code fail the test that caused them.
+//!
+//! The counts are kept per thread and per context, as contexts cannot be shared between threads.
+//! Handles wrapped from raw ODPI-C pointers with `From`, such as the statement of a REF CURSOR, are
+//! recorded without a reference, as it is owned elsewhere, and are attributed to the most recently
+//! created context of the thread that is still alive. Adding a reference to one and releasing it
+//! again is balanced; releasing one without adding a reference first is an over-release.
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::os::raw::c_void;
+
+thread_local! {
+    /// The reference counts of the tracked handles, keyed by handle kind and address, together with
+    /// the address of the context each handle belongs to.
+    static REFS: RefCell<BTreeMap<(&'static str, usize), (usize, i64)>> =
+        RefCell::new(BTreeMap::new());
+    /// The addresses of the contexts created on this thread that are still alive, oldest first.
+    static CONTEXTS: RefCell<Vec<usize>> = RefCell::new(Vec::new());
+}
+
+/// A handle whose references are not balanced.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Outstanding {
+    /// The address of the ODPI-C context the handle belongs to, or zero if it is not known.
+    context: usize,
+    /// The kind of handle, e.g. `Connection`.
+    kind: &'static str,
+    /// The address of the ODPI-C handle.
+    handle: usize,
+    /// The number of references still held. Negative if more references were released than
+    /// added.
+    refs: i64,
+}
+
+impl Outstanding {
+    /// Get the `context` value.
+    pub fn context(&self) -> usize {
+        self.context
+    }
+
+    /// Get the `kind` value.
+    pub fn kind(&self) -> &'static str {
+        self.kind
+    }
+
+    /// Get the `handle` value.
+    pub fn handle(&self) -> usize {
+        self.handle
+    }
+
+    /// Get the `refs` value.
+    pub fn refs(&self) -> i64 {
+        self.refs
+    }
+}
+
+impl fmt::Display for Outstanding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.refs > 0 {
+            write!(f,
+                   "{} {:#x} still holds {} reference(s)",
+                   self.kind,
+                   self.handle,
+                   self.refs)
+        } else {
+            write!(f,
+                   "{} {:#x} was released {} time(s) more than referenced",
+                   self.kind,
+                   self.handle,
+                   -self.refs)
+        }
+    }
+}
+
+/// Returns the most recently created context of this thread that is still alive, or zero.
+fn current_context() -> usize {
+    CONTEXTS.with(|contexts| contexts.borrow().last().cloned().unwrap_or(0))
+}
+
+/// Adjust the count of the given handle, forgetting it once the count is back to zero. A handle
+/// that is not tracked yet is attributed to the given context.
+fn adjust(context: usize, kind: &'static str, handle: *const c_void, delta: i64) {
+    REFS.with(|refs| {
+        let mut refs = refs.borrow_mut();
+        let balanced = {
+            let entry = refs.entry((kind, handle as usize)).or_insert((context, 0));
+            entry.1 += delta;
+            entry.1 == 0
+        };
+
+        if balanced {
+            refs.remove(&(kind, handle as usize));
+        }
+    });
+}
+
+/// Record a newly created context, which handles wrapped with `From` are attributed to until it
+/// is dropped.
+#[doc(hidden)]
+pub fn context_created(context: *const c_void) {
+    CONTEXTS.with(|contexts| contexts.borrow_mut().push(context as usize));
+}
+
+/// Record a context being dropped, panicking if any handle created in it is not balanced, unless
+/// the thread is already panicking. The handles of the context are forgotten either way.
+#[doc(hidden)]
+pub fn context_dropped(context: *const c_void) {
+    let context = context as usize;
+    CONTEXTS.with(|contexts| contexts.borrow_mut().retain(|&known| known != context));
+
+    let outstanding: Vec<Outstanding> = outstanding()
+        .into_iter()
+        .filter(|o| o.context == context)
+        .collect();
+    REFS.with(|refs| refs.borrow_mut().retain(|_, &mut (known, _)| known != context));
+
+    if !::std::thread::panicking() {
+        check(&outstanding);
+    }
+}
+
+/// Record a newly created handle, holding one reference, in the most recently created context of
+/// the thread that is still alive. Used where the context of the handle is not known.
+#[doc(hidden)]
+pub fn created(kind: &'static str, handle: *const c_void) {
+    created_in(current_context() as *const c_void, kind, handle);
+}
+
+/// Record a newly created handle, holding one reference, in the given context.
+#[doc(hidden)]
+pub fn created_in(context: *const c_void, kind: &'static str, handle: *const c_void) {
+    REFS.with(|refs| {
+                  let mut refs = refs.borrow_mut();
+                  let entry = refs.entry((kind, handle as usize)).or_insert((0, 0));
+                  entry.0 = context as usize;
+                  entry.1 += 1;
+              });
+}
+
+/// Record a handle wrapped from a raw ODPI-C pointer, without a reference, unless it is already
+/// tracked.
+#[doc(hidden)]
+pub fn wrapped(kind: &'static str, handle: *const c_void) {
+    let context = current_context();
+    REFS.with(|refs| {
+                  refs.borrow_mut()
+                      .entry((kind, handle as usize))
+                      .or_insert((context, 0));
+              });
+}
+
+/// Record a reference added to the handle.
+#[doc(hidden)]
+pub fn added(kind: &'static str, handle: *const c_void) {
+    adjust(current_context(), kind, handle, 1);
+}
+
+/// Record a reference to the handle being released.
+#[doc(hidden)]
+pub fn released(kind: &'static str, handle: *const c_void) {
+    adjust(current_context(), kind, handle, -1);
+}
+
+/// Returns the handles of the current thread whose references are not balanced, in every context,
+/// ordered by kind and address.
+pub fn outstanding() -> Vec<Outstanding> {
+    REFS.with(|refs| {
+                  refs.borrow()
+                      .iter()
+                      .filter(|&(_, &(_, refs))| refs != 0)
+                      .map(|(&(kind, handle), &(context, refs))| {
+                               Outstanding {
+                                   context: context,
+                                   kind: kind,
+                                   handle: handle,
+                                   refs: refs,
+                               }
+                           })
+                      .collect()
+              })
+}
+
+/// Panics, listing the offending handles, if any handle of the current thread, in any context,
+/// still holds references or was released more often than referenced. Each `Context` checks its
+/// own handles when it is dropped.
+pub fn assert_balanced() {
+    check(&outstanding());
+}
+
+/// Panics, listing the given handles, if there are any.
+fn check(outstanding: &[Outstanding]) {
+    if !outstanding.is_empty() {
+        let descs: Vec<String> = outstanding.iter().map(|o| o.to_string()).collect();
+        panic!("unbalanced handle references:\n  {}", descs.join("\n  "));
+    }
+}
+
+/// Forget every tracked handle of the current thread, e.g. after a test that leaks on purpose.
+pub fn reset() {
+    REFS.with(|refs| refs.borrow_mut().clear());
+}
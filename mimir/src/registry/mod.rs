@@ -0,0 +1,234 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A registry of named databases, for applications that connect to more than one. The connection
+//! parameters of each database are read from the environment or from a file, and a session pool
+//! is created for a database the first time it is asked for, then reused.
+//!
+//! From the environment, `<PREFIX>_DATABASES` holds a comma separated list of names, and the
+//! parameters of each are read as described in the `config` module, with the prefix
+//! `<PREFIX>_<NAME>` and the name in upper case. A file is a TOML document holding one table per
+//! database, with the parameters as string values using the keys described in the `config`
+//! module.
+//!
+//! Each `DatabasePool` handed out shares the context with the registry, so a pool outlives
+//! neither. The pools are closed by `close()`. A pool is released, and closed if it is still open,
+//! when the registry and every caller holding it have dropped it; the context is then dropped
+//! with the last of the registry and its pools.
+//!
+//! ```ignore
+//! // [reporting]
+//! // username = "report"
+//! // password = "secret"
+//! // connect_string = "//reports.example.com/ORCL"
+//! let databases = Databases::from_file(Context::create()?, "databases.toml")?;
+//! let conn = databases.pool("reporting")?.acquire_connection(None, None, None)?;
+//! ```
+use config::{self, ConnectParams, DEFAULT_ENV_PREFIX};
+use context::Context;
+use error::{ErrorKind, Result};
+use odpi::flags;
+use pool::Pool;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::mem;
+use std::ops::Deref;
+use std::path::Path;
+use std::rc::Rc;
+use toml::Value;
+
+/// The named databases known to an application, and the session pools created for them.
+pub struct Databases {
+    /// The context the pools are created in, shared with the pools.
+    context: Rc<Context>,
+    /// The connection parameters, by database name.
+    params: BTreeMap<String, ConnectParams>,
+    /// The pools created so far, by database name.
+    pools: RefCell<BTreeMap<String, Rc<DatabasePool>>>,
+}
+
+/// A session pool created by `Databases::pool()`. It dereferences to the `Pool`, and holds a
+/// reference to the context the pool was created in, so the context is not dropped before the
+/// pool. The pool is closed, if still open, and released when dropped.
+pub struct DatabasePool {
+    /// The session pool.
+    pool: Pool,
+    /// The context the pool was created in.
+    context: Rc<Context>,
+}
+
+impl DatabasePool {
+    /// Get the `context` value.
+    pub fn get_context(&self) -> &Context {
+        &self.context
+    }
+}
+
+impl Deref for DatabasePool {
+    type Target = Pool;
+
+    fn deref(&self) -> &Pool {
+        &self.pool
+    }
+}
+
+/// Closes and releases the pool, before the reference to its context is dropped.
+impl Drop for DatabasePool {
+    fn drop(&mut self) {
+        let _ = self.pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT);
+        let _ = self.pool.release();
+    }
+}
+
+impl Databases {
+    /// Create a new `Databases` registry.
+    ///
+    /// * `context` - the context the pools are created in.
+    /// * `params` - the connection parameters, by database name.
+    pub fn new(context: Context, params: BTreeMap<String, ConnectParams>) -> Databases {
+        Databases {
+            context: Rc::new(context),
+            params: params,
+            pools: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Closes every pool created so far and drops the registry's references to them. Each pool
+    /// is released once no caller holds it either. A later call to `pool()` creates a new pool.
+    pub fn close(&self) -> Result<()> {
+        let pools = mem::replace(&mut *self.pools.borrow_mut(), BTreeMap::new());
+        for pool in pools.values() {
+            pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT)?;
+        }
+        Ok(())
+    }
+
+    /// Read the databases from the environment, using the `MIMIR` prefix.
+    ///
+    /// * `context` - the context the pools are created in.
+    pub fn from_env(context: Context) -> Result<Databases> {
+        Ok(Databases::new(context, from_env_prefix(DEFAULT_ENV_PREFIX)?))
+    }
+
+    /// Read the databases from the file at the given path.
+    ///
+    /// * `context` - the context the pools are created in.
+    /// * `path` - the path to the file.
+    pub fn from_file<P: AsRef<Path>>(context: Context, path: P) -> Result<Databases> {
+        Ok(Databases::new(context, from_file(path)?))
+    }
+
+    /// Get the `context` value.
+    pub fn get_context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Get the connection parameters of the named database, if it is known.
+    ///
+    /// * `name` - the name of the database.
+    pub fn get_params(&self, name: &str) -> Option<&ConnectParams> {
+        self.params.get(name)
+    }
+
+    /// Returns the names of the known databases, in order.
+    pub fn names(&self) -> Vec<&str> {
+        self.params.keys().map(|name| &name[..]).collect()
+    }
+
+    /// Returns the session pool of the named database, creating it with the default pool
+    /// parameters the first time the database is asked for. Returns `ErrorKind::Config` if the
+    /// database is not known.
+    ///
+    /// * `name` - the name of the database.
+    pub fn pool(&self, name: &str) -> Result<Rc<DatabasePool>> {
+        if let Some(pool) = self.pools.borrow().get(name) {
+            return Ok(Rc::clone(pool));
+        }
+
+        let params = self.params
+            .get(name)
+            .ok_or_else(|| ErrorKind::Config(format!("unknown database '{}'", name)))?;
+        let pool = Rc::new(DatabasePool {
+                               pool: params.create_pool(&*self.context, None)?,
+                               context: Rc::clone(&self.context),
+                           });
+        self.pools
+            .borrow_mut()
+            .insert(name.to_string(), Rc::clone(&pool));
+        Ok(pool)
+    }
+}
+
+/// Read the connection parameters of the databases from the environment, using the given prefix.
+///
+/// * `prefix` - the environment variable prefix, e.g. `APP` to read the names from
+/// `APP_DATABASES`.
+pub fn from_env_prefix(prefix: &str) -> Result<BTreeMap<String, ConnectParams>> {
+    let names = env::var(format!("{}_DATABASES", prefix))?;
+    let mut databases = BTreeMap::new();
+
+    for name in names.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let db_prefix = format!("{}_{}", prefix, name.to_uppercase());
+        databases.insert(name.to_string(), ConnectParams::from_env_prefix(&db_prefix)?);
+    }
+
+    Ok(databases)
+}
+
+/// Read the connection parameters of the databases from the file at the given path.
+///
+/// * `path` - the path to the file.
+pub fn from_file<P: AsRef<Path>>(path: P) -> Result<BTreeMap<String, ConnectParams>> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    parse(&contents)
+}
+
+/// Parse the connection parameters of the databases from the contents of a TOML file, with a
+/// table of string values per database.
+///
+/// * `contents` - the TOML document to parse.
+pub fn parse(contents: &str) -> Result<BTreeMap<String, ConnectParams>> {
+    let document: Value = contents
+        .parse()
+        .map_err(|e| ErrorKind::Config(format!("invalid TOML: {}", e)))?;
+    let tables = document
+        .as_table()
+        .ok_or_else(|| ErrorKind::Config("expected a table per database".to_string()))?;
+    let mut databases = BTreeMap::new();
+
+    for (name, table) in tables {
+        let table = table
+            .as_table()
+            .ok_or_else(|| ErrorKind::Config(format!("'{}' is not a [{}] table", name, name)))?;
+        let mut values = HashMap::new();
+
+        for (key, value) in table {
+            if !config::KEYS.contains(&&key[..]) {
+                let err = format!("database '{}': unknown key '{}'", name, key);
+                return Err(ErrorKind::Config(err).into());
+            }
+            let value = value
+                .as_str()
+                .ok_or_else(|| {
+                                let err = format!("database '{}': '{}' is not a string", name, key);
+                                ErrorKind::Config(err)
+                            })?;
+            values.insert(&key[..], value.to_string());
+        }
+
+        let params = config::from_values(&values)
+            .map_err(|e| ErrorKind::Config(format!("database '{}': {}", name, e)))?;
+        databases.insert(name.clone(), params);
+    }
+
+    Ok(databases)
+}
@@ -0,0 +1,156 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Support for the Oracle query result cache. Individual queries can be cached by preparing them
+//! with `PrepareOptions::result_cache` set, which adds the `RESULT_CACHE` hint to the query. All
+//! queries of a session can be cached by setting the session's `ResultCacheMode` to `Force`,
+//! either with `Connection::set_result_cache_mode()` or with the `result_cache_mode` value of
+//! `ConnectParams`. The client result cache is used when it has been sized on the server
+//! (`CLIENT_RESULT_CACHE_SIZE`); otherwise results are cached in the server result cache.
+use error::{ErrorKind, Result};
+use std::str::FromStr;
+
+/// The hint that asks the database to cache the results of a query.
+pub const RESULT_CACHE_HINT: &str = "RESULT_CACHE";
+
+/// The result cache mode of a session.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ResultCacheMode {
+    /// Only queries with the `RESULT_CACHE` hint are cached. This is the Oracle default.
+    Manual,
+    /// All queries are cached unless they have the `NO_RESULT_CACHE` hint.
+    Force,
+}
+
+impl ResultCacheMode {
+    /// The ALTER SESSION statement that sets this mode.
+    pub fn alter_session_sql(&self) -> &'static str {
+        match *self {
+            ResultCacheMode::Manual => "ALTER SESSION SET RESULT_CACHE_MODE = MANUAL",
+            ResultCacheMode::Force => "ALTER SESSION SET RESULT_CACHE_MODE = FORCE",
+        }
+    }
+}
+
+impl Default for ResultCacheMode {
+    fn default() -> ResultCacheMode {
+        ResultCacheMode::Manual
+    }
+}
+
+impl FromStr for ResultCacheMode {
+    type Err = ::error::Error;
+
+    fn from_str(s: &str) -> Result<ResultCacheMode> {
+        match &s.to_lowercase()[..] {
+            "manual" => Ok(ResultCacheMode::Manual),
+            "force" => Ok(ResultCacheMode::Force),
+            _ => Err(ErrorKind::Config(format!("invalid result cache mode '{}'", s)).into()),
+        }
+    }
+}
+
+/// Options used when preparing a statement with `Connection::prepare_stmt_with()`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PrepareOptions<'a> {
+    /// The key used to search for the statement in the statement cache. None uses the SQL text.
+    pub tag: Option<&'a str>,
+    /// Should the statement be scrollable?
+    pub scrollable: bool,
+    /// Should the `RESULT_CACHE` hint be added to the query? This has no effect on statements
+    /// other than queries.
+    pub result_cache: bool,
+}
+
+/// Returns the given query with the `RESULT_CACHE` hint added to its top-level SELECT. A hint
+/// comment already following the SELECT is extended rather than duplicated. Statements other than
+/// queries, and queries that already have the hint, are returned unchanged.
+///
+/// * `sql` - the query to add the hint to.
+pub fn add_result_cache_hint(sql: &str) -> String {
+    let select_end = match find_top_level_select(sql) {
+        Some(select_end) => select_end,
+        None => return sql.to_string(),
+    };
+
+    let rest = &sql[select_end..];
+    let trimmed = rest.trim_left();
+
+    if trimmed.starts_with("/*+") {
+        let hint_start = select_end + (rest.len() - trimmed.len()) + 3;
+        let hint_end = sql[hint_start..].find("*/").map_or(sql.len(), |end| hint_start + end);
+
+        if sql[hint_start..hint_end]
+               .to_uppercase()
+               .split(|c: char| !c.is_alphanumeric() && c != '_')
+               .any(|word| word == RESULT_CACHE_HINT) {
+            sql.to_string()
+        } else {
+            format!("{} {}{}", &sql[..hint_start], RESULT_CACHE_HINT, &sql[hint_start..])
+        }
+    } else {
+        format!("{} /*+ {} */{}", &sql[..select_end], RESULT_CACHE_HINT, rest)
+    }
+}
+
+/// Returns the byte offset just past the SELECT keyword of the top-level query, if the statement
+/// is a query. A leading WITH clause is skipped, along with comments and string literals.
+fn find_top_level_select(sql: &str) -> Option<usize> {
+    let upper = sql.to_ascii_uppercase();
+    let bytes = upper.as_bytes();
+    let mut depth = 0;
+    let mut first_word = true;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\'' => {
+                idx += upper[idx + 1..].find('\'').map_or(bytes.len(), |end| end + 2);
+                continue;
+            }
+            b'-' if upper[idx..].starts_with("--") => {
+                idx += upper[idx..].find('\n').unwrap_or(bytes.len() - idx);
+                continue;
+            }
+            b'/' if upper[idx..].starts_with("/*") => {
+                idx += upper[idx..].find("*/").map_or(bytes.len() - idx, |end| end + 2);
+                continue;
+            }
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            c if is_word_byte(c) => {
+                let start = idx;
+                while idx < bytes.len() && is_word_byte(bytes[idx]) {
+                    idx += 1;
+                }
+                let word = &upper[start..idx];
+
+                if first_word {
+                    first_word = false;
+                    if word != "SELECT" && word != "WITH" {
+                        return None;
+                    }
+                }
+
+                if depth == 0 && word == "SELECT" {
+                    return Some(idx);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        idx += 1;
+    }
+
+    None
+}
+
+/// Can the given byte be part of an unquoted identifier or keyword?
+fn is_word_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'$' || c == b'#'
+}
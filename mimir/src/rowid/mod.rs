@@ -37,7 +37,10 @@ impl Rowid {
     /// created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiRowid_addRef(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(added, "Rowid", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Rowid("dpiRowid_addRef".to_string()))
     }
 
@@ -62,13 +65,17 @@ impl Rowid {
     /// when this count reaches zero, the memory associated with the rowid is freed.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiRowid_release(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(released, "Rowid", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Rowid("dpiRowid_release".to_string()))
     }
 }
 
 impl From<*mut ODPIRowid> for Rowid {
     fn from(inner: *mut ODPIRowid) -> Rowid {
+        track_ref!(wrapped, "Rowid", inner);
         Rowid { inner: inner }
     }
 }
@@ -0,0 +1,260 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Helpers for building dynamic SQL safely. Values should be bound rather than formatted into the
+//! SQL wherever possible; names cannot be bound, so these helpers validate or quote them instead.
+//!
+//! * `check_identifier()` accepts only names that are valid without quotes, and returns them in
+//! upper case, the way Oracle stores them.
+//! * `quote_identifier()` wraps any name Oracle can store in double quotes, keeping its case.
+//! * `quote_literal()` wraps a string in single quotes, doubling the quotes inside it.
+//!
+//! The limits depend on the server. `ServerFeatures`, returned by `Connection::server_features()`,
+//! describes what the server supports, and the SQL builders consult it to generate SQL the server
+//! accepts.
+//!
+//! ```ignore
+//! let table = sql::quote_identifier("Order Lines")?;
+//! let sql = format!("SELECT * FROM {} WHERE note = {}", table, sql::quote_literal("it's"));
+//! ```
+use common::version::Info;
+use error::{ErrorKind, Result};
+
+/// The maximum length of an identifier, in bytes, from Oracle 12.2 on.
+pub const MAX_IDENTIFIER_LEN: usize = 128;
+
+/// The maximum length of an identifier, in bytes, before Oracle 12.2.
+pub const LEGACY_MAX_IDENTIFIER_LEN: usize = 30;
+
+/// The Oracle reserved words, which cannot be used as unquoted identifiers. Sorted, for binary
+/// search.
+const RESERVED_WORDS: &[&str] = &["ACCESS", "ADD", "ALL", "ALTER", "AND", "ANY", "AS", "ASC",
+                                   "AUDIT", "BETWEEN", "BY", "CHAR", "CHECK", "CLUSTER", "COLUMN",
+                                   "COMMENT", "COMPRESS", "CONNECT", "CREATE", "CURRENT", "DATE",
+                                   "DECIMAL", "DEFAULT", "DELETE", "DESC", "DISTINCT", "DROP",
+                                   "ELSE", "EXCLUSIVE", "EXISTS", "FILE", "FLOAT", "FOR", "FROM",
+                                   "GRANT", "GROUP", "HAVING", "IDENTIFIED", "IMMEDIATE", "IN",
+                                   "INCREMENT", "INDEX", "INITIAL", "INSERT", "INTEGER",
+                                   "INTERSECT", "INTO", "IS", "LEVEL", "LIKE", "LOCK", "LONG",
+                                   "MAXEXTENTS", "MINUS", "MLSLABEL", "MODE", "MODIFY", "NOAUDIT",
+                                   "NOCOMPRESS", "NOT", "NOWAIT", "NULL", "NUMBER", "OF",
+                                   "OFFLINE", "ON", "ONLINE", "OPTION", "OR", "ORDER", "PCTFREE",
+                                   "PRIOR", "PRIVILEGES", "PUBLIC", "RAW", "RENAME", "RESOURCE",
+                                   "REVOKE", "ROW", "ROWID", "ROWNUM", "ROWS", "SELECT", "SESSION",
+                                   "SET", "SHARE", "SIZE", "SMALLINT", "START", "SUCCESSFUL",
+                                   "SYNONYM", "SYSDATE", "TABLE", "THEN", "TO", "TRIGGER", "UID",
+                                   "UNION", "UNIQUE", "UPDATE", "USER", "VALIDATE", "VALUES",
+                                   "VARCHAR", "VARCHAR2", "VIEW", "WHENEVER", "WHERE", "WITH"];
+
+/// Validates a name that is used without quotes, returning it in upper case. The name must start
+/// with an ASCII letter, hold only ASCII letters, digits, `_`, `$` and `#`, be at most
+/// `MAX_IDENTIFIER_LEN` bytes long, and not be a reserved word.
+///
+/// * `name` - the name to validate.
+pub fn check_identifier(name: &str) -> Result<String> {
+    check_identifier_with_limit(name, MAX_IDENTIFIER_LEN)
+}
+
+/// Validates a name like `check_identifier()`, with the given length limit, e.g. the one returned
+/// by `ServerFeatures::max_identifier_len()`.
+///
+/// * `name` - the name to validate.
+/// * `max_len` - the maximum length of the name, in bytes.
+pub fn check_identifier_with_limit(name: &str, max_len: usize) -> Result<String> {
+    let mut chars = name.chars();
+    let starts_alpha = chars.next().map_or(false, |c| c.is_ascii_alphabetic());
+    let rest_valid = chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '#');
+    let upper = name.to_uppercase();
+
+    if !starts_alpha || !rest_valid {
+        Err(ErrorKind::Identifier(format!("'{}' is not a simple identifier", name)).into())
+    } else if name.len() > max_len {
+        Err(ErrorKind::Identifier(format!("'{}' is longer than {} bytes", name, max_len)).into())
+    } else if is_reserved_word(&upper) {
+        Err(ErrorKind::Identifier(format!("'{}' is a reserved word", name)).into())
+    } else {
+        Ok(upper)
+    }
+}
+
+/// Validates a name made of identifiers separated by `.`, such as `schema.table`, returning it in
+/// upper case. Each part is validated with `check_identifier()`.
+///
+/// * `name` - the name to validate.
+pub fn check_qualified_name(name: &str) -> Result<String> {
+    check_qualified_name_with_limit(name, MAX_IDENTIFIER_LEN)
+}
+
+/// Validates a qualified name like `check_qualified_name()`, with the given length limit for each
+/// part.
+///
+/// * `name` - the name to validate.
+/// * `max_len` - the maximum length of each part of the name, in bytes.
+pub fn check_qualified_name_with_limit(name: &str, max_len: usize) -> Result<String> {
+    let parts = name.split('.')
+        .map(|part| check_identifier_with_limit(part, max_len))
+        .collect::<Result<Vec<String>>>()?;
+    Ok(parts.join("."))
+}
+
+/// Is the given word an Oracle reserved word? The comparison ignores case.
+///
+/// * `word` - the word to look up.
+pub fn is_reserved_word(word: &str) -> bool {
+    RESERVED_WORDS.binary_search(&&word.to_uppercase()[..]).is_ok()
+}
+
+/// Returns the maximum length of an identifier, in bytes, for the given server version.
+///
+/// * `version` - the server version, as returned by `Connection::get_server_version()`.
+pub fn max_identifier_len(version: &Info) -> usize {
+    ServerFeatures::new(version.major(), version.release_num()).max_identifier_len()
+}
+
+/// Returns the maximum length of an identifier, in bytes, allowed by the given `COMPATIBLE`
+/// setting of a database, e.g. `12.1.0.2.0`. A setting that cannot be parsed gets the legacy
+/// length. The server version can only lower the length, see `max_identifier_len()`.
+///
+/// * `compatible` - the value of the `COMPATIBLE` initialization parameter.
+pub fn compatible_max_identifier_len(compatible: &str) -> usize {
+    let mut parts = compatible.trim().split('.').map(|part| part.parse::<i32>());
+
+    match (parts.next(), parts.next()) {
+        (Some(Ok(major)), Some(Ok(release_num))) => {
+            ServerFeatures::new(major, release_num).max_identifier_len()
+        }
+        _ => LEGACY_MAX_IDENTIFIER_LEN,
+    }
+}
+
+/// Wraps a name in double quotes, making it case sensitive, and allowing reserved words, spaces
+/// and other characters. Oracle has no escape for a double quote inside a quoted identifier, so
+/// names holding `"` or NUL are rejected, as are empty names and names longer than
+/// `MAX_IDENTIFIER_LEN` bytes.
+///
+/// * `name` - the name to quote.
+pub fn quote_identifier(name: &str) -> Result<String> {
+    quote_identifier_with_limit(name, MAX_IDENTIFIER_LEN)
+}
+
+/// Wraps a name in double quotes like `quote_identifier()`, with the given length limit, e.g. the
+/// one returned by `max_identifier_len()`.
+///
+/// * `name` - the name to quote.
+/// * `max_len` - the maximum length of the name, in bytes, without the quotes.
+pub fn quote_identifier_with_limit(name: &str, max_len: usize) -> Result<String> {
+    if name.is_empty() {
+        Err(ErrorKind::Identifier("an identifier cannot be empty".to_string()).into())
+    } else if name.contains('"') || name.contains('\0') {
+        Err(ErrorKind::Identifier(format!("'{}' cannot be quoted", name.escape_default())).into())
+    } else if name.len() > max_len {
+        Err(ErrorKind::Identifier(format!("'{}' is longer than {} bytes", name, max_len)).into())
+    } else {
+        Ok(format!("\"{}\"", name))
+    }
+}
+
+/// Wraps a string in single quotes, doubling the single quotes inside it, for use as a string
+/// literal.
+///
+/// * `value` - the value of the literal.
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// What a server supports, as far as the SQL generated for it is concerned.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ServerFeatures {
+    /// The maximum length of an identifier, in bytes.
+    max_identifier_len: usize,
+    /// Can a MERGE statement leave out its WHEN MATCHED or WHEN NOT MATCHED clause?
+    optional_merge_clauses: bool,
+    /// Can the database be a multitenant container database, holding pluggable databases?
+    multitenant: bool,
+    /// Is the database a multitenant container database?
+    container_db: bool,
+}
+
+impl ServerFeatures {
+    /// Create the `ServerFeatures` of the given server release. The maximum identifier length
+    /// assumes the database is compatible with its release; see `with_max_identifier_len()`.
+    ///
+    /// * `major` - the major version, e.g. 12 for Oracle 12.2.
+    /// * `release_num` - the release version, e.g. 2 for Oracle 12.2.
+    pub fn new(major: i32, release_num: i32) -> ServerFeatures {
+        let long_identifiers = major > 12 || (major == 12 && release_num >= 2);
+        ServerFeatures {
+            max_identifier_len: if long_identifiers {
+                MAX_IDENTIFIER_LEN
+            } else {
+                LEGACY_MAX_IDENTIFIER_LEN
+            },
+            optional_merge_clauses: major >= 10,
+            multitenant: major >= 12,
+            container_db: false,
+        }
+    }
+
+    /// Get the `max_identifier_len` value.
+    pub fn max_identifier_len(&self) -> usize {
+        self.max_identifier_len
+    }
+
+    /// Get the `optional_merge_clauses` value.
+    pub fn optional_merge_clauses(&self) -> bool {
+        self.optional_merge_clauses
+    }
+
+    /// Get the `multitenant` value.
+    pub fn multitenant(&self) -> bool {
+        self.multitenant
+    }
+
+    /// Get the `container_db` value.
+    pub fn container_db(&self) -> bool {
+        self.container_db
+    }
+
+    /// Set whether the database is a container database, e.g. as returned by
+    /// `Connection::is_container_db()`. The release alone does not tell, as a database of a
+    /// multitenant release can still be created as a non-CDB.
+    ///
+    /// * `container_db` - is the database a container database?
+    pub fn with_container_db(mut self, container_db: bool) -> ServerFeatures {
+        self.container_db = container_db;
+        self
+    }
+
+    /// Set the maximum length of an identifier, e.g. the one returned by
+    /// `Connection::max_identifier_length()`, which also accounts for the `COMPATIBLE` setting of
+    /// the database.
+    ///
+    /// * `max_len` - the maximum length of an identifier, in bytes.
+    pub fn with_max_identifier_len(mut self, max_len: usize) -> ServerFeatures {
+        self.max_identifier_len = max_len;
+        self
+    }
+}
+
+impl Default for ServerFeatures {
+    /// The features of the latest releases.
+    fn default() -> ServerFeatures {
+        ServerFeatures {
+            max_identifier_len: MAX_IDENTIFIER_LEN,
+            optional_merge_clauses: true,
+            multitenant: true,
+            container_db: false,
+        }
+    }
+}
+
+impl<'a> From<&'a Info> for ServerFeatures {
+    fn from(version: &Info) -> ServerFeatures {
+        ServerFeatures::new(version.major(), version.release_num())
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A builder for the named binds of a statement, created with `Statement::binder()`. Values are
+//! collected with `Binder::set()` and checked against the bind variables of the statement, so an
+//! unknown name is reported when it is set and a bind variable without a value is reported before
+//! the statement is executed, rather than as ORA-01008 from the server.
+//!
+//! ```ignore
+//! let cols = stmt.binder().set("id", 5)?.set("name", "x")?.execute()?;
+//! ```
+use chrono::{DateTime, Duration, Utc};
+use data::{Data, YearsMonths};
+use error::{ErrorKind, Result};
+use odpi::enums::ODPINativeTypeNum;
+use odpi::flags;
+use odpi::structs::ODPIDataValueUnion;
+use statement::Statement;
+
+/// A value that can be bound with `Binder::set()`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BindValue {
+    /// A NULL value.
+    Null,
+    /// A signed integer.
+    Int64(i64),
+    /// An unsigned integer.
+    Uint64(u64),
+    /// A floating point number.
+    Double(f64),
+    /// A string, bound in the encoding used for CHAR data.
+    String(String),
+    /// A timestamp.
+    Timestamp(DateTime<Utc>),
+    /// An INTERVAL DAY TO SECOND.
+    IntervalDS(Duration),
+    /// An INTERVAL YEAR TO MONTH.
+    IntervalYM(YearsMonths),
+}
+
+impl BindValue {
+    /// The native type used to bind the value.
+    pub(crate) fn native_type(&self) -> ODPINativeTypeNum {
+        match *self {
+            BindValue::Null |
+            BindValue::Int64(_) => ODPINativeTypeNum::Int64,
+            BindValue::Uint64(_) => ODPINativeTypeNum::Uint64,
+            BindValue::Double(_) => ODPINativeTypeNum::Double,
+            BindValue::String(_) => ODPINativeTypeNum::Bytes,
+            BindValue::Timestamp(_) => ODPINativeTypeNum::Timestamp,
+            BindValue::IntervalDS(_) => ODPINativeTypeNum::IntervalDS,
+            BindValue::IntervalYM(_) => ODPINativeTypeNum::IntervalYM,
+        }
+    }
+
+    /// The data holding the value. String data refers to the value, so the value must outlive it.
+    pub(crate) fn to_data(&self) -> Data {
+        let data = Data::new(*self == BindValue::Null, ODPIDataValueUnion { as_int_64: 0 });
+
+        match *self {
+            BindValue::Null => {}
+            BindValue::Int64(val) => data.set_int64(val),
+            BindValue::Uint64(val) => data.set_uint64(val),
+            BindValue::Double(val) => data.set_double(val),
+            BindValue::String(ref val) => data.set_string(val),
+            BindValue::Timestamp(val) => data.set_utc(val),
+            BindValue::IntervalDS(val) => data.set_duration(val),
+            BindValue::IntervalYM(val) => data.set_years_months(val),
+        }
+        data
+    }
+}
+
+impl From<i32> for BindValue {
+    fn from(val: i32) -> BindValue {
+        BindValue::Int64(i64::from(val))
+    }
+}
+
+impl From<i64> for BindValue {
+    fn from(val: i64) -> BindValue {
+        BindValue::Int64(val)
+    }
+}
+
+impl From<u64> for BindValue {
+    fn from(val: u64) -> BindValue {
+        BindValue::Uint64(val)
+    }
+}
+
+impl From<f64> for BindValue {
+    fn from(val: f64) -> BindValue {
+        BindValue::Double(val)
+    }
+}
+
+impl<'a> From<&'a str> for BindValue {
+    fn from(val: &str) -> BindValue {
+        BindValue::String(val.to_string())
+    }
+}
+
+impl From<String> for BindValue {
+    fn from(val: String) -> BindValue {
+        BindValue::String(val)
+    }
+}
+
+impl From<DateTime<Utc>> for BindValue {
+    fn from(val: DateTime<Utc>) -> BindValue {
+        BindValue::Timestamp(val)
+    }
+}
+
+impl From<Duration> for BindValue {
+    fn from(val: Duration) -> BindValue {
+        BindValue::IntervalDS(val)
+    }
+}
+
+impl From<YearsMonths> for BindValue {
+    fn from(val: YearsMonths) -> BindValue {
+        BindValue::IntervalYM(val)
+    }
+}
+
+impl<T: Into<BindValue>> From<Option<T>> for BindValue {
+    fn from(val: Option<T>) -> BindValue {
+        val.map_or(BindValue::Null, Into::into)
+    }
+}
+
+/// Collects the named binds of a statement. See the module documentation.
+pub struct Binder<'a> {
+    /// The statement the values are bound to.
+    stmt: &'a Statement,
+    /// The normalized names of the bind variables of the statement, read on the first `set()`.
+    names: Option<Vec<String>>,
+    /// The values set so far, keyed by normalized name, in the order they were first set.
+    values: Vec<(String, BindValue)>,
+}
+
+impl<'a> Binder<'a> {
+    /// Create a new `Binder` for the given statement.
+    #[doc(hidden)]
+    pub fn new(stmt: &'a Statement) -> Binder<'a> {
+        Binder {
+            stmt: stmt,
+            names: None,
+            values: Vec::new(),
+        }
+    }
+
+    /// Binds all of the values to the statement. An error is returned, and nothing is bound, if a
+    /// bind variable of the statement has no value.
+    pub fn bind(mut self) -> Result<()> {
+        let names = self.names()?.to_vec();
+        let missing: Vec<String> = names
+            .iter()
+            .filter(|name| !self.values.iter().any(|&(ref set, _)| set == *name))
+            .map(|name| format!(":{}", name))
+            .collect();
+
+        if !missing.is_empty() {
+            let msg = format!("no value set for bind variable(s) {}", missing.join(", "));
+            return Err(ErrorKind::Bind(msg).into());
+        }
+
+        for &(ref name, ref value) in &self.values {
+            let data = value.to_data();
+            self.stmt
+                .bind_value_by_name(name, value.native_type(), &data)?;
+        }
+        Ok(())
+    }
+
+    /// Binds all of the values to the statement and executes it in the default mode. See
+    /// `bind()` and `Statement::execute()`.
+    ///
+    /// Returns the number of columns being queried.
+    pub fn execute(self) -> Result<u32> {
+        let stmt = self.stmt;
+        self.bind()?;
+        stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)
+    }
+
+    /// Sets the value of a bind variable, replacing any value set earlier. An error is returned if
+    /// the statement has no bind variable with the given name.
+    ///
+    /// * `name` - the name of the bind variable, with or without the leading colon. Unquoted names
+    /// are matched case insensitively.
+    /// * `value` - the value to bind. `None` binds NULL.
+    pub fn set<V>(mut self, name: &str, value: V) -> Result<Binder<'a>>
+        where V: Into<BindValue>
+    {
+        let name = normalize(name);
+
+        if !self.names()?.contains(&name) {
+            let msg = format!("the statement has no bind variable named :{}", name);
+            return Err(ErrorKind::Bind(msg).into());
+        }
+
+        let value = value.into();
+        match self.values.iter().position(|&(ref set, _)| *set == name) {
+            Some(idx) => self.values[idx].1 = value,
+            None => self.values.push((name, value)),
+        }
+        Ok(self)
+    }
+
+    /// The normalized names of the bind variables of the statement.
+    fn names(&mut self) -> Result<&[String]> {
+        if self.names.is_none() {
+            let count = self.stmt.get_bind_count()?;
+            let names = if count == 0 {
+                Vec::new()
+            } else {
+                self.stmt.get_bind_names(count)?
+            };
+            self.names = Some(names.iter().map(|name| normalize(name)).collect());
+        }
+
+        Ok(self.names.as_ref().map_or(&[], |names| &names[..]))
+    }
+}
+
+/// Strips the leading colon from a bind variable name, and upper cases it unless it is quoted.
+pub fn normalize(name: &str) -> String {
+    let name = name.trim_left_matches(':');
+
+    if name.starts_with('"') {
+        name.trim_matches('"').to_string()
+    } else {
+        name.to_uppercase()
+    }
+}
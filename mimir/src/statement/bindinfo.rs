@@ -0,0 +1,125 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Bind variable metadata, returned by `Statement::bind_info()`. In SQL statements each
+//! occurrence of a bind variable has its own position, so a name used more than once appears once
+//! per occurrence, with the later occurrences marked as duplicates. In PL/SQL statements each
+//! unique name has a single position.
+use statement::binder::normalize;
+
+/// Information about a bind variable of a prepared statement.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Info {
+    /// The name of the bind variable, without the leading colon.
+    name: String,
+    /// The position of the bind variable, starting from 1.
+    position: u32,
+    /// Is this a later occurrence of a name that appears earlier in the statement?
+    duplicate: bool,
+}
+
+impl Info {
+    /// Create a new `Info` struct.
+    pub fn new(name: &str, position: u32, duplicate: bool) -> Info {
+        Info {
+            name: name.to_string(),
+            position: position,
+            duplicate: duplicate,
+        }
+    }
+
+    /// Get the `duplicate` value.
+    ///
+    /// True if the name appears earlier in the statement. Binding by name sets every occurrence.
+    pub fn is_duplicate(&self) -> bool {
+        self.duplicate
+    }
+
+    /// Get the `name` value.
+    ///
+    /// The name of the bind variable as reported by Oracle: without the leading colon, and upper
+    /// cased unless it was quoted.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the `position` value.
+    ///
+    /// The position to use with the `bind_*_by_pos()` functions, starting from 1.
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+}
+
+/// Returns the bind variable occurrences in the given SQL, in order, as `Info` structs. String
+/// literals, quoted identifiers and comments are skipped. Returns None if an occurrence does not
+/// match one of the given unique names, in which case the SQL could not be scanned reliably.
+///
+/// * `sql` - the SQL text of the statement.
+/// * `names` - the unique bind variable names reported by Oracle.
+pub fn scan(sql: &str, names: &[String]) -> Option<Vec<Info>> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut infos: Vec<Info> = Vec::new();
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let c = chars[idx];
+        let next = chars.get(idx + 1).cloned();
+
+        if c == '\'' || c == '"' {
+            idx = skip_past(&chars, idx + 1, &[c]);
+        } else if c == '-' && next == Some('-') {
+            idx = skip_past(&chars, idx + 2, &['\n']);
+        } else if c == '/' && next == Some('*') {
+            idx = skip_past(&chars, idx + 2, &['*', '/']);
+        } else if c == ':' {
+            let start = idx + 1;
+            let end = if next == Some('"') {
+                skip_past(&chars, start + 1, &['"'])
+            } else {
+                start + chars[start..].iter().take_while(|c| is_name_char(**c)).count()
+            };
+
+            if end > start {
+                let raw: String = chars[start..end].iter().collect();
+                let name = normalize(&raw);
+
+                if !names.contains(&name) {
+                    return None;
+                }
+
+                let duplicate = infos.iter().any(|info| info.name == name);
+                infos.push(Info::new(&name, infos.len() as u32 + 1, duplicate));
+            }
+            idx = end;
+        } else {
+            idx += 1;
+        }
+    }
+
+    Some(infos)
+}
+
+/// Can the given character appear in an unquoted bind variable name?
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || c == '#'
+}
+
+/// Returns the index just past the first occurrence of the terminator at or after `from`, or the
+/// end of the text if there is none.
+fn skip_past(chars: &[char], from: usize, terminator: &[char]) -> usize {
+    let mut idx = from;
+
+    while idx + terminator.len() <= chars.len() {
+        if chars[idx..idx + terminator.len()] == *terminator {
+            return idx + terminator.len();
+        }
+        idx += 1;
+    }
+    chars.len()
+}
@@ -0,0 +1,274 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Typed column buffers filled by `Statement::fetch_into()`. Each column of a query is copied
+//! straight from the define variable into a caller-owned `Vec`, one per column, without creating
+//! intermediate `Data` values. A tuple of `Vec`s, one per column in query order, implements
+//! `FetchBuffers`. `Vec<T>` requires the column to be NOT NULL, while `Vec<Option<T>>` accepts
+//! NULL values. Supported element types are `i64`, `f64`, `String`, `Vec<u8>`, `DateTime<Utc>` and
+//! `DateTime<FixedOffset>` for the date and timestamp types, and `Duration` and `YearsMonths` for
+//! the interval types.
+//!
+//! A tuple of the same element types, with `Option<T>` for nullable columns, implements `FetchRow`
+//! and is returned by `Statement::single_row()`.
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use data::YearsMonths;
+use error::{ErrorKind, Result};
+use odpi::enums::ODPINativeTypeNum;
+use odpi::structs::ODPIData;
+use std::slice;
+
+/// A value that can be copied out of a define variable.
+pub trait FetchValue: Sized {
+    /// The native type used to define the column.
+    fn native_type() -> ODPINativeTypeNum;
+
+    /// Copy the value out of the given data, which is not NULL.
+    ///
+    /// # Safety
+    /// The data must have been fetched using the native type returned by `native_type()`.
+    unsafe fn from_data(data: &ODPIData) -> Self;
+}
+
+impl FetchValue for i64 {
+    fn native_type() -> ODPINativeTypeNum {
+        ODPINativeTypeNum::Int64
+    }
+
+    unsafe fn from_data(data: &ODPIData) -> i64 {
+        data.value.as_int_64
+    }
+}
+
+impl FetchValue for f64 {
+    fn native_type() -> ODPINativeTypeNum {
+        ODPINativeTypeNum::Double
+    }
+
+    unsafe fn from_data(data: &ODPIData) -> f64 {
+        data.value.as_double
+    }
+}
+
+impl FetchValue for DateTime<Utc> {
+    fn native_type() -> ODPINativeTypeNum {
+        ODPINativeTypeNum::Timestamp
+    }
+
+    unsafe fn from_data(data: &ODPIData) -> DateTime<Utc> {
+        data.value.as_timestamp.into()
+    }
+}
+
+impl FetchValue for DateTime<FixedOffset> {
+    fn native_type() -> ODPINativeTypeNum {
+        ODPINativeTypeNum::Timestamp
+    }
+
+    unsafe fn from_data(data: &ODPIData) -> DateTime<FixedOffset> {
+        data.value.as_timestamp.into()
+    }
+}
+
+impl FetchValue for Duration {
+    fn native_type() -> ODPINativeTypeNum {
+        ODPINativeTypeNum::IntervalDS
+    }
+
+    unsafe fn from_data(data: &ODPIData) -> Duration {
+        data.value.as_interval_ds.into()
+    }
+}
+
+impl FetchValue for YearsMonths {
+    fn native_type() -> ODPINativeTypeNum {
+        ODPINativeTypeNum::IntervalYM
+    }
+
+    unsafe fn from_data(data: &ODPIData) -> YearsMonths {
+        data.value.as_interval_ym.into()
+    }
+}
+
+impl FetchValue for Vec<u8> {
+    fn native_type() -> ODPINativeTypeNum {
+        ODPINativeTypeNum::Bytes
+    }
+
+    unsafe fn from_data(data: &ODPIData) -> Vec<u8> {
+        let bytes = data.value.as_bytes;
+        if bytes.ptr.is_null() {
+            Vec::new()
+        } else {
+            slice::from_raw_parts(bytes.ptr as *const u8, bytes.length as usize).to_vec()
+        }
+    }
+}
+
+impl FetchValue for String {
+    fn native_type() -> ODPINativeTypeNum {
+        ODPINativeTypeNum::Bytes
+    }
+
+    unsafe fn from_data(data: &ODPIData) -> String {
+        String::from_utf8_lossy(&Vec::<u8>::from_data(data)).into_owned()
+    }
+}
+
+/// A buffer holding the values of one column.
+pub trait ColumnBuffer {
+    /// The native type used to define the column.
+    fn native_type() -> ODPINativeTypeNum;
+
+    /// Remove all values from the buffer.
+    fn clear(&mut self);
+
+    /// Append the value in the given data to the buffer.
+    ///
+    /// * `pos` - the column position, used in error messages.
+    /// * `data` - the fetched data.
+    fn push_data(&mut self, pos: u32, data: &ODPIData) -> Result<()>;
+}
+
+impl<T: FetchValue> ColumnBuffer for Vec<T> {
+    fn native_type() -> ODPINativeTypeNum {
+        T::native_type()
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+
+    fn push_data(&mut self, pos: u32, data: &ODPIData) -> Result<()> {
+        if data.is_null == 1 {
+            let err = format!("fetch_into: NULL value in column {}, use an Option buffer", pos);
+            Err(ErrorKind::Statement(err).into())
+        } else {
+            self.push(unsafe { T::from_data(data) });
+            Ok(())
+        }
+    }
+}
+
+impl<T: FetchValue> ColumnBuffer for Vec<Option<T>> {
+    fn native_type() -> ODPINativeTypeNum {
+        T::native_type()
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+
+    fn push_data(&mut self, _pos: u32, data: &ODPIData) -> Result<()> {
+        if data.is_null == 1 {
+            self.push(None);
+        } else {
+            self.push(Some(unsafe { T::from_data(data) }));
+        }
+        Ok(())
+    }
+}
+
+/// A set of column buffers, one per query column, filled by `Statement::fetch_into()`.
+pub trait FetchBuffers {
+    /// The native types used to define the columns, in query order.
+    fn native_types() -> Vec<ODPINativeTypeNum>;
+
+    /// Remove all values from the buffers.
+    fn clear(&mut self);
+
+    /// Append the given row of the define variable data to the buffers.
+    ///
+    /// * `columns` - the define variable data, one slice per column.
+    /// * `row` - the index of the row in the define variable buffers.
+    fn push_row(&mut self, columns: &[&[ODPIData]], row: usize) -> Result<()>;
+}
+
+macro_rules! fetch_buffers {
+    ($($name:ident: $idx:tt),+) => {
+        impl<$($name: ColumnBuffer),+> FetchBuffers for ($($name,)+) {
+            fn native_types() -> Vec<ODPINativeTypeNum> {
+                vec![$($name::native_type()),+]
+            }
+
+            fn clear(&mut self) {
+                $(self.$idx.clear();)+
+            }
+
+            fn push_row(&mut self, columns: &[&[ODPIData]], row: usize) -> Result<()> {
+                $(self.$idx.push_data($idx + 1, &columns[$idx][row])?;)+
+                Ok(())
+            }
+        }
+    };
+}
+
+fetch_buffers!(A: 0);
+fetch_buffers!(A: 0, B: 1);
+fetch_buffers!(A: 0, B: 1, C: 2);
+fetch_buffers!(A: 0, B: 1, C: 2, D: 3);
+fetch_buffers!(A: 0, B: 1, C: 2, D: 3, E: 4);
+fetch_buffers!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+fetch_buffers!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+fetch_buffers!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+
+/// A value of a column of a single row, fetched by `Statement::single_row()`. `T` requires the
+/// value to be NOT NULL, while `Option<T>` accepts NULL.
+pub trait RowValue: Sized {
+    /// The buffer the value is fetched into.
+    type Buffer: ColumnBuffer + Default;
+
+    /// Take the first value out of the buffer, or None if it is empty.
+    fn take(buffer: Self::Buffer) -> Option<Self>;
+}
+
+impl<T: FetchValue> RowValue for T {
+    type Buffer = Vec<T>;
+
+    fn take(buffer: Vec<T>) -> Option<T> {
+        buffer.into_iter().next()
+    }
+}
+
+impl<T: FetchValue> RowValue for Option<T> {
+    type Buffer = Vec<Option<T>>;
+
+    fn take(buffer: Vec<Option<T>>) -> Option<Option<T>> {
+        buffer.into_iter().next()
+    }
+}
+
+/// A row of values, one per query column, fetched by `Statement::single_row()`.
+pub trait FetchRow: Sized {
+    /// The buffers the row is fetched into.
+    type Buffers: FetchBuffers + Default;
+
+    /// Take the first row out of the buffers, or None if they are empty.
+    fn take(buffers: Self::Buffers) -> Option<Self>;
+}
+
+macro_rules! fetch_row {
+    ($($name:ident: $idx:tt),+) => {
+        impl<$($name: RowValue),+> FetchRow for ($($name,)+) {
+            type Buffers = ($($name::Buffer,)+);
+
+            fn take(buffers: Self::Buffers) -> Option<Self> {
+                Some(($($name::take(buffers.$idx)?,)+))
+            }
+        }
+    };
+}
+
+fetch_row!(A: 0);
+fetch_row!(A: 0, B: 1);
+fetch_row!(A: 0, B: 1, C: 2);
+fetch_row!(A: 0, B: 1, C: 2, D: 3);
+fetch_row!(A: 0, B: 1, C: 2, D: 3, E: 4);
+fetch_row!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+fetch_row!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+fetch_row!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
@@ -12,30 +12,159 @@
 //! `DPI_ORACLE_TYPE_STMT` is created. Statement handles can be closed by calling the function
 //! `close()` or by releasing the last reference to the statement by calling the function
 //! `release()`.
-use common::error;
-use data::Data;
+use common::{encoding, error};
+use connection::{self, Connection};
+use context::ContextRef;
+use context::feature::Feature;
+use data::{self, Data};
 use error::{ErrorKind, Result};
+use lob::Lob;
+use logger::Logger;
+use object::Object;
 use odpi::{enums, externs};
-use odpi::flags::ODPIExecMode;
+use odpi::flags::{self, ODPIExecMode};
+use odpi::constants::DPI_SUCCESS;
 use odpi::opaque::ODPIStmt;
-use odpi::structs::{ODPIData, ODPIQueryInfo, ODPIStmtInfo};
-use query;
-use std::{ptr, slice};
-use util::ODPIStr;
-use variable::Var;
+use odpi::structs::{ODPIData, ODPIDataValueUnion, ODPIErrorInfo, ODPIQueryInfo, ODPIStmtInfo};
+use query::{self, CoercionWarning, ColumnInfo};
+use rowid::Rowid;
+use std::{cmp, ptr, slice};
+use std::cell::{Cell, RefCell};
+use std::ops::Deref;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use timing::Timings;
+use util::{self, ODPIStr};
+use variable::{self, DataArray, Var};
+
+pub mod binder;
+pub mod bindinfo;
+pub mod buffers;
+pub mod row;
+pub mod tracker;
+mod watchdog;
+
+use self::binder::Binder;
+use self::bindinfo::Info as BindInfo;
+use self::buffers::{FetchBuffers, FetchRow, RowValue};
+use self::row::Row;
+use self::tracker::Tracker;
+use self::watchdog::Watchdog;
+
+/// The largest buffer, in bytes, `define_size()` sizes a string or raw column to.
+const MAX_DEFINE_SIZE: u32 = 32_767;
 
 /// This structure represents statements of all types (queries, DML, DLL and PL/SQL) and is
 /// available by handle to a calling application or driver.
 pub struct Statement {
     /// The ODPI-C statement
     inner: *mut ODPIStmt,
+    /// The context the statement's connection was created in, used to retrieve error information.
+    context: ContextRef,
+    /// Whether LOB columns are fetched inline as byte strings rather than as LOB locators.
+    fetch_lobs_inline: Cell<bool>,
+    /// The buffer size, in bytes, preallocated per row for LOB columns fetched inline.
+    lob_prefetch_size: Cell<u32>,
+    /// The factor the described size of string and raw columns is multiplied by when they are
+    /// defined, if set.
+    define_size_margin: Cell<Option<f64>>,
+    /// The open statement tracker of the connection, if tracking is enabled.
+    tracker: Option<Rc<Tracker>>,
+    /// The call timings of the connection, if timing is enabled.
+    timings: Option<Rc<Timings>>,
+    /// The time of the last activity on the connection, updated by calls on the statement.
+    activity: Option<Rc<Cell<Instant>>>,
+    /// The encoding information of the connection, if the statement was prepared on one.
+    encoding: Option<Rc<encoding::Info>>,
+    /// The SQL the statement was prepared with, if known.
+    sql: Option<String>,
+    /// The tag the statement is returned to the statement cache with by `close_cached()`.
+    cache_tag: Option<String>,
+    /// The number of columns of the last executed query, or zero if it was not a query.
+    cols_queried: Cell<u32>,
+    /// The column metadata of the last executed query, taken when it is first asked for.
+    columns: RefCell<Option<Rc<Vec<ColumnInfo>>>>,
+    /// The columns fetched with a native type that may lose data, since the last call to
+    /// `fetch_into()` or `for_each_chunk()`.
+    coercion_warnings: RefCell<Vec<CoercionWarning>>,
+    /// Optional stderr logger, given the coercion warnings.
+    stderr: Option<Logger>,
+    /// The data arrays of the variables bound to the statement, which are marked as stale when a
+    /// DML returning statement is executed.
+    bound: RefCell<Vec<Rc<DataArray>>>,
 }
 
 impl Statement {
     /// Create a new statement from an `ODPIStmt` pointer
     #[doc(hidden)]
     pub fn new(inner: *mut ODPIStmt) -> Statement {
-        Statement { inner: inner }
+        Statement::with_context(inner, ContextRef::none())
+    }
+
+    /// Create a new statement from an `ODPIStmt` pointer and the context of its connection.
+    #[doc(hidden)]
+    pub fn with_context(inner: *mut ODPIStmt, context: ContextRef) -> Statement {
+        Statement {
+            inner: inner,
+            context: context,
+            fetch_lobs_inline: Cell::new(false),
+            lob_prefetch_size: Cell::new(0),
+            define_size_margin: Cell::new(None),
+            tracker: None,
+            timings: None,
+            activity: None,
+            encoding: None,
+            sql: None,
+            cache_tag: None,
+            cols_queried: Cell::new(0),
+            columns: RefCell::new(None),
+            coercion_warnings: RefCell::new(Vec::new()),
+            stderr: None,
+            bound: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Record the statement as open in the given tracker until it is released or closed.
+    #[doc(hidden)]
+    pub fn set_tracker(&mut self, tracker: Rc<Tracker>, sql: Option<&str>) {
+        tracker.opened(self.inner, sql);
+        self.tracker = Some(tracker);
+    }
+
+    /// Set the logger the coercion warnings are written to.
+    #[doc(hidden)]
+    pub fn set_stderr(&mut self, stderr: Option<Logger>) {
+        self.stderr = stderr;
+    }
+
+    /// Record the calls made on the statement as activity on its connection.
+    #[doc(hidden)]
+    pub fn set_activity(&mut self, activity: Rc<Cell<Instant>>) {
+        self.activity = Some(activity);
+    }
+
+    /// Set the encoding information of the connection the statement was prepared on.
+    #[doc(hidden)]
+    pub fn set_encoding(&mut self, encoding: Rc<encoding::Info>) {
+        self.encoding = Some(encoding);
+    }
+
+    /// Set the tag the statement is returned to the statement cache with by `close_cached()`.
+    #[doc(hidden)]
+    pub fn set_cache_tag(&mut self, tag: String) {
+        self.cache_tag = Some(tag);
+    }
+
+    /// Remember the SQL the statement was prepared with, so bind variable positions can be found.
+    #[doc(hidden)]
+    pub fn set_sql(&mut self, sql: &str) {
+        self.sql = Some(sql.to_string());
+    }
+
+    /// Record the time taken by the calls made on the statement in the given timings.
+    #[doc(hidden)]
+    pub fn set_timings(&mut self, timings: Rc<Timings>) {
+        self.timings = Some(timings);
     }
 
     /// Get the `inner` value.
@@ -49,7 +178,13 @@ impl Statement {
     /// was created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiStmt_addRef(self.inner),
-                 Ok(()),
+                 {
+                     if let Some(ref tracker) = self.tracker {
+                         tracker.add_ref(self.inner);
+                     }
+                     track_ref!(added, "Statement", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Statement("dpiStmt_addRef".to_string()))
     }
 
@@ -65,7 +200,10 @@ impl Statement {
 
         /// TODO: Test this when Var is complete.
         try_dpi!(externs::dpiStmt_bindByName(self.inner, name_s.ptr(), name_s.len(), var.inner()),
-                 Ok(()),
+                 {
+                     self.add_bound(var);
+                     Ok(())
+                 },
                  ErrorKind::Statement("dpiStmt_bindByName".to_string()))
     }
 
@@ -79,10 +217,163 @@ impl Statement {
     /// * `var` - a variable which is to be bound.
     pub fn bind_by_pos(&self, pos: u32, var: &Var) -> Result<()> {
         try_dpi!(externs::dpiStmt_bindByPos(self.inner, pos, var.inner()),
-                 Ok(()),
+                 {
+                     self.add_bound(var);
+                     Ok(())
+                 },
                  ErrorKind::Statement("dpiStmt_bindByPos".to_string()))
     }
 
+    /// Remember the data array of a variable bound to the statement, so it can be marked as stale
+    /// by `invalidate_returning()`.
+    fn add_bound(&self, var: &Var) {
+        let array = variable::array(var);
+        let mut bound = self.bound.borrow_mut();
+
+        if !bound.iter().any(|known| Rc::ptr_eq(known, &array)) {
+            bound.push(array);
+        }
+    }
+
+    /// Mark the data arrays of the bound variables as stale if the statement is a DML returning
+    /// statement, as executing it can move and resize them. If the statement information cannot be
+    /// read, the arrays are marked as stale anyway, which only costs a re-read.
+    fn invalidate_returning(&self) {
+        let bound = self.bound.borrow();
+
+        if !bound.is_empty() && self.get_info().map(|info| info.is_returning()).unwrap_or(true) {
+            for array in bound.iter() {
+                array.invalidate();
+            }
+        }
+    }
+
+    /// Binds a handle (LOB, object or rowid) to a named placeholder through an implicitly created
+    /// variable.
+    fn bind_handle_by_name(&self,
+                           name: &str,
+                           native_type: enums::ODPINativeTypeNum,
+                           value: ODPIDataValueUnion)
+                           -> Result<()> {
+        let mut odpi_data = ODPIData {
+            is_null: 0,
+            value: value,
+        };
+        let data = unsafe { data::from_ptr(&mut odpi_data) };
+        self.bind_value_by_name(name, native_type, &data)
+    }
+
+    /// Binds a handle (LOB, object or rowid) to a placeholder by position through an implicitly
+    /// created variable.
+    fn bind_handle_by_pos(&self,
+                          pos: u32,
+                          native_type: enums::ODPINativeTypeNum,
+                          value: ODPIDataValueUnion)
+                          -> Result<()> {
+        let mut odpi_data = ODPIData {
+            is_null: 0,
+            value: value,
+        };
+        let data = unsafe { data::from_ptr(&mut odpi_data) };
+        self.bind_value_by_pos(pos, native_type, &data)
+    }
+
+    /// Returns information about the bind variables of the prepared statement, in order of
+    /// position. In SQL statements a name used more than once is returned once per occurrence,
+    /// with the later occurrences marked as duplicates. If the SQL is not known, e.g. because the
+    /// statement was found in the statement cache by tag alone, each unique name is returned once.
+    pub fn bind_info(&self) -> Result<Vec<BindInfo>> {
+        let count = self.get_bind_count()?;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let names = self.get_bind_names(count)?;
+
+        if !self.get_info()?.is_plsql() {
+            let scanned = self.sql.as_ref().and_then(|sql| bindinfo::scan(sql, &names));
+            if let Some(infos) = scanned {
+                if infos.len() == count as usize {
+                    return Ok(infos);
+                }
+            }
+        }
+
+        Ok(names
+               .iter()
+               .enumerate()
+               .map(|(idx, name)| BindInfo::new(name, idx as u32 + 1, false))
+               .collect())
+    }
+
+    /// Binds a LOB to a named placeholder in the statement without the need to create a
+    /// variable directly. One is created implicitly and released when the statement is released or
+    /// a new value is bound to the same name.
+    ///
+    /// * `name` - a string in the encoding used for CHAR data giving the name of the placeholder
+    /// which is to be bound.
+    /// * `lob` - the LOB which is to be bound.
+    pub fn bind_lob_by_name(&self, name: &str, lob: &Lob) -> Result<()> {
+        let value = ODPIDataValueUnion { as_lob: lob.inner() };
+        self.bind_handle_by_name(name, enums::ODPINativeTypeNum::Lob, value)
+    }
+
+    /// Binds a LOB to a placeholder in the statement by position without the need to
+    /// create a variable directly. One is created implicitly and released when the statement is
+    /// released or a new value is bound to the same position.
+    ///
+    /// * `pos` - the position which is to be bound. The first position is 1.
+    /// * `lob` - the LOB which is to be bound.
+    pub fn bind_lob_by_pos(&self, pos: u32, lob: &Lob) -> Result<()> {
+        let value = ODPIDataValueUnion { as_lob: lob.inner() };
+        self.bind_handle_by_pos(pos, enums::ODPINativeTypeNum::Lob, value)
+    }
+
+    /// Binds an object to a named placeholder in the statement without the need to create a
+    /// variable directly. One is created implicitly and released when the statement is released or
+    /// a new value is bound to the same name.
+    ///
+    /// * `name` - a string in the encoding used for CHAR data giving the name of the placeholder
+    /// which is to be bound.
+    /// * `object` - the object which is to be bound.
+    pub fn bind_object_by_name(&self, name: &str, object: &Object) -> Result<()> {
+        let value = ODPIDataValueUnion { as_object: object.inner() };
+        self.bind_handle_by_name(name, enums::ODPINativeTypeNum::Object, value)
+    }
+
+    /// Binds an object to a placeholder in the statement by position without the need to
+    /// create a variable directly. One is created implicitly and released when the statement is
+    /// released or a new value is bound to the same position.
+    ///
+    /// * `pos` - the position which is to be bound. The first position is 1.
+    /// * `object` - the object which is to be bound.
+    pub fn bind_object_by_pos(&self, pos: u32, object: &Object) -> Result<()> {
+        let value = ODPIDataValueUnion { as_object: object.inner() };
+        self.bind_handle_by_pos(pos, enums::ODPINativeTypeNum::Object, value)
+    }
+
+    /// Binds a rowid to a named placeholder in the statement without the need to create a
+    /// variable directly. One is created implicitly and released when the statement is released or
+    /// a new value is bound to the same name.
+    ///
+    /// * `name` - a string in the encoding used for CHAR data giving the name of the placeholder
+    /// which is to be bound.
+    /// * `rowid` - the rowid which is to be bound.
+    pub fn bind_rowid_by_name(&self, name: &str, rowid: &Rowid) -> Result<()> {
+        let value = ODPIDataValueUnion { as_rowid: rowid.inner() };
+        self.bind_handle_by_name(name, enums::ODPINativeTypeNum::Rowid, value)
+    }
+
+    /// Binds a rowid to a placeholder in the statement by position without the need to
+    /// create a variable directly. One is created implicitly and released when the statement is
+    /// released or a new value is bound to the same position.
+    ///
+    /// * `pos` - the position which is to be bound. The first position is 1.
+    /// * `rowid` - the rowid which is to be bound.
+    pub fn bind_rowid_by_pos(&self, pos: u32, rowid: &Rowid) -> Result<()> {
+        let value = ODPIDataValueUnion { as_rowid: rowid.inner() };
+        self.bind_handle_by_pos(pos, enums::ODPINativeTypeNum::Rowid, value)
+    }
+
     /// Binds a value to a named placeholder in the statement without the need to create a variable
     /// directly. One is created implicitly and released when the statement is released or a new
     /// value is bound to the same name.
@@ -132,6 +423,12 @@ impl Statement {
                  ErrorKind::Statement("dpiStmt_bindValueByPos".to_string()))
     }
 
+    /// Returns a `Binder` that collects values for the named bind variables of the statement and
+    /// checks them against the bind variables before binding. See the `binder` module.
+    pub fn binder(&self) -> Binder {
+        Binder::new(self)
+    }
+
     /// Closes the statement and makes it unusable for further work immediately, rather than when
     /// the reference count reaches zero.
     ///
@@ -142,28 +439,133 @@ impl Statement {
     pub fn close(&self, tag: Option<&str>) -> Result<()> {
         let tag_s = ODPIStr::from(tag);
         try_dpi!(externs::dpiStmt_close(self.inner, tag_s.ptr(), tag_s.len()),
-                 Ok(()),
+                 {
+                     if let Some(ref tracker) = self.tracker {
+                         tracker.closed(self.inner);
+                     }
+                     Ok(())
+                 },
                  ErrorKind::Statement("dpiStmt_close".to_string()))
     }
 
-    // /// Defines the variable that will be used to fetch rows from the statement. A reference to
-    //the
-    // /// variable will be retained until the next define is performed on the same position or the
-    // /// statement is closed.
-    // pub fn define(&self, pos: u32, var: &mut Var) -> Result<()> {
-    //     Ok(())
-    // }
+    /// Closes the statement, returning it to the statement cache under the tag it was prepared with
+    /// by `Connection::prepare_cached()`, so the next `prepare_cached()` of the same SQL and key
+    /// finds it. Statements not prepared by `prepare_cached()` are closed without a tag.
+    pub fn close_cached(&self) -> Result<()> {
+        self.close(self.cache_tag.as_ref().map(|tag| &tag[..]))
+    }
+
+    /// Returns the tag the statement was prepared with by `Connection::prepare_cached()`, or None
+    /// if it was prepared otherwise.
+    pub fn get_cache_tag(&self) -> Option<&str> {
+        self.cache_tag.as_ref().map(|tag| &tag[..])
+    }
+
+    /// Returns the column metadata of the query that was last executed. The metadata is read from
+    /// ODPI-C the first time it is asked for after each execution, and remains available after the
+    /// rows are exhausted. Returns an error if the statement has not been executed as a query.
+    pub fn columns(&self) -> Result<Rc<Vec<ColumnInfo>>> {
+        if let Some(ref columns) = *self.columns.borrow() {
+            return Ok(Rc::clone(columns));
+        }
+
+        let cols_queried = self.cols_queried.get();
+        if cols_queried == 0 {
+            let err = "columns: statement is not an executed query!".to_string();
+            return Err(ErrorKind::Statement(err).into());
+        }
+
+        let mut columns = Vec::with_capacity(cols_queried as usize);
+        for pos in 1..(cols_queried + 1) {
+            columns.push(ColumnInfo::new(pos, &self.get_query_info(pos)?));
+        }
+        let columns = Rc::new(columns);
+        *self.columns.borrow_mut() = Some(Rc::clone(&columns));
+        Ok(columns)
+    }
+
+    /// Defines the variable that will be used to fetch rows from the statement. A reference to the
+    /// variable will be retained until the next define is performed on the same position or the
+    /// statement is closed.
+    ///
+    /// * `pos` - the position which is to be defined. The first position is 1.
+    /// * `var` - the variable to be used for fetching rows from the statement at the given
+    /// position.
+    pub fn define(&self, pos: u32, var: &Var) -> Result<()> {
+        try_dpi!(externs::dpiStmt_define(self.inner, pos, var.inner()),
+                 Ok(()),
+                 ErrorKind::Statement("dpiStmt_define".to_string()))
+    }
+
+    /// Defines a variable that fetches the LOB column at the given position inline, as a byte
+    /// string, instead of as a LOB locator. CLOB and NCLOB columns are fetched as LONG data in the
+    /// encoding used for CHAR data and BLOB columns are fetched as LONG RAW data. This avoids the
+    /// round trips needed to read through a locator, which dominate when the LOBs are small. The
+    /// statement must have been executed so that the column metadata is available.
+    ///
+    /// * `conn` - the connection the statement was prepared on. It is used to create the variable.
+    /// * `pos` - the position which is to be defined. The first position is 1.
+    /// * `max_array_size` - the number of rows the variable can hold. This should be at least the
+    /// fetch array size.
+    ///
+    /// The buffer for each row is preallocated to the LOB prefetch size set with
+    /// `set_lob_prefetch_size()`. LOBs larger than this are still fetched in full, but require the
+    /// buffer to be grown.
+    pub fn define_inline(&self, conn: &Connection, pos: u32, max_array_size: u32) -> Result<Var> {
+        let qi = self.get_query_info(pos)?;
+
+        if let Some(oracle_type_num) = inline_lob_type(qi.oracle_type_num()) {
+            let var = conn.new_var(oracle_type_num,
+                                   enums::ODPINativeTypeNum::Bytes,
+                                   max_array_size,
+                                   self.lob_prefetch_size.get(),
+                                   true,
+                                   false,
+                                   None)?;
+            match self.define(pos, &var) {
+                Ok(()) => Ok(var),
+                Err(e) => {
+                    var.release()?;
+                    Err(e)
+                }
+            }
+        } else {
+            let err = format!("define_inline: column {} is not a LOB column!", pos);
+            Err(ErrorKind::Statement(err).into())
+        }
+    }
 
     /// Executes the statement using the bound values. For queries this makes available metadata
     /// which can be acquired using the function dpiStmt_getQueryInfo(). For non-queries, out and
     /// in-out variables are populated with their values.
     ///
     /// * `mode` - one or more of the values from the enumeration `ODPIExecMode`, OR'ed together.
-    pub fn execute(&self, mode: ODPIExecMode) -> Result<u32> {
+    ///
+    /// If the statement was prepared on a connection, a failure is returned as
+    /// `ErrorKind::OciError`, which carries the offset of a parse error into the SQL. See
+    /// `Error::render_snippet()`.
+    ///
+    /// For queries the column metadata is available from `columns()` until the statement is
+    /// executed again. It is read when first asked for, so a failure to read it does not fail the
+    /// execution.
+    pub fn execute<M: Into<ODPIExecMode>>(&self, mode: M) -> Result<u32> {
+        let mode = mode.into();
         let mut cols_queried = 0;
-        try_dpi!(externs::dpiStmt_execute(self.inner, mode, &mut cols_queried),
-                 Ok(cols_queried),
-                 ErrorKind::Statement("dpiStmt_execute".to_string()))
+        self.context.check()?;
+        *self.columns.borrow_mut() = None;
+        self.cols_queried.set(0);
+
+        let res = self.timed("dpiStmt_execute", || unsafe {
+            externs::dpiStmt_execute(self.inner, mode, &mut cols_queried)
+        });
+
+        if res == DPI_SUCCESS {
+            self.invalidate_returning();
+            self.cols_queried.set(cols_queried);
+            Ok(cols_queried)
+        } else {
+            Err(self.execute_error("dpiStmt_execute").into())
+        }
     }
 
     /// Executes the statement the specified number of times using the bound values. Each bound
@@ -172,31 +574,88 @@ impl Statement {
     /// * `mode` - one or more of the values from the enumeration `ODPIExecMode`, OR'ed together.
     /// * `num_iters` - the number of times the statement is executed. Each iteration corresponds to
     /// one of the elements of the array that was bound earlier.
-    pub fn execute_many(&self, mode: ODPIExecMode, num_iters: u32) -> Result<()> {
-        try_dpi!(externs::dpiStmt_executeMany(self.inner, mode, num_iters),
-                 Ok(()),
-                 ErrorKind::Statement("dpiStmt_executeMany".to_string()))
+    ///
+    /// `ErrorKind::Unsupported` is returned if the mode asks for batch errors or array DML row
+    /// counts and the Oracle Client is older than 12.1.
+    pub fn execute_many<M: Into<ODPIExecMode>>(&self, mode: M, num_iters: u32) -> Result<()> {
+        let mode = mode.into();
+        self.context.check()?;
+        if mode.contains(flags::DPI_MODE_EXEC_BATCH_ERRORS) {
+            self.context.require(Feature::BatchErrors)?;
+        }
+        if mode.contains(flags::DPI_MODE_EXEC_ARRAY_DML_ROWCOUNTS) {
+            self.context.require(Feature::ArrayDmlRowCounts)?;
+        }
+        let res = self.timed("dpiStmt_executeMany", || unsafe {
+            externs::dpiStmt_executeMany(self.inner, mode, num_iters)
+        });
+
+        if res == DPI_SUCCESS {
+            self.invalidate_returning();
+            Ok(())
+        } else {
+            Err(self.execute_error("dpiStmt_executeMany").into())
+        }
+    }
+
+    /// Executes the statement, breaking its execution if it does not complete within the timeout,
+    /// in which case `ErrorKind::Timeout` is returned. ODPI-C 2.0 has no call timeout, so a
+    /// watchdog thread, shared by all calls, calls `Connection::break_execution()` when the timeout
+    /// expires; the connection must be created with `DPI_MODE_CREATE_THREADED` for the break to be
+    /// made from that thread. The watch is cancelled before this returns. A call that completes
+    /// just as the timeout expires may still succeed, in which case the break is cleared with a
+    /// ping, so it does not interrupt the next call on the connection.
+    ///
+    /// * `conn` - the connection the statement was prepared on.
+    /// * `mode` - one or more of the values from the enumeration `ODPIExecMode`, OR'ed together.
+    /// * `timeout` - the time the execution is given to complete.
+    pub fn execute_with_timeout<M>(&self,
+                                   conn: &Connection,
+                                   mode: M,
+                                   timeout: Duration)
+                                   -> Result<u32>
+        where M: Into<ODPIExecMode>
+    {
+        self.context.check()?;
+        let watchdog = Watchdog::start(connection::inner(conn), timeout);
+        let res = self.execute(mode);
+        let broken = watchdog.stop();
+
+        match res {
+            Err(_) if broken => Err(ErrorKind::Timeout(util::duration_millis(timeout)).into()),
+            res => res,
+        }
     }
 
-    /// Fetches a single row from the statement. If the statement does not refer to a query an error
-    /// is returned. All columns that have not been defined prior to this call are implicitly
-    /// defined using the metadata made available when the statement was executed.
+    /// Fetches a single row from the statement. If the statement does not refer to a query
+    /// `ErrorKind::NotAQuery` is returned. All columns that have not been defined prior to this
+    /// call are implicitly defined using the metadata made available when the statement was
+    /// executed.
     ///
     /// Returns a tuple of (found, row_index)
     pub fn fetch(&self) -> Result<(bool, u32)> {
         let mut found = 0;
         let mut buffer_row_index = 0;
 
-        try_dpi!(externs::dpiStmt_fetch(self.inner, &mut found, &mut buffer_row_index),
-                 Ok((found == 1, buffer_row_index)),
-                 ErrorKind::Statement("dpiStmt_fetch".to_string()))
+        let res = self.timed("dpiStmt_fetch", || {
+            try_dpi!(externs::dpiStmt_fetch(self.inner, &mut found, &mut buffer_row_index),
+                     Ok((found == 1, buffer_row_index)),
+                     ErrorKind::Statement("dpiStmt_fetch".to_string()))
+        });
+
+        // A fetch from a statement that is not a query fails; report it as such.
+        res.or_else(|err| {
+                        self.check_query("fetch")?;
+                        Err(err)
+                    })
     }
 
     /// Returns the number of rows that are available in the buffers defined for the query. If no
     /// rows are currently available in the buffers, an internal fetch takes place in order to
-    /// populate them, if rows are available. If the statement does not refer to a query an error
-    /// is returned. All columns that have not been defined prior to this call are implicitly
-    /// defined using the metadata made available when the statement was executed.
+    /// populate them, if rows are available. If the statement does not refer to a query
+    /// `ErrorKind::NotAQuery` is returned. All columns that have not been defined prior to this
+    /// call are implicitly defined using the metadata made available when the statement was
+    /// executed.
     ///
     /// * `max_rows` - the maximum number of rows to fetch. If the number of rows available exceeds
     /// this value only this number will be fetched.
@@ -207,19 +666,161 @@ impl Statement {
         let mut num_rows_fetched = 0;
         let mut more_rows = 0;
 
-        try_dpi!(externs::dpiStmt_fetchRows(self.inner,
-                                            max_rows,
-                                            &mut buffer_row_index,
-                                            &mut num_rows_fetched,
-                                            &mut more_rows),
-                 Ok((buffer_row_index, num_rows_fetched, more_rows == 1)),
-                 ErrorKind::Statement("dpiStmt_fetchRows".to_string()))
+        let res = self.timed("dpiStmt_fetchRows", || {
+            try_dpi!(externs::dpiStmt_fetchRows(self.inner,
+                                                max_rows,
+                                                &mut buffer_row_index,
+                                                &mut num_rows_fetched,
+                                                &mut more_rows),
+                     Ok((buffer_row_index, num_rows_fetched, more_rows == 1)),
+                     ErrorKind::Statement("dpiStmt_fetchRows".to_string()))
+        });
+
+        // A fetch from a statement that is not a query fails; report it as such.
+        res.or_else(|err| {
+                        self.check_query("fetch_rows")?;
+                        Err(err)
+                    })
+    }
+
+    /// Fetches up to `max_rows` rows of an executed query, spending no longer than the deadline
+    /// allows in the internal fetches needed to get them. Rows are fetched from the server in round
+    /// trips of at most the fetch array size, and each round trip's rows are handed to the given
+    /// closure as a `RowBatch`. ODPI-C 2.0 has no call timeout, so each round trip is bounded by a
+    /// watchdog with the time remaining, as with `execute_with_timeout()`; the connection must be
+    /// created with `DPI_MODE_CREATE_THREADED` for a round trip to be broken. This keeps an
+    /// interactive application responsive while paging through a slow query.
+    ///
+    /// Returns a tuple of (num_rows_fetched, deadline_reached). When the deadline was reached the
+    /// rows handed to the closure are a partial batch; a round trip broken by the watchdog
+    /// usually leaves the query unable to fetch further rows. Otherwise fewer than `max_rows` rows
+    /// means the rows are exhausted.
+    ///
+    /// * `conn` - the connection the statement was prepared on. It is used to create the define
+    /// variables.
+    /// * `max_rows` - the maximum number of rows to fetch.
+    /// * `deadline` - the time by which the fetch must complete.
+    /// * `f` - the closure that consumes the rows of each round trip.
+    pub fn fetch_rows_deadline<F>(&self,
+                                  conn: &Connection,
+                                  max_rows: u32,
+                                  deadline: Instant,
+                                  mut f: F)
+                                  -> Result<(u32, bool)>
+        where F: FnMut(&RowBatch)
+    {
+        self.check_query("fetch_rows_deadline")?;
+        let num_cols = self.get_num_query_columns()?;
+
+        if num_cols == 0 {
+            let err = "fetch_rows_deadline: statement is not an executed query!".to_string();
+            return Err(ErrorKind::Statement(err).into());
+        }
+
+        let array_size = self.get_fetch_array_size()?;
+        let vars = self.define_columns(conn, num_cols, array_size)?;
+        let res = self.fetch_until(conn, &vars, array_size, max_rows, deadline, &mut f);
+        self.set_fetch_array_size(array_size)?;
+        vars.release()?;
+        res
+    }
+
+    /// Fetches the rows of an executed query in chunks of at most `max_rows` rows, handing each
+    /// chunk to the given closure as a `RowBatch`. Variables are defined for every column using the
+    /// metadata made available when the statement was executed, and the fetch array size is set to
+    /// `max_rows`, so each chunk corresponds to a single array fetch; it is restored afterwards,
+    /// whether or not the fetch succeeds. The closure returns
+    /// `ControlFlow::Break` to stop fetching early; otherwise fetching continues until the rows are
+    /// exhausted. If `set_fetch_lobs_inline(true)` was called, LOB columns are defined as with
+    /// `define_inline()`; otherwise they are defined as LOB locators.
+    ///
+    /// * `conn` - the connection the statement was prepared on. It is used to create the define
+    /// variables.
+    /// * `max_rows` - the maximum number of rows handed to the closure at one time.
+    /// * `f` - the closure that consumes each chunk of rows.
+    pub fn for_each_chunk<F>(&self, conn: &Connection, max_rows: u32, mut f: F) -> Result<()>
+        where F: FnMut(&RowBatch) -> ControlFlow
+    {
+        self.check_query("for_each_chunk")?;
+        let num_cols = self.get_num_query_columns()?;
+
+        if num_cols == 0 {
+            let err = "for_each_chunk: statement is not an executed query!".to_string();
+            return Err(ErrorKind::Statement(err).into());
+        } else if max_rows == 0 {
+            let err = "for_each_chunk: max_rows must be greater than zero!".to_string();
+            return Err(ErrorKind::Statement(err).into());
+        }
+
+        let array_size = self.get_fetch_array_size()?;
+        self.set_fetch_array_size(max_rows)?;
+        let res = self.define_columns(conn, num_cols, max_rows)
+            .and_then(|vars| {
+                          let res = self.fetch_chunks(&vars, max_rows, &mut f);
+                          let released = vars.release();
+                          res.and(released)
+                      });
+        let restored = self.set_fetch_array_size(array_size);
+        res.and(restored)
+    }
+
+    /// Fetches the rows of an executed query in chunks of at most `max_rows` rows, copying each
+    /// chunk straight from the define variables into the given typed buffers, one per column. The
+    /// buffers are cleared before each chunk is copied in, and then handed to the closure, which
+    /// returns `ControlFlow::Break` to stop fetching early. This avoids creating a `Data` value
+    /// per column per row, for extracting large result sets. Columns are defined with the native
+    /// type of their buffer, so a NUMBER column can be fetched into `i64`, `f64` or `String`
+    /// buffers. LOB columns fetched into `String` or `Vec<u8>` buffers are defined as with
+    /// `define_inline()`. Columns whose values may lose data in their buffer's native type, such as
+    /// a NUMBER with a scale fetched into `i64`, are reported by `get_coercion_warnings()`. The
+    /// fetch array size is set to `max_rows` while fetching, and restored afterwards.
+    ///
+    /// * `conn` - the connection the statement was prepared on. It is used to create the define
+    /// variables.
+    /// * `max_rows` - the maximum number of rows copied into the buffers at one time.
+    /// * `buffers` - a tuple with one `Vec` per query column. See the `buffers` module.
+    /// * `f` - the closure that consumes each chunk of rows.
+    pub fn fetch_into<B, F>(&self,
+                            conn: &Connection,
+                            max_rows: u32,
+                            buffers: &mut B,
+                            mut f: F)
+                            -> Result<()>
+        where B: FetchBuffers,
+              F: FnMut(&mut B) -> ControlFlow
+    {
+        self.check_query("fetch_into")?;
+        let native_types = B::native_types();
+        let num_cols = self.get_num_query_columns()?;
+
+        if num_cols == 0 {
+            let err = "fetch_into: statement is not an executed query!".to_string();
+            return Err(ErrorKind::Statement(err).into());
+        } else if num_cols as usize != native_types.len() {
+            let err = format!("fetch_into: query has {} columns but {} buffers were given!",
+                              num_cols,
+                              native_types.len());
+            return Err(ErrorKind::Statement(err).into());
+        } else if max_rows == 0 {
+            let err = "fetch_into: max_rows must be greater than zero!".to_string();
+            return Err(ErrorKind::Statement(err).into());
+        }
+
+        let array_size = self.get_fetch_array_size()?;
+        self.set_fetch_array_size(max_rows)?;
+        let mut vars = DefineVars::with_capacity(native_types.len());
+        let res = self.define_buffers(conn, max_rows, native_types, &mut vars)
+            .and_then(|_| self.copy_chunks(&vars, max_rows, buffers, &mut f));
+        let released = vars.release();
+        let restored = self.set_fetch_array_size(array_size);
+        res.and(released).and(restored)
     }
 
     /// Returns the number of batch errors that took place during the last execution with batch mode
     /// enabled. Batch errors are only available when both the client and the server are at 12.1.
     pub fn get_batch_error_count(&self) -> Result<u32> {
         let mut count = 0;
+        self.context.require(Feature::BatchErrors)?;
 
         try_dpi!(externs::dpiStmt_getBatchErrorCount(self.inner, &mut count),
                  Ok(count),
@@ -232,15 +833,11 @@ impl Statement {
     /// * `num_errors` - the size of the errors array in number of elements. The number of batch
     /// errors that are available can be determined using `get_batch_error_count()`.
     pub fn get_batch_errors(&self, num_errors: u32) -> Result<Vec<error::Info>> {
-        let err_ptr = ptr::null_mut();
+        let mut errors: Vec<ODPIErrorInfo> = vec![Default::default(); num_errors as usize];
+        self.context.require(Feature::BatchErrors)?;
 
-        try_dpi!(externs::dpiStmt_getBatchErrors(self.inner, num_errors, err_ptr),
-                 {
-                     let err_slice = unsafe { slice::from_raw_parts(err_ptr, num_errors as usize) };
-                     let odpi_vec = Vec::from(err_slice);
-                     let res_vec = odpi_vec.iter().map(|x| (*x).into()).collect();
-                     Ok(res_vec)
-                 },
+        try_dpi!(externs::dpiStmt_getBatchErrors(self.inner, num_errors, errors.as_mut_ptr()),
+                 Ok(errors.into_iter().map(|x| x.into()).collect()),
                  ErrorKind::Statement("dpiStmt_getBatchErrors".to_string()))
     }
 
@@ -252,7 +849,12 @@ impl Statement {
                  ErrorKind::Statement("dpiStmt_getBindCount".to_string()))
     }
 
-    /// Returns the names of the unique bind variables in the prepared statement.
+    /// Returns the names of the unique bind variables in the prepared statement, in the order in
+    /// which they first appear. A name used more than once in a SQL statement is returned once.
+    ///
+    /// * `num_bind_names` - the number of bind variables, as returned by `get_bind_count()`. This
+    /// includes duplicate occurrences in SQL statements, so may be more than the number of names
+    /// returned.
     #[cfg_attr(feature = "cargo-clippy", allow(used_underscore_binding))]
     pub fn get_bind_names(&self, num_bind_names: u32) -> Result<Vec<String>> {
         let mut actual_num_bind_names = num_bind_names;
@@ -270,21 +872,49 @@ impl Statement {
                                                names_vec.as_mut_ptr(),
                                                names_len_vec.as_mut_ptr()),
                  {
-                     let mut res = Vec::new();
-
-                     for (idx, (name, name_len)) in
-                names_vec.iter().zip(names_len_vec.iter()).enumerate() {
-                         if idx <= actual_num_bind_names as usize {
-                             let name_s = ODPIStr::new(*name, *name_len);
-                             res.push(name_s.into());
-                         }
-                     }
+                     let res = names_vec
+                         .iter()
+                         .zip(names_len_vec.iter())
+                         .take(actual_num_bind_names as usize)
+                         .map(|(name, name_len)| ODPIStr::new(*name, *name_len).into())
+                         .collect();
 
                      Ok(res)
                  },
                  ErrorKind::Statement("dpiStmt_getBindNames".to_string()))
     }
 
+    /// Returns the columns fetched as a native type that may lose data, such as a NUMBER with a
+    /// scale fetched into an `i64` buffer, since the last call to `fetch_into()`,
+    /// `for_each_chunk()` or `fetch_rows_deadline()`. Columns read through `Row::get_at()` are
+    /// added as they are read. The values are converted by ODPI-C, so the loss is only known from
+    /// the column metadata. Each warning is also logged to the connection's stderr logger.
+    pub fn get_coercion_warnings(&self) -> Vec<CoercionWarning> {
+        self.coercion_warnings.borrow().clone()
+    }
+
+    /// Returns the size, in bytes, the column at the given position is defined with by
+    /// `fetch_into()`, `for_each_chunk()` and `fetch_rows_deadline()`. See
+    /// `set_define_size_margin()`. The statement must have been executed.
+    ///
+    /// * `pos` - the position of the column. The first position is 1.
+    pub fn get_define_size(&self, pos: u32) -> Result<u32> {
+        Ok(self.define_size(&self.get_query_info(pos)?))
+    }
+
+    /// Returns the factor the described size of string and raw columns is multiplied by when they
+    /// are defined, or None if they are defined with the size reported by the client.
+    pub fn get_define_size_margin(&self) -> Option<f64> {
+        self.define_size_margin.get()
+    }
+
+    /// Returns the encoding information of the connection the statement was prepared on, for
+    /// converting sizes between bytes and characters, e.g. the client size of a column in
+    /// `query::Info`. None if the statement was not prepared by `Connection::prepare_stmt()`.
+    pub fn get_encoding_info(&self) -> Option<&encoding::Info> {
+        self.encoding.as_ref().map(|encoding| &**encoding)
+    }
+
     /// Gets the array size used for performing fetches.
     pub fn get_fetch_array_size(&self) -> Result<u32> {
         let mut size = 0;
@@ -294,9 +924,20 @@ impl Statement {
                  ErrorKind::Statement("dpiStmt_getFetchArraySize".to_string()))
     }
 
+    /// Returns whether LOB columns are fetched inline as byte strings by `for_each_chunk()`.
+    pub fn get_fetch_lobs_inline(&self) -> bool {
+        self.fetch_lobs_inline.get()
+    }
+
+    /// Returns the buffer size, in bytes, preallocated per row for LOB columns fetched inline.
+    pub fn get_lob_prefetch_size(&self) -> u32 {
+        self.lob_prefetch_size.get()
+    }
+
     /// Returns the next implicit result available from the last execution of the statement.
     /// Implicit results are only available when both the client and server are 12.1 or higher.
     pub fn get_implicit_result(&self) -> Result<()> {
+        self.context.require(Feature::ImplicitResults)?;
         Err(ErrorKind::Statement("Not Implemented!".to_string()).into())
     }
 
@@ -328,16 +969,31 @@ impl Statement {
     }
 
     /// Returns the value of the column at the given position for the currently fetched row, without
-    /// needing to provide a variable.
-    pub fn get_query_value(&self, pos: u32) -> Result<(enums::ODPINativeTypeNum, *mut ODPIData)> {
+    /// needing to provide a variable. The value refers to memory owned by the statement, so it
+    /// cannot outlive the statement, and is overwritten by the next fetch.
+    pub fn get_query_value<'a>(&'a self,
+                               pos: u32)
+                               -> Result<(enums::ODPINativeTypeNum, Data<'a>)> {
         let mut data = ptr::null_mut();
         let mut native_type = 0;
 
         try_dpi!(externs::dpiStmt_getQueryValue(self.inner, pos, &mut native_type, &mut data),
-                 Ok((native_type.into(), data)),
+                 Ok((native_type.into(), unsafe { data::from_ptr(data) })),
                  ErrorKind::Statement("dpiStmt_getQueryValue".to_string()))
     }
 
+    /// Returns the value of the named column for the currently fetched row, without needing to
+    /// provide a variable. The column is looked up in the metadata returned by `columns()`, first
+    /// by exact name and then ignoring ASCII case.
+    ///
+    /// * `name` - the name of the column.
+    pub fn get_query_value_by_name<'a>(&'a self,
+                                       name: &str)
+                                       -> Result<(enums::ODPINativeTypeNum, Data<'a>)> {
+        let pos = row::find_column(&self.columns()?, name, false)?;
+        self.get_query_value(pos)
+    }
+
     /// Returns the number of rows affected by the last DML statement that was executed or the
     /// number of rows currently fetched from a query. In all other cases 0 is returned.
     pub fn get_row_count(&self) -> Result<u64> {
@@ -349,10 +1005,27 @@ impl Statement {
     }
 
     /// Returns an array of row counts affected by the last invocation of `Statement::executeMany()`
-    /// with the array DML rowcounts mode enabled. This feature is only available if both client and
-    /// server are at 12.1.
+    /// with the array DML rowcounts mode enabled, one for each iteration. This feature is only
+    /// available if both client and server are at 12.1.
     pub fn get_row_counts(&self) -> Result<Vec<u64>> {
-        Err(ErrorKind::Statement("Not Implemented!".to_string()).into())
+        let mut num_row_counts = 0;
+        let mut row_counts_ptr = ptr::null_mut();
+        self.context.require(Feature::ArrayDmlRowCounts)?;
+
+        try_dpi!(externs::dpiStmt_getRowCounts(self.inner,
+                                               &mut num_row_counts,
+                                               &mut row_counts_ptr),
+                 {
+                     if row_counts_ptr.is_null() {
+                         Ok(Vec::new())
+                     } else {
+                         let row_counts = unsafe {
+                             slice::from_raw_parts(row_counts_ptr, num_row_counts as usize)
+                         };
+                         Ok(row_counts.to_vec())
+                     }
+                 },
+                 ErrorKind::Statement("dpiStmt_getRowCounts".to_string()))
     }
 
     /// Returns the id of the query that was just registered on the subscription by calling
@@ -361,16 +1034,35 @@ impl Statement {
         Err(ErrorKind::Statement("Not Implemented!".to_string()).into())
     }
 
+    /// Returns false if the `Context` the statement's connection was created in has been dropped.
+    /// Calls that need the context, such as `execute()`, then return `ErrorKind::ContextDropped`.
+    pub fn is_context_alive(&self) -> bool {
+        self.context.is_alive()
+    }
+
     /// Releases a reference to the statement. A count of the references to the statement is
     /// maintained and when this count reaches zero, the memory associated with the statement is
     /// freed and the statement is closed if that has not already taken place using the function
     /// `close()`.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiStmt_release(self.inner),
-                 Ok(()),
+                 {
+                     if let Some(ref tracker) = self.tracker {
+                         tracker.released(self.inner);
+                     }
+                     track_ref!(released, "Statement", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Statement("dpiStmt_release".to_string()))
     }
 
+    /// Returns the currently fetched row, for reading its values by column name with `Row::get()`.
+    /// The row is only valid until the next fetch.
+    pub fn row(&self) -> Result<Row> {
+        self.check_query("row")?;
+        Ok(Row::new(self, self.columns()?))
+    }
+
     /// Scrolls the statement to the position in the cursor specified by the mode and offset.
     ///
     /// * `mode` - one of the values from the enumeration `ODPIFetchMode`.
@@ -387,13 +1079,494 @@ impl Statement {
                  ErrorKind::Statement("dpiStmt_scroll".to_string()))
     }
 
+    /// Sets a safety margin for the buffers of string and raw columns defined by `fetch_into()`,
+    /// `for_each_chunk()` and `fetch_rows_deadline()`. By default a column is defined with the size
+    /// the client reports for it, which can be too small for values converted from the database
+    /// character set, or computed by expressions, failing the fetch with ORA-01406. With a margin,
+    /// the size is the largest of the sizes described after execution, in bytes on the server, in
+    /// characters times the maximum bytes per character of the connection's encoding, and in bytes
+    /// on the client, multiplied by the margin and capped at 32767 bytes. The default is the
+    /// connection's default define size margin.
+    ///
+    /// * `margin` - the factor the size is multiplied by, at least 1.0. None uses the size
+    /// reported by the client.
+    pub fn set_define_size_margin(&self, margin: Option<f64>) {
+        self.define_size_margin.set(margin.map(|margin| margin.max(1.0)));
+    }
+
     /// Sets the array size used for performing fetches. All variables defined for fetching must
     /// have this many (or more) elements allocated for them. The higher this value is the less
     /// network round trips are required to fetch rows from the database but more memory is also
     /// required. A value of zero will reset the array size to the default value of
     /// DPI_DEFAULT_FETCH_ARRAY_SIZE.
-    pub fn set_fetch_array_size(&self, _array_size: u32) -> Result<()> {
-        Err(ErrorKind::Statement("Not Implemented!".to_string()).into())
+    ///
+    /// * `array_size` - the number of rows which should be fetched each time more rows need to be
+    /// fetched from the database.
+    pub fn set_fetch_array_size(&self, array_size: u32) -> Result<()> {
+        try_dpi!(externs::dpiStmt_setFetchArraySize(self.inner, array_size),
+                 Ok(()),
+                 ErrorKind::Statement("dpiStmt_setFetchArraySize".to_string()))
+    }
+
+    /// Sets whether LOB columns are fetched inline as byte strings, rather than as LOB locators, by
+    /// `for_each_chunk()`. This is off by default.
+    ///
+    /// * `inline` - true to fetch LOB columns inline.
+    pub fn set_fetch_lobs_inline(&self, inline: bool) {
+        self.fetch_lobs_inline.set(inline);
+    }
+
+    /// Sets the LOB prefetch size. This is the buffer size, in bytes, preallocated for each row of
+    /// a LOB column fetched inline, by `define_inline()` or by `for_each_chunk()` after
+    /// `set_fetch_lobs_inline(true)`, and should cover the typical LOB length. It does not change
+    /// how LOB columns are defined; columns fetched as LOB locators are unaffected. The default is
+    /// the connection's default LOB prefetch size.
+    ///
+    /// * `size` - the LOB prefetch size, in bytes.
+    pub fn set_lob_prefetch_size(&self, size: u32) {
+        self.lob_prefetch_size.set(size);
+    }
+
+    /// Executes the query and returns its only row, with one value per column, e.g.
+    /// `let (name, salary): (String, Option<f64>) = stmt.single_row(&conn)?`. Bind the values
+    /// first, e.g. with `binder().set(..)?.bind()`. Returns `ErrorKind::NoRows` if the query
+    /// returns no rows, `ErrorKind::TooManyRows` if it returns more than one, and
+    /// `ErrorKind::NotAQuery`, without executing it, if the statement is not a query. See
+    /// `fetch_into()` for the supported types.
+    ///
+    /// * `conn` - the connection the statement was prepared on.
+    pub fn single_row<R: FetchRow>(&self, conn: &Connection) -> Result<R> {
+        self.check_query("single_row")?;
+        self.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+
+        let mut buffers = R::Buffers::default();
+        self.fetch_into(conn, 2, &mut buffers, |_| {
+                if self.get_row_count().map_or(true, |count| count > 1) {
+                    ControlFlow::Break
+                } else {
+                    ControlFlow::Continue
+                }
+            })?;
+
+        match self.get_row_count()? {
+            0 => Err(ErrorKind::NoRows.into()),
+            1 => R::take(buffers).ok_or_else(|| ErrorKind::NoRows.into()),
+            _ => Err(ErrorKind::TooManyRows.into()),
+        }
+    }
+
+    /// Executes a query of a single column and returns the value of its only row, e.g.
+    /// `let count: i64 = stmt.single_value(&conn)?`. See `single_row()`.
+    ///
+    /// * `conn` - the connection the statement was prepared on.
+    pub fn single_value<T: RowValue>(&self, conn: &Connection) -> Result<T> {
+        self.single_row(conn).map(|(value,)| value)
+    }
+
+    /// The error for a failed execute. This must be called before any other ODPI-C call is made, as
+    /// the error information is cleared at the start of every call.
+    fn execute_error(&self, fn_name: &str) -> ErrorKind {
+        let context = self.context.inner();
+        if context.is_null() {
+            ErrorKind::Statement(fn_name.to_string())
+        } else {
+            let mut error_info: ODPIErrorInfo = Default::default();
+            unsafe {
+                externs::dpiContext_getError(context, &mut error_info);
+            }
+            ErrorKind::OciError(error_info.into())
+        }
+    }
+
+    /// Returns `ErrorKind::NotAQuery` if the statement is not a query. The statement type is known
+    /// once the statement is prepared, so this takes no round trip.
+    fn check_query(&self, fn_name: &str) -> Result<()> {
+        let info = self.get_info()?;
+
+        if info.is_query() {
+            Ok(())
+        } else {
+            let statement_type = format!("{:?}", info.statement_type());
+            Err(ErrorKind::NotAQuery(fn_name.to_string(), statement_type).into())
+        }
+    }
+
+    /// Run the given closure, timing it as a call to the given ODPI-C function if timing is
+    /// enabled on the connection, and record it as activity on the connection.
+    fn timed<T, F>(&self, fn_name: &'static str, f: F) -> T
+        where F: FnOnce() -> T
+    {
+        let res = match self.timings {
+            Some(ref timings) => timings.time(fn_name, f),
+            None => f(),
+        };
+        if let Some(ref activity) = self.activity {
+            activity.set(Instant::now());
+        }
+        res
+    }
+
+    /// Records, and logs, a coercion warning if fetching the column as the given native type may
+    /// lose data. A column is only recorded once.
+    fn warn_coercion(&self, column: ColumnInfo, native_type: enums::ODPINativeTypeNum) {
+        if let Some(warning) = CoercionWarning::new(column, native_type) {
+            let mut warnings = self.coercion_warnings.borrow_mut();
+            if warnings
+                   .iter()
+                   .all(|recorded| recorded.column().position() != warning.column().position()) {
+                try_warn!(self.stderr, "{}", warning);
+                warnings.push(warning);
+            }
+        }
+    }
+
+    /// Create and define a variable for each column of an executed query, using the metadata made
+    /// available when the statement was executed. LOB columns are defined as with
+    /// `define_inline()` if inline LOB fetching is enabled.
+    fn define_columns(&self,
+                      conn: &Connection,
+                      num_cols: u32,
+                      array_size: u32)
+                      -> Result<DefineVars> {
+        let mut vars = DefineVars::with_capacity(num_cols as usize);
+        self.coercion_warnings.borrow_mut().clear();
+        for pos in 1..(num_cols + 1) {
+            let qi = self.get_query_info(pos)?;
+            let inline = self.fetch_lobs_inline.get() &&
+                         inline_lob_type(qi.oracle_type_num()).is_some();
+
+            if inline {
+                vars.push(self.define_inline(conn, pos, array_size)?);
+            } else {
+                self.warn_coercion(ColumnInfo::new(pos, &qi), qi.default_native_type_num());
+                let var = vars.push(conn.new_var(qi.oracle_type_num(),
+                                                 qi.default_native_type_num(),
+                                                 array_size,
+                                                 self.define_size(&qi),
+                                                 true,
+                                                 false,
+                                                 qi.object_type().as_ref())?);
+                self.define(pos, var)?;
+            }
+        }
+        Ok(vars)
+    }
+
+    /// Returns the size, in bytes, a column is defined with. See `set_define_size_margin()`.
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation, cast_sign_loss))]
+    fn define_size(&self, qi: &query::Info) -> u32 {
+        let client_size = qi.client_size_in_bytes();
+
+        match self.define_size_margin.get() {
+            Some(margin) if client_size > 0 && has_define_margin(qi.oracle_type_num()) => {
+                let char_size = self.encoding
+                    .as_ref()
+                    .map_or(0, |encoding| {
+                        encoding.bytes_for_chars(qi.oracle_type_num(), qi.size_in_chars())
+                    });
+                let size = cmp::max(client_size, cmp::max(qi.db_size_in_bytes(), char_size));
+                let size = (f64::from(size) * margin).ceil();
+                cmp::max(client_size, cmp::min(size as u64, u64::from(MAX_DEFINE_SIZE)) as u32)
+            }
+            _ => client_size,
+        }
+    }
+
+    /// Fetch rows into the given variables, one round trip at a time, until `max_rows` rows have
+    /// been handed to the closure, the rows are exhausted or the deadline is reached. The fetch
+    /// array size is lowered for the last round trip, so no rows are left in the buffers when the
+    /// variables are redefined by the next call.
+    fn fetch_until<F>(&self,
+                      conn: &Connection,
+                      vars: &[Var],
+                      array_size: u32,
+                      max_rows: u32,
+                      deadline: Instant,
+                      f: &mut F)
+                      -> Result<(u32, bool)>
+        where F: FnMut(&RowBatch)
+    {
+        let mut fetched = 0;
+
+        while fetched < max_rows {
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok((fetched, true));
+            }
+
+            let round_trip_rows = cmp::min(array_size, max_rows - fetched);
+            self.set_fetch_array_size(round_trip_rows)?;
+
+            let watchdog = Watchdog::start(connection::inner(conn), deadline - now);
+            let res = self.fetch_rows(round_trip_rows);
+            let broken = watchdog.stop();
+
+            let (buffer_row_index, num_rows, more_rows) = match res {
+                Err(_) if broken => return Ok((fetched, true)),
+                res => res?,
+            };
+
+            if num_rows > 0 {
+                f(&RowBatch::new(vars, buffer_row_index, num_rows)?);
+                fetched += num_rows;
+            }
+
+            if num_rows == 0 || !more_rows {
+                break;
+            }
+        }
+
+        Ok((fetched, false))
+    }
+
+    /// Defines a variable for each column of the query with the native type of its buffer, for
+    /// `fetch_into()`, pushing it onto `vars`.
+    fn define_buffers(&self,
+                      conn: &Connection,
+                      max_rows: u32,
+                      native_types: Vec<enums::ODPINativeTypeNum>,
+                      vars: &mut DefineVars)
+                      -> Result<()> {
+        self.coercion_warnings.borrow_mut().clear();
+        for (pos, native_type) in (1..).zip(native_types) {
+            let qi = self.get_query_info(pos)?;
+            self.warn_coercion(ColumnInfo::new(pos, &qi), native_type);
+            let inline = native_type == enums::ODPINativeTypeNum::Bytes &&
+                         inline_lob_type(qi.oracle_type_num()).is_some();
+
+            if inline {
+                vars.push(self.define_inline(conn, pos, max_rows)?);
+            } else {
+                let var = vars.push(conn.new_var(qi.oracle_type_num(),
+                                                 native_type,
+                                                 max_rows,
+                                                 self.define_size(&qi),
+                                                 true,
+                                                 false,
+                                                 None)?);
+                self.define(pos, var)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetch chunks of rows into the given variables until the rows are exhausted or the closure
+    /// returns `ControlFlow::Break`, handing each chunk to the closure as a `RowBatch`.
+    fn fetch_chunks<F>(&self, vars: &[Var], max_rows: u32, f: &mut F) -> Result<()>
+        where F: FnMut(&RowBatch) -> ControlFlow
+    {
+        loop {
+            let (buffer_row_index, num_rows, more_rows) = self.fetch_rows(max_rows)?;
+
+            if num_rows == 0 {
+                return Ok(());
+            }
+
+            let batch = RowBatch::new(vars, buffer_row_index, num_rows)?;
+
+            if f(&batch) == ControlFlow::Break || !more_rows {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Fetch chunks of rows into the given buffers until the rows are exhausted or the closure
+    /// returns `ControlFlow::Break`.
+    fn copy_chunks<B, F>(&self,
+                         vars: &[Var],
+                         max_rows: u32,
+                         buffers: &mut B,
+                         f: &mut F)
+                         -> Result<()>
+        where B: FetchBuffers,
+              F: FnMut(&mut B) -> ControlFlow
+    {
+        loop {
+            let (buffer_row_index, num_rows, more_rows) = self.fetch_rows(max_rows)?;
+
+            if num_rows == 0 {
+                return Ok(());
+            }
+
+            let mut columns = Vec::with_capacity(vars.len());
+            for var in vars {
+                columns.push(&*unsafe { variable::data(var)? });
+            }
+
+            buffers.clear();
+            for row in buffer_row_index..(buffer_row_index + num_rows) {
+                buffers.push_row(&columns, row as usize)?;
+            }
+
+            if f(buffers) == ControlFlow::Break || !more_rows {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Returns the Oracle type used to fetch a LOB column of the given type inline, or None if the type
+/// is not a LOB type that can be fetched inline.
+fn inline_lob_type(oracle_type_num: enums::ODPIOracleTypeNum) -> Option<enums::ODPIOracleTypeNum> {
+    match oracle_type_num {
+        enums::ODPIOracleTypeNum::Clob |
+        enums::ODPIOracleTypeNum::NClob => Some(enums::ODPIOracleTypeNum::LongVarchar),
+        enums::ODPIOracleTypeNum::Blob => Some(enums::ODPIOracleTypeNum::LongRaw),
+        _ => None,
+    }
+}
+
+/// Returns true for the string and raw types whose define size is multiplied by the define size
+/// margin. The buffers of other types have a fixed size.
+fn has_define_margin(oracle_type_num: enums::ODPIOracleTypeNum) -> bool {
+    match oracle_type_num {
+        enums::ODPIOracleTypeNum::Varchar |
+        enums::ODPIOracleTypeNum::NVarchar |
+        enums::ODPIOracleTypeNum::Char |
+        enums::ODPIOracleTypeNum::NChar |
+        enums::ODPIOracleTypeNum::Raw |
+        enums::ODPIOracleTypeNum::LongVarchar |
+        enums::ODPIOracleTypeNum::LongRaw => true,
+        _ => false,
+    }
+}
+
+/// The define variables created to fetch the rows of a query. Any variables not released by
+/// `release()` are released when the value is dropped, so they are not leaked when fetching fails
+/// part way.
+struct DefineVars {
+    /// The define variables, one per column.
+    vars: Vec<Var>,
+}
+
+impl DefineVars {
+    /// Create an empty set of define variables with room for the given number of columns.
+    fn with_capacity(capacity: usize) -> DefineVars {
+        DefineVars { vars: Vec::with_capacity(capacity) }
+    }
+
+    /// Add a define variable, returning a reference to it.
+    fn push(&mut self, var: Var) -> &Var {
+        self.vars.push(var);
+        &self.vars[self.vars.len() - 1]
+    }
+
+    /// Release the define variables, returning the first error.
+    fn release(mut self) -> Result<()> {
+        let mut res = Ok(());
+        for var in self.vars.drain(..) {
+            let released = var.release();
+            if res.is_ok() {
+                res = released;
+            }
+        }
+        res
+    }
+}
+
+impl Deref for DefineVars {
+    type Target = [Var];
+
+    fn deref(&self) -> &[Var] {
+        &self.vars
+    }
+}
+
+impl Drop for DefineVars {
+    fn drop(&mut self) {
+        for var in &self.vars {
+            let _ = var.release();
+        }
+    }
+}
+
+/// The value returned from the closure given to `Statement::for_each_chunk()`, indicating whether
+/// fetching should continue.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ControlFlow {
+    /// Fetch the next chunk of rows, if any are available.
+    Continue,
+    /// Stop fetching rows.
+    Break,
+}
+
+/// A chunk of fetched rows handed out by `Statement::for_each_chunk()`. The values are only valid
+/// for the duration of the closure call, as the underlying buffers are reused by the next fetch.
+pub struct RowBatch<'a> {
+    /// The define variable data, one slice per column.
+    columns: Vec<&'a [ODPIData]>,
+    /// Whether each row of the batch is null, one mask per column.
+    null_masks: Vec<Vec<bool>>,
+    /// The index of the first row of this batch in the define variable buffers.
+    offset: u32,
+    /// The number of rows in this batch.
+    num_rows: u32,
+}
+
+impl<'a> RowBatch<'a> {
+    /// Create a batch over the rows of the given define variables.
+    fn new(vars: &'a [Var], offset: u32, num_rows: u32) -> Result<RowBatch<'a>> {
+        let mut columns = Vec::with_capacity(vars.len());
+        let mut null_masks = Vec::with_capacity(vars.len());
+        for var in vars {
+            let data = &*unsafe { variable::data(var)? };
+            let start = offset as usize;
+            let end = cmp::min(start + num_rows as usize, data.len());
+            null_masks.push(data[cmp::min(start, end)..end]
+                                .iter()
+                                .map(|value| value.is_null != 0)
+                                .collect());
+            columns.push(data);
+        }
+
+        Ok(RowBatch {
+               columns: columns,
+               null_masks: null_masks,
+               offset: offset,
+               num_rows: num_rows,
+           })
+    }
+
+    /// Returns the number of rows in the batch.
+    pub fn num_rows(&self) -> u32 {
+        self.num_rows
+    }
+
+    /// Returns the number of columns in each row of the batch.
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation))]
+    pub fn num_cols(&self) -> u32 {
+        self.columns.len() as u32
+    }
+
+    /// Returns the value at the given row and column, or None if either is out of range. The value
+    /// is a copy, so setting it does not change the fetched row.
+    ///
+    /// * `row` - the row within the batch. The first row is 0.
+    /// * `pos` - the column position. The first position is 1, matching
+    /// `Statement::get_query_info()`.
+    pub fn get(&self, row: u32, pos: u32) -> Option<Data<'a>> {
+        if row >= self.num_rows || pos == 0 {
+            return None;
+        }
+
+        self.columns
+            .get((pos - 1) as usize)
+            .and_then(|col| col.get((self.offset + row) as usize))
+            .map(data::copied)
+    }
+
+    /// Returns, for each row of the batch, whether the value in the given column is null, or None
+    /// if the column is out of range. The mask is read from the define variable buffers when the
+    /// batch is fetched, so consumers can skip nulls without creating a `Data` value per row.
+    ///
+    /// * `pos` - the column position. The first position is 1, matching
+    /// `Statement::get_query_info()`.
+    pub fn null_mask(&self, pos: u32) -> Option<&[bool]> {
+        if pos == 0 {
+            None
+        } else {
+            self.null_masks.get((pos - 1) as usize).map(|mask| &mask[..])
+        }
     }
 }
 
@@ -449,6 +1622,7 @@ impl Info {
 
 impl From<*mut ODPIStmt> for Statement {
     fn from(inner: *mut ODPIStmt) -> Statement {
-        Statement { inner: inner }
+        track_ref!(wrapped, "Statement", inner);
+        Statement::new(inner)
     }
 }
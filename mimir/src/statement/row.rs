@@ -0,0 +1,269 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Typed access by column name to the currently fetched row of a query, created with
+//! `Statement::row()`. Oracle stores unquoted identifiers in uppercase, so names are matched
+//! ignoring ASCII case by default, with an exact match preferred; `Row::get_exact()` only accepts
+//! an exact match, for columns with quoted names differing only in case.
+//!
+//! ```ignore
+//! while stmt.fetch()?.0 {
+//!     let row = stmt.row()?;
+//!     let id: i64 = row.get("id")?;
+//!     let name: Option<String> = row.get("name")?;
+//! }
+//! ```
+use chrono::{DateTime, Duration, Utc};
+use data::{Data, YearsMonths};
+use error::{ErrorKind, Result};
+use odpi::enums::ODPINativeTypeNum;
+use query::ColumnInfo;
+use statement::Statement;
+use statement::buffers::FetchValue;
+use std::rc::Rc;
+
+/// A value that can be read from a column with `Row::get()`. `T` requires the value to be NOT
+/// NULL, while `Option<T>` accepts NULL.
+pub trait FromValue: Sized {
+    /// Read the value out of the given data, or None if it is NULL or its native type cannot be
+    /// read as `Self`.
+    ///
+    /// * `native_type` - the native type the data was fetched as.
+    /// * `data` - the fetched data.
+    fn from_value(native_type: ODPINativeTypeNum, data: &Data) -> Option<Self>;
+}
+
+/// Read the bytes of the given data, which is not NULL and was fetched as `Bytes`.
+fn bytes(data: &Data) -> Vec<u8> {
+    unsafe { Vec::<u8>::from_data(&*data.inner()) }
+}
+
+impl FromValue for i64 {
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_possible_truncation, float_cmp))]
+    fn from_value(native_type: ODPINativeTypeNum, data: &Data) -> Option<i64> {
+        if data.is_null() {
+            return None;
+        }
+
+        match native_type {
+            ODPINativeTypeNum::Int64 => Some(data.get_int64()),
+            ODPINativeTypeNum::Uint64 => {
+                let val = data.get_uint64();
+                if val <= i64::max_value() as u64 {
+                    Some(val as i64)
+                } else {
+                    None
+                }
+            }
+            ODPINativeTypeNum::Double => {
+                let val = data.get_double();
+                if val.trunc() == val && val >= i64::min_value() as f64 &&
+                   val < i64::max_value() as f64 {
+                    Some(val as i64)
+                } else {
+                    None
+                }
+            }
+            ODPINativeTypeNum::Bytes => String::from_utf8_lossy(&bytes(data)).trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for f64 {
+    #[cfg_attr(feature = "cargo-clippy", allow(cast_precision_loss))]
+    fn from_value(native_type: ODPINativeTypeNum, data: &Data) -> Option<f64> {
+        if data.is_null() {
+            return None;
+        }
+
+        match native_type {
+            ODPINativeTypeNum::Double => Some(data.get_double()),
+            ODPINativeTypeNum::Float => Some(f64::from(data.get_float())),
+            ODPINativeTypeNum::Int64 => Some(data.get_int64() as f64),
+            ODPINativeTypeNum::Uint64 => Some(data.get_uint64() as f64),
+            ODPINativeTypeNum::Bytes => String::from_utf8_lossy(&bytes(data)).trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(native_type: ODPINativeTypeNum, data: &Data) -> Option<bool> {
+        match native_type {
+            ODPINativeTypeNum::Boolean if !data.is_null() => Some(data.get_boolean()),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(native_type: ODPINativeTypeNum, data: &Data) -> Option<String> {
+        match native_type {
+            ODPINativeTypeNum::Bytes if !data.is_null() => {
+                Some(String::from_utf8_lossy(&bytes(data)).into_owned())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Vec<u8> {
+    fn from_value(native_type: ODPINativeTypeNum, data: &Data) -> Option<Vec<u8>> {
+        match native_type {
+            ODPINativeTypeNum::Bytes if !data.is_null() => Some(bytes(data)),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for DateTime<Utc> {
+    fn from_value(native_type: ODPINativeTypeNum, data: &Data) -> Option<DateTime<Utc>> {
+        match native_type {
+            ODPINativeTypeNum::Timestamp if !data.is_null() => Some(data.get_utc()),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Duration {
+    fn from_value(native_type: ODPINativeTypeNum, data: &Data) -> Option<Duration> {
+        match native_type {
+            ODPINativeTypeNum::IntervalDS if !data.is_null() => Some(data.get_duration()),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for YearsMonths {
+    fn from_value(native_type: ODPINativeTypeNum, data: &Data) -> Option<YearsMonths> {
+        match native_type {
+            ODPINativeTypeNum::IntervalYM if !data.is_null() => Some(data.get_years_months()),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(native_type: ODPINativeTypeNum, data: &Data) -> Option<Option<T>> {
+        if data.is_null() {
+            Some(None)
+        } else {
+            T::from_value(native_type, data).map(Some)
+        }
+    }
+}
+
+/// Returns the position of the named column. With `exact` unset, a column whose name matches
+/// exactly is preferred, and otherwise one whose name matches ignoring ASCII case is returned. The
+/// error for an unknown column lists the names of the available ones.
+///
+/// * `columns` - the columns of the query.
+/// * `name` - the name of the column.
+/// * `exact` - whether only an exact match is accepted.
+#[doc(hidden)]
+pub fn find_column(columns: &[ColumnInfo], name: &str, exact: bool) -> Result<u32> {
+    let by_name = |exact: bool| {
+        columns
+            .iter()
+            .find(|col| if exact {
+                      col.name() == name
+                  } else {
+                      col.name().eq_ignore_ascii_case(name)
+                  })
+            .map(|col| col.position())
+    };
+
+    let found = if exact {
+        by_name(true)
+    } else {
+        by_name(true).or_else(|| by_name(false))
+    };
+
+    match found {
+        Some(pos) => Ok(pos),
+        None => {
+            let names: Vec<_> = columns.iter().map(|col| col.name()).collect();
+            let err = format!("unknown column '{}', the columns are: {}", name, names.join(", "));
+            Err(ErrorKind::Statement(err).into())
+        }
+    }
+}
+
+/// The currently fetched row of a query. The values are read from memory owned by the statement,
+/// so they are only valid until the next fetch; the typed values returned are copies.
+pub struct Row<'a> {
+    /// The statement the row was fetched by.
+    stmt: &'a Statement,
+    /// The columns of the query.
+    columns: Rc<Vec<ColumnInfo>>,
+}
+
+impl<'a> Row<'a> {
+    /// Create a new `Row` over the currently fetched row of the given statement.
+    #[doc(hidden)]
+    pub fn new(stmt: &'a Statement, columns: Rc<Vec<ColumnInfo>>) -> Row<'a> {
+        Row {
+            stmt: stmt,
+            columns: columns,
+        }
+    }
+
+    /// Get the `columns` value.
+    pub fn columns(&self) -> &[ColumnInfo] {
+        &self.columns
+    }
+
+    /// Returns the value of the named column, matching the name ignoring ASCII case if no column
+    /// matches exactly, so `row.get("id")` finds the column of the unquoted identifier `id`.
+    ///
+    /// * `name` - the name of the column.
+    pub fn get<T: FromValue>(&self, name: &str) -> Result<T> {
+        let pos = find_column(&self.columns, name, false)?;
+        self.get_at(pos)
+    }
+
+    /// Returns the value of the named column, only matching the name exactly. Use this for quoted
+    /// identifiers, e.g. to tell the columns `"id"` and `"ID"` apart.
+    ///
+    /// * `name` - the name of the column, as returned by `ColumnInfo::name()`.
+    pub fn get_exact<T: FromValue>(&self, name: &str) -> Result<T> {
+        let pos = find_column(&self.columns, name, true)?;
+        self.get_at(pos)
+    }
+
+    /// Returns the value of the column at the given position. If the column was fetched as a
+    /// native type that may lose data, a warning is recorded; see
+    /// `Statement::get_coercion_warnings()`.
+    ///
+    /// * `pos` - the position of the column. The first position is 1.
+    pub fn get_at<T: FromValue>(&self, pos: u32) -> Result<T> {
+        let (native_type, data) = self.stmt.get_query_value(pos)?;
+        if let Some(column) = self.columns.iter().find(|col| col.position() == pos) {
+            self.stmt.warn_coercion(column.clone(), native_type);
+        }
+
+        match T::from_value(native_type, &data) {
+            Some(val) => Ok(val),
+            None => {
+                let name = self.columns
+                    .iter()
+                    .find(|col| col.position() == pos)
+                    .map_or("", |col| col.name());
+                let err = if data.is_null() {
+                    format!("column '{}' is NULL, use an Option to read it", name)
+                } else {
+                    format!("column '{}' of native type {:?} cannot be read as the requested type",
+                            name,
+                            native_type)
+                };
+                Err(ErrorKind::Statement(err).into())
+            }
+        }
+    }
+}
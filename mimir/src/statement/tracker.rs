@@ -0,0 +1,113 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Open statement tracking, enabled per connection with `Connection::set_track_statements()`.
+//! Each statement prepared on the connection is recorded until its last reference is released or
+//! it is closed, so statements that are never released (and hold an open cursor on the server
+//! until the connection is closed) can be found before they exhaust `OPEN_CURSORS` and fail with
+//! ORA-01000. In debug builds the backtrace of the call that prepared each statement is captured
+//! as well.
+use error_chain::Backtrace;
+use odpi::opaque::ODPIStmt;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A statement that has been prepared but not yet released.
+#[derive(Clone, Debug)]
+pub struct OpenStatement {
+    /// The SQL text of the statement, if any.
+    sql: Option<String>,
+    /// The number of references held to the statement.
+    refs: u32,
+    /// The backtrace of the call that prepared the statement. Only captured in debug builds.
+    backtrace: Option<Backtrace>,
+}
+
+impl OpenStatement {
+    /// Get the `backtrace` value.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_ref()
+    }
+
+    /// Get the `sql` value.
+    pub fn sql(&self) -> Option<&str> {
+        self.sql.as_ref().map(|s| s.as_str())
+    }
+}
+
+impl fmt::Display for OpenStatement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "statement '{}' was never released", self.sql().unwrap_or(""))?;
+        if let Some(ref backtrace) = self.backtrace {
+            write!(f, ", prepared at:\n{:?}", backtrace)?;
+        }
+        Ok(())
+    }
+}
+
+/// The statements that are open on a connection, keyed by their ODPI-C handle.
+#[derive(Debug, Default)]
+pub struct Tracker {
+    /// The open statements.
+    open: RefCell<HashMap<usize, OpenStatement>>,
+}
+
+impl Tracker {
+    /// Record a newly prepared statement.
+    pub fn opened(&self, stmt: *mut ODPIStmt, sql: Option<&str>) {
+        let backtrace = if cfg!(debug_assertions) {
+            Some(Backtrace::new())
+        } else {
+            None
+        };
+
+        self.open
+            .borrow_mut()
+            .insert(stmt as usize,
+                    OpenStatement {
+                        sql: sql.map(|s| s.to_string()),
+                        refs: 1,
+                        backtrace: backtrace,
+                    });
+    }
+
+    /// Record a reference added to the statement.
+    pub fn add_ref(&self, stmt: *mut ODPIStmt) {
+        if let Some(open) = self.open.borrow_mut().get_mut(&(stmt as usize)) {
+            open.refs += 1;
+        }
+    }
+
+    /// Record a reference to the statement being released, forgetting the statement once its
+    /// last reference is gone.
+    pub fn released(&self, stmt: *mut ODPIStmt) {
+        let mut open = self.open.borrow_mut();
+        let last = match open.get_mut(&(stmt as usize)) {
+            Some(statement) => {
+                statement.refs -= 1;
+                statement.refs == 0
+            }
+            None => false,
+        };
+
+        if last {
+            open.remove(&(stmt as usize));
+        }
+    }
+
+    /// Forget the statement, as it has been closed.
+    pub fn closed(&self, stmt: *mut ODPIStmt) {
+        self.open.borrow_mut().remove(&(stmt as usize));
+    }
+
+    /// Returns the statements that are currently open.
+    pub fn open_statements(&self) -> Vec<OpenStatement> {
+        self.open.borrow().values().cloned().collect()
+    }
+}
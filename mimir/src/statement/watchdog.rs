@@ -0,0 +1,168 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A watchdog that breaks the execution of a call on a connection when the call does not complete
+//! within a timeout. ODPI-C 2.0 has no call timeout, so this is how
+//! `Statement::execute_with_timeout()` bounds a call. A single thread, started on first use,
+//! watches the calls of every connection, so no thread is spawned per call.
+use odpi::externs;
+use odpi::opaque::ODPIConn;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+lazy_static! {
+    /// Registers calls with the watchdog thread, which is started on first use.
+    static ref WATCHES: Mutex<Sender<Watch>> = {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || run(&receiver));
+        Mutex::new(sender)
+    };
+}
+
+/// A raw ODPI-C pointer that can be moved to the watchdog thread. ODPI-C handles are safe to use
+/// across threads when the connection is created with `DPI_MODE_CREATE_THREADED`.
+struct SendPtr<T>(*mut T);
+
+unsafe impl<T> Send for SendPtr<T> {}
+
+/// The state of a watched call, shared by the caller and the watchdog thread.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum State {
+    /// The call is running and its execution is broken at the deadline.
+    Armed,
+    /// The watchdog broke the execution of the call.
+    Broken,
+    /// The call completed, so its execution is no longer broken.
+    Cancelled,
+}
+
+/// A call registered with the watchdog thread.
+struct Watch {
+    /// The connection the call is made on.
+    conn: SendPtr<ODPIConn>,
+    /// When the execution of the call is broken.
+    deadline: Instant,
+    /// The state of the call.
+    state: Arc<Mutex<State>>,
+}
+
+impl Watch {
+    /// Breaks the execution of the call, unless it has been cancelled. The state is locked while
+    /// the break is made, so a caller cancelling the watch waits for the break to be made.
+    fn expire(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if *state == State::Armed {
+            unsafe {
+                externs::dpiConn_breakExecution(self.conn.0);
+            }
+            *state = State::Broken;
+        }
+    }
+
+    /// Is the call still running?
+    fn is_armed(&self) -> bool {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner()) == State::Armed
+    }
+}
+
+/// A call being watched. Dropping it cancels the watch.
+pub struct Watchdog {
+    /// The connection the call is made on.
+    conn: *mut ODPIConn,
+    /// The state of the call, shared with the watchdog thread.
+    state: Arc<Mutex<State>>,
+}
+
+impl Watchdog {
+    /// Start watching a call, calling `dpiConn_breakExecution()` on the connection if `stop()` is
+    /// not called within the timeout.
+    ///
+    /// * `conn` - the connection the call is made on.
+    /// * `timeout` - the time the call is given to complete.
+    pub fn start(conn: *mut ODPIConn, timeout: Duration) -> Watchdog {
+        let state = Arc::new(Mutex::new(State::Armed));
+        let watch = Watch {
+            conn: SendPtr(conn),
+            deadline: Instant::now() + timeout,
+            state: state.clone(),
+        };
+
+        let sender = WATCHES.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = sender.send(watch);
+
+        Watchdog {
+            conn: conn,
+            state: state,
+        }
+    }
+
+    /// Stop watching the call once it has completed. No break is made after this returns. Returns
+    /// true if the watchdog broke the execution of the call.
+    pub fn stop(self) -> bool {
+        self.cancel()
+    }
+
+    /// Cancels the watch, returning true if the execution was broken. A break made as the call
+    /// completed is still pending, and would interrupt the next call on the connection, so it is
+    /// cleared with a ping.
+    fn cancel(&self) -> bool {
+        let broken = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let broken = *state == State::Broken;
+            *state = State::Cancelled;
+            broken
+        };
+
+        if broken {
+            unsafe {
+                externs::dpiConn_ping(self.conn);
+            }
+        }
+        broken
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        let _ = self.cancel();
+    }
+}
+
+/// The watchdog thread. Waits for new calls until the earliest deadline, then breaks the calls
+/// whose deadline has passed.
+fn run(receiver: &Receiver<Watch>) {
+    let mut watches: Vec<Watch> = Vec::new();
+
+    loop {
+        watches.retain(Watch::is_armed);
+        let received = match watches.iter().map(|watch| watch.deadline).min() {
+            Some(deadline) => {
+                let now = Instant::now();
+                if deadline > now {
+                    receiver.recv_timeout(deadline - now)
+                } else {
+                    Err(RecvTimeoutError::Timeout)
+                }
+            }
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        match received {
+            Ok(watch) => watches.push(watch),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let now = Instant::now();
+        for watch in watches.iter().filter(|watch| watch.deadline <= now) {
+            watch.expire();
+        }
+    }
+}
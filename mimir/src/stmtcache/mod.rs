@@ -0,0 +1,205 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Statement cache statistics, for checking that the size given to
+//! `Connection::set_statement_cache_size()` or `Pool::set_stmt_cache_size()` is large enough.
+//!
+//! ODPI-C does not report whether a prepare found the statement in the cache, so the outcome is
+//! worked out by replaying each prepare against a model of the cache: a least recently used list
+//! of the tags, or SQL text for untagged statements, sized like the real cache. Prepares given
+//! only a tag are exact, as they fail unless the statement is cached. The model of a connection
+//! acquired from a pool starts empty, so statements cached by the session before it was acquired
+//! are counted as misses.
+//!
+//! Oracle looks a statement up by its tag alone when one is given, even if the SQL text differs,
+//! so two statements sharing a tag get each other's cursor. `cache_tag()` derives the tags used by
+//! `Connection::prepare_cached()` from the SQL text, so statements only share a tag if they share
+//! their SQL.
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, AddAssign};
+use std::sync::{Arc, Mutex};
+
+/// The prefix of the tags returned by `cache_tag()`.
+const CACHE_TAG_PREFIX: &str = "mimir";
+
+/// Returns the statement cache tag of the given SQL text and key, as used by
+/// `Connection::prepare_cached()`. The tag is made of the key and a hash of the SQL text and its
+/// length, so different SQL never shares a tag, while the same SQL can be cached more than once
+/// under different keys, e.g. once per set of bind types.
+///
+/// * `sql` - the SQL text of the statement.
+/// * `key` - an additional key distinguishing statements with the same SQL text.
+pub fn cache_tag(sql: &str, key: Option<&str>) -> String {
+    let mut hasher = DefaultHasher::new();
+    sql.hash(&mut hasher);
+
+    format!("{}:{}:{:x}:{:016x}",
+            CACHE_TAG_PREFIX,
+            key.unwrap_or(""),
+            sql.len(),
+            hasher.finish())
+}
+
+/// The number of statement cache hits and misses, returned by
+/// `Connection::get_statement_cache_stats()` and `Pool::get_stmt_cache_stats()`. ODPI-C does not
+/// report cache hits, so the counts are estimated from a least recently used model of the cache.
+/// Prepares given only a tag are exact. Statements cached by a pooled session before the
+/// connection was acquired are counted as misses.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    /// The number of prepares that found the statement in the cache.
+    hits: u64,
+    /// The number of prepares that did not find the statement in the cache.
+    misses: u64,
+    /// The number of statements pushed out of the cache to make room for another.
+    evictions: u64,
+}
+
+impl CacheStats {
+    /// Get the `evictions` value.
+    pub fn get_evictions(&self) -> u64 {
+        self.evictions
+    }
+
+    /// Get the `hits` value.
+    pub fn get_hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Get the fraction of prepares that found the statement in the cache, from 0 to 1. None if no
+    /// statement has been prepared.
+    pub fn get_hit_rate(&self) -> Option<f64> {
+        match self.get_prepares() {
+            0 => None,
+            prepares => Some(self.hits as f64 / prepares as f64),
+        }
+    }
+
+    /// Get the `misses` value.
+    pub fn get_misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Get the number of prepares.
+    pub fn get_prepares(&self) -> u64 {
+        self.hits.saturating_add(self.misses)
+    }
+
+    /// Add the given number of statements pushed out of the cache.
+    pub fn record_evictions(&mut self, evictions: u64) {
+        self.evictions = self.evictions.saturating_add(evictions);
+    }
+
+    /// Add a prepare that did, or did not, find the statement in the cache.
+    pub fn record(&mut self, hit: bool) {
+        if hit {
+            self.hits = self.hits.saturating_add(1);
+        } else {
+            self.misses = self.misses.saturating_add(1);
+        }
+    }
+}
+
+impl Add for CacheStats {
+    type Output = CacheStats;
+
+    fn add(self, other: CacheStats) -> CacheStats {
+        CacheStats {
+            hits: self.hits.saturating_add(other.hits),
+            misses: self.misses.saturating_add(other.misses),
+            evictions: self.evictions.saturating_add(other.evictions),
+        }
+    }
+}
+
+impl AddAssign for CacheStats {
+    fn add_assign(&mut self, other: CacheStats) {
+        *self = *self + other;
+    }
+}
+
+/// The model of the statement cache of a connection, and the statistics recorded with it.
+#[derive(Debug, Default)]
+pub struct Tracker {
+    /// The keys of the cached statements, most recently used first.
+    keys: RefCell<VecDeque<String>>,
+    /// The statistics of the connection.
+    stats: Cell<CacheStats>,
+    /// The statistics of the pool the connection was acquired from, if it records them. They are
+    /// shared with the connections acquired on other threads through a `PoolHandle`.
+    pool_stats: Option<Arc<Mutex<CacheStats>>>,
+}
+
+impl Tracker {
+    /// Create a new `Tracker` that also adds its statistics to those of a pool.
+    pub fn with_pool_stats(pool_stats: Arc<Mutex<CacheStats>>) -> Tracker {
+        Tracker {
+            pool_stats: Some(pool_stats),
+            ..Default::default()
+        }
+    }
+
+    /// Record that every statement was removed from the cache.
+    pub fn flushed(&self) {
+        self.keys.borrow_mut().clear();
+    }
+
+    /// Record a prepare that failed because the tag was not found in the cache.
+    pub fn not_found(&self, tag: &str) {
+        self.keys.borrow_mut().retain(|key| key != tag);
+        self.record(false);
+    }
+
+    /// Record a successful prepare, which leaves the statement in the cache once it is released.
+    ///
+    /// * `key` - the tag of the statement, or its SQL text if it has no tag.
+    /// * `tag_only` - was the statement prepared from its tag alone, so it must have been cached?
+    /// * `capacity` - the size of the statement cache.
+    pub fn prepared(&self, key: &str, tag_only: bool, capacity: usize) {
+        let mut keys = self.keys.borrow_mut();
+        let pos = keys.iter().position(|cached| cached == key);
+
+        if let Some(pos) = pos {
+            keys.remove(pos);
+        }
+        if capacity > 0 {
+            keys.push_front(key.to_string());
+        }
+        let evictions = keys.len().saturating_sub(capacity);
+        keys.truncate(capacity);
+
+        self.record(tag_only || pos.is_some());
+        if evictions > 0 {
+            self.update(|stats| stats.record_evictions(evictions as u64));
+        }
+    }
+
+    /// Returns the statistics recorded so far.
+    pub fn stats(&self) -> CacheStats {
+        self.stats.get()
+    }
+
+    /// Add a prepare to the statistics of the connection and of its pool.
+    fn record(&self, hit: bool) {
+        self.update(|stats| stats.record(hit));
+    }
+
+    /// Apply a change to the statistics of the connection and of its pool.
+    fn update<F: Fn(&mut CacheStats)>(&self, f: F) {
+        let mut stats = self.stats.get();
+        f(&mut stats);
+        self.stats.set(stats);
+
+        if let Some(ref pool_stats) = self.pool_stats {
+            f(&mut pool_stats.lock().unwrap_or_else(|e| e.into_inner()));
+        }
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Notifications decoded from the ODPI-C structures, which are only valid during the callback,
+//! into owned values that can be sent to another thread.
+use common::error::Info as ErrorInfo;
+use odpi::{enums, flags};
+use odpi::structs::{ODPISubscrMessage, ODPISubscrMessageQuery, ODPISubscrMessageRow,
+                    ODPISubscrMessageTable};
+use std::slice;
+use util::ODPIStr;
+
+/// A notification, as delivered by `Subscription::into_receiver()`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// A change to registered queries or objects, or another event such as an instance shutting
+    /// down.
+    Change(Notification),
+    /// The subscription was deregistered: its timeout was reached, it was deregistered after its
+    /// first notification as requested by `DPI_SUBSCR_QOS_DEREG_NFY`, or the database dropped it.
+    /// No more notifications are sent after it, so a long-lived listener should subscribe again
+    /// with `Subscription::renew()`.
+    Deregistered(Notification),
+}
+
+/// A notification decoded from an `ODPISubscrMessage`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notification {
+    /// The type of event that generated the notification.
+    pub event_type: enums::ODPIEventType,
+    /// The name of the database that generated the notification.
+    pub db_name: String,
+    /// The tables that were changed, for `ObjectChange` events.
+    pub tables: Vec<TableChange>,
+    /// The registered queries whose results were changed, for `QueryChange` events.
+    pub queries: Vec<QueryChange>,
+    /// The error reported with the notification, if any. The other values may not be valid when
+    /// an error is reported.
+    pub error: Option<ErrorInfo>,
+}
+
+/// A change to the results of a registered query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryChange {
+    /// The id of the query, as registered on the subscription.
+    pub id: u64,
+    /// The operations that took place on the query.
+    pub operation: flags::ODPIOpCode,
+    /// The tables whose changes affected the query.
+    pub tables: Vec<TableChange>,
+}
+
+/// A change to a row of a table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RowChange {
+    /// The operations that took place on the row.
+    pub operation: flags::ODPIOpCode,
+    /// The rowid of the row.
+    pub rowid: String,
+}
+
+/// A change to a table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableChange {
+    /// The operations that took place on the table.
+    pub operation: flags::ODPIOpCode,
+    /// The name of the table, qualified by its schema.
+    pub name: String,
+    /// The rows that were changed. Empty unless `DPI_SUBSCR_QOS_ROWIDS` was requested, or if too
+    /// many rows were changed, in which case `operation` includes `DPI_OPCODE_ALL_ROWS`.
+    pub rows: Vec<RowChange>,
+}
+
+impl Message {
+    /// Returns the decoded notification, whatever the kind of message.
+    pub fn notification(&self) -> &Notification {
+        match *self {
+            Message::Change(ref notification) |
+            Message::Deregistered(ref notification) => notification,
+        }
+    }
+
+    /// Is this a deregistration, after which no more notifications are sent?
+    pub fn is_deregistered(&self) -> bool {
+        match *self {
+            Message::Deregistered(_) => true,
+            Message::Change(_) => false,
+        }
+    }
+}
+
+impl<'a> From<&'a ODPISubscrMessage> for Message {
+    fn from(message: &'a ODPISubscrMessage) -> Message {
+        let notification = Notification::from(message);
+        if notification.event_type == enums::ODPIEventType::Dereg {
+            Message::Deregistered(notification)
+        } else {
+            Message::Change(notification)
+        }
+    }
+}
+
+impl<'a> From<&'a ODPISubscrMessage> for Notification {
+    fn from(message: &'a ODPISubscrMessage) -> Notification {
+        let error = if message.error_info.is_null() {
+            None
+        } else {
+            Some(ErrorInfo::from(unsafe { *message.error_info }))
+        };
+
+        Notification {
+            event_type: message.event_type,
+            db_name: ODPIStr::new(message.db_name, message.db_name_length).into(),
+            tables: unsafe { as_slice(message.tables, message.num_tables) }
+                .iter()
+                .map(TableChange::from)
+                .collect(),
+            queries: unsafe { as_slice(message.queries, message.num_queries) }
+                .iter()
+                .map(QueryChange::from)
+                .collect(),
+            error: error,
+        }
+    }
+}
+
+impl<'a> From<&'a ODPISubscrMessageQuery> for QueryChange {
+    fn from(query: &'a ODPISubscrMessageQuery) -> QueryChange {
+        QueryChange {
+            id: query.id,
+            operation: query.operation,
+            tables: unsafe { as_slice(query.tables, query.num_tables) }
+                .iter()
+                .map(TableChange::from)
+                .collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a ODPISubscrMessageRow> for RowChange {
+    fn from(row: &'a ODPISubscrMessageRow) -> RowChange {
+        RowChange {
+            operation: row.operation,
+            rowid: ODPIStr::new(row.rowid, row.rowid_length).into(),
+        }
+    }
+}
+
+impl<'a> From<&'a ODPISubscrMessageTable> for TableChange {
+    fn from(table: &'a ODPISubscrMessageTable) -> TableChange {
+        TableChange {
+            operation: table.operation,
+            name: ODPIStr::new(table.name, table.name_length).into(),
+            rows: unsafe { as_slice(table.rows, table.num_rows) }
+                .iter()
+                .map(RowChange::from)
+                .collect(),
+        }
+    }
+}
+
+/// Returns the array of ODPI-C structures as a slice, or an empty slice if the pointer is NULL.
+unsafe fn as_slice<'a, T>(ptr: *const T, len: u32) -> &'a [T] {
+    if ptr.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, len as usize)
+    }
+}
@@ -11,18 +11,281 @@
 //! `Connection::new_subscription()` and are destroyed by calling the function
 //! `Subscription::close()` or releasing the last reference by calling the function
 //! `Subscription::release()`.
+//!
+//! A subscription created without a callback, using the `Callback` protocol, can deliver its
+//! notifications on a channel instead: `Subscription::into_receiver()` returns a `Receiver` of
+//! decoded `Message` values, so no `extern "C"` callback or context pointer is needed.
+//! ODPI-C 2.0 only supports the `DbChange` namespace, so this covers continuous query and object
+//! change notifications. Support for AQ notifications is deferred: with the `odpi-3` feature the
+//! `Aq` namespace can be subscribed to, but the queue and message of an AQ notification are not
+//! decoded yet, so it arrives as a `Message::Change` carrying only its event type and database
+//! name.
+//!
+//! A subscription lapses when its timeout is reached, or after its first notification if
+//! `DPI_SUBSCR_QOS_DEREG_NFY` was requested, and a `Message::Deregistered` is then delivered.
+//! `Subscription::renew()`, or `Receiver::renew()` for a subscription delivering on a channel,
+//! subscribes again with the same parameters.
+use connection::Connection;
+use context::params::SubscrCreate;
 use error::{ErrorKind, Result};
-use odpi::externs;
-use odpi::opaque::ODPISubscr;
+use odpi::{enums, externs};
+use odpi::opaque::{ODPIConn, ODPISubscr};
+use odpi::structs::{ODPISubscrCreateParams, ODPISubscrMessage};
 use statement::Statement;
+use std::ops::Deref;
+use std::os::raw::c_void;
 use std::ptr;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
 use util::ODPIStr;
 
-/// ODPI-C Message Props wrapper.
-#[derive(Clone)]
+pub mod message;
+
+use self::message::Message;
+
+/// The channel the notifications of a subscription are sent on, shared by the subscription and its
+/// callback.
+#[doc(hidden)]
+#[derive(Debug, Default)]
+pub struct Channel {
+    /// The sending half of the channel, once `Subscription::into_receiver()` has been called.
+    sender: Mutex<Option<Sender<Message>>>,
+    /// True while the callback holds a reference to the channel, until the subscription is closed
+    /// or its last reference is released.
+    registered: AtomicBool,
+    /// The number of references to the subscription held by `Subscription` handles sharing the
+    /// channel.
+    refs: AtomicUsize,
+}
+
+impl Channel {
+    /// Sets the callback of the parameters to one sending on a new channel, unless a callback is
+    /// already set or the notifications are not sent by callback. The callback holds a reference
+    /// to the channel until `unregister()` is called. The channel counts the reference returned
+    /// when the subscription is created.
+    ///
+    /// * `subscr_create_params` - the parameters the subscription is created with.
+    pub fn install(subscr_create_params: &mut SubscrCreate) -> Option<Arc<Channel>> {
+        if subscr_create_params.get_callback().is_some() ||
+           subscr_create_params.get_protocol() != enums::ODPISubscrProtocol::Callback {
+            return None;
+        }
+
+        let channel = Arc::new(Channel::default());
+        channel.registered.store(true, Ordering::SeqCst);
+        channel.refs.store(1, Ordering::SeqCst);
+        subscr_create_params.set_callback(Some(notify));
+        subscr_create_params.set_callback_context(Arc::into_raw(channel.clone()) as *mut c_void);
+        Some(channel)
+    }
+
+    /// Drops the reference held by the callback, once no more notifications can be sent.
+    ///
+    /// * `channel` - the channel returned by `install()`.
+    pub fn unregister(channel: &Arc<Channel>) {
+        if channel.registered.swap(false, Ordering::SeqCst) {
+            drop(unsafe { Arc::from_raw(&**channel as *const Channel) });
+        }
+    }
+
+    /// Counts a reference added to the subscription.
+    fn added(&self) {
+        self.refs.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Counts a reference released from the subscription, unregistering the channel when it was
+    /// the last, as ODPI-C has then deregistered the subscription and no more notifications can
+    /// be sent.
+    ///
+    /// * `channel` - the channel returned by `install()`.
+    fn released(channel: &Arc<Channel>) {
+        if channel.refs.fetch_sub(1, Ordering::SeqCst) == 1 {
+            Channel::unregister(channel);
+        }
+    }
+}
+
+/// The receiving end of the notifications of a subscription, returned by
+/// `Subscription::into_receiver()`. It dereferences to the `std::sync::mpsc::Receiver` the
+/// messages arrive on, and closes and releases the subscription when dropped.
+pub struct Receiver {
+    /// The subscription, until it is closed.
+    subscription: Option<Subscription>,
+    /// The receiving half of the channel.
+    receiver: mpsc::Receiver<Message>,
+}
+
+impl Receiver {
+    /// Closes and releases the subscription, so no more notifications are sent. Messages already
+    /// received can still be read.
+    pub fn close(&mut self) -> Result<()> {
+        if let Some(subscription) = self.subscription.take() {
+            let res = subscription.close();
+            subscription.release()?;
+            res?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the subscription with a new one created with the same parameters, using
+    /// `Subscription::renew()`, e.g. on receiving a `Message::Deregistered`. The notifications of
+    /// the new subscription arrive on this receiver, after any not yet read. Queries registered on
+    /// the old subscription must be registered again. If the new subscription cannot be created,
+    /// the receiver is left closed. Returns the id of the new subscription.
+    ///
+    /// * `conn` - the connection the new subscription is created on.
+    pub fn renew(&mut self, conn: &Connection) -> Result<u32> {
+        let subscription = self.subscription
+            .take()
+            .ok_or_else(|| ErrorKind::Subscription("renew: receiver is closed".to_string()))?;
+        let res = subscription.renew(conn);
+        subscription.release()?;
+
+        let (id, renewed) = res?;
+        self.subscription = Some(renewed);
+        Ok(id)
+    }
+
+    /// Returns the subscription, e.g. to register queries on it, or None once it is closed.
+    pub fn subscription(&self) -> Option<&Subscription> {
+        self.subscription.as_ref()
+    }
+}
+
+impl Deref for Receiver {
+    type Target = mpsc::Receiver<Message>;
+
+    fn deref(&self) -> &mpsc::Receiver<Message> {
+        &self.receiver
+    }
+}
+
+/// Closes the subscription, so the callback stops sending once the receiver is gone.
+impl Drop for Receiver {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+/// ODPI-C Subscription wrapper.
 pub struct Subscription {
-    /// The ODPI-C MsgProps pointer.
+    /// The ODPI-C Subscr pointer.
     inner: *mut ODPISubscr,
+    /// The channel the notifications are sent on, if the subscription was created without a
+    /// callback.
+    channel: Option<Arc<Channel>>,
+    /// The parameters the subscription was created with, if it was created by
+    /// `Connection::new_subscription()`.
+    renewal: Option<Rc<Renewal>>,
+    /// The connection the subscription was created on, which ODPI-C 3 closes it with.
+    #[cfg(feature = "odpi-3")]
+    conn: Option<Rc<ConnRef>>,
+}
+
+/// A reference to the connection a subscription was created on, released when dropped.
+#[cfg(feature = "odpi-3")]
+struct ConnRef(*mut ODPIConn);
+
+#[cfg(feature = "odpi-3")]
+impl ConnRef {
+    /// Adds a reference to the given connection.
+    fn new(conn: *mut ODPIConn) -> Result<ConnRef> {
+        try_dpi!(externs::dpiConn_addRef(conn),
+                 {
+                     track_ref!(added, "Connection", conn);
+                     Ok(ConnRef(conn))
+                 },
+                 ErrorKind::Subscription("dpiConn_addRef".to_string()))
+    }
+}
+
+#[cfg(feature = "odpi-3")]
+impl Drop for ConnRef {
+    fn drop(&mut self) {
+        if unsafe { externs::dpiConn_release(self.0) } == ::odpi::constants::DPI_SUCCESS {
+            track_ref!(released, "Connection", self.0);
+        }
+    }
+}
+
+/// Creates a subscription on the given connection, returning its id and handle.
+///
+/// * `conn` - the connection the subscription is created on.
+/// * `params` - the parameters the subscription is created with.
+#[doc(hidden)]
+#[cfg(not(feature = "odpi-3"))]
+pub fn subscribe(conn: *mut ODPIConn,
+                 params: &mut ODPISubscrCreateParams)
+                 -> Result<(u32, *mut ODPISubscr)> {
+    let mut subscr = ptr::null_mut();
+    let mut subscr_id = 0;
+    try_dpi!(externs::dpiConn_newSubscription(conn, params, &mut subscr, &mut subscr_id),
+             Ok((subscr_id, subscr)),
+             ErrorKind::Connection("dpiConn_newSubscription".to_string()))
+}
+
+/// Creates a subscription on the given connection, returning its id and handle. ODPI-C 3 returns
+/// the id of the registration in the parameters.
+///
+/// * `conn` - the connection the subscription is created on.
+/// * `params` - the parameters the subscription is created with.
+#[doc(hidden)]
+#[cfg(feature = "odpi-3")]
+pub fn subscribe(conn: *mut ODPIConn,
+                 params: &mut ODPISubscrCreateParams)
+                 -> Result<(u32, *mut ODPISubscr)> {
+    let mut subscr = ptr::null_mut();
+    try_dpi!(externs::dpiConn_subscribe(conn, params, &mut subscr),
+             Ok((params.out_reg_id as u32, subscr)),
+             ErrorKind::Connection("dpiConn_subscribe".to_string()))
+}
+
+/// The parameters a subscription was created with, holding its own copies of the strings the
+/// ODPI-C structure only borrows, so the subscription can be created again by
+/// `Subscription::renew()`.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Renewal {
+    /// The parameters, without the borrowed strings.
+    params: ODPISubscrCreateParams,
+    /// The name of the subscription.
+    name: String,
+    /// The name of the recipient of the notifications.
+    recipient_name: String,
+}
+
+impl Renewal {
+    /// Copies the given parameters, which should be those given by the caller, before the callback
+    /// of a channel is installed.
+    ///
+    /// * `subscr_create_params` - the parameters the subscription is created with.
+    pub fn new(subscr_create_params: &SubscrCreate) -> Renewal {
+        let mut params = subscr_create_params.inner();
+        params.name = ptr::null();
+        params.name_length = 0;
+        params.recipient_name = ptr::null();
+        params.recipient_name_length = 0;
+
+        Renewal {
+            params: params,
+            name: subscr_create_params.get_name(),
+            recipient_name: subscr_create_params.get_recipient_name(),
+        }
+    }
+
+    /// Returns the parameters, borrowing the strings of the renewal.
+    fn params(&self) -> SubscrCreate {
+        let mut params = SubscrCreate::new(self.params);
+        if !self.name.is_empty() {
+            params.set_name(&self.name);
+        }
+        if !self.recipient_name.is_empty() {
+            params.set_recipient_name(&self.recipient_name);
+        }
+        params
+    }
 }
 
 impl Subscription {
@@ -31,21 +294,88 @@ impl Subscription {
         self.inner
     }
 
+    /// Create a `Subscription` whose notifications are sent on the given channel.
+    #[doc(hidden)]
+    pub fn with_channel(inner: *mut ODPISubscr, channel: Option<Arc<Channel>>) -> Subscription {
+        Subscription {
+            inner: inner,
+            channel: channel,
+            renewal: None,
+            #[cfg(feature = "odpi-3")]
+            conn: None,
+        }
+    }
+
+    /// Keep a reference to the connection the subscription was created on, which ODPI-C 3 needs
+    /// to close it. If the reference cannot be added, the subscription is released.
+    #[doc(hidden)]
+    #[cfg(feature = "odpi-3")]
+    pub fn with_conn(mut self, conn: *mut ODPIConn) -> Result<Subscription> {
+        match ConnRef::new(conn) {
+            Ok(conn) => {
+                self.conn = Some(Rc::new(conn));
+                Ok(self)
+            }
+            Err(e) => {
+                let _ = self.release();
+                Err(e)
+            }
+        }
+    }
+
+    /// Keep the parameters the subscription was created with, so it can be renewed.
+    #[doc(hidden)]
+    pub fn with_renewal(mut self, renewal: Renewal) -> Subscription {
+        self.renewal = Some(Rc::new(renewal));
+        self
+    }
+
     /// Adds a reference to the subscription. This is intended for situations where a reference to
     /// the subscription needs to be maintained independently of the reference returned when the
     /// subscription was created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiSubscr_addRef(self.inner),
-                 Ok(()),
+                 {
+                     if let Some(ref channel) = self.channel {
+                         channel.added();
+                     }
+                     track_ref!(added, "Subscription", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Subscription("dpiSubscr_addRef".to_string()))
     }
 
     /// Closes the subscription now, rather than when the last reference is released. This
     /// deregisters it so that notifications will no longer be sent.
     pub fn close(&self) -> Result<()> {
-        try_dpi!(externs::dpiSubscr_close(self.inner),
-                 Ok(()),
-                 ErrorKind::Subscription("dpiSubscr_close".to_string()))
+        self.unsubscribe()?;
+        if let Some(ref channel) = self.channel {
+            Channel::unregister(channel);
+        }
+        Ok(())
+    }
+
+    /// Returns a `Receiver` of the notifications sent to the subscription, decoded into `Message`
+    /// values. The subscription must have been created without a callback, using the `Callback`
+    /// protocol. The receiver owns the subscription, and closes and releases it when dropped.
+    /// Notifications sent before this is called are dropped.
+    pub fn into_receiver(self) -> Result<Receiver> {
+        let (sender, receiver) = mpsc::channel();
+        {
+            let channel = self.channel
+                .as_ref()
+                .ok_or_else(|| {
+                                let err = "into_receiver: subscription has its own callback";
+                                ErrorKind::Subscription(err.to_string())
+                            })?;
+            let mut slot = channel.sender.lock().unwrap_or_else(|e| e.into_inner());
+            *slot = Some(sender);
+        }
+
+        Ok(Receiver {
+               subscription: Some(self),
+               receiver: receiver,
+           })
     }
 
     /// Prepares a statement for registration on the subscription. The statement is then registered
@@ -56,23 +386,132 @@ impl Subscription {
         let sql_s = ODPIStr::from(sql);
 
         try_dpi!(externs::dpiSubscr_prepareStmt(self.inner, sql_s.ptr(), sql_s.len(), &mut stmt),
-                 Ok(stmt.into()),
+                 {
+                     track_ref!(created, "Statement", stmt);
+                     Ok(stmt.into())
+                 },
                  ErrorKind::Subscription("dpiSubscr_prepareStmt".to_string()))
     }
 
+    /// Closes the subscription and creates it again on the given connection, with the parameters
+    /// it was created with, e.g. on receiving a `Message::Deregistered`. ODPI-C 2.0 cannot extend
+    /// a registration, so the new subscription has a new id, and queries registered on the old one
+    /// must be registered again. Closing the old subscription ignores errors, as the database may
+    /// already have dropped it. If it delivered its notifications on a channel, the new
+    /// subscription delivers on the same channel. Only subscriptions created by
+    /// `Connection::new_subscription()` can be renewed. The reference to the old subscription must
+    /// still be released.
+    ///
+    /// * `conn` - the connection the new subscription is created on.
+    pub fn renew(&self, conn: &Connection) -> Result<(u32, Subscription)> {
+        let renewal = self.renewal
+            .as_ref()
+            .ok_or_else(|| {
+                            let err = "renew: subscription was not created on a connection";
+                            ErrorKind::Subscription(err.to_string())
+                        })?;
+        let _ = self.close();
+
+        let (id, subscription) = conn.new_subscription(renewal.params())?;
+        if let (Some(old), Some(new)) = (self.channel.as_ref(), subscription.channel.as_ref()) {
+            let sender = old.sender.lock().unwrap_or_else(|e| e.into_inner()).take();
+            *new.sender.lock().unwrap_or_else(|e| e.into_inner()) = sender;
+        }
+        Ok((id, subscription))
+    }
+
     /// Releases a reference to the subscription. A count of the references to the subscription is
     /// maintained and when this count reaches zero, the memory associated with the subscription is
     /// freed. The subscription is also deregistered so that notifications are no longer sent, if
-    /// this was not already done using the function `Subscription::close()`.
+    /// this was not already done using the function `Subscription::close()`, and the channel of a
+    /// subscription delivering on one is freed.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiSubscr_release(self.inner),
-                 Ok(()),
+                 {
+                     if let Some(ref channel) = self.channel {
+                         Channel::released(channel);
+                     }
+                     track_ref!(released, "Subscription", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Subscription("dpiSubscr_release".to_string()))
     }
+
+    /// Adds a reference to the subscription and returns a new handle to it, which must be released
+    /// separately. Unlike `clone()`, which panics if the reference cannot be added, this returns
+    /// the error.
+    pub fn try_clone(&self) -> Result<Subscription> {
+        self.add_ref()?;
+        Ok(Subscription {
+               inner: self.inner,
+               channel: self.channel.clone(),
+               renewal: self.renewal.clone(),
+               #[cfg(feature = "odpi-3")]
+               conn: self.conn.clone(),
+           })
+    }
+
+    /// Deregisters the subscription.
+    #[cfg(not(feature = "odpi-3"))]
+    fn unsubscribe(&self) -> Result<()> {
+        try_dpi!(externs::dpiSubscr_close(self.inner),
+                 Ok(()),
+                 ErrorKind::Subscription("dpiSubscr_close".to_string()))
+    }
+
+    /// Deregisters the subscription on the connection it was created on. ODPI-C 3 releases a
+    /// reference to the subscription when it is deregistered, so one is added first, leaving the
+    /// reference of the caller to `release()`.
+    #[cfg(feature = "odpi-3")]
+    fn unsubscribe(&self) -> Result<()> {
+        let conn = self.conn
+            .as_ref()
+            .ok_or_else(|| {
+                            let err = "close: subscription was not created on a connection";
+                            ErrorKind::Subscription(err.to_string())
+                        })?;
+        self.add_ref()?;
+        try_dpi!(externs::dpiConn_unsubscribe(conn.0, self.inner),
+                 {
+                     if let Some(ref channel) = self.channel {
+                         Channel::released(channel);
+                     }
+                     track_ref!(released, "Subscription", self.inner);
+                     Ok(())
+                 },
+                 {
+                     let _ = self.release();
+                     ErrorKind::Subscription("dpiConn_unsubscribe".to_string())
+                 })
+    }
+}
+
+/// Cloning adds a reference to the subscription, so the clone must be released separately. Panics
+/// if the reference cannot be added; use `try_clone()` to handle the error.
+impl Clone for Subscription {
+    fn clone(&self) -> Subscription {
+        self.try_clone().expect("Subscription::clone: dpiSubscr_addRef failed")
+    }
 }
 
 impl From<*mut ODPISubscr> for Subscription {
     fn from(inner: *mut ODPISubscr) -> Subscription {
-        Subscription { inner: inner }
+        track_ref!(wrapped, "Subscription", inner);
+        Subscription::with_channel(inner, None)
+    }
+}
+
+/// The callback installed by `Channel::install()`, run on an ODPI-C thread. Decodes the message and
+/// sends it on the channel, once `Subscription::into_receiver()` has been called.
+unsafe extern "C" fn notify(context: *mut c_void, message: *mut ODPISubscrMessage) {
+    if context.is_null() || message.is_null() {
+        return;
+    }
+
+    let channel = &*(context as *const Channel);
+    if let Ok(sender) = channel.sender.lock() {
+        if let Some(ref sender) = *sender {
+            let _ = sender.send(Message::from(&*message));
+        }
     }
 }
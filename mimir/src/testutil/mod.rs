@@ -0,0 +1,196 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Integration test harness, available with the `test-util` feature. A `Schema` creates the
+//! objects a test suite needs from SQL scripts before the tests run and drops them afterwards, so
+//! the suite can be run against any database rather than one prepared by hand. Scripts use the
+//! SQL*Plus conventions: SQL statements end with a semicolon, and PL/SQL blocks (including
+//! `create type`) end with a line containing a single slash. Connect to the test database using
+//! `ConnectParams`.
+use connection::Connection;
+use error::Result;
+use odpi::flags;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// A list of SQL statements parsed from a script.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Script {
+    /// The statements in the script, without their terminators.
+    statements: Vec<String>,
+}
+
+impl Script {
+    /// Read and parse the script at the given path.
+    ///
+    /// * `path` - the path to the SQL script.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Script> {
+        let mut sql = String::new();
+        File::open(path)?.read_to_string(&mut sql)?;
+        Ok(Script::parse(&sql))
+    }
+
+    /// Parse the given SQL script into statements. Blank lines and `--` comment lines between
+    /// statements are skipped.
+    ///
+    /// * `sql` - the text of the SQL script.
+    pub fn parse(sql: &str) -> Script {
+        let mut statements = Vec::new();
+        let mut current = String::new();
+
+        for line in sql.lines() {
+            let trimmed = line.trim();
+
+            if current.is_empty() && (trimmed.is_empty() || trimmed.starts_with("--")) {
+                continue;
+            }
+
+            if trimmed == "/" {
+                push_statement(&mut statements, &current);
+                current.clear();
+                continue;
+            }
+
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(line.trim_right());
+
+            if trimmed.ends_with(';') && !is_plsql(&current) {
+                push_statement(&mut statements, &current);
+                current.clear();
+            }
+        }
+        push_statement(&mut statements, &current);
+
+        Script { statements: statements }
+    }
+
+    /// Get the `statements` value.
+    pub fn get_statements(&self) -> &[String] {
+        &self.statements
+    }
+
+    /// Execute each statement in the script on the given connection, stopping at the first
+    /// failure. The work is committed once all of the statements have been executed.
+    ///
+    /// * `conn` - the connection to execute the statements on.
+    pub fn run(&self, conn: &Connection) -> Result<()> {
+        for statement in &self.statements {
+            execute(conn, statement)?;
+        }
+        conn.commit()
+    }
+
+    /// Execute each statement in the script on the given connection, continuing past failures.
+    /// This suits teardown scripts, where some of the objects may not exist. Returns the number of
+    /// statements that failed.
+    ///
+    /// * `conn` - the connection to execute the statements on.
+    pub fn run_ignoring_errors(&self, conn: &Connection) -> u32 {
+        let failed = self.statements
+            .iter()
+            .filter(|statement| execute(conn, statement).is_err())
+            .count();
+        let _ = conn.commit();
+        failed as u32
+    }
+}
+
+/// The schema objects required by a test suite, as a pair of setup and teardown scripts.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Schema {
+    /// The script that creates the schema objects.
+    setup: Script,
+    /// The script that drops the schema objects.
+    teardown: Script,
+}
+
+impl Schema {
+    /// Create a new `Schema` from the given scripts.
+    pub fn new(setup: Script, teardown: Script) -> Schema {
+        Schema {
+            setup: setup,
+            teardown: teardown,
+        }
+    }
+
+    /// Create a new `Schema` from the scripts at the given paths.
+    ///
+    /// * `setup` - the path to the script that creates the schema objects.
+    /// * `teardown` - the path to the script that drops the schema objects.
+    pub fn from_files<P: AsRef<Path>, Q: AsRef<Path>>(setup: P, teardown: Q) -> Result<Schema> {
+        Ok(Schema::new(Script::from_file(setup)?, Script::from_file(teardown)?))
+    }
+
+    /// Create the schema objects. Any objects left behind by an earlier run are dropped first, so
+    /// setup can be repeated.
+    ///
+    /// * `conn` - a connection to the schema the objects are created in.
+    pub fn setup(&self, conn: &Connection) -> Result<()> {
+        self.teardown.run_ignoring_errors(conn);
+        self.setup.run(conn)
+    }
+
+    /// Drop the schema objects. This is best effort, as objects may already have been dropped.
+    /// Returns the number of statements in the teardown script that failed.
+    ///
+    /// * `conn` - a connection to the schema the objects were created in.
+    pub fn teardown(&self, conn: &Connection) -> u32 {
+        self.teardown.run_ignoring_errors(conn)
+    }
+}
+
+/// Prepare, execute, and release the given statement.
+fn execute(conn: &Connection, sql: &str) -> Result<()> {
+    let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+    let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT);
+    stmt.release()?;
+    res.map(|_| ())
+}
+
+/// Does the given statement start a PL/SQL block? These are terminated by a slash rather than a
+/// semicolon, and keep their trailing semicolon.
+fn is_plsql(statement: &str) -> bool {
+    let mut words = statement.split_whitespace().map(|word| word.to_uppercase());
+
+    match words.next().as_ref().map(|word| word.as_str()) {
+        Some("BEGIN") | Some("DECLARE") => true,
+        Some("CREATE") => {
+            let mut kind = words.next();
+            if kind.as_ref().map(|word| word.as_str()) == Some("OR") {
+                // Skip over REPLACE.
+                let _ = words.next();
+                kind = words.next();
+            }
+
+            match kind.as_ref().map(|word| word.as_str()) {
+                Some("FUNCTION") | Some("PACKAGE") | Some("PROCEDURE") | Some("TRIGGER") |
+                Some("TYPE") => true,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Add the given statement to the list, removing the terminating semicolon from SQL statements.
+fn push_statement(statements: &mut Vec<String>, statement: &str) {
+    let statement = statement.trim();
+
+    if statement.is_empty() {
+        return;
+    }
+
+    if is_plsql(statement) {
+        statements.push(statement.to_string());
+    } else {
+        statements.push(statement.trim_right_matches(';').trim_right().to_string());
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Wall-clock timing of the calls made into ODPI-C. ODPI-C does not report the number of server
+//! round trips made by a call, so the time spent in each call is measured instead. The time taken
+//! to create or acquire a connection is always recorded and is available from
+//! `Connection::get_connect_time()`. Timing of the calls made on a connection, and on the
+//! statements prepared on it, is enabled with `Connection::set_timing()`; the accumulated timings
+//! are then available from `Connection::get_timings()`, keyed by the name of the ODPI-C function.
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// The accumulated wall-clock time of the calls made for one operation.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Timing {
+    /// The number of calls made.
+    calls: u32,
+    /// The total time spent in the calls.
+    total: Duration,
+    /// The time spent in the slowest call.
+    max: Duration,
+}
+
+impl Timing {
+    /// Get the `calls` value.
+    pub fn get_calls(&self) -> u32 {
+        self.calls
+    }
+
+    /// Get the `max` value.
+    pub fn get_max(&self) -> Duration {
+        self.max
+    }
+
+    /// Get the mean time spent in a call.
+    pub fn get_mean(&self) -> Duration {
+        if self.calls == 0 {
+            Duration::default()
+        } else {
+            self.total / self.calls
+        }
+    }
+
+    /// Get the `total` value.
+    pub fn get_total(&self) -> Duration {
+        self.total
+    }
+
+    /// Add a call that took the given time.
+    pub fn record(&mut self, elapsed: Duration) {
+        self.calls = self.calls.saturating_add(1);
+        self.total += elapsed;
+        if elapsed > self.max {
+            self.max = elapsed;
+        }
+    }
+}
+
+/// The timings of the calls made on a connection, keyed by ODPI-C function name.
+#[derive(Debug, Default)]
+pub struct Timings {
+    /// The timing of each operation.
+    ops: RefCell<BTreeMap<&'static str, Timing>>,
+}
+
+impl Timings {
+    /// Run the given closure, adding the time it took to the timing of the given operation.
+    pub fn time<T, F>(&self, op: &'static str, f: F) -> T
+        where F: FnOnce() -> T
+    {
+        let start = Instant::now();
+        let res = f();
+        self.ops
+            .borrow_mut()
+            .entry(op)
+            .or_insert_with(Timing::default)
+            .record(start.elapsed());
+        res
+    }
+
+    /// Returns the timings recorded so far.
+    pub fn timings(&self) -> BTreeMap<&'static str, Timing> {
+        self.ops.borrow().clone()
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Transaction guards are created by calling `Connection::begin_transaction()`. A transaction that
+//! is dropped without being committed is rolled back. Savepoints can be created within a
+//! transaction by calling `savepoint()`, and the transaction can be partially rolled back to a
+//! savepoint by calling `Savepoint::rollback_to()`. Read-only and serializable transactions are
+//! started by calling `Connection::begin_transaction_with()`.
+use connection::Connection;
+use error::{ErrorKind, Result};
+use odpi::flags;
+use std::cell::Cell;
+use std::ops::Deref;
+
+/// The maximum length of a savepoint name, in bytes.
+const MAX_SAVEPOINT_NAME_LEN: usize = 128;
+
+/// The isolation level of a transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Isolation {
+    /// Each query sees only data committed before the query began. This is the Oracle default.
+    ReadCommitted,
+    /// Each query sees only data committed before the transaction began, along with changes made
+    /// by the transaction itself.
+    Serializable,
+}
+
+impl Default for Isolation {
+    fn default() -> Isolation {
+        Isolation::ReadCommitted
+    }
+}
+
+/// Options used when beginning a transaction with `Connection::begin_transaction_with()`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TxnOptions {
+    /// The isolation level of the transaction. This is ignored for read-only transactions, which
+    /// always see data as of the start of the transaction.
+    pub isolation: Isolation,
+    /// Should the transaction be read-only?
+    pub read_only: bool,
+}
+
+impl TxnOptions {
+    /// The SET TRANSACTION statement that starts a transaction with these options.
+    fn set_transaction_sql(&self) -> &'static str {
+        if self.read_only {
+            "SET TRANSACTION READ ONLY"
+        } else {
+            match self.isolation {
+                Isolation::ReadCommitted => "SET TRANSACTION ISOLATION LEVEL READ COMMITTED",
+                Isolation::Serializable => "SET TRANSACTION ISOLATION LEVEL SERIALIZABLE",
+            }
+        }
+    }
+}
+
+/// A transaction on a connection.
+pub struct Transaction<'a> {
+    /// The connection the transaction is active on.
+    conn: &'a Connection,
+    /// The options the transaction was started with.
+    options: TxnOptions,
+    /// Has the transaction been committed or rolled back?
+    finished: Cell<bool>,
+}
+
+impl<'a> Transaction<'a> {
+    /// Create a new `Transaction` guard on the given connection.
+    #[doc(hidden)]
+    pub fn new(conn: &'a Connection, options: TxnOptions) -> Transaction<'a> {
+        Transaction {
+            conn: conn,
+            options: options,
+            finished: Cell::new(false),
+        }
+    }
+
+    /// Start a new transaction on the given connection by issuing the SET TRANSACTION statement
+    /// for the given options.
+    #[doc(hidden)]
+    pub fn begin(conn: &'a Connection, options: TxnOptions) -> Result<Transaction<'a>> {
+        execute(conn, options.set_transaction_sql())?;
+        Ok(Transaction::new(conn, options))
+    }
+
+    /// Commits the transaction.
+    pub fn commit(self) -> Result<()> {
+        self.finished.set(true);
+        self.conn.commit()
+    }
+
+    /// Get the `options` value.
+    pub fn get_options(&self) -> TxnOptions {
+        self.options
+    }
+
+    /// Rolls back the transaction.
+    pub fn rollback(self) -> Result<()> {
+        self.finished.set(true);
+        self.conn.rollback()
+    }
+
+    /// Creates a savepoint within the transaction. The savepoint is released when the transaction
+    /// is committed or rolled back. Dropping the returned `Savepoint` without calling
+    /// `rollback_to()` keeps all work done since it was created.
+    ///
+    /// * `name` - the name of the savepoint. This must be a valid, unquoted Oracle identifier.
+    /// Creating a savepoint with the same name as an existing savepoint in the transaction moves
+    /// the existing savepoint.
+    pub fn savepoint(&self, name: &str) -> Result<Savepoint> {
+        validate_savepoint_name(name)?;
+        execute(self.conn, &format!("SAVEPOINT {}", name))?;
+        Ok(Savepoint {
+               conn: self.conn,
+               name: name.to_string(),
+           })
+    }
+}
+
+impl<'a> Deref for Transaction<'a> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn
+    }
+}
+
+impl<'a> Drop for Transaction<'a> {
+    fn drop(&mut self) {
+        if !self.finished.get() {
+            let _ = self.conn.rollback();
+        }
+    }
+}
+
+/// A savepoint within a transaction.
+pub struct Savepoint<'t> {
+    /// The connection the transaction is active on.
+    conn: &'t Connection,
+    /// The savepoint name.
+    name: String,
+}
+
+impl<'t> Savepoint<'t> {
+    /// Get the `name` value.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Rolls back all work done in the transaction since the savepoint was created. The
+    /// transaction itself remains active.
+    pub fn rollback_to(self) -> Result<()> {
+        execute(self.conn, &format!("ROLLBACK TO SAVEPOINT {}", self.name))
+    }
+}
+
+/// Prepare, execute, and release the given SQL statement.
+fn execute(conn: &Connection, sql: &str) -> Result<()> {
+    let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+    let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT);
+    stmt.release()?;
+    res.map(|_| ())
+}
+
+/// Savepoint names are interpolated into SQL, so only simple identifiers are accepted.
+fn validate_savepoint_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let starts_alpha = chars.next().map_or(false, |c| c.is_ascii_alphabetic());
+    let rest_valid = chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '#');
+
+    if starts_alpha && rest_valid && name.len() <= MAX_SAVEPOINT_NAME_LEN {
+        Ok(())
+    } else {
+        Err(ErrorKind::Transaction(format!("invalid savepoint name '{}'", name)).into())
+    }
+}
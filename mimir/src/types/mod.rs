@@ -0,0 +1,163 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The Rust types that values of each Oracle type are read as. This is the mapping the driver
+//! itself uses to pick the native type of variables, exposed so code generators, e.g. ones
+//! generating structs from `ALL_TAB_COLUMNS`, agree with it.
+//!
+//! | Oracle type                                  | Native type  | Rust type                 |
+//! |----------------------------------------------|--------------|---------------------------|
+//! | VARCHAR2, NVARCHAR2, CHAR, NCHAR, LONG       | `Bytes`      | `String`                  |
+//! | CLOB, NCLOB                                  | `Lob`        | `Lob`                     |
+//! | RAW, LONG RAW                                | `Bytes`      | `Vec<u8>`                 |
+//! | BLOB, BFILE                                  | `Lob`        | `Lob`                     |
+//! | NUMBER(p, 0) with p up to 18, PLS_INTEGER    | `Int64`      | `i64`                     |
+//! | other NUMBER, FLOAT, BINARY_DOUBLE           | `Double`     | `f64`                     |
+//! | BINARY_FLOAT                                 | `Float`      | `f32`                     |
+//! | DATE, TIMESTAMP, TIMESTAMP WITH LOCAL TZ     | `Timestamp`  | `DateTime<Utc>`           |
+//! | TIMESTAMP WITH TIME ZONE                     | `Timestamp`  | `DateTime<FixedOffset>`   |
+//! | INTERVAL DAY TO SECOND                       | `IntervalDS` | `chrono::Duration`        |
+//! | INTERVAL YEAR TO MONTH                       | `IntervalYM` | `YearsMonths`             |
+//! | ROWID                                        | `Rowid`      | `Rowid`                   |
+//! | PL/SQL BOOLEAN                               | `Boolean`    | `bool`                    |
+//! | REF CURSOR                                   | `Stmt`       | `Statement`               |
+//! | named types                                  | `Object`     | `Object`                  |
+use odpi::enums::{ODPINativeTypeNum, ODPIOracleTypeNum};
+use std::fmt;
+
+/// The most decimal digits of an integral NUMBER read as an `i64`.
+const INT64_DIGITS: i16 = 18;
+
+/// The Rust type values of an Oracle type are read as.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RustType {
+    /// `bool`, read with `Data::get_boolean()`.
+    Bool,
+    /// `Vec<u8>`, fetched into a `Vec<Vec<u8>>` buffer.
+    Bytes,
+    /// `chrono::DateTime<FixedOffset>`, read with `Data::get_datetime()`.
+    DateTime,
+    /// `chrono::DateTime<Utc>`, read with `Data::get_utc()`.
+    DateTimeUtc,
+    /// `chrono::Duration`, read with `Data::get_duration()`.
+    Duration,
+    /// `f32`, read with `Data::get_float()`.
+    F32,
+    /// `f64`, read with `Data::get_double()`.
+    F64,
+    /// `i64`, read with `Data::get_int64()`.
+    I64,
+    /// `Lob`, read with `Data::get_lob()`.
+    Lob,
+    /// `Object`, read with `Data::get_object()`.
+    Object,
+    /// `Rowid`.
+    Rowid,
+    /// `Statement`, read with `Data::get_stmt()`.
+    Statement,
+    /// `String`, read with `Data::get_string()`.
+    String,
+    /// `u64`, read with `Data::get_uint64()`.
+    U64,
+    /// `YearsMonths`, read with `Data::get_years_months()`.
+    YearsMonths,
+}
+
+impl RustType {
+    /// Returns the native type values are transferred as.
+    pub fn native_type_num(&self) -> ODPINativeTypeNum {
+        match *self {
+            RustType::Bool => ODPINativeTypeNum::Boolean,
+            RustType::Bytes | RustType::String => ODPINativeTypeNum::Bytes,
+            RustType::DateTime | RustType::DateTimeUtc => ODPINativeTypeNum::Timestamp,
+            RustType::Duration => ODPINativeTypeNum::IntervalDS,
+            RustType::F32 => ODPINativeTypeNum::Float,
+            RustType::F64 => ODPINativeTypeNum::Double,
+            RustType::I64 => ODPINativeTypeNum::Int64,
+            RustType::Lob => ODPINativeTypeNum::Lob,
+            RustType::Object => ODPINativeTypeNum::Object,
+            RustType::Rowid => ODPINativeTypeNum::Rowid,
+            RustType::Statement => ODPINativeTypeNum::Stmt,
+            RustType::U64 => ODPINativeTypeNum::Uint64,
+            RustType::YearsMonths => ODPINativeTypeNum::IntervalYM,
+        }
+    }
+}
+
+impl fmt::Display for RustType {
+    /// Writes the path of the type, as used in generated code.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = match *self {
+            RustType::Bool => "bool",
+            RustType::Bytes => "Vec<u8>",
+            RustType::DateTime => "::chrono::DateTime<::chrono::FixedOffset>",
+            RustType::DateTimeUtc => "::chrono::DateTime<::chrono::Utc>",
+            RustType::Duration => "::chrono::Duration",
+            RustType::F32 => "f32",
+            RustType::F64 => "f64",
+            RustType::I64 => "i64",
+            RustType::Lob => "::mimir::Lob",
+            RustType::Object => "::mimir::Object",
+            RustType::Rowid => "::mimir::Rowid",
+            RustType::Statement => "::mimir::Statement",
+            RustType::String => "String",
+            RustType::U64 => "u64",
+            RustType::YearsMonths => "::mimir::YearsMonths",
+        };
+        write!(f, "{}", path)
+    }
+}
+
+/// Returns the Rust type values of the given Oracle type are read as, or None for `TypeNone` and
+/// `Max`, which do not describe a database type.
+///
+/// * `oracle_type` - the ODPI-C Oracle type.
+/// * `precision` - the precision of NUMBER. It is ignored for other types.
+/// * `scale` - the scale of NUMBER. It is ignored for other types.
+pub fn rust_type_for(oracle_type: ODPIOracleTypeNum,
+                     precision: i16,
+                     scale: i8)
+                     -> Option<RustType> {
+    let rust_type = match oracle_type {
+        ODPIOracleTypeNum::Varchar |
+        ODPIOracleTypeNum::NVarchar |
+        ODPIOracleTypeNum::Char |
+        ODPIOracleTypeNum::NChar |
+        ODPIOracleTypeNum::LongVarchar => RustType::String,
+        ODPIOracleTypeNum::Raw | ODPIOracleTypeNum::LongRaw => RustType::Bytes,
+        ODPIOracleTypeNum::RowID => RustType::Rowid,
+        ODPIOracleTypeNum::NativeFloat => RustType::F32,
+        ODPIOracleTypeNum::NativeDouble => RustType::F64,
+        ODPIOracleTypeNum::NativeInt => RustType::I64,
+        ODPIOracleTypeNum::NativeUint => RustType::U64,
+        ODPIOracleTypeNum::Number => {
+            if scale == 0 && precision > 0 && precision <= INT64_DIGITS {
+                RustType::I64
+            } else {
+                RustType::F64
+            }
+        }
+        ODPIOracleTypeNum::Date |
+        ODPIOracleTypeNum::Timestamp |
+        ODPIOracleTypeNum::TimestampLtz => RustType::DateTimeUtc,
+        ODPIOracleTypeNum::TimestampTz => RustType::DateTime,
+        ODPIOracleTypeNum::IntervalDS => RustType::Duration,
+        ODPIOracleTypeNum::IntervalYM => RustType::YearsMonths,
+        ODPIOracleTypeNum::Clob |
+        ODPIOracleTypeNum::NClob |
+        ODPIOracleTypeNum::Blob |
+        ODPIOracleTypeNum::BFile => RustType::Lob,
+        ODPIOracleTypeNum::Stmt => RustType::Statement,
+        ODPIOracleTypeNum::Boolean => RustType::Bool,
+        ODPIOracleTypeNum::Object => RustType::Object,
+        ODPIOracleTypeNum::TypeNone | ODPIOracleTypeNum::Max => return None,
+    };
+
+    Some(rust_type)
+}
+
@@ -10,6 +10,7 @@
 use std::os::raw::c_char;
 use std::ptr;
 use std::slice;
+use std::time::Duration;
 
 /// Holds a pointer and a length for ODPI-C strings.
 #[derive(Clone, Copy, Debug)]
@@ -92,3 +93,8 @@ impl From<ODPIStr> for String {
         }
     }
 }
+
+/// Convert the given duration to whole milliseconds.
+pub fn duration_millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000 + u64::from(duration.subsec_nanos() / 1_000_000)
+}
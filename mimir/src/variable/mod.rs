@@ -12,15 +12,20 @@
 //! are bound to statements by calling the function `Statement::bindByName()` or the function
 //! `Statement::bindByPos()`. They can also be used for fetching data from the database by calling
 //! the function `Statement::define()`.
+use common::encoding;
+use data::Data;
 use error::{ErrorKind, Result};
 use lob::Lob;
 use object::Object;
-use odpi::externs;
+use odpi::{enums, externs};
 use odpi::opaque::ODPIVar;
 use odpi::structs::ODPIData;
 use rowid::Rowid;
 use statement::Statement;
 use std::{ptr, slice};
+use std::cell::Cell;
+use std::ops::Range;
+use std::rc::Rc;
 use util::ODPIStr;
 
 /// This structure represents memory areas used for transferring data to and from the database and
@@ -28,9 +33,35 @@ use util::ODPIStr;
 pub struct Var {
     /// The ODPI-C var
     inner: *mut ODPIVar,
+    /// The ODPI-C data array backing the var, shared with the statements the var is bound to.
+    array: Rc<DataArray>,
+    /// The encoding of the connection the variable was created on, and its Oracle type, if known.
+    encoding: Option<(Rc<encoding::Info>, enums::ODPIOracleTypeNum)>,
 }
 
 impl Var {
+    /// Create a new `Var` from an `ODPIVar` pointer and the data array allocated for it.
+    #[doc(hidden)]
+    pub fn new(inner: *mut ODPIVar, data: *mut ODPIData, num_elements: u32) -> Var {
+        Var {
+            inner: inner,
+            array: Rc::new(DataArray {
+                               data: Cell::new(data),
+                               num_elements: Cell::new(num_elements),
+                           }),
+            encoding: None,
+        }
+    }
+
+    /// Set the encoding of the connection the variable was created on, and its Oracle type, so
+    /// sizes can be converted between bytes and characters.
+    #[doc(hidden)]
+    pub fn set_encoding(&mut self,
+                        encoding: Rc<encoding::Info>,
+                        oracle_type_num: enums::ODPIOracleTypeNum) {
+        self.encoding = Some((encoding, oracle_type_num));
+    }
+
     /// Get the `inner` value.
     #[doc(hidden)]
     pub fn inner(&self) -> *mut ODPIVar {
@@ -42,7 +73,10 @@ impl Var {
     /// was created.
     pub fn add_ref(&self) -> Result<()> {
         try_dpi!(externs::dpiVar_addRef(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(added, "Var", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Var("dpiVar_addRef".to_string()))
     }
 
@@ -61,26 +95,54 @@ impl Var {
                  ErrorKind::Var("dpiVar_copyData".to_string()))
     }
 
-    /// Returns a pointer to an array of `ODPIData` structures used for transferring data to and
-    /// from the database. These structures are allocated by the variable itself and are made
-    /// available when the variable is first created using the function `Connection::new_var()`. If
-    /// a DML returning statement is executed, however, the number of allocated elements can change
-    /// in addition to the memory location.
-    pub fn get_data(&self) -> Result<&mut [ODPIData]> {
-        let mut num_elements = 0;
-        let mut data_arr_ptr = ptr::null_mut();
+    /// Copies a range of elements from one variable to another. ODPI-C 2.0 only copies one element
+    /// at a time, so this makes one `copy_data()` call per element; its benefit is that both
+    /// ranges are checked against the elements allocated in the variables before anything is
+    /// copied, and `ErrorKind::VarRange` is returned if either does not fit.
+    ///
+    /// * `src_range` - the array positions from which the data is to be copied. The first position
+    /// is 0.
+    /// * `dst` - the variable into which data is to be copied.
+    /// * `dst_offset` - the array position into which the first element is to be copied.
+    pub fn copy_range(&self, src_range: Range<u32>, dst: &mut Var, dst_offset: u32) -> Result<()> {
+        let src_len = unsafe { data(self)? }.len() as u32;
+        let dst_len = unsafe { data(dst)? }.len() as u32;
+        let count = src_range.end.saturating_sub(src_range.start);
 
-        try_dpi!(externs::dpiVar_getData(self.inner, &mut num_elements, &mut data_arr_ptr),
-                 {
-                     if data_arr_ptr.is_null() || num_elements == 0 {
-                         Ok(&mut [])
-                     } else {
-                         Ok(unsafe {
-                                slice::from_raw_parts_mut(data_arr_ptr, num_elements as usize)
-                            })
-                     }
-                 },
-                 ErrorKind::Var("dpiVar_getData".to_string()))
+        if src_range.start > src_range.end || src_range.end > src_len {
+            let msg = format!("copy_range: source range {:?} exceeds {} elements",
+                              src_range,
+                              src_len);
+            return Err(ErrorKind::VarRange(msg).into());
+        }
+        if dst_offset.checked_add(count).map_or(true, |end| end > dst_len) {
+            let msg = format!("copy_range: {} elements at {} exceed {} elements",
+                              count,
+                              dst_offset,
+                              dst_len);
+            return Err(ErrorKind::VarRange(msg).into());
+        }
+
+        for (src_pos, dst_pos) in src_range.zip(dst_offset..) {
+            self.copy_data(src_pos, dst, dst_pos)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the value of the element at the given array position. The value refers to the
+    /// variable's own buffer, so it reflects later changes to the variable and cannot outlive it.
+    ///
+    /// * `pos` - the array position of the element. The first position is 0. If the position
+    /// exceeds the number of elements allocated in the variable, `ErrorKind::VarRange` is returned.
+    pub fn get_value<'a>(&'a self, pos: u32) -> Result<Data<'a>> {
+        let data = unsafe { self::data(self)? };
+        match data.get_mut(pos as usize) {
+            Some(elem) => Ok(unsafe { ::data::from_ptr(elem) }),
+            None => {
+                let msg = format!("get_value: position {} exceeds {} elements", pos, data.len());
+                Err(ErrorKind::VarRange(msg).into())
+            }
+        }
     }
 
     /// Returns the number of elements in a PL/SQL index-by table if the variable was created as an
@@ -104,12 +166,47 @@ impl Var {
                  ErrorKind::Var("dpiVar_getSizeInBytes".to_string()))
     }
 
+    /// Returns the number of characters that are certain to fit in the buffer used for one element
+    /// of the array, given the maximum number of bytes per character of the connection's encoding.
+    /// For types that are not character data, this is the size in bytes. Returns an error if the
+    /// variable was not created by `Connection::new_var()`, as the encoding is then unknown.
+    pub fn get_size_in_chars(&self) -> Result<u32> {
+        let size_in_bytes = self.get_size_in_bytes()?;
+        let (encoding, oracle_type_num) =
+            self.encoding
+                .as_ref()
+                .map(|&(ref encoding, oracle_type_num)| (encoding, oracle_type_num))
+                .ok_or_else(|| {
+                                let err = "get_size_in_chars: the encoding is unknown";
+                                ErrorKind::Var(err.to_string())
+                            })?;
+        Ok(encoding.chars_for_bytes(oracle_type_num, size_in_bytes))
+    }
+
+    /// Returns the encoding information of the connection the variable was created on, or None if
+    /// the variable was not created by `Connection::new_var()`.
+    pub fn get_encoding_info(&self) -> Option<&encoding::Info> {
+        self.encoding.as_ref().map(|&(ref encoding, _)| &**encoding)
+    }
+
+    /// Re-reads the data array from ODPI-C and caches it in the variable. The number of allocated
+    /// elements and the memory location of the array can change when a DML returning statement is
+    /// executed, so `Statement::execute()` marks the cached array of each variable bound to one as
+    /// stale, and it is re-read when next used.
+    pub fn refresh_data(&self) -> Result<&[ODPIData]> {
+        self.array.refresh(self.inner)?;
+        Ok(unsafe { self.array.as_slice() })
+    }
+
     /// Releases a reference to the variable. A count of the references to the variable is
     /// maintained and when this count reaches zero, the memory associated with the variable is
     /// freed.
     pub fn release(&self) -> Result<()> {
         try_dpi!(externs::dpiVar_release(self.inner),
-                 Ok(()),
+                 {
+                     track_ref!(released, "Var", self.inner);
+                     Ok(())
+                 },
                  ErrorKind::Var("dpiVar_release".to_string()))
     }
 
@@ -185,6 +282,69 @@ impl Var {
 
 impl From<*mut ODPIVar> for Var {
     fn from(inner: *mut ODPIVar) -> Var {
-        Var { inner: inner }
+        track_ref!(wrapped, "Var", inner);
+        Var::new(inner, ptr::null_mut(), 0)
+    }
+}
+
+/// Returns the array of `ODPIData` structures used for transferring data to and from the database.
+/// See `raw::var_data()`. These structures are allocated by the variable itself and are made
+/// available when the variable is first created using the function `Connection::new_var()`, so no
+/// call into ODPI-C is needed here. If a DML returning statement is executed, however, the number
+/// of allocated elements can change in addition to the memory location, so `Statement::execute()`
+/// marks the cached array of each bound variable as stale and it is re-read here.
+///
+/// The caller must ensure that the array is not used once the variable has been released or its
+/// data refreshed, and that it is not aliased by another mutable reference.
+pub unsafe fn data(var: &Var) -> Result<&mut [ODPIData]> {
+    if var.array.data.get().is_null() {
+        var.array.refresh(var.inner)?;
+    }
+    Ok(var.array.as_slice())
+}
+
+/// Returns the data array of the given variable, to be shared with a statement it is bound to.
+pub fn array(var: &Var) -> Rc<DataArray> {
+    var.array.clone()
+}
+
+/// The cached location and length of the ODPI-C data array backing a variable.
+pub struct DataArray {
+    /// The ODPI-C data array, or null if it must be re-read before use.
+    data: Cell<*mut ODPIData>,
+    /// The number of elements in the data array.
+    num_elements: Cell<u32>,
+}
+
+impl DataArray {
+    /// Marks the cached array as stale, so it is re-read from ODPI-C on its next use.
+    pub fn invalidate(&self) {
+        self.data.set(ptr::null_mut());
+        self.num_elements.set(0);
+    }
+
+    /// Re-reads the data array of the given variable from ODPI-C.
+    fn refresh(&self, var: *mut ODPIVar) -> Result<()> {
+        let mut num_elements = 0;
+        let mut data_arr_ptr = ptr::null_mut();
+
+        try_dpi!(externs::dpiVar_getData(var, &mut num_elements, &mut data_arr_ptr),
+                 {
+                     self.data.set(data_arr_ptr);
+                     self.num_elements.set(num_elements);
+                     Ok(())
+                 },
+                 ErrorKind::Var("dpiVar_getData".to_string()))
+    }
+
+    /// Returns the cached array, which is empty if it has not been read.
+    unsafe fn as_slice<'a>(&self) -> &'a mut [ODPIData] {
+        let data_arr_ptr = self.data.get();
+
+        if data_arr_ptr.is_null() || self.num_elements.get() == 0 {
+            &mut []
+        } else {
+            slice::from_raw_parts_mut(data_arr_ptr, self.num_elements.get() as usize)
+        }
     }
 }
@@ -0,0 +1,45 @@
+// Copyright (c) 2017 mimir developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Reading and writing XMLTYPE columns as text. ODPI-C 2.0 reports an XMLTYPE column as a named
+//! type column of the opaque type `SYS.XMLTYPE`, which it cannot define or fetch, so the value is
+//! converted in SQL instead: it is selected as a CLOB with `select_expr()`, and written from a
+//! string bind wrapped in `bind_expr()`. `ColumnInfo::is_xml()` tells which columns of a query
+//! need the conversion.
+//!
+//! The CLOB is read through its locator, or fetched inline as text by defining the column with
+//! `Statement::define_inline()`. A string bind is limited to 32767 bytes; larger documents are
+//! bound as a CLOB with `Statement::bind_lob_by_name()`.
+//!
+//! ```ignore
+//! let sql = format!("select id, {} from docs", xml::select_expr("doc")?);
+//! let insert = format!("insert into docs (id, doc) values (:id, {})", xml::bind_expr("doc")?);
+//! ```
+use error::Result;
+use sql;
+
+/// Returns a select list expression reading the given XMLTYPE column as a CLOB, aliased to the
+/// name of the column, e.g. `XMLSERIALIZE(CONTENT D.DOC AS CLOB) AS DOC` for `d.doc`.
+///
+/// * `column` - the name of the column, optionally qualified with a table name or alias. Each part
+/// is validated with `sql::check_identifier()`.
+pub fn select_expr(column: &str) -> Result<String> {
+    let column = sql::check_qualified_name(column)?;
+    let alias = column.rsplit('.').next().unwrap_or(&column).to_string();
+    Ok(format!("XMLSERIALIZE(CONTENT {} AS CLOB) AS {}", column, alias))
+}
+
+/// Returns an expression converting the named bind variable, holding the text of a document, to
+/// an XMLTYPE, e.g. `XMLTYPE(:DOC)` for `doc`. Use it as the value of an XMLTYPE column in an
+/// INSERT or UPDATE.
+///
+/// * `name` - the name of the bind variable, without the leading `:`. It is validated with
+/// `sql::check_identifier()`.
+pub fn bind_expr(name: &str) -> Result<String> {
+    Ok(format!("XMLTYPE(:{})", sql::check_identifier(name)?))
+}
@@ -0,0 +1,63 @@
+use CONFIG;
+use mimir::{Context, OracleType};
+use mimir::catalog::{Catalog, column_type};
+use mimir::error::Result;
+use mimir::flags;
+
+#[test]
+fn column_types() {
+    assert_eq!(column_type("VARCHAR2", 20, 80, None, None),
+               Some(OracleType::Varchar2(20)));
+    assert_eq!(column_type("RAW", 0, 16, None, None), Some(OracleType::Raw(16)));
+    assert_eq!(column_type("NUMBER", 0, 22, Some(10), Some(2)),
+               Some(OracleType::Number(10, 2)));
+    assert_eq!(column_type("NUMBER", 0, 22, None, Some(0)),
+               Some(OracleType::Number(38, 0)));
+    assert_eq!(column_type("NUMBER", 0, 22, None, None), Some(OracleType::Number(0, 0)));
+    assert_eq!(column_type("FLOAT", 0, 22, Some(63), None),
+               Some(OracleType::Number(63, -127)));
+    assert_eq!(column_type("TIMESTAMP(3) WITH TIME ZONE", 0, 13, None, Some(3)),
+               Some(OracleType::TimestampTz(3)));
+    assert_eq!(column_type("INTERVAL DAY(2) TO SECOND(6)", 0, 11, Some(2), Some(6)),
+               Some(OracleType::IntervalDS));
+    assert_eq!(column_type("DATE", 0, 7, None, None), Some(OracleType::Date));
+    assert!(column_type("UDT_OBJECT", 0, 0, None, None).is_none());
+}
+
+fn catalog_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    conn.run_script("create table mimir_catalog (id number(10) not null, \
+                     code varchar2(8), created timestamp, \
+                     constraint mimir_catalog_pk primary key (code, id))")?;
+
+    let catalog = Catalog::new(&conn);
+    let tables = catalog.tables()?;
+    assert!(tables.iter().any(|table| table.name() == "MIMIR_CATALOG"));
+
+    let columns = catalog.columns("mimir_catalog")?;
+    let names: Vec<&str> = columns.iter().map(|column| column.name()).collect();
+    assert_eq!(names, vec!["ID", "CODE", "CREATED"]);
+    assert_eq!(columns[0].position(), 1);
+    assert_eq!(columns[0].oracle_type(), Some(OracleType::Number(10, 0)));
+    assert!(!columns[0].nullable());
+    assert_eq!(columns[1].oracle_type(), Some(OracleType::Varchar2(8)));
+    assert!(columns[1].nullable());
+    assert_eq!(columns[2].data_type(), "TIMESTAMP(6)");
+
+    let key = catalog.primary_key("MIMIR_CATALOG")?.expect("badness");
+    assert_eq!(key.name(), "MIMIR_CATALOG_PK");
+    assert_eq!(key.columns(), &["CODE".to_string(), "ID".to_string()]);
+    assert!(catalog.primary_key("no_such_table")?.is_none());
+    assert!(catalog.columns("no_such_table")?.is_empty());
+
+    conn.run_script("drop table mimir_catalog")?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn catalog() {
+    check_with_ctxt!(catalog_res)
+}
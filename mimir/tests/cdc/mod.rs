@@ -0,0 +1,61 @@
+use mimir::cdc::{Operation, Snapshot};
+use mimir::{RowEvent, flags};
+
+fn key(value: &str) -> Vec<String> {
+    vec![value.to_string()]
+}
+
+#[test]
+fn apply() {
+    let mut snapshot = Snapshot::new(vec![("A".to_string(), key("1")),
+                                          ("B".to_string(), key("2"))]);
+
+    // An insert committed before the snapshot was taken is already in it.
+    assert!(snapshot
+                .apply("A", flags::DPI_OPCODE_INSERT, Some(key("1")))
+                .is_empty());
+    assert_eq!(snapshot.apply("A", flags::DPI_OPCODE_UPDATE, Some(key("1"))),
+               vec![RowEvent::new(Operation::Update, "A", key("1"))]);
+    assert_eq!(snapshot.apply("B", flags::DPI_OPCODE_UPDATE, Some(key("3"))),
+               vec![RowEvent::new(Operation::Delete, "B", key("2")),
+                    RowEvent::new(Operation::Insert, "B", key("3"))]);
+    assert_eq!(snapshot.get("B"), Some(&key("3")[..]));
+
+    let events = snapshot.apply("C", flags::DPI_OPCODE_INSERT, Some(key("4")));
+    assert_eq!(events, vec![RowEvent::new(Operation::Insert, "C", key("4"))]);
+    assert_eq!(events[0].operation(), Operation::Insert);
+    assert_eq!(events[0].rowid(), "C");
+    assert_eq!(events[0].key(), &key("4")[..]);
+
+    assert_eq!(snapshot.apply("A", flags::DPI_OPCODE_DELETE, None),
+               vec![RowEvent::new(Operation::Delete, "A", key("1"))]);
+    assert!(snapshot.get("A").is_none());
+
+    // A row inserted and deleted before its insert was read again.
+    assert!(snapshot
+                .apply("D", flags::DPI_OPCODE_INSERT, None)
+                .is_empty());
+    assert_eq!(snapshot.len(), 2);
+}
+
+#[test]
+fn refresh() {
+    let mut snapshot = Snapshot::new(vec![("A".to_string(), key("1")),
+                                          ("B".to_string(), key("2")),
+                                          ("C".to_string(), key("3"))]);
+
+    let events = snapshot.refresh(vec![("B".to_string(), key("2")),
+                                       ("C".to_string(), key("5")),
+                                       ("D".to_string(), key("4"))]);
+    assert_eq!(events,
+               vec![RowEvent::new(Operation::Delete, "A", key("1")),
+                    RowEvent::new(Operation::Update, "B", key("2")),
+                    RowEvent::new(Operation::Delete, "C", key("3")),
+                    RowEvent::new(Operation::Insert, "C", key("5")),
+                    RowEvent::new(Operation::Insert, "D", key("4"))]);
+    assert_eq!(snapshot.rows(),
+               vec![("B", &key("2")[..]), ("C", &key("5")[..]), ("D", &key("4")[..])]);
+
+    assert_eq!(snapshot.refresh(Vec::new()).len(), 3);
+    assert!(snapshot.is_empty());
+}
@@ -0,0 +1,141 @@
+use mimir::OracleType;
+use mimir::catalog::Column;
+use mimir::codegen::write_struct;
+
+#[test]
+fn fetchable_struct() {
+    let columns = [Column::new(1, "ORDER_ID", "NUMBER", Some(OracleType::Number(10, 0)), false),
+                   Column::new(2, "TYPE", "VARCHAR2", Some(OracleType::Varchar2(20)), true),
+                   Column::new(3, "NOTES", "CLOB", Some(OracleType::Clob), true)];
+    let mut out = String::new();
+    write_struct("order_lines", &columns, &mut out);
+
+    assert_eq!(out,
+               "/// A row of the `ORDER_LINES` table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderLines {
+    /// `ORDER_ID NUMBER(10,0) NOT NULL`
+    pub order_id: i64,
+    /// `TYPE VARCHAR2(20)`
+    pub type_: Option<String>,
+    /// `NOTES CLOB`
+    pub notes: Option<String>,
+}
+
+impl ::mimir::FetchRow for OrderLines {
+    type Buffers = <(i64, Option<String>, Option<String>) as ::mimir::FetchRow>::Buffers;
+
+    fn take(buffers: Self::Buffers) -> Option<OrderLines> {
+        let row = <(i64, Option<String>, Option<String>) as ::mimir::FetchRow>::take(buffers);
+        row.map(|(order_id, type_, notes)| {
+            OrderLines {
+                order_id: order_id,
+                type_: type_,
+                notes: notes,
+            }
+        })
+    }
+}
+");
+}
+
+#[test]
+fn unfetchable_struct() {
+    let columns = [Column::new(1, "ID", "NUMBER", Some(OracleType::Number(10, 0)), false),
+                   Column::new(2, "CREATED", "DATE", Some(OracleType::Date), false),
+                   Column::new(3, "SHAPE", "SDO_GEOMETRY", None, true)];
+    let mut out = String::new();
+    write_struct("shapes", &columns, &mut out);
+
+    assert_eq!(out,
+               "/// A row of the `SHAPES` table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shapes {
+    /// `ID NUMBER(10,0) NOT NULL`
+    pub id: i64,
+    /// `CREATED DATE NOT NULL`
+    pub created: ::chrono::DateTime<::chrono::Utc>,
+    // SHAPE SDO_GEOMETRY has no Rust type.
+}
+");
+}
+
+#[test]
+fn handle_struct() {
+    let columns = [Column::new(1, "ID", "NUMBER", Some(OracleType::Number(10, 0)), false),
+                   Column::new(2, "SCAN", "BFILE", Some(OracleType::BFile), true)];
+    let mut out = String::new();
+    write_struct("scans", &columns, &mut out);
+
+    assert_eq!(out,
+               "/// A row of the `SCANS` table.
+pub struct Scans {
+    /// `ID NUMBER(10,0) NOT NULL`
+    pub id: i64,
+    /// `SCAN BFILE`
+    pub scan: Option<::mimir::Lob>,
+}
+");
+}
+
+#[test]
+fn duplicate_field_names() {
+    let columns = [Column::new(1, "ORDER_ID", "NUMBER", Some(OracleType::Number(10, 0)), false),
+                   Column::new(2, "ORDER ID", "NUMBER", Some(OracleType::Number(10, 0)), true)];
+    let mut out = String::new();
+    write_struct("orders", &columns, &mut out);
+
+    assert_eq!(out,
+               "/// A row of the `ORDERS` table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Orders {
+    /// `ORDER_ID NUMBER(10,0) NOT NULL`
+    pub order_id: i64,
+    /// `ORDER ID NUMBER(10,0)`
+    pub order_id_2: Option<i64>,
+}
+
+impl ::mimir::FetchRow for Orders {
+    type Buffers = <(i64, Option<i64>) as ::mimir::FetchRow>::Buffers;
+
+    fn take(buffers: Self::Buffers) -> Option<Orders> {
+        let row = <(i64, Option<i64>) as ::mimir::FetchRow>::take(buffers);
+        row.map(|(order_id, order_id_2)| {
+            Orders {
+                order_id: order_id,
+                order_id_2: order_id_2,
+            }
+        })
+    }
+}
+");
+}
+
+#[test]
+fn datetime_struct() {
+    let columns = [Column::new(1, "AT", "TIMESTAMP", Some(OracleType::Timestamp(6)), false)];
+    let mut out = String::new();
+    write_struct("events", &columns, &mut out);
+
+    assert_eq!(out,
+               "/// A row of the `EVENTS` table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Events {
+    /// `AT TIMESTAMP(6) NOT NULL`
+    pub at: ::chrono::DateTime<::chrono::Utc>,
+}
+
+impl ::mimir::FetchRow for Events {
+    type Buffers = <(::chrono::DateTime<::chrono::Utc>,) as ::mimir::FetchRow>::Buffers;
+
+    fn take(buffers: Self::Buffers) -> Option<Events> {
+        let row = <(::chrono::DateTime<::chrono::Utc>,) as ::mimir::FetchRow>::take(buffers);
+        row.map(|(at,)| {
+            Events {
+                at: at,
+            }
+        })
+    }
+}
+");
+}
@@ -0,0 +1,56 @@
+use mimir::{ConnectParams, redact_connect_string};
+use mimir::error::{Error, ErrorKind, Result};
+
+fn redact_res() -> Result<()> {
+    assert_eq!(redact_connect_string("//localhost:1521/XE"),
+               "//localhost:1521/XE");
+    assert_eq!(redact_connect_string("scott/tiger@//localhost:1521/XE"),
+               "scott/<redacted>@//localhost:1521/XE");
+    assert_eq!(redact_connect_string("scott/p@ss@host"), "scott/<redacted>@host");
+    assert_eq!(redact_connect_string("scott@ORCL"), "scott@ORCL");
+    assert_eq!(redact_connect_string("(DESCRIPTION=(ADDRESS=(HOST=h))(PASSWORD = tiger))"),
+               "(DESCRIPTION=(ADDRESS=(HOST=h))(PASSWORD = <redacted>))");
+    assert_eq!(redact_connect_string("host/svc?password=tiger&user=scott"),
+               "host/svc?password=<redacted>&user=scott");
+    assert_eq!(redact_connect_string("//passwordhost/XE"), "//passwordhost/XE");
+
+    let mut params = ConnectParams::new("scott", "tiger", "scott/tiger@ORCL");
+    params.set_encoding(Some("UTF-8"))?;
+    let debug = format!("{:?}", params);
+    assert!(debug.contains("scott"));
+    assert!(debug.contains("UTF-8"));
+    assert!(!debug.contains("tiger"));
+
+    Ok(())
+}
+
+#[test]
+fn redact() {
+    check!(redact_res)
+}
+
+fn validate_res() -> Result<()> {
+    let params = ConnectParams::from(("scott", "tiger", "//localhost/XE"));
+    assert_eq!(params, ConnectParams::new("scott", "tiger", "//localhost/XE"));
+    params.validate(false, true)?;
+    assert!(params.validate(true, true).is_err());
+
+    let no_credentials = ConnectParams::new("", "", "//localhost/XE");
+    no_credentials.validate(true, true)?;
+    no_credentials.validate(false, false)?;
+    assert!(no_credentials.validate(false, true).is_err());
+
+    // A proxy user may be named with external authentication, but a password may not be given.
+    ConnectParams::new("[scott]", "", "").validate(true, true)?;
+
+    match ConnectParams::new("", "tiger", "").validate(false, false) {
+        Err(Error(ErrorKind::Config(_), _)) => Ok(()),
+        Err(e) => Err(e),
+        Ok(_) => panic!("a password without a username was accepted"),
+    }
+}
+
+#[test]
+fn validate() {
+    check!(validate_res)
+}
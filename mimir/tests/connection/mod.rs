@@ -1,14 +1,19 @@
-use CREDS;
+use CONFIG;
+use chrono::Datelike;
 use mimir::flags;
-use mimir::{Connection, Context, ODPISubscrMessage};
+use mimir::{BindValue, Connection, Context, Data, DateFormat, ExpiredPassword, LazyConnection,
+            ScriptStatement, SessionId, TraceAttrs, compiled_object, split_script};
 use mimir::enums::ODPIDeqMode::Remove;
 use mimir::enums::ODPIMessageDeliveryMode::NotSet;
 use mimir::enums::ODPINativeTypeNum::Bytes;
-use mimir::enums::ODPIOracleTypeNum::{Clob, Varchar};
+use mimir::enums::ODPIOracleTypeNum::{Clob, NVarchar, Raw, Varchar};
 use mimir::enums::ODPIVisibility::OnCommit;
-use mimir::error::Result;
+use mimir::error::{Error, ErrorKind, Result};
+use mimir::raw::{self, ODPISubscrMessage};
+use mimir::sql;
 use rand::{self, Rng};
-use std::ffi::CString;
+use std::thread;
+use std::time::Duration;
 
 extern "C" fn subscr_callback(_context: *mut ::std::os::raw::c_void,
                               _message: *mut ODPISubscrMessage) {
@@ -16,18 +21,10 @@ extern "C" fn subscr_callback(_context: *mut ::std::os::raw::c_void,
 }
 
 fn conn(ctxt: &Context) -> Result<()> {
-    let mut common_create_params = ctxt.init_common_create_params()?;
-    let enc_cstr = CString::new("UTF-8").expect("badness");
-    common_create_params.set_encoding(enc_cstr.as_ptr());
-    common_create_params.set_nchar_encoding(enc_cstr.as_ptr());
+    let mut common_create_params = CONFIG.init_common_create_params(ctxt)?;
     common_create_params.set_create_mode(flags::DPI_MODE_CREATE_EVENTS);
 
-    let conn = Connection::create(ctxt,
-                                  Some(&CREDS[0]),
-                                  Some(&CREDS[1]),
-                                  Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
-                                  Some(common_create_params),
-                                  None)?;
+    let conn = CONFIG.connect_with(ctxt, common_create_params)?;
     // add_ref / release / break_execution test
     conn.add_ref()?;
     conn.release()?;
@@ -55,6 +52,13 @@ fn conn(ctxt: &Context) -> Result<()> {
     assert_eq!(encoding_info.nchar_encoding(), "UTF-8");
     assert_eq!(encoding_info.max_bytes_per_char(), 4);
     assert_eq!(encoding_info.max_bytes_per_nchar(), 4);
+    assert_eq!(encoding_info.bytes_for_chars(Varchar, 10), 40);
+    assert_eq!(encoding_info.bytes_for_chars(Raw, 10), 10);
+    assert_eq!(encoding_info.chars_for_bytes(NVarchar, 10), 2);
+    assert_eq!(encoding_info.bytes_for_chars(Clob, u32::max_value()), u32::max_value());
+    let stmt = conn.prepare_stmt(Some("select 1 from dual"), None, false)?;
+    assert_eq!(stmt.get_encoding_info(), Some(&encoding_info));
+    stmt.release()?;
 
     conn.set_statement_cache_size(40)?;
     let statement_cache_size = conn.get_statement_cache_size()?;
@@ -75,6 +79,37 @@ fn conn(ctxt: &Context) -> Result<()> {
     assert_eq!(version_info.release(),
                "Oracle Database 12c Standard Edition Release 12.1.0.2.0 - \
                 64bit Production");
+    assert_eq!(version_info.major(), 12);
+    let cached_version_info = conn.get_server_version()?;
+    assert_eq!(cached_version_info, version_info);
+
+    // max_identifier_length, server_features
+    let max_identifier_len = conn.max_identifier_length()?;
+    assert!(max_identifier_len == sql::LEGACY_MAX_IDENTIFIER_LEN ||
+            max_identifier_len == sql::MAX_IDENTIFIER_LEN);
+    assert!(max_identifier_len <= sql::max_identifier_len(&version_info));
+    let features = conn.server_features()?;
+    assert_eq!(features.max_identifier_len(), max_identifier_len);
+    assert!(features.optional_merge_clauses());
+    assert!(features.multitenant());
+    assert_eq!(features.container_db(), conn.is_container_db()?);
+
+    // get_container, set_container
+    let container = conn.get_container()?;
+    assert!(!container.is_empty());
+    assert!(conn.set_container("no such; container").is_err());
+    if conn.is_container_db()? {
+        conn.set_container(&container)?;
+        assert_eq!(conn.get_container()?, container);
+    } else {
+        assert!(conn.set_container(&container).is_err());
+    }
+
+    // server_time
+    let server_time = conn.server_time()?;
+    assert!(server_time.year() >= 2017);
+    let server_time_again = conn.server_time()?;
+    assert!(server_time_again >= server_time);
 
     // new_deq_options
     let deq_opts = conn.new_deq_options()?;
@@ -110,13 +145,15 @@ fn conn(ctxt: &Context) -> Result<()> {
     assert_eq!(chunk_size, 8132);
 
     // new_var
-    let var = conn.new_var(Varchar, Bytes, 5, 256, false, false)?;
+    let var = conn.new_var(Varchar, Bytes, 5, 256, false, false, None)?;
     let sib = var.get_size_in_bytes()?;
     assert_eq!(sib, 1024);
     let num_elements_in_array = var.get_num_elements_in_array()?;
     assert_eq!(num_elements_in_array, 5);
-    let data_arr = var.get_data()?;
+    let data_arr = unsafe { raw::var_data(&var)? };
     assert_eq!(data_arr.len(), 5);
+    let refreshed_arr = var.refresh_data()?;
+    assert_eq!(refreshed_arr.len(), 5);
 
     // prepare_stmt
     let statement = conn.prepare_stmt(Some("select 1 from dual"), None, false)?;
@@ -128,12 +165,599 @@ fn conn(ctxt: &Context) -> Result<()> {
     conn.set_client_identifier("client_identifier")?;
     conn.set_client_info("client_info")?;
     conn.set_db_op("insert")?;
+    conn.set_econtext_id("ecid")?;
     conn.set_module("module")?;
 
     Ok(())
 }
 
+fn lazy_conn(ctxt: &Context) -> Result<()> {
+    let lazy = LazyConnection::new(ctxt,
+                                   Some(CONFIG.get_username()),
+                                   Some(CONFIG.get_password()),
+                                   Some(CONFIG.get_connect_string()));
+    assert!(!lazy.is_connected());
+
+    lazy.prewarm()?;
+    lazy.ping()?;
+    assert!(lazy.is_connected());
+
+    // A second pre-warm is a no-op once connected.
+    lazy.prewarm()?;
+    let statement = lazy.prepare_stmt(Some("select 1 from dual"), None, false)?;
+    statement.release()?;
+    lazy.get()?.release()?;
+
+    // The error of a failed pre-warm is returned by the first use.
+    let unreachable = LazyConnection::new(ctxt,
+                                          Some(CONFIG.get_username()),
+                                          Some(CONFIG.get_password()),
+                                          Some("//localhost:1/mimir"));
+    unreachable.prewarm()?;
+    assert!(unreachable.ping().is_err());
+    assert!(!unreachable.is_connected());
+
+    Ok(())
+}
+
+fn change_password_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+
+    // Change to the same password so the test account is left untouched.
+    let reconnected = conn.change_password_and_reconnect(ctxt,
+                                                         CONFIG.get_username(),
+                                                         CONFIG.get_password(),
+                                                         CONFIG.get_password(),
+                                                         Some(CONFIG.get_connect_string()),
+                                                         None)?;
+    reconnected.ping()?;
+    reconnected.release()?;
+
+    let changed = Connection::create_with_new_password(ctxt,
+                                                       CONFIG.get_username(),
+                                                       CONFIG.get_password(),
+                                                       CONFIG.get_password(),
+                                                       Some(CONFIG.get_connect_string()),
+                                                       None,
+                                                       None)?;
+    changed.ping()?;
+    changed.release()?;
+
+    conn.release()?;
+    Ok(())
+}
+
+fn expired_password_res(ctxt: &Context) -> Result<()> {
+    assert!(ExpiredPassword::is_expired_code(28_001));
+    assert!(!ExpiredPassword::is_expired_code(28_002));
+    assert!(!ExpiredPassword::is_expired_code(1_017));
+    assert!(ExpiredPassword::is_grace_code(28_002));
+    assert!(!ExpiredPassword::is_grace_code(28_001));
+
+    // A bad password is reported as a plain connection error, not an expired password.
+    let bad = (CONFIG.get_username(), "not the password", CONFIG.get_connect_string());
+    match Connection::create(ctxt, bad, None, None) {
+        Err(Error(ErrorKind::Connection(_), _)) => Ok(()),
+        Err(e) => Err(e),
+        Ok(_) => panic!("connection with a bad password succeeded"),
+    }
+}
+
+fn password_grace_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    assert!(conn.get_warning().is_none());
+
+    // A password past its lifetime enters its grace period at the next login. Creating the
+    // profile and the user needs privileges the test account may not have.
+    let setup = ["create profile mimir_grace limit password_life_time 1/86400 \
+                  password_grace_time 1",
+                 "create user mimir_grace identified by mimir_grace profile mimir_grace",
+                 "grant create session to mimir_grace"];
+    for sql in &setup {
+        if let Err(e) = conn.execute_immediate(sql) {
+            if ctxt.get_error().code() == 1_031 {
+                conn.release()?;
+                return Ok(());
+            }
+            return Err(e);
+        }
+    }
+    thread::sleep(Duration::from_secs(2));
+
+    let grace = Connection::create(ctxt,
+                                   ("mimir_grace", "mimir_grace", CONFIG.get_connect_string()),
+                                   None,
+                                   None);
+    conn.execute_immediate("drop user mimir_grace cascade")?;
+    conn.execute_immediate("drop profile mimir_grace")?;
+    let grace = grace?;
+    assert_eq!(grace.get_warning().map(|info| info.code()), Some(28_002));
+    grace.ping()?;
+    grace.release()?;
+
+    conn.release()?;
+    Ok(())
+}
+
+fn track_statements_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    assert!(conn.get_open_statements().is_empty());
+
+    let untracked = conn.prepare_stmt(Some("select 1 from dual"), None, false)?;
+    assert!(conn.get_open_statements().is_empty());
+    untracked.release()?;
+
+    conn.set_track_statements(true);
+    let leaked = conn.prepare_stmt(Some("select 2 from dual"), None, false)?;
+    let released = conn.prepare_stmt(Some("select 3 from dual"), None, false)?;
+    released.add_ref()?;
+    released.release()?;
+    assert_eq!(conn.get_open_statements().len(), 2);
+    released.release()?;
+
+    let open = conn.get_open_statements();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].sql(), Some("select 2 from dual"));
+    assert_eq!(open[0].backtrace().is_some(), cfg!(debug_assertions));
+
+    leaked.close(None)?;
+    assert!(conn.get_open_statements().is_empty());
+
+    conn.set_track_statements(false);
+    leaked.release()?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
 #[test]
 fn connection() {
     check_with_ctxt!(conn)
 }
+
+#[test]
+fn lazy_connection() {
+    check_with_ctxt!(lazy_conn)
+}
+
+#[test]
+fn change_password() {
+    check_with_ctxt!(change_password_res)
+}
+
+#[test]
+fn expired_password() {
+    check_with_ctxt!(expired_password_res)
+}
+
+#[test]
+fn password_grace() {
+    check_with_ctxt!(password_grace_res)
+}
+
+#[test]
+fn track_statements() {
+    check_with_ctxt!(track_statements_res)
+}
+
+fn keep_alive_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    conn.set_timing(true);
+    assert!(conn.get_keep_alive().is_none());
+    assert!(conn.tick());
+
+    conn.set_keep_alive(Some(Duration::from_secs(3_600)));
+    assert!(conn.tick());
+    assert!(!conn.get_timings().contains_key("dpiConn_ping"));
+
+    conn.set_keep_alive(Some(Duration::from_millis(0)));
+    assert!(conn.tick());
+    assert!(conn.is_healthy());
+    assert_eq!(conn.get_timings()["dpiConn_ping"].get_calls(), 1);
+
+    conn.set_keep_alive(None);
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn keep_alive() {
+    check_with_ctxt!(keep_alive_res)
+}
+
+fn outlives_context_res() -> Result<()> {
+    let ctxt = Context::create()?;
+    let conn = CONFIG.connect(&ctxt)?;
+    let stmt = conn.prepare_stmt(Some("select 1 from dual"), None, false)?;
+    assert!(conn.is_context_alive());
+    assert!(stmt.is_context_alive());
+
+    drop(ctxt);
+    assert!(!conn.is_context_alive());
+    assert!(!stmt.is_context_alive());
+
+    match stmt.execute(flags::DPI_MODE_EXEC_DEFAULT) {
+        Err(Error(ErrorKind::ContextDropped, _)) => {}
+        Err(e) => return Err(e),
+        Ok(_) => panic!("execute succeeded after the context was dropped"),
+    }
+
+    match conn.prepare_stmt(Some("select 1 from dual"), None, false) {
+        Err(Error(ErrorKind::ContextDropped, _)) => {}
+        Err(e) => return Err(e),
+        Ok(_) => panic!("prepare succeeded after the context was dropped"),
+    }
+
+    stmt.release()?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn outlives_context() {
+    check!(outlives_context_res)
+}
+
+fn trace_attrs_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let attrs = TraceAttrs {
+        module: Some("mimir_module".to_string()),
+        action: Some("mimir_action".to_string()),
+        client_id: Some("mimir_client".to_string()),
+        client_info: Some("mimir_info".to_string()),
+        ecid: Some("mimir_ecid".to_string()),
+        ..Default::default()
+    };
+    conn.set_trace_attrs(&attrs)?;
+
+    // The attributes are sent with the query, so they are visible to it.
+    let stmt = conn.prepare_stmt(Some("select sys_context('userenv', 'module'), \
+                                        sys_context('userenv', 'action'), \
+                                        sys_context('userenv', 'client_identifier'), \
+                                        sys_context('userenv', 'client_info') from dual"),
+                                 None,
+                                 false)?;
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    stmt.fetch()?;
+
+    let expected = ["mimir_module", "mimir_action", "mimir_client", "mimir_info"];
+    for (idx, value) in expected.iter().enumerate() {
+        let (_, data_ptr) = stmt.get_query_value(idx as u32 + 1)?;
+        let data: Data = data_ptr.into();
+        assert_eq!(data.get_string(), *value);
+    }
+
+    stmt.close(None)?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn trace_attrs() {
+    check_with_ctxt!(trace_attrs_res)
+}
+
+const SCRIPT: &str = "-- create the table
+create table mimir_script (id number, name varchar2(20));
+insert into mimir_script values (1, 'semi; colon');
+insert into mimir_script
+  values (2, q'[it's /* not a comment */]')
+/
+/* a block */
+create or replace procedure mimir_script_add(p_id number) as
+begin
+  insert into mimir_script values (p_id, 'added');
+end;
+/
+begin
+  mimir_script_add(3);
+end;
+/
+insert into missing_table values (1);
+drop table mimir_script;
+drop procedure mimir_script_add
+";
+
+fn split_script_res() -> Result<()> {
+    let statements = split_script(SCRIPT);
+    let lines: Vec<_> = statements.iter().map(|stmt| (stmt.line, stmt.plsql)).collect();
+    assert_eq!(lines,
+               [(2, false),
+                (3, false),
+                (4, false),
+                (8, true),
+                (13, true),
+                (17, false),
+                (18, false),
+                (19, false)]);
+    assert_eq!(statements[1].sql, "insert into mimir_script values (1, 'semi; colon')");
+    assert_eq!(statements[2].sql,
+               "insert into mimir_script\n  values (2, q'[it's /* not a comment */]')");
+    assert!(statements[3].sql.ends_with("end;"));
+    assert_eq!(statements[4].sql, "begin\n  mimir_script_add(3);\nend;");
+    assert_eq!(statements[7].sql, "drop procedure mimir_script_add");
+
+    assert_eq!(split_script("select 1 from dual;\n/\n -- trailing comment\n"),
+               [ScriptStatement {
+                    line: 1,
+                    sql: "select 1 from dual".to_string(),
+                    plsql: false,
+                }]);
+    assert!(split_script("  ;\n/* only a comment */\n").is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn split_script_statements() {
+    check!(split_script_res)
+}
+
+fn run_script_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let results = conn.run_script(SCRIPT)?;
+    assert_eq!(results.len(), 8);
+
+    let failed: Vec<_> = results
+        .iter()
+        .filter(|res| res.result.is_err())
+        .map(|res| res.statement.line)
+        .collect();
+    assert_eq!(failed, [17]);
+    for res in &results[1..3] {
+        assert_eq!(res.result.as_ref().ok(), Some(&1));
+    }
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn run_script() {
+    check_with_ctxt!(run_script_res)
+}
+
+fn kill_session_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let victim = CONFIG.connect(ctxt)?;
+
+    let id = victim.current_session_id()?;
+    assert!(id.sid > 0);
+    assert_ne!(id, conn.current_session_id()?);
+    assert_eq!(SessionId { sid: 12, serial: 3456 }.to_string(), "12,3456");
+
+    // Killing a session needs the ALTER SYSTEM privilege the test account may not have. The
+    // statement's error carries the ORA-01031 of the failed execute.
+    match conn.kill_session(id.sid, id.serial, true) {
+        Ok(()) => {}
+        Err(Error(ErrorKind::OciError(ref info), _)) if info.code() == 1_031 => {
+            victim.release()?;
+            conn.release()?;
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    }
+    assert!(victim.ping().is_err());
+    assert!(conn.kill_session(id.sid, id.serial, false).is_err());
+
+    victim.release()?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn kill_session() {
+    check_with_ctxt!(kill_session_res)
+}
+
+#[test]
+fn compiled_objects() {
+    let object = |sql| compiled_object(sql).map(|obj| (obj.owner, obj.object_type, obj.name));
+
+    assert_eq!(object("create or replace procedure hr.Fix_It(a number) as begin null; end;"),
+               Some((Some("HR".to_string()), "PROCEDURE".to_string(), "FIX_IT".to_string())));
+    assert_eq!(object("CREATE PACKAGE BODY \"Pkg\" AS END;"),
+               Some((None, "PACKAGE BODY".to_string(), "Pkg".to_string())));
+    assert_eq!(object("create or replace force view v as select x from t"),
+               Some((None, "VIEW".to_string(), "V".to_string())));
+    assert_eq!(object("alter package p compile body"),
+               Some((None, "PACKAGE BODY".to_string(), "P".to_string())));
+    assert!(object("create table t (id number)").is_none());
+    assert!(object("alter package p rename to q").is_none());
+    assert!(object("drop procedure p").is_none());
+}
+
+fn execute_immediate_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+
+    let diagnostics = conn.execute_immediate("create or replace procedure mimir_broken as \
+                                              begin\n  no_such_proc;\nend;")?;
+    // PLS-00201, followed or preceded by "PL/SQL: Statement ignored".
+    assert!(!diagnostics.is_empty());
+    assert!(diagnostics.iter().all(|diag| (diag.line, diag.position) == (3, 3)));
+    assert!(diagnostics.iter().all(|diag| !diag.is_warning));
+    let undeclared = diagnostics
+        .iter()
+        .find(|diag| diag.message_number == 201)
+        .expect("badness");
+    assert!(undeclared.to_string().starts_with("3:3: PLS-00201"));
+
+    assert_eq!(conn.get_object_errors("procedure", "mimir_broken")?, diagnostics);
+    assert!(conn.get_object_errors("package body", "mimir_broken")?.is_empty());
+    assert!(conn.get_object_errors("procedure", "mimir broken").is_err());
+
+    let clean = conn.execute_immediate("create or replace procedure mimir_broken as \
+                                        begin null; end;")?;
+    assert!(clean.is_empty());
+    assert!(conn.execute_immediate("drop procedure mimir_broken")?.is_empty());
+    assert!(conn.execute_immediate("drop procedure mimir_broken").is_err());
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn execute_immediate() {
+    check_with_ctxt!(execute_immediate_res)
+}
+
+fn dbms_output_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+
+    conn.run_script("begin dbms_output.put_line('not buffered'); end;\n/")?;
+    assert!(conn.fetch_dbms_output()?.is_empty());
+
+    conn.enable_dbms_output(None)?;
+    conn.run_script("begin\n\
+                       dbms_output.put_line('first');\n\
+                       dbms_output.put_line(null);\n\
+                       for i in 1 .. 250 loop\n\
+                         dbms_output.put_line('line ' || i);\n\
+                       end loop;\n\
+                     end;\n/")?;
+    let lines = conn.fetch_dbms_output()?;
+    assert_eq!(lines.len(), 252);
+    assert_eq!(lines[0], "first");
+    assert_eq!(lines[1], "");
+    assert_eq!(lines[251], "line 250");
+    assert!(conn.fetch_dbms_output()?.is_empty());
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn dbms_output() {
+    check_with_ctxt!(dbms_output_res)
+}
+
+#[test]
+fn date_format_sql() {
+    assert_eq!(DateFormat::Date.parameter(), "NLS_DATE_FORMAT");
+    assert_eq!(DateFormat::TimestampTz.to_string(), "NLS_TIMESTAMP_TZ_FORMAT");
+    assert_eq!(DateFormat::Timestamp.alter_session_sql("YYYY-MM-DD\"T\"HH24:MI:SS 'x'"),
+               "alter session set NLS_TIMESTAMP_FORMAT = 'YYYY-MM-DD\"T\"HH24:MI:SS ''x'''");
+}
+
+fn date_formats_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+
+    conn.set_date_format(DateFormat::Date, "YYYY-MM-DD")?;
+    assert_eq!(conn.get_date_format(DateFormat::Date)?, "YYYY-MM-DD");
+    let date: String = conn.query_scalar("select to_char(date '2017-03-04') from dual", &[])?;
+    assert_eq!(date, "2017-03-04");
+
+    conn.set_date_format(DateFormat::Timestamp, "YYYY-MM-DD\"T\"HH24:MI:SS")?;
+    let ts: String = conn.query_scalar("select to_char(timestamp '2017-03-04 05:06:07') \
+                                        from dual",
+                                       &[])?;
+    assert_eq!(ts, "2017-03-04T05:06:07");
+    assert!(conn.set_date_format(DateFormat::Date, "NOT A FORMAT").is_err());
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn date_formats() {
+    check_with_ctxt!(date_formats_res)
+}
+
+fn exists_count_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let levels = "select level as n from dual connect by level <= 5";
+
+    assert_eq!(conn.count(levels, &[])?, 5);
+    assert!(conn.exists(levels, &[])?);
+    assert!(!conn.exists("select 1 from dual where 1 = 0", &[])?);
+    assert_eq!(conn.count("select 1 from dual where 1 = 0", &[])?, 0);
+
+    let filtered = format!("select n from ({}) where n > :min", levels);
+    assert_eq!(conn.count(&filtered, &[("min", BindValue::from(3))])?, 2);
+    assert!(!conn.exists(&filtered, &[("min", BindValue::from(5))])?);
+    assert!(conn.count("select from", &[]).is_err());
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn exists_count() {
+    check_with_ctxt!(exists_count_res)
+}
+
+fn batch_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    conn.run_script("create table mimir_batch (id number(10) not null, n number(10))")?;
+
+    let insert = "insert into mimir_batch (id, n) values (:id, 0)";
+    let batch = conn.batch()
+        .add(insert, &[("id", BindValue::from(1))])
+        .add(insert, &[("id", BindValue::from(2))])
+        .add("update mimir_batch set n = n + 1", &[]);
+    assert_eq!(batch.len(), 3);
+    let results = batch.execute();
+    assert_eq!(results.iter().map(|res| *res.as_ref().expect("result")).collect::<Vec<_>>(),
+               [1, 1, 2]);
+
+    let results = conn.batch()
+        .add("insert into no_such_table values (1)", &[])
+        .add(insert, &[("id", BindValue::from(3))])
+        .execute();
+    assert!(results[0].is_err());
+    assert!(results[1].is_err());
+    assert_eq!(conn.count("select id from mimir_batch", &[])?, 2);
+
+    let results = conn.batch()
+        .continue_on_error(true)
+        .add("insert into no_such_table values (1)", &[])
+        .add(insert, &[("id", BindValue::from(3))])
+        .execute();
+    assert!(results[0].is_err());
+    assert_eq!(*results[1].as_ref().expect("result"), 1);
+    assert!(conn.batch().is_empty());
+
+    // The three inserts are one run, executed as a unit, so the row after the failed one is
+    // still inserted and only the update after the run is skipped.
+    let results = conn.batch()
+        .add(insert, &[("id", BindValue::from(4))])
+        .add(insert, &[("id", BindValue::Null)])
+        .add(insert, &[("id", BindValue::from(5))])
+        .add("update mimir_batch set n = n + 1", &[])
+        .execute();
+    assert_eq!(*results[0].as_ref().expect("result"), 1);
+    assert!(results[1].is_err());
+    assert_eq!(*results[2].as_ref().expect("result"), 1);
+    assert!(results[3].is_err());
+    assert_eq!(conn.count("select id from mimir_batch", &[])?, 5);
+    assert_eq!(conn.count("select id from mimir_batch where n = 2", &[])?, 2);
+
+    conn.rollback()?;
+    conn.run_script("drop table mimir_batch")?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn batch() {
+    check_with_ctxt!(batch_res)
+}
@@ -1,5 +1,6 @@
 use mimir::{enums, flags};
-use mimir::{AppContext, Context, ODPISubscrMessage};
+use mimir::{AppContext, Context, Feature};
+use mimir::raw::{ODPISubscrMessage, ODPIVersionInfo};
 use mimir::error::Result;
 use std::ffi::CString;
 
@@ -12,6 +13,25 @@ fn no_op(_ctxt: &Context) -> Result<()> {
     Ok(())
 }
 
+fn client_info(ctxt: &Context) -> Result<()> {
+    let version_info = ctxt.get_client_version()?;
+    assert_eq!(version_info.version(),
+               format!("{}.{}.{}.{}.{}",
+                       version_info.major(),
+                       version_info.release_num(),
+                       version_info.update_num(),
+                       version_info.port_release_num(),
+                       version_info.port_update_num()));
+
+    let client_info = ctxt.client_info_string()?;
+    assert!(client_info.starts_with("Rust Oracle: 0.1.0"));
+    assert!(client_info.contains(version_info.version()));
+
+    let supported = Feature::BatchErrors.is_supported_by(&version_info);
+    assert_eq!(ctxt.client_supports(Feature::BatchErrors)?, supported);
+    Ok(())
+}
+
 fn ccp(ctxt: &Context) -> Result<()> {
     let mut ccp = ctxt.init_common_create_params()?;
     let default_flags = ccp.get_create_mode();
@@ -165,6 +185,34 @@ fn context() {
     check_with_ctxt!(no_op)
 }
 
+#[test]
+fn client_info_string() {
+    check_with_ctxt!(client_info)
+}
+
+#[test]
+fn features() {
+    let version = |major, release_num| {
+        ODPIVersionInfo {
+            version_num: major,
+            release_num: release_num,
+            update_num: 0,
+            port_release_num: 0,
+            port_update_num: 0,
+            full_version_num: 0,
+        }
+    };
+
+    assert!(!Feature::BatchErrors.is_supported_by(&version(11, 2).into()));
+    assert!(Feature::BatchErrors.is_supported_by(&version(12, 1).into()));
+    assert!(Feature::ImplicitResults.is_supported_by(&version(19, 0).into()));
+    assert!(!Feature::CallTimeout.is_supported_by(&version(12, 2).into()));
+    assert!(Feature::CallTimeout.is_supported_by(&version(18, 3).into()));
+    assert_eq!(Feature::ArrayDmlRowCounts.needs(), "12.1");
+    assert_eq!(Feature::CallTimeout.needs(), "18.1");
+    assert_eq!(Feature::ImplicitResults.to_string(), "implicit results");
+}
+
 #[test]
 fn common_create_params() {
     check_with_ctxt!(ccp)
@@ -184,3 +232,102 @@ fn pool_create_params() {
 fn subscription_create_params() {
     check_with_ctxt!(scp)
 }
+
+#[cfg(feature = "global-context")]
+fn global_res() -> Result<()> {
+    use CONFIG;
+
+    let ctxt = Context::global()?;
+    assert!(ctxt.is_global());
+    let ccp = ctxt.init_common_create_params()?;
+    assert!(ccp.get_create_mode().contains(flags::DPI_MODE_CREATE_THREADED));
+
+    let addr = ctxt as *const Context as usize;
+    let other = ::std::thread::spawn(|| Context::global().map(|c| c as *const Context as usize))
+        .join()
+        .expect("badness")?;
+    assert_eq!(addr, other);
+
+    // Connections created without a context use the global context.
+    let conn = CONFIG.connect(None)?;
+    conn.ping()?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[cfg(feature = "global-context")]
+#[test]
+fn global() {
+    check!(global_res)
+}
+
+#[cfg(feature = "ref-tracking")]
+#[test]
+fn ref_tracking() {
+    use mimir::reftrack;
+    use std::os::raw::c_void;
+
+    let conn = 0x10 as *const c_void;
+    let stmt = 0x20 as *const c_void;
+    reftrack::created("Connection", conn);
+    reftrack::created("Statement", stmt);
+    reftrack::added("Statement", stmt);
+    reftrack::released("Statement", stmt);
+
+    let outstanding = reftrack::outstanding();
+    assert_eq!(outstanding.len(), 2);
+    assert_eq!(outstanding[0].kind(), "Connection");
+    assert_eq!(outstanding[0].refs(), 1);
+    assert_eq!(outstanding[1].kind(), "Statement");
+    assert_eq!(outstanding[1].handle(), 0x20);
+    assert_eq!(outstanding[1].to_string(),
+               "Statement 0x20 still holds 1 reference(s)");
+    assert!(::std::panic::catch_unwind(reftrack::assert_balanced).is_err());
+
+    reftrack::released("Connection", conn);
+    reftrack::released("Statement", stmt);
+    reftrack::assert_balanced();
+
+    reftrack::released("Statement", stmt);
+    let outstanding = reftrack::outstanding();
+    assert_eq!(outstanding[0].refs(), -1);
+    assert_eq!(outstanding[0].to_string(),
+               "Statement 0x20 was released 1 time(s) more than referenced");
+
+    reftrack::reset();
+    assert!(reftrack::outstanding().is_empty());
+
+    let first = 0x100 as *const c_void;
+    let second = 0x200 as *const c_void;
+    let cursor = 0x30 as *const c_void;
+    reftrack::context_created(first);
+    reftrack::context_created(second);
+    reftrack::created_in(first, "Connection", conn);
+    reftrack::wrapped("Statement", cursor);
+    reftrack::added("Statement", cursor);
+
+    let outstanding = reftrack::outstanding();
+    assert_eq!(outstanding.len(), 2);
+    assert_eq!(outstanding[0].context(), 0x100);
+    assert_eq!(outstanding[1].context(), 0x200);
+
+    reftrack::released("Statement", cursor);
+    reftrack::context_dropped(second);
+    assert_eq!(reftrack::outstanding().len(), 1);
+    assert!(::std::panic::catch_unwind(|| reftrack::context_dropped(first)).is_err());
+    assert!(reftrack::outstanding().is_empty());
+}
+
+#[cfg(not(feature = "global-context"))]
+#[test]
+fn no_global_context() {
+    use mimir::Connection;
+    use mimir::error::{Error, ErrorKind};
+
+    // Without the global context, a context must be given.
+    match Connection::create(None, ("scott", "tiger", "ORCL"), None, None) {
+        Err(Error(ErrorKind::Context(_), _)) => {}
+        res => panic!("expected a context error, got {:?}", res.map(|_| ())),
+    }
+}
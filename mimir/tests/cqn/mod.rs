@@ -0,0 +1,132 @@
+use CONFIG;
+use mimir::{ChangeEvent, Connection, Context, CqnListener, Subscription};
+use mimir::cqn::{Notification, QueryChange, RowChange, TableChange};
+use mimir::enums::ODPIEventType;
+use mimir::error::Result;
+use mimir::flags;
+use mimir::raw::{ODPISubscrMessage, ODPISubscrMessageQuery, ODPISubscrMessageRow,
+                 ODPISubscrMessageTable};
+use std::ptr;
+use std::sync::mpsc::TryRecvError;
+
+#[test]
+fn decode_query_change() {
+    let rowid = "AAAR3sAAEAAAACXAAA";
+    let table_name = "MIMIR.ORDERS";
+    let db_name = "ORCL";
+    let mut rows = vec![ODPISubscrMessageRow {
+                            operation: flags::DPI_OPCODE_UPDATE,
+                            rowid: rowid.as_ptr() as *const _,
+                            rowid_length: rowid.len() as u32,
+                        }];
+    let mut tables = vec![ODPISubscrMessageTable {
+                              operation: flags::DPI_OPCODE_UPDATE,
+                              name: table_name.as_ptr() as *const _,
+                              name_length: table_name.len() as u32,
+                              rows: rows.as_mut_ptr(),
+                              num_rows: rows.len() as u32,
+                          }];
+    let mut queries = vec![ODPISubscrMessageQuery {
+                               id: 42,
+                               operation: flags::DPI_OPCODE_ALL_OPS,
+                               tables: tables.as_mut_ptr(),
+                               num_tables: tables.len() as u32,
+                           }];
+    let message = ODPISubscrMessage {
+        event_type: ODPIEventType::QueryChange,
+        db_name: db_name.as_ptr() as *const _,
+        db_name_length: db_name.len() as u32,
+        tables: ptr::null_mut(),
+        num_tables: 0,
+        queries: queries.as_mut_ptr(),
+        num_queries: queries.len() as u32,
+        ..Default::default()
+    };
+
+    let table = TableChange {
+        operation: flags::DPI_OPCODE_UPDATE,
+        name: table_name.to_string(),
+        rows: vec![RowChange {
+                       operation: flags::DPI_OPCODE_UPDATE,
+                       rowid: rowid.to_string(),
+                   }],
+    };
+    let expected = ChangeEvent::Change(Notification {
+        event_type: ODPIEventType::QueryChange,
+        db_name: db_name.to_string(),
+        tables: Vec::new(),
+        queries: vec![QueryChange {
+                          id: 42,
+                          operation: flags::DPI_OPCODE_ALL_OPS,
+                          tables: vec![table],
+                      }],
+        error: None,
+    });
+    assert_eq!(ChangeEvent::from(&message), expected);
+    assert!(!expected.is_deregistered());
+}
+
+#[test]
+fn decode_empty_message() {
+    let message = ODPISubscrMessage {
+        event_type: ODPIEventType::Dereg,
+        num_tables: 3,
+        ..Default::default()
+    };
+
+    let event = ChangeEvent::from(&message);
+    assert!(event.is_deregistered());
+    match event {
+        ChangeEvent::Deregistered(ref notification) => {
+            assert_eq!(notification.event_type, ODPIEventType::Dereg);
+            assert!(notification.db_name.is_empty());
+            assert!(notification.tables.is_empty());
+            assert!(notification.queries.is_empty());
+            assert!(notification.error.is_none());
+        }
+        ChangeEvent::Change(_) => panic!("not decoded as a deregistration"),
+    }
+}
+
+#[test]
+fn into_receiver_needs_channel() {
+    // A subscription not created by `Connection::new_subscription()` has no channel.
+    let subscription = Subscription::from(ptr::null_mut());
+    assert!(subscription.into_receiver().is_err());
+}
+
+#[test]
+fn renew_needs_parameters() {
+    // A subscription not created by `Connection::new_subscription()` cannot be created again.
+    let subscription = Subscription::from(ptr::null_mut());
+    assert!(subscription.renew(&Connection::from(ptr::null_mut())).is_err());
+}
+
+fn renew_res(ctxt: &Context) -> Result<()> {
+    let mut common_create_params = CONFIG.init_common_create_params(ctxt)?;
+    common_create_params.set_create_mode(flags::DPI_MODE_CREATE_EVENTS);
+    let conn = CONFIG.connect_with(ctxt, common_create_params)?;
+
+    let mut subscr_create_params = ctxt.init_subscr_create_params()?;
+    subscr_create_params.set_qos(flags::DPI_SUBSCR_QOS_QUERY);
+    subscr_create_params.set_timeout(60);
+    subscr_create_params.set_name("mimir_renew");
+    let mut listener = CqnListener::new(&conn, subscr_create_params)?;
+    let id = listener.id();
+    listener.register_query("select * from username")?;
+
+    // The renewed subscription has a new id and delivers on the same receiver.
+    listener.renew(&conn)?;
+    assert!(listener.id() != id);
+    assert_eq!(listener.receiver().try_recv(), Err(TryRecvError::Empty));
+
+    listener.close()?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn renew() {
+    check_with_ctxt!(renew_res)
+}
@@ -0,0 +1,122 @@
+use chrono::{Duration, TimeZone, Utc};
+use mimir::{Data, YearsMonths};
+use mimir::error::Result;
+use rand::{self, Rng};
+use std::ptr;
+
+fn data() -> Data<'static> {
+    Data::default()
+}
+
+fn set_get_res() -> Result<()> {
+    let data = data();
+    assert!(!data.is_null());
+
+    data.set_boolean(true);
+    assert!(data.get_boolean());
+    data.set_boolean(false);
+    assert!(!data.get_boolean());
+
+    data.set_double(1.5);
+    assert_eq!(data.get_double(), 1.5);
+
+    data.set_float(2.5);
+    assert_eq!(data.get_float(), 2.5);
+
+    data.set_int64(-42);
+    assert_eq!(data.get_int64(), -42);
+
+    data.set_uint64(42);
+    assert_eq!(data.get_uint64(), 42);
+
+    data.set_lob(ptr::null_mut());
+    assert!(data.get_lob().is_null());
+    data.set_object(ptr::null_mut());
+    assert!(data.get_object().is_null());
+    data.set_stmt(ptr::null_mut());
+    assert!(data.get_stmt().is_null());
+
+    let s = "mimir";
+    data.set_string(s);
+    assert_eq!(data.get_string(), "mimir");
+
+    Ok(())
+}
+
+#[test]
+fn set_get() {
+    check!(set_get_res)
+}
+
+fn temporal_res() -> Result<()> {
+    let data = data();
+
+    let dur = Duration::days(3) + Duration::hours(4) + Duration::minutes(5) +
+              Duration::seconds(6) + Duration::nanoseconds(7_000);
+    data.set_duration(dur);
+    assert_eq!(data.get_duration(), dur);
+
+    let utc = Utc.ymd(2017, 11, 24).and_hms_nano(13, 14, 15, 123_456_000);
+    data.set_utc(utc);
+    assert_eq!(data.get_utc(), utc);
+    assert_eq!(data.get_datetime(), utc);
+
+    let neg = -(Duration::days(400) + Duration::hours(23) + Duration::minutes(59) +
+                Duration::seconds(59) + Duration::nanoseconds(999_999_999));
+    data.set_duration(neg);
+    assert_eq!(data.get_duration(), neg);
+
+    let mut ym: YearsMonths = Default::default();
+    ym.set_years(2);
+    ym.set_months(11);
+    data.set_years_months(ym);
+    let ym = data.get_years_months();
+    assert_eq!(*ym.years(), 2);
+    assert_eq!(*ym.months(), 11);
+    assert_eq!(ym.total_months(), 35);
+    assert_eq!(YearsMonths::from_months(35), ym);
+    assert_eq!(YearsMonths::from_months(-14), YearsMonths::new(-1, -2));
+
+    Ok(())
+}
+
+#[test]
+fn temporal() {
+    check!(temporal_res)
+}
+
+#[test]
+fn utc_round_trip() {
+    let data = data();
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..1000 {
+        let utc = Utc.ymd(rng.gen_range(1, 10_000),
+                          rng.gen_range(1, 13),
+                          rng.gen_range(1, 29))
+            .and_hms_nano(rng.gen_range(0, 24),
+                          rng.gen_range(0, 60),
+                          rng.gen_range(0, 60),
+                          rng.gen_range(0, 1_000_000_000));
+        data.set_utc(utc);
+        assert_eq!(data.get_utc(), utc);
+    }
+
+    let leap = Utc.ymd(2016, 12, 31).and_hms_nano(23, 59, 59, 1_250_000_000);
+    data.set_utc(leap);
+    assert_eq!(data.get_utc(),
+               Utc.ymd(2016, 12, 31).and_hms_nano(23, 59, 59, 999_999_999));
+}
+
+#[test]
+fn duration_round_trip() {
+    let data = data();
+    let mut rng = rand::thread_rng();
+    let max = Duration::days(100_000).num_nanoseconds().expect("overflow");
+
+    for _ in 0..1000 {
+        let dur = Duration::nanoseconds(rng.gen_range(-max, max));
+        data.set_duration(dur);
+        assert_eq!(data.get_duration(), dur);
+    }
+}
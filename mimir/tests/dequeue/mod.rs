@@ -1,26 +1,18 @@
-use CREDS;
-use mimir::Connection;
-use mimir::Context;
+use CONFIG;
+use mimir::{Context, DeqOptionsBuilder, DeqWait};
 use mimir::error::Result;
 use mimir::enums::ODPIDeqMode::{Browse, Remove};
 use mimir::enums::ODPIDeqNavigation::{FirstMsg, NextMsg};
+use mimir::enums::ODPIMessageDeliveryMode::{Persistent, PersistentOrBuffered};
 use mimir::enums::ODPIVisibility::{Immediate, OnCommit};
 use mimir::flags;
-use std::ffi::CString;
+use std::time::Duration;
 
 fn dequeue_res(ctxt: &Context) -> Result<()> {
-    let mut ccp = ctxt.init_common_create_params()?;
-    let enc_cstr = CString::new("UTF-8").expect("badness");
-    ccp.set_encoding(enc_cstr.as_ptr());
-    ccp.set_nchar_encoding(enc_cstr.as_ptr());
+    let mut ccp = CONFIG.init_common_create_params(ctxt)?;
     ccp.set_create_mode(flags::DPI_MODE_CREATE_EVENTS);
 
-    let conn = Connection::create(ctxt,
-                                  Some(&CREDS[0]),
-                                  Some(&CREDS[1]),
-                                  Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
-                                  Some(ccp),
-                                  None)?;
+    let conn = CONFIG.connect_with(ctxt, ccp)?;
 
     conn.add_ref()?;
 
@@ -40,9 +32,15 @@ fn dequeue_res(ctxt: &Context) -> Result<()> {
     // let _msg_id = dequeue_opts.get_msg_id()?;
     // assert_eq!(_msg_id, "uno");
 
-    dequeue_opts.set_wait(100000)?;
+    dequeue_opts.set_wait(DeqWait::Seconds(Duration::from_secs(100000)))?;
     let wait = dequeue_opts.get_wait()?;
-    assert_eq!(wait, 100000);
+    assert_eq!(wait, DeqWait::Seconds(Duration::from_secs(100000)));
+
+    dequeue_opts.set_wait(DeqWait::Forever)?;
+    assert_eq!(dequeue_opts.get_wait()?, DeqWait::Forever);
+
+    dequeue_opts.set_wait(DeqWait::Seconds(Duration::from_millis(500)))?;
+    assert_eq!(dequeue_opts.get_wait()?, DeqWait::NoWait);
 
     dequeue_opts.set_transformation(Some("tsfm"))?;
     let transformation = dequeue_opts.get_transformation()?;
@@ -54,6 +52,10 @@ fn dequeue_res(ctxt: &Context) -> Result<()> {
     visibility = dequeue_opts.get_visibility()?;
     assert_eq!(visibility, Immediate);
 
+    assert_eq!(dequeue_opts.get_delivery_mode(), Persistent);
+    dequeue_opts.set_delivery_mode(PersistentOrBuffered)?;
+    assert_eq!(dequeue_opts.get_delivery_mode(), PersistentOrBuffered);
+
     let mut mode = dequeue_opts.get_mode()?;
     assert_eq!(mode, Remove);
     dequeue_opts.set_mode(Browse)?;
@@ -78,3 +80,38 @@ fn dequeue_res(ctxt: &Context) -> Result<()> {
 fn dequeue() {
     check_with_ctxt!(dequeue_res)
 }
+
+fn builder_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+
+    let builder = DeqOptionsBuilder::new()
+        .consumer("jozias")
+        .correlation("joz%")
+        .delivery_mode(PersistentOrBuffered)
+        .mode(Browse)
+        .navigation(FirstMsg)
+        .transformation("tsfm")
+        .visibility(Immediate)
+        .wait(DeqWait::Seconds(Duration::from_secs(5)));
+    let dequeue_opts = conn.new_deq_options_with(&builder)?;
+
+    assert_eq!(dequeue_opts.get_consumer_name()?, "jozias");
+    assert_eq!(dequeue_opts.get_correlation()?, "joz%");
+    assert_eq!(dequeue_opts.get_delivery_mode(), PersistentOrBuffered);
+    assert_eq!(dequeue_opts.get_mode()?, Browse);
+    assert_eq!(dequeue_opts.get_navigation()?, FirstMsg);
+    assert_eq!(dequeue_opts.get_transformation()?, "tsfm");
+    assert_eq!(dequeue_opts.get_visibility()?, Immediate);
+    assert_eq!(dequeue_opts.get_wait()?,
+               DeqWait::Seconds(Duration::from_secs(5)));
+
+    dequeue_opts.release()?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn builder() {
+    check_with_ctxt!(builder_res)
+}
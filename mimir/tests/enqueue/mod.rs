@@ -1,31 +1,21 @@
-use CREDS;
-use mimir::Connection;
-use mimir::Context;
-use mimir::enums::ODPIMessageDeliveryMode::Buffered;
+use CONFIG;
+use mimir::{Context, EnqOptionsBuilder};
+use mimir::enums::ODPIMessageDeliveryMode::{Buffered, Persistent};
 use mimir::enums::ODPIVisibility::{Immediate, OnCommit};
 use mimir::error::Result;
 use mimir::flags;
-use std::ffi::CString;
 
 fn enqueue_res(ctxt: &Context) -> Result<()> {
-    let mut ccp = ctxt.init_common_create_params()?;
-    let enc_cstr = CString::new("UTF-8").expect("badness");
-    ccp.set_encoding(enc_cstr.as_ptr());
-    ccp.set_nchar_encoding(enc_cstr.as_ptr());
-
-    let conn = Connection::create(ctxt,
-                                  Some(&CREDS[0]),
-                                  Some(&CREDS[1]),
-                                  Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
-                                  Some(ccp),
-                                  None)?;
+    let conn = CONFIG.connect(ctxt)?;
 
     conn.add_ref()?;
 
     let enqueue_opts = conn.new_enq_options()?;
     enqueue_opts.add_ref()?;
 
+    assert_eq!(enqueue_opts.get_delivery_mode(), Persistent);
     enqueue_opts.set_delivery_mode(Buffered)?;
+    assert_eq!(enqueue_opts.get_delivery_mode(), Buffered);
 
     enqueue_opts.set_transformation(Some("tsfm"))?;
     // TODO: Fix this test, doesn't seem to work.
@@ -50,3 +40,25 @@ fn enqueue_res(ctxt: &Context) -> Result<()> {
 fn enqueue() {
     check_with_ctxt!(enqueue_res)
 }
+
+fn builder_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+
+    let builder = EnqOptionsBuilder::new()
+        .delivery_mode(Buffered)
+        .visibility(Immediate);
+    let enqueue_opts = conn.new_enq_options_with(&builder)?;
+
+    assert_eq!(enqueue_opts.get_delivery_mode(), Buffered);
+    assert_eq!(enqueue_opts.get_visibility()?, Immediate);
+
+    enqueue_opts.release()?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn builder() {
+    check_with_ctxt!(builder_res)
+}
@@ -0,0 +1,106 @@
+use CONFIG;
+use mimir::{Context, ErrorInfo};
+use mimir::error::{Error, ErrorKind, Result};
+use mimir::flags;
+use mimir::raw::ODPIErrorInfo;
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+fn info(offset: u16) -> ErrorInfo {
+    ErrorInfo::new(942,
+                   offset,
+                   "ORA-00942: table or view does not exist".to_string(),
+                   "dpiStmt_execute".to_string(),
+                   "execute".to_string(),
+                   "42000".to_string(),
+                   false)
+}
+
+fn render_snippet_res() -> Result<()> {
+    let sql = "select *\n  from no_such_table\n where 1 = 1";
+    let offset = sql.find("no_such_table").expect("badness") as u16;
+    assert_eq!(info(offset).render_snippet(sql),
+               "2:   from no_such_table\n          ^");
+    assert_eq!(info(7).render_snippet("select * from"), "select * from\n       ^");
+    assert_eq!(info(1_000).render_snippet("select"), "select\n      ^");
+
+    let err: Error = ErrorKind::OciError(info(0)).into();
+    assert_eq!(err.render_snippet("selec 1"), Some("selec 1\n^".to_string()));
+    let err: Error = ErrorKind::Statement("dpiStmt_execute".to_string()).into();
+    assert!(err.render_snippet("select 1").is_none());
+
+    Ok(())
+}
+
+fn parse_error_offset_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let sql = "select id from no_such_table";
+    let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+
+    match stmt.execute(flags::DPI_MODE_EXEC_DEFAULT) {
+        Err(e) => {
+            match *e.kind() {
+                ErrorKind::OciError(ref info) => {
+                    assert_eq!(info.code(), 942);
+                    assert_eq!(info.offset() as usize, sql.find("no_such_table").expect("x"));
+                }
+                _ => return Err(e),
+            }
+            assert_eq!(e.render_snippet(sql),
+                       Some("select id from no_such_table\n               ^".to_string()));
+        }
+        Ok(_) => panic!("query on a missing table succeeded"),
+    }
+
+    stmt.release()?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn render_snippet() {
+    check!(render_snippet_res)
+}
+
+#[test]
+fn parse_error_offset() {
+    check_with_ctxt!(parse_error_offset_res)
+}
+
+#[test]
+fn from_odpi_error_info() {
+    let info: ErrorInfo = ODPIErrorInfo::default().into();
+    assert_eq!(info.code(), 0);
+    assert_eq!(info.message(), "");
+    assert_eq!(info.fn_name(), "");
+    assert_eq!(info.sql_state(), "");
+    assert!(!info.recoverable());
+
+    let message = "ORA-00942: table or view does not exist";
+    let fn_name = CString::new("dpiStmt_execute").expect("badness");
+    let action = CString::new("execute").expect("badness");
+    let sql_state = CString::new("42000").expect("badness");
+    let mut odpi_info = ODPIErrorInfo::default();
+    odpi_info.code = 942;
+    odpi_info.offset = 14;
+    odpi_info.message = message.as_ptr() as *const c_char;
+    odpi_info.message_length = message.len() as u32;
+    odpi_info.fn_name = fn_name.as_ptr();
+    odpi_info.action = action.as_ptr();
+    odpi_info.sql_state = sql_state.as_ptr();
+    odpi_info.is_recoverable = 1;
+
+    let info: ErrorInfo = odpi_info.into();
+    drop((fn_name, action, sql_state));
+    assert_eq!(info,
+               ErrorInfo::new(942,
+                              14,
+                              message.to_string(),
+                              "dpiStmt_execute".to_string(),
+                              "execute".to_string(),
+                              "42000".to_string(),
+                              true));
+    assert_eq!(info.offset(), 14);
+    assert!(info.recoverable());
+}
@@ -0,0 +1,38 @@
+use mimir::{InstanceEvent, SubscrMessage, SubscrNotification};
+use mimir::enums::ODPIEventType;
+
+fn message(event_type: ODPIEventType) -> SubscrMessage {
+    SubscrMessage::Change(SubscrNotification {
+                              event_type: event_type,
+                              db_name: "ORCL".to_string(),
+                              tables: Vec::new(),
+                              queries: Vec::new(),
+                              error: None,
+                          })
+}
+
+#[test]
+fn instance_events() {
+    let down = [ODPIEventType::Shutdown, ODPIEventType::ShutdownAny, ODPIEventType::DropDB];
+    for event_type in &down {
+        let event = InstanceEvent::from_message(&message(*event_type)).expect("instance event");
+        assert_eq!(event.event_type, *event_type);
+        assert_eq!(event.db_name, "ORCL");
+        assert!(event.is_down());
+    }
+
+    let startup = InstanceEvent::from_message(&message(ODPIEventType::Startup))
+        .expect("instance event");
+    assert!(!startup.is_down());
+}
+
+#[test]
+fn other_notifications_are_not_instance_events() {
+    let others = [ODPIEventType::No,
+                  ODPIEventType::Dereg,
+                  ODPIEventType::ObjectChange,
+                  ODPIEventType::QueryChange];
+    for event_type in &others {
+        assert_eq!(InstanceEvent::from_message(&message(*event_type)), None);
+    }
+}
@@ -0,0 +1,67 @@
+use CONFIG;
+use chrono::{TimeZone, Utc};
+use mimir::{AsOf, Context, ControlFlow, Data, Scn};
+use mimir::error::Result;
+use mimir::flags;
+
+#[test]
+fn as_of_clause() {
+    assert_eq!(AsOf::Scn(Scn(1234)).to_string(), "AS OF SCN 1234");
+    let time = Utc.ymd(2017, 6, 1).and_hms_micro(12, 30, 5, 250);
+    assert_eq!(AsOf::Timestamp(time).to_string(),
+               "AS OF TIMESTAMP TIMESTAMP '2017-06-01 12:30:05.000250 +00:00'");
+    assert!(Scn(1) < Scn::from(2));
+}
+
+fn flashback_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    conn.run_script("create table mimir_flashback (id number) rowdependencies;\n\
+                     insert into mimir_flashback values (1);\n\
+                     commit;")?;
+    let before = conn.current_scn()?;
+
+    conn.run_script("update mimir_flashback set id = 2;\ncommit;")?;
+    let after = conn.current_scn()?;
+    assert!(before < after);
+
+    // ORA_ROWSCN moves past the SCN read before the update, so an optimistic update would fail.
+    let stmt = conn.prepare_stmt(Some("select ora_rowscn from mimir_flashback"), None, false)?;
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    let mut buffers: (Vec<Scn>,) = Default::default();
+    stmt.fetch_into(&conn, 10, &mut buffers, |&mut (ref scns,)| {
+            assert_eq!(scns.len(), 1);
+            assert!(scns[0] > before && scns[0] <= after);
+            ControlFlow::Continue
+        })?;
+    stmt.release()?;
+
+    let sql = format!("select id from mimir_flashback {}", AsOf::Scn(before));
+    let past = conn.prepare_stmt(Some(&sql), None, false)?;
+    past.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    past.fetch()?;
+    let (_, id_ptr) = past.get_query_value(1)?;
+    assert!((Data::from(id_ptr).get_double() - 1.0).abs() < ::std::f64::EPSILON);
+    past.release()?;
+
+    {
+        let flashback = conn.flashback(AsOf::Scn(before))?;
+        let session = flashback.prepare_stmt(Some("select id from mimir_flashback"), None, false)?;
+        session.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+        session.fetch()?;
+        let (_, id_ptr) = session.get_query_value(1)?;
+        assert!((Data::from(id_ptr).get_double() - 1.0).abs() < ::std::f64::EPSILON);
+        session.release()?;
+        flashback.end()?;
+    }
+
+    conn.run_script("drop table mimir_flashback")?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn flashback() {
+    check_with_ctxt!(flashback_res)
+}
@@ -0,0 +1,60 @@
+use CONFIG;
+use mimir::Context;
+use mimir::error::Result;
+use mimir::testutil::{Schema, Script};
+
+#[test]
+fn parse_scripts() {
+    let setup = Script::from_file("tests/sql/setup.sql").expect("badness");
+    assert_eq!(setup.get_statements().len(), 4);
+    assert_eq!(setup.get_statements()[1],
+               "insert into username values (1, 'jozias')");
+    assert_eq!(setup.get_statements()[2],
+               "insert into username values (2, 'mimir')");
+
+    let objects = Script::from_file("tests/sql/objects_setup.sql").expect("badness");
+    assert_eq!(objects.get_statements().len(), 7);
+    assert!(objects.get_statements()[0].starts_with("create type udt_SubObject"));
+    assert!(objects.get_statements()[0].ends_with(");"));
+    assert!(objects.get_statements()[4].starts_with("create table TestObjects"));
+    assert!(objects.get_statements()[4].ends_with(")"));
+
+    let plsql = Script::parse("-- comment\n\
+                               begin\n  null;\nend;\n/\n\n\
+                               create or replace procedure p as\nbegin\n  null;\nend;\n/\n\
+                               select 1 from dual;\n");
+    assert_eq!(plsql.get_statements(),
+               &["begin\n  null;\nend;".to_string(),
+                 "create or replace procedure p as\nbegin\n  null;\nend;".to_string(),
+                 "select 1 from dual".to_string()]);
+}
+
+fn schema_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let schema = Schema::from_files("tests/sql/harness_setup.sql",
+                                    "tests/sql/harness_teardown.sql")?;
+
+    // Setup can be repeated, as any existing objects are dropped first.
+    schema.setup(&conn)?;
+    schema.setup(&conn)?;
+
+    let stmt = conn.prepare_stmt(Some("select count(*) from mimir_harness_test"), None, false)?;
+    stmt.execute(::mimir::flags::DPI_MODE_EXEC_DEFAULT)?;
+    let (found, _) = stmt.fetch()?;
+    assert!(found);
+    let (_, count_ptr) = stmt.get_query_value(1)?;
+    let count: ::mimir::Data = count_ptr.into();
+    assert!((count.get_double() - 2.0).abs() < ::std::f64::EPSILON);
+    stmt.close(None)?;
+
+    assert_eq!(schema.teardown(&conn), 0);
+    assert_eq!(schema.teardown(&conn), 1);
+
+    conn.release()?;
+    Ok(())
+}
+
+#[test]
+fn schema() {
+    check_with_ctxt!(schema_res)
+}
@@ -5,49 +5,97 @@ mod macros;
 
 extern crate chrono;
 extern crate mimir;
+#[cfg(feature = "proptest")]
+#[macro_use]
+extern crate proptest;
 extern crate rand;
 
+mod catalog;
+mod cdc;
+#[cfg(feature = "codegen")]
+mod codegen;
+mod config;
 mod context;
 mod connection;
+mod cqn;
+mod data;
 mod dequeue;
 mod enqueue;
+mod error;
+mod failover;
+mod flashback;
+#[cfg(feature = "test-util")]
+mod harness;
 mod lob;
+mod merge;
 mod message;
+mod migrate;
+mod modes;
+mod naming;
 mod objecttype;
+mod optimistic;
+mod oracletype;
 mod pool;
+#[cfg(feature = "proptest")]
+mod props;
+mod registry;
+mod resultcache;
+mod sql;
 mod statement;
+mod stmtcache;
+mod timing;
+mod transaction;
+mod types;
+mod variable;
+mod xml;
 
+use mimir::ConnectParams;
 use std::env;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Write};
+
+/// The credentials file read before `ConnectParams`, holding a `username:password` line for the
+/// mimir user followed by one for the ODPI-C user.
+const LEGACY_CREDS: &str = ".creds/oic-test";
+
+/// Read the connection parameters from the given file, falling back to the legacy credentials
+/// file and then to the environment variables with the given prefix. The tests expect UTF-8
+/// unless another encoding is configured.
+fn test_config(path: &str, legacy_line: usize, prefix: &str) -> ConnectParams {
+    let file_config = ConnectParams::from_file(path)
+        .ok()
+        .or_else(|| legacy_config(path, legacy_line, prefix));
+    let mut config = match file_config {
+        Some(config) => config,
+        None => ConnectParams::from_env_prefix(prefix).expect("invalid test configuration"),
+    };
+
+    if config.get_encoding().is_none() {
+        config.set_encoding(Some("UTF-8")).expect("invalid encoding");
+    }
+    config
+}
+
+/// Read the credentials on the given line of the legacy credentials file, if it exists. The
+/// connect string is taken from the `<prefix>_CONNECT_STRING` environment variable.
+fn legacy_config(path: &str, line: usize, prefix: &str) -> Option<ConnectParams> {
+    let file = File::open(LEGACY_CREDS).ok()?;
+    let creds = BufReader::new(file).lines().nth(line)?.ok()?;
+    let mut parts = creds.splitn(2, ':').map(|part| part.trim_right());
+    let (username, password) = (parts.next()?, parts.next()?);
+    let connect_string = env::var(format!("{}_CONNECT_STRING", prefix)).ok()?;
+
+    writeln!(io::stderr(),
+             "{} is deprecated, move the {} credentials to {} (see the README)",
+             LEGACY_CREDS,
+             prefix,
+             path)
+            .expect("badness");
+    Some(ConnectParams::new(username, password, &connect_string))
+}
 
 #[cfg(test)]
 lazy_static! {
-    pub static ref CREDS: Vec<String> = {
-        let mut creds = Vec::new();
-        if let Ok(file) = File::open(".creds/oic-test") {
-            let buf_reader = BufReader::new(file);
-
-            #[cfg_attr(feature = "cargo-clippy", allow(used_underscore_binding))]
-            for line_res in buf_reader.lines() {
-                if let Ok(line) = line_res {
-                    let parts = line.split(':').map(|x| {
-                        x.trim_right().to_string()
-                    }).collect::<Vec<String>>();
-                    creds.extend(parts);
-                }
-            }
-        } else {
-            let username = env::var("MIMIR_USERNAME").expect("invalid username");
-            let password = env::var("MIMIR_PASSWORD").expect("invalid password");
-            creds.push(username);
-            creds.push(password);
-
-            let odpic_username = env::var("ODPIC_USERNAME").expect("invalid username");
-            let odpic_password = env::var("ODPIC_PASSWORD").expect("invalid password");
-            creds.push(odpic_username);
-            creds.push(odpic_password);
-        }
-        creds
-    };
+    pub static ref CONFIG: ConnectParams = test_config(".creds/mimir-test", 0, "MIMIR");
+    pub static ref ODPIC_CONFIG: ConnectParams = test_config(".creds/odpic-test", 1, "ODPIC");
 }
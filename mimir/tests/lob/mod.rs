@@ -1,23 +1,11 @@
-use CREDS;
-use mimir::Connection;
-use mimir::Context;
-use mimir::enums::ODPIOracleTypeNum::Blob;
+use CONFIG;
+use mimir::{Context, Data, lob};
+use mimir::enums::ODPIOracleTypeNum::{Blob, Clob};
 use mimir::error::Result;
 use mimir::flags;
-use std::ffi::CString;
 
 fn lob_res(ctxt: &Context) -> Result<()> {
-    let mut ccp = ctxt.init_common_create_params()?;
-    let enc_cstr = CString::new("UTF-8").expect("badness");
-    ccp.set_encoding(enc_cstr.as_ptr());
-    ccp.set_nchar_encoding(enc_cstr.as_ptr());
-
-    let conn = Connection::create(ctxt,
-                                  Some(&CREDS[0]),
-                                  Some(&CREDS[1]),
-                                  Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
-                                  Some(ccp),
-                                  None)?;
+    let conn = CONFIG.connect(ctxt)?;
 
     let temp_lob = conn.new_temp_lob(Blob)?;
 
@@ -50,6 +38,27 @@ fn lob_res(ctxt: &Context) -> Result<()> {
     let is_open_after_close = temp_lob.get_is_resource_open()?;
     assert!(!is_open_after_close);
 
+    // bind_lob_by_pos / bind_lob_by_name test
+    let by_pos = conn.prepare_stmt(Some("select dbms_lob.getlength(:1) from dual"), None, false)?;
+    by_pos.bind_lob_by_pos(1, &temp_lob)?;
+    by_pos.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    by_pos.fetch()?;
+    let (_, len_ptr) = by_pos.get_query_value(1)?;
+    let len_data: Data = len_ptr.into();
+    assert!((len_data.get_double() - 16264.0).abs() < ::std::f64::EPSILON);
+    by_pos.close(None)?;
+
+    let by_name = conn.prepare_stmt(Some("select dbms_lob.getlength(:lob) from dual"),
+                                    None,
+                                    false)?;
+    by_name.bind_lob_by_name(":lob", &temp_lob)?;
+    by_name.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    by_name.fetch()?;
+    let (_, len_ptr) = by_name.get_query_value(1)?;
+    let len_data: Data = len_ptr.into();
+    assert!((len_data.get_double() - 16264.0).abs() < ::std::f64::EPSILON);
+    by_name.close(None)?;
+
     temp_lob.release()?;
     conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
     conn.release()?;
@@ -61,3 +70,71 @@ fn lob_res(ctxt: &Context) -> Result<()> {
 fn lob() {
     check_with_ctxt!(lob_res)
 }
+
+fn copy_between_res(ctxt: &Context) -> Result<()> {
+    let src_conn = CONFIG.connect(ctxt)?;
+    let dst_conn = CONFIG.connect(ctxt)?;
+
+    let src = src_conn.new_temp_lob(Clob)?;
+    let dst = dst_conn.new_temp_lob(Clob)?;
+    dst.set_from_bytes(&to_i8("stale content that is replaced"))?;
+
+    let text = "Grüße aus Köln, ".repeat(100);
+    src.set_from_bytes(&to_i8(&text))?;
+
+    let chars = text.chars().count() as u64;
+    assert_eq!(lob::copy_between(&src, &dst, 7)?, chars);
+    assert_eq!(dst.get_size()?, chars);
+    assert!(!dst.get_is_resource_open()?);
+
+    let copied: Vec<u8> = dst.read_bytes(1, chars)?.iter().map(|b| *b as u8).collect();
+    assert_eq!(String::from_utf8_lossy(&copied), text);
+
+    src.release()?;
+    dst.release()?;
+    src_conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    src_conn.release()?;
+    dst_conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    dst_conn.release()?;
+
+    Ok(())
+}
+
+fn to_i8(s: &str) -> Vec<i8> {
+    s.bytes().map(|b| b as i8).collect()
+}
+
+#[test]
+fn copy_between() {
+    check_with_ctxt!(copy_between_res)
+}
+
+fn insert_with_lob_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    conn.run_script("create table mimir_lob (id number(10) not null, body blob)")?;
+
+    let body: Vec<i8> = (0..100_000).map(|i| (i % 128) as i8).collect();
+    let inserted = conn.insert_with_lob("insert into mimir_lob (id, body) \
+                                         values (1, empty_blob()) returning body into :body",
+                                        &body)?;
+    assert_eq!(inserted, 1);
+    let size: i64 = conn.query_scalar("select dbms_lob.getlength(body) from mimir_lob", &[])?;
+    assert_eq!(size, 100_000);
+    assert!(conn.insert_with_lob("insert into mimir_lob (id) values (2)", &body).is_err());
+    assert!(conn.insert_with_lob("update mimir_lob set body = empty_blob() \
+                                  returning body into :body",
+                                 &body)
+                .is_err());
+
+    conn.rollback()?;
+    conn.run_script("drop table mimir_lob")?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn insert_with_lob() {
+    check_with_ctxt!(insert_with_lob_res)
+}
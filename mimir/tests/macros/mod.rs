@@ -13,6 +13,19 @@ pub fn within_context(f: &Fn(&Context) -> Result<()>) -> Result<()> {
     }
 }
 
+macro_rules! check {
+    ($f:ident) => {{
+        match $f() {
+            Ok(_) => assert!(true),
+            Err(e) => {
+                use std::io::{self, Write};
+                writeln!(io::stderr(), "{}", e).expect("badness");
+                assert!(false);
+            }
+        }
+    }};
+}
+
 macro_rules! check_with_ctxt {
     ($f:ident) => {{
         match $crate::macros::within_context(&$f) {
@@ -0,0 +1,75 @@
+use CONFIG;
+use mimir::{Context, Data, Merge, ServerFeatures};
+use mimir::error::Result;
+use mimir::flags;
+
+fn sql_res() -> Result<()> {
+    let merge = Merge::into("hr.accounts")
+        .on(&["id"])
+        .set(&[("id", 7.into()), ("name", "savings".into()), ("balance", 1.5.into())]);
+    assert_eq!(merge.sql()?,
+               "MERGE INTO HR.ACCOUNTS TGT \
+                USING (SELECT :ID AS ID, :NAME AS NAME, :BALANCE AS BALANCE FROM DUAL) SRC \
+                ON (TGT.ID = SRC.ID) \
+                WHEN MATCHED THEN UPDATE SET TGT.NAME = SRC.NAME, TGT.BALANCE = SRC.BALANCE \
+                WHEN NOT MATCHED THEN INSERT (ID, NAME, BALANCE) \
+                VALUES (SRC.ID, SRC.NAME, SRC.BALANCE)");
+
+    let keys_only = Merge::into("tags").on(&["name"]).set(&[("name", "x".into())]);
+    assert_eq!(keys_only.sql()?,
+               "MERGE INTO TAGS TGT USING (SELECT :NAME AS NAME FROM DUAL) SRC \
+                ON (TGT.NAME = SRC.NAME) \
+                WHEN NOT MATCHED THEN INSERT (NAME) VALUES (SRC.NAME)");
+
+    assert!(keys_only.sql_for(&ServerFeatures::new(9, 2)).is_err());
+    let long = Merge::into("tags")
+        .on(&["a_rather_long_column_name_12345"])
+        .set(&[("a_rather_long_column_name_12345", 1.into())]);
+    assert!(long.sql_for(&ServerFeatures::new(12, 1)).is_err());
+    assert!(long.sql_for(&ServerFeatures::new(12, 2)).is_ok());
+
+    assert!(Merge::into("tags").set(&[("name", "x".into())]).sql().is_err());
+    assert!(Merge::into("tags").on(&["id"]).set(&[("name", "x".into())]).sql().is_err());
+    assert!(Merge::into("tags").on(&["name"]).set(&[("name) --", "x".into())]).sql().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn sql() {
+    check!(sql_res)
+}
+
+fn upsert_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    conn.run_script("create table mimir_merge (id number primary key, name varchar2(20))")?;
+
+    let merge = |name: &str| {
+        Merge::into("mimir_merge")
+            .on(&["id"])
+            .set(&[("id", 1.into()), ("name", name.into())])
+            .execute(&conn)
+    };
+    assert_eq!(merge("inserted")?, 1);
+    assert_eq!(merge("updated")?, 1);
+
+    let stmt = conn.prepare_stmt(Some("select count(*), max(name) from mimir_merge"), None, false)?;
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    stmt.fetch()?;
+    let (_, count_ptr) = stmt.get_query_value(1)?;
+    let (_, name_ptr) = stmt.get_query_value(2)?;
+    assert_eq!(Data::from(count_ptr).get_double() as u32, 1);
+    assert_eq!(Data::from(name_ptr).get_string(), "updated");
+    stmt.release()?;
+
+    conn.run_script("drop table mimir_merge")?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn upsert() {
+    check_with_ctxt!(upsert_res)
+}
@@ -1,25 +1,13 @@
-use CREDS;
+use CONFIG;
 use chrono::{Datelike, Utc, Timelike};
-use mimir::Connection;
 use mimir::Context;
 use mimir::enums::ODPIMessageDeliveryMode::NotSet;
 use mimir::enums::ODPIMessageState::Ready;
 use mimir::error::Result;
 use mimir::flags;
-use std::ffi::CString;
 
 fn msg(ctxt: &Context) -> Result<()> {
-    let mut ccp = ctxt.init_common_create_params()?;
-    let enc_cstr = CString::new("UTF-8").expect("badness");
-    ccp.set_encoding(enc_cstr.as_ptr());
-    ccp.set_nchar_encoding(enc_cstr.as_ptr());
-
-    let conn = Connection::create(ctxt,
-                                  Some(&CREDS[0]),
-                                  Some(&CREDS[1]),
-                                  Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
-                                  Some(ccp),
-                                  None)?;
+    let conn = CONFIG.connect(ctxt)?;
 
     conn.add_ref()?;
 
@@ -88,3 +76,26 @@ fn msg(ctxt: &Context) -> Result<()> {
 fn msg_props() {
     check_with_ctxt!(msg)
 }
+
+fn clone_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+
+    let msg_props = conn.new_msg_props()?;
+    msg_props.set_correlation("shared")?;
+    let cloned = msg_props.clone();
+
+    // The clone holds its own reference, so it remains usable after the original is released.
+    msg_props.release()?;
+    assert_eq!(cloned.get_correlation()?, "shared");
+    cloned.release()?;
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn clone() {
+    check_with_ctxt!(clone_res)
+}
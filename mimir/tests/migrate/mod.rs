@@ -0,0 +1,101 @@
+use CONFIG;
+use mimir::{Connection, Context, Migrator};
+use mimir::error::{Error, ErrorKind, Result};
+use mimir::flags;
+use mimir::migrate::{self, State};
+use rand::{self, Rng};
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Creates an empty temporary directory holding the given scripts.
+fn migrations_dir(scripts: &[(&str, &str)]) -> Result<PathBuf> {
+    let dir = env::temp_dir().join(format!("mimir-migrate-{}", rand::thread_rng().gen::<u32>()));
+    fs::create_dir_all(&dir)?;
+    for &(file_name, script) in scripts {
+        File::create(dir.join(file_name))?.write_all(script.as_bytes())?;
+    }
+    Ok(dir)
+}
+
+fn migrations_res() -> Result<()> {
+    let dir = migrations_dir(&[("0002_add_users.sql", "insert into t values (1);"),
+                               ("0001_create.sql", "create table t (id number);"),
+                               ("README.md", "not a migration")])?;
+    let migrations = migrate::migrations(&dir)?;
+    let found: Vec<_> = migrations.iter().map(|m| (m.version(), m.name())).collect();
+    assert_eq!(found, [(1, "create"), (2, "add_users")]);
+    assert_eq!(migrations[0].checksum().len(), 16);
+    assert!(migrations[0].checksum() != migrations[1].checksum());
+
+    File::create(dir.join("0002_duplicate.sql"))?;
+    match migrate::migrations(&dir) {
+        Err(Error(ErrorKind::Migration(ref msg), _)) => assert!(msg.contains("duplicate")),
+        Err(e) => return Err(e),
+        Ok(_) => panic!("duplicate versions were accepted"),
+    }
+
+    let bad = migrations_dir(&[("create.sql", "")])?;
+    assert!(migrate::migrations(&bad).is_err());
+
+    fs::remove_dir_all(dir)?;
+    fs::remove_dir_all(bad)?;
+    Ok(())
+}
+
+#[test]
+fn migrations() {
+    check!(migrations_res)
+}
+
+fn drop_table(conn: &Connection, table: &str) -> Result<()> {
+    let stmt = conn.prepare_stmt(Some(&format!("drop table {}", table)), None, false)?;
+    let _ = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT);
+    stmt.release()
+}
+
+fn apply_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let migrator = Migrator::with_table(&conn, "mimir_test_migrations")?;
+    assert!(Migrator::with_table(&conn, "bad name").is_err());
+
+    let dir = migrations_dir(&[("1_create.sql",
+                                "create table mimir_migrate (id number);\n"),
+                               ("2_insert.sql",
+                                "insert into mimir_migrate values (1);\n\
+                                 insert into mimir_migrate values (2);\n")])?;
+    let applied: Vec<_> = migrator.apply(&dir)?.iter().map(|m| m.version()).collect();
+    assert_eq!(applied, [1, 2]);
+    assert!(migrator.apply(&dir)?.is_empty());
+
+    // A failing script is rolled back, including its registry row.
+    File::create(dir.join("3_fail.sql"))?
+        .write_all(b"insert into mimir_migrate values (3);\ninsert into missing values (1);\n")?;
+    match migrator.apply(&dir) {
+        Err(Error(ErrorKind::Migration(ref msg), _)) => assert!(msg.contains("line 2")),
+        Err(e) => return Err(e),
+        Ok(_) => panic!("a failing migration was applied"),
+    }
+
+    let states: Vec<_> = migrator.status(&dir)?.iter().map(|s| (s.version, s.state)).collect();
+    assert_eq!(states,
+               [(1, State::Applied), (2, State::Applied), (3, State::Pending)]);
+
+    File::create(dir.join("1_create.sql"))?.write_all(b"create table changed (id number);")?;
+    assert!(migrator.apply(&dir).is_err());
+    assert_eq!(migrator.status(&dir)?[0].state, State::Changed);
+
+    drop_table(&conn, "mimir_migrate")?;
+    drop_table(&conn, "mimir_test_migrations")?;
+    fs::remove_dir_all(dir)?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn apply() {
+    check_with_ctxt!(apply_res)
+}
@@ -0,0 +1,23 @@
+use mimir::{AuthMode, ConnCloseMode, CreateMode, ExecMode, PoolCloseMode, SubscrQos, flags};
+
+#[test]
+fn modes() {
+    assert_eq!(ExecMode::default().bits(), flags::DPI_MODE_EXEC_DEFAULT);
+    assert_eq!(ExecMode::default()
+                   .commit_on_success()
+                   .batch_errors()
+                   .bits(),
+               flags::DPI_MODE_EXEC_COMMIT_ON_SUCCESS | flags::DPI_MODE_EXEC_BATCH_ERRORS);
+    assert_eq!(ExecMode::parse_only().bits(), flags::DPI_MODE_EXEC_PARSE_ONLY);
+
+    let auth: flags::ODPIAuthMode = AuthMode::sysdba().prelim().into();
+    assert_eq!(auth, flags::DPI_MODE_AUTH_SYSDBA | flags::DPI_MODE_AUTH_PRELIM);
+    assert_eq!(AuthMode::default().bits(), flags::DPI_MODE_AUTH_DEFAULT);
+
+    assert_eq!(ConnCloseMode::drop_session().bits(), flags::DPI_MODE_CONN_CLOSE_DROP);
+    assert_eq!(PoolCloseMode::force().bits(), flags::DPI_MODE_POOL_CLOSE_FORCE);
+    assert_eq!(CreateMode::default().threaded().events().bits(),
+               flags::DPI_MODE_CREATE_THREADED | flags::DPI_MODE_CREATE_EVENTS);
+    assert_eq!(SubscrQos::default().query().rowids().bits(),
+               flags::DPI_SUBSCR_QOS_QUERY | flags::DPI_SUBSCR_QOS_ROWIDS);
+}
@@ -0,0 +1,128 @@
+use mimir::{Compression, ConnectString, NamingMethod, TnsNames};
+use mimir::error::Result;
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::time::Duration;
+
+const TNSNAMES: &str = "
+# Test aliases
+ORCL, orcl.world =
+  (DESCRIPTION =
+    (ADDRESS = (PROTOCOL = TCP)(HOST = oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com)(PORT = 1521))
+    (CONNECT_DATA =
+      (SERVICE_NAME = ORCL)  # the service
+    )
+  )
+
+easy = //localhost:1521/XE
+";
+
+const ORCL: &str = "(DESCRIPTION=(ADDRESS=(PROTOCOL=TCP)\
+                    (HOST=oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com)(PORT=1521))\
+                    (CONNECT_DATA=(SERVICE_NAME=ORCL)))";
+
+fn tnsnames_res() -> Result<()> {
+    let tns = TnsNames::parse(TNSNAMES)?;
+    assert_eq!(tns.aliases().len(), 3);
+    assert_eq!(tns.get("orcl"), Some(ORCL));
+    assert_eq!(tns.get("ORCL.WORLD"), Some(ORCL));
+    assert_eq!(tns.get("easy"), Some("//localhost:1521/XE"));
+    assert!(tns.get("missing").is_none());
+    assert!(tns.resolve("missing").is_err());
+
+    assert!(TnsNames::parse("bad = (DESCRIPTION = (ADDRESS = ").is_err());
+    assert!(TnsNames::parse("no_equals").is_err());
+
+    let path = env::temp_dir().join("mimir-tnsnames.ora");
+    File::create(&path)?.write_all(TNSNAMES.as_bytes())?;
+    let file_method = NamingMethod::TnsNames {
+        path: path.clone(),
+        alias: "orcl".to_string(),
+    };
+    assert_eq!(file_method.resolve()?, ORCL);
+
+    let admin_dir = env::temp_dir().join("mimir-tns-admin");
+    fs::create_dir_all(&admin_dir)?;
+    File::create(admin_dir.join("tnsnames.ora"))?.write_all(TNSNAMES.as_bytes())?;
+    assert_eq!(TnsNames::from_dir(&admin_dir)?.resolve("easy")?,
+               "//localhost:1521/XE");
+
+    let direct = NamingMethod::Direct("( DESCRIPTION = (ADDRESS = (HOST = h)) )".to_string());
+    assert_eq!(direct.resolve()?, "(DESCRIPTION=(ADDRESS=(HOST=h)))");
+    assert!(NamingMethod::Direct("(DESCRIPTION=".to_string()).resolve().is_err());
+    assert!(NamingMethod::Direct("".to_string()).resolve().is_err());
+    assert!(NamingMethod::Direct("not valid".to_string()).resolve().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn naming() {
+    check!(tnsnames_res)
+}
+
+fn connect_string_res() -> Result<()> {
+    let plain = ConnectString::new("db.example.com", 1521, "orclpdb").build()?;
+    assert_eq!(plain,
+               "(DESCRIPTION=(ADDRESS=(PROTOCOL=TCP)(HOST=db.example.com)(PORT=1521))\
+                (CONNECT_DATA=(SERVICE_NAME=orclpdb)))");
+
+    let cloud = ConnectString::new("lb.example.com", 1522, "orclpdb")
+        .tcps()
+        .expire_time(Duration::from_secs(90))
+        .disable_oob()
+        .compression(Compression::High)
+        .build()?;
+    assert_eq!(cloud,
+               "(DESCRIPTION=(EXPIRE_TIME=2)(DISABLE_OOB=ON)(COMPRESSION=ON)\
+                (COMPRESSION_LEVELS=(LEVEL=HIGH))\
+                (ADDRESS=(PROTOCOL=TCPS)(HOST=lb.example.com)(PORT=1522))\
+                (CONNECT_DATA=(SERVICE_NAME=orclpdb)))");
+    assert_eq!(NamingMethod::Direct(cloud.clone()).resolve()?, cloud);
+
+    let rac = ConnectString::new("rac1.example.com", 1521, "sales")
+        .address("rac2.example.com", 1521)
+        .address("rac3.example.com", 1522)
+        .load_balance()
+        .failover(true)
+        .retry_count(3)
+        .retry_delay(Duration::from_millis(2500))
+        .build()?;
+    assert_eq!(rac,
+               "(DESCRIPTION=(RETRY_COUNT=3)(RETRY_DELAY=3)\
+                (ADDRESS_LIST=(LOAD_BALANCE=ON)(FAILOVER=ON)\
+                (ADDRESS=(PROTOCOL=TCP)(HOST=rac1.example.com)(PORT=1521))\
+                (ADDRESS=(PROTOCOL=TCP)(HOST=rac2.example.com)(PORT=1521))\
+                (ADDRESS=(PROTOCOL=TCP)(HOST=rac3.example.com)(PORT=1522)))\
+                (CONNECT_DATA=(SERVICE_NAME=sales)))");
+
+    let standby = ConnectString::new("primary", 1521, "sales")
+        .address("standby", 1521)
+        .build()?;
+    assert_eq!(standby,
+               "(DESCRIPTION=(ADDRESS_LIST=\
+                (ADDRESS=(PROTOCOL=TCP)(HOST=primary)(PORT=1521))\
+                (ADDRESS=(PROTOCOL=TCP)(HOST=standby)(PORT=1521)))\
+                (CONNECT_DATA=(SERVICE_NAME=sales)))");
+
+    let no_failover = ConnectString::new("primary", 1521, "sales")
+        .failover(false)
+        .build()?;
+    assert_eq!(no_failover,
+               "(DESCRIPTION=(ADDRESS_LIST=(FAILOVER=OFF)\
+                (ADDRESS=(PROTOCOL=TCP)(HOST=primary)(PORT=1521)))\
+                (CONNECT_DATA=(SERVICE_NAME=sales)))");
+
+    assert!(ConnectString::new("", 1521, "orclpdb").build().is_err());
+    assert!(ConnectString::new("host", 1521, "orclpdb").address("bad host", 1521).build().is_err());
+    assert!(ConnectString::new("host", 1521, "bad name").build().is_err());
+    assert!(ConnectString::new("host)(", 1521, "orclpdb").build().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn connect_string() {
+    check!(connect_string_res)
+}
@@ -1,50 +1,37 @@
-use CREDS;
+use ODPIC_CONFIG;
 use chrono::{TimeZone, Utc};
-use mimir::{Connection, Context, Data, Object, ObjectAttr, ObjectType, ODPIData,
-            ODPIObjectAttrInfo, ODPIObjectTypeInfo, ODPIStr, Statement};
+use mimir::{Context, Data, Object, ObjectAttr, ObjectAttrInfo, ObjectType, ObjectTypeInfo,
+            Statement};
 use mimir::enums;
 use mimir::error::Result;
 use mimir::flags;
-use std::ffi::CString;
 
-fn validate_object_attr_info(idx: usize, attr_info: &ODPIObjectAttrInfo) -> Result<()> {
-    let name_s = ODPIStr::new(attr_info.name, attr_info.name_length);
-    let name: String = name_s.into();
+fn validate_object_attr_info(idx: usize, attr_info: &ObjectAttrInfo) -> Result<()> {
     match idx {
-        0 => assert_eq!(name, "NUMBERVALUE"),
-        1 => assert_eq!(name, "STRINGVALUE"),
-        2 => assert_eq!(name, "FIXEDCHARVALUE"),
-        3 => assert_eq!(name, "DATEVALUE"),
-        4 => assert_eq!(name, "TIMESTAMPVALUE"),
-        5 => assert_eq!(name, "SUBOBJECTVALUE"),
-        6 => assert_eq!(name, "SUBOBJECTARRAY"),
+        0 => assert_eq!(attr_info.name(), "NUMBERVALUE"),
+        1 => assert_eq!(attr_info.name(), "STRINGVALUE"),
+        2 => assert_eq!(attr_info.name(), "FIXEDCHARVALUE"),
+        3 => assert_eq!(attr_info.name(), "DATEVALUE"),
+        4 => assert_eq!(attr_info.name(), "TIMESTAMPVALUE"),
+        5 => assert_eq!(attr_info.name(), "SUBOBJECTVALUE"),
+        6 => assert_eq!(attr_info.name(), "SUBOBJECTARRAY"),
         _ => assert!(false),
     }
     Ok(())
 }
 
-fn validate_object_type_info(type_info: &ODPIObjectTypeInfo) -> Result<()> {
-    let schema = ODPIStr::new(type_info.schema, type_info.schema_length);
-    let name = ODPIStr::new(type_info.name, type_info.name_length);
-    let schema_str: String = schema.into();
-    let name_str: String = name.into();
-
-    assert_eq!(schema_str, "ODPIC");
-    assert_eq!(name_str, "UDT_OBJECT");
-    assert_eq!(type_info.is_collection, 0);
-    // assert_eq!(type_info.element_oracle_type_num, Max);
-    assert_eq!(type_info.element_default_native_type_num,
-               enums::ODPINativeTypeNum::Invalid);
-    assert!(type_info.element_object_type.is_null());
-    assert_eq!(type_info.num_attributes, 7);
+fn validate_object_type_info(type_info: &ObjectTypeInfo) -> Result<()> {
+    assert_eq!(type_info.schema(), "ODPIC");
+    assert_eq!(type_info.name(), "UDT_OBJECT");
+    assert!(!type_info.is_collection());
+    assert!(type_info.element().is_none());
+    assert_eq!(type_info.num_attributes(), 7);
 
     Ok(())
 }
 
-fn validate_bytes(idx: usize, attr_data: &ODPIData) -> Result<()> {
-    let data_bytes = unsafe { attr_data.value.as_bytes };
-    let o_str = ODPIStr::new(data_bytes.ptr, data_bytes.length);
-    let data_str: String = o_str.into();
+fn validate_bytes(idx: usize, attr_data: &Data) -> Result<()> {
+    let data_str = attr_data.get_string();
     if idx == 1 {
         assert_eq!(data_str, "First row");
     } else if idx == 2 {
@@ -55,24 +42,17 @@ fn validate_bytes(idx: usize, attr_data: &ODPIData) -> Result<()> {
     Ok(())
 }
 
-fn validate_double(idx: usize, attr_data: &ODPIData) -> Result<()> {
+fn validate_double(idx: usize, attr_data: &Data) -> Result<()> {
     if idx == 0 {
-        unsafe { assert!(attr_data.value.as_double - 1.0 < ::std::f64::EPSILON) };
+        assert!(attr_data.get_double() - 1.0 < ::std::f64::EPSILON);
     } else {
         assert!(false);
     }
     Ok(())
 }
 
-fn validate_timestamp(idx: usize, attr_data: &ODPIData) -> Result<()> {
-    let odpi_ts = unsafe { attr_data.value.as_timestamp };
-    let y = odpi_ts.year as i32;
-    let m = odpi_ts.month as u32;
-    let d = odpi_ts.day as u32;
-    let h = odpi_ts.hour as u32;
-    let mi = odpi_ts.minute as u32;
-    let se = odpi_ts.second as u32;
-    let ts = Utc.ymd(y, m, d).and_hms_nano(h, mi, se, odpi_ts.fsecond);
+fn validate_timestamp(idx: usize, attr_data: &Data) -> Result<()> {
+    let ts = attr_data.get_utc();
 
     if idx == 3 {
         let expected = Utc.ymd(2007, 3, 6).and_hms_nano(0, 0, 0, 0);
@@ -87,84 +67,71 @@ fn validate_timestamp(idx: usize, attr_data: &ODPIData) -> Result<()> {
 }
 
 fn validate_subobject(obj_type: &ObjectType) -> Result<()> {
-    let type_info = obj_type.get_info()?;
-    let schema = ODPIStr::new(type_info.schema, type_info.schema_length);
-    let name = ODPIStr::new(type_info.name, type_info.name_length);
-    let schema_str: String = schema.into();
-    let name_str: String = name.into();
-
-    assert_eq!(schema_str, "ODPIC");
-    assert_eq!(name_str, "UDT_SUBOBJECT");
-    assert_eq!(type_info.is_collection, 0);
-    assert_eq!(type_info.num_attributes, 2);
+    let type_info = obj_type.get_type_info()?;
+    assert_eq!(type_info.schema(), "ODPIC");
+    assert_eq!(type_info.name(), "UDT_SUBOBJECT");
+    assert!(!type_info.is_collection());
+    assert_eq!(type_info.num_attributes(), 2);
+    assert!(type_info.element().is_none());
     Ok(())
 }
 
 fn validate_objectarr(obj_type: &ObjectType) -> Result<()> {
-    let type_info = obj_type.get_info()?;
-    let schema = ODPIStr::new(type_info.schema, type_info.schema_length);
-    let name = ODPIStr::new(type_info.name, type_info.name_length);
-    let schema_str: String = schema.into();
-    let name_str: String = name.into();
-
-    assert_eq!(schema_str, "ODPIC");
-    assert_eq!(name_str, "UDT_OBJECTARRAY");
-    assert_eq!(type_info.is_collection, 1);
-    assert_eq!(type_info.element_oracle_type_num,
-               enums::ODPIOracleTypeNum::Object);
-    assert_eq!(type_info.element_default_native_type_num,
+    let type_info = obj_type.get_type_info()?;
+    assert_eq!(type_info.schema(), "ODPIC");
+    assert_eq!(type_info.name(), "UDT_OBJECTARRAY");
+    assert!(type_info.is_collection());
+
+    let element = type_info.element().expect("collection without element info");
+    assert_eq!(element.oracle_type_num(), enums::ODPIOracleTypeNum::Object);
+    assert_eq!(element.default_native_type_num(),
                enums::ODPINativeTypeNum::Object);
-    assert!(!type_info.element_object_type.is_null());
-
-    let arr_obj_type: ObjectType = type_info.element_object_type.into();
-    validate_subobject(&arr_obj_type)?;
+    let element_type = element.object_type().expect("object elements without a type");
+    validate_subobject(element_type)?;
 
     Ok(())
 }
 
-fn validate_object(idx: usize, attr_info: &ODPIObjectAttrInfo, attr_data: &ODPIData) -> Result<()> {
-    let nested_obj_type_ptr = attr_info.object_type;
-
-    if nested_obj_type_ptr.is_null() {
-        assert!(false);
-    } else {
-        let nested_obj_type: ObjectType = nested_obj_type_ptr.into();
-        let odpi_obj_ptr = unsafe { attr_data.value.as_object };
-        let odpi_obj: Object = odpi_obj_ptr.into();
-
-        if idx == 5 {
-            validate_subobject(&nested_obj_type)?;
-        } else if idx == 6 {
-            validate_objectarr(&nested_obj_type)?;
-
-            let (first_index, first_index_exists) = odpi_obj.get_first_index()?;
-            assert_eq!(first_index, 0);
-            assert!(first_index_exists);
-
-            let (last_index, last_index_exists) = odpi_obj.get_last_index()?;
-            assert_eq!(last_index, 1);
-            assert!(last_index_exists);
-
-            let (next_index, next_index_exists) = odpi_obj.get_next_index(0)?;
-            assert_eq!(next_index, 1);
-            assert!(next_index_exists);
-            let (next_index_1, next_index_exists_1) = odpi_obj.get_next_index(1)?;
-            assert_eq!(next_index_1, 0);
-            assert!(!next_index_exists_1);
-
-            let (prev_index, prev_index_exists) = odpi_obj.get_prev_index(1)?;
-            assert_eq!(prev_index, 0);
-            assert!(prev_index_exists);
-            let (prev_index_1, prev_index_exists_1) = odpi_obj.get_prev_index(0)?;
-            assert_eq!(prev_index_1, 0);
-            assert!(!prev_index_exists_1);
-
-            let mut size = odpi_obj.get_size()?;
-            assert_eq!(size, 2);
-
-            odpi_obj.trim(1)?;
-            size = odpi_obj.get_size()?;
-            assert_eq!(size, 1);
+fn validate_object(idx: usize, attr_info: &ObjectAttrInfo, attr_data: &Data) -> Result<()> {
+    match attr_info.object_type() {
+        None => assert!(false),
+        Some(nested_obj_type) => {
+            let odpi_obj: Object = attr_data.get_object().into();
+
+            if idx == 5 {
+                validate_subobject(nested_obj_type)?;
+            } else if idx == 6 {
+                validate_objectarr(nested_obj_type)?;
+
+                let (first_index, first_index_exists) = odpi_obj.get_first_index()?;
+                assert_eq!(first_index, 0);
+                assert!(first_index_exists);
+
+                let (last_index, last_index_exists) = odpi_obj.get_last_index()?;
+                assert_eq!(last_index, 1);
+                assert!(last_index_exists);
+
+                let (next_index, next_index_exists) = odpi_obj.get_next_index(0)?;
+                assert_eq!(next_index, 1);
+                assert!(next_index_exists);
+                let (next_index_1, next_index_exists_1) = odpi_obj.get_next_index(1)?;
+                assert_eq!(next_index_1, 0);
+                assert!(!next_index_exists_1);
+
+                let (prev_index, prev_index_exists) = odpi_obj.get_prev_index(1)?;
+                assert_eq!(prev_index, 0);
+                assert!(prev_index_exists);
+                let (prev_index_1, prev_index_exists_1) = odpi_obj.get_prev_index(0)?;
+                assert_eq!(prev_index_1, 0);
+                assert!(!prev_index_exists_1);
+
+                let mut size = odpi_obj.get_size()?;
+                assert_eq!(size, 2);
+
+                odpi_obj.trim(1)?;
+                size = odpi_obj.get_size()?;
+                assert_eq!(size, 1);
+            }
         }
     }
     Ok(())
@@ -173,10 +140,11 @@ fn validate_object(idx: usize, attr_info: &ODPIObjectAttrInfo, attr_data: &ODPID
 fn validate_query_value(idx: usize,
                         obj: &Object,
                         obj_attr: &ObjectAttr,
-                        attr_info: &ODPIObjectAttrInfo)
+                        attr_info: &ObjectAttrInfo)
                         -> Result<()> {
-    let attr_data = obj.get_attribute_value(obj_attr, attr_info)?;
-    match attr_info.default_native_type_num {
+    let native_type = attr_info.default_native_type_num();
+    let attr_data = obj.get_attribute_value(obj_attr, native_type)?;
+    match native_type {
         enums::ODPINativeTypeNum::Bytes => validate_bytes(idx, &attr_data)?,
         enums::ODPINativeTypeNum::Double => validate_double(idx, &attr_data)?,
         enums::ODPINativeTypeNum::Timestamp => validate_timestamp(idx, &attr_data)?,
@@ -190,21 +158,16 @@ fn validate_query_value(idx: usize,
 
 #[cfg_attr(feature = "cargo-clippy", allow(used_underscore_binding))]
 fn validate_object_type(object_col: &Statement, object_type: &ObjectType) -> Result<()> {
-    let attrs = object_type.get_attributes(7)?;
-    let mut obj_attrs = Vec::new();
+    let obj_attrs = object_type.get_attributes(7)?;
     let mut attr_infos = Vec::new();
 
-    for (idx, obj_attr) in attrs.iter().enumerate() {
-        let attr: ObjectAttr = (*obj_attr).into();
-        let attr_info = attr.get_info()?;
-
+    for (idx, attr) in obj_attrs.iter().enumerate() {
+        let attr_info = attr.get_attr_info()?;
         validate_object_attr_info(idx, &attr_info)?;
-
-        obj_attrs.push(attr);
         attr_infos.push(attr_info);
     }
 
-    let type_info = object_type.get_info()?;
+    let type_info = object_type.get_type_info()?;
     validate_object_type_info(&type_info)?;
 
     object_col.fetch()?;
@@ -217,9 +180,8 @@ fn validate_object_type(object_col: &Statement, object_type: &ObjectType) -> Res
     // assert_eq!(exists, 1);
 
     // Get the object value out of the query.
-    let (object_col_type, object_col_ptr) = object_col.get_query_value(1)?;
+    let (object_col_type, data) = object_col.get_query_value(1)?;
     assert_eq!(object_col_type, enums::ODPINativeTypeNum::Object);
-    let data: Data = object_col_ptr.into();
     let obj: Object = data.get_object().into();
 
     for (idx, (obj_attr, attr_info)) in obj_attrs.iter().zip(attr_infos.iter()).enumerate() {
@@ -233,17 +195,7 @@ fn validate_object_type(object_col: &Statement, object_type: &ObjectType) -> Res
 }
 
 fn obj_type(ctxt: &Context) -> Result<()> {
-    let mut ccp = ctxt.init_common_create_params()?;
-    let enc_cstr = CString::new("UTF-8").expect("badness");
-    ccp.set_encoding(enc_cstr.as_ptr());
-    ccp.set_nchar_encoding(enc_cstr.as_ptr());
-
-    let conn = Connection::create(ctxt,
-                                  Some(&CREDS[2]),
-                                  Some(&CREDS[3]),
-                                  Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
-                                  Some(ccp),
-                                  None)?;
+    let conn = ODPIC_CONFIG.connect(ctxt)?;
 
     conn.add_ref()?;
 
@@ -262,6 +214,17 @@ fn obj_type(ctxt: &Context) -> Result<()> {
 
     if let Some(object_type) = query_info.object_type() {
         validate_object_type(&object_col, &object_type)?;
+
+        // Create a variable of this object type.
+        let obj_var = conn.new_var(enums::ODPIOracleTypeNum::Object,
+                                   enums::ODPINativeTypeNum::Object,
+                                   1,
+                                   0,
+                                   false,
+                                   false,
+                                   Some(&object_type))?;
+        assert_eq!(obj_var.get_num_elements_in_array()?, 1);
+        obj_var.release()?;
     }
 
     object_col.close(None)?;
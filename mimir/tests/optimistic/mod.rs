@@ -0,0 +1,67 @@
+use CONFIG;
+use mimir::{Context, VersionedUpdate};
+use mimir::error::{Error, ErrorKind, Result};
+use mimir::flags;
+
+fn sql_res() -> Result<()> {
+    let update = VersionedUpdate::new("hr.accounts", "version")?
+        .key("id")?
+        .column("balance")?
+        .column("name")?;
+    assert_eq!(update.sql()?,
+               "UPDATE HR.ACCOUNTS SET BALANCE = :BALANCE, NAME = :NAME, VERSION = VERSION + 1 \
+                WHERE ID = :ID AND VERSION = :OLD_VERSION");
+
+    assert!(VersionedUpdate::new("accounts; drop table x", "version").is_err());
+    assert!(VersionedUpdate::new("accounts", "version")?.sql().is_err());
+    assert!(VersionedUpdate::new("accounts", "version")?.key("version").is_err());
+    assert!(VersionedUpdate::new("accounts", "version")?.key("id")?.column("ID").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn sql() {
+    check!(sql_res)
+}
+
+fn stale_row_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    conn.run_script("create table mimir_optimistic \
+                     (id number primary key, balance number, version number);\n\
+                     insert into mimir_optimistic values (1, 10, 1);\n\
+                     commit;")?;
+    let update = VersionedUpdate::new("mimir_optimistic", "version")?
+        .key("id")?
+        .column("balance")?;
+
+    {
+        let txn = conn.begin_transaction()?;
+        let version = update.execute(&txn, &[("id", 1.into()), ("balance", 20.into())], 1)?;
+        assert_eq!(version, 2);
+        txn.commit()?;
+    }
+
+    {
+        let txn = conn.begin_transaction()?;
+        match update.execute(&txn, &[("id", 1.into()), ("balance", 30.into())], 1) {
+            Err(Error(ErrorKind::StaleRow(ref table, version), _)) => {
+                assert_eq!(table, "MIMIR_OPTIMISTIC");
+                assert_eq!(version, 1);
+            }
+            Err(e) => return Err(e),
+            Ok(_) => panic!("a stale row was updated"),
+        }
+    }
+
+    conn.run_script("drop table mimir_optimistic")?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn stale_row() {
+    check_with_ctxt!(stale_row_res)
+}
@@ -0,0 +1,73 @@
+use mimir::OracleType;
+use mimir::enums::ODPINativeTypeNum::{Bytes, Double, Int64};
+use mimir::enums::ODPIOracleTypeNum;
+use mimir::error::{Error, ErrorKind, Result};
+
+fn display_parse_res() -> Result<()> {
+    let types = [(OracleType::Varchar2(256), "VARCHAR2(256)"),
+                 (OracleType::Number(38, 0), "NUMBER(38,0)"),
+                 (OracleType::Number(0, 0), "NUMBER"),
+                 (OracleType::Number(126, -127), "FLOAT(126)"),
+                 (OracleType::TimestampTz(6), "TIMESTAMP(6) WITH TIME ZONE"),
+                 (OracleType::TimestampLtz(3), "TIMESTAMP(3) WITH LOCAL TIME ZONE"),
+                 (OracleType::IntervalDS, "INTERVAL DAY TO SECOND"),
+                 (OracleType::LongRaw, "LONG RAW")];
+
+    for &(oracle_type, name) in &types {
+        assert_eq!(oracle_type.to_string(), name);
+        assert_eq!(name.parse::<OracleType>()?, oracle_type);
+    }
+
+    assert_eq!("  timestamp  with time zone ".parse::<OracleType>()?,
+               OracleType::TimestampTz(6));
+    assert_eq!("number(10)".parse::<OracleType>()?, OracleType::Number(10, 0));
+
+    for invalid in &["VARCHAR2", "NUMBER(39)", "DATE(1)", "TIMESTAMP(10)", "VARCHAR(10"] {
+        match invalid.parse::<OracleType>() {
+            Err(Error(ErrorKind::OracleType(_), _)) => {}
+            Err(e) => return Err(e),
+            Ok(oracle_type) => panic!("'{}' parsed as {}", invalid, oracle_type),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn display_parse() {
+    check!(display_parse_res)
+}
+
+#[test]
+fn odpi_mapping() {
+    let number = OracleType::from_odpi(ODPIOracleTypeNum::Number, 0, 10, 0);
+    assert_eq!(number, Some(OracleType::Number(10, 0)));
+    assert_eq!(OracleType::Number(10, 0).native_type_num(), Int64);
+    assert_eq!(OracleType::Number(38, 2).native_type_num(), Double);
+    assert_eq!(OracleType::Varchar2(20).native_type_num(), Bytes);
+    assert_eq!(OracleType::Varchar2(20).size(), 20);
+    assert_eq!(OracleType::Clob.oracle_type_num(), ODPIOracleTypeNum::Clob);
+    assert!(OracleType::from_odpi(ODPIOracleTypeNum::TypeNone, 0, 0, 0).is_none());
+}
+
+#[test]
+fn conversion_loss() {
+    use mimir::ConversionLoss::{Fraction, Overflow, Precision};
+    use mimir::enums::ODPINativeTypeNum::Float;
+
+    assert_eq!(OracleType::Number(10, 0).conversion_loss(Int64), None);
+    assert_eq!(OracleType::Number(18, 0).conversion_loss(Int64), None);
+    assert_eq!(OracleType::Number(10, 2).conversion_loss(Int64), Some(Fraction));
+    assert_eq!(OracleType::Number(0, -127).conversion_loss(Int64), Some(Fraction));
+    assert_eq!(OracleType::Number(19, 0).conversion_loss(Int64), Some(Overflow));
+    assert_eq!(OracleType::Number(17, -2).conversion_loss(Int64), Some(Overflow));
+    assert_eq!(OracleType::BinaryDouble.conversion_loss(Int64), Some(Fraction));
+    assert_eq!(OracleType::Number(15, 2).conversion_loss(Double), None);
+    assert_eq!(OracleType::Number(38, 2).conversion_loss(Double), Some(Precision));
+    assert_eq!(OracleType::Number(0, -127).conversion_loss(Double), Some(Precision));
+    assert_eq!(OracleType::Number(53, -127).conversion_loss(Double), None);
+    assert_eq!(OracleType::Number(126, -127).conversion_loss(Double), Some(Precision));
+    assert_eq!(OracleType::BinaryDouble.conversion_loss(Float), Some(Precision));
+    assert_eq!(OracleType::Number(6, 0).conversion_loss(Float), None);
+    assert_eq!(OracleType::Varchar2(20).conversion_loss(Bytes), None);
+}
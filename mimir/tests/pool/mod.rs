@@ -1,23 +1,14 @@
-use CREDS;
+use CONFIG;
 use mimir::{Context, Data, Pool};
 use mimir::enums;
 use mimir::enums::ODPINativeTypeNum::{Bytes, Double};
-use mimir::error::Result;
+use mimir::error::{Error, ErrorKind, Result};
 use mimir::flags;
-use std::ffi::CString;
+use std::thread;
+use std::time::{Duration, Instant};
 
 fn pool_res(ctxt: &Context) -> Result<()> {
-    let mut ccp = ctxt.init_common_create_params()?;
-    let enc_cstr = CString::new("UTF-8").expect("badness");
-    ccp.set_encoding(enc_cstr.as_ptr());
-    ccp.set_nchar_encoding(enc_cstr.as_ptr());
-
-    let pool = Pool::create(ctxt,
-                            Some(&CREDS[0]),
-                            Some(&CREDS[1]),
-                            Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
-                            Some(ccp),
-                            None)?;
+    let pool = CONFIG.create_pool(ctxt, None)?;
     pool.add_ref()?;
 
     let ei = pool.get_encoding_info()?;
@@ -86,6 +77,19 @@ fn pool_res(ctxt: &Context) -> Result<()> {
     stmt.release()?;
     conn.release()?;
     conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    // purge / close_drop test
+    let idle_count = pool.get_open_count()? - pool.get_busy_count()?;
+    assert_eq!(pool.purge()?, idle_count);
+    assert_eq!(pool.get_open_count()?, 0);
+    assert_eq!(pool.get_get_mode()?, enums::ODPIPoolGetMode::ForceGet);
+
+    let broken = pool.acquire_connection(None, None, None)?;
+    assert_eq!(pool.get_open_count()?, 1);
+    broken.close_drop()?;
+    broken.release()?;
+    assert_eq!(pool.get_open_count()?, 0);
+
     pool.release()?;
     pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT)?;
 
@@ -96,3 +100,238 @@ fn pool_res(ctxt: &Context) -> Result<()> {
 fn pool() {
     check_with_ctxt!(pool_res)
 }
+
+fn pool_backpressure_res(ctxt: &Context) -> Result<()> {
+    let mut pool_cp = ctxt.init_pool_create_params()?;
+    pool_cp.set_min_sessions(1);
+    pool_cp.set_max_sessions(1);
+    pool_cp.set_session_increment(0);
+    let pool = CONFIG.create_pool(ctxt, Some(pool_cp))?;
+    assert_eq!(pool.get_get_mode()?, enums::ODPIPoolGetMode::NoWait);
+    assert!(pool.get_wait_timeout().is_none());
+
+    let conn = pool.try_acquire(None, None, None)?.expect("a free session");
+    assert!(pool.try_acquire(None, None, None)?.is_none());
+
+    pool.set_get_mode(enums::ODPIPoolGetMode::Wait)?;
+    pool.set_wait_timeout(Some(Duration::from_millis(250)));
+    assert_eq!(pool.get_wait_timeout(), Some(Duration::from_millis(250)));
+
+    let start = Instant::now();
+    match pool.acquire_connection(None, None, None) {
+        Err(Error(ErrorKind::PoolTimeout(250), _)) => {}
+        Err(e) => return Err(e),
+        Ok(_) => panic!("acquired a session from an exhausted pool"),
+    }
+    assert!(start.elapsed() >= Duration::from_millis(250));
+    assert_eq!(pool.get_get_mode()?, enums::ODPIPoolGetMode::Wait);
+
+    // A wait timeout given for the call takes precedence over the one of the pool.
+    let mut conn_cp = ctxt.init_conn_create_params()?;
+    conn_cp.set_wait_timeout(Some(Duration::from_millis(50)));
+    match pool.acquire_connection(None, None, Some(conn_cp)) {
+        Err(Error(ErrorKind::PoolTimeout(50), _)) => {}
+        Err(e) => return Err(e),
+        Ok(_) => panic!("acquired a session from an exhausted pool"),
+    }
+    assert_eq!(pool.get_get_mode()?, enums::ODPIPoolGetMode::Wait);
+
+    conn.release()?;
+    let conn = pool.acquire_connection(None, None, None)?;
+    conn.release()?;
+
+    pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT)?;
+
+    Ok(())
+}
+
+#[test]
+fn pool_backpressure() {
+    check_with_ctxt!(pool_backpressure_res)
+}
+
+fn session_init_res(ctxt: &Context) -> Result<()> {
+    let format_sql = "select value from nls_session_parameters \
+                      where parameter = 'NLS_DATE_FORMAT'";
+    let mut pool_cp = ctxt.init_pool_create_params()?;
+    pool_cp.set_min_sessions(1);
+    pool_cp.set_max_sessions(1);
+    pool_cp.set_session_increment(0);
+    let pool = CONFIG
+        .create_pool(ctxt, Some(pool_cp))?
+        .with_session_init_sql(&["alter session set nls_date_format = 'YYYY-MM-DD'"]);
+
+    let conn = pool.acquire_connection(None, None, None)?;
+    assert_eq!(conn.get_tag_found(), Some(false));
+    assert_eq!(conn.get_acquired_tag(), None);
+    let format: String = conn.query_scalar(format_sql, &[])?;
+    assert_eq!(format, "YYYY-MM-DD");
+    conn.run_script("alter session set nls_date_format = 'DD.MM.YYYY'")?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    // The session is tagged as initialized, so the statements are not run again.
+    let conn = pool.acquire_connection(None, None, None)?;
+    assert_eq!(conn.get_tag_found(), Some(true));
+    assert!(conn.get_acquired_tag().is_some());
+    let format: String = conn.query_scalar(format_sql, &[])?;
+    assert_eq!(format, "DD.MM.YYYY");
+    conn.close_drop()?;
+    conn.release()?;
+
+    let conn = pool.acquire_connection(None, None, None)?;
+    let format: String = conn.query_scalar(format_sql, &[])?;
+    assert_eq!(format, "YYYY-MM-DD");
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    let standalone = CONFIG.connect(ctxt)?;
+    assert_eq!(standalone.get_tag_found(), None);
+    assert_eq!(standalone.get_acquired_tag(), None);
+    standalone.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    standalone.release()?;
+
+    pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT)?;
+    pool.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn session_init() {
+    check_with_ctxt!(session_init_res)
+}
+
+fn warm_up_res(ctxt: &Context) -> Result<()> {
+    let mut pool_cp = ctxt.init_pool_create_params()?;
+    pool_cp.set_min_sessions(2);
+    pool_cp.set_max_sessions(3);
+    pool_cp.set_session_increment(1);
+    let pool = CONFIG
+        .create_pool(ctxt, Some(pool_cp))?
+        .with_session_init_sql(&["alter session set nls_date_format = 'YYYY-MM-DD'"]);
+    assert_eq!(pool.get_min_sessions(), 2);
+    pool.set_get_mode(enums::ODPIPoolGetMode::Wait)?;
+
+    // The minimum sessions are opened with the pool, so none are newly opened.
+    assert_eq!(pool.warm_up()?, 0);
+    let open_count = pool.get_open_count()?;
+    assert!(open_count >= 2);
+    assert_eq!(pool.get_busy_count()?, 0);
+    assert_eq!(pool.get_get_mode()?, enums::ODPIPoolGetMode::Wait);
+
+    // A dropped session is opened again.
+    let dropped = pool.acquire_connection(None, None, None)?;
+    dropped.close_drop()?;
+    dropped.release()?;
+    assert_eq!(pool.get_open_count()?, open_count - 1);
+    assert_eq!(pool.warm_up()?, 1);
+    assert_eq!(pool.get_open_count()?, open_count);
+
+    // The warmed sessions are initialized and tagged.
+    let conn = pool.acquire_connection(None, None, None)?;
+    assert_eq!(conn.get_tag_found(), Some(true));
+
+    // Sessions held elsewhere are not counted, and no session is opened beyond the maximum.
+    let other = pool.acquire_connection(None, None, None)?;
+    let third = pool.acquire_connection(None, None, None)?;
+    assert_eq!(pool.warm_up()?, 0);
+    assert_eq!(pool.get_get_mode()?, enums::ODPIPoolGetMode::Wait);
+    third.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    third.release()?;
+
+    for conn in &[conn, other] {
+        conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+        conn.release()?;
+    }
+    pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT)?;
+    pool.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn warm_up() {
+    check_with_ctxt!(warm_up_res)
+}
+
+fn close_busy_res(ctxt: &Context) -> Result<()> {
+    let pool = CONFIG.create_pool(ctxt, None)?;
+    let conn = pool.acquire_connection(None, None, None)?;
+
+    match pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT) {
+        Err(Error(ErrorKind::PoolBusy(busy), _)) => assert_eq!(busy, 1),
+        Err(e) => return Err(e),
+        Ok(_) => panic!("the pool closed with a busy session"),
+    }
+
+    let start = Instant::now();
+    assert_eq!(pool.close_gracefully(Duration::from_millis(200))?, 1);
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    conn.release()?;
+    pool.release()?;
+
+    let pool = CONFIG.create_pool(ctxt, None)?;
+    let conn = pool.acquire_connection(None, None, None)?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+    assert_eq!(pool.close_gracefully(Duration::from_secs(1))?, 0);
+    pool.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn close_busy() {
+    check_with_ctxt!(close_busy_res)
+}
+
+fn thread_handle_res(ctxt: &Context) -> Result<()> {
+    let pool = CONFIG.create_pool(ctxt, None)?;
+    match pool.thread_handle() {
+        Err(Error(ErrorKind::Pool(_), _)) => {}
+        Err(e) => return Err(e),
+        Ok(_) => panic!("a pool created without DPI_MODE_CREATE_THREADED was shared"),
+    }
+    pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT)?;
+    pool.release()?;
+
+    let mut common_create = CONFIG.init_common_create_params(ctxt)?;
+    let create_mode = common_create.get_create_mode() | flags::DPI_MODE_CREATE_THREADED;
+    common_create.set_create_mode(create_mode);
+    let mut pool_cp = ctxt.init_pool_create_params()?;
+    pool_cp.set_min_sessions(1);
+    pool_cp.set_max_sessions(1);
+    pool_cp.set_session_increment(0);
+    let pool = Pool::create(ctxt, &*CONFIG, Some(common_create), Some(pool_cp))?
+        .with_session_init_sql(&["alter session set nls_date_format = 'YYYY-MM-DD'"]);
+    pool.set_stmt_cache_stats(true);
+    let handle = pool.thread_handle()?;
+    let worker = thread::spawn(move || -> Result<()> {
+        let pool = handle.into_pool();
+        let conn = pool.acquire_connection(None, None, None)?;
+        // The session initialization statements and the context travel with the handle, so the
+        // session is initialized and an exhausted pool is reported as such.
+        let format: String = conn.query_scalar("select value from nls_session_parameters \
+                                                where parameter = 'NLS_DATE_FORMAT'",
+                                               &[])?;
+        assert_eq!(format, "YYYY-MM-DD");
+        assert!(pool.try_acquire(None, None, None)?.is_none());
+        conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+        conn.release()?;
+        pool.release()
+    });
+    worker.join().expect("worker panicked")?;
+    assert!(pool.get_stmt_cache_stats().get_prepares() > 0);
+    // A handle dropped without being used releases its reference.
+    drop(pool.thread_handle()?);
+    pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT)?;
+    pool.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn thread_handle() {
+    check_with_ctxt!(thread_handle_res)
+}
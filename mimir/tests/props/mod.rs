@@ -0,0 +1,154 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use mimir::{Data, ExecMode, OracleType, SubscrQos, YearsMonths, flags};
+use mimir::enums::{ODPINativeTypeNum, ODPIOracleTypeNum};
+use mimir::raw::ODPIStr;
+use proptest::prelude::*;
+
+/// The scale of a FLOAT column.
+const FLOAT_SCALE: i8 = -127;
+
+fn data() -> Data<'static> {
+    Data::default()
+}
+
+/// Timestamps between the years 1 and 9999, the range of an Oracle DATE, with nanoseconds.
+fn utc() -> BoxedStrategy<DateTime<Utc>> {
+    (1i32..10_000, 1u32..13, 1u32..29, 0u32..24, 0u32..60, 0u32..60, 0u32..1_000_000_000)
+        .prop_map(|(y, m, d, h, mm, s, ns)| {
+                      let naive = NaiveDate::from_ymd(y, m, d).and_hms_nano(h, mm, s, ns);
+                      DateTime::from_utc(naive, Utc)
+                  })
+        .boxed()
+}
+
+/// Types with a canonical SQL name, i.e. those `Display` writes in the form `FromStr` parses.
+fn oracle_type() -> BoxedStrategy<OracleType> {
+    prop_oneof![(1u32..32_768).prop_map(OracleType::Varchar2),
+                (1u32..32_768).prop_map(OracleType::NVarchar2),
+                (1u32..2_001).prop_map(OracleType::Char),
+                (1u32..2_001).prop_map(OracleType::NChar),
+                (1u32..32_768).prop_map(OracleType::Raw),
+                (1i16..39, -84i8..127).prop_map(|(p, s)| OracleType::Number(p, s)),
+                (1i16..127).prop_map(|p| OracleType::Number(p, FLOAT_SCALE)),
+                (0u8..10).prop_map(OracleType::Timestamp),
+                (0u8..10).prop_map(OracleType::TimestampTz),
+                (0u8..10).prop_map(OracleType::TimestampLtz),
+                Just(OracleType::Rowid),
+                Just(OracleType::BinaryFloat),
+                Just(OracleType::BinaryDouble),
+                Just(OracleType::Date),
+                Just(OracleType::IntervalDS),
+                Just(OracleType::IntervalYM),
+                Just(OracleType::Clob),
+                Just(OracleType::Blob),
+                Just(OracleType::LongRaw)]
+            .boxed()
+}
+
+proptest! {
+    #[test]
+    fn odpi_str_round_trip(s in ".*") {
+        let odpi_s = ODPIStr::from(&s[..]);
+        prop_assert_eq!(odpi_s.len() as usize, s.len());
+        prop_assert_eq!(String::from(odpi_s), s);
+    }
+
+    #[test]
+    fn duration_round_trip(nanos in -86_400_000_000_000_000i64..86_400_000_000_000_000) {
+        let duration = Duration::nanoseconds(nanos);
+        let data = data();
+        data.set_duration(duration);
+        prop_assert_eq!(data.get_duration(), duration);
+    }
+
+    #[test]
+    fn utc_round_trip(datetime in utc()) {
+        let data = data();
+        data.set_utc(datetime);
+        prop_assert_eq!(data.get_utc(), datetime);
+    }
+
+    #[test]
+    fn years_months_round_trip(months in -119_999i32..120_000) {
+        let interval = YearsMonths::from_months(months);
+        prop_assert_eq!(interval.total_months(), months);
+
+        let data = data();
+        data.set_years_months(interval);
+        prop_assert_eq!(data.get_years_months(), interval);
+    }
+
+    #[test]
+    fn oracle_type_round_trip(oracle_type in oracle_type()) {
+        let parsed: OracleType = oracle_type.to_string().parse().expect("a valid type name");
+        prop_assert_eq!(parsed, oracle_type);
+        let lower: OracleType = oracle_type.to_string()
+            .to_lowercase()
+            .parse()
+            .expect("a valid type name");
+        prop_assert_eq!(lower, oracle_type);
+    }
+
+    #[test]
+    fn native_type_num_from_i32(val in prop_oneof![2_990i32..3_020, any::<i32>()]) {
+        let native_type = ODPINativeTypeNum::from(val);
+        if val >= 3_000 && val <= 3_012 {
+            prop_assert_eq!(native_type as i32, val);
+        } else {
+            prop_assert_eq!(native_type, ODPINativeTypeNum::Invalid);
+        }
+    }
+
+    #[test]
+    fn oracle_type_num_round_trip(oracle_type in oracle_type()) {
+        let (size, precision, scale) = match oracle_type {
+            OracleType::Varchar2(size) |
+            OracleType::NVarchar2(size) |
+            OracleType::Char(size) |
+            OracleType::NChar(size) |
+            OracleType::Raw(size) => (size, 0, 0),
+            OracleType::Number(precision, scale) => (0, precision, scale),
+            OracleType::Timestamp(fsprecision) |
+            OracleType::TimestampTz(fsprecision) |
+            OracleType::TimestampLtz(fsprecision) => (0, i16::from(fsprecision), 0),
+            _ => (0, 0, 0),
+        };
+        let oracle_type_num = oracle_type.oracle_type_num();
+        prop_assert_ne!(oracle_type_num, ODPIOracleTypeNum::TypeNone);
+        let converted = OracleType::from_odpi(oracle_type_num, size, precision, scale);
+        prop_assert_eq!(converted, Some(oracle_type));
+    }
+
+    #[test]
+    fn exec_mode_flags(commit in any::<bool>(), batch in any::<bool>(), counts in any::<bool>()) {
+        let mut mode = ExecMode::default();
+        let mut expected = flags::DPI_MODE_EXEC_DEFAULT;
+        if commit {
+            mode = mode.commit_on_success();
+            expected |= flags::DPI_MODE_EXEC_COMMIT_ON_SUCCESS;
+        }
+        if batch {
+            mode = mode.batch_errors();
+            expected |= flags::DPI_MODE_EXEC_BATCH_ERRORS;
+        }
+        if counts {
+            mode = mode.array_dml_row_counts();
+            expected |= flags::DPI_MODE_EXEC_ARRAY_DML_ROWCOUNTS;
+        }
+        prop_assert_eq!(flags::ODPIExecMode::from(mode), expected);
+    }
+
+    #[test]
+    fn subscr_qos_flags(bits in 0u32..32) {
+        let qos = (0..5)
+            .filter(|bit| bits & (1 << bit) != 0)
+            .fold(SubscrQos::default(), |qos, bit| match bit {
+                0 => qos.reliable(),
+                1 => qos.deregister_on_notify(),
+                2 => qos.rowids(),
+                3 => qos.query(),
+                _ => qos.best_effort(),
+            });
+        prop_assert_eq!(qos.bits(), flags::ODPISubscrQOS::from_bits_truncate(bits));
+    }
+}
@@ -0,0 +1,83 @@
+use CONFIG;
+use mimir::{ConnectParams, Context, Databases};
+use mimir::error::{Error, ErrorKind, Result};
+use mimir::registry;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+fn parse_res() -> Result<()> {
+    let databases = registry::parse("# databases\n\
+                                     [reporting]\n\
+                                     username = \"report\"\n\
+                                     password = \"secret\"\n\
+                                     connect_string = \"//reports/ORCL\"\n\
+                                     \n\
+                                     [orders]\n\
+                                     username = \"orders\"\n\
+                                     password = \"secret\"\n\
+                                     connect_string = \"//orders/ORCL\"\n\
+                                     encoding = \"UTF-8\"\n")?;
+    let names: Vec<_> = databases.keys().map(|name| &name[..]).collect();
+    assert_eq!(names, ["orders", "reporting"]);
+    assert_eq!(databases["reporting"],
+               ConnectParams::new("report", "secret", "//reports/ORCL"));
+    assert_eq!(databases["orders"].get_encoding(), Some("UTF-8"));
+
+    for contents in &["username = \"report\"\n[reporting]\n",
+                      "[reporting\n",
+                      "[a]\nusername = \"a\"\npassword = \"a\"\nconnect_string = \"a\"\n[a]\n",
+                      "[reporting]\nusername = report\n",
+                      "[reporting]\nusername = \"report\"\n",
+                      "[reporting]\nusername = 1\n",
+                      "[reporting]\nuser = \"report\"\n"] {
+        match registry::parse(contents) {
+            Err(Error(ErrorKind::Config(_), _)) => {}
+            res => panic!("expected a config error for {:?}, got {:?}", contents, res),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn parse() {
+    check!(parse_res)
+}
+
+fn pool_res() -> Result<()> {
+    let mut params = BTreeMap::new();
+    params.insert("main".to_string(), CONFIG.clone());
+    let databases = Databases::new(Context::create()?, params);
+    assert_eq!(databases.names(), ["main"]);
+    assert_eq!(databases.get_params("main"), Some(&*CONFIG));
+
+    let pool = databases.pool("main")?;
+    assert!(Rc::ptr_eq(&pool, &databases.pool("main")?));
+    let conn = pool.acquire_connection(None, None, None)?;
+    conn.ping()?;
+    conn.release()?;
+    drop(pool);
+
+    match databases.pool("missing") {
+        Err(Error(ErrorKind::Config(_), _)) => {}
+        Err(e) => return Err(e),
+        Ok(_) => panic!("an unknown database has a pool"),
+    }
+
+    // A closed registry creates a new pool on the next lookup. That pool keeps the context alive
+    // after the registry is dropped, and is closed when it is dropped itself.
+    databases.close()?;
+    let pool = databases.pool("main")?;
+    drop(databases);
+    assert!(pool.is_context_alive());
+    let conn = pool.acquire_connection(None, None, None)?;
+    conn.ping()?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn pool() {
+    check!(pool_res)
+}
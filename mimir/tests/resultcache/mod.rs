@@ -0,0 +1,34 @@
+use mimir::{ConnectParams, ResultCacheMode, add_result_cache_hint};
+use mimir::error::Result;
+
+fn result_cache_hint_res() -> Result<()> {
+    assert_eq!(add_result_cache_hint("select * from username"),
+               "select /*+ RESULT_CACHE */ * from username");
+    assert_eq!(add_result_cache_hint("  SELECT id FROM username"),
+               "  SELECT /*+ RESULT_CACHE */ id FROM username");
+    assert_eq!(add_result_cache_hint("select /*+ FULL(u) */ * from username u"),
+               "select /*+ RESULT_CACHE FULL(u) */ * from username u");
+    assert_eq!(add_result_cache_hint("select /*+ result_cache */ * from username"),
+               "select /*+ result_cache */ * from username");
+    assert_eq!(add_result_cache_hint("with u as (select * from username) select id from u"),
+               "with u as (select * from username) select /*+ RESULT_CACHE */ id from u");
+    assert_eq!(add_result_cache_hint("-- the users\nselect 'select' from dual"),
+               "-- the users\nselect /*+ RESULT_CACHE */ 'select' from dual");
+    assert_eq!(add_result_cache_hint("update username set id = 1"),
+               "update username set id = 1");
+
+    assert_eq!("force".parse::<ResultCacheMode>()?, ResultCacheMode::Force);
+    assert_eq!("MANUAL".parse::<ResultCacheMode>()?, ResultCacheMode::Manual);
+    assert!("sometimes".parse::<ResultCacheMode>().is_err());
+
+    let params = ConnectParams::parse("username = u\npassword = p\nconnect_string = db\n\
+                                       result_cache_mode = force")?;
+    assert_eq!(params.get_result_cache_mode(), Some(ResultCacheMode::Force));
+
+    Ok(())
+}
+
+#[test]
+fn result_cache_hint() {
+    check!(result_cache_hint_res)
+}
@@ -0,0 +1,5 @@
+-- Schema objects used by the harness tests. These are kept apart from setup.sql, as the harness
+-- tests drop and recreate them while the other tests are running.
+create table mimir_harness_test (id number not null, name varchar2(30) not null);
+insert into mimir_harness_test values (1, 'one');
+insert into mimir_harness_test values (2, 'two');
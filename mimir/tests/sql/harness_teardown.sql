@@ -0,0 +1,2 @@
+-- Drops the schema objects created by harness_setup.sql.
+drop table mimir_harness_test purge;
@@ -0,0 +1,72 @@
+use mimir::error::{Error, ErrorKind, Result};
+use mimir::sql;
+
+fn identifiers_res() -> Result<()> {
+    assert_eq!(sql::check_identifier("order_lines$1")?, "ORDER_LINES$1");
+    assert_eq!(sql::check_qualified_name("hr.Accounts")?, "HR.ACCOUNTS");
+    assert_eq!(sql::quote_identifier("Order Lines")?, "\"Order Lines\"");
+    assert_eq!(sql::quote_identifier("select")?, "\"select\"");
+
+    let long = "A".repeat(sql::LEGACY_MAX_IDENTIFIER_LEN + 1);
+    assert!(sql::quote_identifier(&long).is_ok());
+    assert!(sql::quote_identifier_with_limit(&long, sql::LEGACY_MAX_IDENTIFIER_LEN).is_err());
+    assert!(sql::check_identifier(&"A".repeat(sql::MAX_IDENTIFIER_LEN + 1)).is_err());
+
+    for invalid in &["", "1abc", "a b", "a;drop", "hr..x", "level", "Select"] {
+        match sql::check_qualified_name(invalid) {
+            Err(Error(ErrorKind::Identifier(_), _)) => {}
+            Err(e) => return Err(e),
+            Ok(name) => panic!("'{}' accepted as {}", invalid, name),
+        }
+    }
+    for invalid in &["", "a\"b", "a\0b"] {
+        assert!(sql::quote_identifier(invalid).is_err());
+    }
+
+    assert!(sql::is_reserved_word("Varchar2"));
+    assert!(!sql::is_reserved_word("name"));
+
+    Ok(())
+}
+
+#[test]
+fn identifiers() {
+    check!(identifiers_res)
+}
+
+#[test]
+fn server_features() {
+    let legacy = sql::ServerFeatures::new(12, 1);
+    assert_eq!(legacy.max_identifier_len(), sql::LEGACY_MAX_IDENTIFIER_LEN);
+    assert!(legacy.optional_merge_clauses());
+
+    let current = sql::ServerFeatures::new(18, 0);
+    assert_eq!(current, sql::ServerFeatures::default());
+    assert_eq!(current.max_identifier_len(), sql::MAX_IDENTIFIER_LEN);
+    let not_compatible = current.with_max_identifier_len(sql::LEGACY_MAX_IDENTIFIER_LEN);
+    assert_eq!(not_compatible.max_identifier_len(), sql::LEGACY_MAX_IDENTIFIER_LEN);
+
+    assert!(!sql::ServerFeatures::new(9, 2).optional_merge_clauses());
+
+    assert!(legacy.multitenant());
+    assert!(!sql::ServerFeatures::new(11, 2).multitenant());
+    assert!(!current.container_db());
+    assert!(current.with_container_db(true).container_db());
+
+    assert_eq!(sql::compatible_max_identifier_len("12.1.0.2.0"), sql::LEGACY_MAX_IDENTIFIER_LEN);
+    assert_eq!(sql::compatible_max_identifier_len("12.2.0"), sql::MAX_IDENTIFIER_LEN);
+    assert_eq!(sql::compatible_max_identifier_len("19.0.0"), sql::MAX_IDENTIFIER_LEN);
+    assert_eq!(sql::compatible_max_identifier_len("unknown"), sql::LEGACY_MAX_IDENTIFIER_LEN);
+
+    let long = "A".repeat(sql::LEGACY_MAX_IDENTIFIER_LEN + 1);
+    assert!(sql::check_identifier_with_limit(&long, current.max_identifier_len()).is_ok());
+    assert!(sql::check_identifier_with_limit(&long, legacy.max_identifier_len()).is_err());
+    assert!(sql::check_qualified_name_with_limit(&format!("hr.{}", long), 30).is_err());
+}
+
+#[test]
+fn literals() {
+    assert_eq!(sql::quote_literal("plain"), "'plain'");
+    assert_eq!(sql::quote_literal("it's"), "'it''s'");
+    assert_eq!(sql::quote_literal(""), "''");
+}
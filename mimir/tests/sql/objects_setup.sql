@@ -0,0 +1,39 @@
+-- Object types and tables used by the object type tests, created in the ODPIC schema.
+create type udt_SubObject as object (
+    SubNumberValue number,
+    SubStringValue varchar2(60)
+);
+/
+
+create type udt_ObjectArray as varray(10) of udt_SubObject;
+/
+
+create type udt_Object as object (
+    NumberValue number,
+    StringValue varchar2(60),
+    FixedCharValue char(10),
+    DateValue date,
+    TimestampValue timestamp,
+    SubObjectValue udt_SubObject,
+    SubObjectArray udt_ObjectArray
+);
+/
+
+create type udt_Array as varray(10) of number;
+/
+
+create table TestObjects (
+    IntCol number(9) not null,
+    ObjectCol udt_Object,
+    ArrayCol udt_Array
+);
+
+insert into TestObjects values (1,
+    udt_Object(1, 'First row', 'First', to_date(20070306, 'YYYYMMDD'),
+        to_timestamp('20080912 16:40:00', 'YYYYMMDD HH24:MI:SS'),
+        udt_SubObject(11, 'Sub object 1'),
+        udt_ObjectArray(udt_SubObject(5, 'first element'),
+            udt_SubObject(6, 'second element'))),
+    udt_Array(5, 10, null, 20));
+
+insert into TestObjects values (2, null, udt_Array(3, null, 9, 12, 15));
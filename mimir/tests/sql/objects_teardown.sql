@@ -0,0 +1,6 @@
+-- Drops the schema objects created by objects_setup.sql.
+drop table TestObjects purge;
+drop type udt_Array;
+drop type udt_Object;
+drop type udt_ObjectArray;
+drop type udt_SubObject;
@@ -0,0 +1,2 @@
+-- Schema objects used by the transaction savepoint test.
+create table mimir_savepoint_test (id number);
@@ -0,0 +1,2 @@
+-- Drops the schema objects created by savepoint_setup.sql.
+drop table mimir_savepoint_test purge;
@@ -0,0 +1,5 @@
+-- Schema objects used by the integration tests, created in the main test schema.
+create table username (id number not null, username varchar2(256) not null);
+insert into username values (1, 'jozias');
+insert into username values (2, 'mimir');
+create table mimir_txn_test (id number);
@@ -0,0 +1,3 @@
+-- Drops the schema objects created by setup.sql.
+drop table mimir_txn_test purge;
+drop table username purge;
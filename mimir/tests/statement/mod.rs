@@ -1,13 +1,16 @@
-use CREDS;
-use mimir::{Connection, Context, Data, ODPIBytes, ODPIDataValueUnion, ODPIStr, QueryInfo, Var};
+use CONFIG;
+use chrono::Duration;
+use mimir::{Connection, Context, ControlFlow, ConversionLoss, Data, FromValue, OracleType,
+            QueryInfo, Var, YearsMonths};
 use mimir::enums::ODPIFetchMode::Last;
-use mimir::enums::ODPINativeTypeNum::{Bytes, Double, Int64};
+use mimir::enums::ODPINativeTypeNum::{Bytes, Double, Int64, Timestamp};
 use mimir::enums::ODPIOracleTypeNum::{Number, Varchar};
 use mimir::enums::ODPIStatementType::Insert;
-use mimir::error::Result;
+use mimir::error::{Error, ErrorKind, Result};
 use mimir::flags;
+use mimir::raw;
 use rand::{self, Rng};
-use std::ffi::CString;
+use std::time::Instant;
 
 fn add_ref_release(conn: &Connection) -> Result<()> {
     let dual = conn.prepare_stmt(Some("select 1 from dual"), None, false)?;
@@ -27,6 +30,7 @@ fn validate_query_info(query_info: &QueryInfo) -> Result<()> {
     assert_eq!(query_info.size_in_chars(), 0);
     assert_eq!(query_info.precision(), 38);
     assert_eq!(query_info.scale(), 0);
+    assert_eq!(query_info.oracle_type(), Some(OracleType::Number(38, 0)));
     assert!(!query_info.null_ok());
     assert!(query_info.object_type().is_none());
     Ok(())
@@ -76,18 +80,8 @@ fn bind_by_name(conn: &Connection, username_var: &Var) -> Result<()> {
 
 #[cfg_attr(feature = "cargo-clippy", allow(used_underscore_binding))]
 fn stmt_res(ctxt: &Context) -> Result<()> {
-    let mut ccp = ctxt.init_common_create_params()?;
-    let enc_cstr = CString::new("UTF-8").expect("badness");
-    ccp.set_encoding(enc_cstr.as_ptr());
-    ccp.set_nchar_encoding(enc_cstr.as_ptr());
-
-    let conn = Connection::create(ctxt,
-                                  Some(&CREDS[0]),
-                                  Some(&CREDS[1]),
-                                  Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
-                                  Some(ccp),
-                                  None)?;
-    let username_var = conn.new_var(Varchar, Bytes, 1, 256, false, false)?;
+    let conn = CONFIG.connect(ctxt)?;
+    let username_var = conn.new_var(Varchar, Bytes, 1, 256, false, false, None)?;
     username_var.set_from_bytes(0, "jozias")?;
 
     // add_ref / release test
@@ -104,16 +98,8 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
     assert_eq!(cols, 2);
 
     // bind_value_by_name / execute test
-    let blah = ODPIStr::from("test");
-    let enc = String::from("UTF-8\0");
-
-    let odpi_bytes = ODPIBytes {
-        ptr: blah.ptr() as *mut i8,
-        length: blah.len(),
-        encoding: enc.as_ptr() as *const ::std::os::raw::c_char,
-    };
-
-    let t_data = Data::new(false, ODPIDataValueUnion { as_bytes: odpi_bytes });
+    let t_data = Data::default();
+    t_data.set_string("test");
     let bind_by_value_name = conn.prepare_stmt(Some("select * from username \
                                                      where username = :username"),
                                                None,
@@ -128,7 +114,8 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
                                                     where username = :username"),
                                               None,
                                               false)?;
-    let t_data_1 = Data::new(false, ODPIDataValueUnion { as_bytes: odpi_bytes });
+    let t_data_1 = Data::default();
+    t_data_1.set_string("test");
     bind_by_value_pos.bind_value_by_pos(1, Bytes, &t_data_1)?;
     cols = bind_by_value_pos.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
     assert_eq!(cols, 2);
@@ -176,6 +163,11 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
             _ => assert!(false),
         }
     }
+    let bind_info = bn.bind_info()?;
+    assert_eq!(bind_info.len(), 2);
+    assert_eq!(bind_info[1].name(), "USERNAME");
+    assert_eq!(bind_info[1].position(), 2);
+    assert!(!bind_info[1].is_duplicate());
     let error_count = bn.get_batch_error_count()?;
     assert_eq!(error_count, 0);
     let info = bn.get_info()?;
@@ -191,13 +183,190 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
     assert!(row_count >= 2);
     all_users.scroll(Last, 0, 0)?;
 
+    // for_each_chunk test
+    let chunked = conn.prepare_stmt(Some("select * from username"), None, false)?;
+    chunked.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    let array_size = chunked.get_fetch_array_size()?;
+    let mut chunk_rows = 0;
+    chunked
+        .for_each_chunk(&conn, 1, |batch| {
+            assert_eq!(batch.num_rows(), 1);
+            assert_eq!(batch.num_cols(), 2);
+            let id = batch.get(0, 1).expect("badness");
+            assert!(!id.is_null());
+            assert!(batch.get(1, 1).is_none());
+            assert!(batch.get(0, 3).is_none());
+            chunk_rows += batch.num_rows();
+            if chunk_rows == 2 {
+                ControlFlow::Break
+            } else {
+                ControlFlow::Continue
+            }
+        })?;
+    assert_eq!(chunk_rows, 2);
+    assert_eq!(chunked.get_fetch_array_size()?, array_size);
+
+    // null_mask test
+    let masked = conn.prepare_stmt(Some("select id, null from username"), None, false)?;
+    masked.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    masked
+        .for_each_chunk(&conn, 10, |batch| {
+            let ids = batch.null_mask(1).expect("badness");
+            let nulls = batch.null_mask(2).expect("badness");
+            assert_eq!(ids.len(), batch.num_rows() as usize);
+            assert!(ids.iter().all(|is_null| !is_null));
+            assert!(nulls.iter().all(|is_null| *is_null));
+            assert!(batch.null_mask(0).is_none());
+            assert!(batch.null_mask(3).is_none());
+            ControlFlow::Continue
+        })?;
+    masked.close(None)?;
+
+    // fetch_into test
+    let typed = conn.prepare_stmt(Some("select id, username, null from username order by id"),
+                                  None,
+                                  false)?;
+    typed.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    let mut buffers: (Vec<i64>, Vec<String>, Vec<Option<f64>>) = Default::default();
+    let mut typed_rows = 0;
+    typed
+        .fetch_into(&conn, 1, &mut buffers, |&mut (ref ids, ref usernames, ref nulls)| {
+            assert_eq!(ids.len(), 1);
+            assert_eq!(usernames.len(), 1);
+            assert_eq!(nulls, &vec![None]);
+            if typed_rows == 0 {
+                assert_eq!(ids[0], 1);
+                assert_eq!(usernames[0], "jozias");
+            }
+            typed_rows += ids.len();
+            ControlFlow::Continue
+        })?;
+    assert!(typed_rows >= 2);
+    assert_eq!(typed.get_fetch_array_size()?, array_size);
+    assert!(typed.get_coercion_warnings().is_empty());
+    typed.close(None)?;
+
+    let lossy = conn.prepare_stmt(Some("select cast(1.5 as number(10,2)) from dual"), None, false)?;
+    lossy.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    let mut truncated: (Vec<i64>,) = Default::default();
+    lossy.fetch_into(&conn, 1, &mut truncated, |_| ControlFlow::Continue)?;
+    assert_eq!(truncated.0.len(), 1);
+    let warnings = lossy.get_coercion_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].column().position(), 1);
+    assert_eq!(warnings[0].loss(), ConversionLoss::Fraction);
+    lossy.close(None)?;
+
+    let wide = "select cast(12345678901234567890 as number(38)) from dual";
+    let chunked = conn.prepare_stmt(Some(wide), None, false)?;
+    chunked.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    chunked.for_each_chunk(&conn, 1, |_| ControlFlow::Continue)?;
+    let warnings = chunked.get_coercion_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].column().position(), 1);
+    chunked.close(None)?;
+
+    let read = conn.prepare_stmt(Some(wide), None, false)?;
+    read.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    assert!(read.fetch()?.0);
+    let _: f64 = read.row()?.get_at(1)?;
+    let _: f64 = read.row()?.get_at(1)?;
+    assert_eq!(read.get_coercion_warnings().len(), 1);
+    read.close(None)?;
+
+    let mismatched = conn.prepare_stmt(Some("select id from username"), None, false)?;
+    mismatched.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    let mut too_many: (Vec<i64>, Vec<String>) = Default::default();
+    assert!(mismatched
+                .fetch_into(&conn, 10, &mut too_many, |_| ControlFlow::Break)
+                .is_err());
+    mismatched.close(None)?;
+
+    // define_inline / set_fetch_lobs_inline test
+    let clob = conn.prepare_stmt(Some("select to_clob('jozias') from dual"), None, false)?;
+    clob.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    let clob_var = clob.define_inline(&conn, 1, 1)?;
+    let (clob_found, clob_idx) = clob.fetch()?;
+    assert!(clob_found);
+    let clob_data = clob_var.get_value(clob_idx)?;
+    assert_eq!(clob_data.get_string(), "jozias");
+    clob.close(None)?;
+
+    let inline = conn.prepare_stmt(Some("select to_clob(username) from username"), None, false)?;
+    assert!(!inline.get_fetch_lobs_inline());
+    inline.set_fetch_lobs_inline(true);
+    assert!(inline.get_fetch_lobs_inline());
+    inline.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    inline
+        .for_each_chunk(&conn, 10, |batch| {
+            let username = batch.get(0, 1).expect("badness");
+            assert_eq!(username.get_string(), "jozias");
+            ControlFlow::Break
+        })?;
+    inline.close(None)?;
+
+    // set_default_lob_prefetch_size / set_lob_prefetch_size test
+    assert_eq!(conn.get_default_lob_prefetch_size(), 0);
+    conn.set_default_lob_prefetch_size(4096);
+    let prefetch = conn.prepare_stmt(Some("select to_clob(username) from username where id = 1"),
+                                     None,
+                                     false)?;
+    assert_eq!(prefetch.get_lob_prefetch_size(), 4096);
+    assert!(!prefetch.get_fetch_lobs_inline());
+    // The prefetch size alone leaves LOB columns defined as LOB locators.
+    prefetch.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    prefetch
+        .for_each_chunk(&conn, 10, |batch| {
+            let username = batch.get(0, 1).expect("badness");
+            assert!(!username.get_lob().is_null());
+            ControlFlow::Break
+        })?;
+    prefetch.set_fetch_lobs_inline(true);
+    prefetch.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    prefetch
+        .for_each_chunk(&conn, 10, |batch| {
+            let username = batch.get(0, 1).expect("badness");
+            assert_eq!(username.get_string(), "jozias");
+            ControlFlow::Break
+        })?;
+    prefetch.close(None)?;
+    conn.set_default_lob_prefetch_size(0);
+
+    // set_default_define_size_margin / set_define_size_margin test
+    assert_eq!(conn.get_default_define_size_margin(), None);
+    conn.set_default_define_size_margin(Some(2.0));
+    let margin = conn.prepare_stmt(Some("select username || 'é' from username"), None, false)?;
+    assert_eq!(margin.get_define_size_margin(), Some(2.0));
+    margin.set_define_size_margin(Some(0.5));
+    assert_eq!(margin.get_define_size_margin(), Some(1.0));
+    margin.set_define_size_margin(Some(1.5));
+    margin.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    let client_size = margin.get_query_info(1)?.client_size_in_bytes();
+    assert!(margin.get_define_size(1)? >= client_size + client_size / 2);
+    margin
+        .for_each_chunk(&conn, 10, |batch| {
+            let username = batch.get(0, 1).expect("badness");
+            assert_eq!(username.get_string(), "joziasé");
+            ControlFlow::Break
+        })?;
+    margin.close(None)?;
+    let fixed = conn.prepare_stmt(Some("select id, sysdate from username"), None, false)?;
+    fixed.set_define_size_margin(Some(2.0));
+    fixed.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    for pos in 1..3 {
+        assert_eq!(fixed.get_define_size(pos)?,
+                   fixed.get_query_info(pos)?.client_size_in_bytes());
+    }
+    fixed.close(None)?;
+    conn.set_default_define_size_margin(None);
+
     // execute_many test
     let em = conn.prepare_stmt(Some("insert into username values (:id, :username)"),
                                None,
                                false)?;
     // setup the id binds.
-    let id_var = conn.new_var(Number, Int64, 2, 0, false, false)?;
-    let mut id_data = id_var.get_data()?;
+    let id_var = conn.new_var(Number, Int64, 2, 0, false, false, None)?;
+    let id_data = unsafe { raw::var_data(&id_var)? };
     let mut rng = rand::thread_rng();
     for data in id_data.iter_mut() {
         (*data).is_null = 0;
@@ -206,7 +375,7 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
     em.bind_by_pos(1, &id_var)?;
 
     // setup the username binds
-    let un_var = conn.new_var(Varchar, Bytes, 2, 256, true, false)?;
+    let un_var = conn.new_var(Varchar, Bytes, 2, 256, true, false, None)?;
 
     for i in 0..2 {
         un_var.set_from_bytes(i, "jozias")?;
@@ -222,6 +391,7 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
     fetch_rows.close(None)?;
     bn.close(None)?;
     all_users.close(None)?;
+    chunked.close(None)?;
     em.close(None)?;
 
     conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
@@ -233,3 +403,347 @@ fn stmt_res(ctxt: &Context) -> Result<()> {
 fn statement() {
     check_with_ctxt!(stmt_res)
 }
+
+fn binder_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let stmt = conn.prepare_stmt(Some("select :id + 1, :name, :missing from dual"),
+                                 None,
+                                 false)?;
+
+    match stmt.binder().set("nope", 1) {
+        Err(Error(ErrorKind::Bind(ref msg), _)) => assert!(msg.contains(":NOPE")),
+        Err(e) => return Err(e),
+        Ok(_) => panic!("an unknown bind variable was accepted"),
+    }
+
+    match stmt.binder().set(":id", 5)?.set("name", "x")?.execute() {
+        Err(Error(ErrorKind::Bind(ref msg), _)) => {
+            assert!(msg.contains(":MISSING"));
+            assert!(!msg.contains(":ID"));
+        }
+        Err(e) => return Err(e),
+        Ok(_) => panic!("a statement with a missing bind was executed"),
+    }
+
+    let cols = stmt.binder()
+        .set("ID", 5)?
+        .set("name", "x")?
+        .set("missing", None::<i64>)?
+        .execute()?;
+    assert_eq!(cols, 3);
+
+    let (found, _) = stmt.fetch()?;
+    assert!(found);
+    let (_, id_ptr) = stmt.get_query_value(1)?;
+    let id: Data = id_ptr.into();
+    assert!((id.get_double() - 6.0).abs() < ::std::f64::EPSILON);
+    let (_, name_ptr) = stmt.get_query_value(2)?;
+    let name: Data = name_ptr.into();
+    assert_eq!(name.get_string(), "x");
+    let (_, missing_ptr) = stmt.get_query_value(3)?;
+    let missing: Data = missing_ptr.into();
+    assert!(missing.is_null());
+
+    stmt.close(None)?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn binder() {
+    check_with_ctxt!(binder_res)
+}
+
+fn intervals_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let stmt = conn.prepare_stmt(Some("select :ds + interval '1' day, :ym + interval '1' month \
+                                       from dual"),
+                                 None,
+                                 false)?;
+    let ds = -(Duration::days(2) + Duration::hours(3) + Duration::milliseconds(250));
+    stmt.binder()
+        .set("ds", ds)?
+        .set("ym", YearsMonths::new(1, 11))?
+        .execute()?;
+
+    let mut buffers: (Vec<Duration>, Vec<YearsMonths>) = Default::default();
+    stmt.fetch_into(&conn, 1, &mut buffers, |_| ControlFlow::Continue)?;
+    assert_eq!(buffers.0, vec![ds + Duration::days(1)]);
+    assert_eq!(buffers.1, vec![YearsMonths::new(2, 0)]);
+
+    stmt.close(None)?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+
+    Ok(())
+}
+
+#[test]
+fn intervals() {
+    check_with_ctxt!(intervals_res)
+}
+
+fn bind_info_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+
+    let sql = "select :a, ':b' /* :c */, :\"Mixed\", :a -- :d\n from dual";
+    let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+    assert_eq!(stmt.get_bind_count()?, 3);
+    assert_eq!(stmt.get_bind_names(3)?, ["A", "Mixed"]);
+
+    let info: Vec<_> = stmt.bind_info()?
+        .iter()
+        .map(|info| (info.name().to_string(), info.position(), info.is_duplicate()))
+        .collect();
+    assert_eq!(info,
+               [("A".to_string(), 1, false),
+                ("Mixed".to_string(), 2, false),
+                ("A".to_string(), 3, true)]);
+    stmt.close(None)?;
+
+    let plsql = conn.prepare_stmt(Some("begin :a := :b + :a; end;"), None, false)?;
+    let names: Vec<_> = plsql.bind_info()?
+        .iter()
+        .map(|info| (info.name().to_string(), info.position()))
+        .collect();
+    assert_eq!(names, [("A".to_string(), 1), ("B".to_string(), 2)]);
+    plsql.close(None)?;
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn bind_info() {
+    check_with_ctxt!(bind_info_res)
+}
+
+fn columns_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let stmt = conn.prepare_stmt(Some("select 1 as id, 'x' as \"Name\" from dual"), None, false)?;
+    assert!(stmt.columns().is_err());
+    assert_eq!(stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?, 2);
+
+    let columns = stmt.columns()?;
+    let names: Vec<_> = columns.iter().map(|col| (col.position(), col.name())).collect();
+    assert_eq!(names, [(1, "ID"), (2, "Name")]);
+    assert_eq!(columns[0].oracle_type_num(), Number);
+    assert_eq!(columns[1].oracle_type_num(), Varchar);
+
+    let (found, _) = stmt.fetch()?;
+    assert!(found);
+    let (_, name_ptr) = stmt.get_query_value_by_name("name")?;
+    let name: Data = name_ptr.into();
+    assert_eq!(name.get_string(), "x");
+    assert!(stmt.get_query_value_by_name("missing").is_err());
+
+    let row = stmt.row()?;
+    assert_eq!(row.get::<i64>("id")?, 1);
+    assert_eq!(row.get::<String>("NAME")?, "x");
+    assert_eq!(row.get_exact::<Option<String>>("Name")?, Some("x".to_string()));
+    assert!(row.get_exact::<String>("NAME").is_err());
+    assert!(row.get::<Duration>("id").is_err());
+    match row.get::<i64>("missing") {
+        Err(Error(ErrorKind::Statement(msg), _)) => assert!(msg.ends_with("ID, Name")),
+        _ => panic!("expected an unknown column error"),
+    }
+
+    let (found, _) = stmt.fetch()?;
+    assert!(!found);
+    assert_eq!(*stmt.columns()?, *columns);
+
+    stmt.close(None)?;
+    assert_eq!(columns[0].name(), "ID");
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn from_value() {
+    let data = Data::default();
+    data.set_double(42.0);
+    assert_eq!(i64::from_value(Double, &data), Some(42));
+    assert_eq!(f64::from_value(Double, &data), Some(42.0));
+    assert_eq!(Option::<f64>::from_value(Double, &data), Some(Some(42.0)));
+    data.set_double(0.5);
+    assert_eq!(i64::from_value(Double, &data), None);
+
+    data.set_int64(-7);
+    assert_eq!(i64::from_value(Int64, &data), Some(-7));
+    assert_eq!(f64::from_value(Int64, &data), Some(-7.0));
+    assert_eq!(String::from_value(Int64, &data), None);
+    assert_eq!(Duration::from_value(Timestamp, &data), None);
+
+    data.set_string(" 12 ");
+    assert_eq!(String::from_value(Bytes, &data), Some(" 12 ".to_string()));
+    assert_eq!(i64::from_value(Bytes, &data), Some(12));
+    assert_eq!(Vec::<u8>::from_value(Bytes, &data), Some(b" 12 ".to_vec()));
+}
+
+#[test]
+fn columns() {
+    check_with_ctxt!(columns_res)
+}
+
+fn single_row_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+
+    let (id, name): (i64, String) =
+        conn.query_row("select 1, 'x' from dual where 1 = :one", &[("one", 1.into())])?;
+    assert_eq!(id, 1);
+    assert_eq!(name, "x");
+
+    let count: i64 = conn.query_scalar("select count(*) from dual", &[])?;
+    assert_eq!(count, 1);
+    let null: Option<String> = conn.query_scalar("select null from dual", &[])?;
+    assert!(null.is_none());
+
+    match conn.query_scalar::<i64>("select 1 from dual where 1 = 0", &[]) {
+        Err(Error(ErrorKind::NoRows, _)) => {}
+        res => panic!("expected no rows, got {:?}", res.map(|_| ())),
+    }
+    match conn.query_scalar::<i64>("select 1 from dual connect by level <= 3", &[]) {
+        Err(Error(ErrorKind::TooManyRows, _)) => {}
+        res => panic!("expected too many rows, got {:?}", res.map(|_| ())),
+    }
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn single_row() {
+    check_with_ctxt!(single_row_res)
+}
+
+fn execute_with_timeout_res(ctxt: &Context) -> Result<()> {
+    let mut ccp = CONFIG.init_common_create_params(ctxt)?;
+    let create_mode = ccp.get_create_mode() | flags::DPI_MODE_CREATE_THREADED;
+    ccp.set_create_mode(create_mode);
+    let conn = CONFIG.connect_with(ctxt, ccp)?;
+
+    let quick = conn.prepare_stmt(Some("select 1 from dual"), None, false)?;
+    let timeout = ::std::time::Duration::from_secs(5);
+    assert_eq!(quick.execute_with_timeout(&conn, flags::DPI_MODE_EXEC_DEFAULT, timeout)?,
+               1);
+    quick.close(None)?;
+
+    let slow = conn.prepare_stmt(Some("begin for i in 1 .. 1000000000 loop null; end loop; end;"),
+                                 None,
+                                 false)?;
+    let timeout = ::std::time::Duration::from_millis(200);
+    match slow.execute_with_timeout(&conn, flags::DPI_MODE_EXEC_DEFAULT, timeout) {
+        Err(Error(ErrorKind::Timeout(200), _)) => {}
+        res => panic!("expected a timeout, got {:?}", res),
+    }
+    slow.close(None)?;
+
+    // No break is left pending for the next call on the connection.
+    conn.ping()?;
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn execute_with_timeout() {
+    check_with_ctxt!(execute_with_timeout_res)
+}
+
+fn fetch_rows_deadline_res(ctxt: &Context) -> Result<()> {
+    let mut ccp = CONFIG.init_common_create_params(ctxt)?;
+    let create_mode = ccp.get_create_mode() | flags::DPI_MODE_CREATE_THREADED;
+    ccp.set_create_mode(create_mode);
+    let conn = CONFIG.connect_with(ctxt, ccp)?;
+
+    let levels = conn.prepare_stmt(Some("select level from dual connect by level <= 25"),
+                                   None,
+                                   false)?;
+    levels.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    levels.set_fetch_array_size(10)?;
+
+    let deadline = Instant::now() + ::std::time::Duration::from_secs(30);
+    let mut values = Vec::new();
+    let (fetched, reached) = levels.fetch_rows_deadline(&conn, 15, deadline, |batch| {
+        for row in 0..batch.num_rows() {
+            values.push(batch.get(row, 1).expect("badness").get_int64());
+        }
+    })?;
+    assert_eq!((fetched, reached), (15, false));
+    assert_eq!(values, (1..16).collect::<Vec<i64>>());
+    assert_eq!(levels.get_fetch_array_size()?, 10);
+
+    values.clear();
+    let (fetched, reached) = levels.fetch_rows_deadline(&conn, 15, deadline, |batch| {
+        for row in 0..batch.num_rows() {
+            values.push(batch.get(row, 1).expect("badness").get_int64());
+        }
+    })?;
+    assert_eq!((fetched, reached), (10, false));
+    assert_eq!(values, (16..26).collect::<Vec<i64>>());
+    levels.close(None)?;
+
+    let expired = conn.prepare_stmt(Some("select 1 from dual"), None, false)?;
+    expired.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    let (fetched, reached) = expired.fetch_rows_deadline(&conn, 1, Instant::now(), |_| {})?;
+    assert_eq!((fetched, reached), (0, true));
+    expired.close(None)?;
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn fetch_rows_deadline() {
+    check_with_ctxt!(fetch_rows_deadline_res)
+}
+
+fn not_a_query_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let stmt = conn.prepare_stmt(Some("begin null; end;"), None, false)?;
+
+    match stmt.single_value::<i64>(&conn) {
+        Err(Error(ErrorKind::NotAQuery(fn_name, statement_type), _)) => {
+            assert_eq!(fn_name, "single_row");
+            assert_eq!(statement_type, "Begin");
+        }
+        _ => panic!("expected a not a query error"),
+    }
+
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    match stmt.fetch() {
+        Err(Error(ErrorKind::NotAQuery(fn_name, _), _)) => assert_eq!(fn_name, "fetch"),
+        _ => panic!("expected a not a query error"),
+    }
+    match stmt.for_each_chunk(&conn, 10, |_| ControlFlow::Continue) {
+        Err(Error(ErrorKind::NotAQuery(..), _)) => {}
+        _ => panic!("expected a not a query error"),
+    }
+    assert!(stmt.row().is_err());
+    stmt.release()?;
+
+    match conn.query_scalar::<i64>("begin null; end;", &[]) {
+        Err(Error(ErrorKind::NotAQuery(..), _)) => {}
+        _ => panic!("expected a not a query error"),
+    }
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn not_a_query() {
+    check_with_ctxt!(not_a_query_res)
+}
@@ -0,0 +1,103 @@
+use CONFIG;
+use mimir::{CacheStats, Context, cache_tag};
+use mimir::error::Result;
+use mimir::flags;
+
+#[test]
+fn cache_stats() {
+    let mut stats: CacheStats = Default::default();
+    assert_eq!(stats.get_prepares(), 0);
+    assert!(stats.get_hit_rate().is_none());
+
+    stats.record(false);
+    stats.record(true);
+    stats.record(true);
+    stats.record(true);
+
+    assert_eq!(stats.get_hits(), 3);
+    assert_eq!(stats.get_misses(), 1);
+    assert_eq!(stats.get_hit_rate(), Some(0.75));
+
+    let total = stats + stats;
+    assert_eq!(total.get_prepares(), 8);
+    assert_eq!(total.get_hit_rate(), Some(0.75));
+
+    stats.record_evictions(2);
+    assert_eq!(stats.get_evictions(), 2);
+    assert_eq!((stats + stats).get_evictions(), 4);
+}
+
+#[test]
+fn cache_tags() {
+    let tag = cache_tag("select 1 from dual", None);
+    assert_eq!(tag, cache_tag("select 1 from dual", None));
+    assert!(tag.starts_with("mimir::"));
+    assert_ne!(tag, cache_tag("select 2 from dual", None));
+    assert_ne!(tag, cache_tag("select 1 from dual", Some("int")));
+    assert!(cache_tag("select 1 from dual", Some("int")).starts_with("mimir:int:"));
+}
+
+fn connection_cache_stats_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    conn.set_statement_cache_size(2)?;
+    assert_eq!(conn.get_statement_cache_stats().get_prepares(), 0);
+
+    conn.set_statement_cache_stats(true);
+    for sql in &["select 1 from dual",
+                 "select 1 from dual",
+                 "select 2 from dual",
+                 "select 3 from dual",
+                 "select 1 from dual"] {
+        let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+        stmt.release()?;
+    }
+
+    let stats = conn.get_statement_cache_stats();
+    assert_eq!(stats.get_hits(), 1);
+    assert_eq!(stats.get_misses(), 4);
+    assert_eq!(stats.get_evictions(), 2);
+
+    let stmt = conn.prepare_stmt(Some("select 4 from dual"), Some("mimir_cache_stats"), false)?;
+    stmt.release()?;
+    let stmt = conn.prepare_stmt(None, Some("mimir_cache_stats"), false)?;
+    stmt.release()?;
+    assert!(conn.prepare_stmt(None, Some("mimir_no_such_tag"), false).is_err());
+
+    let stats = conn.get_statement_cache_stats();
+    assert_eq!(stats.get_hits(), 2);
+    assert_eq!(stats.get_misses(), 6);
+
+    // prepare_cached
+    let stmt = conn.prepare_cached("select 5 from dual", Some("key"))?;
+    assert_eq!(stmt.get_cache_tag(),
+               Some(&cache_tag("select 5 from dual", Some("key"))[..]));
+    stmt.close_cached()?;
+    let stmt = conn.prepare_cached("select 5 from dual", Some("key"))?;
+    stmt.close_cached()?;
+    let stats = conn.get_statement_cache_stats();
+    assert_eq!(stats.get_hits(), 3);
+    assert_eq!(stats.get_misses(), 7);
+
+    // flush_statement_cache
+    conn.flush_statement_cache()?;
+    assert_eq!(conn.get_statement_cache_size()?, 2);
+    assert!(conn.prepare_stmt(None, Some("mimir_cache_stats"), false).is_err());
+    let stmt = conn.prepare_stmt(Some("select 1 from dual"), None, false)?;
+    stmt.release()?;
+    let stats = conn.get_statement_cache_stats();
+    assert_eq!(stats.get_hits(), 2);
+    assert_eq!(stats.get_misses(), 8);
+
+    conn.set_statement_cache_stats(false);
+    assert_eq!(conn.get_statement_cache_stats().get_prepares(), 0);
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn connection_cache_stats() {
+    check_with_ctxt!(connection_cache_stats_res)
+}
@@ -0,0 +1,64 @@
+use CONFIG;
+use mimir::{Context, Timing};
+use mimir::error::Result;
+use mimir::flags;
+use std::time::Duration;
+
+fn timing_res() -> Result<()> {
+    let mut timing: Timing = Default::default();
+    assert_eq!(timing.get_calls(), 0);
+    assert_eq!(timing.get_mean(), Duration::default());
+
+    timing.record(Duration::from_millis(10));
+    timing.record(Duration::from_millis(30));
+    timing.record(Duration::from_millis(20));
+
+    assert_eq!(timing.get_calls(), 3);
+    assert_eq!(timing.get_total(), Duration::from_millis(60));
+    assert_eq!(timing.get_max(), Duration::from_millis(30));
+    assert_eq!(timing.get_mean(), Duration::from_millis(20));
+
+    Ok(())
+}
+
+#[test]
+fn timing() {
+    check!(timing_res)
+}
+
+fn connection_timings_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    assert!(conn.get_connect_time().is_some());
+    assert!(conn.get_timings().is_empty());
+
+    conn.set_timing(true);
+    conn.ping()?;
+    conn.ping()?;
+
+    let stmt = conn.prepare_stmt(Some("select 1 from dual"), None, false)?;
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    stmt.fetch()?;
+    stmt.close(None)?;
+    conn.commit()?;
+
+    let timings = conn.get_timings();
+    assert_eq!(timings["dpiConn_ping"].get_calls(), 2);
+    assert_eq!(timings["dpiConn_prepareStmt"].get_calls(), 1);
+    assert_eq!(timings["dpiStmt_execute"].get_calls(), 1);
+    assert_eq!(timings["dpiStmt_fetch"].get_calls(), 1);
+    assert_eq!(timings["dpiConn_commit"].get_calls(), 1);
+    assert!(!timings.contains_key("dpiConn_rollback"));
+
+    conn.set_timing(false);
+    assert!(conn.get_timings().is_empty());
+
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn connection_timings() {
+    check_with_ctxt!(connection_timings_res)
+}
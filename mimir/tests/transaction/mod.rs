@@ -0,0 +1,133 @@
+use CONFIG;
+use mimir::{Connection, Context, Data, Isolation, TxnOptions};
+use mimir::error::Result;
+use mimir::flags;
+#[cfg(feature = "test-util")]
+use mimir::testutil::Schema;
+
+/// The table used by the savepoint test, created and dropped through the test harness.
+#[cfg(feature = "test-util")]
+const SAVEPOINT_TABLE: &str = "mimir_savepoint_test";
+/// The table used by the transaction options test.
+const TXN_TABLE: &str = "mimir_txn_test";
+
+/// Create the test table, ignoring ORA-00955 if it already exists.
+const CREATE_TABLE: &str = "begin \
+                            execute immediate 'create table mimir_txn_test (id number)'; \
+                            exception when others then \
+                            if sqlcode != -955 then raise; end if; \
+                            end;";
+
+fn count_rows(conn: &Connection, table: &str) -> Result<f64> {
+    let sql = format!("select count(*) from {}", table);
+    let stmt = conn.prepare_stmt(Some(&sql), None, false)?;
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    stmt.fetch()?;
+    let (_, count_ptr) = stmt.get_query_value(1)?;
+    let count: Data = count_ptr.into();
+    let count = count.get_double();
+    stmt.close(None)?;
+    Ok(count)
+}
+
+fn insert_row(conn: &Connection, table: &str, id: u32) -> Result<()> {
+    let sql = format!("insert into {} values ({})", table, id);
+    let stmt = conn.prepare_stmt(Some(&sql), None, false)?;
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    stmt.close(None)?;
+    Ok(())
+}
+
+#[cfg(feature = "test-util")]
+fn savepoint_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let schema = Schema::from_files("tests/sql/savepoint_setup.sql",
+                                    "tests/sql/savepoint_teardown.sql")?;
+    schema.setup(&conn)?;
+
+    // rollback_to undoes only the work done after the savepoint.
+    {
+        let txn = conn.begin_transaction()?;
+        insert_row(&txn, SAVEPOINT_TABLE, 1)?;
+        let savepoint = txn.savepoint("before_two")?;
+        assert_eq!(savepoint.get_name(), "before_two");
+        insert_row(&txn, SAVEPOINT_TABLE, 2)?;
+        assert!((count_rows(&txn, SAVEPOINT_TABLE)? - 2.0).abs() < ::std::f64::EPSILON);
+        savepoint.rollback_to()?;
+        assert!((count_rows(&txn, SAVEPOINT_TABLE)? - 1.0).abs() < ::std::f64::EPSILON);
+        txn.commit()?;
+    }
+    assert!((count_rows(&conn, SAVEPOINT_TABLE)? - 1.0).abs() < ::std::f64::EPSILON);
+
+    // Dropping a savepoint keeps the work, dropping the transaction rolls it back.
+    {
+        let txn = conn.begin_transaction()?;
+        {
+            let _savepoint = txn.savepoint("sp1")?;
+            insert_row(&txn, SAVEPOINT_TABLE, 3)?;
+        }
+        assert!((count_rows(&txn, SAVEPOINT_TABLE)? - 2.0).abs() < ::std::f64::EPSILON);
+    }
+    assert!((count_rows(&conn, SAVEPOINT_TABLE)? - 1.0).abs() < ::std::f64::EPSILON);
+
+    // Savepoint names are validated before any SQL is issued.
+    {
+        let txn = conn.begin_transaction()?;
+        assert!(txn.savepoint("1bad").is_err());
+        assert!(txn.savepoint("bad name; drop table x").is_err());
+        assert!(txn.savepoint("").is_err());
+        txn.rollback()?;
+    }
+
+    assert_eq!(schema.teardown(&conn), 0);
+    conn.release()?;
+    Ok(())
+}
+
+fn txn_options_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    let create = conn.prepare_stmt(Some(CREATE_TABLE), None, false)?;
+    create.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    create.close(None)?;
+    assert_eq!(conn.get_transaction_options(), None);
+
+    let read_only = TxnOptions {
+        isolation: Isolation::ReadCommitted,
+        read_only: true,
+    };
+    {
+        let txn = conn.begin_transaction_with(read_only)?;
+        assert_eq!(txn.get_options(), read_only);
+        assert_eq!(conn.get_transaction_options(), Some(read_only));
+        count_rows(&txn, TXN_TABLE)?;
+        assert!(insert_row(&txn, TXN_TABLE, 1).is_err());
+        assert!(conn.begin_transaction_with(Default::default()).is_err());
+        txn.commit()?;
+    }
+    assert_eq!(conn.get_transaction_options(), None);
+
+    let serializable = TxnOptions {
+        isolation: Isolation::Serializable,
+        read_only: false,
+    };
+    {
+        let txn = conn.begin_transaction_with(serializable)?;
+        assert_eq!(conn.get_transaction_options(), Some(serializable));
+        count_rows(&txn, TXN_TABLE)?;
+    }
+    assert_eq!(conn.get_transaction_options(), None);
+
+    conn.release()?;
+    Ok(())
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn savepoint() {
+    check_with_ctxt!(savepoint_res)
+}
+
+#[test]
+fn transaction_options() {
+    check_with_ctxt!(txn_options_res)
+}
@@ -0,0 +1,49 @@
+use mimir::{OracleType, RustType};
+use mimir::enums::ODPIOracleTypeNum::{Blob, Date, LongVarchar, Max, NativeUint, Number, Raw,
+                                      TimestampTz, TypeNone, Varchar};
+use mimir::types::rust_type_for;
+
+#[test]
+fn mapping() {
+    assert_eq!(rust_type_for(Varchar, 0, 0), Some(RustType::String));
+    assert_eq!(rust_type_for(LongVarchar, 0, 0), Some(RustType::String));
+    assert_eq!(rust_type_for(Raw, 0, 0), Some(RustType::Bytes));
+    assert_eq!(rust_type_for(Number, 10, 0), Some(RustType::I64));
+    assert_eq!(rust_type_for(Number, 18, 0), Some(RustType::I64));
+    assert_eq!(rust_type_for(Number, 19, 0), Some(RustType::F64));
+    assert_eq!(rust_type_for(Number, 10, 2), Some(RustType::F64));
+    assert_eq!(rust_type_for(Number, 0, -127), Some(RustType::F64));
+    assert_eq!(rust_type_for(NativeUint, 0, 0), Some(RustType::U64));
+    assert_eq!(rust_type_for(Date, 0, 0), Some(RustType::DateTimeUtc));
+    assert_eq!(rust_type_for(TimestampTz, 6, 0), Some(RustType::DateTime));
+    assert_eq!(rust_type_for(Blob, 0, 0), Some(RustType::Lob));
+    assert!(rust_type_for(TypeNone, 0, 0).is_none());
+    assert!(rust_type_for(Max, 0, 0).is_none());
+}
+
+#[test]
+fn matches_oracle_type() {
+    let types = [OracleType::Varchar2(20),
+                 OracleType::Number(10, 0),
+                 OracleType::Number(38, 2),
+                 OracleType::BinaryFloat,
+                 OracleType::TimestampLtz(6),
+                 OracleType::IntervalYM,
+                 OracleType::Rowid,
+                 OracleType::Cursor];
+
+    for oracle_type in &types {
+        assert_eq!(oracle_type.rust_type().native_type_num(),
+                   oracle_type.native_type_num());
+    }
+    assert_eq!(OracleType::Number(10, 0).rust_type(), RustType::I64);
+    assert_eq!(OracleType::Raw(16).rust_type(), RustType::Bytes);
+}
+
+#[test]
+fn display() {
+    assert_eq!(RustType::I64.to_string(), "i64");
+    assert_eq!(RustType::Bytes.to_string(), "Vec<u8>");
+    assert_eq!(RustType::DateTimeUtc.to_string(), "::chrono::DateTime<::chrono::Utc>");
+    assert_eq!(RustType::YearsMonths.to_string(), "::mimir::YearsMonths");
+}
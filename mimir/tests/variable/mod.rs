@@ -1,47 +1,54 @@
-use CREDS;
-use mimir::ODPIData;
-use mimir::data::Data;
-use mimir::Connection;
+use CONFIG;
 use mimir::Context;
-use mimir::error::Result;
+use mimir::error::{Error, ErrorKind, Result};
 use mimir::enums::ODPINativeTypeNum::*;
 use mimir::enums::ODPIOracleTypeNum::*;
 use mimir::flags;
-use std::ffi::CString;
 
 fn var_res(ctxt: &Context) -> Result<()> {
-    let mut ccp = ctxt.init_common_create_params()?;
-    let enc_cstr = CString::new("UTF-8").expect("badness");
-    ccp.set_encoding(enc_cstr.as_ptr());
-    ccp.set_nchar_encoding(enc_cstr.as_ptr());
-
-    let conn = Connection::create(&ctxt,
-                                  Some(&CREDS[0]),
-                                  Some(&CREDS[1]),
-                                  Some("//oic.cbsnae86d3iv.us-east-2.rds.amazonaws.com/ORCL"),
-                                  Some(ccp),
-                                  None)?;
+    let conn = CONFIG.connect(ctxt)?;
 
     conn.add_ref()?;
 
-    let var = conn.new_var(Number, Int64, 2, 0, false, false)?;
+    let var = conn.new_var(Number, Int64, 2, 0, false, false, None)?;
     let num_elements = var.get_num_elements_in_array()?;
     assert_eq!(num_elements, 2);
     let size_in_bytes = var.get_size_in_bytes()?;
     assert_eq!(size_in_bytes, 22);
+    assert_eq!(var.get_size_in_chars()?, 22);
+    assert!(var.get_encoding_info().is_some());
 
-    let str_test = conn.new_var(Varchar, Bytes, 2, 256, false, false)?;
+    let str_test = conn.new_var(Varchar, Bytes, 2, 256, false, false, None)?;
     str_test.set_from_bytes(0, "jozias")?;
-    let mut str_test_data = str_test.get_data()?;
-    assert_eq!(str_test_data.len(), 2);
-    for (idx, d) in str_test_data.iter_mut().enumerate() {
-        let data: Data = (d as *mut ODPIData).into();
-        match idx {
-            0 => assert_eq!(data.get_string(), "jozias"),
-            1 => assert_eq!(data.get_string(), ""),
-            _ => assert!(false),
-        }
+    assert_eq!(str_test.get_value(0)?.get_string(), "jozias");
+    assert_eq!(str_test.get_value(1)?.get_string(), "");
+    assert!(str_test.get_value(2).is_err());
+    assert_eq!(str_test.get_size_in_chars()?, 256);
+    let encoding_info = str_test.get_encoding_info().expect("encoding info");
+    assert_eq!(str_test.get_size_in_bytes()?,
+               256 * encoding_info.max_bytes_per_char() as u32);
+    let nchar_test = conn.new_var(NVarchar, Bytes, 1, 100, true, false, None)?;
+    assert_eq!(nchar_test.get_size_in_chars()?, 25);
+    nchar_test.release()?;
+
+    // copy_range
+    let src = conn.new_var(Varchar, Bytes, 3, 16, false, false, None)?;
+    let mut dst = conn.new_var(Varchar, Bytes, 4, 16, false, false, None)?;
+    for (pos, value) in ["a", "b", "c"].iter().enumerate() {
+        src.set_from_bytes(pos as u32, value)?;
+    }
+    src.copy_range(1..3, &mut dst, 2)?;
+    let values = (0..4)
+        .map(|pos| dst.get_value(pos).map(|data| data.get_string()))
+        .collect::<Result<Vec<String>>>()?;
+    assert_eq!(values, vec!["", "", "b", "c"]);
+    match src.copy_range(0..3, &mut dst, 2) {
+        Err(Error(ErrorKind::VarRange(_), _)) => {}
+        res => panic!("copied past the end of the variable: {:?}", res.map(|_| ())),
     }
+    assert!(src.copy_range(2..4, &mut dst, 0).is_err());
+    src.release()?;
+    dst.release()?;
 
     conn.release()?;
     conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
@@ -52,4 +59,37 @@ fn var_res(ctxt: &Context) -> Result<()> {
 #[test]
 fn variable() {
     check_with_ctxt!(var_res)
-}
\ No newline at end of file
+}
+fn returning_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+
+    // The variable is allocated one element, but the statement returns two rows.
+    let stmt = conn.prepare_stmt(Some("update username set username = username \
+                                       where id in (1, 2) returning id into :id"),
+                                 None,
+                                 false)?;
+    let var = conn.new_var(Number, Int64, 1, 0, false, false, None)?;
+    assert_eq!(var.refresh_data()?.len(), 1);
+    stmt.bind_by_name("id", &var)?;
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+
+    let num_rows = var.get_num_elements_in_array()?;
+    assert_eq!(num_rows, 2);
+    let mut ids = (0..num_rows)
+        .map(|pos| var.get_value(pos).map(|data| data.get_int64()))
+        .collect::<Result<Vec<i64>>>()?;
+    ids.sort();
+    assert_eq!(ids, vec![1, 2]);
+
+    conn.rollback()?;
+    stmt.release()?;
+    var.release()?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn returning() {
+    check_with_ctxt!(returning_res)
+}
@@ -0,0 +1,58 @@
+use CONFIG;
+use mimir::{Context, xml};
+use mimir::error::Result;
+use mimir::flags;
+
+#[test]
+fn exprs() {
+    assert_eq!(xml::select_expr("doc").expect("select expr"),
+               "XMLSERIALIZE(CONTENT DOC AS CLOB) AS DOC");
+    assert_eq!(xml::select_expr("d.doc").expect("select expr"),
+               "XMLSERIALIZE(CONTENT D.DOC AS CLOB) AS DOC");
+    assert_eq!(xml::bind_expr("doc").expect("bind expr"), "XMLTYPE(:DOC)");
+
+    assert!(xml::select_expr("doc) from dual --").is_err());
+    assert!(xml::select_expr("d.").is_err());
+    assert!(xml::bind_expr(":doc").is_err());
+}
+
+fn xmltype_res(ctxt: &Context) -> Result<()> {
+    let conn = CONFIG.connect(ctxt)?;
+    conn.run_script("create table mimir_xml (id number(10) not null, doc xmltype)")?;
+
+    let doc = "<order><line qty=\"2\">widget</line></order>";
+    let insert = format!("insert into mimir_xml (id, doc) values (1, {})",
+                         xml::bind_expr("doc")?);
+    let stmt = conn.prepare_stmt(Some(&insert), None, false)?;
+    stmt.binder().set("doc", doc)?.execute()?;
+    stmt.release()?;
+
+    let raw = conn.prepare_stmt(Some("select id, doc from mimir_xml"), None, false)?;
+    raw.execute(flags::DPI_MODE_EXEC_DESCRIBE_ONLY)?;
+    let columns = raw.columns()?;
+    assert!(!columns[0].is_xml());
+    assert!(columns[1].is_xml());
+    raw.release()?;
+
+    let query = format!("select id, {} from mimir_xml d", xml::select_expr("d.doc")?);
+    let stmt = conn.prepare_stmt(Some(&query), None, false)?;
+    stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+    assert!(!stmt.columns()?[1].is_xml());
+    let doc_var = stmt.define_inline(&conn, 2, 1)?;
+    let (found, idx) = stmt.fetch()?;
+    assert!(found);
+    assert!(doc_var.get_value(idx)?.get_string().contains("<line qty=\"2\">widget</line>"));
+    stmt.close(None)?;
+
+    conn.rollback()?;
+    conn.run_script("drop table mimir_xml")?;
+    conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+    conn.release()?;
+
+    Ok(())
+}
+
+#[test]
+fn xmltype() {
+    check_with_ctxt!(xmltype_res)
+}
@@ -0,0 +1,230 @@
+//! Benchmark an Oracle RDS instance through a `mimir` session pool
+use clap::{App, Arg, ArgMatches, SubCommand};
+use error::{ErrorKind, Result};
+use mimir::{Connection, Context, Pool, flags};
+use rusoto_core::Region;
+use sql;
+use std::thread;
+use std::time::{Duration, Instant};
+use term;
+
+/// The statement run when none is given.
+const DEFAULT_STATEMENT: &str = "select 1 from dual";
+
+/// The latency percentiles reported.
+const PERCENTILES: &[f64] = &[50.0, 90.0, 99.0];
+
+/// The outcome of the iterations run by a worker.
+#[derive(Default)]
+struct Samples {
+    /// The execute latency of each successful iteration.
+    latencies: Vec<Duration>,
+    /// The number of failed iterations.
+    errors: usize,
+    /// The error of the first failed iteration.
+    first_error: Option<String>,
+}
+
+/// Bench subcommand.
+pub fn subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("bench")
+        .about("Benchmark an Oracle RDS instance with a pool of concurrent workers")
+        .args(&sql::connection_args())
+        .arg(Arg::with_name("workers")
+                 .help("The number of worker threads. (default: 4)")
+                 .short("w")
+                 .long("workers")
+                 .takes_value(true)
+                 .value_name("WORKERS"))
+        .arg(Arg::with_name("iterations")
+                 .help("The number of statements each worker runs. (default: 100)")
+                 .short("n")
+                 .long("iterations")
+                 .takes_value(true)
+                 .value_name("ITERATIONS"))
+        .arg(Arg::with_name("max_sessions")
+                 .help("The maximum number of sessions in the pool. (default: the workers)")
+                 .long("max-sessions")
+                 .takes_value(true)
+                 .value_name("MAX_SESSIONS"))
+        .arg(Arg::with_name("statement")
+                 .help("A statement of the mix, run in turn by each worker. Repeat a statement to \
+                        weight it. DML is rolled back. (default: select 1 from dual)")
+                 .short("s")
+                 .long("statement")
+                 .takes_value(true)
+                 .multiple(true)
+                 .number_of_values(1)
+                 .value_name("SQL"))
+}
+
+/// Bench dispatching
+pub fn dispatch(region: Region, matches: &ArgMatches) -> Result<()> {
+    let params = sql::connect_params(region, matches)?;
+    let workers = parse_count(matches, "workers", 4)?;
+    let iterations = parse_count(matches, "iterations", 100)?;
+    let max_sessions = parse_count(matches, "max_sessions", workers)?;
+    let statements: Vec<String> = match matches.values_of("statement") {
+        Some(statements) => statements.map(|sql| sql.to_string()).collect(),
+        None => vec![DEFAULT_STATEMENT.to_string()],
+    };
+
+    let ctxt = Context::create()?;
+    let mut common_create = params.init_common_create_params(&ctxt)?;
+    let create_mode = common_create.get_create_mode() | flags::DPI_MODE_CREATE_THREADED;
+    common_create.set_create_mode(create_mode);
+    let mut pool_create = ctxt.init_pool_create_params()?;
+    pool_create.set_min_sessions(1);
+    pool_create.set_max_sessions(max_sessions as u32);
+    pool_create.set_session_increment(1);
+    let pool = Pool::create(&ctxt, &params, Some(common_create), Some(pool_create))?;
+
+    let start = Instant::now();
+    let mut handles = Vec::with_capacity(workers);
+    for worker in 0..workers {
+        let pool_handle = pool.thread_handle()?;
+        let statements = statements.clone();
+        handles.push(thread::spawn(move || {
+            let pool = pool_handle.into_pool();
+            let samples = run_worker(&pool, &statements, worker, iterations);
+            let _ = pool.release();
+            samples
+        }));
+    }
+
+    let mut samples = Samples::default();
+    for handle in handles {
+        let worker_samples = handle.join().map_err(|_| ErrorKind::WorkerPanicked)?;
+        samples.latencies.extend(worker_samples.latencies);
+        samples.errors += worker_samples.errors;
+        if samples.first_error.is_none() {
+            samples.first_error = worker_samples.first_error;
+        }
+    }
+    let elapsed = start.elapsed();
+
+    pool.close(flags::DPI_MODE_POOL_CLOSE_DEFAULT)?;
+    pool.release()?;
+    report(&mut samples, elapsed, workers)
+}
+
+/// Parse a positive count argument, or return the default if it was not given.
+fn parse_count(matches: &ArgMatches, name: &str, default: usize) -> Result<usize> {
+    match matches.value_of(name) {
+        Some(value) => {
+            let count = value.parse::<usize>()?;
+            if count == 0 {
+                Err(ErrorKind::InvalidCommand.into())
+            } else {
+                Ok(count)
+            }
+        }
+        None => Ok(default),
+    }
+}
+
+/// Run the iterations of a worker, each on a session acquired from the pool. Workers start at
+/// different statements of the mix, so every statement is run concurrently. Only the execute is
+/// timed, so acquiring the session, fetching and rolling back do not count toward the latency.
+fn run_worker(pool: &Pool, statements: &[String], worker: usize, iterations: usize) -> Samples {
+    let mut samples = Samples::default();
+
+    for iteration in 0..iterations {
+        let sql = &statements[(worker + iteration) % statements.len()];
+        let res = pool.acquire_connection(None, None, None)
+            .map_err(Into::into)
+            .and_then(|conn| {
+                          let res = run_statement(&conn, sql);
+                          conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None)?;
+                          conn.release()?;
+                          res
+                      });
+
+        match res {
+            Ok(latency) => samples.latencies.push(latency),
+            Err(e) => {
+                samples.errors += 1;
+                if samples.first_error.is_none() {
+                    samples.first_error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    samples
+}
+
+/// Run a statement, fetching every row of a query, then roll back any changes. Returns the time
+/// taken by the execute.
+fn run_statement(conn: &Connection, sql: &str) -> Result<Duration> {
+    let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+    let start = Instant::now();
+    let res = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)
+        .and_then(|cols| {
+                      let latency = start.elapsed();
+                      if cols > 0 {
+                          while let (true, _) = stmt.fetch()? {}
+                      }
+                      Ok(latency)
+                  });
+    stmt.release()?;
+    let latency = res?;
+    conn.rollback()?;
+    Ok(latency)
+}
+
+/// Print the throughput and latency percentiles of the run.
+fn report(samples: &mut Samples, elapsed: Duration, workers: usize) -> Result<()> {
+    let mut stdout = term::stdout().ok_or_else(|| ErrorKind::CreateTerm)?;
+    samples.latencies.sort();
+    let secs = as_secs_f64(elapsed);
+    let completed = samples.latencies.len();
+
+    stdout.fg(term::color::GREEN)?;
+    stdout.attr(term::Attr::Bold)?;
+    writeln!(stdout,
+             "{} statement(s) in {:.3}s with {} worker(s): {:.1} statements/s",
+             completed,
+             secs,
+             workers,
+             completed as f64 / secs)?;
+    stdout.reset()?;
+
+    if let (Some(min), Some(max)) = (samples.latencies.first(), samples.latencies.last()) {
+        writeln!(stdout, "{:>8}: {:.3}ms", "min", as_millis_f64(*min))?;
+        for percentile in PERCENTILES {
+            let latency = nearest_rank(&samples.latencies, *percentile);
+            writeln!(stdout, "{:>8}: {:.3}ms", format!("p{}", percentile), latency)?;
+        }
+        writeln!(stdout, "{:>8}: {:.3}ms", "max", as_millis_f64(*max))?;
+    }
+
+    if samples.errors > 0 {
+        stdout.fg(term::color::RED)?;
+        writeln!(stdout, "{} statement(s) failed", samples.errors)?;
+        if let Some(ref error) = samples.first_error {
+            writeln!(stdout, "first error: {}", error)?;
+        }
+        stdout.reset()?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Returns the latency, in milliseconds, at the given percentile of the sorted latencies, using
+/// the nearest rank method.
+fn nearest_rank(latencies: &[Duration], percentile: f64) -> f64 {
+    let rank = (percentile / 100.0 * latencies.len() as f64).ceil() as usize;
+    let idx = if rank == 0 { 0 } else { rank - 1 };
+    as_millis_f64(latencies[idx])
+}
+
+/// Convert the duration to fractional seconds.
+fn as_secs_f64(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1_000_000_000.0
+}
+
+/// Convert the duration to fractional milliseconds.
+fn as_millis_f64(duration: Duration) -> f64 {
+    as_secs_f64(duration) * 1_000.0
+}
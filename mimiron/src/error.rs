@@ -17,6 +17,7 @@ error_chain!{
         DescribeEventSubscriptions(::rusoto_rds::DescribeEventSubscriptionsError);
         DescribeEvents(::rusoto_rds::DescribeEventsError);
         Io(::std::io::Error);
+        Mimir(::mimir::error::Error) #[cfg(feature = "sql")];
         ParseInt(::std::num::ParseIntError);
         StartDBInstance(::rusoto_rds::StartDBInstanceError);
         StopDBInstance(::rusoto_rds::StopDBInstanceError);
@@ -33,5 +34,25 @@ error_chain!{
             description("Invaid command!")
             display("Invalid command!")
         }
+        #[cfg(feature = "sql")]
+        MissingArg(name: String) {
+            description("A required argument is missing!")
+            display("The {} argument is required!", name)
+        }
+        #[cfg(feature = "sql")]
+        NoEndpoint(instance_id: String) {
+            description("The instance has no endpoint!")
+            display("The instance '{}' was not found or has no endpoint yet!", instance_id)
+        }
+        #[cfg(feature = "sql")]
+        ScriptFailed(count: usize) {
+            description("The script failed!")
+            display("{} statement(s) of the script failed!", count)
+        }
+        #[cfg(feature = "sql")]
+        WorkerPanicked {
+            description("A worker thread panicked!")
+            display("A worker thread panicked!")
+        }
     }
 }
@@ -17,15 +17,21 @@ extern crate lazy_static;
 mod macros;
 
 extern crate clap;
+#[cfg(feature = "sql")]
+extern crate mimir;
 extern crate rusoto_core;
 extern crate rusoto_rds;
 extern crate term;
 
+#[cfg(feature = "sql")]
+mod bench;
 mod db;
 #[allow(unused_doc_comment)]
 mod error;
 mod event;
 mod run;
+#[cfg(feature = "sql")]
+mod sql;
 
 use std::io::{self, Write};
 use std::process;
@@ -7,13 +7,17 @@
 // modified, or distributed except according to those terms.
 
 //! `mimiron` runtime
-use clap::{App, AppSettings, Arg, SubCommand};
+#[cfg(feature = "sql")]
+use bench;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use db;
 use event;
 use error::{ErrorKind, Result};
 use rusoto_core::{default_tls_client, ProfileProvider, Region};
 use rusoto_rds::{DBInstanceMessage, DescribeDBInstancesMessage, Rds, RdsClient,
                  StartDBInstanceMessage, StopDBInstanceMessage};
+#[cfg(feature = "sql")]
+use sql;
 use std::collections::HashMap;
 use std::iter;
 use term;
@@ -21,10 +25,10 @@ use term;
 /// CLI Runtime
 pub fn run() -> Result<i32> {
     let range: Vec<String> = (20..101).map(|x| x.to_string()).collect();
-    let matches = App::new(env!("CARGO_PKG_NAME"))
+    let app = App::new(env!("CARGO_PKG_NAME"))
         .version(env!("CARGO_PKG_VERSION"))
         .author(env!("CARGO_PKG_AUTHORS"))
-        .about("Manage Oracle RDS instances.")
+        .about("Manage Oracle RDS instances, and run SQL against them.")
         .setting(AppSettings::SubcommandRequiredElseHelp)
         .subcommand(SubCommand::with_name("create")
                         .about("Create an Oracle RDS instance.")
@@ -45,8 +49,10 @@ pub fn run() -> Result<i32> {
                                  .help("The unique instance identifier")
                                  .required(true))
                         .arg(Arg::with_name("snapshot_id")
-                                 .help("An optional pre-shutdown snapshot identifier")))
-        .get_matches();
+                                 .help("An optional pre-shutdown snapshot identifier")));
+    #[cfg(feature = "sql")]
+    let app = app.subcommand(bench::subcommand()).subcommand(sql::subcommand());
+    let matches = app.get_matches();
 
     let mut stderr = term::stderr().ok_or_else(|| ErrorKind::CreateTerm)?;
     let provider = ProfileProvider::new()?;
@@ -81,7 +87,7 @@ pub fn run() -> Result<i32> {
             .map(|s| s.to_string());
         let instance_message = client.describe_db_instances(&describe_message)?;
         status(&instance_message)?;
-    } else {
+    } else if !sql_dispatch(&matches)? {
         stderr.fg(term::color::RED)?;
         stderr.attr(term::Attr::Bold)?;
         writeln!(stderr, "Unknown command!")?;
@@ -90,6 +96,25 @@ pub fn run() -> Result<i32> {
     Ok(0)
 }
 
+/// Dispatch the subcommands run through `mimir`, returning whether one matched.
+#[cfg(feature = "sql")]
+fn sql_dispatch(matches: &ArgMatches) -> Result<bool> {
+    if let Some(bench_matches) = matches.subcommand_matches("bench") {
+        bench::dispatch(Region::UsEast2, bench_matches)?;
+    } else if let Some(sql_matches) = matches.subcommand_matches("sql") {
+        sql::dispatch(Region::UsEast2, sql_matches)?;
+    } else {
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Without the `sql` feature, there are no `mimir` subcommands to dispatch.
+#[cfg(not(feature = "sql"))]
+fn sql_dispatch(_matches: &ArgMatches) -> Result<bool> {
+    Ok(false)
+}
+
 /// Output the status messages.
 fn status(message: &DBInstanceMessage) -> Result<()> {
     let mut stdout = term::stdout().ok_or_else(|| ErrorKind::CreateTerm)?;
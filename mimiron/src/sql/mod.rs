@@ -0,0 +1,266 @@
+//! Run SQL against an Oracle RDS instance through `mimir`
+use clap::{App, Arg, ArgMatches, SubCommand};
+use error::{ErrorKind, Result};
+use mimir::enums::ODPINativeTypeNum;
+use mimir::{ConnectParams, ConnectString, Connection, Context, Data, Statement, flags};
+use rusoto_core::{self, ProfileProvider, Region};
+use rusoto_rds::{DescribeDBInstancesMessage, Rds, RdsClient};
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::iter;
+use term;
+
+/// The environment variable holding the password, when it is not given on the command line.
+const PASSWORD_VAR: &str = "MIMIRON_PASSWORD";
+
+/// SQL subcommand.
+pub fn subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("sql")
+        .about("Run SQL against an Oracle RDS instance")
+        .args(&connection_args())
+        .arg(Arg::with_name("file")
+                 .help("A script of statements to run, separated by ';' or '/' lines.")
+                 .short("f")
+                 .long("file")
+                 .takes_value(true)
+                 .value_name("FILE")
+                 .required_unless("sql")
+                 .conflicts_with("sql"))
+        .arg(Arg::with_name("commit")
+                 .help("Commit the changes made. They are rolled back otherwise.")
+                 .long("commit"))
+        .arg(Arg::with_name("sql").help("The statement to run."))
+}
+
+/// SQL dispatching
+pub fn dispatch(region: Region, matches: &ArgMatches) -> Result<()> {
+    let params = connect_params(region, matches)?;
+    let ctxt = Context::create()?;
+    let conn = params.connect(&ctxt)?;
+
+    let res = if let Some(path) = matches.value_of("file") {
+        let mut script = String::new();
+        File::open(path)?.read_to_string(&mut script)?;
+        script_results(&conn, &script)
+    } else if let Some(sql) = matches.value_of("sql") {
+        statement(&conn, sql)
+    } else {
+        Err(ErrorKind::InvalidCommand.into())
+    };
+    let res = res.and_then(|_| if matches.is_present("commit") {
+                                   conn.commit().map_err(Into::into)
+                               } else {
+                                   conn.rollback().map_err(Into::into)
+                               });
+
+    let closed = conn.close(flags::DPI_MODE_CONN_CLOSE_DEFAULT, None);
+    let released = conn.release();
+    res.and_then(|_| closed.map_err(Into::into))
+        .and_then(|_| released.map_err(Into::into))
+}
+
+/// The arguments identifying the database and the user to connect as.
+pub fn connection_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![Arg::with_name("instance_id")
+             .help("The instance to connect to, found with the RDS API.")
+             .short("i")
+             .long("instance")
+             .takes_value(true)
+             .value_name("INSTANCE_ID")
+             .required_unless("connect_string")
+             .conflicts_with("connect_string"),
+         Arg::with_name("connect_string")
+             .help("The connect string to connect with, instead of an instance.")
+             .short("c")
+             .long("connect")
+             .takes_value(true)
+             .value_name("CONNECT_STRING"),
+         Arg::with_name("username")
+             .help("The user to connect as. (default: the instance's master username)")
+             .short("u")
+             .long("username")
+             .takes_value(true)
+             .value_name("USERNAME"),
+         Arg::with_name("password")
+             .help("The password of the user. (default: $MIMIRON_PASSWORD)")
+             .short("p")
+             .long("password")
+             .takes_value(true)
+             .value_name("PASSWORD")]
+}
+
+/// Returns the parameters to connect with, from the arguments added by `connection_args()`.
+pub fn connect_params(region: Region, matches: &ArgMatches) -> Result<ConnectParams> {
+    let (connect_string, master_username) = match matches.value_of("connect_string") {
+        Some(connect_string) => (connect_string.to_string(), None),
+        None => {
+            let instance_id = matches.value_of("instance_id").ok_or(ErrorKind::InvalidCommand)?;
+            endpoint(region, instance_id)?
+        }
+    };
+    let username = match matches.value_of("username") {
+        Some(username) => username.to_string(),
+        None => master_username.ok_or(ErrorKind::MissingArg("username".to_string()))?,
+    };
+    let password = match matches.value_of("password") {
+        Some(password) => password.to_string(),
+        None => env::var(PASSWORD_VAR)
+            .map_err(|_| ErrorKind::MissingArg("password".to_string()))?,
+    };
+
+    Ok(ConnectParams::new(&username, &password, &connect_string))
+}
+
+/// Returns the connect string and master username of an instance, from the RDS API.
+fn endpoint(region: Region, instance_id: &str) -> Result<(String, Option<String>)> {
+    let provider = ProfileProvider::new()?;
+    let tls_client = rusoto_core::default_tls_client()?;
+    let client = RdsClient::new(tls_client, provider, region);
+    let mut message: DescribeDBInstancesMessage = Default::default();
+    message.db_instance_identifier = Some(instance_id.to_string());
+
+    let out_message = client.describe_db_instances(&message)?;
+    let instance = out_message
+        .db_instances
+        .and_then(|instances| instances.into_iter().next())
+        .ok_or_else(|| ErrorKind::NoEndpoint(instance_id.to_string()))?;
+    let endpoint = instance
+        .endpoint
+        .ok_or_else(|| ErrorKind::NoEndpoint(instance_id.to_string()))?;
+    let address = endpoint
+        .address
+        .ok_or_else(|| ErrorKind::NoEndpoint(instance_id.to_string()))?;
+    let port = endpoint.port.unwrap_or(1521) as u16;
+    let service = instance.db_name.unwrap_or_else(|| "ORCL".to_string());
+
+    Ok((ConnectString::new(&address, port, &service).build()?, instance.master_username))
+}
+
+/// Run a single statement, printing the rows of a query as a table.
+fn statement(conn: &Connection, sql: &str) -> Result<()> {
+    let stmt = conn.prepare_stmt(Some(sql), None, false)?;
+    let res = print_results(&stmt);
+    let released = stmt.release();
+    res.and_then(|_| released.map_err(Into::into))
+}
+
+/// Execute the statement and print its rows, or the number of rows affected.
+fn print_results(stmt: &Statement) -> Result<()> {
+    let mut stdout = term::stdout().ok_or_else(|| ErrorKind::CreateTerm)?;
+    let cols = stmt.execute(flags::DPI_MODE_EXEC_DEFAULT)?;
+
+    if cols == 0 {
+        writeln!(stdout, "{} row(s) affected.", stmt.get_row_count()?)?;
+        return Ok(());
+    }
+
+    let headers: Vec<String> = stmt.columns()?
+        .iter()
+        .map(|column| column.name().to_string())
+        .collect();
+    let mut rows = Vec::new();
+    while let (true, _) = stmt.fetch()? {
+        let mut row = Vec::with_capacity(headers.len());
+        for pos in 1..cols + 1 {
+            let (native_type, data_ptr) = stmt.get_query_value(pos)?;
+            row.push(format_value(native_type, &Data::from(data_ptr)));
+        }
+        rows.push(row);
+    }
+
+    stdout.reset()?;
+    write!(stdout, "{}", format_table(&headers, &rows))?;
+    writeln!(stdout, "{} row(s) selected.", rows.len())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Run a script, printing the outcome of each statement.
+fn script_results(conn: &Connection, script: &str) -> Result<()> {
+    let mut stdout = term::stdout().ok_or_else(|| ErrorKind::CreateTerm)?;
+    let mut failed = 0;
+
+    for result in conn.run_script(script)? {
+        match result.result {
+            Ok(rows) => {
+                stdout.fg(term::color::GREEN)?;
+                writeln!(stdout, "line {}: ok, {} row(s)", result.statement.line, rows)?;
+            }
+            Err(e) => {
+                failed += 1;
+                stdout.fg(term::color::RED)?;
+                writeln!(stdout, "line {}: {}", result.statement.line, e)?;
+            }
+        }
+        stdout.reset()?;
+    }
+    stdout.flush()?;
+
+    if failed > 0 {
+        Err(ErrorKind::ScriptFailed(failed).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Formats a fetched value for display.
+fn format_value(native_type: ODPINativeTypeNum, data: &Data) -> String {
+    if data.is_null() {
+        return String::new();
+    }
+
+    match native_type {
+        ODPINativeTypeNum::Int64 => data.get_int64().to_string(),
+        ODPINativeTypeNum::Uint64 => data.get_uint64().to_string(),
+        ODPINativeTypeNum::Float => data.get_float().to_string(),
+        ODPINativeTypeNum::Double => data.get_double().to_string(),
+        ODPINativeTypeNum::Bytes => data.get_string(),
+        ODPINativeTypeNum::Timestamp => data.get_datetime().to_rfc3339(),
+        ODPINativeTypeNum::IntervalDS => data.get_duration().to_string(),
+        ODPINativeTypeNum::IntervalYM => {
+            let interval = data.get_years_months();
+            format!("{}-{}", interval.years(), interval.months().abs())
+        }
+        ODPINativeTypeNum::Boolean => data.get_boolean().to_string(),
+        other => format!("<{:?}>", other),
+    }
+}
+
+/// Formats the rows as a table, with a header line and columns padded to their widest value.
+fn format_table(headers: &[String], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(idx, header)| {
+                 rows.iter()
+                     .map(|row| row[idx].chars().count())
+                     .chain(iter::once(header.chars().count()))
+                     .max()
+                     .unwrap_or(0)
+             })
+        .collect();
+    let line = |values: &[String]| -> String {
+        let cells: Vec<String> = values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| {
+                     let padding: String =
+                         iter::repeat(' ').take(width - value.chars().count()).collect();
+                     format!("{}{}", value, padding)
+                 })
+            .collect();
+        format!("{}\n", cells.join(" | ").trim_right())
+    };
+    let separator: Vec<String> = widths
+        .iter()
+        .map(|width| iter::repeat('-').take(*width).collect())
+        .collect();
+
+    let mut table = line(headers);
+    table.push_str(&format!("{}\n", separator.join("-+-")));
+    for row in rows {
+        table.push_str(&line(row));
+    }
+    table
+}